@@ -0,0 +1,59 @@
+use anyhow::Result;
+/// # RSI Backtest Example
+///
+/// Does "buy when RSI drops below 30" make money? This example replays the
+/// rule over two years of daily history and prints the trades.
+///
+/// Run this example with: `cargo run --example backtest_rsi [SYMBOL]`
+use auto_analyser::backtest::{Backtester, EntryRule, ExitRule};
+use auto_analyser::providers::Interval;
+use auto_analyser::StockAnalyzer;
+use chrono::{Duration, Utc};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let symbol = std::env::args().nth(1).unwrap_or_else(|| "AAPL".to_string());
+    println!("🧪 Backtesting RSI < 30 entries on {}", symbol);
+    println!("{}", "=".repeat(60));
+
+    let analyzer = StockAnalyzer::new();
+    let end = Utc::now();
+    let start = end - Duration::days(730);
+    let data = analyzer.fetch_stock_data(&symbol, start, end, Interval::Daily).await?;
+    if data.is_empty() {
+        println!("❌ No data available for {}", symbol);
+        return Ok(());
+    }
+
+    let exit = ExitRule {
+        take_profit_pct: Some(10.0),
+        stop_loss_pct: Some(5.0),
+        max_holding_days: Some(30),
+    };
+    let backtester = Backtester::new(EntryRule::rsi_below(30.0), exit);
+    backtester.validate().map_err(anyhow::Error::msg)?;
+    let report = backtester.run(&data);
+
+    for trade in &report.trades {
+        println!(
+            "{} @ {:>8.2} → {} @ {:>8.2}  {:>+7.2}%  ({:?})",
+            trade.entry_time.format("%Y-%m-%d"),
+            trade.entry_price,
+            trade.exit_time.format("%Y-%m-%d"),
+            trade.exit_price,
+            trade.return_pct,
+            trade.exit_reason
+        );
+    }
+
+    println!("{}", "-".repeat(60));
+    println!("Trades:        {}", report.trade_count);
+    if let (Some(win_rate), Some(average)) = (report.win_rate_pct, report.average_return_pct) {
+        println!("Win rate:      {:.1}%", win_rate);
+        println!("Average trade: {:+.2}%", average);
+    }
+    println!("Total return:  {:+.2}%", report.total_return_pct);
+    println!("Max drawdown:  {:.2}%", report.max_drawdown_pct);
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use auto_analyser::providers::Interval;
 use auto_analyser::StockAnalyzer;
 use chrono::{Duration, Utc};
 
@@ -13,7 +14,7 @@ async fn main() -> Result<()> {
 
     println!("Fetching 30 days of data for {}...", symbol);
 
-    match analyzer.fetch_stock_data(symbol, start, end).await {
+    match analyzer.fetch_stock_data(symbol, start, end, Interval::Daily).await {
         Ok(stock_data) => {
             if stock_data.is_empty() {
                 println!("No data available for {}", symbol);
@@ -28,7 +29,7 @@ async fn main() -> Result<()> {
             // Show the last 5 data points with indicators
             println!("\n=== Last 5 Trading Days ===");
             let len = stock_data.len();
-            let start_idx = if len >= 5 { len - 5 } else { 0 };
+            let start_idx = len.saturating_sub(5);
 
             for i in start_idx..len {
                 let data = &stock_data[i];
@@ -8,7 +8,8 @@ use anyhow::Result;
 /// 4. Generate comprehensive reports
 ///
 /// Run this example with: `cargo run --example complete_analysis`
-use auto_analyser::StockAnalyzer;
+use auto_analyser::providers::Interval;
+use auto_analyser::{StockAnalyzer, StockFilter};
 use chrono::{Duration, Utc};
 
 #[tokio::main]
@@ -39,9 +40,7 @@ async fn main() -> Result<()> {
     // Get large cap stocks (for stability)
     let large_cap_stocks = StockAnalyzer::filter_tickers(
         &all_tickers,
-        None,
-        Some(10_000_000_000.0), // $10B+ market cap
-        None,
+        &StockFilter::new().with_market_cap_range(Some(10_000_000_000.0), None), // $10B+ market cap
     );
     println!(
         "🏢 Found {} large-cap stocks (>$10B)",
@@ -102,7 +101,7 @@ async fn main() -> Result<()> {
             analysis_candidates.len(),
             "=".repeat(20)
         );
-        match analyzer.fetch_stock_data(symbol, start, end).await {
+        match analyzer.fetch_stock_data(symbol, start, end, Interval::Daily).await {
             Ok(stock_data) => {
                 if stock_data.is_empty() {
                     println!("❌ No data available for {}", symbol);
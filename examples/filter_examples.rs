@@ -68,7 +68,7 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn test_filter_quickly(filter: &StockFilter, name: &str) -> Result<()> {
+async fn test_filter_quickly(filter: &StockFilter, _name: &str) -> Result<()> {
     match StockAnalyzer::fetch_n_tickers(100).await {
         Ok(tickers) => {
             let filtered = StockAnalyzer::filter_tickers(&tickers, filter);
@@ -1,3 +1,4 @@
+use auto_analyser::providers::Interval;
 use auto_analyser::StockAnalyzer;
 use chrono::Utc;
 
@@ -12,7 +13,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let end_date = Utc::now();
     let start_date = end_date - chrono::Duration::days(30);
     
-    match analyzer.fetch_stock_data("AAPL", start_date, end_date).await {
+    match analyzer.fetch_stock_data("AAPL", start_date, end_date, Interval::Daily).await {
         Ok(stock_data) => {
             println!("Fetched {} data points", stock_data.len());
             
@@ -7,6 +7,7 @@ use anyhow::Result;
 /// 3. Generate trading signals
 ///
 /// Run this example with: `cargo run --example simple_analysis`
+use auto_analyser::providers::Interval;
 use auto_analyser::StockAnalyzer;
 use chrono::{Duration, Utc};
 
@@ -24,7 +25,7 @@ async fn main() -> Result<()> {
 
     println!("📊 Fetching {} days of data for {}...", 30, symbol);
 
-    match analyzer.fetch_stock_data(symbol, start, end).await {
+    match analyzer.fetch_stock_data(symbol, start, end, Interval::Daily).await {
         Ok(stock_data) => {
             if stock_data.is_empty() {
                 println!("❌ No data available for {}", symbol);
@@ -44,7 +45,7 @@ async fn main() -> Result<()> {
             println!("{}", "-".repeat(40));
 
             let len = stock_data.len();
-            let start_idx = if len >= 5 { len - 5 } else { 0 };
+            let start_idx = len.saturating_sub(5);
 
             for i in start_idx..len {
                 let data = &stock_data[i];
@@ -8,7 +8,7 @@ use anyhow::Result;
 /// 4. Display formatted ticker information
 ///
 /// Run this example with: `cargo run --example ticker_collection`
-use auto_analyser::{StockAnalyzer, TickerInfo};
+use auto_analyser::{StockAnalyzer, StockFilter, TickerInfo};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -35,7 +35,10 @@ async fn main() -> Result<()> {
 
             // 2. Filter by Technology sector
             let tech_stocks =
-                StockAnalyzer::filter_tickers(&all_tickers, Some("Technology"), None, None);
+                StockAnalyzer::filter_tickers(
+                &all_tickers,
+                &StockFilter::new().with_sectors(vec!["Technology".to_string()]),
+            );
             println!("\n💻 Found {} Technology stocks", tech_stocks.len());
 
             // Show top 10 tech performers
@@ -45,9 +48,7 @@ async fn main() -> Result<()> {
             // 3. Filter by large market cap (> $1B)
             let large_cap_stocks = StockAnalyzer::filter_tickers(
                 &all_tickers,
-                None,
-                Some(1_000_000_000.0), // $1B minimum
-                None,
+                &StockFilter::new().with_market_cap_range(Some(1_000_000_000.0), None), // $1B minimum
             );
             println!(
                 "\n🏢 Found {} large-cap stocks (>$1B market cap)",
@@ -61,9 +62,9 @@ async fn main() -> Result<()> {
             // 4. Filter by Healthcare sector with large market cap
             let healthcare_large_cap = StockAnalyzer::filter_tickers(
                 &all_tickers,
-                Some("Health Care"),
-                Some(1_000_000_000.0),
-                None,
+                &StockFilter::new()
+                    .with_sectors(vec!["Health Care".to_string()])
+                    .with_market_cap_range(Some(1_000_000_000.0), None),
             );
             println!(
                 "\n🏥 Found {} large-cap Healthcare stocks",
@@ -91,7 +92,7 @@ async fn main() -> Result<()> {
             }
 
             let mut sector_vec: Vec<_> = sector_counts.into_iter().collect();
-            sector_vec.sort_by(|a, b| b.1.cmp(&a.1));
+            sector_vec.sort_by_key(|b| std::cmp::Reverse(b.1));
 
             for (sector, count) in sector_vec.iter().take(10) {
                 println!("{:<30} {:>6} stocks", sector, count);
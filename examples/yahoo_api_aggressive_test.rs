@@ -11,6 +11,7 @@ use anyhow::Result;
 /// - With chunk size and delay: `cargo run --example yahoo_api_aggressive_test -- --chunk-size 10 --chunk-delay 1000`
 ///
 /// Note: Requests within each chunk are sent CONCURRENTLY for maximum impact!
+use auto_analyser::providers::Interval;
 use auto_analyser::StockAnalyzer;
 use chrono::{DateTime, Utc};
 use std::sync::Arc;
@@ -80,7 +81,7 @@ async fn main() -> Result<()> {
             let future = tokio::spawn(async move {
                 let request_start = Instant::now();
                 let result = analyzer_clone
-                    .fetch_stock_data(&symbol_clone, DateTime::<Utc>::UNIX_EPOCH, Utc::now())
+                    .fetch_stock_data(&symbol_clone, DateTime::<Utc>::UNIX_EPOCH, Utc::now(), Interval::Daily)
                     .await;
                 (req_num_clone, symbol_clone, request_start, result)
             });
@@ -142,7 +143,7 @@ async fn main() -> Result<()> {
                                     break;
                                 }
                                 Err(e) => {
-                                    println!("   ❌ Still blocked: {}", e.to_string());
+                                    println!("   ❌ Still blocked: {}", e);
                                 }
                             }
                         }
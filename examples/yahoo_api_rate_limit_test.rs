@@ -8,11 +8,13 @@ use anyhow::Result;
 /// 4. Optimal request timing to avoid blocks
 ///
 /// Run this example with: `cargo run --example yahoo_api_rate_limit_test`
-use auto_analyser::StockAnalyzer;
+use auto_analyser::providers::Interval;
+use auto_analyser::{AnalyzerError, StockAnalyzer};
 use chrono::{Duration, Utc};
 use std::time::{Duration as StdDuration, Instant};
 use tokio::time::sleep;
 
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 struct RequestResult {
     request_number: u32,
@@ -23,6 +25,7 @@ struct RequestResult {
     timestamp: chrono::DateTime<Utc>,
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 struct TestConfig {
     test_symbols: Vec<String>,
@@ -191,7 +194,7 @@ impl RateLimitTester {
 
         match self
             .analyzer
-            .fetch_stock_data(symbol, start_date, end)
+            .fetch_stock_data(symbol, start_date, end, Interval::Daily)
             .await
         {
             Ok(_) => RequestResult {
@@ -239,26 +242,27 @@ impl RateLimitTester {
         }
     }
 
-    fn categorize_error(&self, error: &anyhow::Error) -> String {
-        let error_str = error.to_string().to_lowercase();
-
-        if error_str.contains("429") || error_str.contains("too many requests") {
-            "RATE_LIMIT_429".to_string()
-        } else if error_str.contains("403") || error_str.contains("forbidden") {
-            "FORBIDDEN_403".to_string()
-        } else if error_str.contains("401") || error_str.contains("unauthorized") {
-            "UNAUTHORIZED_401".to_string()
-        } else if error_str.contains("timeout") {
-            "TIMEOUT".to_string()
-        } else if error_str.contains("connection") {
-            "CONNECTION_ERROR".to_string()
-        } else if error_str.contains("502")
-            || error_str.contains("503")
-            || error_str.contains("504")
-        {
-            "SERVER_ERROR_5XX".to_string()
-        } else {
-            format!("OTHER: {}", error_str)
+    fn categorize_error(&self, error: &AnalyzerError) -> String {
+        match error {
+            AnalyzerError::RateLimited { .. } => "RATE_LIMIT_429".to_string(),
+            AnalyzerError::SymbolNotFound(_) | AnalyzerError::NoData(_) => "NOT_FOUND".to_string(),
+            AnalyzerError::Upstream(message) => {
+                let message = message.to_lowercase();
+                if message.contains("403") || message.contains("forbidden") {
+                    "FORBIDDEN_403".to_string()
+                } else if message.contains("401") || message.contains("unauthorized") {
+                    "UNAUTHORIZED_401".to_string()
+                } else if message.contains("timeout") || message.contains("timed out") {
+                    "TIMEOUT".to_string()
+                } else if message.contains("connection") {
+                    "CONNECTION_ERROR".to_string()
+                } else if message.contains("502") || message.contains("503") || message.contains("504") {
+                    "SERVER_ERROR_5XX".to_string()
+                } else {
+                    format!("OTHER: {}", message)
+                }
+            }
+            other => format!("OTHER: {}", other),
         }
     }
 
@@ -9,6 +9,7 @@ use auto_analyser::StockAnalyzer;
 use std::time::{Duration as StdDuration, Instant};
 use tokio::time::sleep;
 
+#[allow(dead_code)]
 #[derive(Debug)]
 struct TimingTestResult {
     delay_ms: u64,
@@ -39,7 +39,8 @@ function App() {
 
   // React Query hooks
   const { data: continuousStatus } = useContinuousStatus();
-  const { data: filteredResults = [], isLoading: resultsLoading, dataUpdatedAt } = useFilteredResults(filter);
+  const { data: resultsPage, isLoading: resultsLoading, dataUpdatedAt } = useFilteredResults(filter);
+  const filteredResults = resultsPage?.results ?? [];
   const { data: filterStats } = useFilterStats(filter);
   // const { data: cacheStats } = useCacheStats();
   // const { data: databaseStats } = useDatabaseStats();
@@ -132,7 +133,7 @@ function App() {
             <DashboardStats
               continuousStatus={continuousStatus}
               filterStats={filterStats}
-              filteredResultsCount={filteredResults.length}
+              filteredResultsCount={resultsPage?.total ?? 0}
               isConnected={isConnected}
               lastResultsUpdate={lastResultsUpdate}
             />
@@ -19,6 +19,12 @@ const DashboardStats = ({ continuousStatus, filterStats, filteredResultsCount, i
     return date.toLocaleTimeString();
   };
 
+  const formatNextRun = () => {
+    const minutes = Math.max(0, Math.round((new Date(continuousStatus.next_run_at) - Date.now()) / 60000));
+    if (minutes >= 60) return `${Math.floor(minutes / 60)}h ${minutes % 60}m`;
+    return `${minutes}m`;
+  };
+
   const formatResultsUpdate = () => {
     if (!lastResultsUpdate) return 'Never';
     return lastResultsUpdate.toLocaleTimeString();
@@ -54,6 +60,11 @@ const DashboardStats = ({ continuousStatus, filterStats, filteredResultsCount, i
             <div className="mt-2 text-xs text-gray-500">
               Last update: {formatLastUpdate()}
             </div>
+            {continuousStatus.next_run_at && !continuousStatus.is_running && (
+              <div className="text-xs text-gray-500">
+                Next cycle in {formatNextRun()}
+              </div>
+            )}
           </div>
         )}
       </div>
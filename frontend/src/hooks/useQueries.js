@@ -127,6 +127,9 @@ export function useWebSocketUpdates(onMessage, onError) {
     try {
       const ws = api.connectWebSocket(
         (data) => {
+          // Subscription acknowledgements and typed events carry a `type`; status updates don't
+          if (data.type) return;
+
           // Update the continuous status query cache
           queryClient.setQueryData([QUERY_KEYS.continuousStatus], data);
           
@@ -144,6 +147,7 @@ export function useWebSocketUpdates(onMessage, onError) {
       ws.onopen = () => {
         console.log('✅ WebSocket connected');
         isConnectingRef.current = false;
+        ws.send(JSON.stringify({ action: 'subscribe', topic: 'status' }));
       };
       
       ws.onclose = (event) => {
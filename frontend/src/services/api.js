@@ -34,8 +34,10 @@ export const getAnalysisStatus = async (sessionId) => {
   return response.data;
 };
 
-export const getAnalysisResults = async (sessionId) => {
-  const response = await api.get(`/analysis/${sessionId}/results`);
+// Both results endpoints return { total, page, per_page, results } and accept
+// page, per_page, sort_by and order parameters
+export const getAnalysisResults = async (sessionId, params = {}) => {
+  const response = await api.get(`/analysis/${sessionId}/results`, { params });
   return response.data;
 };
 
@@ -45,8 +47,8 @@ export const getContinuousStatus = async () => {
   return response.data;
 };
 
-export const getFilteredResults = async (filter) => {
-  const response = await api.post('/filtered-results', filter);
+export const getFilteredResults = async (filter, params = {}) => {
+  const response = await api.post('/filtered-results', filter, { params });
   return response.data;
 };
 
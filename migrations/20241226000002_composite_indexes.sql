@@ -0,0 +1,4 @@
+-- Composite indexes matching the latest-per-ticker, per-session and opportunity queries
+CREATE INDEX IF NOT EXISTS idx_ticker_timestamp ON analysis_results(ticker, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_session_timestamp ON analysis_results(analysis_session, timestamp);
+CREATE INDEX IF NOT EXISTS idx_opportunity_timestamp ON analysis_results(is_opportunity, timestamp);
@@ -0,0 +1,2 @@
+-- Human-readable per-result summary generated by the analysis engine
+ALTER TABLE analysis_results ADD COLUMN summary TEXT;
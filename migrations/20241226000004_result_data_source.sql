@@ -0,0 +1,3 @@
+-- Which quote provider served each result's price history, and when
+ALTER TABLE analysis_results ADD COLUMN data_source TEXT;
+ALTER TABLE analysis_results ADD COLUMN data_fetched_at TEXT;
@@ -0,0 +1,9 @@
+-- Ticker renames: old_symbol trades as new_symbol from effective_date on
+CREATE TABLE IF NOT EXISTS symbol_aliases (
+    old_symbol TEXT PRIMARY KEY,
+    new_symbol TEXT NOT NULL,
+    effective_date TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_symbol_aliases_new ON symbol_aliases(new_symbol);
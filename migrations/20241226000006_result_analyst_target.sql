@@ -0,0 +1,3 @@
+-- Consensus analyst target and the implied move from the analyzed price
+ALTER TABLE analysis_results ADD COLUMN analyst_target REAL;
+ALTER TABLE analysis_results ADD COLUMN upside_to_target_pct REAL;
@@ -0,0 +1,5 @@
+-- Symbols the continuous loop analyzes first in every cycle
+CREATE TABLE IF NOT EXISTS watchlist (
+    symbol TEXT PRIMARY KEY,
+    added_at TEXT NOT NULL
+);
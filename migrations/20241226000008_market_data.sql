@@ -0,0 +1,11 @@
+-- Daily OHLCV history, so analysis runs only fetch the bars they are missing
+CREATE TABLE IF NOT EXISTS market_data (
+    symbol TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    open REAL NOT NULL,
+    high REAL NOT NULL,
+    low REAL NOT NULL,
+    close REAL NOT NULL,
+    volume INTEGER NOT NULL,
+    UNIQUE(symbol, timestamp)
+);
@@ -0,0 +1,24 @@
+-- Price and RSI alerts checked against every continuous analysis result
+CREATE TABLE IF NOT EXISTS alerts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    condition_type TEXT NOT NULL,
+    condition_value REAL NOT NULL,
+    is_active INTEGER NOT NULL DEFAULT 1,
+    is_triggered INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_alerts_symbol ON alerts(symbol);
+
+CREATE TABLE IF NOT EXISTS alert_triggers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    alert_id INTEGER NOT NULL,
+    symbol TEXT NOT NULL,
+    condition_type TEXT NOT NULL,
+    condition_value REAL NOT NULL,
+    observed_value REAL NOT NULL,
+    triggered_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_triggers_alert ON alert_triggers(alert_id, triggered_at);
@@ -0,0 +1,5 @@
+-- Screener sector and industry, for the per-sector summary
+ALTER TABLE analysis_results ADD COLUMN sector TEXT;
+ALTER TABLE analysis_results ADD COLUMN industry TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_sector ON analysis_results(sector);
@@ -0,0 +1,6 @@
+-- Named StockFilter configurations, stored as JSON
+CREATE TABLE IF NOT EXISTS filter_presets (
+    name TEXT PRIMARY KEY,
+    filter TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
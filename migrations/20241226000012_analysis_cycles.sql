@@ -0,0 +1,10 @@
+-- One row per continuous analysis cycle; its results are stored under session_id
+CREATE TABLE IF NOT EXISTS analysis_cycles (
+    cycle_number INTEGER PRIMARY KEY,
+    session_id TEXT NOT NULL UNIQUE,
+    started_at TEXT NOT NULL,
+    finished_at TEXT,
+    tickers_analyzed INTEGER NOT NULL DEFAULT 0,
+    opportunities_found INTEGER NOT NULL DEFAULT 0,
+    error_count INTEGER NOT NULL DEFAULT 0
+);
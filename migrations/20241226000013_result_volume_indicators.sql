@@ -0,0 +1,4 @@
+-- Volume indicators, so stored results can be screened for unusual volume
+ALTER TABLE analysis_results ADD COLUMN obv REAL;
+ALTER TABLE analysis_results ADD COLUMN vwap REAL;
+ALTER TABLE analysis_results ADD COLUMN volume_vs_avg REAL;
@@ -0,0 +1,8 @@
+-- Symbols the continuous loop failed to fetch, and until which cycle it skips them
+CREATE TABLE IF NOT EXISTS symbol_health (
+    symbol TEXT PRIMARY KEY,
+    consecutive_failures INTEGER NOT NULL,
+    last_error TEXT NOT NULL,
+    last_attempt TEXT NOT NULL,
+    skip_until_cycle INTEGER
+);
@@ -0,0 +1,2 @@
+-- Crossovers within the lookback when the result was analyzed, as JSON; NULL when there were none
+ALTER TABLE analysis_results ADD COLUMN crossovers TEXT;
@@ -0,0 +1,13 @@
+-- One row per on-demand analysis session, with the request and resolved filter that started it
+CREATE TABLE IF NOT EXISTS sessions (
+    session_id TEXT PRIMARY KEY,
+    request TEXT NOT NULL,
+    filter TEXT NOT NULL,
+    status TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    finished_at TEXT,
+    analyzed_count INTEGER NOT NULL DEFAULT 0,
+    total_count INTEGER NOT NULL DEFAULT 0,
+    opportunities_found INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT
+);
@@ -0,0 +1,12 @@
+-- Daily indicator values per symbol, so indicator charts don't need the price history refetched
+CREATE TABLE IF NOT EXISTS indicator_history (
+    symbol TEXT NOT NULL,
+    date TEXT NOT NULL,
+    rsi REAL,
+    sma_20 REAL,
+    sma_50 REAL,
+    macd REAL,
+    macd_signal REAL,
+    macd_histogram REAL,
+    UNIQUE(symbol, date)
+);
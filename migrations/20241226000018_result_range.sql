@@ -0,0 +1,7 @@
+-- Rolling high/low over the indicator range window, so stored results can be
+-- screened by distance from the 52-week high and low
+ALTER TABLE analysis_results ADD COLUMN high_52w REAL;
+ALTER TABLE analysis_results ADD COLUMN low_52w REAL;
+ALTER TABLE analysis_results ADD COLUMN pct_from_high REAL;
+ALTER TABLE analysis_results ADD COLUMN pct_from_low REAL;
+ALTER TABLE analysis_results ADD COLUMN range_window_complete INTEGER NOT NULL DEFAULT 0;
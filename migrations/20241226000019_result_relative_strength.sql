@@ -0,0 +1,6 @@
+-- Return relative to the benchmark over 1-week, 1-month and 3-month windows,
+-- and the 3-month ratio's percentile rank within its continuous cycle
+ALTER TABLE analysis_results ADD COLUMN rs_1w REAL;
+ALTER TABLE analysis_results ADD COLUMN rs_1m REAL;
+ALTER TABLE analysis_results ADD COLUMN rs_3m REAL;
+ALTER TABLE analysis_results ADD COLUMN rs_rank REAL;
@@ -0,0 +1,2 @@
+-- Listing exchange carried over from the screener (NASDAQ, NYSE or AMEX)
+ALTER TABLE analysis_results ADD COLUMN exchange TEXT;
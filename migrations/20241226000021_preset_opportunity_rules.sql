@@ -0,0 +1,2 @@
+-- Opportunity rules (JSON) sessions started from the preset use
+ALTER TABLE filter_presets ADD COLUMN opportunity_rules TEXT;
@@ -0,0 +1,3 @@
+-- Market cap parsed from the screener's text (e.g. "$1.5B"), so results
+-- can be filtered on it in SQL. Earlier rows are filled in on connect.
+ALTER TABLE analysis_results ADD COLUMN market_cap_value REAL;
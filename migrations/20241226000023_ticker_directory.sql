@@ -0,0 +1,8 @@
+-- Latest listing of every symbol the ticker fetch has returned, and when
+CREATE TABLE IF NOT EXISTS tickers (
+    symbol TEXT PRIMARY KEY,
+    info TEXT NOT NULL,
+    first_seen TEXT NOT NULL,
+    last_seen TEXT NOT NULL,
+    missing_fetches INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,14 @@
+-- Every notification delivery attempt, one row per notification and channel
+CREATE TABLE IF NOT EXISTS notification_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    symbol TEXT,
+    title TEXT NOT NULL,
+    message TEXT NOT NULL,
+    channel TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    error TEXT,
+    created_at TEXT NOT NULL,
+    finished_at TEXT NOT NULL
+);
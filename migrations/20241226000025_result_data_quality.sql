@@ -0,0 +1,4 @@
+-- How much of a result's price history was repaired when fetched, as JSON,
+-- with the repaired share in its own column for the results filter
+ALTER TABLE analysis_results ADD COLUMN data_quality TEXT;
+ALTER TABLE analysis_results ADD COLUMN repaired_bar_ratio REAL;
@@ -0,0 +1,4 @@
+-- Trend strength of each result: ADX(14) and its directional indicators
+ALTER TABLE analysis_results ADD COLUMN adx REAL;
+ALTER TABLE analysis_results ADD COLUMN plus_di REAL;
+ALTER TABLE analysis_results ADD COLUMN minus_di REAL;
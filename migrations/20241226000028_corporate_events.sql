@@ -0,0 +1,12 @@
+-- Splits and dividends behind the stored daily history, so it can be
+-- adjusted without being fetched again
+CREATE TABLE IF NOT EXISTS corporate_events (
+    symbol TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    value REAL NOT NULL,
+    UNIQUE(symbol, timestamp, kind)
+);
+
+-- Ex-date of the latest split in the history behind each result
+ALTER TABLE analysis_results ADD COLUMN last_split TEXT;
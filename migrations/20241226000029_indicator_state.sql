@@ -0,0 +1,10 @@
+-- Streaming indicator state per symbol and settings, so the continuous loop
+-- only feeds new bars after a restart
+CREATE TABLE IF NOT EXISTS indicator_state (
+    symbol TEXT NOT NULL,
+    settings TEXT NOT NULL,
+    last_timestamp TEXT,
+    state TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY(symbol, settings)
+);
@@ -0,0 +1,13 @@
+-- Per-session trace of what happened to each ticker, served by
+-- GET /api/analysis/:session_id/events once the in-memory log is gone
+CREATE TABLE IF NOT EXISTS session_events (
+    session_id TEXT NOT NULL,
+    seq INTEGER NOT NULL,
+    timestamp TEXT NOT NULL,
+    ticker TEXT,
+    level TEXT NOT NULL,
+    event TEXT NOT NULL,
+    PRIMARY KEY(session_id, seq)
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_events_ticker ON session_events(session_id, ticker);
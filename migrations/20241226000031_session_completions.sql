@@ -0,0 +1,23 @@
+-- One row per session whose results were all stored, written in the same
+-- transaction as its last results so a crash never leaves a partial
+-- session looking complete
+CREATE TABLE IF NOT EXISTS session_completions (
+    session_id TEXT PRIMARY KEY,
+    result_count INTEGER NOT NULL,
+    completed_at TEXT NOT NULL
+);
+
+-- Sessions and cycles that finished before the marker existed
+INSERT OR IGNORE INTO session_completions (session_id, result_count, completed_at)
+SELECT s.session_id,
+       (SELECT COUNT(*) FROM analysis_results r WHERE r.analysis_session = s.session_id),
+       s.finished_at
+FROM sessions s
+WHERE s.status = 'completed' AND s.finished_at IS NOT NULL;
+
+INSERT OR IGNORE INTO session_completions (session_id, result_count, completed_at)
+SELECT c.session_id,
+       (SELECT COUNT(*) FROM analysis_results r WHERE r.analysis_session = c.session_id),
+       c.finished_at
+FROM analysis_cycles c
+WHERE c.finished_at IS NOT NULL;
@@ -0,0 +1,83 @@
+-- Results record the timeframe their indicators were computed on, and a
+-- ticker's daily and weekly results in one session no longer replace each
+-- other. SQLite can't change a UNIQUE constraint in place, so the table is
+-- rebuilt.
+CREATE TABLE analysis_results_new (
+    id TEXT PRIMARY KEY,
+    ticker TEXT NOT NULL,
+    name TEXT NOT NULL,
+    current_price REAL,
+    rsi REAL,
+    sma_20 REAL,
+    sma_50 REAL,
+    macd REAL,
+    macd_signal REAL,
+    macd_histogram REAL,
+    volume INTEGER,
+    pct_change REAL,
+    market_cap TEXT,
+    is_opportunity INTEGER NOT NULL,
+    signals TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    analysis_session TEXT NOT NULL,
+    summary TEXT,
+    data_source TEXT,
+    data_fetched_at TEXT,
+    analyst_target REAL,
+    upside_to_target_pct REAL,
+    sector TEXT,
+    industry TEXT,
+    obv REAL,
+    vwap REAL,
+    volume_vs_avg REAL,
+    crossovers TEXT,
+    high_52w REAL,
+    low_52w REAL,
+    pct_from_high REAL,
+    pct_from_low REAL,
+    range_window_complete INTEGER NOT NULL DEFAULT 0,
+    rs_1w REAL,
+    rs_1m REAL,
+    rs_3m REAL,
+    rs_rank REAL,
+    exchange TEXT,
+    market_cap_value REAL,
+    data_quality TEXT,
+    repaired_bar_ratio REAL,
+    adx REAL,
+    plus_di REAL,
+    minus_di REAL,
+    last_split TEXT,
+    timeframe TEXT NOT NULL DEFAULT 'daily',
+    UNIQUE(ticker, analysis_session, timeframe)
+);
+
+INSERT INTO analysis_results_new (
+    id, ticker, name, current_price, rsi, sma_20, sma_50, macd,
+    macd_signal, macd_histogram, volume, pct_change, market_cap, is_opportunity, signals, timestamp,
+    analysis_session, summary, data_source, data_fetched_at, analyst_target, upside_to_target_pct, sector, industry,
+    obv, vwap, volume_vs_avg, crossovers, high_52w, low_52w, pct_from_high, pct_from_low,
+    range_window_complete, rs_1w, rs_1m, rs_3m, rs_rank, exchange, market_cap_value, data_quality,
+    repaired_bar_ratio, adx, plus_di, minus_di, last_split
+)
+SELECT
+    id, ticker, name, current_price, rsi, sma_20, sma_50, macd,
+    macd_signal, macd_histogram, volume, pct_change, market_cap, is_opportunity, signals, timestamp,
+    analysis_session, summary, data_source, data_fetched_at, analyst_target, upside_to_target_pct, sector, industry,
+    obv, vwap, volume_vs_avg, crossovers, high_52w, low_52w, pct_from_high, pct_from_low,
+    range_window_complete, rs_1w, rs_1m, rs_3m, rs_rank, exchange, market_cap_value, data_quality,
+    repaired_bar_ratio, adx, plus_di, minus_di, last_split
+FROM analysis_results;
+
+DROP TABLE analysis_results;
+ALTER TABLE analysis_results_new RENAME TO analysis_results;
+
+CREATE INDEX IF NOT EXISTS idx_ticker ON analysis_results(ticker);
+CREATE INDEX IF NOT EXISTS idx_timestamp ON analysis_results(timestamp);
+CREATE INDEX IF NOT EXISTS idx_session ON analysis_results(analysis_session);
+CREATE INDEX IF NOT EXISTS idx_opportunity ON analysis_results(is_opportunity);
+CREATE INDEX IF NOT EXISTS idx_rsi ON analysis_results(rsi);
+CREATE INDEX IF NOT EXISTS idx_ticker_timestamp ON analysis_results(ticker, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_session_timestamp ON analysis_results(analysis_session, timestamp);
+CREATE INDEX IF NOT EXISTS idx_opportunity_timestamp ON analysis_results(is_opportunity, timestamp);
+CREATE INDEX IF NOT EXISTS idx_sector ON analysis_results(sector);
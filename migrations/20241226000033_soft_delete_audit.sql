@@ -0,0 +1,20 @@
+-- Watchlist items, alerts and presets deleted through the API are kept
+-- with a deleted_at time so they can be restored
+ALTER TABLE watchlist ADD COLUMN deleted_at TEXT;
+ALTER TABLE alerts ADD COLUMN deleted_at TEXT;
+ALTER TABLE filter_presets ADD COLUMN deleted_at TEXT;
+
+-- Every create, update, delete and restore made through the API, with the
+-- resource before and after as JSON
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    resource TEXT NOT NULL,
+    resource_id TEXT NOT NULL,
+    action TEXT NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    source_ip TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_resource ON audit_log(resource, resource_id);
@@ -0,0 +1,17 @@
+-- Results that break the sanity bounds list how, as a JSON array; NULL for
+-- believable ones, so the partial index stays small
+ALTER TABLE analysis_results ADD COLUMN anomalies TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_anomalous_results ON analysis_results(timestamp) WHERE anomalies IS NOT NULL;
+
+-- Anomalous results kept out of analysis_results under the quarantine policy
+CREATE TABLE IF NOT EXISTS quarantined_results (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ticker TEXT NOT NULL,
+    analysis_session TEXT NOT NULL,
+    anomalies TEXT NOT NULL,
+    result TEXT NOT NULL,
+    timestamp TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_quarantined_results_timestamp ON quarantined_results(timestamp);
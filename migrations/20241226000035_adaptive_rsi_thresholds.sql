@@ -0,0 +1,4 @@
+-- RSI thresholds an adaptive filter applied to the symbol; NULL for results
+-- screened at the static thresholds only
+ALTER TABLE analysis_results ADD COLUMN rsi_oversold_used REAL;
+ALTER TABLE analysis_results ADD COLUMN rsi_overbought_used REAL;
@@ -0,0 +1,18 @@
+-- RSI relative to the result's sector and industry in its continuous
+-- cycle; set once the cycle finishes
+ALTER TABLE analysis_results ADD COLUMN rsi_sector_zscore REAL;
+ALTER TABLE analysis_results ADD COLUMN rsi_industry_zscore REAL;
+
+-- Mean and spread of RSI and percent change per sector and industry (and
+-- the whole market) of each continuous cycle
+CREATE TABLE IF NOT EXISTS group_stats (
+    session_id TEXT NOT NULL,
+    scope TEXT NOT NULL,
+    name TEXT NOT NULL,
+    names INTEGER NOT NULL,
+    rsi_mean REAL NOT NULL,
+    rsi_stddev REAL NOT NULL,
+    pct_change_mean REAL,
+    pct_change_stddev REAL,
+    PRIMARY KEY (session_id, scope, name)
+);
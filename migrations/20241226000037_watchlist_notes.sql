@@ -0,0 +1,2 @@
+-- Free-text note kept with a watchlist symbol, as imported from CSV
+ALTER TABLE watchlist ADD COLUMN note TEXT;
@@ -0,0 +1,3 @@
+-- Tickers a session stopped short of when its budget ran out, as a JSON
+-- array; NULL for sessions that got through their whole list
+ALTER TABLE sessions ADD COLUMN remaining_tickers TEXT;
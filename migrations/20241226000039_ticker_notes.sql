@@ -0,0 +1,11 @@
+-- Free-text notes on tickers, joined onto their results while active
+CREATE TABLE IF NOT EXISTS ticker_notes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    note TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    expires_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticker_notes_symbol ON ticker_notes(symbol);
@@ -0,0 +1,14 @@
+-- Each symbol's run of consecutive opportunity flags in the continuous loop,
+-- so a symbol that stays flagged is announced once
+CREATE TABLE IF NOT EXISTS opportunity_states (
+    symbol TEXT PRIMARY KEY,
+    started_at TEXT NOT NULL,
+    streak INTEGER NOT NULL,
+    last_exited_at TEXT,
+    updated_at TEXT NOT NULL
+);
+
+-- Where each result stood in that run; 'none' for manual sessions
+ALTER TABLE analysis_results ADD COLUMN opportunity_state TEXT NOT NULL DEFAULT 'none';
+ALTER TABLE analysis_results ADD COLUMN opportunity_streak INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE analysis_results ADD COLUMN opportunity_since TEXT;
@@ -0,0 +1,11 @@
+-- User-defined indicators, arithmetic over built-in series such as
+-- (close - sma_50) / atr_14, computed for every analyzed symbol
+CREATE TABLE IF NOT EXISTS custom_indicators (
+    name TEXT PRIMARY KEY,
+    expression TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Their values at each result's latest bar, as a JSON object by name
+ALTER TABLE analysis_results ADD COLUMN custom_indicators TEXT;
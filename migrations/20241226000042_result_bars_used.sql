@@ -0,0 +1,3 @@
+-- Bars of history each result's indicators were computed from, so thin
+-- histories such as recent listings can be screened out
+ALTER TABLE analysis_results ADD COLUMN bars_used INTEGER;
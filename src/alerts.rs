@@ -0,0 +1,278 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::symbols::normalize_symbol;
+use crate::web_api::StockAnalysisResult;
+
+/// What an alert watches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertCondition {
+    PriceAbove,
+    PriceBelow,
+    RsiAbove,
+    RsiBelow,
+}
+
+impl AlertCondition {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlertCondition::PriceAbove => "price_above",
+            AlertCondition::PriceBelow => "price_below",
+            AlertCondition::RsiAbove => "rsi_above",
+            AlertCondition::RsiBelow => "rsi_below",
+        }
+    }
+
+    /// The watched value of `result`, if it has one
+    pub fn observe(self, result: &StockAnalysisResult) -> Option<f64> {
+        match self {
+            AlertCondition::PriceAbove | AlertCondition::PriceBelow => result.current_price,
+            AlertCondition::RsiAbove | AlertCondition::RsiBelow => result.rsi,
+        }
+    }
+
+    pub fn is_met(self, observed: f64, threshold: f64) -> bool {
+        match self {
+            AlertCondition::PriceAbove | AlertCondition::RsiAbove => observed > threshold,
+            AlertCondition::PriceBelow | AlertCondition::RsiBelow => observed < threshold,
+        }
+    }
+}
+
+impl FromStr for AlertCondition {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "price_above" => Ok(AlertCondition::PriceAbove),
+            "price_below" => Ok(AlertCondition::PriceBelow),
+            "rsi_above" => Ok(AlertCondition::RsiAbove),
+            "rsi_below" => Ok(AlertCondition::RsiBelow),
+            other => Err(format!("unknown alert condition: {}", other)),
+        }
+    }
+}
+
+/// A stored alert. `is_triggered` is set when it fires and cleared once the
+/// condition stops holding, so an alert fires once per crossing rather than
+/// on every cycle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: i64,
+    pub symbol: String,
+    pub condition_type: AlertCondition,
+    pub condition_value: f64,
+    pub is_active: bool,
+    pub is_triggered: bool,
+    pub created_at: DateTime<Utc>,
+    /// When it was deleted; deleted alerts are never checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// Body of `POST /api/alerts`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewAlert {
+    pub symbol: String,
+    pub condition_type: AlertCondition,
+    pub condition_value: f64,
+}
+
+impl NewAlert {
+    /// Normalize the symbol and check the threshold is usable
+    pub fn validate(mut self) -> Result<Self, String> {
+        self.symbol = normalize_symbol(&self.symbol);
+        if self.symbol.is_empty() {
+            return Err("symbol must not be empty".to_string());
+        }
+        if !self.condition_value.is_finite() {
+            return Err("condition_value must be a finite number".to_string());
+        }
+        let is_rsi = matches!(self.condition_type, AlertCondition::RsiAbove | AlertCondition::RsiBelow);
+        if is_rsi && !(0.0..=100.0).contains(&self.condition_value) {
+            return Err(format!("RSI thresholds must be between 0 and 100, got {}", self.condition_value));
+        }
+        Ok(self)
+    }
+}
+
+/// An alert firing, as stored and broadcast
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertTrigger {
+    pub alert_id: i64,
+    pub symbol: String,
+    pub condition_type: AlertCondition,
+    pub condition_value: f64,
+    pub observed_value: f64,
+    pub triggered_at: DateTime<Utc>,
+}
+
+/// Change to an alert's triggered flag produced by `evaluate_alerts`
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertUpdate {
+    Fired(AlertTrigger),
+    /// The condition no longer holds; the alert may fire again
+    Rearmed(i64),
+}
+
+/// Check `result` against the active alerts for its symbol, updating their
+/// triggered flags in place
+pub fn evaluate_alerts(alerts: &mut [Alert], result: &StockAnalysisResult, now: DateTime<Utc>) -> Vec<AlertUpdate> {
+    let symbol = normalize_symbol(&result.ticker);
+    let mut updates = Vec::new();
+
+    for alert in alerts.iter_mut().filter(|alert| alert.is_active && alert.symbol == symbol) {
+        let Some(observed) = alert.condition_type.observe(result) else {
+            continue;
+        };
+        let met = alert.condition_type.is_met(observed, alert.condition_value);
+
+        if met && !alert.is_triggered {
+            alert.is_triggered = true;
+            updates.push(AlertUpdate::Fired(AlertTrigger {
+                alert_id: alert.id,
+                symbol: alert.symbol.clone(),
+                condition_type: alert.condition_type,
+                condition_value: alert.condition_value,
+                observed_value: observed,
+                triggered_at: now,
+            }));
+        } else if !met && alert.is_triggered {
+            alert.is_triggered = false;
+            updates.push(AlertUpdate::Rearmed(alert.id));
+        }
+    }
+
+    updates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opportunity_state::OpportunityState;
+    use crate::resample::Timeframe;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 2, 14, 30, 0).unwrap()
+    }
+
+    fn alert(id: i64, condition_type: AlertCondition, condition_value: f64) -> Alert {
+        Alert {
+            id,
+            symbol: "AAPL".to_string(),
+            condition_type,
+            condition_value,
+            is_active: true,
+            is_triggered: false,
+            created_at: now(),
+            deleted_at: None,
+        }
+    }
+
+    fn result(price: f64, rsi: Option<f64>) -> StockAnalysisResult {
+        StockAnalysisResult {
+            ticker: "AAPL".to_string(),
+            name: "Apple".to_string(),
+            current_price: Some(price),
+            rsi,
+            sma_20: None,
+            sma_50: None,
+            macd: None,
+            macd_signal: None,
+            macd_histogram: None,
+            volume: None,
+            pct_change: None,
+            market_cap: None,
+            sector: None,
+            industry: None,
+            exchange: None,
+            is_opportunity: false,
+            signals: Vec::new(),
+            timestamp: now(),
+            data_source: None,
+            data_fetched_at: None,
+            summary: None,
+            analyst_target: None,
+            upside_to_target_pct: None,
+            obv: None,
+            vwap: None,
+            volume_vs_avg: None,
+            high_52w: None,
+            low_52w: None,
+            pct_from_high: None,
+            pct_from_low: None,
+            range_window_complete: false,
+            rs_1w: None,
+            rs_1m: None,
+            rs_3m: None,
+            rs_rank: None,
+            moving_averages: Default::default(),
+            crossovers: Vec::new(),
+            data_quality: None,
+            adx: None,
+            plus_di: None,
+            minus_di: None,
+            last_split: None,
+            timeframe: Timeframe::Daily,
+            anomalies: Vec::new(),
+            rsi_oversold_used: None,
+            rsi_overbought_used: None,
+            rsi_sector_zscore: None,
+            rsi_industry_zscore: None,
+            note: None,
+            opportunity_state: OpportunityState::None,
+            opportunity_streak: 0,
+            opportunity_since: None,
+            custom: Default::default(),
+            bars_used: None,
+        }
+    }
+
+    #[test]
+    fn test_fires_once_per_crossing() {
+        let mut alerts = vec![alert(1, AlertCondition::PriceAbove, 200.0)];
+
+        assert!(evaluate_alerts(&mut alerts, &result(190.0, None), now()).is_empty());
+
+        let updates = evaluate_alerts(&mut alerts, &result(205.0, None), now());
+        assert!(matches!(&updates[..], [AlertUpdate::Fired(trigger)] if trigger.observed_value == 205.0));
+
+        // Still above: no repeat while triggered
+        assert!(evaluate_alerts(&mut alerts, &result(210.0, None), now()).is_empty());
+
+        assert_eq!(evaluate_alerts(&mut alerts, &result(195.0, None), now()), vec![AlertUpdate::Rearmed(1)]);
+        assert_eq!(evaluate_alerts(&mut alerts, &result(201.0, None), now()).len(), 1);
+    }
+
+    #[test]
+    fn test_only_matching_active_alerts() {
+        let mut alerts = vec![
+            alert(1, AlertCondition::RsiBelow, 30.0),
+            Alert { is_active: false, ..alert(2, AlertCondition::RsiBelow, 30.0) },
+            Alert { symbol: "MSFT".to_string(), ..alert(3, AlertCondition::RsiBelow, 30.0) },
+            alert(4, AlertCondition::RsiAbove, 70.0),
+        ];
+
+        let updates = evaluate_alerts(&mut alerts, &result(100.0, Some(25.0)), now());
+        assert!(matches!(&updates[..], [AlertUpdate::Fired(trigger)] if trigger.alert_id == 1));
+
+        // No RSI value: RSI alerts are left alone
+        assert!(evaluate_alerts(&mut alerts, &result(100.0, None), now()).is_empty());
+    }
+
+    #[test]
+    fn test_new_alert_validation() {
+        let new = |condition_type, condition_value| NewAlert {
+            symbol: " aapl ".to_string(),
+            condition_type,
+            condition_value,
+        };
+        assert_eq!(new(AlertCondition::PriceBelow, 150.0).validate().unwrap().symbol, "AAPL");
+        assert!(new(AlertCondition::RsiAbove, 120.0).validate().is_err());
+        assert!(new(AlertCondition::PriceAbove, f64::NAN).validate().is_err());
+        assert_eq!("rsi_below".parse(), Ok(AlertCondition::RsiBelow));
+    }
+}
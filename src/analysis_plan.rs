@@ -0,0 +1,158 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Per-ticker timings kept for each kind of ticker
+pub const TIMING_WINDOW: usize = 200;
+/// Assumed time to analyze a ticker whose history is cached, before any
+/// has been timed
+pub const DEFAULT_CACHED_TICKER_TIME: Duration = Duration::from_millis(20);
+/// Assumed time to analyze a ticker whose history has to be fetched,
+/// before any has been timed
+pub const DEFAULT_FETCHED_TICKER_TIME: Duration = Duration::from_millis(500);
+
+/// Rolling averages of how long recent tickers took to analyze, kept apart
+/// for tickers served from the cache and tickers fetched upstream since the
+/// two differ by orders of magnitude
+#[derive(Debug, Clone, Default)]
+pub struct TickerTimings {
+    cached: VecDeque<Duration>,
+    fetched: VecDeque<Duration>,
+}
+
+impl TickerTimings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one analyzed ticker, dropping the oldest timing of its kind
+    /// once the window is full
+    pub fn record(&mut self, cache_hit: bool, elapsed: Duration) {
+        let window = match cache_hit {
+            true => &mut self.cached,
+            false => &mut self.fetched,
+        };
+        if window.len() == TIMING_WINDOW {
+            window.pop_front();
+        }
+        window.push_back(elapsed);
+    }
+
+    /// Average time of recent tickers of one kind, or the default for it
+    /// when none has been timed
+    pub fn average(&self, cache_hit: bool) -> Duration {
+        let (window, default) = match cache_hit {
+            true => (&self.cached, DEFAULT_CACHED_TICKER_TIME),
+            false => (&self.fetched, DEFAULT_FETCHED_TICKER_TIME),
+        };
+        match window.len() {
+            0 => default,
+            n => window.iter().sum::<Duration>() / n as u32,
+        }
+    }
+
+    /// Timings the averages are based on
+    pub fn samples(&self) -> usize {
+        self.cached.len() + self.fetched.len()
+    }
+
+    /// How long `cache_hits` cached and `fresh_fetches` fetched tickers
+    /// should take one after another
+    pub fn estimate(&self, cache_hits: usize, fresh_fetches: usize) -> Duration {
+        self.average(true) * cache_hits as u32 + self.average(false) * fresh_fetches as u32
+    }
+}
+
+/// What an analysis request would do, returned instead of starting a
+/// session when the request is a dry run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisPlan {
+    /// Tickers in the ticker list
+    pub total_tickers: usize,
+    /// Tickers passing the filter
+    pub filtered_tickers: usize,
+    /// Tickers that would be analyzed, after `max_analysis`
+    pub planned_tickers: usize,
+    /// Planned tickers whose history is cached
+    pub cache_hits: usize,
+    /// Planned tickers whose history would be fetched upstream
+    pub fresh_fetches: usize,
+    pub avg_cached_ticker_ms: u64,
+    pub avg_fetched_ticker_ms: u64,
+    /// Analyzed tickers the averages come from; 0 means they are defaults
+    pub timing_samples: usize,
+    pub estimated_duration_secs: f64,
+    pub estimated_completion: DateTime<Utc>,
+}
+
+impl AnalysisPlan {
+    /// Plan for `cache_hit`, one flag per planned ticker, starting at `now`
+    pub fn new(total_tickers: usize, filtered_tickers: usize, cache_hit: &[bool], timings: &TickerTimings, now: DateTime<Utc>) -> Self {
+        let cache_hits = cache_hit.iter().filter(|hit| **hit).count();
+        let fresh_fetches = cache_hit.len() - cache_hits;
+        let duration = timings.estimate(cache_hits, fresh_fetches);
+        Self {
+            total_tickers,
+            filtered_tickers,
+            planned_tickers: cache_hit.len(),
+            cache_hits,
+            fresh_fetches,
+            avg_cached_ticker_ms: timings.average(true).as_millis() as u64,
+            avg_fetched_ticker_ms: timings.average(false).as_millis() as u64,
+            timing_samples: timings.samples(),
+            estimated_duration_secs: duration.as_secs_f64(),
+            estimated_completion: completion_at(now, duration),
+        }
+    }
+}
+
+/// `now` plus `duration`, saturating at the latest representable time
+pub fn completion_at(now: DateTime<Utc>, duration: Duration) -> DateTime<Utc> {
+    chrono::Duration::from_std(duration)
+        .ok()
+        .and_then(|duration| now.checked_add_signed(duration))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_until_timed() {
+        let timings = TickerTimings::new();
+        assert_eq!(timings.estimate(10, 2), DEFAULT_CACHED_TICKER_TIME * 10 + DEFAULT_FETCHED_TICKER_TIME * 2);
+        assert_eq!(timings.samples(), 0);
+    }
+
+    #[test]
+    fn test_rolling_averages_per_kind() {
+        let mut timings = TickerTimings::new();
+        timings.record(true, Duration::from_millis(10));
+        timings.record(true, Duration::from_millis(30));
+        timings.record(false, Duration::from_millis(300));
+        assert_eq!(timings.average(true), Duration::from_millis(20));
+        assert_eq!(timings.average(false), Duration::from_millis(300));
+
+        // Only the latest window of fetched timings counts
+        for _ in 0..TIMING_WINDOW {
+            timings.record(false, Duration::from_millis(100));
+        }
+        assert_eq!(timings.average(false), Duration::from_millis(100));
+        assert_eq!(timings.samples(), 2 + TIMING_WINDOW);
+    }
+
+    #[test]
+    fn test_plan_counts_hits_and_fetches() {
+        let mut timings = TickerTimings::new();
+        timings.record(true, Duration::from_millis(10));
+        timings.record(false, Duration::from_millis(1000));
+        let now = "2024-03-04T21:00:00Z".parse().unwrap();
+
+        let plan = AnalysisPlan::new(50, 4, &[true, false, true, false], &timings, now);
+        assert_eq!((plan.planned_tickers, plan.cache_hits, plan.fresh_fetches), (4, 2, 2));
+        assert_eq!(plan.estimated_duration_secs, 2.02);
+        assert_eq!(plan.estimated_completion, now + chrono::Duration::milliseconds(2020));
+    }
+}
@@ -1,12 +1,34 @@
-use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use reqwest;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use time::OffsetDateTime;
-use yahoo_finance_api as yahoo;
-
-use crate::indicators::{CustomRSI, SimpleMovingAverage, MovingAverageConvergenceDivergence};
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::clock::{system_clock, SharedClock};
+use crate::corporate_events::{adjust_bars, merge_events, AdjustmentPolicy};
+use crate::custom_indicators::{validate_name, SharedCustomIndicators};
+use crate::data_quality::{BadBarPolicy, DataQuality};
+use crate::error::{AnalyzerError, Result};
+use crate::http::{HttpClient, HttpClientConfig, HttpClientFactory};
+use crate::indicators::incremental::{plan_update, IndicatorSnapshot, MovingAverageState, SeriesFingerprint, UpdatePlan, PERSISTED_OUTPUTS};
+use crate::indicators::{
+    AdxValue, AverageDirectionalIndex, AverageTrueRange, BollingerBands, BollingerBandsValue, CommodityChannelIndex, CustomRSI, ExponentialMovingAverage,
+    MovingAverageConvergenceDivergence, OnBalanceVolume, RelativeVolume, RollingRange, SimpleMovingAverage,
+    StochasticOscillator, StochasticValue, Vwap, DEFAULT_RANGE_WINDOW,
+};
+use crate::opportunity::HistoryRequirements;
+use crate::opportunity_state::OpportunityState;
+use crate::parsing::{parse_field, parse_market_cap, parse_percentage, parse_price, parse_volume};
+use crate::providers::{default_provider_with, BatchQuoteSource, Interval, MarketDataProvider, ProviderError, QuoteSeries, YahooBatchQuotes};
+use crate::rate_limit::{RateLimiter, RequestPermit};
+use crate::region::{country_matches, looks_like_adr, normalize_country, Region};
+use crate::report::{ticker_table, SymbolReport};
+use crate::resample::{resample, resample_as_of, Timeframe};
+use crate::retry::RetryPolicy;
+use crate::symbol_universe::SymbolUniverse;
+use crate::symbols::normalize_symbol;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TickerInfo {
@@ -21,9 +43,261 @@ pub struct TickerInfo {
     pub volume: Option<String>,
     pub sector: Option<String>,
     pub industry: Option<String>,
+    /// Extended screener columns; absent in the basic screener response
+    /// and in ticker snapshots cached before they were collected
+    #[serde(default)]
+    pub exchange: Option<String>,
+    #[serde(default)]
+    pub beta: Option<String>,
+    #[serde(default)]
+    pub fifty_two_week_high: Option<String>,
+    #[serde(default)]
+    pub fifty_two_week_low: Option<String>,
+    /// `last_sale`, `market_cap`, `pct_change` and `volume` parsed once when
+    /// the tickers are fetched; `None` when the string is missing, a
+    /// placeholder or unparseable. Fill them with `with_parsed_fields`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_sale_f64: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market_cap_f64: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pct_change_f64: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume_u64: Option<u64>,
+    /// `country` under its canonical name ("USA" reads "United States"),
+    /// and its region; filled by `with_parsed_fields`, while `country`
+    /// keeps the screener's spelling
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_normalized: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<Region>,
+    /// Whether the listing looks like depositary receipts of a foreign
+    /// company; see `region::looks_like_adr`
+    #[serde(default)]
+    pub is_adr: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl TickerInfo {
+    /// Parse the numeric display strings into the typed fields, and
+    /// normalize the country and flag ADRs
+    pub fn with_parsed_fields(mut self) -> Self {
+        self.last_sale_f64 = parse_field(self.last_sale.as_deref(), parse_price);
+        self.market_cap_f64 = parse_field(self.market_cap.as_deref(), parse_market_cap);
+        self.pct_change_f64 = parse_field(self.pct_change.as_deref(), parse_percentage);
+        self.volume_u64 = parse_field(self.volume.as_deref(), parse_volume);
+        let country = self.country.as_deref().and_then(normalize_country);
+        self.region = country.as_ref().and_then(|(_, region)| *region);
+        self.country_normalized = country.map(|(name, _)| name);
+        self.is_adr = looks_like_adr(&self.symbol, &self.name, self.country_normalized.as_deref());
+        self
+    }
+
+    /// A ticker known only by its symbol, for symbols missing from the
+    /// cached listing
+    pub fn unlisted(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            name: symbol.to_string(),
+            last_sale: None,
+            net_change: None,
+            pct_change: None,
+            market_cap: None,
+            country: None,
+            ipo_year: None,
+            volume: None,
+            sector: None,
+            industry: None,
+            exchange: None,
+            beta: None,
+            fifty_two_week_high: None,
+            fifty_two_week_low: None,
+            last_sale_f64: None,
+            market_cap_f64: None,
+            pct_change_f64: None,
+            volume_u64: None,
+            country_normalized: None,
+            region: None,
+            is_adr: false,
+        }
+    }
+
+    /// Percent the last sale sits above the 52-week low, when both are known
+    pub fn pct_above_52_week_low(&self) -> Option<f64> {
+        ParsedTickerInfo::pct_above_52_week_low(self)
+    }
+
+    /// Dollars traded today: last sale times volume, when both are known
+    pub fn dollar_volume(&self) -> Option<f64> {
+        Some(self.last_sale_f64? * self.volume_u64? as f64)
+    }
+}
+
+/// US exchange the Nasdaq screener lists stocks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Exchange {
+    Nasdaq,
+    Nyse,
+    Amex,
+}
+
+impl Exchange {
+    /// Every exchange `fetch_n_tickers` covers, in the order a symbol
+    /// listed on more than one keeps its first
+    pub const ALL: [Exchange; 3] = [Exchange::Nasdaq, Exchange::Nyse, Exchange::Amex];
+
+    /// Name as the screener's `exchange` parameter spells it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Exchange::Nasdaq => "NASDAQ",
+            Exchange::Nyse => "NYSE",
+            Exchange::Amex => "AMEX",
+        }
+    }
+}
+
+/// Parsed numeric `TickerInfo` field tickers can be ranked by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TickerSortField {
+    #[default]
+    PctChange,
+    Volume,
+    MarketCap,
+    LastSale,
+}
+
+impl TickerSortField {
+    pub const ALL: [TickerSortField; 4] = [
+        TickerSortField::PctChange,
+        TickerSortField::Volume,
+        TickerSortField::MarketCap,
+        TickerSortField::LastSale,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TickerSortField::PctChange => "pct_change",
+            TickerSortField::Volume => "volume",
+            TickerSortField::MarketCap => "market_cap",
+            TickerSortField::LastSale => "last_sale",
+        }
+    }
+
+    /// The ticker's parsed value for this field, if it has one
+    pub fn value(self, ticker: &TickerInfo) -> Option<f64> {
+        match self {
+            TickerSortField::PctChange => ticker.pct_change_f64,
+            TickerSortField::Volume => ticker.volume_u64.map(|volume| volume as f64),
+            TickerSortField::MarketCap => ticker.market_cap_f64,
+            TickerSortField::LastSale => ticker.last_sale_f64,
+        }
+    }
+}
+
+impl std::str::FromStr for TickerSortField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|field| field.as_str() == s).ok_or_else(|| {
+            let valid: Vec<&str> = Self::ALL.iter().map(|field| field.as_str()).collect();
+            format!("unknown sort '{}'; valid fields are {}", s, valid.join(", "))
+        })
+    }
+}
+
+/// Dollar volume (low, high) scored from 0 to 1 on a log scale by `rank_tickers`
+pub const DOLLAR_VOLUME_SCALE: (f64, f64) = (1e5, 1e9);
+/// Market cap (low, high) scored from 0 to 1 on a log scale by `rank_tickers`
+pub const MARKET_CAP_SCALE: (f64, f64) = (1e7, 1e12);
+
+/// How `StockAnalyzer::rank_tickers` weighs a ticker's move against how
+/// much it trades and how big it is. Each part scores from 0 to 1 (the
+/// move from -1) before weighting, so a huge move on no volume can't
+/// outrank a solid move that traded millions.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RankingConfig {
+    pub pct_change_weight: f64,
+    pub dollar_volume_weight: f64,
+    pub market_cap_weight: f64,
+    /// Moves of this many percent either way score in full
+    pub pct_change_cap: f64,
+    /// Tickers trading fewer dollars than this are left out, as are those
+    /// without a price or volume
+    pub min_dollar_volume: Option<f64>,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            pct_change_weight: 0.5,
+            dollar_volume_weight: 0.35,
+            market_cap_weight: 0.15,
+            pct_change_cap: 10.0,
+            min_dollar_volume: None,
+        }
+    }
+}
+
+impl RankingConfig {
+    pub fn with_weights(mut self, pct_change: f64, dollar_volume: f64, market_cap: f64) -> Self {
+        self.pct_change_weight = pct_change;
+        self.dollar_volume_weight = dollar_volume;
+        self.market_cap_weight = market_cap;
+        self
+    }
+
+    pub fn with_min_dollar_volume(mut self, min: Option<f64>) -> Self {
+        self.min_dollar_volume = min;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let weights = [self.pct_change_weight, self.dollar_volume_weight, self.market_cap_weight];
+        if weights.iter().any(|weight| !weight.is_finite() || *weight < 0.0) {
+            return Err("ranking weights must be non-negative numbers".to_string());
+        }
+        if weights.iter().sum::<f64>() == 0.0 {
+            return Err("at least one ranking weight must be positive".to_string());
+        }
+        if !self.pct_change_cap.is_finite() || self.pct_change_cap <= 0.0 {
+            return Err("pct_change_cap must be positive".to_string());
+        }
+        if self.min_dollar_volume.is_some_and(|min| !min.is_finite() || min < 0.0) {
+            return Err("min_dollar_volume must not be negative".to_string());
+        }
+        Ok(())
+    }
+
+    /// The ticker's weighted score; `None` without a percent change or
+    /// below `min_dollar_volume`. A missing dollar volume or market cap
+    /// scores 0.
+    pub fn score(&self, ticker: &TickerInfo) -> Option<f64> {
+        let pct_change = ticker.pct_change_f64?;
+        let dollar_volume = ticker.dollar_volume();
+        if let Some(min) = self.min_dollar_volume {
+            if dollar_volume.is_none_or(|traded| traded < min) {
+                return None;
+            }
+        }
+        let change = (pct_change / self.pct_change_cap).clamp(-1.0, 1.0);
+        Some(
+            self.pct_change_weight * change
+                + self.dollar_volume_weight * log_scaled(dollar_volume, DOLLAR_VOLUME_SCALE)
+                + self.market_cap_weight * log_scaled(ticker.market_cap_f64, MARKET_CAP_SCALE),
+        )
+    }
+}
+
+/// Where `value` falls between `low` and `high` on a log scale, clamped to 0..=1
+fn log_scaled(value: Option<f64>, (low, high): (f64, f64)) -> f64 {
+    match value {
+        Some(value) if value > 0.0 => ((value.log10() - low.log10()) / (high.log10() - low.log10())).clamp(0.0, 1.0),
+        _ => 0.0,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StockFilter {
     pub min_market_cap: Option<f64>,
     pub max_market_cap: Option<f64>,
@@ -42,6 +316,102 @@ pub struct StockFilter {
     pub max_ipo_year: Option<i32>,
     pub oversold_rsi_threshold: Option<f64>,
     pub overbought_rsi_threshold: Option<f64>,
+    #[serde(default)]
+    pub exchanges: Option<Vec<String>>,
+    #[serde(default)]
+    pub min_beta: Option<f64>,
+    #[serde(default)]
+    pub max_beta: Option<f64>,
+    /// Keep tickers whose last sale is at most this many percent above the
+    /// 52-week low (e.g. 5.0 for "within 5% of the low")
+    #[serde(default)]
+    pub max_pct_above_52_week_low: Option<f64>,
+    /// Keep results whose analyst target implies at least this much upside
+    /// (percent). Only applies to analyzed results; stale targets never pass.
+    #[serde(default)]
+    pub min_upside_to_target_pct: Option<f64>,
+    /// Keep results whose latest volume is at least this multiple of the
+    /// previous 20 candles' average (e.g. 2.0 for twice the usual volume).
+    /// Only applies to analyzed results.
+    #[serde(default)]
+    pub min_volume_vs_avg: Option<f64>,
+    /// Keep results whose close is at most this many percent above the
+    /// low of the indicator range window. Only applies to analyzed results.
+    #[serde(default)]
+    pub max_pct_from_low: Option<f64>,
+    /// Keep results whose close is at least this many percent below the
+    /// high of the indicator range window. Only applies to analyzed results.
+    #[serde(default)]
+    pub min_pct_from_high: Option<f64>,
+    /// Keep results whose relative strength rank in their continuous
+    /// cycle is at least this percentile (0-100). Only applies to analyzed
+    /// results; unranked results never pass.
+    #[serde(default)]
+    pub min_rs_rank: Option<f64>,
+    /// Keep results whose RSI is at most this many standard deviations
+    /// from their sector's mean in the latest continuous cycle, e.g. -1.5
+    /// for names unusually oversold within their sector. Only applies to
+    /// analyzed results; unscored results never pass.
+    #[serde(default)]
+    pub max_rsi_sector_zscore: Option<f64>,
+    /// Keep results whose 14-period ADX is at least this (0-100), i.e.
+    /// that trend at least this strongly in either direction. Only applies
+    /// to analyzed results.
+    #[serde(default)]
+    pub min_adx: Option<f64>,
+    /// Drop results with a stock split in this many days before they were
+    /// analyzed, whose indicators may straddle the jump. Only applies to
+    /// analyzed results.
+    #[serde(default)]
+    pub exclude_split_within_days: Option<u32>,
+    /// Drop results whose fetched history had more than this share (0-1)
+    /// of its bars dropped, filled or deduplicated. Only applies to analyzed
+    /// results; results without data quality pass.
+    #[serde(default)]
+    pub max_repaired_bar_ratio: Option<f64>,
+    /// Keep results with a crossover (golden or death cross, MACD or RSI
+    /// cross, price crossing SMA50) within the configured lookback. Only
+    /// applies to analyzed results.
+    #[serde(default)]
+    pub recent_crossovers_only: bool,
+    /// Keep results of tickers with an active note. Only applies to
+    /// analyzed results.
+    #[serde(default)]
+    pub has_note: bool,
+    /// Keep results in any of these opportunity states, e.g. `["new"]`.
+    /// Only applies to analyzed results.
+    #[serde(default)]
+    pub opportunity_states: Option<Vec<OpportunityState>>,
+    /// Keep results whose opportunity state began at most this many hours
+    /// before they were analyzed, so `["new", "continuing"]` with 24 lists
+    /// the symbols that became opportunities within a day of their latest
+    /// cycle. Only applies to analyzed results; results with no
+    /// opportunity state never pass.
+    #[serde(default)]
+    pub opportunity_within_hours: Option<u32>,
+    /// Keep results whose custom indicators from `/api/custom-indicators`
+    /// lie within these inclusive ranges, keyed by name, e.g.
+    /// `{"stretch": [null, -2.0]}` for a stretch of -2 or less. Only applies
+    /// to analyzed results; results without a value never pass.
+    #[serde(default)]
+    pub custom_indicator_ranges: HashMap<String, (Option<f64>, Option<f64>)>,
+    /// Keep results whose indicators were computed from at least this many
+    /// bars, leaving out recent listings. Only applies to analyzed results;
+    /// results stored before the count was kept never pass.
+    #[serde(default)]
+    pub min_history_bars: Option<usize>,
+    /// Drop tickers whose market cap, last sale, volume or percent change is
+    /// missing or unparseable, even when no filter is set on that field
+    #[serde(default)]
+    pub require_complete_data: bool,
+    /// Drop tickers that look like depositary receipts of foreign companies
+    #[serde(default)]
+    pub exclude_adrs: bool,
+    /// Call a symbol oversold or overbought at its own 10th and 90th RSI
+    /// percentiles over the past year rather than at the static
+    /// thresholds, which still apply to symbols with too little history
+    #[serde(default)]
+    pub adaptive_rsi: bool,
 }
 
 impl Default for StockFilter {
@@ -64,6 +434,28 @@ impl Default for StockFilter {
             max_ipo_year: None,
             oversold_rsi_threshold: Some(30.0),
             overbought_rsi_threshold: Some(70.0),
+            exchanges: None,
+            min_beta: None,
+            max_beta: None,
+            max_pct_above_52_week_low: None,
+            min_upside_to_target_pct: None,
+            min_volume_vs_avg: None,
+            max_pct_from_low: None,
+            min_pct_from_high: None,
+            min_rs_rank: None,
+            max_rsi_sector_zscore: None,
+            min_adx: None,
+            exclude_split_within_days: None,
+            max_repaired_bar_ratio: None,
+            recent_crossovers_only: false,
+            has_note: false,
+            opportunity_states: None,
+            opportunity_within_hours: None,
+            custom_indicator_ranges: HashMap::new(),
+            min_history_bars: None,
+            require_complete_data: false,
+            exclude_adrs: false,
+            adaptive_rsi: false,
         }
     }
 }
@@ -129,6 +521,263 @@ impl StockFilter {
         self.overbought_rsi_threshold = overbought;
         self
     }
+
+    pub fn with_exchanges(mut self, exchanges: Vec<String>) -> Self {
+        self.exchanges = Some(exchanges);
+        self
+    }
+
+    pub fn with_beta_range(mut self, min: Option<f64>, max: Option<f64>) -> Self {
+        self.min_beta = min;
+        self.max_beta = max;
+        self
+    }
+
+    pub fn with_max_pct_above_52_week_low(mut self, pct: Option<f64>) -> Self {
+        self.max_pct_above_52_week_low = pct;
+        self
+    }
+
+    pub fn with_min_upside_to_target_pct(mut self, pct: Option<f64>) -> Self {
+        self.min_upside_to_target_pct = pct;
+        self
+    }
+
+    pub fn with_min_volume_vs_avg(mut self, ratio: Option<f64>) -> Self {
+        self.min_volume_vs_avg = ratio;
+        self
+    }
+
+    pub fn with_max_pct_from_low(mut self, pct: Option<f64>) -> Self {
+        self.max_pct_from_low = pct;
+        self
+    }
+
+    pub fn with_min_pct_from_high(mut self, pct: Option<f64>) -> Self {
+        self.min_pct_from_high = pct;
+        self
+    }
+
+    pub fn with_min_rs_rank(mut self, rank: Option<f64>) -> Self {
+        self.min_rs_rank = rank;
+        self
+    }
+
+    pub fn with_max_rsi_sector_zscore(mut self, zscore: Option<f64>) -> Self {
+        self.max_rsi_sector_zscore = zscore;
+        self
+    }
+
+    pub fn with_min_adx(mut self, adx: Option<f64>) -> Self {
+        self.min_adx = adx;
+        self
+    }
+
+    pub fn with_exclude_split_within_days(mut self, days: Option<u32>) -> Self {
+        self.exclude_split_within_days = days;
+        self
+    }
+
+    pub fn with_max_repaired_bar_ratio(mut self, ratio: Option<f64>) -> Self {
+        self.max_repaired_bar_ratio = ratio;
+        self
+    }
+
+    pub fn with_recent_crossovers_only(mut self, only: bool) -> Self {
+        self.recent_crossovers_only = only;
+        self
+    }
+
+    pub fn with_has_note(mut self, has_note: bool) -> Self {
+        self.has_note = has_note;
+        self
+    }
+
+    pub fn with_opportunity_states(mut self, states: Vec<OpportunityState>) -> Self {
+        self.opportunity_states = Some(states);
+        self
+    }
+
+    pub fn with_opportunity_within_hours(mut self, hours: u32) -> Self {
+        self.opportunity_within_hours = Some(hours);
+        self
+    }
+
+    /// Keep results whose custom indicator `name` lies within `min..=max`
+    pub fn with_custom_indicator_range(mut self, name: &str, min: Option<f64>, max: Option<f64>) -> Self {
+        self.custom_indicator_ranges.insert(name.to_string(), (min, max));
+        self
+    }
+
+    pub fn with_min_history_bars(mut self, bars: Option<usize>) -> Self {
+        self.min_history_bars = bars;
+        self
+    }
+
+    pub fn with_require_complete_data(mut self, require: bool) -> Self {
+        self.require_complete_data = require;
+        self
+    }
+
+    pub fn with_exclude_adrs(mut self, exclude: bool) -> Self {
+        self.exclude_adrs = exclude;
+        self
+    }
+
+    pub fn with_adaptive_rsi(mut self, adaptive: bool) -> Self {
+        self.adaptive_rsi = adaptive;
+        self
+    }
+}
+
+impl StockFilter {
+    /// Check that ranges and thresholds are internally consistent
+    pub fn validate(&self) -> Result<(), String> {
+        fn check_range<T: PartialOrd + std::fmt::Display>(name: &str, min: &Option<T>, max: &Option<T>) -> Result<(), String> {
+            if let (Some(min), Some(max)) = (min, max) {
+                if min > max {
+                    return Err(format!("min_{} ({}) is greater than max_{} ({})", name, min, name, max));
+                }
+            }
+            Ok(())
+        }
+
+        check_range("market_cap", &self.min_market_cap, &self.max_market_cap)?;
+        check_range("price", &self.min_price, &self.max_price)?;
+        check_range("volume", &self.min_volume, &self.max_volume)?;
+        check_range("pct_change", &self.min_pct_change, &self.max_pct_change)?;
+        check_range("rsi", &self.min_rsi, &self.max_rsi)?;
+        check_range("ipo_year", &self.min_ipo_year, &self.max_ipo_year)?;
+        check_range("beta", &self.min_beta, &self.max_beta)?;
+
+        if let Some(pct) = self.max_pct_above_52_week_low {
+            if pct < 0.0 {
+                return Err(format!("max_pct_above_52_week_low must not be negative (got {})", pct));
+            }
+        }
+        if let Some(ratio) = self.min_volume_vs_avg {
+            if ratio < 0.0 {
+                return Err(format!("min_volume_vs_avg must not be negative (got {})", ratio));
+            }
+        }
+        for (name, value) in [("max_pct_from_low", self.max_pct_from_low), ("min_pct_from_high", self.min_pct_from_high)] {
+            if let Some(pct) = value {
+                if pct < 0.0 {
+                    return Err(format!("{} must not be negative (got {})", name, pct));
+                }
+            }
+        }
+
+        if let Some(zscore) = self.max_rsi_sector_zscore {
+            if !zscore.is_finite() {
+                return Err(format!("max_rsi_sector_zscore must be a finite number (got {})", zscore));
+            }
+        }
+
+        for (name, (min, max)) in &self.custom_indicator_ranges {
+            validate_name(name).map_err(|e| format!("custom_indicator_ranges: {}", e))?;
+            if min.iter().chain(max).any(|bound| !bound.is_finite()) {
+                return Err(format!("custom_indicator_ranges.{} bounds must be finite numbers", name));
+            }
+            if let (Some(min), Some(max)) = (min, max) {
+                if min > max {
+                    return Err(format!("custom_indicator_ranges.{}: min ({}) is greater than max ({})", name, min, max));
+                }
+            }
+        }
+
+        if let Some(ratio) = self.max_repaired_bar_ratio {
+            if !(0.0..=1.0).contains(&ratio) {
+                return Err(format!("max_repaired_bar_ratio must be between 0 and 1 (got {})", ratio));
+            }
+        }
+
+        for (name, value) in [
+            ("min_rsi", self.min_rsi),
+            ("max_rsi", self.max_rsi),
+            ("oversold_rsi_threshold", self.oversold_rsi_threshold),
+            ("overbought_rsi_threshold", self.overbought_rsi_threshold),
+            ("min_rs_rank", self.min_rs_rank),
+            ("min_adx", self.min_adx),
+        ] {
+            if let Some(v) = value {
+                if !(0.0..=100.0).contains(&v) {
+                    return Err(format!("{} must be between 0 and 100 (got {})", name, v));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A union of filters: a ticker passes when it passes any member filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeFilter {
+    pub any_of: Vec<StockFilter>,
+}
+
+/// Either a single `StockFilter` or a `CompositeFilter`.
+///
+/// Serialized untagged, so both shapes are accepted wherever a filter is.
+/// Deserialization picks the shape by the presence of an `any_of` key so a
+/// malformed composite is reported as an error instead of silently becoming
+/// an empty (match-everything) single filter.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+pub enum FilterSpec {
+    Composite(CompositeFilter),
+    Single(StockFilter),
+}
+
+impl<'de> Deserialize<'de> for FilterSpec {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let value = serde_json::Value::deserialize(deserializer)?;
+        if value.get("any_of").is_some() {
+            CompositeFilter::deserialize(value)
+                .map(FilterSpec::Composite)
+                .map_err(D::Error::custom)
+        } else {
+            StockFilter::deserialize(value)
+                .map(FilterSpec::Single)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+impl From<StockFilter> for FilterSpec {
+    fn from(filter: StockFilter) -> Self {
+        FilterSpec::Single(filter)
+    }
+}
+
+impl FilterSpec {
+    /// The member filters (a single filter is a one-member union)
+    pub fn members(&self) -> &[StockFilter] {
+        match self {
+            FilterSpec::Composite(composite) => &composite.any_of,
+            FilterSpec::Single(filter) => std::slice::from_ref(filter),
+        }
+    }
+
+    /// Validate every member filter
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            FilterSpec::Single(filter) => filter.validate(),
+            FilterSpec::Composite(composite) => {
+                if composite.any_of.is_empty() {
+                    return Err("any_of must contain at least one filter".to_string());
+                }
+                for (i, filter) in composite.any_of.iter().enumerate() {
+                    filter.validate().map_err(|e| format!("any_of[{}]: {}", i, e))?;
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -136,9 +785,25 @@ struct NasdaqApiResponse {
     data: NasdaqData,
 }
 
+/// The default screener nests rows under `table`; the `download=true`
+/// variant puts them directly under `data`
 #[derive(Debug, Deserialize)]
 struct NasdaqData {
-    table: NasdaqTable,
+    table: Option<NasdaqTable>,
+    rows: Option<Vec<NasdaqRow>>,
+    /// Rows in the whole listing, not just this page
+    #[serde(default, rename = "totalrecords", deserialize_with = "record_count")]
+    total_records: Option<usize>,
+}
+
+impl NasdaqData {
+    fn into_rows(self) -> Vec<NasdaqRow> {
+        match (self.rows, self.table) {
+            (Some(rows), _) => rows,
+            (None, Some(table)) => table.rows,
+            (None, None) => Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -164,9 +829,340 @@ struct NasdaqRow {
     volume: Option<String>,
     sector: Option<String>,
     industry: Option<String>,
+    exchange: Option<String>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    beta: Option<String>,
+    #[serde(default, alias = "fiftyTwoWeekHigh", alias = "high52Week", deserialize_with = "string_or_number")]
+    fifty_two_week_high: Option<String>,
+    #[serde(default, alias = "fiftyTwoWeekLow", alias = "low52Week", deserialize_with = "string_or_number")]
+    fifty_two_week_low: Option<String>,
+}
+
+/// Extended screener columns arrive either as display strings or bare numbers
+fn string_or_number<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(match value {
+        Some(serde_json::Value::String(s)) if !s.trim().is_empty() => Some(s),
+        Some(serde_json::Value::Number(n)) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+/// `totalrecords` is a number, but tolerate it arriving as a display string
+fn record_count<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<usize>, D::Error> {
+    Ok(string_or_number(deserializer)?.and_then(|count| count.replace(',', "").trim().parse().ok()))
+}
+
+/// Default rows requested per screener page
+pub const DEFAULT_NASDAQ_PAGE_SIZE: usize = 1000;
+/// Default fraction of the reported total an exchange's assembled listing
+/// may differ by
+pub const DEFAULT_NASDAQ_TOTAL_TOLERANCE: f64 = 0.01;
+const NASDAQ_BASE_URL: &str = "https://api.nasdaq.com";
+
+/// Pages through the Nasdaq stock screener. A single large response can
+/// come back silently truncated, so each exchange is requested `page_size`
+/// rows at a time until a short page, and the rows received are checked
+/// against the total Nasdaq reports.
+#[derive(Clone)]
+pub struct NasdaqScreener {
+    client: HttpClient,
+    base_url: String,
+    page_size: usize,
+    retry_policy: RetryPolicy,
+    total_tolerance: f64,
+    clock: SharedClock,
+}
+
+impl Default for NasdaqScreener {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NasdaqScreener {
+    pub fn new() -> Self {
+        Self {
+            client: HttpClient::default(),
+            base_url: NASDAQ_BASE_URL.to_string(),
+            page_size: DEFAULT_NASDAQ_PAGE_SIZE,
+            retry_policy: RetryPolicy::default(),
+            total_tolerance: DEFAULT_NASDAQ_TOTAL_TOLERANCE,
+            clock: system_clock(),
+        }
+    }
+
+    /// Point the screener at a different host (used by tests)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Send requests through `client`, e.g. one with a proxy
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Rows requested per page
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// How a failed page is retried; the pages already received are kept
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Time retry delays on `clock` rather than the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Fraction of the reported total an exchange's listing may be short
+    /// by before the exchange counts as failed
+    pub fn with_total_tolerance(mut self, tolerance: f64) -> Self {
+        self.total_tolerance = tolerance.max(0.0);
+        self
+    }
+
+    /// Up to `count` tickers per exchange across NASDAQ, NYSE and AMEX,
+    /// keeping the first listing of a symbol on more than one; a `count`
+    /// of 0 means no limit
+    pub async fn fetch_tickers(&self, count: usize) -> Result<Vec<TickerInfo>> {
+        let mut listings = Vec::new();
+        let mut first_error = None;
+        for exchange in Exchange::ALL {
+            match self.fetch_exchange(exchange, count).await {
+                Ok(tickers) => listings.push(tickers),
+                // The other exchanges would be refused too
+                Err(e @ AnalyzerError::RateLimited { .. }) => return Err(e),
+                Err(e) => {
+                    tracing::warn!("Failed to fetch {} tickers: {}", exchange.as_str(), e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        if let (true, Some(e)) = (listings.is_empty(), first_error) {
+            return Err(e);
+        }
+
+        let mut tickers = StockAnalyzer::merge_listings(listings);
+        if count > 0 {
+            tickers.truncate(count);
+        }
+
+        let message = if count == 0 { 
+            " (no limit)".to_string() 
+        } else { 
+            format!(" (requested: {})", count) 
+        };
+        tracing::info!("📊 Fetched {} tickers from Nasdaq API{}", tickers.len(), message);
+        Ok(tickers)
+    }
+
+    /// One exchange's listing, page by page. Tickers whose row has no
+    /// exchange column get the requested one. A listing that ends well
+    /// short of the reported total is an error rather than a partial
+    /// list, since the missing symbols would otherwise look delisted.
+    async fn fetch_exchange(&self, exchange: Exchange, count: usize) -> Result<Vec<TickerInfo>> {
+        let mut seen = HashSet::new();
+        let mut rows = Vec::new();
+        let mut received = 0;
+        let mut total = None;
+        let complete = loop {
+            if count > 0 && received >= count {
+                break false;
+            }
+            let limit = if count == 0 { self.page_size } else { self.page_size.min(count - received) };
+            let offset = received;
+            let page = self
+                .retry_policy
+                .run(self.clock.as_ref(), |_| self.fetch_page(exchange, offset, limit))
+                .await?;
+            total = page.data.total_records.or(total);
+
+            let page_rows = page.data.into_rows();
+            let page_len = page_rows.len();
+            let unique_before = rows.len();
+            received += page_len;
+            // Rows shift between pages when the listing changes mid-fetch
+            rows.extend(page_rows.into_iter().filter(|row| seen.insert(normalize_symbol(&row.symbol))));
+
+            if page_len < limit || total.is_some_and(|total| received >= total) {
+                break true;
+            }
+            if rows.len() == unique_before {
+                tracing::warn!("{} page at offset {} only repeated earlier rows; stopping", exchange.as_str(), offset);
+                break true;
+            }
+        };
+        if complete {
+            self.check_total(exchange, received, total)?;
+        }
+
+        Ok(StockAnalyzer::tickers_from_rows(rows)
+            .into_iter()
+            .map(|mut ticker| {
+                ticker.exchange.get_or_insert_with(|| exchange.as_str().to_string());
+                ticker
+            })
+            .collect())
+    }
+
+    async fn fetch_page(&self, exchange: Exchange, offset: usize, limit: usize) -> Result<NasdaqApiResponse> {
+        // download=true returns the extended column set (exchange, beta, 52-week range)
+        let url = format!(
+            "{}/api/screener/stocks?tableonly=true&download=true&exchange={}&limit={}&offset={}",
+            self.base_url,
+            exchange.as_str(),
+            limit,
+            offset
+        );
+
+        let response = self.client.get(url).send().await?;
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok()?.parse().ok())
+                .map(std::time::Duration::from_secs);
+            return Err(AnalyzerError::RateLimited { retry_after });
+        }
+        if !response.status().is_success() {
+            return Err(AnalyzerError::Upstream(format!("Nasdaq API returned {}", response.status())));
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Compare the rows received with the total Nasdaq reported for the
+    /// exchange, when it reported one
+    fn check_total(&self, exchange: Exchange, received: usize, total: Option<usize>) -> Result<()> {
+        let Some(total) = total else {
+            tracing::debug!("Nasdaq reported no total for {}; received {} rows", exchange.as_str(), received);
+            return Ok(());
+        };
+        let difference = received.abs_diff(total);
+        if difference as f64 <= total as f64 * self.total_tolerance {
+            if difference > 0 {
+                tracing::debug!("Received {} {} rows of {} reported", received, exchange.as_str(), total);
+            }
+            return Ok(());
+        }
+
+        let message = format!("received {} {} rows but Nasdaq reported {}", received, exchange.as_str(), total);
+        if received < total {
+            return Err(AnalyzerError::Upstream(message));
+        }
+        tracing::warn!("{}", message);
+        Ok(())
+    }
 }
 
-#[derive(Debug, Clone)]
+/// Numeric ticker fields gathered once so several filters can be evaluated
+/// cheaply. A field is `None` when Nasdaq sent nothing, a placeholder like
+/// "N/A", or a value that could not be parsed.
+struct ParsedTickerInfo<'a> {
+    ticker: &'a TickerInfo,
+    market_cap: Option<f64>,
+    price: Option<f64>,
+    volume: Option<u64>,
+    pct_change: Option<f64>,
+    ipo_year: Option<i32>,
+    beta: Option<f64>,
+    pct_above_52_week_low: Option<f64>,
+}
+
+impl<'a> ParsedTickerInfo<'a> {
+    fn new(ticker: &'a TickerInfo) -> Self {
+        Self {
+            ticker,
+            market_cap: ticker.market_cap_f64,
+            price: ticker.last_sale_f64,
+            volume: ticker.volume_u64,
+            pct_change: ticker.pct_change_f64,
+            ipo_year: parse_field(ticker.ipo_year.as_deref(), str::parse::<i32>),
+            beta: parse_field(ticker.beta.as_deref(), str::parse::<f64>),
+            pct_above_52_week_low: Self::pct_above_52_week_low(ticker),
+        }
+    }
+
+    /// How far the last sale sits above the 52-week low, in percent
+    fn pct_above_52_week_low(ticker: &TickerInfo) -> Option<f64> {
+        let price = ticker.last_sale_f64?;
+        match parse_field(ticker.fifty_two_week_low.as_deref(), parse_price) {
+            Some(low) if low > 0.0 => Some((price - low) / low * 100.0),
+            _ => None,
+        }
+    }
+
+    /// Whether the ticker's country, normalized when the listing was
+    /// fetched, is wanted by the `countries` filter
+    fn matches_country(&self, allowed: &Option<Vec<String>>) -> bool {
+        let Some(allowed) = allowed else {
+            return true;
+        };
+        let country = match self.ticker.country_normalized {
+            Some(ref country) => Some((country.clone(), self.ticker.region)),
+            None => self.ticker.country.as_deref().and_then(normalize_country),
+        };
+        let Some((country, region)) = country else {
+            return false;
+        };
+        allowed.iter().any(|wanted| country_matches(&country, region, wanted))
+    }
+
+    /// Whether every field `require_complete_data` asks for is known
+    fn is_complete(&self) -> bool {
+        self.market_cap.is_some() && self.price.is_some() && self.volume.is_some() && self.pct_change.is_some()
+    }
+
+    /// Check the basic (non-RSI) filters. A field that is missing or
+    /// unparseable fails any filter set on it and is ignored otherwise.
+    fn passes(&self, filter: &StockFilter) -> bool {
+        fn within<T: PartialOrd + Copy>(value: Option<T>, min: Option<T>, max: Option<T>) -> bool {
+            match value {
+                None => min.is_none() && max.is_none(),
+                Some(v) => min.is_none_or(|min| v >= min) && max.is_none_or(|max| v <= max),
+            }
+        }
+
+        if filter.require_complete_data && !self.is_complete() {
+            return false;
+        }
+        if filter.exclude_adrs && self.ticker.is_adr {
+            return false;
+        }
+
+        fn matches_any(value: &Option<String>, allowed: &Option<Vec<String>>) -> bool {
+            match (allowed, value) {
+                (None, _) => true,
+                (Some(_), None) => false,
+                (Some(allowed), Some(value)) => {
+                    let value = value.to_lowercase();
+                    allowed.iter().any(|a| value.contains(&a.to_lowercase()))
+                }
+            }
+        }
+
+        within(self.market_cap, filter.min_market_cap, filter.max_market_cap)
+            && within(self.price, filter.min_price, filter.max_price)
+            && within(self.volume, filter.min_volume, filter.max_volume)
+            && within(self.pct_change, filter.min_pct_change, filter.max_pct_change)
+            && matches_any(&self.ticker.sector, &filter.sectors)
+            && self.matches_country(&filter.countries)
+            && matches_any(&self.ticker.industry, &filter.industries)
+            && within(self.ipo_year, filter.min_ipo_year, filter.max_ipo_year)
+            && matches_any(&self.ticker.exchange, &filter.exchanges)
+            && within(self.beta, filter.min_beta, filter.max_beta)
+            && within(self.pct_above_52_week_low, None, filter.max_pct_above_52_week_low)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StockData {
     pub symbol: String,
     pub timestamp: DateTime<Utc>,
@@ -175,43 +1171,615 @@ pub struct StockData {
     pub low: f64,
     pub close: f64,
     pub volume: u64,
+    /// Close adjusted for later splits and dividends, when the provider
+    /// reports one or the series has been adjusted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adjusted_close: Option<f64>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TechnicalIndicators {
+    /// Same as `moving_averages["sma_20"]`; `None` when that average isn't configured
     pub sma_20: Option<f64>,
+    /// Same as `moving_averages["sma_50"]`; `None` when that average isn't configured
     pub sma_50: Option<f64>,
     pub rsi: Option<f64>,
     pub macd: Option<(f64, f64, f64)>, // (macd, signal, histogram)
+    pub bollinger: Option<BollingerBandsValue>, // (20, 2)
+    pub stochastic: Option<StochasticValue>, // (14, 3)
+    pub atr: Option<f64>, // 14
+    pub cci: Option<f64>, // 20
+    pub obv: Option<f64>,
+    pub vwap: Option<f64>, // 20
+    /// Volume as a multiple of the previous 20 candles' average
+    pub volume_vs_avg: Option<f64>,
+    /// Every configured moving average that has a value, keyed like "sma_20" or "ema_9"
+    pub moving_averages: HashMap<String, f64>,
+    /// Highest high over `IndicatorConfig::range_window` candles (252 by default)
+    pub high_52w: Option<f64>,
+    /// Lowest low over the same window
+    pub low_52w: Option<f64>,
+    /// Percent the close is below `high_52w`
+    pub pct_from_high: Option<f64>,
+    /// Percent the close is above `low_52w`
+    pub pct_from_low: Option<f64>,
+    /// False while fewer candles than the range window exist, in which case
+    /// the high and low cover only the available history
+    pub window_complete: bool,
+    pub adx: Option<AdxValue>, // 14
+    /// User-defined indicators from `/api/custom-indicators`, keyed by
+    /// name; set on the latest candle only, leaving out any that cannot be
+    /// computed there
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom: HashMap<String, f64>,
+    /// Candles of history up to and including this one that the
+    /// indicators were computed from
+    #[serde(default)]
+    pub bars_used: usize,
+}
+
+/// Which moving averages to compute, the RSI and MACD periods and the
+/// high/low range window. The default reproduces the fixed SMA(20), SMA(50),
+/// RSI(14) and MACD(12, 26, 9) set with a 252-candle range.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndicatorConfig {
+    pub sma_periods: Vec<usize>,
+    pub ema_periods: Vec<usize>,
+    pub rsi_period: usize,
+    pub macd_fast: usize,
+    pub macd_slow: usize,
+    pub macd_signal: usize,
+    pub range_window: usize,
+}
+
+impl Default for IndicatorConfig {
+    fn default() -> Self {
+        Self {
+            sma_periods: vec![20, 50],
+            ema_periods: Vec::new(),
+            rsi_period: 14,
+            macd_fast: 12,
+            macd_slow: 26,
+            macd_signal: 9,
+            range_window: DEFAULT_RANGE_WINDOW,
+        }
+    }
+}
+
+impl IndicatorConfig {
+    pub fn with_sma_periods(mut self, periods: Vec<usize>) -> Self {
+        self.sma_periods = periods;
+        self
+    }
+
+    pub fn with_ema_periods(mut self, periods: Vec<usize>) -> Self {
+        self.ema_periods = periods;
+        self
+    }
+
+    pub fn with_rsi_period(mut self, period: usize) -> Self {
+        self.rsi_period = period;
+        self
+    }
+
+    pub fn with_macd(mut self, fast: usize, slow: usize, signal: usize) -> Self {
+        self.macd_fast = fast;
+        self.macd_slow = slow;
+        self.macd_signal = signal;
+        self
+    }
+
+    pub fn with_range_window(mut self, window: usize) -> Self {
+        self.range_window = window;
+        self
+    }
+
+    /// Check that every period is usable
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(period) = self.sma_periods.iter().chain(&self.ema_periods).find(|period| **period == 0) {
+            return Err(format!("moving average periods must be positive, got {}", period));
+        }
+        if self.rsi_period == 0 {
+            return Err("rsi_period must be positive".to_string());
+        }
+        if self.macd_fast == 0 || self.macd_signal == 0 {
+            return Err("MACD periods must be positive".to_string());
+        }
+        if self.macd_fast >= self.macd_slow {
+            return Err(format!(
+                "macd_fast ({}) must be shorter than macd_slow ({})",
+                self.macd_fast, self.macd_slow
+            ));
+        }
+        if self.range_window == 0 {
+            return Err("range_window must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Which part of a symbol's history to analyze. The default is all daily
+/// history; `end` is inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HistoryRange {
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+    #[serde(default)]
+    pub interval: Interval,
 }
 
+impl HistoryRange {
+    pub fn new(start: Option<NaiveDate>, end: Option<NaiveDate>, interval: Interval) -> Self {
+        Self { start, end, interval }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let (Some(start), Some(end)) = (self.start, self.end) {
+            if start > end {
+                return Err(format!("start_date ({}) is after end_date ({})", start, end));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the range starts no earlier than bars of its interval are
+    /// served at `now`
+    pub fn validate_lookback(&self, now: DateTime<Utc>) -> Result<(), String> {
+        match (self.start, self.interval.earliest_start(now)) {
+            (Some(start), Some(earliest)) if start < earliest => Err(format!(
+                "{} bars only go back {} days; start_date must be on or after {}",
+                self.interval,
+                self.interval.max_lookback_days().unwrap_or_default(),
+                earliest
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fetch bounds: midnight UTC on `start` up to the end of `end`.
+    /// Intraday ranges start no earlier than their interval is served.
+    pub fn bounds(&self, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let midnight = |date: NaiveDate| date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let start = self.start.max(self.interval.earliest_start(now)).map(midnight).unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
+        let end = self
+            .end
+            .and_then(|end| end.succ_opt())
+            .map(midnight)
+            .map_or(now, |end| end.min(now));
+        (start, end)
+    }
+
+    /// Quote cache key; the default range keeps the plain per-symbol key
+    pub fn cache_key(&self, symbol: &str) -> String {
+        if *self == Self::default() {
+            return format!("stock_data_{}", symbol);
+        }
+        let date = |date: Option<NaiveDate>| date.map_or_else(|| "-".to_string(), |d| d.to_string());
+        format!("stock_data_{}_{}_{}_{}", symbol, self.interval, date(self.start), date(self.end))
+    }
+}
+
+/// Candles over which `indicator_signals` looks for OBV diverging from price
+pub const OBV_DIVERGENCE_WINDOW: usize = 20;
+
+/// Symbols per request in `get_latest_quotes_batch` unless configured otherwise
+pub const DEFAULT_QUOTE_BATCH_SIZE: usize = 50;
+
 pub struct StockAnalyzer {
-    provider: yahoo::YahooConnector,
+    provider: Arc<dyn MarketDataProvider>,
+    batch_quotes: Arc<dyn BatchQuoteSource>,
+    quote_batch_size: usize,
     indicators: HashMap<String, IndicatorSet>,
     cache: Option<crate::cache::CacheManager>,
+    database: Option<Arc<crate::database::Database>>,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    bad_bar_policy: BadBarPolicy,
+    adjustment_policy: AdjustmentPolicy,
+    custom_indicators: SharedCustomIndicators,
+    symbol_universe: Option<Arc<SymbolUniverse>>,
+    history_requirements: HistoryRequirements,
 }
 
 struct IndicatorSet {
-    sma_20: SimpleMovingAverage,
-    sma_50: SimpleMovingAverage,
+    /// Keyed like "sma_20" or "ema_9", in configuration order
+    moving_averages: Vec<(String, MovingAverage)>,
     rsi: CustomRSI,
     macd: MovingAverageConvergenceDivergence,
+    bollinger: BollingerBands,
+    stochastic: StochasticOscillator,
+    atr: AverageTrueRange,
+    cci: CommodityChannelIndex,
+    obv: OnBalanceVolume,
+    vwap: Vwap,
+    relative_volume: RelativeVolume,
+    range: RollingRange,
+    adx: AverageDirectionalIndex,
+    /// Candles fed since the last reset
+    bars: usize,
+}
+
+enum MovingAverage {
+    Simple(SimpleMovingAverage),
+    Exponential(ExponentialMovingAverage),
+}
+
+impl MovingAverage {
+    fn next(&mut self, close: f64) -> Option<f64> {
+        match self {
+            MovingAverage::Simple(sma) => sma.next(close),
+            MovingAverage::Exponential(ema) => ema.next(close),
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            MovingAverage::Simple(sma) => sma.reset(),
+            MovingAverage::Exponential(ema) => ema.reset(),
+        }
+    }
+
+    fn snapshot(&self) -> MovingAverageState {
+        match self {
+            MovingAverage::Simple(sma) => MovingAverageState::Simple(sma.snapshot()),
+            MovingAverage::Exponential(ema) => MovingAverageState::Exponential(ema.snapshot()),
+        }
+    }
+
+    fn restore(&mut self, state: &MovingAverageState) {
+        match (self, state) {
+            (MovingAverage::Simple(sma), MovingAverageState::Simple(state)) => sma.restore(state),
+            (MovingAverage::Exponential(ema), MovingAverageState::Exponential(state)) => ema.restore(state),
+            _ => unreachable!("snapshot taken with different moving average settings"),
+        }
+    }
+}
+
+/// Compute the indicator stack over a series from a fresh state.
+///
+/// This is the pure entry point behind `StockAnalyzer::calculate_indicators`:
+/// no caching, no per-symbol state, and the output depends only on `stock_data`.
+pub fn compute_indicators(stock_data: &[StockData]) -> Vec<TechnicalIndicators> {
+    let mut indicators = IndicatorSet::new();
+    stock_data.iter().map(|data| indicators.next(data)).collect()
+}
+
+/// `compute_indicators` with configurable moving averages, RSI and MACD
+/// periods and range window
+pub fn compute_indicators_with_config(
+    stock_data: &[StockData],
+    config: &IndicatorConfig,
+) -> Result<Vec<TechnicalIndicators>> {
+    let mut indicators = IndicatorSet::with_config(config)?;
+    Ok(stock_data.iter().map(|data| indicators.next(data)).collect())
+}
+
+/// Fingerprint of the periods used by `IndicatorSet`; retained state is only
+/// reused for the same settings
+const INDICATOR_SETTINGS: &str = "sma20-sma50-rsi14-macd12.26.9-bb20.2-stoch14.3-atr14-cci20-obv-vwap20-relvol20-range252-adx14-bars";
+
+fn indicator_state_key(symbol: &str) -> String {
+    format!("indicator_state_{}_{}", symbol, INDICATOR_SETTINGS)
+}
+
+impl IndicatorSet {
+    fn new() -> Self {
+        Self::with_config(&IndicatorConfig::default()).unwrap()
+    }
+
+    fn with_config(config: &IndicatorConfig) -> Result<Self> {
+        config.validate().map_err(AnalyzerError::InvalidRequest)?;
+
+        let mut moving_averages = Vec::new();
+        for &period in &config.sma_periods {
+            moving_averages.push((format!("sma_{}", period), MovingAverage::Simple(SimpleMovingAverage::new(period)?)));
+        }
+        for &period in &config.ema_periods {
+            moving_averages.push((format!("ema_{}", period), MovingAverage::Exponential(ExponentialMovingAverage::new(period)?)));
+        }
+
+        Ok(Self {
+            moving_averages,
+            rsi: CustomRSI::new(config.rsi_period),
+            macd: MovingAverageConvergenceDivergence::new(config.macd_fast, config.macd_slow, config.macd_signal)?,
+            bollinger: BollingerBands::new(20, 2.0),
+            stochastic: StochasticOscillator::new(14, 3),
+            atr: AverageTrueRange::new(14),
+            cci: CommodityChannelIndex::new(20, 0.015),
+            obv: OnBalanceVolume::new(),
+            vwap: Vwap::new(20),
+            relative_volume: RelativeVolume::new(20),
+            range: RollingRange::new(config.range_window),
+            adx: AverageDirectionalIndex::new(14),
+            bars: 0,
+        })
+    }
+
+    fn reset(&mut self) {
+        for (_, average) in &mut self.moving_averages {
+            average.reset();
+        }
+        self.rsi.reset();
+        self.macd.reset();
+        self.bollinger.reset();
+        self.stochastic.reset();
+        self.atr.reset();
+        self.cci.reset();
+        self.obv.reset();
+        self.vwap.reset();
+        self.relative_volume.reset();
+        self.range.reset();
+        self.adx.reset();
+        self.bars = 0;
+    }
+
+    /// Feed one candle. SMA, RSI and MACD only use the close; the band,
+    /// range, oscillator and volume indicators need the full bar.
+    fn next(&mut self, data: &StockData) -> TechnicalIndicators {
+        let moving_averages: HashMap<String, f64> = self
+            .moving_averages
+            .iter_mut()
+            .filter_map(|(key, average)| average.next(data.close).map(|value| (key.clone(), value)))
+            .collect();
+        let rsi = self.rsi.next(data.close);
+        let macd = self.macd.next(data.close);
+        let range = self.range.next(data);
+        self.bars += 1;
+
+        TechnicalIndicators {
+            sma_20: moving_averages.get("sma_20").copied(),
+            sma_50: moving_averages.get("sma_50").copied(),
+            rsi,
+            macd: macd.map(|m| (m.macd, m.signal, m.histogram)),
+            bollinger: self.bollinger.next(data),
+            stochastic: self.stochastic.next(data),
+            atr: self.atr.next(data),
+            cci: self.cci.next(data),
+            obv: self.obv.next(data),
+            vwap: self.vwap.next(data),
+            volume_vs_avg: self.relative_volume.next(data),
+            moving_averages,
+            high_52w: range.map(|r| r.high),
+            low_52w: range.map(|r| r.low),
+            pct_from_high: range.map(|r| r.pct_from_high),
+            pct_from_low: range.map(|r| r.pct_from_low),
+            window_complete: range.is_some_and(|r| r.window_complete),
+            adx: self.adx.next(data),
+            custom: HashMap::new(),
+            bars_used: self.bars,
+        }
+    }
+
+    fn snapshot(&self, fingerprint: SeriesFingerprint, outputs: Vec<TechnicalIndicators>) -> IndicatorSnapshot {
+        IndicatorSnapshot {
+            fingerprint,
+            moving_averages: self.moving_averages.iter().map(|(_, average)| average.snapshot()).collect(),
+            rsi: self.rsi.snapshot(),
+            macd: self.macd.snapshot(),
+            bollinger: self.bollinger.snapshot(),
+            stochastic: self.stochastic.snapshot(),
+            atr: self.atr.snapshot(),
+            cci: self.cci.snapshot(),
+            obv: self.obv.snapshot(),
+            vwap: self.vwap.snapshot(),
+            relative_volume: self.relative_volume.snapshot(),
+            range: self.range.snapshot(),
+            adx: self.adx.snapshot(),
+            outputs: Arc::new(outputs),
+        }
+    }
+
+    fn restore(&mut self, snapshot: &IndicatorSnapshot) {
+        for ((_, average), state) in self.moving_averages.iter_mut().zip(&snapshot.moving_averages) {
+            average.restore(state);
+        }
+        self.rsi.restore(&snapshot.rsi);
+        self.macd.restore(&snapshot.macd);
+        self.bollinger.restore(&snapshot.bollinger);
+        self.stochastic.restore(&snapshot.stochastic);
+        self.atr.restore(&snapshot.atr);
+        self.cci.restore(&snapshot.cci);
+        self.obv.restore(&snapshot.obv);
+        self.vwap.restore(&snapshot.vwap);
+        self.relative_volume.restore(&snapshot.relative_volume);
+        self.range.restore(&snapshot.range);
+        self.adx.restore(&snapshot.adx);
+        self.bars = snapshot.fingerprint.len;
+    }
+}
+
+impl Default for StockAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds a `StockAnalyzer` whose upstream requests use custom HTTP
+/// settings; anything left unset keeps `HttpClientConfig`'s default
+#[derive(Default)]
+pub struct StockAnalyzerBuilder {
+    http: HttpClientConfig,
+    cache: Option<crate::cache::CacheManager>,
+}
+
+impl StockAnalyzerBuilder {
+    /// Start from a whole set of HTTP settings, such as `AppConfig::http`
+    pub fn http_config(mut self, config: HttpClientConfig) -> Self {
+        self.http = config;
+        self
+    }
+
+    /// Route every upstream request through the proxy at `url`
+    pub fn proxy(mut self, url: &str) -> Self {
+        self.http.proxy = Some(url.to_string());
+        self
+    }
+
+    /// Limit both connecting and waiting on a response to `timeout`
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.connect_timeout(timeout).read_timeout(timeout)
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http.connect_timeout_ms = timeout.as_millis() as u64;
+        self
+    }
+
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.http.read_timeout_ms = timeout.as_millis() as u64;
+        self
+    }
+
+    /// User agents rotated request by request
+    pub fn user_agents(mut self, user_agents: Vec<String>) -> Self {
+        self.http.user_agents = user_agents;
+        self
+    }
+
+    /// Send requests for `host` to `address` instead of looking it up
+    pub fn resolve(mut self, host: &str, address: SocketAddr) -> Self {
+        self.http.resolve.insert(host.to_lowercase(), address);
+        self
+    }
+
+    /// Share `cache` and its request limiter, as `new_with_cache` does
+    pub fn cache(mut self, cache: crate::cache::CacheManager) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Fails when the settings are invalid, e.g. an unusable proxy URL
+    pub fn build(self) -> Result<StockAnalyzer> {
+        self.http.validate().map_err(AnalyzerError::InvalidRequest)?;
+        let client = HttpClientFactory::new(self.http).client()?;
+        Ok(StockAnalyzer::with_http_client(client, self.cache))
+    }
 }
 
 impl StockAnalyzer {
     pub fn new() -> Self {
-        Self {
-            provider: yahoo::YahooConnector::new().unwrap(),
-            indicators: HashMap::new(),
-            cache: None,
-        }
+        Self::with_http_client(HttpClient::default(), None)
     }
 
     pub fn new_with_cache(cache: crate::cache::CacheManager) -> Self {
+        Self::with_http_client(HttpClient::default(), Some(cache))
+    }
+
+    /// Configure the HTTP settings (proxy, timeouts, user agents) of a new
+    /// analyzer, e.g.
+    /// `StockAnalyzer::builder().proxy("http://proxy:3128").timeout(Duration::from_secs(5)).build()`
+    pub fn builder() -> StockAnalyzerBuilder {
+        StockAnalyzerBuilder::default()
+    }
+
+    /// An analyzer whose default providers all send requests through `client`
+    fn with_http_client(client: HttpClient, cache: Option<crate::cache::CacheManager>) -> Self {
         Self {
-            provider: yahoo::YahooConnector::new().unwrap(),
+            provider: default_provider_with(client.clone()).unwrap(),
+            batch_quotes: Arc::new(YahooBatchQuotes::new().with_http_client(client)),
+            quote_batch_size: DEFAULT_QUOTE_BATCH_SIZE,
             indicators: HashMap::new(),
-            cache: Some(cache),
+            rate_limiter: cache.as_ref().map(|cache| cache.request_limiter().clone()),
+            cache,
+            database: None,
+            retry_policy: RetryPolicy::default(),
+            bad_bar_policy: BadBarPolicy::default(),
+            adjustment_policy: AdjustmentPolicy::default(),
+            custom_indicators: SharedCustomIndicators::default(),
+            symbol_universe: None,
+            history_requirements: HistoryRequirements::default(),
+        }
+    }
+
+    /// Replace the source of history, quotes and ticker listings (Yahoo with
+    /// Stooq fallback and the Nasdaq screener by default)
+    pub fn with_provider(mut self, provider: Arc<dyn MarketDataProvider>) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    /// Replace the source of batched latest quotes (Yahoo's v7 quote endpoint by default)
+    pub fn with_batch_quote_source(mut self, source: Arc<dyn BatchQuoteSource>) -> Self {
+        self.batch_quotes = source;
+        self
+    }
+
+    /// Symbols per batched quote request
+    pub fn with_quote_batch_size(mut self, size: usize) -> Self {
+        self.quote_batch_size = size.max(1);
+        self
+    }
+
+    /// Keep daily history in `database` so later fetches only request the
+    /// bars that are missing, and indicator state so it outlives a restart
+    pub fn with_database(mut self, database: Arc<crate::database::Database>) -> Self {
+        self.database = Some(database);
+        self
+    }
+
+    /// Share `limiter` across every provider request; analyzers built with a
+    /// cache use the cache's limiter
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Backoff used by the retrying fetches
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// How fetched bars with an unusable close are handled (dropped by default)
+    pub fn with_bad_bar_policy(mut self, policy: BadBarPolicy) -> Self {
+        self.bad_bar_policy = policy;
+        self
+    }
+
+    /// Whether fetched history is adjusted for splits and dividends (the
+    /// default) or served as traded
+    pub fn with_adjustment_policy(mut self, policy: AdjustmentPolicy) -> Self {
+        self.adjustment_policy = policy;
+        self
+    }
+
+    /// User-defined indicators to compute for the last candle of every
+    /// series the `calculate_*` methods are given
+    pub fn with_custom_indicators(mut self, custom_indicators: SharedCustomIndicators) -> Self {
+        self.custom_indicators = custom_indicators;
+        self
+    }
+
+    /// List tickers from `universe` instead of the provider's listing
+    pub fn with_symbol_universe(mut self, universe: Arc<SymbolUniverse>) -> Self {
+        self.symbol_universe = Some(universe);
+        self
+    }
+
+    /// Hold back RSI, SMA and MACD signals until a series is as long as
+    /// `requirements` ask
+    pub fn with_history_requirements(mut self, requirements: HistoryRequirements) -> Self {
+        self.history_requirements = requirements;
+        self
+    }
+
+    /// Set the custom indicators on the last of `outputs`, which belongs to
+    /// the last of `stock_data`
+    fn apply_custom_indicators(&self, stock_data: &[StockData], outputs: &mut [TechnicalIndicators]) {
+        if let Some(latest) = outputs.last_mut() {
+            latest.custom = self.custom_indicators.current().evaluate(stock_data);
+        }
+    }
+
+    /// `series` as the analyzer's `AdjustmentPolicy` has it
+    pub fn adjust(&self, series: QuoteSeries) -> QuoteSeries {
+        match self.adjustment_policy {
+            AdjustmentPolicy::Raw => series,
+            AdjustmentPolicy::Adjusted => QuoteSeries {
+                bars: adjust_bars(&series.bars, &series.events),
+                ..series
+            },
         }
     }
     /**
@@ -219,104 +1787,320 @@ impl StockAnalyzer {
      */
     pub async fn fetch_all_stock_data(&self, symbol: &str) -> Result<Vec<StockData>> {
         return self
-            .fetch_stock_data(symbol, DateTime::<Utc>::UNIX_EPOCH, Utc::now())
+            .fetch_stock_data(symbol, DateTime::<Utc>::UNIX_EPOCH, Utc::now(), Interval::Daily)
             .await;
     }
 
-    /**
-     * Fetches stock data with caching support
-     */
-    pub async fn fetch_stock_data_cached(&self, symbol: &str) -> Result<Vec<StockData>> {
-        let cache_key = format!("stock_data_{}", symbol);
+    /**
+     * Fetches stock data with caching support
+     */
+    pub async fn fetch_stock_data_cached(&self, symbol: &str) -> Result<Vec<StockData>> {
+        Ok(self.fetch_quote_series_cached(symbol).await?.bars)
+    }
+
+    /// Fetch the full daily history with caching, keeping track of which
+    /// provider served it
+    pub async fn fetch_quote_series_cached(&self, symbol: &str) -> Result<QuoteSeries> {
+        self.fetch_quote_series_range_cached(symbol, &HistoryRange::default()).await
+    }
+
+    /// Fetch part of a symbol's history with caching; each range and
+    /// interval is cached separately. The cache keeps the bars as traded;
+    /// they are adjusted on the way out.
+    pub async fn fetch_quote_series_range_cached(&self, symbol: &str, range: &HistoryRange) -> Result<QuoteSeries> {
+        Ok(self.adjust(self.fetch_raw_series_cached(symbol, range).await?))
+    }
+
+    async fn fetch_raw_series_cached(&self, symbol: &str, range: &HistoryRange) -> Result<QuoteSeries> {
+        let cache_key = range.cache_key(symbol);
 
         // Check cache first
         if let Some(ref cache) = self.cache {
-            if let Some(cached_data) = cache.get_stock_data(&cache_key).await {
+            if let Some(cached_series) = cache.get_quote_series(&cache_key).await {
                 tracing::debug!("Using cached stock data for {}", symbol);
-                return Ok(cached_data);
+                return Ok(cached_series);
             }
         }
 
-        // Rate limiting check
-        if let Some(ref cache) = self.cache {
-            if cache.should_rate_limit(symbol, std::time::Duration::from_millis(200)) {
-                return Err(anyhow::anyhow!("Rate limited for symbol: {}", symbol));
+        // Concurrent callers for the same range share one fetch
+        match self.cache {
+            Some(ref cache) => {
+                cache
+                    .coalesce_quote_series(&cache_key, async {
+                        // A fetch that finished since the check above has cached its result
+                        if let Some(cached_series) = cache.get_quote_series(&cache_key).await {
+                            return Ok(cached_series);
+                        }
+
+                        // Rate limiting check
+                        let interval = std::time::Duration::from_millis(200);
+                        if cache.should_rate_limit(symbol, interval) {
+                            return Err(AnalyzerError::RateLimited { retry_after: Some(interval) });
+                        }
+
+                        let series = self.fetch_quote_series_uncached(symbol, range).await?;
+                        cache.cache_quote_series(cache_key.clone(), series.clone()).await;
+                        Ok(series)
+                    })
+                    .await
             }
+            None => self.fetch_quote_series_uncached(symbol, range).await,
         }
+    }
 
-        // Fetch from API
-        let stock_data = self.fetch_all_stock_data(symbol).await?;
+    /// Fetch the full daily history upstream and cache it, replacing cached
+    /// bars even if they are still fresh
+    pub async fn refresh_quote_series_cached(&self, symbol: &str) -> Result<()> {
+        let range = HistoryRange::default();
+        let Some(ref cache) = self.cache else {
+            return Ok(());
+        };
+        let cache_key = range.cache_key(symbol);
+        cache
+            .coalesce_quote_series(&cache_key, async {
+                let series = self.fetch_quote_series_uncached(symbol, &range).await?;
+                cache.cache_quote_series(cache_key.clone(), series.clone()).await;
+                Ok(series)
+            })
+            .await?;
+        Ok(())
+    }
 
-        // Cache the result
-        if let Some(ref cache) = self.cache {
-            cache.cache_stock_data(cache_key, stock_data.clone()).await;
+    /// Fetch from the API, or just the missing tail when history is stored
+    async fn fetch_quote_series_uncached(&self, symbol: &str, range: &HistoryRange) -> Result<QuoteSeries> {
+        match self.database.clone() {
+            Some(database) if *range == HistoryRange::default() => {
+                self.fetch_quote_series_persistent(&database, symbol).await
+            }
+            _ => {
+                let (start, end) = range.bounds(Utc::now());
+                self.fetch_quote_series_with_retry(symbol, start, end, range.interval).await
+            }
+        }
+    }
+
+    /// Full daily history, served from the database where possible.
+    ///
+    /// Only bars from the start of the last stored day onward are requested;
+    /// that day is re-fetched because its bar may have been partial. With
+    /// nothing stored, the whole history is fetched. If the tail request
+    /// fails, the stored history is returned as is.
+    pub async fn fetch_quote_series_persistent(
+        &self,
+        database: &crate::database::Database,
+        symbol: &str,
+    ) -> Result<QuoteSeries> {
+        let now = Utc::now();
+        let stored = database
+            .get_stock_data(symbol, DateTime::<Utc>::UNIX_EPOCH, now)
+            .await
+            .map_err(AnalyzerError::database)?;
+
+        let Some(last) = stored.last() else {
+            let series = self
+                .fetch_quote_series_with_retry(symbol, DateTime::<Utc>::UNIX_EPOCH, now, Interval::Daily)
+                .await?;
+            database.store_stock_data(&series.bars).await.map_err(AnalyzerError::database)?;
+            database.store_corporate_events(symbol, &series.events).await.map_err(AnalyzerError::database)?;
+            return Ok(series);
+        };
+        // Splits and dividends of the stored bars, which the tail fetch doesn't repeat
+        let stored_events = database.get_corporate_events(symbol).await.map_err(AnalyzerError::database)?;
+
+        let tail_start = last.timestamp.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let tail = match self.fetch_quote_series_with_retry(symbol, tail_start, now, Interval::Daily).await {
+            Ok(tail) => tail,
+            Err(e) => {
+                tracing::warn!("Serving stored history for {}; tail fetch failed: {}", symbol, e);
+                return Ok(QuoteSeries {
+                    bars: stored,
+                    source: "database".to_string(),
+                    fetched_at: now,
+                    quality: None,
+                    events: stored_events,
+                });
+            }
+        };
+        let events = merge_events(stored_events, &tail.events);
+        if tail.bars.is_empty() {
+            return Ok(QuoteSeries { bars: stored, events, ..tail });
         }
 
-        Ok(stock_data)
+        database
+            .replace_stock_data_from(symbol, tail_start, &tail.bars)
+            .await
+            .map_err(AnalyzerError::database)?;
+        database.store_corporate_events(symbol, &tail.events).await.map_err(AnalyzerError::database)?;
+        let mut bars: Vec<StockData> = stored.into_iter().filter(|bar| bar.timestamp < tail_start).collect();
+        // Stored bars were sanitized when first fetched
+        let quality = tail.quality.map(|quality| DataQuality {
+            total_bars: quality.total_bars + bars.len(),
+            ..quality
+        });
+        bars.extend(tail.bars);
+        Ok(QuoteSeries { bars, quality, events, ..tail })
+    }
+
+    /// Bars of the given size over a Yahoo range code such as "5d" (e.g.
+    /// `fetch_stock_data_interval("AAPL", "5d", Interval::FifteenMinutes)`).
+    /// Ranges longer than Yahoo serves at that interval are rejected before
+    /// any request is made.
+    pub async fn fetch_stock_data_interval(&self, symbol: &str, range: &str, interval: Interval) -> Result<Vec<StockData>> {
+        interval.validate_range(range).map_err(AnalyzerError::InvalidRequest)?;
+        let _permit = self.acquire_permit().await;
+        let result = self.provider.fetch_range(symbol, range, interval).await;
+        Ok(self.adjust(self.sanitize(symbol, self.observe(result)?)?).bars)
     }
 
-    /// Fetch historical stock data for a given symbol
+    /// Fetch historical stock data for a given symbol, adjusted according
+    /// to the analyzer's `AdjustmentPolicy`
     pub async fn fetch_stock_data(
         &self,
         symbol: &str,
         start: DateTime<Utc>,
         end: DateTime<Utc>,
+        interval: Interval,
     ) -> Result<Vec<StockData>> {
-        // Convert chrono DateTime to time OffsetDateTime
-        let start_time = OffsetDateTime::from_unix_timestamp(start.timestamp())?;
-        let end_time = OffsetDateTime::from_unix_timestamp(end.timestamp())?;
-
-        let response = self
-            .provider
-            .get_quote_history(symbol, start_time, end_time)
-            .await?;
+        Ok(self.adjust(self.fetch_quote_series(symbol, start, end, interval).await?).bars)
+    }
 
-        let mut stock_data = Vec::new();
-        let quotes = response.quotes()?;
+    /// Fetch historical stock data along with the provider that served it
+    /// and the splits and dividends within it. The bars are as traded; see
+    /// `adjust`.
+    pub async fn fetch_quote_series(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: Interval,
+    ) -> Result<QuoteSeries> {
+        let _permit = self.acquire_permit().await;
+        let result = self.provider.fetch_history_interval(symbol, start, end, interval).await;
+        self.sanitize(symbol, self.observe(result)?)
+    }
 
-        for quote in quotes {
-            stock_data.push(StockData {
-                symbol: symbol.to_string(),
-                timestamp: DateTime::from_timestamp(quote.timestamp as i64, 0)
-                    .unwrap_or(Utc::now()),
-                open: quote.open,
-                high: quote.high,
-                low: quote.low,
-                close: quote.close,
-                volume: quote.volume,
-            });
+    /// Apply the analyzer's `BadBarPolicy` and record what was repaired
+    fn sanitize(&self, symbol: &str, series: QuoteSeries) -> Result<QuoteSeries> {
+        let (bars, quality) = crate::data_quality::sanitize_bars(series.bars, self.bad_bar_policy)?;
+        if quality.repaired() > 0 {
+            tracing::debug!("Repaired {} of {} bars for {}", quality.repaired(), quality.total_bars, symbol);
         }
+        Ok(QuoteSeries {
+            bars,
+            quality: Some(quality),
+            ..series
+        })
+    }
+
+    /// `fetch_stock_data`, retrying rate limits and transient failures
+    /// according to the analyzer's `RetryPolicy`
+    pub async fn fetch_stock_data_with_retry(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: Interval,
+    ) -> Result<Vec<StockData>> {
+        Ok(self.adjust(self.fetch_quote_series_with_retry(symbol, start, end, interval).await?).bars)
+    }
 
-        // Sort by timestamp (oldest first)
-        stock_data.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-        Ok(stock_data)
+    /// `fetch_quote_series` with retries. Unknown or delisted symbols fail
+    /// on the first attempt.
+    pub async fn fetch_quote_series_with_retry(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: Interval,
+    ) -> Result<QuoteSeries> {
+        let clock = self
+            .cache
+            .as_ref()
+            .map_or_else(system_clock, |cache| cache.clock().clone());
+        self.retry_policy
+            .run(clock.as_ref(), |_| self.fetch_quote_series(symbol, start, end, interval))
+            .await
     }
 
     /// Get the latest quote for a symbol
     pub async fn get_latest_quote(&self, symbol: &str) -> Result<StockData> {
-        let response = self.provider.get_latest_quotes(symbol, "1d").await?;
-        let quote = response.last_quote()?;
+        let _permit = self.acquire_permit().await;
+        let result = self.provider.latest_quote(symbol).await;
+        Ok(self.observe(result)?)
+    }
 
-        Ok(StockData {
-            symbol: symbol.to_string(),
-            timestamp: DateTime::from_timestamp(quote.timestamp as i64, 0).unwrap_or(Utc::now()),
-            open: quote.open,
-            high: quote.high,
-            low: quote.low,
-            close: quote.close,
-            volume: quote.volume,
-        })
+    /// Latest quote for many symbols, one upstream request per
+    /// `quote_batch_size` of them.
+    ///
+    /// Symbols missing from a response are logged and left out rather than
+    /// failing their chunk, and a chunk that fails is logged and skipped.
+    /// Only when every chunk fails is the last error returned.
+    pub async fn get_latest_quotes_batch(&self, symbols: &[String]) -> Result<HashMap<String, StockData>> {
+        let clock = self
+            .cache
+            .as_ref()
+            .map_or_else(system_clock, |cache| cache.clock().clone());
+
+        let mut quotes = HashMap::with_capacity(symbols.len());
+        let mut last_error = None;
+        let mut succeeded = false;
+        for chunk in symbols.chunks(self.quote_batch_size) {
+            let fetched = self
+                .retry_policy
+                .run(clock.as_ref(), |_| async {
+                    let _permit = self.acquire_permit().await;
+                    let result = self.batch_quotes.latest_quotes(chunk).await;
+                    Ok(self.observe(result)?)
+                })
+                .await;
+
+            match fetched {
+                Ok(fetched) => {
+                    succeeded = true;
+                    for symbol in chunk.iter().filter(|symbol| !fetched.contains_key(*symbol)) {
+                        tracing::warn!("No quote for {} in {} batch response", symbol, self.batch_quotes.name());
+                    }
+                    quotes.extend(fetched);
+                }
+                Err(e) => {
+                    tracing::warn!("Batch quote request for {} symbols failed: {}", chunk.len(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if !succeeded => Err(e),
+            _ => Ok(quotes),
+        }
+    }
+
+    /// List a single ticker, to check the listing source answers
+    pub async fn probe_ticker_listing(&self) -> Result<()> {
+        if self.symbol_universe.is_some() {
+            return Ok(());
+        }
+        self.provider.fetch_tickers(1).await.map(|_| ())
+    }
+
+    async fn acquire_permit(&self) -> Option<RequestPermit> {
+        match self.rate_limiter {
+            Some(ref limiter) => Some(limiter.acquire().await),
+            None => None,
+        }
+    }
+
+    /// Let the limiter adapt to throttle responses
+    fn observe<T>(&self, result: Result<T, ProviderError>) -> Result<T, ProviderError> {
+        if let (Err(ProviderError::RateLimited { .. }), Some(limiter)) = (&result, &self.rate_limiter) {
+            limiter.record_throttle();
+        }
+        result
     }
 
     /// Initialize indicators for a specific symbol
     fn initialize_indicators(&mut self, symbol: &str) {
-        let indicator_set = IndicatorSet {
-            sma_20: SimpleMovingAverage::new(20).unwrap(),
-            sma_50: SimpleMovingAverage::new(50).unwrap(),
-            rsi: CustomRSI::new(14),
-            macd: MovingAverageConvergenceDivergence::new(12, 26, 9).unwrap(),
-        };
-        self.indicators.insert(symbol.to_string(), indicator_set);
+        self.indicators.insert(symbol.to_string(), IndicatorSet::new());
     }
 
     /// Calculate technical indicators for stock data
@@ -332,67 +2116,245 @@ impl StockAnalyzer {
         let mut results = Vec::new();
 
         if let Some(indicators) = self.indicators.get_mut(symbol) {
-            // Reset indicators
-            indicators.sma_20.reset();
-            indicators.sma_50.reset();
-            indicators.rsi.reset();
-            indicators.macd.reset();
+            indicators.reset();
 
             for data in stock_data {
-                let sma_20 = indicators.sma_20.next(data.close);
-                let sma_50 = indicators.sma_50.next(data.close);
-                let rsi = indicators.rsi.next(data.close);
-                let macd_result = indicators.macd.next(data.close);
-
-                results.push(TechnicalIndicators {
-                    sma_20: Some(sma_20),
-                    sma_50: Some(sma_50),
-                    rsi: rsi,
-                    macd: Some((macd_result.macd, macd_result.signal, macd_result.histogram)),
-                });
+                results.push(indicators.next(data));
             }
         }
 
+        self.apply_custom_indicators(stock_data, &mut results);
         results
     }
 
-    /// Calculate indicators with caching support
+    /// Resample daily bars to `timeframe` and calculate indicators on the
+    /// resampled bars, returning both. With `today`, a week or month still
+    /// trading then is left out rather than counted as a partial bar.
+    /// Weekly and monthly series keep their own per-symbol state so the
+    /// daily one retained for the symbol is left alone.
+    pub fn calculate_timeframe_indicators(
+        &mut self,
+        symbol: &str,
+        daily: &[StockData],
+        timeframe: Timeframe,
+        today: Option<NaiveDate>,
+    ) -> (Vec<StockData>, Vec<TechnicalIndicators>) {
+        let bars = match today {
+            Some(today) => resample_as_of(daily, timeframe, today, false),
+            None => resample(daily, timeframe),
+        };
+        let indicators = match timeframe {
+            Timeframe::Daily => self.calculate_indicators(symbol, &bars),
+            _ => self.calculate_indicators(&format!("{}@{}", symbol, timeframe), &bars),
+        };
+        (bars, indicators)
+    }
+
+    /// Calculate indicators with a custom set of moving averages and RSI
+    /// and MACD periods. The default configuration goes through the
+    /// retained per-symbol state like `calculate_indicators`; any other is
+    /// computed from scratch.
+    pub fn calculate_indicators_with_config(
+        &mut self,
+        symbol: &str,
+        stock_data: &[StockData],
+        config: &IndicatorConfig,
+    ) -> Result<Vec<TechnicalIndicators>> {
+        if *config == IndicatorConfig::default() {
+            return Ok(self.calculate_indicators(symbol, stock_data));
+        }
+        let mut outputs = compute_indicators_with_config(stock_data, config)?;
+        self.apply_custom_indicators(stock_data, &mut outputs);
+        Ok(outputs)
+    }
+
+    /// Calculate indicators with caching support.
+    ///
+    /// The streaming state left after processing a series is retained, so
+    /// when the next call's series only appends candles just those candles
+    /// are fed through. Any change to an already processed candle, or a
+    /// series that no longer covers the processed one, recomputes from scratch.
     pub async fn calculate_indicators_cached(
         &mut self,
         symbol: &str,
         stock_data: &[StockData],
     ) -> Vec<TechnicalIndicators> {
-        let cache_key = format!("indicators_{}_{}", symbol, stock_data.len());
+        let cache = match self.cache.clone() {
+            Some(cache) => cache,
+            None => return self.calculate_indicators(symbol, stock_data),
+        };
 
-        // Check cache first
-        if let Some(ref cache) = self.cache {
-            if let Some(cached_indicators) = cache.get_indicators(&cache_key).await {
-                tracing::debug!("Using cached indicators for {}", symbol);
-                return cached_indicators;
+        let state_key = indicator_state_key(symbol);
+        // State restored from the database lacks the earlier outputs
+        let previous = cache.get_indicator_state(&state_key).await.filter(IndicatorSnapshot::is_complete);
+        let plan = plan_update(previous.as_ref().map(|p| &p.fingerprint), stock_data);
+
+        let outputs = match (plan, previous) {
+            (UpdatePlan::Unchanged, Some(previous)) => {
+                tracing::debug!("Using retained indicators for {}", symbol);
+                // The definitions may have changed since they were retained
+                let mut outputs = previous.outputs.as_ref().clone();
+                self.apply_custom_indicators(stock_data, &mut outputs);
+                return outputs;
+            }
+            (UpdatePlan::Extend { from }, Some(previous)) => {
+                tracing::debug!("Extending indicators for {} by {} candles", symbol, stock_data.len() - from);
+                let mut outputs = previous.outputs.as_ref().clone();
+                outputs.extend(self.feed_indicators(symbol, &previous, &stock_data[from..]));
+                self.apply_custom_indicators(stock_data, &mut outputs);
+                outputs
+            }
+            (plan, _) => {
+                tracing::debug!("Recomputing indicators for {} ({:?})", symbol, plan);
+                self.calculate_indicators(symbol, stock_data)
+            }
+        };
+
+        if let Some(indicators) = self.indicators.get(symbol) {
+            let snapshot = indicators.snapshot(SeriesFingerprint::of(stock_data), outputs.clone());
+            self.retain_indicator_state(symbol, snapshot).await;
+        }
+
+        outputs
+    }
+
+    /// Feed `new_bars` through the indicator state retained for `symbol`
+    /// without resetting it, and return their indicators. Bars not later
+    /// than the last one processed are skipped. Without retained state the
+    /// bars are taken as the whole series.
+    ///
+    /// Only the bars' timestamps are checked against the retained state, so
+    /// callers holding the full series should go through
+    /// `calculate_latest_indicators`, which recomputes when earlier bars changed.
+    pub async fn update_indicators(&mut self, symbol: &str, new_bars: &[StockData]) -> Vec<TechnicalIndicators> {
+        match self.retained_indicator_state(symbol).await {
+            Some(previous) => {
+                let unseen = new_bars
+                    .iter()
+                    .position(|bar| previous.fingerprint.last_timestamp.is_none_or(|last| bar.timestamp > last))
+                    .map_or(&[][..], |first| &new_bars[first..]);
+                if unseen.is_empty() {
+                    return Vec::new();
+                }
+                tracing::debug!("Updating indicators for {} with {} new bars", symbol, unseen.len());
+                let outputs = self.feed_indicators(symbol, &previous, unseen);
+                self.extend_indicator_state(symbol, &previous, unseen, &outputs).await;
+                outputs
+            }
+            None => {
+                let outputs = self.calculate_indicators(symbol, new_bars);
+                let snapshot = self.indicators[symbol].snapshot(SeriesFingerprint::of(new_bars), outputs.clone());
+                self.retain_indicator_state(symbol, snapshot).await;
+                outputs
+            }
+        }
+    }
+
+    /// Indicators for at least the last `min_outputs` bars of `stock_data`
+    /// (all of them when shorter), aligned with its end: the returned
+    /// `outputs[i]` belongs to `stock_data[stock_data.len() - outputs.len() + i]`.
+    ///
+    /// Uses whatever state is retained for the symbol, in memory or in the
+    /// database, so only bars after the last processed one are computed.
+    /// A revised or shortened series, as after a split adjusts the history,
+    /// recomputes from scratch.
+    pub async fn calculate_latest_indicators(
+        &mut self,
+        symbol: &str,
+        stock_data: &[StockData],
+        min_outputs: usize,
+    ) -> Vec<TechnicalIndicators> {
+        if let Some(previous) = self.retained_indicator_state(symbol).await.filter(|p| !p.is_complete()) {
+            let retained = previous.outputs.len();
+            match plan_update(Some(&previous.fingerprint), stock_data) {
+                UpdatePlan::Unchanged if retained >= min_outputs.min(stock_data.len()) => {
+                    let mut outputs = previous.outputs.as_ref().clone();
+                    self.apply_custom_indicators(stock_data, &mut outputs);
+                    return outputs;
+                }
+                UpdatePlan::Extend { from } if retained + stock_data.len() - from >= min_outputs.min(stock_data.len()) => {
+                    tracing::debug!("Extending restored indicators for {} by {} candles", symbol, stock_data.len() - from);
+                    let new_bars = &stock_data[from..];
+                    let outputs = self.feed_indicators(symbol, &previous, new_bars);
+                    self.extend_indicator_state(symbol, &previous, new_bars, &outputs).await;
+                    let mut all = previous.outputs.as_ref().clone();
+                    all.extend(outputs);
+                    self.apply_custom_indicators(stock_data, &mut all);
+                    return all;
+                }
+                plan => tracing::debug!("Recomputing restored indicators for {} ({:?})", symbol, plan),
             }
         }
+        self.calculate_indicators_cached(symbol, stock_data).await
+    }
+
+    /// Restore `previous` into the symbol's indicator set and feed `bars`
+    fn feed_indicators(&mut self, symbol: &str, previous: &IndicatorSnapshot, bars: &[StockData]) -> Vec<TechnicalIndicators> {
+        if !self.indicators.contains_key(symbol) {
+            self.initialize_indicators(symbol);
+        }
+        let indicators = self.indicators.get_mut(symbol).unwrap();
+        indicators.restore(previous);
+        bars.iter().map(|data| indicators.next(data)).collect()
+    }
 
-        // Calculate indicators
-        let indicators = self.calculate_indicators(symbol, stock_data);
+    /// Retain the state left by feeding `bars` on top of `previous`
+    async fn extend_indicator_state(&self, symbol: &str, previous: &IndicatorSnapshot, bars: &[StockData], outputs: &[TechnicalIndicators]) {
+        let mut all = previous.outputs.as_ref().clone();
+        all.extend_from_slice(outputs);
+        let complete = previous.is_complete();
+        let mut snapshot = self.indicators[symbol].snapshot(previous.fingerprint.extend(bars), all);
+        if !complete {
+            snapshot = snapshot.trimmed(PERSISTED_OUTPUTS);
+        }
+        self.retain_indicator_state(symbol, snapshot).await;
+    }
 
-        // Cache the result
+    /// The state retained for `symbol` in the cache, or else in the database
+    async fn retained_indicator_state(&self, symbol: &str) -> Option<IndicatorSnapshot> {
         if let Some(ref cache) = self.cache {
-            cache.cache_indicators(cache_key, indicators.clone()).await;
+            if let Some(snapshot) = cache.get_indicator_state(&indicator_state_key(symbol)).await {
+                return Some(snapshot);
+            }
+        }
+        let database = self.database.as_ref()?;
+        match database.get_indicator_state(symbol, INDICATOR_SETTINGS).await {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                tracing::warn!("Failed to load indicator state for {}: {}", symbol, e);
+                None
+            }
         }
+    }
 
-        indicators
+    async fn retain_indicator_state(&self, symbol: &str, snapshot: IndicatorSnapshot) {
+        if let Some(ref database) = self.database {
+            if let Err(e) = database.store_indicator_state(symbol, INDICATOR_SETTINGS, &snapshot.trimmed(PERSISTED_OUTPUTS)).await {
+                tracing::warn!("Failed to store indicator state for {}: {}", symbol, e);
+            }
+        }
+        if let Some(ref cache) = self.cache {
+            cache.cache_indicator_state(indicator_state_key(symbol), snapshot).await;
+        }
     }
 
-    /// Analyze stock with basic signals
+    /// Signals for the latest candle of a series, given the indicators
+    /// computed for each of its candles
     pub fn analyze_signals(
         &self,
-        data: &StockData,
-        indicators: &TechnicalIndicators,
+        stock_data: &[StockData],
+        indicators: &[TechnicalIndicators],
     ) -> Vec<String> {
-        let mut signals = Vec::new();
+        let (Some(data), Some(latest)) = (stock_data.last(), indicators.last()) else {
+            return Vec::new();
+        };
+        let band_signals = Self::indicator_signals(stock_data, indicators);
+        let indicators = latest;
+        let requirements = &self.history_requirements;
+        let mut signals: Vec<String> = requirements.shortfall_signal(indicators.bars_used).into_iter().collect();
 
         // RSI signals
-        if let Some(rsi) = indicators.rsi {
+        if let Some(rsi) = indicators.rsi.filter(|_| requirements.supports_rsi(indicators.bars_used)) {
             if rsi > 70.0 {
                 signals.push("RSI Overbought (>70)".to_string());
             } else if rsi < 30.0 {
@@ -401,7 +2363,8 @@ impl StockAnalyzer {
         }
 
         // SMA crossover signals
-        if let (Some(sma_20), Some(sma_50)) = (indicators.sma_20, indicators.sma_50) {
+        let sma_50 = indicators.sma_50.filter(|_| requirements.supports_rsi(indicators.bars_used));
+        if let (Some(sma_20), Some(sma_50)) = (indicators.sma_20, sma_50) {
             if sma_20 > sma_50 && data.close > sma_20 {
                 signals.push("Bullish: Price above SMA20 > SMA50".to_string());
             } else if sma_20 < sma_50 && data.close < sma_20 {
@@ -410,7 +2373,7 @@ impl StockAnalyzer {
         }
 
         // MACD signals
-        if let Some((macd, signal, _)) = indicators.macd {
+        if let Some((macd, signal, _)) = indicators.macd.filter(|_| requirements.supports_macd(indicators.bars_used)) {
             if macd > signal {
                 signals.push("MACD Bullish: MACD above Signal".to_string());
             } else {
@@ -418,6 +2381,36 @@ impl StockAnalyzer {
             }
         }
 
+        signals.extend(band_signals);
+        signals
+    }
+
+    /// Bollinger, Stochastic, CCI and VWAP signals for the latest candle,
+    /// judged against the one before it, OBV divergence over the last
+    /// `OBV_DIVERGENCE_WINDOW` candles and the ADX trend strength. Empty
+    /// until two candles have values.
+    pub fn indicator_signals(stock_data: &[StockData], indicators: &[TechnicalIndicators]) -> Vec<String> {
+        let len = stock_data.len().min(indicators.len());
+        if len < 2 {
+            return Vec::new();
+        }
+        let data = &stock_data[stock_data.len() - 2..];
+        let last_two = &indicators[indicators.len() - 2..];
+
+        let bollinger: Vec<_> = last_two.iter().map(|i| i.bollinger.clone()).collect();
+        let stochastic: Vec<_> = last_two.iter().map(|i| i.stochastic.clone()).collect();
+        let cci: Vec<_> = last_two.iter().map(|i| i.cci).collect();
+        let vwap: Vec<_> = last_two.iter().map(|i| i.vwap).collect();
+
+        let window = len.min(OBV_DIVERGENCE_WINDOW);
+        let obv: Vec<_> = indicators[indicators.len() - window..].iter().map(|i| i.obv).collect();
+
+        let mut signals = BollingerBands::default().generate_signals(data, &bollinger);
+        signals.extend(StochasticOscillator::default().generate_signals(&stochastic));
+        signals.extend(CommodityChannelIndex::default().generate_signals(&cci));
+        signals.extend(Vwap::default().generate_signals(data, &vwap));
+        signals.extend(OnBalanceVolume::new().generate_signals(&stock_data[stock_data.len() - window..], &obv));
+        signals.extend(AverageDirectionalIndex::default().generate_signals(last_two[1].adx.as_ref()));
         signals
     }
 
@@ -428,73 +2421,49 @@ impl StockAnalyzer {
         stock_data: &[StockData],
         indicators: &[TechnicalIndicators],
     ) {
-        println!("=== Stock Analysis for {} ===", symbol);
-
-        if let (Some(latest_data), Some(latest_indicators)) = (stock_data.last(), indicators.last())
-        {
-            println!(
-                "Latest Data ({}):",
-                latest_data.timestamp.format("%Y-%m-%d")
-            );
-            println!("  Price: ${:.2}", latest_data.close);
-            println!("  Volume: {}", latest_data.volume);
-
-            println!("\nTechnical Indicators:");
-            if let Some(sma_20) = latest_indicators.sma_20 {
-                println!("  SMA(20): ${:.2}", sma_20);
-            }
-            if let Some(sma_50) = latest_indicators.sma_50 {
-                println!("  SMA(50): ${:.2}", sma_50);
-            }
-            if let Some(rsi) = latest_indicators.rsi {
-                println!("  RSI(14): {:.2}", rsi);
-            }
-            if let Some((macd, signal, histogram)) = latest_indicators.macd {
-                println!(
-                    "  MACD: {:.4}, Signal: {:.4}, Histogram: {:.4}",
-                    macd, signal, histogram
-                );
-            }
-
-            println!("\nSignals:");
-            let signals = self.analyze_signals(latest_data, latest_indicators);
-            if signals.is_empty() {
-                println!("  No strong signals detected");
-            } else {
-                for signal in signals {
-                    println!("  • {}", signal);
-                }
-            }
-        }
+        print!("{}", self.symbol_report(symbol, stock_data, indicators));
+    }
+
+    /// The latest candle, indicators and signals as a `SymbolReport`
+    pub fn symbol_report(
+        &self,
+        symbol: &str,
+        stock_data: &[StockData],
+        indicators: &[TechnicalIndicators],
+    ) -> SymbolReport {
+        let signals = if stock_data.is_empty() || indicators.is_empty() {
+            Vec::new()
+        } else {
+            self.analyze_signals(stock_data, indicators)
+        };
+        SymbolReport::new(symbol, stock_data, indicators, signals)
     }
     /**
-     * Fetches n amount of tickers from the nasdaq api.
+     * Fetches n amount of tickers from the nasdaq api, across NASDAQ, NYSE and AMEX.
      * NOTE: count=0 infers max amount (no limit)
      */
     pub async fn fetch_n_tickers(count: usize) -> Result<Vec<TickerInfo>> {
-        let url = format!(
-            "https://api.nasdaq.com/api/screener/stocks?tableonly=true&limit={}",
-            if count == 0 { 10000 } else { count }  // Use large number for unlimited
-        );
-
-        let client = reqwest::Client::new();
-        let response = client
-            .get(url)
-            .header(
-                "User-Agent",
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
-            )
-            .send()
-            .await?;
+        NasdaqScreener::new().fetch_tickers(count).await
+    }
 
-        let nasdaq_response: NasdaqApiResponse = response.json().await?;
+    /// Concatenate exchange listings, keeping the first listing of a symbol
+    /// that appears on more than one
+    fn merge_listings(listings: Vec<Vec<TickerInfo>>) -> Vec<TickerInfo> {
+        let mut seen = HashSet::new();
+        listings
+            .into_iter()
+            .flatten()
+            .filter(|ticker| seen.insert(normalize_symbol(&ticker.symbol)))
+            .collect()
+    }
 
-        let mut tickers = Vec::new();
-        for row in nasdaq_response.data.table.rows {
-            if row.symbol.contains("^") || row.symbol.contains("/") {
-                continue; // Skip indices and special symbols
-            }
-            tickers.push(TickerInfo {
+    /// Convert screener rows (from either response shape) into tickers,
+    /// skipping indices and special symbols
+    fn tickers_from_rows(rows: Vec<NasdaqRow>) -> Vec<TickerInfo> {
+        rows
+            .into_iter()
+            .filter(|row| !row.symbol.contains('^') && !row.symbol.contains('/'))
+            .map(|row| TickerInfo {
                 symbol: row.symbol,
                 name: row.name,
                 last_sale: row.last_sale,
@@ -506,54 +2475,70 @@ impl StockAnalyzer {
                 volume: row.volume,
                 sector: row.sector,
                 industry: row.industry,
-            });
-        }
-
-        let message = if count == 0 { 
-            " (no limit)".to_string() 
-        } else { 
-            format!(" (requested: {})", count) 
-        };
-        tracing::info!("📊 Fetched {} tickers from Nasdaq API{}", tickers.len(), message);
-        Ok(tickers)
+                exchange: row.exchange,
+                beta: row.beta,
+                fifty_two_week_high: row.fifty_two_week_high,
+                fifty_two_week_low: row.fifty_two_week_low,
+                last_sale_f64: None,
+                market_cap_f64: None,
+                pct_change_f64: None,
+                volume_u64: None,
+                country_normalized: None,
+                region: None,
+                is_adr: false,
+            }
+            .with_parsed_fields())
+            .collect()
     }
 
     /// Fetch all available tickers from Nasdaq API
     pub async fn fetch_all_tickers() -> Result<Vec<TickerInfo>> {
-        return StockAnalyzer::fetch_n_tickers(10000).await;  // Use large number instead of 0
+        StockAnalyzer::fetch_n_tickers(10000).await// Use large number instead of 0
     }
 
     /// Fetch all tickers with caching support
     pub async fn fetch_all_tickers_cached(&self) -> Result<Vec<TickerInfo>> {
-        let cache_key = "all_tickers";
-        
-        // Check cache first
-        if let Some(ref cache) = self.cache {
-            if let Some(cached_tickers) = cache.get_tickers(cache_key).await {
-                tracing::debug!("Using cached tickers ({} entries)", cached_tickers.len());
-                return Ok(cached_tickers);
-            }
-        }
-
-        // Rate limiting check to prevent excessive API calls
-        if let Some(ref cache) = self.cache {
-            if cache.should_rate_limit("nasdaq_api_tickers", std::time::Duration::from_secs(10)) {
-                tracing::warn!("Rate limiting Nasdaq API ticker fetch - too many requests");
-                return Err(anyhow::anyhow!("Rate limited: ticker fetch"));
-            }
-        }
-
-        tracing::info!("Cache miss - fetching tickers from Nasdaq API");
+        Ok(self.fetch_all_tickers_cached_at().await?.0)
+    }
 
-        // Fetch from API
-        let tickers = Self::fetch_n_tickers(10000).await?;
+    /// `fetch_all_tickers_cached` along with when the list was fetched from
+    /// Nasdaq, or read from the symbol universe, so callers can tell how
+    /// stale it is
+    pub async fn fetch_all_tickers_cached_at(&self) -> Result<(Vec<TickerInfo>, DateTime<Utc>)> {
+        let Some(ref cache) = self.cache else {
+            let tickers = match self.symbol_universe {
+                Some(ref universe) => universe.tickers().to_vec(),
+                None => self.provider.fetch_tickers(10000).await?,
+            };
+            return Ok((tickers, Utc::now()));
+        };
 
-        // Cache the result
-        if let Some(ref cache) = self.cache {
-            cache.cache_tickers(cache_key.to_string(), tickers.clone()).await;
+        // Cached like a screener listing, so a reload that invalidates the
+        // cache reaches the ticker directory as a fresh fetch
+        if let Some(universe) = self.symbol_universe.clone() {
+            return cache
+                .get_or_refresh_tickers(crate::cache::ALL_TICKERS_KEY, move || async move {
+                    tracing::info!("Reading tickers from {}", universe.path().display());
+                    Ok(universe.tickers().to_vec())
+                })
+                .await;
         }
 
-        Ok(tickers)
+        // Only a refresh reaches the provider; concurrent callers share it
+        let provider = self.provider.clone();
+        let limiter = cache.clone();
+        cache
+            .get_or_refresh_tickers(crate::cache::ALL_TICKERS_KEY, move || async move {
+                // Rate limiting check to prevent excessive API calls
+                let interval = std::time::Duration::from_secs(10);
+                if limiter.should_rate_limit("nasdaq_api_tickers", interval) {
+                    tracing::warn!("Rate limiting Nasdaq API ticker fetch - too many requests");
+                    return Err(AnalyzerError::RateLimited { retry_after: Some(interval) });
+                }
+                tracing::info!("Refreshing tickers from {}", provider.name());
+                provider.fetch_tickers(10000).await
+            })
+            .await
     }
 
     /// Filter tickers by comprehensive criteria
@@ -565,6 +2550,32 @@ impl StockAnalyzer {
             .collect()
     }
 
+    /// Filter tickers against a single filter or a composite (OR) of filters.
+    /// Each ticker is parsed once and every member filter is evaluated against it.
+    /// Returns the union of passing tickers (in input order) and, per member,
+    /// how many tickers that member matched.
+    pub fn filter_tickers_spec(tickers: &[TickerInfo], spec: &FilterSpec) -> (Vec<TickerInfo>, Vec<usize>) {
+        let members = spec.members();
+        let mut member_matches = vec![0; members.len()];
+        let mut passed = Vec::new();
+
+        for ticker in tickers {
+            let parsed = ParsedTickerInfo::new(ticker);
+            let mut any = false;
+            for (i, filter) in members.iter().enumerate() {
+                if parsed.passes(filter) {
+                    member_matches[i] += 1;
+                    any = true;
+                }
+            }
+            if any {
+                passed.push(ticker.clone());
+            }
+        }
+
+        (passed, member_matches)
+    }
+
     /// Filter tickers and their corresponding RSI values
     pub async fn filter_tickers_with_analysis(
         &mut self,
@@ -631,211 +2642,49 @@ impl StockAnalyzer {
 
     /// Check if a ticker passes the basic (non-RSI) filters
     fn passes_basic_filters(ticker: &TickerInfo, filter: &StockFilter) -> bool {
-        // Filter by market cap
-        if let (Some(market_cap_str), Some(min_cap)) = (&ticker.market_cap, filter.min_market_cap) {
-            if let Ok(market_cap) = Self::parse_market_cap(market_cap_str) {
-                if market_cap < min_cap { return false; }
-            } else { return false; }
-        }
-        if let (Some(market_cap_str), Some(max_cap)) = (&ticker.market_cap, filter.max_market_cap) {
-            if let Ok(market_cap) = Self::parse_market_cap(market_cap_str) {
-                if market_cap > max_cap { return false; }
-            } else { return false; }
-        }
-
-        // Filter by price
-        if let (Some(price_str), Some(min_price)) = (&ticker.last_sale, filter.min_price) {
-            if let Ok(price) = Self::parse_price(price_str) {
-                if price < min_price { return false; }
-            } else { return false; }
-        }
-        if let (Some(price_str), Some(max_price)) = (&ticker.last_sale, filter.max_price) {
-            if let Ok(price) = Self::parse_price(price_str) {
-                if price > max_price { return false; }
-            } else { return false; }
-        }
-
-        // Filter by volume
-        if let (Some(volume_str), Some(min_volume)) = (&ticker.volume, filter.min_volume) {
-            if let Ok(volume) = Self::parse_volume(volume_str) {
-                if volume < min_volume { return false; }
-            } else { return false; }
-        }
-        if let (Some(volume_str), Some(max_volume)) = (&ticker.volume, filter.max_volume) {
-            if let Ok(volume) = Self::parse_volume(volume_str) {
-                if volume > max_volume { return false; }
-            } else { return false; }
-        }
-
-        // Filter by percentage change
-        if let (Some(pct_str), Some(min_pct)) = (&ticker.pct_change, filter.min_pct_change) {
-            if let Ok(pct) = Self::parse_percentage(pct_str) {
-                if pct < min_pct { return false; }
-            } else { return false; }
-        }
-        if let (Some(pct_str), Some(max_pct)) = (&ticker.pct_change, filter.max_pct_change) {
-            if let Ok(pct) = Self::parse_percentage(pct_str) {
-                if pct > max_pct { return false; }
-            } else { return false; }
-        }
-
-        // Filter by sectors
-        if let Some(allowed_sectors) = &filter.sectors {
-            if let Some(ticker_sector) = &ticker.sector {
-                if !allowed_sectors.iter().any(|sector| 
-                    ticker_sector.to_lowercase().contains(&sector.to_lowercase())
-                ) {
-                    return false;
-                }
-            } else { return false; }
-        }
-
-        // Filter by countries
-        if let Some(allowed_countries) = &filter.countries {
-            if let Some(ticker_country) = &ticker.country {
-                if !allowed_countries.iter().any(|country| 
-                    ticker_country.to_lowercase().contains(&country.to_lowercase())
-                ) {
-                    return false;
-                }
-            } else { return false; }
-        }
-
-        // Filter by industries
-        if let Some(allowed_industries) = &filter.industries {
-            if let Some(ticker_industry) = &ticker.industry {
-                if !allowed_industries.iter().any(|industry| 
-                    ticker_industry.to_lowercase().contains(&industry.to_lowercase())
-                ) {
-                    return false;
-                }
-            } else { return false; }
-        }
-
-        // Filter by IPO year
-        if let (Some(ipo_str), Some(min_year)) = (&ticker.ipo_year, filter.min_ipo_year) {
-            if let Ok(year) = ipo_str.parse::<i32>() {
-                if year < min_year { return false; }
-            } else { return false; }
-        }
-        if let (Some(ipo_str), Some(max_year)) = (&ticker.ipo_year, filter.max_ipo_year) {
-            if let Ok(year) = ipo_str.parse::<i32>() {
-                if year > max_year { return false; }
-            } else { return false; }
-        }
-
-        true
-    }
-
-    /// Parse market cap string (e.g., "$1.5B", "$500M") to float
-    pub fn parse_market_cap(market_cap_str: &str) -> Result<f64, std::num::ParseFloatError> {
-        let cleaned = market_cap_str.replace('$', "").replace(',', "");
-
-        if cleaned.ends_with('B') {
-            let num_str = cleaned.trim_end_matches('B');
-            let num: f64 = num_str.parse()?;
-            Ok(num * 1_000_000_000.0)
-        } else if cleaned.ends_with('M') {
-            let num_str = cleaned.trim_end_matches('M');
-            let num: f64 = num_str.parse()?;
-            Ok(num * 1_000_000.0)
-        } else if cleaned.ends_with('K') {
-            let num_str = cleaned.trim_end_matches('K');
-            let num: f64 = num_str.parse()?;
-            Ok(num * 1_000.0)
-        } else {
-            cleaned.parse()
-        }
-    }
-
-    /// Parse price string (e.g., "$123.45") to float
-    fn parse_price(price_str: &str) -> Result<f64, std::num::ParseFloatError> {
-        let cleaned = price_str.replace('$', "").replace(',', "");
-        cleaned.parse()
+        ParsedTickerInfo::new(ticker).passes(filter)
     }
 
-    /// Parse volume string (e.g., "1,234,567") to u64
-    fn parse_volume(volume_str: &str) -> Result<u64, std::num::ParseIntError> {
-        let cleaned = volume_str.replace(',', "");
-        cleaned.parse()
+    /// The first `limit` tickers of `rank_tickers` under the default weights
+    pub fn get_top_performers(tickers: &[TickerInfo], limit: usize) -> Vec<TickerInfo> {
+        let mut ranked = Self::rank_tickers(tickers, &RankingConfig::default());
+        ranked.truncate(limit);
+        ranked
     }
 
-    /// Parse percentage string (e.g., "2.5%", "-1.25%") to float
-    pub fn parse_percentage(pct_str: &str) -> Result<f64, std::num::ParseFloatError> {
-        let cleaned = pct_str.replace('%', "");
-        cleaned.parse()
+    /// Tickers with a `RankingConfig::score`, best first; ties keep their
+    /// input order
+    pub fn rank_tickers(tickers: &[TickerInfo], config: &RankingConfig) -> Vec<TickerInfo> {
+        let mut scored: Vec<(f64, &TickerInfo)> =
+            tickers.iter().filter_map(|ticker| Some((config.score(ticker)?, ticker))).collect();
+        scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        scored.into_iter().map(|(_, ticker)| ticker.clone()).collect()
     }
 
-    /// Get top performing tickers by percentage change
-    pub fn get_top_performers(tickers: &[TickerInfo], limit: usize) -> Vec<TickerInfo> {
-        let mut sorted_tickers: Vec<TickerInfo> = tickers
-            .iter()
-            .filter_map(|ticker| {
-                if let Some(pct_change_str) = &ticker.pct_change {
-                    let cleaned = pct_change_str.replace('%', "");
-                    if cleaned.parse::<f64>().is_ok() {
-                        let ticker_clone = ticker.clone();
-                        // Store the parsed percentage for sorting
-                        return Some(ticker_clone);
-                    }
-                }
-                None
-            })
-            .collect();
-
-        // Sort by percentage change (descending)
-        sorted_tickers.sort_by(|a, b| {
-            let a_pct = a
-                .pct_change
-                .as_ref()
-                .and_then(|s| s.replace('%', "").parse::<f64>().ok())
-                .unwrap_or(0.0);
-            let b_pct = b
-                .pct_change
-                .as_ref()
-                .and_then(|s| s.replace('%', "").parse::<f64>().ok())
-                .unwrap_or(0.0);
-            b_pct
-                .partial_cmp(&a_pct)
-                .unwrap_or(std::cmp::Ordering::Equal)
+    /// The first `limit` tickers ordered by `field`. Tickers without a value
+    /// for the field go last in either direction, and ties keep their input
+    /// order.
+    pub fn sort_tickers(tickers: &[TickerInfo], field: TickerSortField, descending: bool, limit: usize) -> Vec<TickerInfo> {
+        let mut ranked: Vec<(Option<f64>, &TickerInfo)> = tickers.iter().map(|ticker| (field.value(ticker), ticker)).collect();
+        ranked.sort_by(|(a, _), (b, _)| match (a, b) {
+            (Some(a), Some(b)) if descending => b.total_cmp(a),
+            (Some(a), Some(b)) => a.total_cmp(b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
         });
 
-        sorted_tickers.into_iter().take(limit).collect()
+        ranked.into_iter().take(limit).map(|(_, ticker)| ticker.clone()).collect()
     }
 
     /// Print ticker information in a formatted table
     pub fn print_tickers(tickers: &[TickerInfo], title: &str) {
-        println!("\n{}", "=".repeat(80));
+        // Show max 20 for readability
+        let table = ticker_table(tickers).with_max_rows(20);
+        println!("\n{}", "=".repeat(table.width()));
         println!("📊 {}", title);
-        println!("{}", "=".repeat(80));
-        println!(
-            "{:<8} {:<30} {:<12} {:<10} {:<15} {:<20}",
-            "Symbol", "Name", "Last Sale", "Change%", "Market Cap", "Sector"
-        );
-        println!("{}", "-".repeat(80));
-
-        for ticker in tickers.iter().take(20) {
-            // Show max 20 for readability
-            println!(
-                "{:<8} {:<30} {:<12} {:<10} {:<15} {:<20}",
-                ticker.symbol,
-                ticker.name.chars().take(28).collect::<String>(),
-                ticker.last_sale.as_deref().unwrap_or("N/A"),
-                ticker.pct_change.as_deref().unwrap_or("N/A"),
-                ticker.market_cap.as_deref().unwrap_or("N/A"),
-                ticker
-                    .sector
-                    .as_deref()
-                    .unwrap_or("N/A")
-                    .chars()
-                    .take(18)
-                    .collect::<String>()
-            );
-        }
-
-        if tickers.len() > 20 {
-            println!("... and {} more tickers", tickers.len() - 20);
-        }
+        println!("{}", "=".repeat(table.width()));
+        print!("{}", table);
     }
 }
 
@@ -854,6 +2703,7 @@ mod tests {
             low: 99.0,
             close: 102.0,
             volume: 1000000,
+            adjusted_close: None,
         };
 
         assert_eq!(stock_data.symbol, "AAPL");
@@ -862,6 +2712,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_stock_analyzer_creation() {
         let _analyzer = StockAnalyzer::new();
         // Just test that we can create an analyzer without panic
@@ -875,9 +2726,197 @@ mod tests {
             sma_50: Some(95.0),
             rsi: Some(65.0),
             macd: Some((0.5, 0.3, 0.2)),
+            ..Default::default()
         };
 
         assert_eq!(indicators.sma_20, Some(100.0));
         assert_eq!(indicators.rsi, Some(65.0));
     }
+
+    fn parse_fixture(json: &str) -> Vec<TickerInfo> {
+        let response: NasdaqApiResponse = serde_json::from_str(json).unwrap();
+        StockAnalyzer::tickers_from_rows(response.data.into_rows())
+    }
+
+    #[test]
+    fn test_parse_basic_screener_response() {
+        let tickers = parse_fixture(include_str!("../tests/fixtures/nasdaq_screener_table.json"));
+
+        // Indices and share-class symbols are skipped
+        assert_eq!(tickers.len(), 1);
+        let aapl = &tickers[0];
+        assert_eq!(aapl.symbol, "AAPL");
+        assert_eq!(aapl.last_sale.as_deref(), Some("$189.50"));
+        assert_eq!(aapl.last_sale_f64, Some(189.5));
+        assert_eq!(aapl.market_cap_f64, Some(2_950_000_000_000.0));
+        assert_eq!(aapl.pct_change_f64, Some(0.664));
+        assert_eq!(aapl.volume_u64, None);
+        assert!(aapl.exchange.is_none());
+        assert!(aapl.beta.is_none());
+        assert!(aapl.fifty_two_week_low.is_none());
+        assert!(aapl.pct_above_52_week_low().is_none());
+    }
+
+    #[test]
+    fn test_parse_extended_screener_response() {
+        let tickers = parse_fixture(include_str!("../tests/fixtures/nasdaq_screener_download.json"));
+        assert_eq!(tickers.len(), 3);
+
+        let low = &tickers[0];
+        assert_eq!(low.exchange.as_deref(), Some("NASDAQ"));
+        assert_eq!(low.beta.as_deref(), Some("1.35"));
+        assert_eq!(low.fifty_two_week_high.as_deref(), Some("$25.00"));
+        assert!((low.pct_above_52_week_low().unwrap() - 2.0).abs() < 1e-9);
+
+        // Numeric columns are accepted as well as display strings
+        let high = &tickers[1];
+        assert_eq!(high.beta.as_deref(), Some("0.8"));
+        assert_eq!(high.fifty_two_week_low.as_deref(), Some("20.0"));
+
+        assert!(tickers[2].fifty_two_week_low.is_none());
+    }
+
+    #[test]
+    fn test_merge_listings_keeps_first_exchange() {
+        let listed = |symbol: &str, exchange: &str| TickerInfo {
+            exchange: Some(exchange.to_string()),
+            ..TickerInfo::unlisted(symbol)
+        };
+        let merged = StockAnalyzer::merge_listings(vec![
+            vec![listed("AAPL", "NASDAQ"), listed("BRK.B", "NASDAQ")],
+            vec![listed("IBM", "NYSE"), listed("brk.b", "NYSE")],
+            vec![listed("IMO", "AMEX")],
+        ]);
+        let symbols: Vec<(&str, &str)> =
+            merged.iter().map(|t| (t.symbol.as_str(), t.exchange.as_deref().unwrap())).collect();
+        assert_eq!(symbols, [("AAPL", "NASDAQ"), ("BRK.B", "NASDAQ"), ("IBM", "NYSE"), ("IMO", "AMEX")]);
+        assert_eq!(serde_json::to_string(&Exchange::ALL).unwrap(), r#"["NASDAQ","NYSE","AMEX"]"#);
+    }
+
+    /// A canned screener page listing `symbols` out of `total`
+    fn screener_page(symbols: &[&str], total: usize) -> String {
+        let rows: Vec<serde_json::Value> =
+            symbols.iter().map(|symbol| serde_json::json!({"symbol": symbol, "name": symbol})).collect();
+        serde_json::json!({"data": {"totalrecords": total, "table": {"rows": rows}}}).to_string()
+    }
+
+    async fn mock_page(server: &mut mockito::ServerGuard, offset: usize, status: usize, body: String) -> mockito::Mock {
+        server
+            .mock("GET", "/api/screener/stocks")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("exchange".into(), "NASDAQ".into()),
+                mockito::Matcher::UrlEncoded("limit".into(), "2".into()),
+                mockito::Matcher::UrlEncoded("offset".into(), offset.to_string()),
+            ]))
+            .with_status(status)
+            .with_body(body)
+            .expect(1)
+            .create_async()
+            .await
+    }
+
+    fn test_screener(server: &mockito::ServerGuard) -> NasdaqScreener {
+        let retry_policy = RetryPolicy { base_delay: std::time::Duration::ZERO, jitter: 0.0, ..RetryPolicy::default() };
+        NasdaqScreener::new()
+            .with_base_url(&server.url())
+            .with_page_size(2)
+            .with_retry_policy(retry_policy)
+            .with_clock(Arc::new(crate::clock::TestClock::new(Utc::now())))
+    }
+
+    #[tokio::test]
+    async fn test_screener_pages_until_a_short_page() {
+        let mut server = mockito::Server::new_async().await;
+        let pages = [
+            mock_page(&mut server, 0, 200, screener_page(&["AAPL", "MSFT"], 5)).await,
+            // MSFT shifted onto the next page while paging
+            mock_page(&mut server, 2, 200, screener_page(&["MSFT", "GOOG"], 5)).await,
+            mock_page(&mut server, 4, 200, screener_page(&["^IXIC"], 5)).await,
+        ];
+
+        let tickers = test_screener(&server).fetch_exchange(Exchange::Nasdaq, 0).await.unwrap();
+        let symbols: Vec<&str> = tickers.iter().map(|t| t.symbol.as_str()).collect();
+        assert_eq!(symbols, ["AAPL", "MSFT", "GOOG"]);
+        assert!(tickers.iter().all(|t| t.exchange.as_deref() == Some("NASDAQ")));
+        for page in pages {
+            page.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_screener_rejects_a_truncated_listing() {
+        let mut server = mockito::Server::new_async().await;
+        mock_page(&mut server, 0, 200, screener_page(&["A", "B"], 6)).await;
+        mock_page(&mut server, 2, 200, screener_page(&["C", "D"], 6)).await;
+        mock_page(&mut server, 4, 200, screener_page(&["E"], 6)).await;
+
+        let error = test_screener(&server).fetch_exchange(Exchange::Nasdaq, 0).await.unwrap_err();
+        assert!(matches!(error, AnalyzerError::Upstream(ref message) if message.contains("received 5 NASDAQ rows but Nasdaq reported 6")));
+    }
+
+    #[tokio::test]
+    async fn test_screener_tolerates_a_small_shortfall() {
+        let mut server = mockito::Server::new_async().await;
+        mock_page(&mut server, 0, 200, screener_page(&["A", "B"], 6)).await;
+        mock_page(&mut server, 2, 200, screener_page(&["C", "D"], 6)).await;
+        mock_page(&mut server, 4, 200, screener_page(&["E"], 6)).await;
+
+        let screener = test_screener(&server).with_total_tolerance(0.2);
+        assert_eq!(screener.fetch_exchange(Exchange::Nasdaq, 0).await.unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_screener_retries_a_failed_page() {
+        let mut server = mockito::Server::new_async().await;
+        let first = mock_page(&mut server, 0, 200, screener_page(&["A", "B"], 3)).await;
+        // Matched first while it still expects a hit, then the good page
+        let failed = mock_page(&mut server, 2, 503, String::new()).await;
+        let retried = mock_page(&mut server, 2, 200, screener_page(&["C"], 3)).await;
+
+        let tickers = test_screener(&server).fetch_exchange(Exchange::Nasdaq, 0).await.unwrap();
+        assert_eq!(tickers.len(), 3);
+        first.assert_async().await;
+        failed.assert_async().await;
+        retried.assert_async().await;
+    }
+
+    #[test]
+    fn test_cached_ticker_snapshot_without_extended_fields() {
+        let json = r#"[{"symbol":"OLD","name":"Old Snapshot","last_sale":"$1.00","net_change":null,
+            "pct_change":null,"market_cap":null,"country":null,"ipo_year":null,"volume":null,
+            "sector":null,"industry":null}]"#;
+        let tickers: Vec<TickerInfo> = serde_json::from_str(json).unwrap();
+        assert!(tickers[0].exchange.is_none());
+        assert!(tickers[0].fifty_two_week_high.is_none());
+        assert_eq!(tickers[0].last_sale_f64, None);
+
+        // Unparsed fields are left out, so older readers see the old shape
+        let json = serde_json::to_value(&tickers[0]).unwrap();
+        assert!(json.get("last_sale_f64").is_none());
+        let parsed = serde_json::to_value(tickers[0].clone().with_parsed_fields()).unwrap();
+        assert_eq!(parsed["last_sale_f64"], 1.0);
+        assert!(parsed.get("volume_u64").is_none());
+    }
+
+    #[test]
+    fn test_52_week_low_prescreen() {
+        let tickers = parse_fixture(include_str!("../tests/fixtures/nasdaq_screener_download.json"));
+        let filter = StockFilter::new().with_max_pct_above_52_week_low(Some(5.0));
+
+        // HIGHX is 140% above its low; NODATA has no range, so it can't pass
+        let symbols: Vec<String> = StockAnalyzer::filter_tickers(&tickers, &filter)
+            .into_iter()
+            .map(|t| t.symbol)
+            .collect();
+        assert_eq!(symbols, vec!["LOWX"]);
+
+        let filter = StockFilter::new()
+            .with_exchanges(vec!["nyse".to_string()])
+            .with_beta_range(None, Some(1.0));
+        let symbols: Vec<String> = StockAnalyzer::filter_tickers(&tickers, &filter)
+            .into_iter()
+            .map(|t| t.symbol)
+            .collect();
+        assert_eq!(symbols, vec!["HIGHX"]);
+    }
 }
@@ -0,0 +1,279 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::web_api::StockAnalysisResult;
+
+/// Default for `SanityBounds::min_price`; listed shares do not trade below
+/// a tenth of a cent, so anything lower is a bad bar
+pub const DEFAULT_MIN_PRICE: f64 = 0.001;
+/// Default for `SanityBounds::max_abs_pct_change`
+pub const DEFAULT_MAX_ABS_PCT_CHANGE: f64 = 100.0;
+/// Default for `SanityBounds::confirming_volume_vs_avg`
+pub const DEFAULT_CONFIRMING_VOLUME_VS_AVG: f64 = 5.0;
+/// Default for `SanityBounds::max_volume`
+pub const DEFAULT_MAX_VOLUME: u64 = 10_000_000_000;
+/// Flagged results `GET /api/anomalies` returns when no `limit` is given
+pub const DEFAULT_ANOMALY_LIMIT: u32 = 50;
+/// Most flagged results `GET /api/anomalies` returns
+pub const MAX_ANOMALY_LIMIT: u32 = 1000;
+
+/// Values a believable result stays within; a result outside them most
+/// likely came from a bad upstream bar
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SanityBounds {
+    /// Lowest believable price; prices at or below zero never are
+    pub min_price: f64,
+    /// Largest believable daily move, in percent either way
+    pub max_abs_pct_change: f64,
+    /// Volume, as a multiple of its average, that makes a larger move
+    /// believable after all
+    pub confirming_volume_vs_avg: f64,
+    /// Volume no single bar reaches
+    pub max_volume: u64,
+}
+
+impl Default for SanityBounds {
+    fn default() -> Self {
+        Self {
+            min_price: DEFAULT_MIN_PRICE,
+            max_abs_pct_change: DEFAULT_MAX_ABS_PCT_CHANGE,
+            confirming_volume_vs_avg: DEFAULT_CONFIRMING_VOLUME_VS_AVG,
+            max_volume: DEFAULT_MAX_VOLUME,
+        }
+    }
+}
+
+impl SanityBounds {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.min_price.is_finite() && self.min_price >= 0.0) {
+            return Err("min_price must be a non-negative number".to_string());
+        }
+        if !(self.max_abs_pct_change.is_finite() && self.max_abs_pct_change > 0.0) {
+            return Err("max_abs_pct_change must be a positive number".to_string());
+        }
+        if self.confirming_volume_vs_avg.is_nan() || self.confirming_volume_vs_avg <= 0.0 {
+            return Err("confirming_volume_vs_avg must be positive".to_string());
+        }
+        if self.max_volume == 0 {
+            return Err("max_volume must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// What happens to a result that breaks the sanity bounds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyPolicy {
+    /// Store and broadcast it as usual, with its `anomalies` listed
+    #[default]
+    Flag,
+    /// Keep it out of the results and store it in `quarantined_results`
+    Quarantine,
+    /// Discard it, logging a warning
+    Drop,
+}
+
+impl AnomalyPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AnomalyPolicy::Flag => "flag",
+            AnomalyPolicy::Quarantine => "quarantine",
+            AnomalyPolicy::Drop => "drop",
+        }
+    }
+}
+
+impl FromStr for AnomalyPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "flag" => Ok(AnomalyPolicy::Flag),
+            "quarantine" => Ok(AnomalyPolicy::Quarantine),
+            "drop" => Ok(AnomalyPolicy::Drop),
+            other => Err(format!("unknown anomaly policy: {}", other)),
+        }
+    }
+}
+
+/// Describe each way `result` breaks `bounds`; empty when it is believable.
+/// A missing value is not an anomaly, but a NaN or infinite one is, since
+/// it would be served as `null` and look merely missing.
+pub fn detect_anomalies(result: &StockAnalysisResult, bounds: &SanityBounds) -> Vec<String> {
+    let mut anomalies = Vec::new();
+
+    if let Some(price) = result.current_price {
+        if !price.is_finite() {
+            anomalies.push("price is not a finite number".to_string());
+        } else if price <= 0.0 {
+            anomalies.push(format!("price {} is not positive", price));
+        } else if price < bounds.min_price {
+            anomalies.push(format!("price {} is below {}", price, bounds.min_price));
+        }
+    }
+
+    if let Some(rsi) = result.rsi.filter(|rsi| rsi.is_finite()) {
+        if !(0.0..=100.0).contains(&rsi) {
+            anomalies.push(format!("RSI {:.2} is outside 0-100", rsi));
+        }
+    }
+
+    if let Some(pct_change) = result.pct_change.filter(|pct| pct.is_finite()) {
+        let confirmed = result
+            .volume_vs_avg
+            .is_some_and(|ratio| ratio >= bounds.confirming_volume_vs_avg);
+        if pct_change.abs() >= bounds.max_abs_pct_change && !confirmed {
+            anomalies.push(format!(
+                "pct_change {:.1}% is beyond ±{}% without the volume to support it",
+                pct_change, bounds.max_abs_pct_change
+            ));
+        }
+    }
+
+    if let Some(volume) = result.volume.filter(|volume| *volume >= bounds.max_volume) {
+        anomalies.push(format!("volume {} is not below {}", volume, bounds.max_volume));
+    }
+
+    let values = [
+        ("RSI", result.rsi),
+        ("pct_change", result.pct_change),
+        ("sma_20", result.sma_20),
+        ("sma_50", result.sma_50),
+        ("macd", result.macd),
+        ("macd_signal", result.macd_signal),
+        ("macd_histogram", result.macd_histogram),
+        ("obv", result.obv),
+        ("vwap", result.vwap),
+        ("volume_vs_avg", result.volume_vs_avg),
+        ("adx", result.adx),
+    ];
+    for (name, value) in values {
+        if value.is_some_and(|value| !value.is_finite()) {
+            anomalies.push(format!("{} is not a finite number", name));
+        }
+    }
+
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opportunity_state::OpportunityState;
+    use crate::resample::Timeframe;
+    use std::collections::HashMap;
+
+    fn result(price: f64, rsi: f64, pct_change: f64, volume: u64) -> StockAnalysisResult {
+        StockAnalysisResult {
+            ticker: "ODD".to_string(),
+            name: "Odd Inc".to_string(),
+            current_price: Some(price),
+            rsi: Some(rsi),
+            sma_20: Some(price),
+            sma_50: Some(price),
+            macd: None,
+            macd_signal: None,
+            macd_histogram: None,
+            volume: Some(volume),
+            pct_change: Some(pct_change),
+            market_cap: None,
+            sector: None,
+            industry: None,
+            exchange: None,
+            is_opportunity: false,
+            signals: Vec::new(),
+            timestamp: chrono::Utc::now(),
+            data_source: None,
+            data_fetched_at: None,
+            summary: None,
+            analyst_target: None,
+            upside_to_target_pct: None,
+            obv: None,
+            vwap: None,
+            volume_vs_avg: Some(1.0),
+            high_52w: None,
+            low_52w: None,
+            pct_from_high: None,
+            pct_from_low: None,
+            range_window_complete: true,
+            rs_1w: None,
+            rs_1m: None,
+            rs_3m: None,
+            rs_rank: None,
+            moving_averages: HashMap::new(),
+            crossovers: Vec::new(),
+            data_quality: None,
+            adx: None,
+            plus_di: None,
+            minus_di: None,
+            last_split: None,
+            timeframe: Timeframe::Daily,
+            anomalies: Vec::new(),
+            rsi_oversold_used: None,
+            rsi_overbought_used: None,
+            rsi_sector_zscore: None,
+            rsi_industry_zscore: None,
+            note: None,
+            opportunity_state: OpportunityState::None,
+            opportunity_streak: 0,
+            opportunity_since: None,
+            custom: HashMap::new(),
+            bars_used: None,
+        }
+    }
+
+    #[test]
+    fn test_believable_result_has_no_anomalies() {
+        let bounds = SanityBounds::default();
+        assert!(detect_anomalies(&result(42.0, 55.0, -3.5, 1_000_000), &bounds).is_empty());
+
+        let missing = StockAnalysisResult { current_price: None, rsi: None, pct_change: None, volume: None, ..result(1.0, 50.0, 0.0, 0) };
+        assert!(detect_anomalies(&missing, &bounds).is_empty(), "missing values are not anomalies");
+    }
+
+    #[test]
+    fn test_each_bound_is_reported() {
+        let bounds = SanityBounds::default();
+        let anomalies = detect_anomalies(&result(0.0001, 55.0, 14_000.0, 20_000_000_000), &bounds);
+        assert_eq!(
+            anomalies,
+            [
+                "price 0.0001 is below 0.001",
+                "pct_change 14000.0% is beyond ±100% without the volume to support it",
+                "volume 20000000000 is not below 10000000000",
+            ]
+        );
+
+        assert_eq!(detect_anomalies(&result(-2.0, 101.5, 0.0, 0), &bounds), ["price -2 is not positive", "RSI 101.50 is outside 0-100"]);
+    }
+
+    #[test]
+    fn test_non_finite_values_are_anomalies() {
+        let bounds = SanityBounds::default();
+        let nan = StockAnalysisResult { sma_20: None, sma_50: None, macd: Some(f64::INFINITY), ..result(f64::NAN, f64::NAN, 0.0, 0) };
+        assert_eq!(
+            detect_anomalies(&nan, &bounds),
+            ["price is not a finite number", "RSI is not a finite number", "macd is not a finite number"]
+        );
+    }
+
+    #[test]
+    fn test_heavy_volume_supports_a_big_move() {
+        let bounds = SanityBounds::default();
+        let squeeze = StockAnalysisResult { volume_vs_avg: Some(12.0), ..result(8.0, 88.0, 240.0, 50_000_000) };
+        assert!(detect_anomalies(&squeeze, &bounds).is_empty());
+
+        let strict = SanityBounds { confirming_volume_vs_avg: 20.0, ..bounds };
+        assert_eq!(detect_anomalies(&squeeze, &strict).len(), 1);
+    }
+
+    #[test]
+    fn test_policy_parses() {
+        assert_eq!("quarantine".parse::<AnomalyPolicy>(), Ok(AnomalyPolicy::Quarantine));
+        assert_eq!(AnomalyPolicy::default().as_str(), "flag");
+        assert!("ignore".parse::<AnomalyPolicy>().is_err());
+        assert!(SanityBounds { max_volume: 0, ..SanityBounds::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Entries `GET /api/audit` returns when no `limit` is given
+pub const DEFAULT_AUDIT_LIMIT: u32 = 50;
+/// Most entries `GET /api/audit` returns
+pub const MAX_AUDIT_LIMIT: u32 = 1000;
+
+/// API resource whose changes are audited, named like its endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditResource {
+    Watchlist,
+    Alerts,
+    Presets,
+    Notes,
+    CustomIndicators,
+}
+
+impl AuditResource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditResource::Watchlist => "watchlist",
+            AuditResource::Alerts => "alerts",
+            AuditResource::Presets => "presets",
+            AuditResource::Notes => "notes",
+            AuditResource::CustomIndicators => "custom_indicators",
+        }
+    }
+}
+
+impl FromStr for AuditResource {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "watchlist" => Ok(AuditResource::Watchlist),
+            "alerts" => Ok(AuditResource::Alerts),
+            "presets" => Ok(AuditResource::Presets),
+            "notes" => Ok(AuditResource::Notes),
+            "custom_indicators" => Ok(AuditResource::CustomIndicators),
+            other => Err(format!("unknown resource: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    /// A soft delete; the row stays until restored
+    Delete,
+    Restore,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+            AuditAction::Restore => "restore",
+        }
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "create" => Ok(AuditAction::Create),
+            "update" => Ok(AuditAction::Update),
+            "delete" => Ok(AuditAction::Delete),
+            "restore" => Ok(AuditAction::Restore),
+            other => Err(format!("unknown audit action: {}", other)),
+        }
+    }
+}
+
+/// One change made through the API, written in the same transaction as
+/// the change itself
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub resource: AuditResource,
+    /// Watchlist symbol, alert or note id, or preset or custom indicator name
+    pub resource_id: String,
+    pub action: AuditAction,
+    /// The resource before the change; `None` when it was created
+    pub old_value: Option<serde_json::Value>,
+    /// The resource after the change, soft-deleted ones with their `deleted_at`
+    pub new_value: Option<serde_json::Value>,
+    /// Address of the client that made the change, when the server knows it
+    pub source_ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
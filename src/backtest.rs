@@ -0,0 +1,413 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::analyzer::{compute_indicators, StockData, StockFilter, TechnicalIndicators};
+
+/// When to open a position, checked against each bar's close and indicators
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntryRule {
+    /// Enter only while RSI is strictly below this (the oversold threshold)
+    pub rsi_below: Option<f64>,
+    pub min_rsi: Option<f64>,
+    pub max_rsi: Option<f64>,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    pub min_volume: Option<u64>,
+    pub max_volume: Option<u64>,
+    /// Bounds on the close-to-close change from the previous bar, in percent
+    pub min_pct_change: Option<f64>,
+    pub max_pct_change: Option<f64>,
+}
+
+impl EntryRule {
+    /// Enter whenever RSI drops below `threshold`
+    pub fn rsi_below(threshold: f64) -> Self {
+        Self {
+            rsi_below: Some(threshold),
+            ..Self::default()
+        }
+    }
+
+    /// The per-bar conditions of `filter`: its RSI range and oversold
+    /// threshold, price, volume and percentage change. Fields describing the
+    /// company rather than the bar (market cap, sector, beta, ...) don't
+    /// change over the series and are ignored, as is the overbought
+    /// threshold, which marks a sell rather than a buy.
+    pub fn from_filter(filter: &StockFilter) -> Self {
+        Self {
+            rsi_below: filter.oversold_rsi_threshold,
+            min_rsi: filter.min_rsi,
+            max_rsi: filter.max_rsi,
+            min_price: filter.min_price,
+            max_price: filter.max_price,
+            min_volume: filter.min_volume,
+            max_volume: filter.max_volume,
+            min_pct_change: filter.min_pct_change,
+            max_pct_change: filter.max_pct_change,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if *self == Self::default() {
+            return Err("entry rule has no conditions; set an RSI, price, volume or percentage change bound".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether a position should be opened at `bar`, given the bar before it
+    pub fn is_met(&self, bar: &StockData, indicators: &TechnicalIndicators, previous: Option<&StockData>) -> bool {
+        fn within<T: PartialOrd + Copy>(value: Option<T>, min: Option<T>, max: Option<T>) -> bool {
+            if min.is_none() && max.is_none() {
+                return true;
+            }
+            value.is_some_and(|v| min.is_none_or(|min| v >= min) && max.is_none_or(|max| v <= max))
+        }
+
+        let pct_change = previous
+            .filter(|previous| previous.close > 0.0)
+            .map(|previous| (bar.close - previous.close) / previous.close * 100.0);
+
+        self.rsi_below.is_none_or(|threshold| indicators.rsi.is_some_and(|rsi| rsi < threshold))
+            && within(indicators.rsi, self.min_rsi, self.max_rsi)
+            && within(Some(bar.close), self.min_price, self.max_price)
+            && within(Some(bar.volume), self.min_volume, self.max_volume)
+            && within(pct_change, self.min_pct_change, self.max_pct_change)
+    }
+}
+
+/// When to close a position. Every limit is checked against the close of
+/// each bar after entry; the first one reached closes the trade at that close.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExitRule {
+    pub take_profit_pct: Option<f64>,
+    pub stop_loss_pct: Option<f64>,
+    /// Calendar days after entry
+    pub max_holding_days: Option<u32>,
+}
+
+impl ExitRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if *self == Self::default() {
+            return Err("exit rule needs take_profit_pct, stop_loss_pct or max_holding_days".to_string());
+        }
+        for (name, value) in [("take_profit_pct", self.take_profit_pct), ("stop_loss_pct", self.stop_loss_pct)] {
+            if let Some(v) = value {
+                if !(v.is_finite() && v > 0.0) {
+                    return Err(format!("{} must be a positive number (got {})", name, v));
+                }
+            }
+        }
+        if self.max_holding_days == Some(0) {
+            return Err("max_holding_days must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    fn reason_to_exit(&self, entry: &StockData, bar: &StockData) -> Option<ExitReason> {
+        let return_pct = (bar.close - entry.close) / entry.close * 100.0;
+        if self.stop_loss_pct.is_some_and(|stop| return_pct <= -stop) {
+            Some(ExitReason::StopLoss)
+        } else if self.take_profit_pct.is_some_and(|target| return_pct >= target) {
+            Some(ExitReason::TakeProfit)
+        } else if self
+            .max_holding_days
+            .is_some_and(|days| (bar.timestamp - entry.timestamp).num_days() >= i64::from(days))
+        {
+            Some(ExitReason::MaxHoldingDays)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitReason {
+    TakeProfit,
+    StopLoss,
+    MaxHoldingDays,
+    /// Still open at the end of the series; closed at the last close
+    EndOfData,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trade {
+    pub entry_time: DateTime<Utc>,
+    pub entry_price: f64,
+    pub exit_time: DateTime<Utc>,
+    pub exit_price: f64,
+    pub return_pct: f64,
+    pub exit_reason: ExitReason,
+}
+
+/// Value of one unit of starting capital at a bar, marked to the close
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub timestamp: DateTime<Utc>,
+    pub equity: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub symbol: String,
+    pub trade_count: usize,
+    /// Percentage of trades closed above their entry; `None` without trades
+    pub win_rate_pct: Option<f64>,
+    pub average_return_pct: Option<f64>,
+    /// Compounded return of taking every trade with all capital
+    pub total_return_pct: f64,
+    /// Largest peak-to-trough fall of the equity curve, in percent
+    pub max_drawdown_pct: f64,
+    pub trades: Vec<Trade>,
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+/// Replays an entry and exit rule over a symbol's daily history.
+///
+/// A position is opened at the close of a bar meeting the entry rule and
+/// held until the exit rule fires. Signals while a position is open are
+/// ignored. Indicators are computed over the whole series, so bars before
+/// the date range still warm them up.
+#[derive(Debug, Clone)]
+pub struct Backtester {
+    entry: EntryRule,
+    exit: ExitRule,
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+}
+
+impl Backtester {
+    pub fn new(entry: EntryRule, exit: ExitRule) -> Self {
+        Self {
+            entry,
+            exit,
+            start_date: None,
+            end_date: None,
+        }
+    }
+
+    /// Only trade on bars within `start..=end` (either bound optional)
+    pub fn with_date_range(mut self, start: Option<NaiveDate>, end: Option<NaiveDate>) -> Self {
+        self.start_date = start;
+        self.end_date = end;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.entry.validate()?;
+        self.exit.validate()?;
+        if let (Some(start), Some(end)) = (self.start_date, self.end_date) {
+            if start > end {
+                return Err(format!("start_date ({}) is after end_date ({})", start, end));
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the rules over `data`, which must be in chronological order
+    pub fn run(&self, data: &[StockData]) -> BacktestReport {
+        self.simulate(data, &compute_indicators(data))
+    }
+
+    fn in_range(&self, bar: &StockData) -> bool {
+        let date = bar.timestamp.date_naive();
+        self.start_date.is_none_or(|start| date >= start) && self.end_date.is_none_or(|end| date <= end)
+    }
+
+    fn simulate(&self, data: &[StockData], indicators: &[TechnicalIndicators]) -> BacktestReport {
+        let mut trades = Vec::new();
+        let mut equity_curve = Vec::new();
+        let mut capital = 1.0;
+        let mut open: Option<&StockData> = None;
+        let mut last_bar: Option<&StockData> = None;
+
+        for (i, (bar, bar_indicators)) in data.iter().zip(indicators).enumerate() {
+            if !self.in_range(bar) {
+                continue;
+            }
+            last_bar = Some(bar);
+
+            match open {
+                Some(entry) => {
+                    if let Some(reason) = self.exit.reason_to_exit(entry, bar) {
+                        let trade = close_trade(entry, bar, reason);
+                        capital *= 1.0 + trade.return_pct / 100.0;
+                        trades.push(trade);
+                        open = None;
+                    }
+                }
+                None => {
+                    let previous = i.checked_sub(1).map(|p| &data[p]);
+                    if self.entry.is_met(bar, bar_indicators, previous) {
+                        open = Some(bar);
+                    }
+                }
+            }
+
+            let equity = match open {
+                Some(entry) => capital * bar.close / entry.close,
+                None => capital,
+            };
+            equity_curve.push(EquityPoint {
+                timestamp: bar.timestamp,
+                equity,
+            });
+        }
+
+        if let (Some(entry), Some(last)) = (open, last_bar) {
+            let trade = close_trade(entry, last, ExitReason::EndOfData);
+            capital *= 1.0 + trade.return_pct / 100.0;
+            trades.push(trade);
+        }
+
+        let returns: Vec<f64> = trades.iter().map(|trade| trade.return_pct).collect();
+        let (win_rate_pct, average_return_pct) = if returns.is_empty() {
+            (None, None)
+        } else {
+            let wins = returns.iter().filter(|&&r| r > 0.0).count();
+            (
+                Some(wins as f64 / returns.len() as f64 * 100.0),
+                Some(returns.iter().sum::<f64>() / returns.len() as f64),
+            )
+        };
+
+        BacktestReport {
+            symbol: data.first().map(|bar| bar.symbol.clone()).unwrap_or_default(),
+            trade_count: trades.len(),
+            win_rate_pct,
+            average_return_pct,
+            total_return_pct: (capital - 1.0) * 100.0,
+            max_drawdown_pct: max_drawdown_pct(&equity_curve),
+            trades,
+            equity_curve,
+        }
+    }
+}
+
+fn close_trade(entry: &StockData, exit: &StockData, exit_reason: ExitReason) -> Trade {
+    Trade {
+        entry_time: entry.timestamp,
+        entry_price: entry.close,
+        exit_time: exit.timestamp,
+        exit_price: exit.close,
+        return_pct: (exit.close - entry.close) / entry.close * 100.0,
+        exit_reason,
+    }
+}
+
+fn max_drawdown_pct(curve: &[EquityPoint]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut worst: f64 = 0.0;
+    for point in curve {
+        peak = peak.max(point.equity);
+        if peak > 0.0 {
+            worst = worst.max((peak - point.equity) / peak * 100.0);
+        }
+    }
+    worst
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn day(i: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(i)
+    }
+
+    /// Bars with the given closes one day apart, and an RSI series alongside
+    fn series(bars: &[(f64, Option<f64>)]) -> (Vec<StockData>, Vec<TechnicalIndicators>) {
+        bars.iter()
+            .enumerate()
+            .map(|(i, &(close, rsi))| {
+                let bar = StockData {
+                    symbol: "BT".to_string(),
+                    timestamp: day(i as i64),
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: 1_000,
+                    adjusted_close: None,
+                };
+                (bar, TechnicalIndicators { rsi, ..Default::default() })
+            })
+            .unzip()
+    }
+
+    fn exit(take_profit: Option<f64>, stop_loss: Option<f64>, max_days: Option<u32>) -> ExitRule {
+        ExitRule {
+            take_profit_pct: take_profit,
+            stop_loss_pct: stop_loss,
+            max_holding_days: max_days,
+        }
+    }
+
+    #[test]
+    fn test_take_profit_ignores_signals_while_open() {
+        let (data, indicators) = series(&[(100.0, Some(20.0)), (101.0, Some(20.0)), (111.0, Some(50.0)), (112.0, Some(50.0))]);
+        let report = Backtester::new(EntryRule::rsi_below(30.0), exit(Some(10.0), None, None)).simulate(&data, &indicators);
+
+        assert_eq!(report.trade_count, 1, "the second oversold bar falls inside the open trade");
+        let trade = &report.trades[0];
+        assert_eq!((trade.entry_price, trade.exit_price), (100.0, 111.0));
+        assert_eq!(trade.exit_reason, ExitReason::TakeProfit);
+        assert!((report.total_return_pct - 11.0).abs() < 1e-9);
+        assert_eq!(report.win_rate_pct, Some(100.0));
+        assert_eq!(report.equity_curve.len(), 4);
+    }
+
+    #[test]
+    fn test_stop_loss_holding_limit_and_end_of_data() {
+        let (data, indicators) = series(&[
+            (100.0, Some(25.0)),
+            (94.0, Some(25.0)),
+            (95.0, Some(25.0)),
+            (96.0, None),
+            (97.0, None),
+            (99.0, Some(25.0)),
+            (104.0, None),
+        ]);
+        let report = Backtester::new(EntryRule::rsi_below(30.0), exit(Some(50.0), Some(5.0), Some(2))).simulate(&data, &indicators);
+
+        let reasons: Vec<ExitReason> = report.trades.iter().map(|trade| trade.exit_reason).collect();
+        assert_eq!(reasons, vec![ExitReason::StopLoss, ExitReason::MaxHoldingDays, ExitReason::EndOfData]);
+        assert_eq!(report.trades[1].exit_time, day(4));
+        assert_eq!(report.trades[2].exit_price, 104.0, "closed at the last close");
+
+        assert!((report.win_rate_pct.unwrap() - 200.0 / 3.0).abs() < 1e-9);
+        // 100 -> 94 is the deepest fall from the starting peak
+        assert!((report.max_drawdown_pct - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_date_range_and_validation() {
+        let (data, indicators) = series(&[(100.0, Some(20.0)), (90.0, Some(50.0)), (80.0, Some(20.0)), (88.0, Some(50.0))]);
+        let backtester = Backtester::new(EntryRule::rsi_below(30.0), exit(Some(5.0), None, None))
+            .with_date_range(Some(day(2).date_naive()), None);
+        let report = backtester.simulate(&data, &indicators);
+        assert_eq!(report.trade_count, 1);
+        assert_eq!(report.trades[0].entry_price, 80.0);
+        assert_eq!(report.equity_curve.len(), 2);
+
+        assert!(Backtester::new(EntryRule::default(), exit(Some(5.0), None, None)).validate().is_err());
+        assert!(Backtester::new(EntryRule::rsi_below(30.0), ExitRule::default()).validate().is_err());
+        assert!(Backtester::new(EntryRule::rsi_below(30.0), exit(Some(-1.0), None, None)).validate().is_err());
+        assert!(backtester.with_date_range(Some(day(3).date_naive()), Some(day(1).date_naive())).validate().is_err());
+
+        let filter = StockFilter::default().with_price_range(Some(10.0), None);
+        let entry = EntryRule::from_filter(&filter);
+        assert_eq!((entry.rsi_below, entry.min_price), (Some(30.0), Some(10.0)));
+    }
+
+    #[test]
+    fn test_no_trades() {
+        let (data, indicators) = series(&[(100.0, Some(50.0)), (101.0, None)]);
+        let report = Backtester::new(EntryRule::rsi_below(30.0), exit(Some(5.0), None, None)).simulate(&data, &indicators);
+        assert_eq!(report.trade_count, 0);
+        assert_eq!(report.win_rate_pct, None);
+        assert_eq!(report.total_return_pct, 0.0);
+        assert_eq!(report.max_drawdown_pct, 0.0);
+    }
+}
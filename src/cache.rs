@@ -1,57 +1,359 @@
+use anyhow::Context;
 use dashmap::DashMap;
 use moka::future::Cache;
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::time::Instant;
+use tokio::sync::watch;
 
+use crate::clock::{system_clock, to_chrono, SharedClock};
+use crate::error::{AnalyzerError, Result};
+use crate::indicators::incremental::IndicatorSnapshot;
+use crate::providers::{AnalystTarget, QuoteSeries};
+use crate::rate_limit::{RateLimitConfig, RateLimiter, RateLimiterStats};
+use crate::stats::{FieldStats, Seasonality};
 use crate::{StockData, TechnicalIndicators, TickerInfo};
 
+/// How long fetched data stays fresh, in seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheTtls {
+    pub stock_data_secs: u64,
+    pub indicators_secs: u64,
+    pub tickers_secs: u64,
+    /// How long past `tickers_secs` an expired ticker list is still served
+    /// while one refresh runs in the background; older lists wait for it
+    pub tickers_max_stale_secs: u64,
+}
+
+impl Default for CacheTtls {
+    fn default() -> Self {
+        Self {
+            stock_data_secs: 300, // 5 minutes
+            indicators_secs: 300, // 5 minutes
+            tickers_secs: 3600,   // 1 hour
+            tickers_max_stale_secs: 6 * 3600, // 6 hours
+        }
+    }
+}
+
+impl CacheTtls {
+    pub fn stock_data(&self) -> Duration {
+        Duration::from_secs(self.stock_data_secs)
+    }
+
+    pub fn indicators(&self) -> Duration {
+        Duration::from_secs(self.indicators_secs)
+    }
+
+    pub fn tickers(&self) -> Duration {
+        Duration::from_secs(self.tickers_secs)
+    }
+
+    pub fn tickers_max_stale(&self) -> Duration {
+        Duration::from_secs(self.tickers_max_stale_secs)
+    }
+
+    /// How long a ticker list is kept at all: fresh, then stale
+    fn tickers_retention(&self) -> Duration {
+        self.tickers() + self.tickers_max_stale()
+    }
+}
+
+/// Cache key of the full ticker listing
+pub const ALL_TICKERS_KEY: &str = "all_tickers";
+
+/// Longest a fetch shared by concurrent callers may take before they all
+/// get a timeout error
+pub const DEFAULT_FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Outcome of an in-flight fetch; `None` until the fetching caller finishes
+type InFlightResult = Option<Result<QuoteSeries>>;
+
+/// Outcome of a ticker list refresh; `None` until it finishes
+type TickerRefreshResult = Option<Result<(Vec<TickerInfo>, DateTime<Utc>)>>;
+
+/// How a cached ticker list compares with its TTL and `max_stale` bound
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Freshness {
+    Fresh,
+    /// Past its TTL but still served while it is refreshed
+    Stale,
+    /// Nothing cached, or too old to serve
+    Missing,
+}
+
+/// When the last ticker list refresh finished and how long it took
+#[derive(Debug, Clone, Copy)]
+struct RefreshRecord {
+    finished_at: DateTime<Utc>,
+    duration: Duration,
+}
+
 #[derive(Clone)]
 pub struct CacheManager {
-    stock_data_cache: Cache<String, (Vec<StockData>, Instant)>,
-    indicators_cache: Cache<String, (Vec<TechnicalIndicators>, Instant)>,
-    tickers_cache: Cache<String, (Vec<TickerInfo>, Instant)>,
-    rate_limiter: Arc<DashMap<String, Instant>>,
+    stock_data_cache: Cache<String, (QuoteSeries, DateTime<Utc>)>,
+    indicators_cache: Cache<String, (Vec<TechnicalIndicators>, DateTime<Utc>)>,
+    tickers_cache: Cache<String, (Vec<TickerInfo>, DateTime<Utc>)>,
+    field_stats_cache: Cache<String, (Vec<FieldStats>, DateTime<Utc>)>,
+    indicator_state_cache: Cache<String, IndicatorSnapshot>,
+    analyst_target_cache: Cache<String, Option<AnalystTarget>>,
+    seasonality_cache: Cache<String, Seasonality>,
+    rate_limiter: Arc<DashMap<String, DateTime<Utc>>>,
+    request_limiter: Arc<RateLimiter>,
+    last_persisted_at: Arc<Mutex<Option<DateTime<Utc>>>>,
+    /// Quote series fetches under way, by cache key
+    in_flight: Arc<Mutex<HashMap<String, watch::Receiver<InFlightResult>>>>,
+    /// Ticker list refreshes under way, by cache key
+    ticker_refreshes: Arc<Mutex<HashMap<String, watch::Receiver<TickerRefreshResult>>>>,
+    last_ticker_refresh: Arc<Mutex<Option<RefreshRecord>>>,
+    fetch_timeout: Duration,
+    ttls: CacheTtls,
+    clock: SharedClock,
+}
+
+/// Removes a key from the in-flight map when its fetch ends or is dropped,
+/// so waiters on a cancelled fetch see the channel close and retry
+struct InFlightGuard<'a> {
+    in_flight: &'a Mutex<HashMap<String, watch::Receiver<InFlightResult>>>,
+    key: &'a str,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.in_flight.lock().unwrap().remove(self.key);
+    }
+}
+
+/// One cache entry as written by `persist_to_disk`, stamped with the time
+/// it was originally cached so freshness survives the round trip
+#[derive(Serialize, Deserialize)]
+struct PersistedEntry<T> {
+    key: String,
+    cached_at: DateTime<Utc>,
+    value: T,
+}
+
+/// On-disk form of the caches worth keeping warm across restarts
+#[derive(Serialize, Deserialize)]
+struct PersistedCache {
+    saved_at: DateTime<Utc>,
+    tickers: Vec<PersistedEntry<Vec<TickerInfo>>>,
+    stock_data: Vec<PersistedEntry<QuoteSeries>>,
+}
+
+impl Default for CacheManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CacheManager {
     pub fn new() -> Self {
+        Self::with_clock(system_clock())
+    }
+
+    /// Judge freshness and rate limits by `clock` instead of the system time
+    pub fn with_clock(clock: SharedClock) -> Self {
+        let ttls = CacheTtls::default();
         Self {
             stock_data_cache: Cache::builder()
-                .time_to_live(Duration::from_secs(300)) // 5 minutes
+                .time_to_live(ttls.stock_data())
                 .max_capacity(1000)
                 .build(),
             indicators_cache: Cache::builder()
-                .time_to_live(Duration::from_secs(300)) // 5 minutes
+                .time_to_live(ttls.indicators())
                 .max_capacity(1000)
                 .build(),
             tickers_cache: Cache::builder()
-                .time_to_live(Duration::from_secs(3600)) // 1 hour
+                .time_to_live(ttls.tickers_retention())
+                .max_capacity(10)
+                .build(),
+            field_stats_cache: Cache::builder()
+                .time_to_live(Duration::from_secs(30)) // 30 seconds
                 .max_capacity(10)
                 .build(),
+            // Retained across analysis cycles; dropped once a symbol stops being analyzed
+            indicator_state_cache: Cache::builder()
+                .time_to_idle(Duration::from_secs(3600)) // 1 hour
+                .max_capacity(1000)
+                .build(),
+            // Targets move slowly; "no coverage" answers are cached too
+            analyst_target_cache: Cache::builder()
+                .time_to_live(Duration::from_secs(24 * 3600)) // 24 hours
+                .max_capacity(10_000)
+                .build(),
+            // Years of history barely move a day's statistics
+            seasonality_cache: Cache::builder()
+                .time_to_live(Duration::from_secs(24 * 3600)) // 24 hours
+                .max_capacity(1000)
+                .build(),
             rate_limiter: Arc::new(DashMap::new()),
+            request_limiter: Arc::new(RateLimiter::new(RateLimitConfig::default(), clock.clone())),
+            last_persisted_at: Arc::new(Mutex::new(None)),
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+            ticker_refreshes: Arc::new(Mutex::new(HashMap::new())),
+            last_ticker_refresh: Arc::new(Mutex::new(None)),
+            fetch_timeout: DEFAULT_FETCH_TIMEOUT,
+            ttls,
+            clock,
         }
     }
 
+    /// Change how long stock data, indicators and ticker lists stay fresh.
+    /// Those caches are rebuilt, so call this before anything has been cached.
+    pub fn with_ttls(mut self, ttls: CacheTtls) -> Self {
+        self.stock_data_cache = Cache::builder()
+            .time_to_live(ttls.stock_data())
+            .max_capacity(1000)
+            .build();
+        self.indicators_cache = Cache::builder()
+            .time_to_live(ttls.indicators())
+            .max_capacity(1000)
+            .build();
+        self.tickers_cache = Cache::builder()
+            .time_to_live(ttls.tickers_retention())
+            .max_capacity(10)
+            .build();
+        self.ttls = ttls;
+        self
+    }
+
+    /// Replace the global upstream request limiter
+    pub fn with_rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.request_limiter = Arc::new(RateLimiter::new(config, self.clock.clone()));
+        self
+    }
+
+    /// Change how long a shared fetch may take, see `DEFAULT_FETCH_TIMEOUT`
+    pub fn with_fetch_timeout(mut self, timeout: Duration) -> Self {
+        self.fetch_timeout = timeout;
+        self
+    }
+
+    pub fn clock(&self) -> &SharedClock {
+        &self.clock
+    }
+
+    pub fn ttls(&self) -> &CacheTtls {
+        &self.ttls
+    }
+
+    /// Limiter shared by every quote request made through this cache
+    pub fn request_limiter(&self) -> &Arc<RateLimiter> {
+        &self.request_limiter
+    }
+
+    fn is_fresh(&self, cached_at: DateTime<Utc>, max_age: Duration) -> bool {
+        (self.clock.now() - cached_at).to_std().is_ok_and(|age| age < max_age)
+    }
+
     pub async fn get_stock_data(&self, key: &str) -> Option<Vec<StockData>> {
-        if let Some((data, cached_at)) = self.stock_data_cache.get(key).await {
-            // Check if cache is still fresh (less than 5 minutes old)
-            if cached_at.elapsed() < Duration::from_secs(300) {
+        self.get_quote_series(key).await.map(|series| series.bars)
+    }
+
+    pub async fn cache_stock_data(&self, key: String, data: Vec<StockData>) {
+        let series = QuoteSeries {
+            bars: data,
+            source: "unknown".to_string(),
+            fetched_at: self.clock.now(),
+            quality: None,
+            events: Vec::new(),
+        };
+        self.cache_quote_series(key, series).await;
+    }
+
+    /// Cached bars together with the provider that served them
+    pub async fn get_quote_series(&self, key: &str) -> Option<QuoteSeries> {
+        if let Some((series, cached_at)) = self.stock_data_cache.get(key).await {
+            if self.is_fresh(cached_at, self.ttls.stock_data()) {
                 tracing::debug!("Cache hit for stock data: {}", key);
-                return Some(data);
+                return Some(series);
             }
         }
         None
     }
 
-    pub async fn cache_stock_data(&self, key: String, data: Vec<StockData>) {
-        tracing::debug!("Caching stock data: {}", key);
-        self.stock_data_cache.insert(key, (data, Instant::now())).await;
+    /// Whether `get_quote_series` would find fresh bars for `key`
+    pub async fn has_quote_series(&self, key: &str) -> bool {
+        match self.stock_data_cache.get(key).await {
+            Some((_, cached_at)) => self.is_fresh(cached_at, self.ttls.stock_data()),
+            None => false,
+        }
+    }
+
+    /// When the bars cached for `key` stop being served, if any are cached
+    pub async fn quote_series_fresh_until(&self, key: &str) -> Option<DateTime<Utc>> {
+        let (_, cached_at) = self.stock_data_cache.get(key).await?;
+        Some(cached_at + to_chrono(self.ttls.stock_data()))
+    }
+
+    pub async fn cache_quote_series(&self, key: String, series: QuoteSeries) {
+        tracing::debug!("Caching stock data: {} (from {})", key, series.source);
+        self.stock_data_cache.insert(key, (series, self.clock.now())).await;
+    }
+
+    /// Run `fetch` for `key` unless another caller is already fetching it,
+    /// in which case wait for that fetch and return a copy of its result,
+    /// error included. Nothing is cached here; a fetch that takes longer
+    /// than the fetch timeout fails for everyone waiting on it.
+    pub async fn coalesce_quote_series<F>(&self, key: &str, fetch: F) -> Result<QuoteSeries>
+    where
+        F: Future<Output = Result<QuoteSeries>>,
+    {
+        let mut fetch = Some(fetch);
+        loop {
+            let joined = {
+                let mut in_flight = self.in_flight.lock().unwrap();
+                match in_flight.get(key) {
+                    Some(receiver) => Err(receiver.clone()),
+                    None => {
+                        let (sender, receiver) = watch::channel(None);
+                        in_flight.insert(key.to_string(), receiver);
+                        Ok(sender)
+                    }
+                }
+            };
+
+            match joined {
+                Ok(sender) => {
+                    let guard = InFlightGuard { in_flight: &self.in_flight, key };
+                    let fetch = fetch.take().expect("a caller leads at most one fetch");
+                    let result = tokio::select! {
+                        result = fetch => result,
+                        _ = self.clock.sleep(self.fetch_timeout) => Err(AnalyzerError::Upstream(format!(
+                            "fetch of {} timed out after {:?}",
+                            key, self.fetch_timeout
+                        ))),
+                    };
+                    drop(guard);
+                    sender.send_replace(Some(result.clone()));
+                    return result;
+                }
+                Err(mut receiver) => {
+                    tracing::debug!("Joining in-flight fetch of {}", key);
+                    if let Ok(outcome) = receiver.wait_for(Option::is_some).await {
+                        return outcome.clone().expect("waited for an outcome");
+                    }
+                    // The fetching caller was dropped before finishing; take over
+                }
+            }
+        }
+    }
+
+    /// Keys with a fetch under way
+    pub fn in_flight_fetches(&self) -> usize {
+        self.in_flight.lock().unwrap().len()
     }
 
     pub async fn get_indicators(&self, key: &str) -> Option<Vec<TechnicalIndicators>> {
         if let Some((indicators, cached_at)) = self.indicators_cache.get(key).await {
-            if cached_at.elapsed() < Duration::from_secs(300) {
+            if self.is_fresh(cached_at, self.ttls.indicators()) {
                 tracing::debug!("Cache hit for indicators: {}", key);
                 return Some(indicators);
             }
@@ -61,14 +363,19 @@ impl CacheManager {
 
     pub async fn cache_indicators(&self, key: String, indicators: Vec<TechnicalIndicators>) {
         tracing::debug!("Caching indicators: {}", key);
-        self.indicators_cache.insert(key, (indicators, Instant::now())).await;
+        self.indicators_cache.insert(key, (indicators, self.clock.now())).await;
     }
 
     pub async fn get_tickers(&self, key: &str) -> Option<Vec<TickerInfo>> {
+        self.get_tickers_with_timestamp(key).await.map(|(tickers, _)| tickers)
+    }
+
+    /// Cached tickers together with the time they were cached
+    pub async fn get_tickers_with_timestamp(&self, key: &str) -> Option<(Vec<TickerInfo>, DateTime<Utc>)> {
         if let Some((tickers, cached_at)) = self.tickers_cache.get(key).await {
-            if cached_at.elapsed() < Duration::from_secs(3600) {
+            if self.is_fresh(cached_at, self.ttls.tickers()) {
                 tracing::debug!("Cache hit for tickers: {}", key);
-                return Some(tickers);
+                return Some((tickers, cached_at));
             }
         }
         None
@@ -76,35 +383,254 @@ impl CacheManager {
 
     pub async fn cache_tickers(&self, key: String, tickers: Vec<TickerInfo>) {
         tracing::debug!("Caching tickers: {}", key);
-        self.tickers_cache.insert(key, (tickers, Instant::now())).await;
+        self.tickers_cache.insert(key, (tickers, self.clock.now())).await;
+    }
+
+    /// Where the ticker list under `key` stands against its TTL
+    pub async fn tickers_freshness(&self, key: &str) -> Freshness {
+        match self.tickers_cache.get(key).await {
+            Some((_, cached_at)) if self.is_fresh(cached_at, self.ttls.tickers()) => Freshness::Fresh,
+            Some((_, cached_at)) if self.is_fresh(cached_at, self.ttls.tickers_retention()) => Freshness::Stale,
+            _ => Freshness::Missing,
+        }
+    }
+
+    /// The ticker list under `key` and when it was cached, refreshed with
+    /// `fetch` when needed. A fresh list is returned as is. A stale one is
+    /// returned straight away while a background task refreshes it; with
+    /// nothing servable, callers wait for the refresh. Concurrent callers
+    /// share one refresh, and it runs to the end even if they go away.
+    pub async fn get_or_refresh_tickers<F, Fut>(&self, key: &str, fetch: F) -> Result<(Vec<TickerInfo>, DateTime<Utc>)>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<TickerInfo>>> + Send + 'static,
+    {
+        let cached = self.tickers_cache.get(key).await;
+        if let Some((tickers, cached_at)) = cached.clone().filter(|(_, at)| self.is_fresh(*at, self.ttls.tickers())) {
+            tracing::debug!("Cache hit for tickers: {}", key);
+            return Ok((tickers, cached_at));
+        }
+
+        let mut receiver = self.refresh_tickers(key, fetch);
+        if let Some((tickers, cached_at)) = cached.filter(|(_, at)| self.is_fresh(*at, self.ttls.tickers_retention())) {
+            tracing::debug!("Serving stale tickers for {} while they refresh", key);
+            return Ok((tickers, cached_at));
+        }
+        let outcome = receiver.wait_for(Option::is_some).await.map(|outcome| outcome.clone());
+        match outcome {
+            Ok(outcome) => outcome.expect("waited for an outcome"),
+            Err(_) => Err(AnalyzerError::Upstream(format!("refresh of {} was cancelled", key))),
+        }
+    }
+
+    /// Start refreshing the ticker list under `key` with `fetch`, or join
+    /// the refresh already under way
+    fn refresh_tickers<F, Fut>(&self, key: &str, fetch: F) -> watch::Receiver<TickerRefreshResult>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<TickerInfo>>> + Send + 'static,
+    {
+        let mut refreshes = self.ticker_refreshes.lock().unwrap();
+        if let Some(receiver) = refreshes.get(key) {
+            tracing::debug!("Joining in-flight ticker refresh of {}", key);
+            return receiver.clone();
+        }
+        let (sender, receiver) = watch::channel(None);
+        refreshes.insert(key.to_string(), receiver.clone());
+        drop(refreshes);
+
+        let cache = self.clone();
+        let key = key.to_string();
+        let fetch = fetch();
+        tokio::spawn(async move {
+            let started = std::time::Instant::now();
+            let result = tokio::select! {
+                result = fetch => result,
+                _ = cache.clock.sleep(cache.fetch_timeout) => Err(AnalyzerError::Upstream(format!(
+                    "refresh of {} timed out after {:?}",
+                    key, cache.fetch_timeout
+                ))),
+            };
+            let result = match result {
+                Ok(tickers) => {
+                    let cached_at = cache.clock.now();
+                    cache.tickers_cache.insert(key.clone(), (tickers.clone(), cached_at)).await;
+                    Ok((tickers, cached_at))
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to refresh tickers for {}: {}", key, e);
+                    Err(e)
+                }
+            };
+            *cache.last_ticker_refresh.lock().unwrap() =
+                Some(RefreshRecord { finished_at: cache.clock.now(), duration: started.elapsed() });
+            // Later callers find the new list in the cache rather than this refresh
+            cache.ticker_refreshes.lock().unwrap().remove(&key);
+            sender.send_replace(Some(result));
+        });
+        receiver
+    }
+
+    pub async fn get_field_stats(&self, key: &str) -> Option<Vec<FieldStats>> {
+        if let Some((stats, cached_at)) = self.field_stats_cache.get(key).await {
+            if self.is_fresh(cached_at, Duration::from_secs(30)) {
+                tracing::debug!("Cache hit for field stats: {}", key);
+                return Some(stats);
+            }
+        }
+        None
+    }
+
+    pub async fn cache_field_stats(&self, key: String, stats: Vec<FieldStats>) {
+        tracing::debug!("Caching field stats: {}", key);
+        self.field_stats_cache.insert(key, (stats, self.clock.now())).await;
+    }
+
+    /// Streaming indicator state for a symbol and indicator settings
+    pub async fn get_indicator_state(&self, key: &str) -> Option<IndicatorSnapshot> {
+        self.indicator_state_cache.get(key).await
+    }
+
+    pub async fn cache_indicator_state(&self, key: String, snapshot: IndicatorSnapshot) {
+        tracing::debug!("Retaining indicator state: {}", key);
+        self.indicator_state_cache.insert(key, snapshot).await;
+    }
+
+    /// Cached analyst target lookup; `Some(None)` means the symbol has no coverage
+    pub async fn get_analyst_target(&self, symbol: &str) -> Option<Option<AnalystTarget>> {
+        self.analyst_target_cache.get(symbol).await
+    }
+
+    pub async fn cache_analyst_target(&self, symbol: String, target: Option<AnalystTarget>) {
+        self.analyst_target_cache.insert(symbol, target).await;
+    }
+
+    /// Seasonality statistics computed from a symbol's daily history
+    pub async fn get_seasonality(&self, symbol: &str) -> Option<Seasonality> {
+        self.seasonality_cache.get(symbol).await
+    }
+
+    pub async fn cache_seasonality(&self, symbol: String, seasonality: Seasonality) {
+        self.seasonality_cache.insert(symbol, seasonality).await;
     }
 
     pub fn should_rate_limit(&self, identifier: &str, min_interval: Duration) -> bool {
         if let Some(last_request) = self.rate_limiter.get(identifier) {
-            if last_request.elapsed() < min_interval {
+            if self.is_fresh(*last_request, min_interval) {
                 tracing::warn!("Rate limiting request for: {}", identifier);
                 return true;
             }
         }
         
-        self.rate_limiter.insert(identifier.to_string(), Instant::now());
+        self.rate_limiter.insert(identifier.to_string(), self.clock.now());
         false
     }
 
+    /// Drop the cached ticker listing so the next fetch refreshes it
+    pub fn invalidate_tickers(&self) {
+        self.tickers_cache.invalidate_all();
+    }
+
     pub async fn clear_cache(&self) {
         tracing::info!("Clearing all caches");
         self.stock_data_cache.invalidate_all();
         self.indicators_cache.invalidate_all();
         self.tickers_cache.invalidate_all();
+        self.field_stats_cache.invalidate_all();
+        self.indicator_state_cache.invalidate_all();
+        self.analyst_target_cache.invalidate_all();
+        self.seasonality_cache.invalidate_all();
         self.rate_limiter.clear();
     }
 
+    /// Write the ticker lists and price histories that are still fresh to
+    /// `path` as JSON, returning how many entries were saved
+    pub async fn persist_to_disk(&self, path: impl AsRef<Path>) -> anyhow::Result<usize> {
+        let path = path.as_ref();
+        let snapshot = PersistedCache {
+            saved_at: self.clock.now(),
+            tickers: self.fresh_entries(&self.tickers_cache, self.ttls.tickers()),
+            stock_data: self.fresh_entries(&self.stock_data_cache, self.ttls.stock_data()),
+        };
+        let saved = snapshot.tickers.len() + snapshot.stock_data.len();
+
+        // Write beside the target and rename so a crash never leaves a torn file
+        let json = serde_json::to_vec(&snapshot)?;
+        let tmp_path = path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, json)
+            .await
+            .with_context(|| format!("writing {}", tmp_path.display()))?;
+        tokio::fs::rename(&tmp_path, path)
+            .await
+            .with_context(|| format!("replacing {}", path.display()))?;
+
+        *self.last_persisted_at.lock().unwrap() = Some(snapshot.saved_at);
+        tracing::info!("Persisted {} cache entries to {}", saved, path.display());
+        Ok(saved)
+    }
+
+    /// Repopulate the caches from a file written by `persist_to_disk`,
+    /// skipping entries whose TTL has run out since. A missing file loads
+    /// nothing; returns how many entries were restored.
+    pub async fn load_from_disk(&self, path: impl AsRef<Path>) -> anyhow::Result<usize> {
+        let path = path.as_ref();
+        let json = match tokio::fs::read(path).await {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+        };
+        let snapshot: PersistedCache =
+            serde_json::from_slice(&json).with_context(|| format!("parsing {}", path.display()))?;
+
+        let mut loaded = 0;
+        for entry in snapshot.tickers {
+            if self.is_fresh(entry.cached_at, self.ttls.tickers()) {
+                // Snapshots saved before the typed fields existed only carry the strings
+                let tickers = entry.value.into_iter().map(TickerInfo::with_parsed_fields).collect();
+                self.tickers_cache.insert(entry.key, (tickers, entry.cached_at)).await;
+                loaded += 1;
+            }
+        }
+        for entry in snapshot.stock_data {
+            if self.is_fresh(entry.cached_at, self.ttls.stock_data()) {
+                self.stock_data_cache.insert(entry.key, (entry.value, entry.cached_at)).await;
+                loaded += 1;
+            }
+        }
+        tracing::info!("Loaded {} cache entries from {}", loaded, path.display());
+        Ok(loaded)
+    }
+
+    fn fresh_entries<T: Clone + Send + Sync + 'static>(
+        &self,
+        cache: &Cache<String, (T, DateTime<Utc>)>,
+        max_age: Duration,
+    ) -> Vec<PersistedEntry<T>> {
+        cache
+            .iter()
+            .filter(|(_, (_, cached_at))| self.is_fresh(*cached_at, max_age))
+            .map(|(key, (value, cached_at))| PersistedEntry {
+                key: key.as_ref().clone(),
+                cached_at,
+                value,
+            })
+            .collect()
+    }
+
     pub async fn get_cache_stats(&self) -> CacheStats {
+        let last_ticker_refresh = *self.last_ticker_refresh.lock().unwrap();
         CacheStats {
             stock_data_entries: self.stock_data_cache.entry_count(),
             indicators_entries: self.indicators_cache.entry_count(),
             tickers_entries: self.tickers_cache.entry_count(),
+            tickers_freshness: self.tickers_freshness(ALL_TICKERS_KEY).await,
+            tickers_refreshing: !self.ticker_refreshes.lock().unwrap().is_empty(),
+            last_tickers_refresh_at: last_ticker_refresh.map(|record| record.finished_at),
+            last_tickers_refresh_ms: last_ticker_refresh.map(|record| record.duration.as_millis() as u64),
+            indicator_state_entries: self.indicator_state_cache.entry_count(),
             rate_limiter_entries: self.rate_limiter.len(),
+            request_rate: self.request_limiter.stats(),
+            last_persisted_at: *self.last_persisted_at.lock().unwrap(),
         }
     }
 }
@@ -114,5 +640,15 @@ pub struct CacheStats {
     pub stock_data_entries: u64,
     pub indicators_entries: u64,
     pub tickers_entries: u64,
+    /// Whether the full ticker listing is fresh, served stale or missing
+    pub tickers_freshness: Freshness,
+    pub tickers_refreshing: bool,
+    /// When the last ticker listing refresh finished, and how long it took
+    pub last_tickers_refresh_at: Option<DateTime<Utc>>,
+    pub last_tickers_refresh_ms: Option<u64>,
+    pub indicator_state_entries: u64,
     pub rate_limiter_entries: usize,
+    pub request_rate: RateLimiterStats,
+    /// When `persist_to_disk` last succeeded in this process
+    pub last_persisted_at: Option<DateTime<Utc>>,
 }
\ No newline at end of file
@@ -0,0 +1,583 @@
+//! Command-line interface of the `auto-analyser` binary.
+//!
+//! `--json` output is one of the serde structs below, printed pretty; their
+//! field names are stable so scripts can rely on them.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::backtest::{BacktestReport, Backtester, EntryRule, ExitRule};
+use crate::config::AppConfig;
+use crate::database::Database;
+use crate::error::AnalyzerError;
+use crate::providers::Interval;
+use crate::report::ticker_table;
+use crate::symbols::normalize_symbol;
+use crate::{StockAnalyzer, StockData, StockFilter, TechnicalIndicators, TickerInfo};
+
+/// The command ran to the end
+pub const EXIT_OK: u8 = 0;
+/// Anything else failed: an unreadable file, the database or the server
+pub const EXIT_FAILURE: u8 = 1;
+/// Invalid arguments, filter or rule; clap also exits with 2 on usage errors
+pub const EXIT_USAGE: u8 = 2;
+/// The market data provider failed or rate limited the request
+pub const EXIT_UPSTREAM: u8 = 3;
+/// The symbol has no price history, or the preset does not exist
+pub const EXIT_NOT_FOUND: u8 = 4;
+
+/// Days of history `analyze` fetches unless `--days` says otherwise
+pub const DEFAULT_ANALYZE_DAYS: u32 = 365;
+
+/// Screen, analyze and backtest stocks, or serve the dashboard API
+#[derive(Debug, Parser)]
+#[command(name = "auto-analyser", version)]
+pub struct Cli {
+    /// Without a subcommand the built-in oversold screen runs over every listed ticker
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the web API server
+    Serve(ServeArgs),
+    /// Analyze one symbol's daily history
+    Analyze(AnalyzeArgs),
+    /// Screen the ticker listing without fetching any price history
+    Screen(Box<ScreenArgs>),
+    /// Replay an entry and exit rule over a symbol's daily history
+    Backtest(BacktestArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// Address to listen on as HOST:PORT; defaults to the config file's
+    #[arg(long, value_name = "ADDR")]
+    pub bind: Option<String>,
+    /// SQLite database file; defaults to the config file's
+    #[arg(long, value_name = "PATH")]
+    pub db: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct AnalyzeArgs {
+    pub symbol: String,
+    /// Days of daily history to analyze; SMA 50 needs at least 50 trading days
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_ANALYZE_DAYS, value_parser = clap::value_parser!(u32).range(1..))]
+    pub days: u32,
+    /// Print an `AnalyzeOutput` as JSON instead of the report
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ScreenArgs {
+    /// Saved filter preset to screen with
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["filter_json", "FilterArgs"])]
+    pub preset: Option<String>,
+    /// JSON file holding a filter, in the shape the API accepts
+    #[arg(long, value_name = "FILE", conflicts_with = "FilterArgs")]
+    pub filter_json: Option<PathBuf>,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+    /// List at most this many matching tickers
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+    /// SQLite database holding the presets; defaults to the config file's
+    #[arg(long, value_name = "PATH")]
+    pub db: Option<PathBuf>,
+    /// Print a `ScreenOutput` as JSON instead of a table
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct BacktestArgs {
+    pub symbol: String,
+    /// JSON file holding a `BacktestRule`
+    #[arg(long, value_name = "FILE")]
+    pub rule: PathBuf,
+    /// Print the `BacktestReport` as JSON instead of the trade list
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Filter flags of `screen`, mapped onto the `StockFilter` builder
+#[derive(Debug, Clone, Default, Args)]
+#[group(multiple = true)]
+pub struct FilterArgs {
+    #[arg(long, value_name = "USD")]
+    pub min_market_cap: Option<f64>,
+    #[arg(long, value_name = "USD")]
+    pub max_market_cap: Option<f64>,
+    #[arg(long, value_name = "USD")]
+    pub min_price: Option<f64>,
+    #[arg(long, value_name = "USD")]
+    pub max_price: Option<f64>,
+    #[arg(long, value_name = "SHARES")]
+    pub min_volume: Option<u64>,
+    #[arg(long, value_name = "SHARES")]
+    pub max_volume: Option<u64>,
+    #[arg(long, value_name = "PCT", allow_negative_numbers = true)]
+    pub min_pct_change: Option<f64>,
+    #[arg(long, value_name = "PCT", allow_negative_numbers = true)]
+    pub max_pct_change: Option<f64>,
+    /// Keep this sector; repeat for several
+    #[arg(long = "sector", value_name = "SECTOR")]
+    pub sectors: Vec<String>,
+    /// Keep this country or region; repeat for several
+    #[arg(long = "country", value_name = "COUNTRY")]
+    pub countries: Vec<String>,
+    /// Keep this exchange; repeat for several
+    #[arg(long = "exchange", value_name = "EXCHANGE")]
+    pub exchanges: Vec<String>,
+    #[arg(long)]
+    pub exclude_adrs: bool,
+}
+
+impl FilterArgs {
+    pub fn to_filter(&self) -> StockFilter {
+        let mut filter = StockFilter::new()
+            .with_market_cap_range(self.min_market_cap, self.max_market_cap)
+            .with_price_range(self.min_price, self.max_price)
+            .with_volume_range(self.min_volume, self.max_volume)
+            .with_pct_change_range(self.min_pct_change, self.max_pct_change)
+            .with_exclude_adrs(self.exclude_adrs);
+        if !self.sectors.is_empty() {
+            filter = filter.with_sectors(self.sectors.clone());
+        }
+        if !self.countries.is_empty() {
+            filter = filter.with_countries(self.countries.clone());
+        }
+        if !self.exchanges.is_empty() {
+            filter = filter.with_exchanges(self.exchanges.clone());
+        }
+        filter
+    }
+}
+
+/// Contents of a `backtest --rule` file: `POST /api/backtest`'s body
+/// without the symbol. The filter defaults to entering below RSI 30.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestRule {
+    #[serde(default)]
+    pub filter: StockFilter,
+    pub exit: ExitRule,
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+}
+
+impl BacktestRule {
+    pub fn backtester(&self) -> Result<Backtester, CliError> {
+        self.filter.validate().map_err(CliError::Usage)?;
+        let backtester = Backtester::new(EntryRule::from_filter(&self.filter), self.exit.clone())
+            .with_date_range(self.start_date, self.end_date);
+        backtester.validate().map_err(CliError::Usage)?;
+        Ok(backtester)
+    }
+}
+
+/// `analyze --json` output: the latest bar, its indicators and signals
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalyzeOutput {
+    pub symbol: String,
+    /// Timestamp of the latest bar
+    pub as_of: Option<DateTime<Utc>>,
+    /// Bars the indicators were computed over
+    pub bars: usize,
+    pub close: Option<f64>,
+    pub volume: Option<u64>,
+    pub rsi: Option<f64>,
+    pub sma_20: Option<f64>,
+    pub sma_50: Option<f64>,
+    pub macd: Option<f64>,
+    pub macd_signal: Option<f64>,
+    pub macd_histogram: Option<f64>,
+    pub signals: Vec<String>,
+}
+
+impl AnalyzeOutput {
+    pub fn new(symbol: &str, stock_data: &[StockData], indicators: &[TechnicalIndicators], signals: Vec<String>) -> Self {
+        let latest = stock_data.last();
+        let indicator = indicators.last();
+        let macd = indicator.and_then(|i| i.macd);
+        Self {
+            symbol: symbol.to_string(),
+            as_of: latest.map(|bar| bar.timestamp),
+            bars: stock_data.len(),
+            close: latest.map(|bar| bar.close),
+            volume: latest.map(|bar| bar.volume),
+            rsi: indicator.and_then(|i| i.rsi),
+            sma_20: indicator.and_then(|i| i.sma_20),
+            sma_50: indicator.and_then(|i| i.sma_50),
+            macd: macd.map(|m| m.0),
+            macd_signal: macd.map(|m| m.1),
+            macd_histogram: macd.map(|m| m.2),
+            signals,
+        }
+    }
+}
+
+/// `screen --json` output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenOutput {
+    pub filter: StockFilter,
+    /// Tickers in the listing
+    pub listed: usize,
+    /// Tickers passing the filter, before `--limit`
+    pub matched: usize,
+    pub tickers: Vec<TickerInfo>,
+}
+
+/// Screen `tickers` by the listing fields of `filter`, keeping the
+/// listing's order
+pub fn screen(tickers: &[TickerInfo], filter: &StockFilter, limit: Option<usize>) -> ScreenOutput {
+    let mut matched = StockAnalyzer::filter_tickers(tickers, filter);
+    let count = matched.len();
+    if let Some(limit) = limit {
+        matched.truncate(limit);
+    }
+    ScreenOutput { filter: filter.clone(), listed: tickers.len(), matched: count, tickers: matched }
+}
+
+/// Why a command failed; each kind has its own exit code
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    #[error("{0}")]
+    Usage(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("upstream failure: {0}")]
+    Upstream(AnalyzerError),
+    #[error("{0}")]
+    Failed(String),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Usage(_) => EXIT_USAGE,
+            CliError::NotFound(_) => EXIT_NOT_FOUND,
+            CliError::Upstream(_) => EXIT_UPSTREAM,
+            CliError::Failed(_) => EXIT_FAILURE,
+        }
+    }
+
+    /// Classify a failed fetch of `what`: a symbol the provider doesn't
+    /// know is not found, a bad request is a usage error and anything
+    /// else on the way is the upstream's
+    pub fn fetch(what: &str, error: AnalyzerError) -> Self {
+        match error {
+            error if error.is_missing_symbol() => CliError::NotFound(format!("{}: {}", what, error)),
+            AnalyzerError::InvalidRequest(message) => CliError::Usage(message),
+            AnalyzerError::Database(message) => CliError::Failed(message),
+            error => CliError::Upstream(error),
+        }
+    }
+}
+
+/// Run `command`, printing its output to stdout
+pub async fn run(command: Command) -> Result<(), CliError> {
+    match command {
+        Command::Serve(args) => serve(args).await,
+        Command::Analyze(args) => analyze(args).await,
+        Command::Screen(args) => run_screen(*args).await,
+        Command::Backtest(args) => backtest(args).await,
+    }
+}
+
+async fn serve(args: ServeArgs) -> Result<(), CliError> {
+    let mut config = load_config()?;
+    if let Some(ref bind) = args.bind {
+        let (host, port) = parse_bind(bind)?;
+        config.bind_address = host;
+        config.port = port;
+    }
+    if let Some(ref path) = args.db {
+        config.database_url = sqlite_url(path);
+    }
+    crate::web_api::serve(config).await.map_err(|e| CliError::Failed(format!("server error: {}", e)))
+}
+
+/// Split `HOST:PORT`; an IPv6 host keeps its brackets
+pub fn parse_bind(bind: &str) -> Result<(String, u16), CliError> {
+    let invalid = || CliError::Usage(format!("--bind must be HOST:PORT (got '{}')", bind));
+    let (host, port) = bind.rsplit_once(':').ok_or_else(invalid)?;
+    let port = port.parse().map_err(|_| invalid())?;
+    if host.is_empty() {
+        return Err(invalid());
+    }
+    Ok((host.to_string(), port))
+}
+
+async fn analyze(args: AnalyzeArgs) -> Result<(), CliError> {
+    let symbol = parse_symbol(&args.symbol)?;
+    let mut analyzer = StockAnalyzer::new();
+    let end = Utc::now();
+    let start = end - Duration::days(i64::from(args.days));
+    let data = analyzer
+        .fetch_stock_data(&symbol, start, end, Interval::Daily)
+        .await
+        .map_err(|e| CliError::fetch(&symbol, e))?;
+    if data.is_empty() {
+        return Err(CliError::NotFound(format!("no price history for {}", symbol)));
+    }
+    let indicators = analyzer.calculate_indicators(&symbol, &data);
+
+    if args.json {
+        let signals = analyzer.analyze_signals(&data, &indicators);
+        print_json(&AnalyzeOutput::new(&symbol, &data, &indicators, signals))
+    } else {
+        print!("{}", analyzer.symbol_report(&symbol, &data, &indicators));
+        Ok(())
+    }
+}
+
+async fn run_screen(args: ScreenArgs) -> Result<(), CliError> {
+    let filter = match (&args.preset, &args.filter_json) {
+        (Some(name), _) => load_preset(name, args.db.as_deref()).await?,
+        (None, Some(path)) => read_json(path)?,
+        (None, None) => args.filter.to_filter(),
+    };
+    filter.validate().map_err(CliError::Usage)?;
+
+    let tickers = StockAnalyzer::fetch_all_tickers()
+        .await
+        .map_err(|e| CliError::fetch("ticker listing", e))?;
+    let output = screen(&tickers, &filter, args.limit);
+
+    if args.json {
+        return print_json(&output);
+    }
+    print!("{}", ticker_table(&output.tickers));
+    println!("{} of {} listed tickers match", output.matched, output.listed);
+    Ok(())
+}
+
+async fn load_preset(name: &str, db: Option<&Path>) -> Result<StockFilter, CliError> {
+    let database_url = match db {
+        Some(path) => sqlite_url(path),
+        None => load_config()?.database_url,
+    };
+    let database = Database::new(&database_url)
+        .await
+        .map_err(|e| CliError::Failed(format!("cannot open {}: {:#}", database_url, e)))?;
+    let preset = database
+        .get_preset(name)
+        .await
+        .map_err(|e| CliError::Failed(format!("cannot load preset '{}': {:#}", name, e)))?;
+    preset
+        .map(|preset| preset.filter)
+        .ok_or_else(|| CliError::NotFound(format!("preset '{}' not found", name)))
+}
+
+async fn backtest(args: BacktestArgs) -> Result<(), CliError> {
+    let symbol = parse_symbol(&args.symbol)?;
+    let rule: BacktestRule = read_json(&args.rule)?;
+    let backtester = rule.backtester()?;
+
+    let data = StockAnalyzer::new()
+        .fetch_all_stock_data(&symbol)
+        .await
+        .map_err(|e| CliError::fetch(&symbol, e))?;
+    if data.is_empty() {
+        return Err(CliError::NotFound(format!("no price history for {}", symbol)));
+    }
+    let report = backtester.run(&data);
+
+    if args.json {
+        return print_json(&report);
+    }
+    print_backtest(&report);
+    Ok(())
+}
+
+fn print_backtest(report: &BacktestReport) {
+    for trade in &report.trades {
+        println!(
+            "{} @ {:>8.2} → {} @ {:>8.2}  {:>+7.2}%  ({:?})",
+            trade.entry_time.format("%Y-%m-%d"),
+            trade.entry_price,
+            trade.exit_time.format("%Y-%m-%d"),
+            trade.exit_price,
+            trade.return_pct,
+            trade.exit_reason
+        );
+    }
+    println!("Trades:        {}", report.trade_count);
+    if let (Some(win_rate), Some(average)) = (report.win_rate_pct, report.average_return_pct) {
+        println!("Win rate:      {:.1}%", win_rate);
+        println!("Average trade: {:+.2}%", average);
+    }
+    println!("Total return:  {:+.2}%", report.total_return_pct);
+    println!("Max drawdown:  {:.2}%", report.max_drawdown_pct);
+}
+
+fn parse_symbol(symbol: &str) -> Result<String, CliError> {
+    let symbol = normalize_symbol(symbol);
+    if symbol.is_empty() {
+        return Err(CliError::Usage("symbol must not be empty".to_string()));
+    }
+    Ok(symbol)
+}
+
+fn load_config() -> Result<AppConfig, CliError> {
+    AppConfig::load().map_err(|e| CliError::Failed(e.to_string()))
+}
+
+fn sqlite_url(path: &Path) -> String {
+    format!("sqlite:{}", path.display())
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, CliError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| CliError::Failed(format!("cannot read {}: {}", path.display(), e)))?;
+    serde_json::from_str(&text).map_err(|e| CliError::Usage(format!("invalid JSON in {}: {}", path.display(), e)))
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), CliError> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| CliError::Failed(e.to_string()))?;
+    println!("{}", json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("auto-analyser").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn parses_each_subcommand() {
+        assert!(parse(&[]).unwrap().command.is_none());
+
+        let Some(Command::Serve(serve)) = parse(&["serve", "--bind", "0.0.0.0:8080", "--db", "a.db"]).unwrap().command else {
+            panic!("expected serve");
+        };
+        assert_eq!(serve.bind.as_deref(), Some("0.0.0.0:8080"));
+        assert_eq!(serve.db, Some(PathBuf::from("a.db")));
+
+        let Some(Command::Analyze(analyze)) = parse(&["analyze", "aapl", "--json"]).unwrap().command else {
+            panic!("expected analyze");
+        };
+        assert_eq!((analyze.symbol.as_str(), analyze.days, analyze.json), ("aapl", DEFAULT_ANALYZE_DAYS, true));
+        assert!(parse(&["analyze", "AAPL", "--days", "0"]).is_err());
+
+        let Some(Command::Backtest(backtest)) = parse(&["backtest", "MSFT", "--rule", "rule.json"]).unwrap().command else {
+            panic!("expected backtest");
+        };
+        assert_eq!(backtest.rule, PathBuf::from("rule.json"));
+        assert!(parse(&["backtest", "MSFT"]).is_err(), "--rule is required");
+    }
+
+    #[test]
+    fn screen_flags_build_the_filter() {
+        let Some(Command::Screen(screen)) = parse(&[
+            "screen", "--min-price", "5", "--max-market-cap", "2e9", "--min-pct-change", "-3",
+            "--sector", "Technology", "--sector", "Energy", "--exclude-adrs", "--limit", "10",
+        ])
+        .unwrap()
+        .command
+        else {
+            panic!("expected screen");
+        };
+        assert_eq!(screen.limit, Some(10));
+        let filter = screen.filter.to_filter();
+        assert_eq!(filter.min_price, Some(5.0));
+        assert_eq!(filter.max_market_cap, Some(2e9));
+        assert_eq!(filter.min_pct_change, Some(-3.0));
+        assert_eq!(filter.sectors, Some(vec!["Technology".to_string(), "Energy".to_string()]));
+        assert_eq!(filter.countries, None);
+        assert!(filter.exclude_adrs);
+
+        // A preset or filter file is the whole filter
+        assert!(parse(&["screen", "--preset", "tech", "--min-price", "5"]).is_err());
+        assert!(parse(&["screen", "--preset", "tech", "--filter-json", "f.json"]).is_err());
+        assert!(parse(&["screen", "--filter-json", "f.json", "--exclude-adrs"]).is_err());
+        assert!(parse(&["screen", "--preset", "tech", "--limit", "5", "--json"]).is_ok());
+    }
+
+    #[test]
+    fn screen_limits_after_counting_matches() {
+        let ticker = |symbol: &str, price: &str| TickerInfo {
+            symbol: symbol.to_string(),
+            name: format!("{} Inc", symbol),
+            last_sale: Some(price.to_string()),
+            ..TickerInfo::unlisted(symbol)
+        }
+        .with_parsed_fields();
+        let tickers = vec![ticker("AAA", "$10.00"), ticker("BBB", "$2.00"), ticker("CCC", "$30.00")];
+
+        let output = screen(&tickers, &StockFilter::new().with_price_range(Some(5.0), None), Some(1));
+        assert_eq!((output.listed, output.matched), (3, 2));
+        assert_eq!(output.tickers.len(), 1);
+        assert_eq!(output.tickers[0].symbol, "AAA");
+    }
+
+    #[test]
+    fn analyze_output_reads_the_latest_bar() {
+        let bar = |day: u32, close: f64| StockData {
+            symbol: "ABC".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1_000 * u64::from(day),
+            adjusted_close: None,
+        };
+        let data = vec![bar(1, 10.0), bar(2, 11.0)];
+        let latest = TechnicalIndicators { rsi: Some(28.5), macd: Some((0.5, 0.25, 0.25)), ..TechnicalIndicators::default() };
+        let indicators = vec![TechnicalIndicators::default(), latest];
+
+        let output = AnalyzeOutput::new("ABC", &data, &indicators, vec!["RSI Oversold (<30)".to_string()]);
+        assert_eq!(output.as_of, Some(data[1].timestamp));
+        assert_eq!((output.bars, output.close, output.volume), (2, Some(11.0), Some(2_000)));
+        assert_eq!((output.rsi, output.macd_histogram, output.sma_20), (Some(28.5), Some(0.25), None));
+
+        let json = serde_json::to_value(&output).unwrap();
+        let mut keys: Vec<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            ["as_of", "bars", "close", "macd", "macd_histogram", "macd_signal", "rsi", "signals", "sma_20", "sma_50", "symbol", "volume"]
+        );
+    }
+
+    #[test]
+    fn backtest_rule_defaults_to_oversold_entries() {
+        let rule: BacktestRule = serde_json::from_str(r#"{"exit": {"take_profit_pct": 10}}"#).unwrap();
+        assert!(rule.backtester().is_ok());
+        assert_eq!(EntryRule::from_filter(&rule.filter).rsi_below, Some(30.0));
+
+        let no_exit: BacktestRule = serde_json::from_str(r#"{"exit": {}}"#).unwrap();
+        assert_eq!(no_exit.backtester().unwrap_err().exit_code(), EXIT_USAGE);
+    }
+
+    #[test]
+    fn errors_map_to_exit_codes() {
+        let code = |error: AnalyzerError| CliError::fetch("XYZ", error).exit_code();
+        assert_eq!(code(AnalyzerError::SymbolNotFound("XYZ".to_string())), EXIT_NOT_FOUND);
+        assert_eq!(code(AnalyzerError::NoData("delisted".to_string())), EXIT_NOT_FOUND);
+        assert_eq!(code(AnalyzerError::RateLimited { retry_after: None }), EXIT_UPSTREAM);
+        assert_eq!(code(AnalyzerError::Upstream("502".to_string())), EXIT_UPSTREAM);
+        assert_eq!(code(AnalyzerError::InvalidRequest("bad".to_string())), EXIT_USAGE);
+        assert_eq!(code(AnalyzerError::Database("locked".to_string())), EXIT_FAILURE);
+
+        assert_eq!(parse_bind("127.0.0.1:3001").unwrap(), ("127.0.0.1".to_string(), 3001));
+        assert_eq!(parse_bind("[::1]:80").unwrap(), ("[::1]".to_string(), 80));
+        for bad in ["localhost", ":80", "host:port"] {
+            assert_eq!(parse_bind(bad).unwrap_err().exit_code(), EXIT_USAGE, "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,464 @@
+//! Typed async client for the web API, behind the `client` feature.
+//!
+//! Requests and responses are the serde types the server itself uses, so
+//! a change to the API's JSON shows up here as a compile error rather than
+//! a parse failure at run time.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, Stream, StreamExt};
+use reqwest::{Method, RequestBuilder, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::alerts::{Alert, NewAlert};
+use crate::analysis_plan::AnalysisPlan;
+use crate::custom_indicators::{CustomIndicator, NewCustomIndicator};
+use crate::message_log::SequencedMessage;
+use crate::notes::{NewNote, NoteUpdate, TickerNote};
+use crate::subscriptions::{ClientCommand, ServerReply, Topic};
+use crate::web_api::{
+    AnalysisRequest, AnalysisStarted, AnalysisStatus, BatchAnalysis, ContinuousAnalysisStatus, ErrorBody, EventsResponse, FilterStats, HealthReport, ResultsPage,
+    StockAnalysisResult, WatchlistAdded, WatchlistImport, WatchlistItem, WatchlistRequest, API_KEY_HEADER,
+};
+use crate::{FilterSpec, TickerInfo};
+
+/// Failure of a client call
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("invalid server URL: {0}")]
+    InvalidUrl(String),
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The server answered with an error status. `code` is one of
+    /// `ApiError::code` when the body was a JSON error.
+    #[error("server returned {status}: {message}")]
+    Api { status: StatusCode, code: Option<String>, message: String },
+    #[error("unexpected response body: {0}")]
+    Decode(#[from] serde_json::Error),
+    #[error("WebSocket error: {0}")]
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for ClientError {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+        ClientError::WebSocket(Box::new(error))
+    }
+}
+
+impl ClientError {
+    /// The HTTP status of an `Api` error
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::Api { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// The machine-readable code of an `Api` error with a JSON body
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            ClientError::Api { code, .. } => code.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+pub type Result<T, E = ClientError> = std::result::Result<T, E>;
+
+/// Session states after which a session no longer changes
+const FINISHED_SESSION_STATES: &[&str] = &["completed", "error", "interrupted"];
+
+/// Client for one auto-analyser server. Cheap to clone; clones share the
+/// connection pool.
+#[derive(Debug, Clone)]
+pub struct AutoAnalyserClient {
+    base_url: Url,
+    http: reqwest::Client,
+    api_key: Option<String>,
+}
+
+impl AutoAnalyserClient {
+    /// Client for the server at `base_url`, e.g. "http://127.0.0.1:3001"
+    pub fn new(base_url: &str) -> Result<Self> {
+        let base_url = Url::parse(base_url).map_err(|e| ClientError::InvalidUrl(format!("{}: {}", base_url, e)))?;
+        if base_url.cannot_be_a_base() || !matches!(base_url.scheme(), "http" | "https") {
+            return Err(ClientError::InvalidUrl(format!("{} is not an http(s) URL", base_url)));
+        }
+        Ok(Self { base_url, http: reqwest::Client::new(), api_key: None })
+    }
+
+    /// Send requests through `http`, e.g. one with a timeout or proxy set
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Send `key` as the `X-API-Key` header, for admin-only endpoints and
+    /// changes to a read-only server
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// `GET /api/health`. A server that is down still describes why, so
+    /// its 503 comes back as a report rather than an error.
+    pub async fn health(&self) -> Result<HealthReport> {
+        let response = self.request(Method::GET, &["api", "health"])?.send().await?;
+        if response.status() == StatusCode::SERVICE_UNAVAILABLE {
+            return Ok(serde_json::from_slice(&response.bytes().await?)?);
+        }
+        decode(response).await
+    }
+
+    /// `GET /api/tickers`
+    pub async fn tickers(&self) -> Result<Vec<TickerInfo>> {
+        self.get(&["api", "tickers"]).await
+    }
+
+    /// `POST /api/filter-stats`
+    pub async fn filter_stats(&self, filter: &FilterSpec) -> Result<FilterStats> {
+        self.post(&["api", "filter-stats"], filter).await
+    }
+
+    /// `POST /api/analysis`, returning a handle to follow the session with
+    pub async fn start_analysis(&self, request: &AnalysisRequest) -> Result<SessionHandle> {
+        let started: AnalysisStarted = self.post(&["api", "analysis"], request).await?;
+        Ok(SessionHandle { client: self.clone(), session_id: started.session_id })
+    }
+
+    /// `POST /api/analysis` as a dry run: what the request would analyze
+    /// and how long it should take, without starting a session
+    pub async fn preview_analysis(&self, request: &AnalysisRequest) -> Result<AnalysisPlan> {
+        let request = AnalysisRequest { dry_run: true, ..request.clone() };
+        self.post(&["api", "analysis"], &request).await
+    }
+
+    /// `POST /api/analyze-batch`: the request's `symbols`, at most
+    /// `MAX_BATCH_SYMBOLS`, analyzed before the call returns
+    pub async fn analyze_batch(&self, request: &AnalysisRequest) -> Result<BatchAnalysis> {
+        self.post(&["api", "analyze-batch"], request).await
+    }
+
+    /// `GET /api/analysis/:session_id`
+    pub async fn session_status(&self, session_id: &str) -> Result<AnalysisStatus> {
+        self.get(&["api", "analysis", session_id]).await
+    }
+
+    /// `GET /api/continuous-status`
+    pub async fn continuous_status(&self) -> Result<ContinuousAnalysisStatus> {
+        self.get(&["api", "continuous-status"]).await
+    }
+
+    /// First page of `POST /api/filtered-results`
+    pub async fn filtered_results(&self, filter: &FilterSpec) -> Result<ResultsPage> {
+        self.post(&["api", "filtered-results"], filter).await
+    }
+
+    /// Page `page` (counting from 1) of `POST /api/filtered-results`
+    pub async fn filtered_results_page(&self, filter: &FilterSpec, page: u32, per_page: u32) -> Result<ResultsPage> {
+        let response = self
+            .request(Method::POST, &["api", "filtered-results"])?
+            .query(&[("page", page), ("per_page", per_page)])
+            .json(filter)
+            .send()
+            .await?;
+        decode(response).await
+    }
+
+    /// `GET /api/filtered-results/changed-since`: the latest result of each
+    /// ticker analyzed after `since`, newest first
+    pub async fn results_changed_since(&self, since: DateTime<Utc>) -> Result<Vec<StockAnalysisResult>> {
+        let response = self
+            .request(Method::GET, &["api", "filtered-results", "changed-since"])?
+            .query(&[("timestamp", since.to_rfc3339())])
+            .send()
+            .await?;
+        decode(response).await
+    }
+
+    /// `GET /api/watchlist`
+    pub async fn watchlist(&self) -> Result<Vec<WatchlistItem>> {
+        self.get(&["api", "watchlist"]).await
+    }
+
+    /// `POST /api/watchlist`
+    pub async fn add_to_watchlist(&self, symbol: &str) -> Result<WatchlistAdded> {
+        self.post(&["api", "watchlist"], &WatchlistRequest { symbol: symbol.to_string() }).await
+    }
+
+    /// `DELETE /api/watchlist/:symbol`
+    pub async fn remove_from_watchlist(&self, symbol: &str) -> Result<()> {
+        let response = self.request(Method::DELETE, &["api", "watchlist", symbol])?.send().await?;
+        check(response).await.map(drop)
+    }
+
+    /// `POST /api/watchlist/import` with a CSV or one-symbol-per-line body
+    pub async fn import_watchlist(&self, csv: impl Into<String>) -> Result<WatchlistImport> {
+        let request = self.request(Method::POST, &["api", "watchlist", "import"])?;
+        decode(request.header(reqwest::header::CONTENT_TYPE, "text/csv").body(csv.into()).send().await?).await
+    }
+
+    /// `GET /api/watchlist/export`, the watchlist as CSV
+    pub async fn export_watchlist(&self) -> Result<String> {
+        let response = self.request(Method::GET, &["api", "watchlist", "export"])?.send().await?;
+        Ok(check(response).await?.text().await?)
+    }
+
+    /// `GET /api/alerts`
+    pub async fn alerts(&self) -> Result<Vec<Alert>> {
+        self.get(&["api", "alerts"]).await
+    }
+
+    /// `POST /api/alerts`
+    pub async fn create_alert(&self, alert: &NewAlert) -> Result<Alert> {
+        self.post(&["api", "alerts"], alert).await
+    }
+
+    /// `DELETE /api/alerts/:id`
+    pub async fn delete_alert(&self, id: i64) -> Result<()> {
+        let response = self.request(Method::DELETE, &["api", "alerts", &id.to_string()])?.send().await?;
+        check(response).await.map(drop)
+    }
+
+    /// `GET /api/notes`, the active notes
+    pub async fn notes(&self) -> Result<Vec<TickerNote>> {
+        self.get(&["api", "notes"]).await
+    }
+
+    /// `POST /api/notes`
+    pub async fn create_note(&self, note: &NewNote) -> Result<TickerNote> {
+        self.post(&["api", "notes"], note).await
+    }
+
+    /// `PUT /api/notes/:id`
+    pub async fn update_note(&self, id: i64, update: &NoteUpdate) -> Result<TickerNote> {
+        decode(self.request(Method::PUT, &["api", "notes", &id.to_string()])?.json(update).send().await?).await
+    }
+
+    /// `DELETE /api/notes/:id`
+    pub async fn delete_note(&self, id: i64) -> Result<()> {
+        let response = self.request(Method::DELETE, &["api", "notes", &id.to_string()])?.send().await?;
+        check(response).await.map(drop)
+    }
+
+    /// `GET /api/custom-indicators`
+    pub async fn custom_indicators(&self) -> Result<Vec<CustomIndicator>> {
+        self.get(&["api", "custom-indicators"]).await
+    }
+
+    /// `POST /api/custom-indicators`, replacing any with the same name
+    pub async fn save_custom_indicator(&self, indicator: &NewCustomIndicator) -> Result<CustomIndicator> {
+        self.post(&["api", "custom-indicators"], indicator).await
+    }
+
+    /// `DELETE /api/custom-indicators/:name`
+    pub async fn delete_custom_indicator(&self, name: &str) -> Result<()> {
+        let response = self.request(Method::DELETE, &["api", "custom-indicators", name])?.send().await?;
+        check(response).await.map(drop)
+    }
+
+    /// `GET /api/events`: broadcasts after `after_seq`, waiting up to
+    /// `timeout` for one when there are none yet. An `after_seq` past the
+    /// server's `latest_seq` predates a restart and returns at once.
+    pub async fn events(&self, after_seq: u64, timeout: Duration) -> Result<EventsResponse> {
+        let response = self
+            .request(Method::GET, &["api", "events"])?
+            .query(&[("after_seq", after_seq), ("timeout", timeout.as_secs())])
+            .send()
+            .await?;
+        decode(response).await
+    }
+
+    /// Open `/ws` and subscribe to `topics`. The stream starts with the
+    /// continuous status snapshot and an acknowledgement per topic, then
+    /// carries the subscribed broadcasts and heartbeats.
+    pub async fn subscribe_ws(&self, topics: impl IntoIterator<Item = Topic>) -> Result<WsStream> {
+        let mut stream = self.connect_ws(self.ws_url()?).await?;
+        for topic in topics {
+            stream.subscribe(topic).await?;
+        }
+        Ok(stream)
+    }
+
+    /// Reopen `/ws` after a disconnect. After the snapshot and topic
+    /// acknowledgements, the stream replays the broadcasts on `topics` after
+    /// `since_seq`, then a `ServerReply::Replayed`, then live broadcasts with
+    /// none missed or repeated.
+    pub async fn resume_ws(&self, topics: impl IntoIterator<Item = Topic>, since_seq: u64) -> Result<WsStream> {
+        let topics: Vec<String> = topics.into_iter().map(|topic| topic.to_string()).collect();
+        let mut url = self.ws_url()?;
+        url.query_pairs_mut().append_pair("since_seq", &since_seq.to_string()).append_pair("topics", &topics.join(","));
+        self.connect_ws(url).await
+    }
+
+    fn ws_url(&self) -> Result<Url> {
+        let mut url = self.endpoint(&["ws"])?;
+        let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+        url.set_scheme(scheme).map_err(|()| ClientError::InvalidUrl(format!("{} has no WebSocket equivalent", url)))?;
+        Ok(url)
+    }
+
+    async fn connect_ws(&self, url: Url) -> Result<WsStream> {
+        let (socket, _) = tokio_tungstenite::connect_async(url.as_str()).await?;
+        Ok(WsStream { socket })
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &[&str]) -> Result<T> {
+        decode(self.request(Method::GET, path)?.send().await?).await
+    }
+
+    async fn post<T: DeserializeOwned>(&self, path: &[&str], body: &impl serde::Serialize) -> Result<T> {
+        decode(self.request(Method::POST, path)?.json(body).send().await?).await
+    }
+
+    fn request(&self, method: Method, path: &[&str]) -> Result<RequestBuilder> {
+        let request = self.http.request(method, self.endpoint(path)?);
+        Ok(match &self.api_key {
+            Some(key) => request.header(API_KEY_HEADER, key),
+            None => request,
+        })
+    }
+
+    /// `base_url` with `path` appended, each segment percent-encoded
+    fn endpoint(&self, path: &[&str]) -> Result<Url> {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .map_err(|()| ClientError::InvalidUrl(self.base_url.to_string()))?
+            .pop_if_empty()
+            .extend(path);
+        Ok(url)
+    }
+}
+
+/// Pass a successful response through; turn any other into `ClientError::Api`
+async fn check(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    // Some endpoints answer with a plain-text message instead
+    let text = response.text().await.unwrap_or_default();
+    Err(match serde_json::from_str::<ErrorBody>(&text) {
+        Ok(body) => ClientError::Api { status, code: Some(body.error.code), message: body.error.message },
+        Err(_) => ClientError::Api { status, code: None, message: text },
+    })
+}
+
+async fn decode<T: DeserializeOwned>(response: reqwest::Response) -> Result<T> {
+    let body = check(response).await?.bytes().await?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+/// An analysis session started through `start_analysis`
+#[derive(Debug, Clone)]
+pub struct SessionHandle {
+    client: AutoAnalyserClient,
+    pub session_id: String,
+}
+
+impl SessionHandle {
+    pub async fn status(&self) -> Result<AnalysisStatus> {
+        self.client.session_status(&self.session_id).await
+    }
+
+    /// Poll the session every `poll_interval` until it completes, fails or
+    /// is interrupted, and return its final status
+    pub async fn wait(&self, poll_interval: Duration) -> Result<AnalysisStatus> {
+        loop {
+            let status = self.status().await?;
+            if FINISHED_SESSION_STATES.contains(&status.status.as_str()) {
+                return Ok(status);
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+}
+
+/// A frame the server sends over `/ws`
+#[derive(Debug, Clone)]
+pub enum WsMessage {
+    /// The continuous loop's status, sent once on connect
+    Snapshot(ContinuousAnalysisStatus),
+    /// Answer to a subscribe or unsubscribe, or the end of a replay
+    Reply(ServerReply),
+    Heartbeat { ts: DateTime<Utc> },
+    /// A broadcast on one of the subscribed topics: session progress,
+    /// results, quotes, alert firings and so on
+    Broadcast(SequencedMessage),
+}
+
+#[derive(Deserialize)]
+struct Heartbeat {
+    ts: DateTime<Utc>,
+}
+
+impl WsMessage {
+    /// Tell the frame kinds apart by shape: broadcasts carry a `seq`, the
+    /// snapshot is the only frame without a `type`
+    pub fn parse(text: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(text)?;
+        if value.get("seq").is_some() {
+            return Ok(WsMessage::Broadcast(serde_json::from_value(value)?));
+        }
+        match value.get("type").and_then(|kind| kind.as_str()) {
+            Some("heartbeat") => {
+                let Heartbeat { ts } = serde_json::from_value(value)?;
+                Ok(WsMessage::Heartbeat { ts })
+            }
+            Some(_) => Ok(WsMessage::Reply(serde_json::from_value(value)?)),
+            None => Ok(WsMessage::Snapshot(serde_json::from_value(value)?)),
+        }
+    }
+}
+
+/// Typed messages from `/ws`. Pings are answered while the stream is
+/// polled, so a consumer that keeps reading is never closed as idle.
+pub struct WsStream {
+    socket: WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>,
+}
+
+impl WsStream {
+    pub async fn subscribe(&mut self, topic: Topic) -> Result<()> {
+        self.send(ClientCommand::Subscribe(topic)).await
+    }
+
+    pub async fn unsubscribe(&mut self, topic: Topic) -> Result<()> {
+        self.send(ClientCommand::Unsubscribe(topic)).await
+    }
+
+    pub async fn close(mut self) -> Result<()> {
+        self.socket.close(None).await?;
+        Ok(())
+    }
+
+    async fn send(&mut self, command: ClientCommand) -> Result<()> {
+        self.socket.send(Message::Text(serde_json::to_string(&command)?)).await?;
+        Ok(())
+    }
+}
+
+impl Stream for WsStream {
+    type Item = Result<WsMessage>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match futures::ready!(self.socket.poll_next_unpin(cx)) {
+                Some(Ok(Message::Text(text))) => return Poll::Ready(Some(WsMessage::parse(&text))),
+                // Pings, pongs and the close handshake are handled by the socket
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Poll::Ready(Some(Err(e.into()))),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
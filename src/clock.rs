@@ -0,0 +1,170 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Source of wall-clock time for the analysis loops and caches.
+///
+/// Production code uses [`SystemClock`]; tests inject a [`TestClock`] so
+/// hour-long intervals and cache expiry can be exercised in milliseconds.
+#[async_trait]
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Resolve once `now()` has reached `deadline`
+    async fn sleep_until(&self, deadline: DateTime<Utc>);
+
+    async fn sleep(&self, duration: Duration) {
+        self.sleep_until(self.now() + to_chrono(duration)).await
+    }
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The real clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    async fn sleep_until(&self, deadline: DateTime<Utc>) {
+        if let Ok(remaining) = (deadline - Utc::now()).to_std() {
+            tokio::time::sleep(remaining).await;
+        }
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+pub fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// A clock that only moves when told to.
+///
+/// Sleepers wake as soon as `advance` or `set` moves the time past their
+/// deadline. `wait_for_sleepers` lets a test wait until the code under test
+/// has actually parked before advancing.
+#[derive(Debug)]
+pub struct TestClock {
+    now: watch::Sender<DateTime<Utc>>,
+    sleepers: watch::Sender<usize>,
+}
+
+impl TestClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now: watch::Sender::new(start),
+            sleepers: watch::Sender::new(0),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.send_modify(|now| *now += to_chrono(duration));
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.now.send_replace(now);
+    }
+
+    /// Tasks currently blocked in `sleep_until`
+    pub fn sleepers(&self) -> usize {
+        *self.sleepers.borrow()
+    }
+
+    pub async fn wait_for_sleepers(&self, count: usize) {
+        let mut rx = self.sleepers.subscribe();
+        let _ = rx.wait_for(|sleepers| *sleepers >= count).await;
+    }
+}
+
+#[async_trait]
+impl Clock for TestClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.borrow()
+    }
+
+    async fn sleep_until(&self, deadline: DateTime<Utc>) {
+        let mut rx = self.now.subscribe();
+        if *rx.borrow_and_update() >= deadline {
+            return;
+        }
+
+        self.sleepers.send_modify(|sleepers| *sleepers += 1);
+        let _parked = SleeperGuard(&self.sleepers);
+        let _ = rx.wait_for(|now| *now >= deadline).await;
+    }
+}
+
+/// Keeps the sleeper count right even when a sleeping task is cancelled
+struct SleeperGuard<'a>(&'a watch::Sender<usize>);
+
+impl Drop for SleeperGuard<'_> {
+    fn drop(&mut self) {
+        self.0.send_modify(|sleepers| *sleepers -= 1);
+    }
+}
+
+/// Whether something last done at `last` is due again at `now`; never-run
+/// work is always due
+pub fn is_due(last: Option<DateTime<Utc>>, interval: Duration, now: DateTime<Utc>) -> bool {
+    last.is_none_or(|last| now - last >= to_chrono(interval))
+}
+
+/// A std duration as a chrono one, saturating instead of overflowing
+pub fn to_chrono(duration: Duration) -> chrono::Duration {
+    chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 2, 14, 30, 0).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sleep_wakes_on_advance() {
+        let clock = Arc::new(TestClock::new(start()));
+        let sleeper = {
+            let clock = clock.clone();
+            tokio::spawn(async move { clock.sleep(Duration::from_secs(3600)).await })
+        };
+
+        clock.wait_for_sleepers(1).await;
+        clock.advance(Duration::from_secs(3599));
+        tokio::task::yield_now().await;
+        assert!(!sleeper.is_finished());
+        assert_eq!(clock.sleepers(), 1);
+
+        clock.advance(Duration::from_secs(1));
+        sleeper.await.unwrap();
+        assert_eq!(clock.sleepers(), 0);
+        assert_eq!(clock.now(), start() + chrono::Duration::hours(1));
+    }
+
+    #[tokio::test]
+    async fn test_sleep_until_past_deadline_returns_immediately() {
+        let clock = TestClock::new(start());
+        clock.sleep_until(start() - chrono::Duration::seconds(1)).await;
+        clock.sleep(Duration::ZERO).await;
+        assert_eq!(clock.sleepers(), 0);
+    }
+
+    #[test]
+    fn test_is_due() {
+        let interval = Duration::from_secs(15 * 60);
+        assert!(is_due(None, interval, start()));
+        assert!(!is_due(Some(start()), interval, start() + chrono::Duration::minutes(14)));
+        assert!(is_due(Some(start()), interval, start() + chrono::Duration::minutes(15)));
+    }
+}
@@ -0,0 +1,231 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::StockData;
+
+/// Days before the latest bar in which a split or dividend is noted in a
+/// result's signals
+pub const EVENT_LOOKBACK_DAYS: i64 = 30;
+
+/// A split or cash dividend, dated by its ex-date
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CorporateEvent {
+    /// `ratio` new shares for each old one: 2.0 for a 2:1 split, 0.1 for a
+    /// 1:10 reverse split
+    Split { ratio: f64, date: DateTime<Utc> },
+    /// Cash paid per share
+    Dividend { amount: f64, date: DateTime<Utc> },
+}
+
+impl CorporateEvent {
+    pub fn date(&self) -> DateTime<Utc> {
+        match self {
+            CorporateEvent::Split { date, .. } | CorporateEvent::Dividend { date, .. } => *date,
+        }
+    }
+
+    pub fn is_split(&self) -> bool {
+        matches!(self, CorporateEvent::Split { .. })
+    }
+
+    /// How the event reads in a result's signals, as "Recent 2:1 split
+    /// (2024-06-10)" or "Recent $0.24 dividend (ex 2024-06-10)"
+    pub fn signal(&self) -> String {
+        let day = self.date().format("%Y-%m-%d");
+        match *self {
+            CorporateEvent::Split { ratio, .. } if ratio >= 1.0 => {
+                format!("Recent {}:1 split ({})", round_ratio(ratio), day)
+            }
+            CorporateEvent::Split { ratio, .. } => format!("Recent 1:{} reverse split ({})", round_ratio(1.0 / ratio), day),
+            CorporateEvent::Dividend { amount, .. } => format!("Recent ${:.2} dividend (ex {})", amount, day),
+        }
+    }
+}
+
+fn round_ratio(ratio: f64) -> f64 {
+    (ratio * 1000.0).round() / 1000.0
+}
+
+/// Whether indicators see prices as traded or adjusted for splits and
+/// dividends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdjustmentPolicy {
+    Raw,
+    /// Earlier prices scaled to be comparable with later ones, so the
+    /// series does not jump on an ex-date
+    #[default]
+    Adjusted,
+}
+
+/// `bars` (oldest first) with prices before each of `events` scaled to be
+/// comparable with later ones, and `adjusted_close` set to the close used.
+///
+/// A bar's own `adjusted_close`, as Yahoo reports it, sets its price factor.
+/// Otherwise the factor comes from `events`: a dividend scales earlier prices
+/// by `1 - amount / previous close`, and a split by `1 / ratio`. A split only
+/// counts when prices actually jump by about its ratio on the ex-date, since
+/// providers that already adjust for splits leave nothing to undo; volume
+/// before a split that counts is multiplied by its ratio. Adjusting an
+/// adjusted series changes nothing.
+pub fn adjust_bars(bars: &[StockData], events: &[CorporateEvent]) -> Vec<StockData> {
+    // (first bar on or after the ex-date, price factor, volume factor)
+    let mut steps: Vec<(usize, f64, f64)> = events
+        .iter()
+        .filter_map(|event| {
+            let day = event.date().date_naive();
+            let index = bars.iter().position(|bar| bar.timestamp.date_naive() >= day)?;
+            let previous = bars.get(index.checked_sub(1)?)?;
+            match *event {
+                CorporateEvent::Split { ratio, .. } if ratio > 0.0 && shows_split(previous, &bars[index], ratio) => {
+                    Some((index, 1.0 / ratio, ratio))
+                }
+                CorporateEvent::Dividend { amount, .. } if amount > 0.0 && previous.close > amount => {
+                    Some((index, 1.0 - amount / previous.close, 1.0))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+    steps.sort_by_key(|step| std::cmp::Reverse(step.0));
+
+    let mut adjusted = bars.to_vec();
+    let (mut price_factor, mut volume_factor) = (1.0, 1.0);
+    let mut steps = steps.into_iter().peekable();
+    for (i, bar) in adjusted.iter_mut().enumerate().rev() {
+        while let Some(&(index, price, volume)) = steps.peek() {
+            if index <= i {
+                break;
+            }
+            price_factor *= price;
+            volume_factor *= volume;
+            steps.next();
+        }
+
+        let factor = match bar.adjusted_close {
+            Some(adjusted_close) if bar.close > 0.0 => adjusted_close / bar.close,
+            _ => price_factor,
+        };
+        bar.open *= factor;
+        bar.high *= factor;
+        bar.low *= factor;
+        bar.close *= factor;
+        bar.volume = (bar.volume as f64 * volume_factor).round() as u64;
+        bar.adjusted_close = Some(bar.close);
+    }
+    adjusted
+}
+
+/// Whether the move from `previous` into `bar` is closer to a split by
+/// `ratio` than to no split at all
+fn shows_split(previous: &StockData, bar: &StockData, ratio: f64) -> bool {
+    let open = if bar.open > 0.0 { bar.open } else { bar.close };
+    if previous.close <= 0.0 || open <= 0.0 {
+        return false;
+    }
+    let jump = (previous.close / open).ln();
+    (jump - ratio.ln()).abs() < jump.abs()
+}
+
+/// `stored` events with `fetched` ones added, oldest first; a fetched event
+/// replaces a stored one of the same kind and date
+pub fn merge_events(stored: Vec<CorporateEvent>, fetched: &[CorporateEvent]) -> Vec<CorporateEvent> {
+    let same = |a: &CorporateEvent, b: &CorporateEvent| a.is_split() == b.is_split() && a.date() == b.date();
+    let mut events: Vec<CorporateEvent> = stored.into_iter().filter(|event| !fetched.iter().any(|f| same(event, f))).collect();
+    events.extend_from_slice(fetched);
+    events.sort_by_key(CorporateEvent::date);
+    events
+}
+
+/// Date of the latest split among `events`
+pub fn last_split(events: &[CorporateEvent]) -> Option<DateTime<Utc>> {
+    events.iter().filter(|event| event.is_split()).map(CorporateEvent::date).max()
+}
+
+/// Signals for the events within `EVENT_LOOKBACK_DAYS` up to `as_of`, oldest first
+pub fn recent_event_signals(events: &[CorporateEvent], as_of: DateTime<Utc>) -> Vec<String> {
+    let since = as_of - Duration::days(EVENT_LOOKBACK_DAYS);
+    let mut recent: Vec<&CorporateEvent> = events.iter().filter(|event| event.date() > since && event.date() <= as_of).collect();
+    recent.sort_by_key(|event| event.date());
+    recent.into_iter().map(CorporateEvent::signal).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn day(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, day, 14, 30, 0).unwrap()
+    }
+
+    fn bar(day_of_month: u32, close: f64, volume: u64) -> StockData {
+        StockData {
+            symbol: "SPLT".to_string(),
+            timestamp: day(day_of_month),
+            open: close,
+            high: close * 1.01,
+            low: close * 0.99,
+            close,
+            volume,
+            adjusted_close: None,
+        }
+    }
+
+    #[test]
+    fn test_split_and_dividend_adjustment() {
+        let bars = vec![bar(3, 100.0, 1_000), bar(4, 102.0, 1_000), bar(5, 51.0, 2_000), bar(6, 50.0, 2_000)];
+        let events = [
+            CorporateEvent::Split { ratio: 2.0, date: day(5) },
+            CorporateEvent::Dividend { amount: 1.0, date: day(6) },
+        ];
+        let adjusted = adjust_bars(&bars, &events);
+
+        let closes: Vec<f64> = adjusted.iter().map(|bar| bar.close).collect();
+        // The dividend takes 1/51 off everything before the 6th, the split halves what came before the 5th
+        let dividend = 1.0 - 1.0 / 51.0;
+        assert_eq!(closes, [50.0 * dividend, 51.0 * dividend, 51.0 * dividend, 50.0]);
+        let volumes: Vec<u64> = adjusted.iter().map(|bar| bar.volume).collect();
+        assert_eq!(volumes, [2_000, 2_000, 2_000, 2_000]);
+        assert_eq!(adjusted[0].adjusted_close, Some(adjusted[0].close));
+        assert!((adjusted[0].high - 50.5 * dividend).abs() < 1e-9);
+
+        // Nothing is left to adjust the second time
+        let again = adjust_bars(&adjusted, &events);
+        assert_eq!(again.iter().map(|bar| bar.close).collect::<Vec<_>>(), closes);
+        assert_eq!(again.iter().map(|bar| bar.volume).collect::<Vec<_>>(), volumes);
+    }
+
+    #[test]
+    fn test_split_already_in_the_prices_is_not_applied_again() {
+        let bars = vec![bar(3, 50.0, 2_000), bar(4, 51.0, 2_000), bar(5, 51.0, 2_000)];
+        let adjusted = adjust_bars(&bars, &[CorporateEvent::Split { ratio: 2.0, date: day(4) }]);
+        assert_eq!(adjusted.iter().map(|bar| (bar.close, bar.volume)).collect::<Vec<_>>(), [(50.0, 2_000), (51.0, 2_000), (51.0, 2_000)]);
+
+        // The provider's own adjusted close wins over the events
+        let reported = vec![StockData { adjusted_close: Some(40.0), ..bar(3, 50.0, 2_000) }, bar(4, 51.0, 2_000)];
+        let adjusted = adjust_bars(&reported, &[]);
+        assert_eq!((adjusted[0].open, adjusted[0].close), (40.0, 40.0));
+    }
+
+    #[test]
+    fn test_reverse_split_signals() {
+        let events = [
+            CorporateEvent::Dividend { amount: 0.24, date: day(1) - Duration::days(60) },
+            CorporateEvent::Split { ratio: 0.1, date: day(10) },
+            CorporateEvent::Dividend { amount: 0.24, date: day(3) },
+        ];
+        assert_eq!(
+            recent_event_signals(&events, day(20)),
+            ["Recent $0.24 dividend (ex 2024-06-03)", "Recent 1:10 reverse split (2024-06-10)"]
+        );
+        assert_eq!(last_split(&events), Some(day(10)));
+        assert_eq!(CorporateEvent::Split { ratio: 2.0, date: day(10) }.signal(), "Recent 2:1 split (2024-06-10)");
+
+        // A 1:10 reverse split multiplies the price tenfold
+        let bars = vec![bar(7, 1.0, 10_000), bar(10, 10.0, 1_000)];
+        let adjusted = adjust_bars(&bars, &events[1..2]);
+        assert_eq!(adjusted.iter().map(|bar| (bar.close, bar.volume)).collect::<Vec<_>>(), [(10.0, 1_000), (10.0, 1_000)]);
+    }
+}
@@ -0,0 +1,406 @@
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::database::IndicatorHistoryPoint;
+use crate::events::{line_crosses, CrossDirection};
+use crate::indicators::SimpleMovingAverage;
+use crate::{StockData, TickerInfo};
+
+pub const DEFAULT_FAST_PERIOD: usize = 20;
+pub const DEFAULT_SLOW_PERIOD: usize = 50;
+/// A calendar week
+pub const DEFAULT_WITHIN_DAYS: u32 = 7;
+/// Longest SMA a scan computes
+pub const MAX_SCAN_PERIOD: usize = 400;
+pub const MAX_WITHIN_DAYS: u32 = 365;
+
+fn default_fast_period() -> usize {
+    DEFAULT_FAST_PERIOD
+}
+
+fn default_slow_period() -> usize {
+    DEFAULT_SLOW_PERIOD
+}
+
+fn default_within_days() -> u32 {
+    DEFAULT_WITHIN_DAYS
+}
+
+/// Which SMA crossovers to look for: the fast SMA crossing the slow one
+/// within the last `within_days` calendar days
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrossoverScan {
+    #[serde(default = "default_fast_period")]
+    pub fast_period: usize,
+    #[serde(default = "default_slow_period")]
+    pub slow_period: usize,
+    #[serde(default = "default_within_days")]
+    pub within_days: u32,
+    /// `up` for golden crosses, `down` for death crosses; either when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<CrossDirection>,
+}
+
+impl Default for CrossoverScan {
+    fn default() -> Self {
+        Self {
+            fast_period: DEFAULT_FAST_PERIOD,
+            slow_period: DEFAULT_SLOW_PERIOD,
+            within_days: DEFAULT_WITHIN_DAYS,
+            direction: None,
+        }
+    }
+}
+
+impl CrossoverScan {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.fast_period < 2 {
+            return Err("fast_period must be at least 2".to_string());
+        }
+        if self.slow_period <= self.fast_period {
+            return Err(format!("slow_period ({}) must be longer than fast_period ({})", self.slow_period, self.fast_period));
+        }
+        if self.slow_period > MAX_SCAN_PERIOD {
+            return Err(format!("slow_period must be at most {}", MAX_SCAN_PERIOD));
+        }
+        if !(1..=MAX_WITHIN_DAYS).contains(&self.within_days) {
+            return Err(format!("within_days must be between 1 and {}", MAX_WITHIN_DAYS));
+        }
+        Ok(())
+    }
+
+    /// Whether stored SMA 20 and SMA 50 history answers this scan
+    pub fn uses_stored_smas(&self) -> bool {
+        (self.fast_period, self.slow_period) == (20, 50)
+    }
+
+    /// Earliest day a cross counts for
+    pub fn window_start(&self, as_of: NaiveDate) -> NaiveDate {
+        as_of - Duration::days(i64::from(self.within_days))
+    }
+
+    /// Earliest day of daily bars to load so the slow SMA is warmed up a
+    /// little before the window, allowing for weekends and holidays
+    pub fn history_start(&self, as_of: NaiveDate) -> NaiveDate {
+        let trading_days = self.slow_period as i64 + 5;
+        self.window_start(as_of) - Duration::days(trading_days * 7 / 5 + 7)
+    }
+}
+
+/// The data a symbol is scanned from; nothing is fetched for a scan
+#[derive(Debug, Clone)]
+pub enum ScanSeries {
+    /// Daily bars, oldest first; the SMAs are computed from their closes
+    Bars(Vec<StockData>),
+    /// Stored SMA 20 and SMA 50 values, oldest first, with whatever daily
+    /// bars are held for the closes. Scans of other periods use the bars.
+    Stored { history: Vec<IndicatorHistoryPoint>, bars: Vec<StockData> },
+}
+
+/// Where a match's SMA values came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanSource {
+    Bars,
+    IndicatorHistory,
+}
+
+/// A symbol whose fast SMA crossed the slow one within the window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrossoverMatch {
+    pub symbol: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sector: Option<String>,
+    pub market_cap: Option<f64>,
+    pub direction: CrossDirection,
+    /// First day the fast SMA closed on the new side
+    pub cross_date: NaiveDate,
+    pub fast_sma: f64,
+    pub slow_sma: f64,
+    pub close_at_cross: Option<f64>,
+    pub latest_close: Option<f64>,
+    /// Percentage move from the close on the cross date to the latest close
+    pub pct_since_cross: Option<f64>,
+    pub source: ScanSource,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrossoverScanReport {
+    pub scan: CrossoverScan,
+    pub as_of: NaiveDate,
+    pub scanned: usize,
+    /// Largest market cap first; symbols without one last
+    pub matches: Vec<CrossoverMatch>,
+    /// Symbols without enough held data reaching into the window
+    pub insufficient_data: Vec<String>,
+}
+
+/// One day with both SMAs known
+struct SmaPoint {
+    date: NaiveDate,
+    close: Option<f64>,
+    fast: f64,
+    slow: f64,
+}
+
+fn computed_points(bars: &[StockData], scan: &CrossoverScan) -> Vec<SmaPoint> {
+    let (Ok(mut fast), Ok(mut slow)) = (SimpleMovingAverage::new(scan.fast_period), SimpleMovingAverage::new(scan.slow_period)) else {
+        return Vec::new();
+    };
+    bars.iter()
+        .filter_map(|bar| {
+            let (fast, slow) = (fast.next(bar.close), slow.next(bar.close));
+            Some(SmaPoint { date: bar.timestamp.date_naive(), close: Some(bar.close), fast: fast?, slow: slow? })
+        })
+        .collect()
+}
+
+fn stored_points(history: &[IndicatorHistoryPoint], bars: &[StockData]) -> Vec<SmaPoint> {
+    let closes: HashMap<NaiveDate, f64> = bars.iter().map(|bar| (bar.timestamp.date_naive(), bar.close)).collect();
+    history
+        .iter()
+        .filter_map(|point| {
+            Some(SmaPoint { date: point.date, close: closes.get(&point.date).copied(), fast: point.sma_20?, slow: point.sma_50? })
+        })
+        .collect()
+}
+
+/// Find the symbols of `tickers` whose fast SMA crossed the slow one
+/// between `scan.window_start(as_of)` and `as_of`, from the data in
+/// `series` (keyed by symbol). A symbol that crossed more than once is
+/// reported at its latest matching cross. Symbols with no series, fewer
+/// than two days with both SMAs, or data ending before the window are
+/// listed as insufficient.
+pub fn scan_crossovers(
+    tickers: &[TickerInfo],
+    series: &HashMap<String, ScanSeries>,
+    scan: &CrossoverScan,
+    as_of: NaiveDate,
+) -> CrossoverScanReport {
+    let window_start = scan.window_start(as_of);
+    let mut matches = Vec::new();
+    let mut insufficient_data = Vec::new();
+
+    for ticker in tickers {
+        let (points, bars, source) = match series.get(&ticker.symbol) {
+            Some(ScanSeries::Stored { history, bars }) if scan.uses_stored_smas() => {
+                (stored_points(history, bars), bars.as_slice(), ScanSource::IndicatorHistory)
+            }
+            Some(ScanSeries::Stored { bars, .. }) | Some(ScanSeries::Bars(bars)) => {
+                (computed_points(bars, scan), bars.as_slice(), ScanSource::Bars)
+            }
+            None => (Vec::new(), &[][..], ScanSource::Bars),
+        };
+        if points.len() < 2 || points.last().is_some_and(|last| last.date < window_start) {
+            insufficient_data.push(ticker.symbol.clone());
+            continue;
+        }
+
+        let crosses = line_crosses(points.iter().map(|point| (point, point.fast, point.slow)));
+        let Some((cross, direction)) = crosses
+            .into_iter()
+            .rev()
+            .filter(|(point, _)| point.date >= window_start && point.date <= as_of)
+            .find(|(_, direction)| scan.direction.is_none_or(|wanted| wanted == *direction))
+        else {
+            continue;
+        };
+        let latest_close = bars.last().map(|bar| bar.close);
+        let pct_since_cross = cross
+            .close
+            .zip(latest_close)
+            .filter(|(at_cross, _)| *at_cross > 0.0)
+            .map(|(at_cross, latest)| (latest - at_cross) / at_cross * 100.0);
+        matches.push(CrossoverMatch {
+            symbol: ticker.symbol.clone(),
+            name: ticker.name.clone(),
+            sector: ticker.sector.clone(),
+            market_cap: ticker.market_cap_f64,
+            direction,
+            cross_date: cross.date,
+            fast_sma: cross.fast,
+            slow_sma: cross.slow,
+            close_at_cross: cross.close,
+            latest_close,
+            pct_since_cross,
+            source,
+        });
+    }
+
+    matches.sort_by(|a, b| match (a.market_cap, b.market_cap) {
+        (Some(a_cap), Some(b_cap)) => b_cap.partial_cmp(&a_cap).unwrap_or(Ordering::Equal),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }.then_with(|| a.symbol.cmp(&b.symbol)));
+
+    CrossoverScanReport {
+        scan: *scan,
+        as_of,
+        scanned: tickers.len(),
+        matches,
+        insufficient_data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn day(n: i64) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + Duration::days(n)
+    }
+
+    fn bars(symbol: &str, closes: &[f64]) -> Vec<StockData> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| StockData {
+                symbol: symbol.to_string(),
+                timestamp: Utc.from_utc_datetime(&day(i as i64).and_hms_opt(21, 0, 0).unwrap()),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 1_000,
+                adjusted_close: None,
+            })
+            .collect()
+    }
+
+    fn ticker(symbol: &str, market_cap: Option<f64>) -> TickerInfo {
+        TickerInfo {
+            symbol: symbol.to_string(),
+            name: format!("{} Corp", symbol),
+            last_sale: None,
+            net_change: None,
+            pct_change: None,
+            market_cap: None,
+            country: None,
+            ipo_year: None,
+            volume: None,
+            sector: None,
+            industry: None,
+            exchange: None,
+            beta: None,
+            fifty_two_week_high: None,
+            fifty_two_week_low: None,
+            last_sale_f64: None,
+            market_cap_f64: market_cap,
+            pct_change_f64: None,
+            volume_u64: None,
+            country_normalized: None,
+            region: None,
+            is_adr: false,
+        }
+    }
+
+    /// Falling for `fall` days, then rising for `rise` days
+    fn v_shape(fall: usize, rise: usize) -> Vec<f64> {
+        let bottom = 100.0 - fall as f64;
+        (0..fall).map(|i| 100.0 - i as f64).chain((0..rise).map(|i| bottom + 2.0 * i as f64)).collect()
+    }
+
+    fn scan(fast: usize, slow: usize, within_days: u32) -> CrossoverScan {
+        CrossoverScan { fast_period: fast, slow_period: slow, within_days, direction: None }
+    }
+
+    #[test]
+    fn test_golden_cross_in_window() {
+        let closes = v_shape(20, 12);
+        let last = day(closes.len() as i64 - 1);
+        let series = HashMap::from([("UP".to_string(), ScanSeries::Bars(bars("UP", &closes)))]);
+        let tickers = [ticker("UP", Some(1e9))];
+
+        let report = scan_crossovers(&tickers, &series, &scan(3, 8, 10), last);
+        assert_eq!(report.matches.len(), 1, "{:?}", report);
+        let hit = &report.matches[0];
+        assert_eq!(hit.direction, CrossDirection::Up);
+        assert!(hit.fast_sma > hit.slow_sma);
+        assert!(hit.cross_date > day(20) && hit.cross_date <= last, "{}", hit.cross_date);
+        let at_cross = hit.close_at_cross.unwrap();
+        assert_eq!(hit.latest_close, closes.last().copied());
+        assert!((hit.pct_since_cross.unwrap() - (closes[31] - at_cross) / at_cross * 100.0).abs() < 1e-9);
+        assert_eq!(hit.source, ScanSource::Bars);
+
+        // Outside a one-day window, and not a death cross
+        assert!(scan_crossovers(&tickers, &series, &scan(3, 8, 1), last).matches.is_empty());
+        let death = CrossoverScan { direction: Some(CrossDirection::Down), ..scan(3, 8, 10) };
+        assert!(scan_crossovers(&tickers, &series, &death, last).matches.is_empty());
+    }
+
+    #[test]
+    fn test_sorted_by_market_cap_with_insufficient_listed() {
+        let closes = v_shape(20, 12);
+        let last = day(closes.len() as i64 - 1);
+        let series = HashMap::from([
+            ("SMALL".to_string(), ScanSeries::Bars(bars("SMALL", &closes))),
+            ("BIG".to_string(), ScanSeries::Bars(bars("BIG", &closes))),
+            ("NOCAP".to_string(), ScanSeries::Bars(bars("NOCAP", &closes))),
+            ("SHORT".to_string(), ScanSeries::Bars(bars("SHORT", &closes[..5]))),
+            // Ended long before the window
+            ("STALE".to_string(), ScanSeries::Bars(bars("STALE", &v_shape(10, 5)))),
+        ]);
+        let tickers = [
+            ticker("SMALL", Some(1e8)),
+            ticker("NOCAP", None),
+            ticker("BIG", Some(5e10)),
+            ticker("SHORT", Some(1e12)),
+            ticker("STALE", Some(1e12)),
+            ticker("NONE", Some(1e12)),
+        ];
+
+        let report = scan_crossovers(&tickers, &series, &scan(3, 8, 10), last);
+        let symbols: Vec<&str> = report.matches.iter().map(|m| m.symbol.as_str()).collect();
+        assert_eq!(symbols, ["BIG", "SMALL", "NOCAP"]);
+        assert_eq!(report.insufficient_data, ["SHORT", "STALE", "NONE"]);
+        assert_eq!(report.scanned, 6);
+    }
+
+    #[test]
+    fn test_stored_smas_answer_20_50_scans() {
+        let history: Vec<IndicatorHistoryPoint> = [(49.0, 50.0), (49.5, 50.0), (50.5, 50.0), (51.0, 50.0)]
+            .iter()
+            .enumerate()
+            .map(|(i, &(sma_20, sma_50))| IndicatorHistoryPoint {
+                date: day(i as i64),
+                rsi: None,
+                sma_20: Some(sma_20),
+                sma_50: Some(sma_50),
+                macd: None,
+                macd_signal: None,
+                macd_histogram: None,
+            })
+            .collect();
+        let series = HashMap::from([(
+            "HIST".to_string(),
+            ScanSeries::Stored { history, bars: bars("HIST", &[10.0, 10.0, 10.0, 11.0]) },
+        )]);
+        let tickers = [ticker("HIST", None)];
+
+        let report = scan_crossovers(&tickers, &series, &CrossoverScan::default(), day(3));
+        let hit = &report.matches[0];
+        assert_eq!((hit.cross_date, hit.source), (day(2), ScanSource::IndicatorHistory));
+        assert_eq!((hit.fast_sma, hit.slow_sma), (50.5, 50.0));
+        assert!((hit.pct_since_cross.unwrap() - 10.0).abs() < 1e-9);
+
+        // Four bars are too few to compute other periods from
+        let report = scan_crossovers(&tickers, &series, &scan(5, 10, 7), day(3));
+        assert_eq!(report.insufficient_data, ["HIST"]);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(CrossoverScan::default().validate().is_ok());
+        assert!(scan(50, 20, 7).validate().is_err());
+        assert!(scan(1, 20, 7).validate().is_err());
+        assert!(scan(20, 500, 7).validate().is_err());
+        assert!(scan(20, 50, 0).validate().is_err());
+        let parsed: CrossoverScan = serde_json::from_str(r#"{ "direction": "up", "within_days": 5 }"#).unwrap();
+        assert_eq!(parsed, CrossoverScan { within_days: 5, direction: Some(CrossDirection::Up), ..CrossoverScan::default() });
+    }
+}
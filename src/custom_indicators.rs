@@ -0,0 +1,762 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use crate::indicators::{AverageTrueRange, CustomRSI, ExponentialMovingAverage, MovingAverageConvergenceDivergence, SimpleMovingAverage};
+use crate::StockData;
+
+/// Longest expression accepted, in characters
+pub const MAX_EXPRESSION_LENGTH: usize = 256;
+/// Longest indicator name accepted
+pub const MAX_NAME_LENGTH: usize = 64;
+/// Largest period a series like `sma_N` may ask for
+pub const MAX_PERIOD: usize = 1000;
+
+/// A built-in series an expression can refer to, valued at the latest bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Series {
+    Close,
+    Volume,
+    Sma(usize),
+    Ema(usize),
+    Rsi(usize),
+    Atr(usize),
+    /// MACD(12, 26, 9) line
+    Macd,
+    /// MACD(12, 26, 9) line less its signal line
+    MacdHistogram,
+}
+
+impl Series {
+    fn parse(name: &str, position: usize) -> Result<Self, ExpressionError> {
+        let unknown = || ExpressionError::UnknownIdentifier { name: name.to_string(), position };
+        match name {
+            "close" => return Ok(Series::Close),
+            "volume" => return Ok(Series::Volume),
+            "macd" => return Ok(Series::Macd),
+            "macd_histogram" => return Ok(Series::MacdHistogram),
+            _ => {}
+        }
+        let (kind, period) = name.rsplit_once('_').ok_or_else(unknown)?;
+        let series: fn(usize) -> Series = match kind {
+            "sma" => Series::Sma,
+            "ema" => Series::Ema,
+            "rsi" => Series::Rsi,
+            "atr" => Series::Atr,
+            _ => return Err(unknown()),
+        };
+        let period: usize = period.parse().map_err(|_| unknown())?;
+        if period == 0 || period > MAX_PERIOD {
+            return Err(ExpressionError::InvalidPeriod { name: name.to_string(), position });
+        }
+        Ok(series(period))
+    }
+
+    /// The series' value at the last of `bars`; `None` while it is still
+    /// warming up
+    fn latest(self, bars: &[StockData]) -> Option<f64> {
+        let last = bars.last()?;
+        match self {
+            Series::Close => Some(last.close),
+            Series::Volume => Some(last.volume as f64),
+            Series::Sma(period) => {
+                let mut sma = SimpleMovingAverage::new(period).ok()?;
+                bars.iter().map(|bar| sma.next(bar.close)).last().flatten()
+            }
+            Series::Ema(period) => {
+                let mut ema = ExponentialMovingAverage::new(period).ok()?;
+                bars.iter().map(|bar| ema.next(bar.close)).last().flatten()
+            }
+            Series::Rsi(period) => {
+                let mut rsi = CustomRSI::new(period);
+                bars.iter().map(|bar| rsi.next(bar.close)).last().flatten()
+            }
+            Series::Atr(period) => {
+                let mut atr = AverageTrueRange::new(period);
+                bars.iter().map(|bar| atr.next(bar)).last().flatten()
+            }
+            Series::Macd | Series::MacdHistogram => {
+                let mut macd = MovingAverageConvergenceDivergence::new(12, 26, 9).ok()?;
+                let output = bars.iter().map(|bar| macd.next(bar.close)).last().flatten()?;
+                Some(if self == Series::Macd { output.macd } else { output.histogram })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Series {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Series::Close => write!(f, "close"),
+            Series::Volume => write!(f, "volume"),
+            Series::Sma(period) => write!(f, "sma_{}", period),
+            Series::Ema(period) => write!(f, "ema_{}", period),
+            Series::Rsi(period) => write!(f, "rsi_{}", period),
+            Series::Atr(period) => write!(f, "atr_{}", period),
+            Series::Macd => write!(f, "macd"),
+            Series::MacdHistogram => write!(f, "macd_histogram"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinaryOp {
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Add | BinaryOp::Sub => 1,
+            BinaryOp::Mul | BinaryOp::Div => 2,
+        }
+    }
+
+    fn symbol(self) -> char {
+        match self {
+            BinaryOp::Add => '+',
+            BinaryOp::Sub => '-',
+            BinaryOp::Mul => '*',
+            BinaryOp::Div => '/',
+        }
+    }
+}
+
+/// Parsed form of an expression like `(close - sma_50) / atr_14`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Number(f64),
+    Series(Series),
+    Neg(Box<Expression>),
+    Binary(BinaryOp, Box<Expression>, Box<Expression>),
+}
+
+/// Why an expression was rejected; positions count characters from 1
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ExpressionError {
+    #[error("expression must not be empty")]
+    Empty,
+    #[error("expression must be at most {MAX_EXPRESSION_LENGTH} characters")]
+    TooLong,
+    #[error("unexpected character {character:?} at position {position}")]
+    UnexpectedCharacter { character: char, position: usize },
+    #[error("invalid number {text:?} at position {position}")]
+    InvalidNumber { text: String, position: usize },
+    #[error("unexpected {found} at position {position}")]
+    UnexpectedToken { found: String, position: usize },
+    #[error("expression ends unexpectedly")]
+    UnexpectedEnd,
+    #[error("unknown identifier {name:?} at position {position}; expected close, volume, macd, macd_histogram or sma_N, ema_N, rsi_N, atr_N")]
+    UnknownIdentifier { name: String, position: usize },
+    #[error("{name:?} at position {position} needs a period from 1 to {MAX_PERIOD}")]
+    InvalidPeriod { name: String, position: usize },
+    #[error("division by a constant zero")]
+    DivisionByZero,
+    #[error("expression must refer to at least one series")]
+    NoSeries,
+}
+
+impl Expression {
+    /// Parse and validate `text`
+    pub fn parse(text: &str) -> Result<Self, ExpressionError> {
+        if text.trim().is_empty() {
+            return Err(ExpressionError::Empty);
+        }
+        if text.chars().count() > MAX_EXPRESSION_LENGTH {
+            return Err(ExpressionError::TooLong);
+        }
+        let mut parser = Parser { tokens: tokenize(text)?, next: 0 };
+        let expression = parser.expression()?;
+        if let Some(token) = parser.peek() {
+            return Err(token.unexpected());
+        }
+        expression.validate()?;
+        Ok(expression)
+    }
+
+    fn validate(&self) -> Result<(), ExpressionError> {
+        if self.series().is_empty() {
+            return Err(ExpressionError::NoSeries);
+        }
+        self.check_divisors()
+    }
+
+    fn check_divisors(&self) -> Result<(), ExpressionError> {
+        match self {
+            Expression::Number(_) | Expression::Series(_) => Ok(()),
+            Expression::Neg(operand) => operand.check_divisors(),
+            Expression::Binary(op, left, right) => {
+                if *op == BinaryOp::Div && right.constant_value() == Some(0.0) {
+                    return Err(ExpressionError::DivisionByZero);
+                }
+                left.check_divisors()?;
+                right.check_divisors()
+            }
+        }
+    }
+
+    /// The value of a subexpression without series
+    fn constant_value(&self) -> Option<f64> {
+        match self {
+            Expression::Number(value) => Some(*value),
+            Expression::Series(_) => None,
+            Expression::Neg(operand) => operand.constant_value().map(|value| -value),
+            Expression::Binary(op, left, right) => apply(*op, left.constant_value()?, right.constant_value()?),
+        }
+    }
+
+    /// Every series the expression refers to
+    pub fn series(&self) -> BTreeSet<Series> {
+        let mut series = BTreeSet::new();
+        self.collect_series(&mut series);
+        series
+    }
+
+    fn collect_series(&self, series: &mut BTreeSet<Series>) {
+        match self {
+            Expression::Number(_) => {}
+            Expression::Series(name) => {
+                series.insert(*name);
+            }
+            Expression::Neg(operand) => operand.collect_series(series),
+            Expression::Binary(_, left, right) => {
+                left.collect_series(series);
+                right.collect_series(series);
+            }
+        }
+    }
+
+    /// The value at the last of `bars`. `None` when a series is not warmed
+    /// up yet, a divisor is zero or the result is not finite.
+    pub fn evaluate(&self, bars: &[StockData]) -> Option<f64> {
+        let values = latest_values(&self.series(), bars);
+        self.evaluate_with(&values)
+    }
+
+    fn evaluate_with(&self, values: &HashMap<Series, f64>) -> Option<f64> {
+        self.eval(values).filter(|value| value.is_finite())
+    }
+
+    fn eval(&self, values: &HashMap<Series, f64>) -> Option<f64> {
+        match self {
+            Expression::Number(value) => Some(*value),
+            Expression::Series(series) => values.get(series).copied(),
+            Expression::Neg(operand) => operand.eval(values).map(|value| -value),
+            Expression::Binary(op, left, right) => apply(*op, left.eval(values)?, right.eval(values)?),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expression::Binary(op, _, _) => op.precedence(),
+            Expression::Neg(_) => 3,
+            Expression::Number(_) | Expression::Series(_) => 4,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>, parenthesize: bool) -> fmt::Result {
+        if parenthesize {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+/// Written back with only the parentheses the precedence needs, so
+/// parsing the text gives the same expression
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Number(value) => write!(f, "{}", value),
+            Expression::Series(series) => write!(f, "{}", series),
+            Expression::Neg(operand) => {
+                write!(f, "-")?;
+                operand.fmt_operand(f, operand.precedence() < 3)
+            }
+            Expression::Binary(op, left, right) => {
+                let precedence = op.precedence();
+                left.fmt_operand(f, left.precedence() < precedence)?;
+                write!(f, " {} ", op.symbol())?;
+                // `a - (b - c)` and `a / (b * c)` keep theirs
+                let tight = matches!(op, BinaryOp::Sub | BinaryOp::Div);
+                right.fmt_operand(f, right.precedence() < precedence || (tight && right.precedence() == precedence))
+            }
+        }
+    }
+}
+
+fn apply(op: BinaryOp, left: f64, right: f64) -> Option<f64> {
+    match op {
+        BinaryOp::Add => Some(left + right),
+        BinaryOp::Sub => Some(left - right),
+        BinaryOp::Mul => Some(left * right),
+        BinaryOp::Div => (right != 0.0).then(|| left / right),
+    }
+}
+
+/// Each of `series` that has a value at the last of `bars`
+fn latest_values(series: &BTreeSet<Series>, bars: &[StockData]) -> HashMap<Series, f64> {
+    series.iter().filter_map(|series| Some((*series, series.latest(bars)?))).collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Number(f64),
+    Identifier(String),
+    Operator(BinaryOp),
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+impl Token {
+    fn unexpected(&self) -> ExpressionError {
+        let found = match &self.kind {
+            TokenKind::Number(value) => format!("number {}", value),
+            TokenKind::Identifier(name) => format!("identifier {:?}", name),
+            TokenKind::Operator(op) => format!("'{}'", op.symbol()),
+            TokenKind::Open => "'('".to_string(),
+            TokenKind::Close => "')'".to_string(),
+        };
+        ExpressionError::UnexpectedToken { found, position: self.position }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, ExpressionError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let character = chars[index];
+        let position = index + 1;
+        let kind = match character {
+            c if c.is_whitespace() => {
+                index += 1;
+                continue;
+            }
+            '+' => TokenKind::Operator(BinaryOp::Add),
+            '-' => TokenKind::Operator(BinaryOp::Sub),
+            '*' => TokenKind::Operator(BinaryOp::Mul),
+            '/' => TokenKind::Operator(BinaryOp::Div),
+            '(' => TokenKind::Open,
+            ')' => TokenKind::Close,
+            c if c.is_ascii_digit() || c == '.' => {
+                let end = chars[index..].iter().position(|c| !(c.is_ascii_digit() || *c == '.')).map_or(chars.len(), |offset| index + offset);
+                let number: String = chars[index..end].iter().collect();
+                let value = number.parse().map_err(|_| ExpressionError::InvalidNumber { text: number.clone(), position })?;
+                tokens.push(Token { kind: TokenKind::Number(value), position });
+                index = end;
+                continue;
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let end = chars[index..]
+                    .iter()
+                    .position(|c| !(c.is_ascii_alphanumeric() || *c == '_'))
+                    .map_or(chars.len(), |offset| index + offset);
+                let name: String = chars[index..end].iter().collect::<String>().to_ascii_lowercase();
+                tokens.push(Token { kind: TokenKind::Identifier(name), position });
+                index = end;
+                continue;
+            }
+            character => return Err(ExpressionError::UnexpectedCharacter { character, position }),
+        };
+        tokens.push(Token { kind, position });
+        index += 1;
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over
+///
+/// ```text
+/// expression := term (('+' | '-') term)*
+/// term       := unary (('*' | '/') unary)*
+/// unary      := '-' unary | primary
+/// primary    := number | series | '(' expression ')'
+/// ```
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next)
+    }
+
+    fn advance(&mut self) -> Result<Token, ExpressionError> {
+        let token = self.tokens.get(self.next).cloned().ok_or(ExpressionError::UnexpectedEnd)?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    /// The next token if it is one of `ops`
+    fn operator(&mut self, ops: &[BinaryOp]) -> Option<BinaryOp> {
+        match self.peek()?.kind {
+            TokenKind::Operator(op) if ops.contains(&op) => {
+                self.next += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expression, ExpressionError> {
+        let mut left = self.term()?;
+        while let Some(op) = self.operator(&[BinaryOp::Add, BinaryOp::Sub]) {
+            left = Expression::Binary(op, Box::new(left), Box::new(self.term()?));
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Expression, ExpressionError> {
+        let mut left = self.unary()?;
+        while let Some(op) = self.operator(&[BinaryOp::Mul, BinaryOp::Div]) {
+            left = Expression::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expression, ExpressionError> {
+        if self.operator(&[BinaryOp::Sub]).is_some() {
+            return Ok(Expression::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expression, ExpressionError> {
+        let token = self.advance()?;
+        match token.kind {
+            TokenKind::Number(value) => Ok(Expression::Number(value)),
+            TokenKind::Identifier(ref name) => Ok(Expression::Series(Series::parse(name, token.position)?)),
+            TokenKind::Open => {
+                let inner = self.expression()?;
+                match self.advance()? {
+                    Token { kind: TokenKind::Close, .. } => Ok(inner),
+                    other => Err(other.unexpected()),
+                }
+            }
+            _ => Err(token.unexpected()),
+        }
+    }
+}
+
+/// A named expression computed for every analyzed symbol, kept in the
+/// `custom_indicators` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomIndicator {
+    pub name: String,
+    /// As written when saved, e.g. `(close - sma_50) / atr_14`
+    pub expression: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body of `POST /api/custom-indicators`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewCustomIndicator {
+    pub name: String,
+    pub expression: String,
+}
+
+impl NewCustomIndicator {
+    /// Lowercase the name, check it and parse the expression
+    pub fn validate(mut self) -> Result<Self, String> {
+        self.name = self.name.trim().to_ascii_lowercase();
+        validate_name(&self.name)?;
+        self.expression = self.expression.trim().to_string();
+        Expression::parse(&self.expression).map_err(|e| e.to_string())?;
+        Ok(self)
+    }
+}
+
+/// Check a custom indicator name: a lowercase letter, then lowercase
+/// letters, digits and underscores
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("name must not be empty".to_string());
+    }
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(format!("name must be at most {} characters", MAX_NAME_LENGTH));
+    }
+    let valid_start = name.starts_with(|c: char| c.is_ascii_lowercase());
+    if !valid_start || !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        return Err("name must start with a letter and contain only letters, digits and underscores".to_string());
+    }
+    Ok(())
+}
+
+/// Parsed custom indicators, evaluated together so series they share are
+/// computed once per symbol
+#[derive(Debug, Clone, Default)]
+pub struct CustomIndicatorSet {
+    indicators: Vec<(String, Expression)>,
+}
+
+impl CustomIndicatorSet {
+    /// Parse stored definitions, skipping (and logging) any that no longer
+    /// parse
+    pub fn new(definitions: &[CustomIndicator]) -> Self {
+        let indicators = definitions
+            .iter()
+            .filter_map(|definition| match Expression::parse(&definition.expression) {
+                Ok(expression) => Some((definition.name.clone(), expression)),
+                Err(e) => {
+                    tracing::warn!("Skipping custom indicator '{}': {}", definition.name, e);
+                    None
+                }
+            })
+            .collect();
+        Self { indicators }
+    }
+
+    pub fn len(&self) -> usize {
+        self.indicators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indicators.is_empty()
+    }
+
+    /// Each indicator's value at the last of `bars`, leaving out those that
+    /// cannot be computed there
+    pub fn evaluate(&self, bars: &[StockData]) -> HashMap<String, f64> {
+        if self.indicators.is_empty() {
+            return HashMap::new();
+        }
+        let series = self.indicators.iter().flat_map(|(_, expression)| expression.series()).collect();
+        let values = latest_values(&series, bars);
+        self.indicators
+            .iter()
+            .filter_map(|(name, expression)| Some((name.clone(), expression.evaluate_with(&values)?)))
+            .collect()
+    }
+}
+
+/// The current custom indicators, shared by the server and every analyzer
+/// it hands out so a saved definition reaches analyzers already running
+#[derive(Debug, Clone, Default)]
+pub struct SharedCustomIndicators(Arc<RwLock<Arc<CustomIndicatorSet>>>);
+
+impl SharedCustomIndicators {
+    pub fn current(&self) -> Arc<CustomIndicatorSet> {
+        self.0.read().unwrap().clone()
+    }
+
+    pub fn replace(&self, indicators: CustomIndicatorSet) {
+        *self.0.write().unwrap() = Arc::new(indicators);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn number(value: f64) -> Expression {
+        Expression::Number(value)
+    }
+
+    fn series(series: Series) -> Expression {
+        Expression::Series(series)
+    }
+
+    fn binary(op: BinaryOp, left: Expression, right: Expression) -> Expression {
+        Expression::Binary(op, Box::new(left), Box::new(right))
+    }
+
+    /// Bars closing at `closes`, each spanning 1 either side of its close
+    fn bars(closes: &[f64]) -> Vec<StockData> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 21, 0, 0).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(day, &close)| StockData {
+                symbol: "TEST".to_string(),
+                timestamp: start + Duration::days(day as i64),
+                open: close,
+                high: close + 1.0,
+                low: close - 1.0,
+                close,
+                volume: 1_000 + day as u64,
+                adjusted_close: None,
+            })
+            .collect()
+    }
+
+    fn definition(name: &str, expression: &str) -> CustomIndicator {
+        let at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        CustomIndicator { name: name.to_string(), expression: expression.to_string(), created_at: at, updated_at: at }
+    }
+
+    #[test]
+    fn test_parses_series_and_precedence() {
+        use BinaryOp::*;
+        assert_eq!(
+            Expression::parse("(close - sma_50) / atr_14").unwrap(),
+            binary(Div, binary(Sub, series(Series::Close), series(Series::Sma(50))), series(Series::Atr(14)))
+        );
+        // Multiplication binds tighter, and operators of one level group left
+        assert_eq!(
+            Expression::parse("close + 2 * volume - 1").unwrap(),
+            binary(Sub, binary(Add, series(Series::Close), binary(Mul, number(2.0), series(Series::Volume))), number(1.0))
+        );
+        assert_eq!(
+            Expression::parse("rsi_14 / 2 / 5").unwrap(),
+            binary(Div, binary(Div, series(Series::Rsi(14)), number(2.0)), number(5.0))
+        );
+        assert_eq!(
+            Expression::parse("--ema_9 * MACD_HISTOGRAM").unwrap(),
+            binary(
+                Mul,
+                Expression::Neg(Box::new(Expression::Neg(Box::new(series(Series::Ema(9)))))),
+                series(Series::MacdHistogram)
+            )
+        );
+        assert_eq!(Expression::parse(" macd*0.5 ").unwrap(), binary(Mul, series(Series::Macd), number(0.5)));
+    }
+
+    #[test]
+    fn test_rejects_malformed_expressions() {
+        let error = |text: &str| Expression::parse(text).unwrap_err();
+        assert_eq!(error("  "), ExpressionError::Empty);
+        assert_eq!(error(&format!("close{}", " ".repeat(MAX_EXPRESSION_LENGTH))), ExpressionError::TooLong);
+        assert_eq!(error("close +"), ExpressionError::UnexpectedEnd);
+        assert_eq!(error("(close - sma_20"), ExpressionError::UnexpectedEnd);
+        assert_eq!(error("close % 2"), ExpressionError::UnexpectedCharacter { character: '%', position: 7 });
+        assert_eq!(error("close)"), ExpressionError::UnexpectedToken { found: "')'".to_string(), position: 6 });
+        assert_eq!(error("close sma_20"), ExpressionError::UnexpectedToken { found: "identifier \"sma_20\"".to_string(), position: 7 });
+        assert_eq!(error("* close"), ExpressionError::UnexpectedToken { found: "'*'".to_string(), position: 1 });
+        assert_eq!(error("()"), ExpressionError::UnexpectedToken { found: "')'".to_string(), position: 2 });
+        assert_eq!(error("close * 1.2.3"), ExpressionError::InvalidNumber { text: "1.2.3".to_string(), position: 9 });
+        assert!(matches!(error("2close"), ExpressionError::UnexpectedToken { position: 2, .. }));
+    }
+
+    #[test]
+    fn test_rejects_unknown_series_and_periods() {
+        let error = |text: &str| Expression::parse(text).unwrap_err();
+        for unknown in ["open", "sma", "sma_", "sma_x", "wma_20", "atr_14_2", "_close"] {
+            assert!(
+                matches!(error(&format!("close - {}", unknown)), ExpressionError::UnknownIdentifier { ref name, position: 9 } if name == unknown),
+                "{}",
+                unknown
+            );
+        }
+        assert_eq!(error("sma_0"), ExpressionError::InvalidPeriod { name: "sma_0".to_string(), position: 1 });
+        assert!(matches!(error(&format!("rsi_{}", MAX_PERIOD + 1)), ExpressionError::InvalidPeriod { .. }));
+        assert_eq!(Expression::parse(&format!("rsi_{}", MAX_PERIOD)).unwrap(), series(Series::Rsi(MAX_PERIOD)));
+    }
+
+    #[test]
+    fn test_rejects_constant_zero_divisors_and_constant_expressions() {
+        let error = |text: &str| Expression::parse(text).unwrap_err();
+        assert_eq!(error("close / 0"), ExpressionError::DivisionByZero);
+        assert_eq!(error("close / (2 - 2)"), ExpressionError::DivisionByZero);
+        assert_eq!(error("(close / -0.0) + 1"), ExpressionError::DivisionByZero);
+        assert_eq!(error("close * (1 / 0)"), ExpressionError::DivisionByZero);
+        assert_eq!(error("2 + 3"), ExpressionError::NoSeries);
+        // Divisors that only happen to be zero are left to evaluation
+        assert!(Expression::parse("close / (close - close)").is_ok());
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for text in [
+            "(close - sma_50) / atr_14",
+            "close - (sma_20 - sma_50)",
+            "close / (atr_14 * 2)",
+            "close - sma_20 - sma_50",
+            "-(close - ema_9) * -volume",
+            "macd - macd_histogram + rsi_14 * 0.25",
+        ] {
+            let expression = Expression::parse(text).unwrap();
+            assert_eq!(expression.to_string(), text);
+            assert_eq!(Expression::parse(&expression.to_string()).unwrap(), expression);
+        }
+        assert_eq!(Expression::parse("((close)) + (rsi_14 * 2)").unwrap().to_string(), "close + rsi_14 * 2");
+    }
+
+    #[test]
+    fn test_evaluates_at_the_latest_bar() {
+        let history = bars(&[10.0, 11.0, 12.0, 13.0, 14.0]);
+        let value = |text: &str| Expression::parse(text).unwrap().evaluate(&history);
+        assert_eq!(value("close"), Some(14.0));
+        assert_eq!(value("volume"), Some(1_004.0));
+        // Average of 12, 13 and 14
+        assert_eq!(value("sma_3"), Some(13.0));
+        assert_eq!(value("(close - sma_3) * 10 / 4"), Some(2.5));
+        assert_eq!(value("-close + 20"), Some(6.0));
+        // Rising every bar: no losses
+        assert_eq!(value("rsi_3"), Some(100.0));
+        // True range is 2 on every bar
+        assert_eq!(value("(close - sma_3) / atr_3"), Some(0.5));
+        assert_eq!(Expression::parse("close").unwrap().evaluate(&[]), None);
+    }
+
+    #[test]
+    fn test_unavailable_values_give_none() {
+        let history = bars(&[10.0, 11.0, 12.0, 13.0, 14.0]);
+        let value = |text: &str| Expression::parse(text).unwrap().evaluate(&history);
+        // Not warmed up on five bars
+        assert_eq!(value("close - sma_50"), None);
+        assert_eq!(value("(close - sma_3) / atr_14"), None);
+        assert_eq!(value("macd"), None);
+        // Division by a series that is zero here
+        assert_eq!(value("close / (close - close)"), None);
+        assert_eq!(value("close / (sma_3 - 13)"), None);
+
+        let huge = bars(&[1e300, 1e300]);
+        assert_eq!(Expression::parse("close * close").unwrap().evaluate(&huge), None);
+    }
+
+    #[test]
+    fn test_macd_series_match_the_built_in_indicator() {
+        let closes: Vec<f64> = (0..60).map(|day| 100.0 + (day as f64 * 0.7).sin() * 5.0 + day as f64 * 0.2).collect();
+        let history = bars(&closes);
+        let mut macd = MovingAverageConvergenceDivergence::new(12, 26, 9).unwrap();
+        let expected = closes.iter().map(|&close| macd.next(close)).last().flatten().unwrap();
+        let value = |text: &str| Expression::parse(text).unwrap().evaluate(&history).unwrap();
+        assert!((value("macd") - expected.macd).abs() < 1e-12);
+        assert!((value("macd_histogram") - expected.histogram).abs() < 1e-12);
+        assert!((value("macd - macd_histogram") - expected.signal).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_set_evaluates_each_definition() {
+        let set = CustomIndicatorSet::new(&[
+            definition("stretch", "(close - sma_3) / atr_3"),
+            definition("slow", "close - sma_50"),
+            definition("broken", "close +"),
+        ]);
+        assert_eq!(set.len(), 2);
+        let values = set.evaluate(&bars(&[10.0, 11.0, 12.0, 13.0, 14.0]));
+        assert_eq!(values, HashMap::from([("stretch".to_string(), 0.5)]));
+        assert!(CustomIndicatorSet::default().evaluate(&bars(&[1.0])).is_empty());
+    }
+
+    #[test]
+    fn test_new_indicator_is_normalized() {
+        let new = NewCustomIndicator { name: " Stretch_14 ".to_string(), expression: " close / atr_14 ".to_string() }.validate().unwrap();
+        assert_eq!((new.name.as_str(), new.expression.as_str()), ("stretch_14", "close / atr_14"));
+
+        let invalid = |name: &str, expression: &str| {
+            NewCustomIndicator { name: name.to_string(), expression: expression.to_string() }.validate().unwrap_err()
+        };
+        assert_eq!(invalid(" ", "close"), "name must not be empty");
+        assert!(invalid("14_stretch", "close").starts_with("name must start with a letter"));
+        assert!(invalid("stretch-14", "close").starts_with("name must start with a letter"));
+        assert!(invalid(&"x".repeat(MAX_NAME_LENGTH + 1), "close").starts_with("name must be at most"));
+        assert_eq!(invalid("stretch", "close / 0"), "division by a constant zero");
+    }
+}
@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::error::{AnalyzerError, Result};
+use crate::StockData;
+
+/// What to do with a bar whose close is NaN, infinite, zero or negative,
+/// as Yahoo serves for halts and thinly traded ADRs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BadBarPolicy {
+    /// Leave the bar out
+    #[default]
+    Drop,
+    /// Replace it with a flat bar at the previous close and no volume; bad
+    /// bars before the first good one are dropped
+    ForwardFill,
+    /// Fail the fetch
+    Error,
+}
+
+impl BadBarPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BadBarPolicy::Drop => "drop",
+            BadBarPolicy::ForwardFill => "forward_fill",
+            BadBarPolicy::Error => "error",
+        }
+    }
+}
+
+impl FromStr for BadBarPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "drop" => Ok(BadBarPolicy::Drop),
+            "forward_fill" => Ok(BadBarPolicy::ForwardFill),
+            "error" => Ok(BadBarPolicy::Error),
+            other => Err(format!("unknown bad bar policy: {}", other)),
+        }
+    }
+}
+
+/// How much of a fetched series had to be repaired
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataQuality {
+    /// Bars the provider returned
+    pub total_bars: usize,
+    /// Bars left out for an unusable close
+    pub dropped: usize,
+    /// Bars forward-filled for an unusable close, or whose unusable open,
+    /// high or low was replaced by the close
+    pub filled: usize,
+    /// Extra bars sharing a timestamp with another; the last one is kept
+    pub duplicates: usize,
+}
+
+impl DataQuality {
+    pub fn repaired(&self) -> usize {
+        self.dropped + self.filled + self.duplicates
+    }
+
+    /// Share of the provider's bars that were repaired, from 0 to 1
+    pub fn repaired_ratio(&self) -> f64 {
+        if self.total_bars == 0 {
+            return 0.0;
+        }
+        self.repaired() as f64 / self.total_bars as f64
+    }
+}
+
+fn is_usable(price: f64) -> bool {
+    price.is_finite() && price > 0.0
+}
+
+/// Sort `bars`, keep the last of any sharing a timestamp and handle bars
+/// with an unusable close according to `policy`. A usable close with an
+/// unusable open, high or low has those set to the close.
+pub fn sanitize_bars(mut bars: Vec<StockData>, policy: BadBarPolicy) -> Result<(Vec<StockData>, DataQuality)> {
+    let mut quality = DataQuality {
+        total_bars: bars.len(),
+        ..DataQuality::default()
+    };
+    bars.sort_by_key(|bar| bar.timestamp);
+    let mut unique: Vec<StockData> = Vec::with_capacity(bars.len());
+    for bar in bars {
+        match unique.last_mut() {
+            Some(last) if last.timestamp == bar.timestamp => {
+                *last = bar;
+                quality.duplicates += 1;
+            }
+            _ => unique.push(bar),
+        }
+    }
+
+    let bad = unique.iter().filter(|bar| !is_usable(bar.close)).count();
+    if bad > 0 && policy == BadBarPolicy::Error {
+        let symbol = unique.first().map_or("", |bar| bar.symbol.as_str());
+        return Err(AnalyzerError::Parse(format!("{} has {} bars without a usable close", symbol, bad)));
+    }
+
+    let mut clean: Vec<StockData> = Vec::with_capacity(unique.len());
+    for mut bar in unique {
+        if !is_usable(bar.close) {
+            match (policy, clean.last()) {
+                (BadBarPolicy::ForwardFill, Some(previous)) => {
+                    let close = previous.close;
+                    clean.push(StockData {
+                        open: close,
+                        high: close,
+                        low: close,
+                        close,
+                        volume: 0,
+                        ..bar
+                    });
+                    quality.filled += 1;
+                }
+                _ => quality.dropped += 1,
+            }
+            continue;
+        }
+        let mut patched = false;
+        for price in [&mut bar.open, &mut bar.high, &mut bar.low] {
+            if !is_usable(*price) {
+                *price = bar.close;
+                patched = true;
+            }
+        }
+        if patched {
+            quality.filled += 1;
+        }
+        clean.push(bar);
+    }
+    Ok((clean, quality))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn bar(day: i64, open: f64, close: f64) -> StockData {
+        StockData {
+            symbol: "ADR".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 4, 21, 0, 0).unwrap() + Duration::days(day),
+            open,
+            high: open.max(close),
+            low: open.min(close),
+            close,
+            volume: 1_000,
+            adjusted_close: None,
+        }
+    }
+
+    fn closes(bars: &[StockData]) -> Vec<f64> {
+        bars.iter().map(|bar| bar.close).collect()
+    }
+
+    /// A halt, a NaN close, a zero open and a repeated bar
+    fn messy() -> Vec<StockData> {
+        vec![
+            bar(0, 10.0, 10.5),
+            bar(2, 10.5, f64::NAN),
+            bar(1, 10.5, 0.0),
+            bar(3, 0.0, 11.0),
+            bar(3, 10.8, 11.2),
+            bar(4, 11.2, 11.4),
+        ]
+    }
+
+    #[test]
+    fn test_drop_policy() {
+        let (bars, quality) = sanitize_bars(messy(), BadBarPolicy::Drop).unwrap();
+        assert_eq!(closes(&bars), [10.5, 11.2, 11.4]);
+        assert_eq!(quality, DataQuality { total_bars: 6, dropped: 2, filled: 0, duplicates: 1 });
+        assert_eq!(quality.repaired_ratio(), 0.5);
+    }
+
+    #[test]
+    fn test_forward_fill_policy() {
+        let mut input = messy();
+        // The later duplicate wins, so its zero open is patched from the close
+        input[4].open = 0.0;
+        input.insert(0, bar(-1, f64::NAN, f64::NAN));
+        let (bars, quality) = sanitize_bars(input, BadBarPolicy::ForwardFill).unwrap();
+        assert_eq!(closes(&bars), [10.5, 10.5, 10.5, 11.2, 11.4]);
+        assert_eq!((bars[1].open, bars[1].high, bars[1].low, bars[1].volume), (10.5, 10.5, 10.5, 0));
+        assert_eq!(bars[3].open, 11.2);
+        assert_eq!(quality, DataQuality { total_bars: 7, dropped: 1, filled: 3, duplicates: 1 });
+        assert!(bars.windows(2).all(|pair| pair[0].timestamp < pair[1].timestamp));
+    }
+
+    #[test]
+    fn test_error_policy_and_clean_series() {
+        let err = sanitize_bars(messy(), BadBarPolicy::Error).unwrap_err();
+        assert_eq!(err.to_string(), "ADR has 2 bars without a usable close");
+
+        let clean: Vec<StockData> = (0..5).map(|day| bar(day, 10.0, 10.0 + day as f64)).collect();
+        let (bars, quality) = sanitize_bars(clean, BadBarPolicy::Error).unwrap();
+        assert_eq!(bars.len(), 5);
+        assert_eq!((quality.repaired(), quality.repaired_ratio()), (0, 0.0));
+        assert_eq!(DataQuality::default().repaired_ratio(), 0.0);
+        assert_eq!("forward_fill".parse::<BadBarPolicy>(), Ok(BadBarPolicy::ForwardFill));
+    }
+}
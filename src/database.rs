@@ -1,10 +1,43 @@
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{sqlite::SqlitePool, Row};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePool, SqliteRow},
+    ConnectOptions, Connection, Encode, QueryBuilder, Row, Sqlite, Type,
+};
+use futures::TryStreamExt;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Mutex;
 use uuid::Uuid;
 
-use crate::web_api::StockAnalysisResult;
+use crate::alerts::{Alert, AlertTrigger, NewAlert};
+use crate::audit::{AuditAction, AuditEntry, AuditResource};
+use crate::corporate_events::CorporateEvent;
+use crate::custom_indicators::{CustomIndicator, NewCustomIndicator};
+use crate::indicators::incremental::IndicatorSnapshot;
+use crate::maintenance::{MaintenanceReport, RetentionPolicy, DELETE_CHUNK_SIZE};
+use crate::notes::{active_notes, NewNote, NoteUpdate, TickerNote};
+use crate::notifications::{Delivery, Notification};
+use crate::parsing::{parse_field, parse_market_cap};
+use crate::opportunity::OpportunityRule;
+use crate::opportunity_state::OpportunityStreak;
+use crate::sector_stats::{CycleGroupStats, GroupStats};
+use crate::symbol_health::SymbolHealth;
+use crate::session_events::{EventFilter, EventLevel, SessionEvent};
+use crate::ticker_directory::ListedTicker;
+use crate::symbols::{normalize_symbol, SymbolAlias};
+use crate::providers::analyst::STALE_TARGET_UPSIDE_PCT;
+use crate::relative_strength::RsRank;
+use crate::web_api::{AnalysisRequest, ResultSort, SortField, SortOrder, StockAnalysisResult};
+use crate::{FilterSpec, StockData, StockFilter, TechnicalIndicators};
+
+/// Analysis results written per transaction by the batch store
+pub const RESULT_BATCH_SIZE: usize = 250;
+
+/// Damaged rows listed individually in an integrity report
+pub const MAX_REPORTED_CORRUPT_ROWS: usize = 100;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredAnalysisResult {
@@ -21,26 +54,221 @@ pub struct StoredAnalysisResult {
     pub volume: Option<i64>,
     pub pct_change: Option<f64>,
     pub market_cap: Option<String>,
+    pub sector: Option<String>,
+    pub industry: Option<String>,
     pub is_opportunity: bool,
     pub signals: String, // JSON array as string
     pub timestamp: DateTime<Utc>,
     pub analysis_session: String,
+    pub summary: Option<String>,
+    pub data_source: Option<String>,
+    pub data_fetched_at: Option<DateTime<Utc>>,
+    pub analyst_target: Option<f64>,
+    pub upside_to_target_pct: Option<f64>,
+}
+
+/// Indicator values stored for one symbol's daily bar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndicatorHistoryPoint {
+    pub date: NaiveDate,
+    pub rsi: Option<f64>,
+    pub sma_20: Option<f64>,
+    pub sma_50: Option<f64>,
+    pub macd: Option<f64>,
+    pub macd_signal: Option<f64>,
+    pub macd_histogram: Option<f64>,
+}
+
+impl IndicatorHistoryPoint {
+    pub fn new(bar: &StockData, indicators: &TechnicalIndicators) -> Self {
+        Self {
+            date: bar.timestamp.date_naive(),
+            rsi: indicators.rsi,
+            sma_20: indicators.sma_20,
+            sma_50: indicators.sma_50,
+            macd: indicators.macd.map(|m| m.0),
+            macd_signal: indicators.macd.map(|m| m.1),
+            macd_histogram: indicators.macd.map(|m| m.2),
+        }
+    }
+}
+
+/// A symbol on the watchlist
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchlistEntry {
+    pub symbol: String,
+    pub added_at: DateTime<Utc>,
+    /// Free-text note, as imported with the symbol
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// When it was removed; removed entries are only listed on request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// A result that broke the sanity bounds, as `GET /api/anomalies` lists it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalousResult {
+    pub session_id: String,
+    /// Kept out of the results under the quarantine policy rather than
+    /// stored with them
+    pub quarantined: bool,
+    pub result: StockAnalysisResult,
+}
+
+/// Which page of a result listing to fetch, and in what order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pagination {
+    pub sort: Option<ResultSort>,
+    pub limit: u32,
+    pub offset: u64,
+}
+
+/// A page fetched by `Database::query_results`
+#[derive(Debug, Clone)]
+pub struct PagedResults {
+    /// Latest results matching the filters, counted before decoding, so it
+    /// stays the same from page to page
+    pub total: u64,
+    /// Rows on this page left out because they were too damaged to read
+    pub skipped: u64,
+    pub results: Vec<StockAnalysisResult>,
+}
+
+/// A named, reusable filter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterPreset {
+    pub name: String,
+    pub filter: StockFilter,
+    /// What counts as an opportunity in sessions run from this preset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opportunity_rules: Option<Vec<OpportunityRule>>,
+    pub updated_at: DateTime<Utc>,
+    /// When it was deleted; deleted presets are only listed on request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// One continuous analysis cycle. Its results are stored under `session_id`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisCycle {
+    pub cycle_number: u64,
+    pub session_id: String,
+    pub started_at: DateTime<Utc>,
+    /// `None` while the cycle is running
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Tickers that produced a result
+    pub tickers_analyzed: u64,
+    pub opportunities_found: u64,
+    /// Tickers whose analysis failed
+    pub error_count: u64,
+}
+
+/// One on-demand analysis session and the request that started it. Its
+/// results are stored under `session_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisSession {
+    pub session_id: String,
+    pub request: AnalysisRequest,
+    /// The filter analyzed with, after resolving `request.preset_name`
+    pub filter: FilterSpec,
+    /// "running", "completed", "budget_exhausted" or "error", as in
+    /// `AnalysisStatus`; or "interrupted" when the server stopped while it ran
+    pub status: String,
+    pub started_at: DateTime<Utc>,
+    /// `None` while the session is running
+    pub finished_at: Option<DateTime<Utc>>,
+    pub analyzed_count: u64,
+    pub total_count: u64,
+    pub opportunities_found: u64,
+    pub error_message: Option<String>,
+    /// Tickers left unanalyzed when the session's budget ran out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remaining_tickers: Vec<String>,
+}
+
+/// Stored results grouped by the session that produced them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub result_count: u64,
+    pub opportunities: u64,
+    pub first_result: DateTime<Utc>,
+    pub last_result: DateTime<Utc>,
+}
+
+/// How one session's results moved relative to another's.
+/// Only tickers analyzed in both sessions are compared.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionDiff {
+    pub session_id: String,
+    pub vs: String,
+    pub compared_tickers: usize,
+    /// Opportunities in `session_id` that were not opportunities in `vs`
+    pub new_opportunities: Vec<String>,
+    /// Opportunities in `vs` that no longer are in `session_id`
+    pub dropped_opportunities: Vec<String>,
+    /// Tickers with an RSI in both sessions, largest move first
+    pub rsi_changes: Vec<RsiChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RsiChange {
+    pub ticker: String,
+    /// RSI in `vs`
+    pub from: f64,
+    /// RSI in `session_id`
+    pub to: f64,
+    pub delta: f64,
 }
 
 pub struct Database {
     pool: SqlitePool,
+    /// Ids of the damaged analysis result rows met by reads since connecting
+    corrupt_rows: Mutex<HashSet<String>>,
 }
 
 impl Database {
     pub async fn new(database_url: &str) -> Result<Self> {
         tracing::info!("Connecting to database: {}", database_url);
         
-        let pool = SqlitePool::connect(database_url).await?;
+        let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+        let pool = SqlitePool::connect_with(options).await?;
         
         // Run migrations
         sqlx::migrate!("./migrations").run(&pool).await?;
         
-        Ok(Self { pool })
+        let db = Self { pool, corrupt_rows: Mutex::new(HashSet::new()) };
+        db.backfill_market_cap_values().await?;
+        Ok(db)
+    }
+
+    /// Parse `market_cap_value` for rows stored before the column existed.
+    /// Each distinct market cap string is parsed once; unparseable ones
+    /// stay NULL and are looked at again on the next connect.
+    async fn backfill_market_cap_values(&self) -> Result<u64> {
+        let market_caps: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT market_cap FROM analysis_results WHERE market_cap IS NOT NULL AND market_cap_value IS NULL",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut updated = 0;
+        for market_cap in market_caps {
+            let Some(value) = parse_field(Some(&market_cap), parse_market_cap) else { continue };
+            updated += sqlx::query("UPDATE analysis_results SET market_cap_value = ? WHERE market_cap = ? AND market_cap_value IS NULL")
+                .bind(value)
+                .bind(&market_cap)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+        tx.commit().await?;
+        if updated > 0 {
+            tracing::info!("Parsed the market cap of {} stored results", updated);
+        }
+        Ok(updated)
     }
 
     pub async fn initialize_tables(&self) -> Result<()> {
@@ -65,7 +293,46 @@ impl Database {
             signals TEXT NOT NULL,
             timestamp TEXT NOT NULL,
             analysis_session TEXT NOT NULL,
-            UNIQUE(ticker, analysis_session)
+            summary TEXT,
+            data_source TEXT,
+            data_fetched_at TEXT,
+            analyst_target REAL,
+            upside_to_target_pct REAL,
+            sector TEXT,
+            industry TEXT,
+            obv REAL,
+            vwap REAL,
+            volume_vs_avg REAL,
+            crossovers TEXT,
+            high_52w REAL,
+            low_52w REAL,
+            pct_from_high REAL,
+            pct_from_low REAL,
+            range_window_complete INTEGER NOT NULL DEFAULT 0,
+            rs_1w REAL,
+            rs_1m REAL,
+            rs_3m REAL,
+            rs_rank REAL,
+            exchange TEXT,
+            market_cap_value REAL,
+            data_quality TEXT,
+            repaired_bar_ratio REAL,
+            adx REAL,
+            plus_di REAL,
+            minus_di REAL,
+            last_split TEXT,
+            timeframe TEXT NOT NULL DEFAULT 'daily',
+            anomalies TEXT,
+            rsi_oversold_used REAL,
+            rsi_overbought_used REAL,
+            rsi_sector_zscore REAL,
+            rsi_industry_zscore REAL,
+            opportunity_state TEXT NOT NULL DEFAULT 'none',
+            opportunity_streak INTEGER NOT NULL DEFAULT 0,
+            opportunity_since TEXT,
+            custom_indicators TEXT,
+            bars_used INTEGER,
+            UNIQUE(ticker, analysis_session, timeframe)
         );
         
         CREATE INDEX IF NOT EXISTS idx_ticker ON analysis_results(ticker);
@@ -73,6 +340,244 @@ impl Database {
         CREATE INDEX IF NOT EXISTS idx_session ON analysis_results(analysis_session);
         CREATE INDEX IF NOT EXISTS idx_opportunity ON analysis_results(is_opportunity);
         CREATE INDEX IF NOT EXISTS idx_rsi ON analysis_results(rsi);
+        CREATE INDEX IF NOT EXISTS idx_ticker_timestamp ON analysis_results(ticker, timestamp DESC);
+        CREATE INDEX IF NOT EXISTS idx_session_timestamp ON analysis_results(analysis_session, timestamp);
+        CREATE INDEX IF NOT EXISTS idx_opportunity_timestamp ON analysis_results(is_opportunity, timestamp);
+        CREATE INDEX IF NOT EXISTS idx_sector ON analysis_results(sector);
+        CREATE INDEX IF NOT EXISTS idx_anomalous_results ON analysis_results(timestamp) WHERE anomalies IS NOT NULL;
+
+        CREATE TABLE IF NOT EXISTS quarantined_results (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            ticker TEXT NOT NULL,
+            analysis_session TEXT NOT NULL,
+            anomalies TEXT NOT NULL,
+            result TEXT NOT NULL,
+            timestamp TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_quarantined_results_timestamp ON quarantined_results(timestamp);
+
+        CREATE TABLE IF NOT EXISTS group_stats (
+            session_id TEXT NOT NULL,
+            scope TEXT NOT NULL,
+            name TEXT NOT NULL,
+            names INTEGER NOT NULL,
+            rsi_mean REAL NOT NULL,
+            rsi_stddev REAL NOT NULL,
+            pct_change_mean REAL,
+            pct_change_stddev REAL,
+            PRIMARY KEY (session_id, scope, name)
+        );
+
+        CREATE TABLE IF NOT EXISTS symbol_aliases (
+            old_symbol TEXT PRIMARY KEY,
+            new_symbol TEXT NOT NULL,
+            effective_date TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_symbol_aliases_new ON symbol_aliases(new_symbol);
+
+        CREATE TABLE IF NOT EXISTS watchlist (
+            symbol TEXT PRIMARY KEY,
+            added_at TEXT NOT NULL,
+            deleted_at TEXT,
+            note TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS filter_presets (
+            name TEXT PRIMARY KEY,
+            filter TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            opportunity_rules TEXT,
+            deleted_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS market_data (
+            symbol TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            open REAL NOT NULL,
+            high REAL NOT NULL,
+            low REAL NOT NULL,
+            close REAL NOT NULL,
+            volume INTEGER NOT NULL,
+            UNIQUE(symbol, timestamp)
+        );
+
+        CREATE TABLE IF NOT EXISTS corporate_events (
+            symbol TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            value REAL NOT NULL,
+            UNIQUE(symbol, timestamp, kind)
+        );
+
+        CREATE TABLE IF NOT EXISTS indicator_state (
+            symbol TEXT NOT NULL,
+            settings TEXT NOT NULL,
+            last_timestamp TEXT,
+            state TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY(symbol, settings)
+        );
+
+        CREATE TABLE IF NOT EXISTS session_events (
+            session_id TEXT NOT NULL,
+            seq INTEGER NOT NULL,
+            timestamp TEXT NOT NULL,
+            ticker TEXT,
+            level TEXT NOT NULL,
+            event TEXT NOT NULL,
+            PRIMARY KEY(session_id, seq)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_session_events_ticker ON session_events(session_id, ticker);
+
+        CREATE TABLE IF NOT EXISTS session_completions (
+            session_id TEXT PRIMARY KEY,
+            result_count INTEGER NOT NULL,
+            completed_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            resource TEXT NOT NULL,
+            resource_id TEXT NOT NULL,
+            action TEXT NOT NULL,
+            old_value TEXT,
+            new_value TEXT,
+            source_ip TEXT,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_audit_log_resource ON audit_log(resource, resource_id);
+
+        CREATE TABLE IF NOT EXISTS indicator_history (
+            symbol TEXT NOT NULL,
+            date TEXT NOT NULL,
+            rsi REAL,
+            sma_20 REAL,
+            sma_50 REAL,
+            macd REAL,
+            macd_signal REAL,
+            macd_histogram REAL,
+            UNIQUE(symbol, date)
+        );
+
+        CREATE TABLE IF NOT EXISTS alerts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            symbol TEXT NOT NULL,
+            condition_type TEXT NOT NULL,
+            condition_value REAL NOT NULL,
+            is_active INTEGER NOT NULL DEFAULT 1,
+            is_triggered INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            deleted_at TEXT
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_alerts_symbol ON alerts(symbol);
+
+        CREATE TABLE IF NOT EXISTS alert_triggers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            alert_id INTEGER NOT NULL,
+            symbol TEXT NOT NULL,
+            condition_type TEXT NOT NULL,
+            condition_value REAL NOT NULL,
+            observed_value REAL NOT NULL,
+            triggered_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_alert_triggers_alert ON alert_triggers(alert_id, triggered_at);
+
+        CREATE TABLE IF NOT EXISTS ticker_notes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            symbol TEXT NOT NULL,
+            note TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            expires_at TEXT
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_ticker_notes_symbol ON ticker_notes(symbol);
+
+        CREATE TABLE IF NOT EXISTS analysis_cycles (
+            cycle_number INTEGER PRIMARY KEY,
+            session_id TEXT NOT NULL UNIQUE,
+            started_at TEXT NOT NULL,
+            finished_at TEXT,
+            tickers_analyzed INTEGER NOT NULL DEFAULT 0,
+            opportunities_found INTEGER NOT NULL DEFAULT 0,
+            error_count INTEGER NOT NULL DEFAULT 0
+        );
+
+        CREATE TABLE IF NOT EXISTS sessions (
+            session_id TEXT PRIMARY KEY,
+            request TEXT NOT NULL,
+            filter TEXT NOT NULL,
+            status TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            finished_at TEXT,
+            analyzed_count INTEGER NOT NULL DEFAULT 0,
+            total_count INTEGER NOT NULL DEFAULT 0,
+            opportunities_found INTEGER NOT NULL DEFAULT 0,
+            error_message TEXT,
+            remaining_tickers TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS opportunity_states (
+            symbol TEXT PRIMARY KEY,
+            started_at TEXT NOT NULL,
+            streak INTEGER NOT NULL,
+            last_exited_at TEXT,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS custom_indicators (
+            name TEXT PRIMARY KEY,
+            expression TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS symbol_health (
+            symbol TEXT PRIMARY KEY,
+            consecutive_failures INTEGER NOT NULL,
+            last_error TEXT NOT NULL,
+            last_attempt TEXT NOT NULL,
+            skip_until_cycle INTEGER
+        );
+
+        CREATE TABLE IF NOT EXISTS tickers (
+            symbol TEXT PRIMARY KEY,
+            info TEXT NOT NULL,
+            first_seen TEXT NOT NULL,
+            last_seen TEXT NOT NULL,
+            missing_fetches INTEGER NOT NULL DEFAULT 0
+        );
+
+        CREATE TABLE IF NOT EXISTS notification_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            symbol TEXT,
+            title TEXT NOT NULL,
+            message TEXT NOT NULL,
+            channel TEXT NOT NULL,
+            status TEXT NOT NULL,
+            attempts INTEGER NOT NULL,
+            error TEXT,
+            created_at TEXT NOT NULL,
+            finished_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS maintenance_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            started_at TEXT NOT NULL,
+            finished_at TEXT NOT NULL,
+            expired_results INTEGER NOT NULL,
+            thinned_results INTEGER NOT NULL,
+            bytes_before INTEGER NOT NULL,
+            bytes_after INTEGER NOT NULL
+        );
         "#;
         
         sqlx::query(query).execute(&self.pool).await?;
@@ -82,94 +587,222 @@ impl Database {
     }
 
     pub async fn store_analysis_result(&self, result: &StockAnalysisResult, session: &str) -> Result<()> {
-        let id = Uuid::new_v4().to_string();
-        let signals_json = serde_json::to_string(&result.signals)?;
-        
+        let mut tx = self.pool.begin().await?;
+        insert_result(&mut tx, result, session).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Store results under `session` in transactions of up to
+    /// `RESULT_BATCH_SIZE` rows, rather than one per row
+    pub async fn store_analysis_results_batch(&self, results: &[StockAnalysisResult], session: &str) -> Result<()> {
+        for chunk in results.chunks(RESULT_BATCH_SIZE) {
+            let mut tx = self.pool.begin().await?;
+            for result in chunk {
+                insert_result(&mut tx, result, session).await?;
+            }
+            tx.commit().await?;
+        }
+        Ok(())
+    }
+
+    /// Store the last of a session's results like `store_analysis_results_batch`
+    /// and mark the session complete in the same transaction as the final
+    /// chunk, so a session cut short never carries the marker
+    pub async fn complete_session_results(
+        &self,
+        results: &[StockAnalysisResult],
+        session: &str,
+        completed_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let last_chunk = results.len().saturating_sub(1) / RESULT_BATCH_SIZE * RESULT_BATCH_SIZE;
+        self.store_analysis_results_batch(&results[..last_chunk], session).await?;
+
+        let mut tx = self.pool.begin().await?;
+        for result in &results[last_chunk..] {
+            insert_result(&mut tx, result, session).await?;
+        }
         let query = r#"
-        INSERT OR REPLACE INTO analysis_results (
-            id, ticker, name, current_price, rsi, sma_20, sma_50, macd, macd_signal, 
-            macd_histogram, volume, pct_change, market_cap, is_opportunity, signals, 
-            timestamp, analysis_session
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        INSERT OR REPLACE INTO session_completions (session_id, result_count, completed_at)
+        VALUES (?, (SELECT COUNT(*) FROM analysis_results WHERE analysis_session = ?), ?)
         "#;
-        
         sqlx::query(query)
-            .bind(id)
-            .bind(&result.ticker)
-            .bind(&result.name)
-            .bind(result.current_price)
-            .bind(result.rsi)
-            .bind(result.sma_20)
-            .bind(result.sma_50)
-            .bind(result.macd)
-            .bind(result.macd_signal)
-            .bind(result.macd_histogram)
-            .bind(result.volume.map(|v| v as i64))
-            .bind(result.pct_change)
-            .bind(&result.market_cap)
-            .bind(result.is_opportunity)
-            .bind(signals_json)
-            .bind(result.timestamp.to_rfc3339())
             .bind(session)
-            .execute(&self.pool)
+            .bind(session)
+            .bind(completed_at.to_rfc3339())
+            .execute(&mut *tx)
             .await?;
-
+        tx.commit().await?;
         Ok(())
     }
 
+    /// Whether every result of `session` was stored. Sessions still running,
+    /// stopped or cut short by a crash are not complete.
+    pub async fn is_session_complete(&self, session: &str) -> Result<bool> {
+        let found: Option<i64> = sqlx::query_scalar("SELECT 1 FROM session_completions WHERE session_id = ?")
+            .bind(session)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(found.is_some())
+    }
+
+    /// Most recent daily result for every ticker, newest first. Weekly and
+    /// monthly results are read through their session.
+    ///
+    /// Joins against a per-ticker `MAX(timestamp)` so SQLite can answer the
+    /// grouping from `idx_ticker_timestamp` instead of running a subquery per row.
     pub async fn get_latest_results(&self, limit: Option<i32>) -> Result<Vec<StockAnalysisResult>> {
-        let query = if let Some(limit) = limit {
-            format!(
-                r#"
-                SELECT * FROM analysis_results 
-                WHERE timestamp = (
-                    SELECT MAX(timestamp) FROM analysis_results WHERE ticker = analysis_results.ticker
-                )
-                ORDER BY timestamp DESC 
-                LIMIT {}
-                "#,
-                limit
-            )
-        } else {
-            r#"
-            SELECT * FROM analysis_results 
-            WHERE timestamp = (
-                SELECT MAX(timestamp) FROM analysis_results WHERE ticker = analysis_results.ticker
-            )
-            ORDER BY timestamp DESC
-            "#.to_string()
-        };
+        let query = r#"
+        SELECT r.* FROM analysis_results r
+        JOIN (
+            SELECT ticker, MAX(timestamp) AS latest_timestamp
+            FROM analysis_results
+            WHERE timeframe = 'daily'
+            GROUP BY ticker
+        ) latest ON r.ticker = latest.ticker AND r.timestamp = latest.latest_timestamp AND r.timeframe = 'daily'
+        ORDER BY r.timestamp DESC, r.ticker
+        LIMIT ?
+        "#;
 
-        let rows = sqlx::query(&query).fetch_all(&self.pool).await?;
-        
-        let mut results = Vec::new();
-        for row in rows {
-            let signals_json: String = row.get("signals");
-            let signals: Vec<String> = serde_json::from_str(&signals_json)?;
-            let timestamp_str: String = row.get("timestamp");
-            let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)?
-                .with_timezone(&Utc);
-
-            results.push(StockAnalysisResult {
-                ticker: row.get("ticker"),
-                name: row.get("name"),
-                current_price: row.get("current_price"),
-                rsi: row.get("rsi"),
-                sma_20: row.get("sma_20"),
-                sma_50: row.get("sma_50"),
-                macd: row.get("macd"),
-                macd_signal: row.get("macd_signal"),
-                macd_histogram: row.get("macd_histogram"),
-                volume: row.get::<Option<i64>, _>("volume").map(|v| v as u64),
-                pct_change: row.get("pct_change"),
-                market_cap: row.get("market_cap"),
-                is_opportunity: row.get::<i32, _>("is_opportunity") != 0,
-                signals,
-                timestamp,
-            });
+        // A negative LIMIT means "no limit" in SQLite
+        let rows = sqlx::query(query)
+            .bind(limit.unwrap_or(-1))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(self.rows_to_results(&rows))
+    }
+
+    /// One page of the latest daily result per ticker that passes any of
+    /// `filters`, with the number of latest results that pass in total and
+    /// how many rows of the page couldn't be read.
+    ///
+    /// Every bound the in-memory result filter checks becomes a
+    /// parameterized WHERE condition, so both paths agree on which results
+    /// match without loading the rest. Without a sort the page keeps
+    /// `get_latest_results`' newest-first order.
+    pub async fn query_results(&self, filters: &[StockFilter], pagination: Pagination) -> Result<PagedResults> {
+        let Pagination { sort, limit, offset } = pagination;
+        let mut count = QueryBuilder::new("SELECT COUNT(*)");
+        push_latest_matching(&mut count, filters);
+        let total: i64 = count.build_query_scalar().fetch_one(&self.pool).await?;
+
+        let mut query = QueryBuilder::new("SELECT r.*");
+        push_latest_matching(&mut query, filters);
+        match sort {
+            // Missing values go last in either direction, as in `ResultSort::compare`
+            Some(sort) => {
+                let column = sort_column(sort.field);
+                let direction = match sort.order {
+                    SortOrder::Asc => "ASC",
+                    SortOrder::Desc => "DESC",
+                };
+                query.push(format!(" ORDER BY ({column} IS NULL), {column} {direction}, r.ticker"));
+            }
+            None => {
+                query.push(" ORDER BY r.timestamp DESC, r.ticker");
+            }
         }
+        query.push(" LIMIT ").push_bind(limit as i64);
+        query.push(" OFFSET ").push_bind(offset as i64);
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+        let results = self.rows_to_results(&rows);
+        Ok(PagedResults {
+            total: total as u64,
+            skipped: (rows.len() - results.len()) as u64,
+            results,
+        })
+    }
+
+    /// The ticker's most recent stored daily result from any session
+    pub async fn get_latest_result(&self, ticker: &str) -> Result<Option<StockAnalysisResult>> {
+        let row = sqlx::query("SELECT * FROM analysis_results WHERE ticker = ? AND timeframe = 'daily' ORDER BY timestamp DESC LIMIT 1")
+            .bind(ticker)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().and_then(|row| self.decode_result(row)))
+    }
+
+    /// Latest daily result per ticker among those stored after `since`, newest
+    /// first. The range scan on `idx_timestamp` keeps this cheap when only
+    /// a few tickers have been analyzed since.
+    pub async fn get_results_changed_since(&self, since: DateTime<Utc>) -> Result<Vec<StockAnalysisResult>> {
+        let query = r#"
+        SELECT r.* FROM analysis_results r
+        JOIN (
+            SELECT ticker, MAX(timestamp) AS latest_timestamp
+            FROM analysis_results
+            WHERE timestamp > ? AND timeframe = 'daily'
+            GROUP BY ticker
+        ) latest ON r.ticker = latest.ticker AND r.timestamp = latest.latest_timestamp AND r.timeframe = 'daily'
+        ORDER BY r.timestamp DESC, r.ticker
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(since.to_rfc3339())
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(self.rows_to_results(&rows))
+    }
+
+    /// Keep an anomalous result for inspection without storing it with the
+    /// session's results
+    pub async fn quarantine_result(&self, result: &StockAnalysisResult, session: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO quarantined_results (ticker, analysis_session, anomalies, result, timestamp) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&result.ticker)
+        .bind(session)
+        .bind(serde_json::to_string(&result.anomalies)?)
+        .bind(serde_json::to_string(result)?)
+        .bind(result.timestamp.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
 
-        Ok(results)
+    /// The latest `limit` flagged and quarantined results, optionally of one
+    /// ticker, newest first
+    pub async fn get_anomalies(&self, ticker: Option<&str>, limit: u32) -> Result<Vec<AnomalousResult>> {
+        let flagged = sqlx::query(
+            "SELECT * FROM analysis_results WHERE anomalies IS NOT NULL AND (?1 IS NULL OR ticker = ?1) \
+             ORDER BY timestamp DESC LIMIT ?2",
+        )
+        .bind(ticker)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        let quarantined = sqlx::query(
+            "SELECT analysis_session, result FROM quarantined_results WHERE ?1 IS NULL OR ticker = ?1 \
+             ORDER BY timestamp DESC LIMIT ?2",
+        )
+        .bind(ticker)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut anomalies = Vec::with_capacity(flagged.len() + quarantined.len());
+        for row in &flagged {
+            let Some(result) = self.decode_result(row) else { continue };
+            anomalies.push(AnomalousResult {
+                session_id: row.get("analysis_session"),
+                quarantined: false,
+                result,
+            });
+        }
+        for row in &quarantined {
+            anomalies.push(AnomalousResult {
+                session_id: row.get("analysis_session"),
+                quarantined: true,
+                result: serde_json::from_str(row.get::<&str, _>("result"))?,
+            });
+        }
+        anomalies.sort_by_key(|anomaly| std::cmp::Reverse(anomaly.result.timestamp));
+        anomalies.truncate(limit as usize);
+        Ok(anomalies)
     }
 
     pub async fn get_results_by_session(&self, session: &str) -> Result<Vec<StockAnalysisResult>> {
@@ -183,101 +816,2205 @@ impl Database {
             .bind(session)
             .fetch_all(&self.pool)
             .await?;
-        
-        let mut results = Vec::new();
-        for row in rows {
-            let signals_json: String = row.get("signals");
-            let signals: Vec<String> = serde_json::from_str(&signals_json)?;
-            let timestamp_str: String = row.get("timestamp");
-            let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)?
-                .with_timezone(&Utc);
-
-            results.push(StockAnalysisResult {
-                ticker: row.get("ticker"),
-                name: row.get("name"),
-                current_price: row.get("current_price"),
-                rsi: row.get("rsi"),
-                sma_20: row.get("sma_20"),
-                sma_50: row.get("sma_50"),
-                macd: row.get("macd"),
-                macd_signal: row.get("macd_signal"),
-                macd_histogram: row.get("macd_histogram"),
-                volume: row.get::<Option<i64>, _>("volume").map(|v| v as u64),
-                pct_change: row.get("pct_change"),
-                market_cap: row.get("market_cap"),
-                is_opportunity: row.get::<i32, _>("is_opportunity") != 0,
-                signals,
-                timestamp,
-            });
+
+        Ok(self.rows_to_results(&rows))
+    }
+
+    /// Set the relative strength rank of results already stored under
+    /// `session`, in a single transaction. Returns how many rows changed.
+    pub async fn update_rs_ranks(&self, session: &str, ranks: &[RsRank]) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut updated = 0;
+        for rank in ranks {
+            updated += sqlx::query("UPDATE analysis_results SET rs_rank = ? WHERE ticker = ? AND analysis_session = ?")
+                .bind(rank.rs_rank)
+                .bind(&rank.ticker)
+                .bind(session)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    /// Store a finished cycle's group statistics and set the RSI z-scores
+    /// of its results, in a single transaction. Returns how many results
+    /// changed.
+    pub async fn save_group_stats(&self, session: &str, stats: &CycleGroupStats) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        for group in &stats.stats {
+            sqlx::query(
+                "INSERT OR REPLACE INTO group_stats (session_id, scope, name, names, rsi_mean, rsi_stddev, pct_change_mean, pct_change_stddev) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(session)
+            .bind(group.scope.as_str())
+            .bind(&group.name)
+            .bind(group.names as i64)
+            .bind(group.rsi_mean)
+            .bind(group.rsi_stddev)
+            .bind(group.pct_change_mean)
+            .bind(group.pct_change_stddev)
+            .execute(&mut *tx)
+            .await?;
         }
+        let mut updated = 0;
+        for zscores in &stats.zscores {
+            updated += sqlx::query(
+                "UPDATE analysis_results SET rsi_sector_zscore = ?, rsi_industry_zscore = ? WHERE ticker = ? AND analysis_session = ?",
+            )
+            .bind(zscores.rsi_sector_zscore)
+            .bind(zscores.rsi_industry_zscore)
+            .bind(&zscores.ticker)
+            .bind(session)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+        tx.commit().await?;
+        Ok(updated)
+    }
 
-        Ok(results)
+    /// Group statistics stored for `session`: the market row first, then
+    /// sectors and industries by name
+    pub async fn get_group_stats(&self, session: &str) -> Result<Vec<GroupStats>> {
+        let rows = sqlx::query(
+            "SELECT * FROM group_stats WHERE session_id = ? \
+             ORDER BY CASE scope WHEN 'market' THEN 0 WHEN 'sector' THEN 1 ELSE 2 END, name",
+        )
+        .bind(session)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(GroupStats {
+                    scope: row.get::<String, _>("scope").parse().map_err(anyhow::Error::msg)?,
+                    name: row.get("name"),
+                    names: row.get::<i64, _>("names") as usize,
+                    rsi_mean: row.get("rsi_mean"),
+                    rsi_stddev: row.get("rsi_stddev"),
+                    pct_change_mean: row.get("pct_change_mean"),
+                    pct_change_stddev: row.get("pct_change_stddev"),
+                })
+            })
+            .collect()
     }
 
-    pub async fn cleanup_old_results(&self, older_than_days: i32) -> Result<usize> {
+    /// Every session with stored results, most recently active first
+    pub async fn get_sessions(&self) -> Result<Vec<SessionSummary>> {
         let query = r#"
-        DELETE FROM analysis_results 
-        WHERE timestamp < datetime('now', '-' || ? || ' days')
+        SELECT
+            analysis_session,
+            COUNT(*) AS result_count,
+            SUM(CASE WHEN is_opportunity = 1 THEN 1 ELSE 0 END) AS opportunities,
+            MIN(timestamp) AS first_result,
+            MAX(timestamp) AS last_result
+        FROM analysis_results
+        GROUP BY analysis_session
+        ORDER BY last_result DESC, analysis_session
         "#;
 
-        let result = sqlx::query(query)
-            .bind(older_than_days)
+        let rows = sqlx::query(query).fetch_all(&self.pool).await?;
+        rows.iter()
+            .map(|row| {
+                let first_result: String = row.get("first_result");
+                let last_result: String = row.get("last_result");
+                Ok(SessionSummary {
+                    session_id: row.get("analysis_session"),
+                    result_count: row.get::<i64, _>("result_count") as u64,
+                    opportunities: row.get::<i64, _>("opportunities") as u64,
+                    first_result: DateTime::parse_from_rfc3339(&first_result)?.with_timezone(&Utc),
+                    last_result: DateTime::parse_from_rfc3339(&last_result)?.with_timezone(&Utc),
+                })
+            })
+            .collect()
+    }
+
+    async fn has_session(&self, session: &str) -> Result<bool> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM analysis_results WHERE analysis_session = ?)")
+            .bind(session)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(exists)
+    }
+
+    /// Compare the results stored under `session` with those under `vs`.
+    /// Returns `None` if either session has no stored results.
+    pub async fn diff_sessions(&self, session: &str, vs: &str) -> Result<Option<SessionDiff>> {
+        if !self.has_session(session).await? || !self.has_session(vs).await? {
+            return Ok(None);
+        }
+
+        let query = r#"
+        SELECT
+            cur.ticker,
+            cur.is_opportunity AS cur_opportunity,
+            cur.rsi AS cur_rsi,
+            prev.is_opportunity AS prev_opportunity,
+            prev.rsi AS prev_rsi
+        FROM analysis_results cur
+        JOIN analysis_results prev ON prev.ticker = cur.ticker AND prev.analysis_session = ?
+        WHERE cur.analysis_session = ?
+        ORDER BY cur.ticker
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(vs)
+            .bind(session)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut diff = SessionDiff {
+            session_id: session.to_string(),
+            vs: vs.to_string(),
+            compared_tickers: rows.len(),
+            new_opportunities: Vec::new(),
+            dropped_opportunities: Vec::new(),
+            rsi_changes: Vec::new(),
+        };
+        for row in &rows {
+            let ticker: String = row.get("ticker");
+            let is_opportunity = row.get::<i32, _>("cur_opportunity") != 0;
+            let was_opportunity = row.get::<i32, _>("prev_opportunity") != 0;
+            if is_opportunity && !was_opportunity {
+                diff.new_opportunities.push(ticker.clone());
+            } else if was_opportunity && !is_opportunity {
+                diff.dropped_opportunities.push(ticker.clone());
+            }
+
+            if let (Some(from), Some(to)) = (row.get::<Option<f64>, _>("prev_rsi"), row.get::<Option<f64>, _>("cur_rsi")) {
+                diff.rsi_changes.push(RsiChange { ticker, from, to, delta: to - from });
+            }
+        }
+        diff.rsi_changes.sort_by(|a, b| b.delta.abs().total_cmp(&a.delta.abs()).then_with(|| a.ticker.cmp(&b.ticker)));
+
+        Ok(Some(diff))
+    }
+
+    /// Insert or update the record for `cycle.cycle_number`
+    pub async fn record_cycle(&self, cycle: &AnalysisCycle) -> Result<()> {
+        let query = r#"
+        INSERT INTO analysis_cycles (
+            cycle_number, session_id, started_at, finished_at,
+            tickers_analyzed, opportunities_found, error_count
+        ) VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(cycle_number) DO UPDATE SET
+            session_id = excluded.session_id,
+            started_at = excluded.started_at,
+            finished_at = excluded.finished_at,
+            tickers_analyzed = excluded.tickers_analyzed,
+            opportunities_found = excluded.opportunities_found,
+            error_count = excluded.error_count
+        "#;
+
+        sqlx::query(query)
+            .bind(cycle.cycle_number as i64)
+            .bind(&cycle.session_id)
+            .bind(cycle.started_at.to_rfc3339())
+            .bind(cycle.finished_at.map(|t| t.to_rfc3339()))
+            .bind(cycle.tickers_analyzed as i64)
+            .bind(cycle.opportunities_found as i64)
+            .bind(cycle.error_count as i64)
             .execute(&self.pool)
             .await?;
 
-        tracing::info!("Cleaned up {} old analysis results", result.rows_affected());
-        Ok(result.rows_affected() as usize)
+        Ok(())
     }
 
-    pub async fn get_analysis_stats(&self) -> Result<AnalysisStats> {
+    /// Insert or update the record for `session.session_id`
+    pub async fn record_session(&self, session: &AnalysisSession) -> Result<()> {
         let query = r#"
-        SELECT 
-            COUNT(*) as total_results,
-            COUNT(DISTINCT ticker) as unique_tickers,
-            COUNT(DISTINCT analysis_session) as total_sessions,
-            SUM(CASE WHEN is_opportunity = 1 THEN 1 ELSE 0 END) as opportunities,
-            AVG(rsi) as avg_rsi,
-            MIN(timestamp) as oldest_result,
-            MAX(timestamp) as newest_result
-        FROM analysis_results
+        INSERT INTO sessions (
+            session_id, request, filter, status, started_at, finished_at,
+            analyzed_count, total_count, opportunities_found, error_message, remaining_tickers
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(session_id) DO UPDATE SET
+            request = excluded.request,
+            filter = excluded.filter,
+            status = excluded.status,
+            started_at = excluded.started_at,
+            finished_at = excluded.finished_at,
+            analyzed_count = excluded.analyzed_count,
+            total_count = excluded.total_count,
+            opportunities_found = excluded.opportunities_found,
+            error_message = excluded.error_message,
+            remaining_tickers = excluded.remaining_tickers
         "#;
 
-        let row = sqlx::query(query).fetch_one(&self.pool).await?;
-        
-        let oldest_str: Option<String> = row.get("oldest_result");
-        let newest_str: Option<String> = row.get("newest_result");
-        
-        let oldest_result = if let Some(s) = oldest_str {
-            Some(DateTime::parse_from_rfc3339(&s)?.with_timezone(&Utc))
-        } else {
-            None
-        };
-        
-        let newest_result = if let Some(s) = newest_str {
-            Some(DateTime::parse_from_rfc3339(&s)?.with_timezone(&Utc))
-        } else {
-            None
-        };
+        sqlx::query(query)
+            .bind(&session.session_id)
+            .bind(serde_json::to_string(&session.request)?)
+            .bind(serde_json::to_string(&session.filter)?)
+            .bind(&session.status)
+            .bind(session.started_at.to_rfc3339())
+            .bind(session.finished_at.map(|t| t.to_rfc3339()))
+            .bind(session.analyzed_count as i64)
+            .bind(session.total_count as i64)
+            .bind(session.opportunities_found as i64)
+            .bind(&session.error_message)
+            .bind(match session.remaining_tickers.is_empty() {
+                true => None,
+                false => Some(serde_json::to_string(&session.remaining_tickers)?),
+            })
+            .execute(&self.pool)
+            .await?;
 
-        Ok(AnalysisStats {
-            total_results: row.get::<i32, _>("total_results") as u64,
-            unique_tickers: row.get::<i32, _>("unique_tickers") as u64,
-            total_sessions: row.get::<i32, _>("total_sessions") as u64,
-            opportunities: row.get::<i32, _>("opportunities") as u64,
-            avg_rsi: row.get("avg_rsi"),
-            oldest_result,
-            newest_result,
+        Ok(())
+    }
+
+    pub async fn get_session(&self, session_id: &str) -> Result<Option<AnalysisSession>> {
+        let row = sqlx::query("SELECT * FROM sessions WHERE session_id = ?")
+            .bind(session_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(Self::row_to_session).transpose()
+    }
+
+    /// Mark sessions a previous process left running as interrupted,
+    /// returning how many there were
+    pub async fn interrupt_running_sessions(&self, at: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("UPDATE sessions SET status = 'interrupted', finished_at = ? WHERE status = 'running'")
+            .bind(at.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    fn row_to_session(row: &sqlx::sqlite::SqliteRow) -> Result<AnalysisSession> {
+        let request: String = row.get("request");
+        let filter: String = row.get("filter");
+        let started_at: String = row.get("started_at");
+        let finished_at = match row.get::<Option<String>, _>("finished_at") {
+            Some(s) => Some(DateTime::parse_from_rfc3339(&s)?.with_timezone(&Utc)),
+            None => None,
+        };
+        let remaining_tickers: Option<String> = row.get("remaining_tickers");
+        Ok(AnalysisSession {
+            session_id: row.get("session_id"),
+            request: serde_json::from_str(&request)?,
+            filter: serde_json::from_str(&filter)?,
+            status: row.get("status"),
+            started_at: DateTime::parse_from_rfc3339(&started_at)?.with_timezone(&Utc),
+            finished_at,
+            analyzed_count: row.get::<i64, _>("analyzed_count") as u64,
+            total_count: row.get::<i64, _>("total_count") as u64,
+            opportunities_found: row.get::<i64, _>("opportunities_found") as u64,
+            error_message: row.get("error_message"),
+            remaining_tickers: remaining_tickers.as_deref().map(serde_json::from_str).transpose()?.unwrap_or_default(),
         })
     }
-}
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AnalysisStats {
-    pub total_results: u64,
-    pub unique_tickers: u64,
-    pub total_sessions: u64,
-    pub opportunities: u64,
-    pub avg_rsi: Option<f64>,
-    pub oldest_result: Option<DateTime<Utc>>,
+    /// Recorded cycles, newest first
+    pub async fn get_cycles(&self, limit: Option<i32>) -> Result<Vec<AnalysisCycle>> {
+        // A negative LIMIT means "no limit" in SQLite
+        let rows = sqlx::query("SELECT * FROM analysis_cycles ORDER BY cycle_number DESC LIMIT ?")
+            .bind(limit.unwrap_or(-1))
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let started_at: String = row.get("started_at");
+                let finished_at = match row.get::<Option<String>, _>("finished_at") {
+                    Some(s) => Some(DateTime::parse_from_rfc3339(&s)?.with_timezone(&Utc)),
+                    None => None,
+                };
+                Ok(AnalysisCycle {
+                    cycle_number: row.get::<i64, _>("cycle_number") as u64,
+                    session_id: row.get("session_id"),
+                    started_at: DateTime::parse_from_rfc3339(&started_at)?.with_timezone(&Utc),
+                    finished_at,
+                    tickers_analyzed: row.get::<i64, _>("tickers_analyzed") as u64,
+                    opportunities_found: row.get::<i64, _>("opportunities_found") as u64,
+                    error_count: row.get::<i64, _>("error_count") as u64,
+                })
+            })
+            .collect()
+    }
+
+    /// Highest recorded cycle number, or 0 if no cycle has run yet
+    pub async fn last_cycle_number(&self) -> Result<u64> {
+        let last: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(cycle_number), 0) FROM analysis_cycles")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(last as u64)
+    }
+
+    /// Decode `rows`, leaving out any that can't be read at all. Damaged
+    /// rows are logged and remembered in `corrupt_rows` the first time
+    /// they're met.
+    fn rows_to_results<'a>(&self, rows: impl IntoIterator<Item = &'a SqliteRow>) -> Vec<StockAnalysisResult> {
+        rows.into_iter().filter_map(|row| self.decode_result(row)).collect()
+    }
+
+    /// One stored result, or `None` if the row is too damaged to use
+    fn decode_result(&self, row: &SqliteRow) -> Option<StockAnalysisResult> {
+        let mut problems = Vec::new();
+        let decoded = Self::row_to_result(row, &mut problems);
+        if let Err(e) = &decoded {
+            problems.push(e.to_string());
+        }
+        let id: String = row.get("id");
+        if !problems.is_empty() && self.corrupt_rows.lock().unwrap().insert(id.clone()) {
+            tracing::warn!(
+                "Damaged analysis result {} for {}: {}{}",
+                id,
+                row.get::<String, _>("ticker"),
+                problems.join("; "),
+                if decoded.is_err() { " (skipped)" } else { "" }
+            );
+        }
+        decoded.ok()
+    }
+
+    /// Decode a stored result. JSON columns that don't parse read as empty
+    /// and are described in `problems`; an unreadable timestamp, timeframe
+    /// or opportunity state fails the row.
+    fn row_to_result(row: &SqliteRow, problems: &mut Vec<String>) -> Result<StockAnalysisResult> {
+        let signals: Vec<String> = json_column(row, "signals", problems);
+        let timestamp = timestamp_column(row, "timestamp")?
+            .ok_or_else(|| anyhow::anyhow!("missing timestamp"))?;
+        let data_fetched_at = timestamp_column(row, "data_fetched_at")?;
+        let last_split = timestamp_column(row, "last_split")?;
+        let sma_20: Option<f64> = row.get("sma_20");
+        let sma_50: Option<f64> = row.get("sma_50");
+        let crossovers = json_column(row, "crossovers", problems);
+        let data_quality = json_column(row, "data_quality", problems);
+        let anomalies = json_column(row, "anomalies", problems);
+        let opportunity_since = timestamp_column(row, "opportunity_since")?;
+        let custom = json_column(row, "custom_indicators", problems);
+
+        Ok(StockAnalysisResult {
+            ticker: row.get("ticker"),
+            name: row.get("name"),
+            current_price: row.get("current_price"),
+            rsi: row.get("rsi"),
+            sma_20,
+            sma_50,
+            macd: row.get("macd"),
+            macd_signal: row.get("macd_signal"),
+            macd_histogram: row.get("macd_histogram"),
+            volume: row.get::<Option<i64>, _>("volume").map(|v| v as u64),
+            pct_change: row.get("pct_change"),
+            market_cap: row.get("market_cap"),
+            sector: row.get("sector"),
+            industry: row.get("industry"),
+            exchange: row.get("exchange"),
+            is_opportunity: row.get::<i32, _>("is_opportunity") != 0,
+            signals,
+            timestamp,
+            data_source: row.get("data_source"),
+            data_fetched_at,
+            summary: row.get("summary"),
+            analyst_target: row.get("analyst_target"),
+            upside_to_target_pct: row.get("upside_to_target_pct"),
+            obv: row.get("obv"),
+            vwap: row.get("vwap"),
+            volume_vs_avg: row.get("volume_vs_avg"),
+            high_52w: row.get("high_52w"),
+            low_52w: row.get("low_52w"),
+            pct_from_high: row.get("pct_from_high"),
+            pct_from_low: row.get("pct_from_low"),
+            range_window_complete: row.get::<i32, _>("range_window_complete") != 0,
+            rs_1w: row.get("rs_1w"),
+            rs_1m: row.get("rs_1m"),
+            rs_3m: row.get("rs_3m"),
+            rs_rank: row.get("rs_rank"),
+            // Only the fixed averages have columns
+            moving_averages: [("sma_20", sma_20), ("sma_50", sma_50)]
+                .into_iter()
+                .filter_map(|(key, value)| value.map(|value| (key.to_string(), value)))
+                .collect(),
+            crossovers,
+            data_quality,
+            adx: row.get("adx"),
+            plus_di: row.get("plus_di"),
+            minus_di: row.get("minus_di"),
+            last_split,
+            timeframe: row.get::<String, _>("timeframe").parse().map_err(|e| anyhow::anyhow!("malformed timeframe: {e}"))?,
+            anomalies,
+            rsi_oversold_used: row.get("rsi_oversold_used"),
+            rsi_overbought_used: row.get("rsi_overbought_used"),
+            rsi_sector_zscore: row.get("rsi_sector_zscore"),
+            rsi_industry_zscore: row.get("rsi_industry_zscore"),
+            note: None,
+            opportunity_state: row
+                .get::<String, _>("opportunity_state")
+                .parse()
+                .map_err(|e| anyhow::anyhow!("malformed opportunity_state: {e}"))?,
+            opportunity_streak: row.get::<i64, _>("opportunity_streak") as u32,
+            opportunity_since,
+            custom,
+            bars_used: row.get::<Option<i64>, _>("bars_used").map(|bars| bars as usize),
+        })
+    }
+
+    pub async fn cleanup_old_results(&self, older_than_days: i32) -> Result<usize> {
+        let query = r#"
+        DELETE FROM analysis_results 
+        WHERE timestamp < datetime('now', '-' || ? || ' days')
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(older_than_days)
+            .execute(&self.pool)
+            .await?;
+
+        tracing::info!("Cleaned up {} old analysis results", result.rows_affected());
+        self.optimize().await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    /// Cheapest query that proves the database answers
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Refresh planner statistics so the composite indexes keep being chosen
+    /// as the table grows or shrinks
+    pub async fn optimize(&self) -> Result<()> {
+        sqlx::query("ANALYZE").execute(&self.pool).await?;
+        sqlx::query("PRAGMA optimize").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Insert or replace the rename recorded for `alias.old_symbol`
+    pub async fn upsert_symbol_alias(&self, alias: &SymbolAlias) -> Result<()> {
+        let query = r#"
+        INSERT INTO symbol_aliases (old_symbol, new_symbol, effective_date, created_at)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT(old_symbol) DO UPDATE SET
+            new_symbol = excluded.new_symbol,
+            effective_date = excluded.effective_date
+        "#;
+
+        sqlx::query(query)
+            .bind(&alias.old_symbol)
+            .bind(&alias.new_symbol)
+            .bind(alias.effective_date.to_string())
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_symbol_aliases(&self) -> Result<Vec<SymbolAlias>> {
+        let rows = sqlx::query("SELECT old_symbol, new_symbol, effective_date FROM symbol_aliases ORDER BY old_symbol")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let effective_date: String = row.get("effective_date");
+                Ok(SymbolAlias {
+                    old_symbol: row.get("old_symbol"),
+                    new_symbol: row.get("new_symbol"),
+                    effective_date: effective_date.parse()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Insert or replace the opportunity run of `streak.symbol`
+    pub async fn upsert_opportunity_streak(&self, streak: &OpportunityStreak) -> Result<()> {
+        let query = r#"
+        INSERT INTO opportunity_states (symbol, started_at, streak, last_exited_at, updated_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(symbol) DO UPDATE SET
+            started_at = excluded.started_at,
+            streak = excluded.streak,
+            last_exited_at = excluded.last_exited_at,
+            updated_at = excluded.updated_at
+        "#;
+
+        sqlx::query(query)
+            .bind(&streak.symbol)
+            .bind(streak.started_at.to_rfc3339())
+            .bind(streak.streak as i64)
+            .bind(streak.last_exited_at.map(|t| t.to_rfc3339()))
+            .bind(streak.updated_at.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_opportunity_streaks(&self) -> Result<Vec<OpportunityStreak>> {
+        let rows = sqlx::query("SELECT symbol, started_at, streak, last_exited_at, updated_at FROM opportunity_states ORDER BY symbol")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let parse = |at: String| DateTime::parse_from_rfc3339(&at).map(|at| at.with_timezone(&Utc));
+                let streak: i64 = row.get("streak");
+                Ok(OpportunityStreak {
+                    symbol: row.get("symbol"),
+                    started_at: parse(row.get("started_at"))?,
+                    streak: streak as u32,
+                    last_exited_at: row.get::<Option<String>, _>("last_exited_at").map(parse).transpose()?,
+                    updated_at: parse(row.get("updated_at"))?,
+                })
+            })
+            .collect()
+    }
+
+    /// Insert or replace the failure record for `health.symbol`
+    pub async fn upsert_symbol_health(&self, health: &SymbolHealth) -> Result<()> {
+        let query = r#"
+        INSERT INTO symbol_health (symbol, consecutive_failures, last_error, last_attempt, skip_until_cycle)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(symbol) DO UPDATE SET
+            consecutive_failures = excluded.consecutive_failures,
+            last_error = excluded.last_error,
+            last_attempt = excluded.last_attempt,
+            skip_until_cycle = excluded.skip_until_cycle
+        "#;
+
+        sqlx::query(query)
+            .bind(&health.symbol)
+            .bind(health.consecutive_failures as i64)
+            .bind(&health.last_error)
+            .bind(health.last_attempt.to_rfc3339())
+            .bind(health.skip_until_cycle.map(|cycle| cycle as i64))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_symbol_health(&self) -> Result<Vec<SymbolHealth>> {
+        let rows = sqlx::query(
+            "SELECT symbol, consecutive_failures, last_error, last_attempt, skip_until_cycle FROM symbol_health ORDER BY symbol",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let last_attempt: String = row.get("last_attempt");
+                let failures: i64 = row.get("consecutive_failures");
+                let skip_until: Option<i64> = row.get("skip_until_cycle");
+                Ok(SymbolHealth {
+                    symbol: row.get("symbol"),
+                    consecutive_failures: failures as u32,
+                    last_error: row.get("last_error"),
+                    last_attempt: DateTime::parse_from_rfc3339(&last_attempt)?.with_timezone(&Utc),
+                    skip_until_cycle: skip_until.map(|cycle| cycle as u64),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns false if `symbol` had no failure record
+    pub async fn delete_symbol_health(&self, symbol: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM symbol_health WHERE symbol = ?")
+            .bind(symbol)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Insert or update each symbol's row in the ticker directory
+    pub async fn save_listed_tickers<'a>(&self, entries: impl IntoIterator<Item = &'a ListedTicker>) -> Result<()> {
+        let query = r#"
+        INSERT INTO tickers (symbol, info, first_seen, last_seen, missing_fetches)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(symbol) DO UPDATE SET
+            info = excluded.info,
+            first_seen = excluded.first_seen,
+            last_seen = excluded.last_seen,
+            missing_fetches = excluded.missing_fetches
+        "#;
+
+        let mut tx = self.pool.begin().await?;
+        for entry in entries {
+            sqlx::query(query)
+                .bind(normalize_symbol(&entry.info.symbol))
+                .bind(serde_json::to_string(&entry.info)?)
+                .bind(entry.first_seen.to_rfc3339())
+                .bind(entry.last_seen.to_rfc3339())
+                .bind(entry.missing_fetches as i64)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_listed_tickers(&self) -> Result<Vec<ListedTicker>> {
+        let rows = sqlx::query("SELECT info, first_seen, last_seen, missing_fetches FROM tickers ORDER BY symbol")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let info: String = row.get("info");
+                let first_seen: String = row.get("first_seen");
+                let last_seen: String = row.get("last_seen");
+                let missing_fetches: i64 = row.get("missing_fetches");
+                Ok(ListedTicker {
+                    info: serde_json::from_str(&info)?,
+                    first_seen: DateTime::parse_from_rfc3339(&first_seen)?.with_timezone(&Utc),
+                    last_seen: DateTime::parse_from_rfc3339(&last_seen)?.with_timezone(&Utc),
+                    missing_fetches: missing_fetches as u32,
+                })
+            })
+            .collect()
+    }
+
+    /// Insert or update daily bars, keyed by symbol and timestamp
+    pub async fn store_stock_data(&self, bars: &[StockData]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for bar in bars {
+            insert_bar(&mut tx, bar).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Replace everything stored for `symbol` from `from` on with `bars`.
+    /// Used for the refreshed tail of a series, whose last bar may have been
+    /// partial or timestamped differently by another provider.
+    pub async fn replace_stock_data_from(&self, symbol: &str, from: DateTime<Utc>, bars: &[StockData]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM market_data WHERE symbol = ? AND timestamp >= ?")
+            .bind(symbol)
+            .bind(from.timestamp())
+            .execute(&mut *tx)
+            .await?;
+        for bar in bars {
+            insert_bar(&mut tx, bar).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Insert or update the splits and dividends of `symbol`, keyed by ex-date and kind
+    pub async fn store_corporate_events(&self, symbol: &str, events: &[CorporateEvent]) -> Result<()> {
+        let query = r#"
+        INSERT INTO corporate_events (symbol, timestamp, kind, value)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT(symbol, timestamp, kind) DO UPDATE SET value = excluded.value
+        "#;
+
+        let mut tx = self.pool.begin().await?;
+        for event in events {
+            let (kind, value) = match *event {
+                CorporateEvent::Split { ratio, .. } => ("split", ratio),
+                CorporateEvent::Dividend { amount, .. } => ("dividend", amount),
+            };
+            sqlx::query(query)
+                .bind(symbol)
+                .bind(event.date().timestamp())
+                .bind(kind)
+                .bind(value)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Stored splits and dividends of `symbol`, oldest first
+    pub async fn get_corporate_events(&self, symbol: &str) -> Result<Vec<CorporateEvent>> {
+        let rows = sqlx::query("SELECT timestamp, kind, value FROM corporate_events WHERE symbol = ? ORDER BY timestamp")
+            .bind(symbol)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(corporate_event_from_row).collect()
+    }
+
+    /// Stored splits and dividends of every symbol, oldest first, in one query
+    pub async fn get_all_corporate_events(&self) -> Result<HashMap<String, Vec<CorporateEvent>>> {
+        let rows = sqlx::query("SELECT symbol, timestamp, kind, value FROM corporate_events ORDER BY symbol, timestamp")
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut events: HashMap<String, Vec<CorporateEvent>> = HashMap::new();
+        for row in &rows {
+            events.entry(row.get("symbol")).or_default().push(corporate_event_from_row(row)?);
+        }
+        Ok(events)
+    }
+
+    /// Stored bars for `symbol` between `start` and `end` (inclusive), oldest first
+    pub async fn get_stock_data(&self, symbol: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<StockData>> {
+        let rows = sqlx::query(
+            "SELECT * FROM market_data WHERE symbol = ? AND timestamp BETWEEN ? AND ? ORDER BY timestamp",
+        )
+        .bind(symbol)
+        .bind(start.timestamp())
+        .bind(end.timestamp())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(stock_data_from_row).collect()
+    }
+
+    /// Stored bars of every symbol between `start` and `end` (inclusive),
+    /// oldest first, in one query
+    pub async fn get_all_stock_data(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<HashMap<String, Vec<StockData>>> {
+        let rows = sqlx::query("SELECT * FROM market_data WHERE timestamp BETWEEN ? AND ? ORDER BY symbol, timestamp")
+            .bind(start.timestamp())
+            .bind(end.timestamp())
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut bars: HashMap<String, Vec<StockData>> = HashMap::new();
+        for row in &rows {
+            let bar = stock_data_from_row(row)?;
+            bars.entry(bar.symbol.clone()).or_default().push(bar);
+        }
+        Ok(bars)
+    }
+
+    /// Save the indicator state left after processing `symbol`'s history
+    /// with the given settings, replacing the previous one
+    pub async fn store_indicator_state(&self, symbol: &str, settings: &str, snapshot: &IndicatorSnapshot) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO indicator_state (symbol, settings, last_timestamp, state, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(symbol, settings) DO UPDATE SET
+                last_timestamp = excluded.last_timestamp,
+                state = excluded.state,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(symbol)
+        .bind(settings)
+        .bind(snapshot.fingerprint.last_timestamp.map(|t| t.to_rfc3339()))
+        .bind(serde_json::to_string(snapshot)?)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Indicator state saved for `symbol` and the given settings
+    pub async fn get_indicator_state(&self, symbol: &str, settings: &str) -> Result<Option<IndicatorSnapshot>> {
+        let state: Option<String> = sqlx::query_scalar("SELECT state FROM indicator_state WHERE symbol = ? AND settings = ?")
+            .bind(symbol)
+            .bind(settings)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(state.map(|state| serde_json::from_str(&state)).transpose()?)
+    }
+
+    /// Store drained session events in one transaction. An event already
+    /// stored under the same session and sequence number is kept.
+    pub async fn store_session_events(&self, events: &[(String, SessionEvent)]) -> Result<()> {
+        let query = r#"
+        INSERT OR IGNORE INTO session_events (session_id, seq, timestamp, ticker, level, event)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#;
+
+        let mut tx = self.pool.begin().await?;
+        for (session_id, event) in events {
+            sqlx::query(query)
+                .bind(session_id)
+                .bind(event.seq as i64)
+                .bind(event.timestamp.to_rfc3339())
+                .bind(&event.ticker)
+                .bind(event.level.as_str())
+                .bind(serde_json::to_string(event)?)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Stored events of `session_id` passing `filter`, oldest first. Up to
+    /// `filter.limit + 1` are returned so callers can tell whether more follow.
+    pub async fn get_session_events(&self, session_id: &str, filter: &EventFilter) -> Result<Vec<SessionEvent>> {
+        let levels: Vec<EventLevel> = filter.min_level.unwrap_or(EventLevel::Debug).and_above().collect();
+        let sql = format!(
+            "SELECT event FROM session_events WHERE session_id = ? AND seq > ? AND level IN ({}) {} ORDER BY seq LIMIT ?",
+            vec!["?"; levels.len()].join(", "),
+            if filter.ticker.is_some() { "AND ticker = ? COLLATE NOCASE" } else { "" },
+        );
+        let mut query = sqlx::query_scalar::<_, String>(&sql).bind(session_id).bind(filter.after_seq as i64);
+        for level in levels {
+            query = query.bind(level.as_str());
+        }
+        if let Some(ref ticker) = filter.ticker {
+            query = query.bind(ticker);
+        }
+        let rows = query.bind(filter.limit as i64 + 1).fetch_all(&self.pool).await?;
+        Ok(rows.iter().map(|event| serde_json::from_str(event)).collect::<Result<_, _>>()?)
+    }
+
+    /// Sequence number of the newest stored event of `session_id`
+    pub async fn last_session_event_seq(&self, session_id: &str) -> Result<Option<u64>> {
+        let seq: Option<i64> = sqlx::query_scalar("SELECT MAX(seq) FROM session_events WHERE session_id = ?")
+            .bind(session_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(seq.map(|seq| seq as u64))
+    }
+
+    /// Insert or update the daily indicator values of `symbol`, keyed by the
+    /// bar's date, in a single transaction. Returns how many rows were written.
+    pub async fn store_indicator_series(&self, symbol: &str, series: &[(&StockData, &TechnicalIndicators)]) -> Result<usize> {
+        let query = r#"
+        INSERT INTO indicator_history (symbol, date, rsi, sma_20, sma_50, macd, macd_signal, macd_histogram)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(symbol, date) DO UPDATE SET
+            rsi = excluded.rsi,
+            sma_20 = excluded.sma_20,
+            sma_50 = excluded.sma_50,
+            macd = excluded.macd,
+            macd_signal = excluded.macd_signal,
+            macd_histogram = excluded.macd_histogram
+        "#;
+
+        let mut tx = self.pool.begin().await?;
+        for (bar, indicators) in series {
+            let point = IndicatorHistoryPoint::new(bar, indicators);
+            sqlx::query(query)
+                .bind(symbol)
+                .bind(point.date.to_string())
+                .bind(point.rsi)
+                .bind(point.sma_20)
+                .bind(point.sma_50)
+                .bind(point.macd)
+                .bind(point.macd_signal)
+                .bind(point.macd_histogram)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(series.len())
+    }
+
+    /// Date of the newest stored indicator row for `symbol`
+    pub async fn latest_indicator_date(&self, symbol: &str) -> Result<Option<NaiveDate>> {
+        let date: Option<String> = sqlx::query_scalar("SELECT MAX(date) FROM indicator_history WHERE symbol = ?")
+            .bind(symbol)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(date.map(|date| date.parse()).transpose()?)
+    }
+
+    /// Stored indicator values for `symbol` from `since` on, oldest first
+    pub async fn get_indicator_history(&self, symbol: &str, since: NaiveDate) -> Result<Vec<IndicatorHistoryPoint>> {
+        let rows = sqlx::query("SELECT * FROM indicator_history WHERE symbol = ? AND date >= ? ORDER BY date")
+            .bind(symbol)
+            .bind(since.to_string())
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(indicator_history_point).collect()
+    }
+
+    /// Stored indicator values of every symbol from `since` on, oldest
+    /// first, in one query
+    pub async fn get_all_indicator_history(&self, since: NaiveDate) -> Result<HashMap<String, Vec<IndicatorHistoryPoint>>> {
+        let rows = sqlx::query("SELECT * FROM indicator_history WHERE date >= ? ORDER BY symbol, date")
+            .bind(since.to_string())
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut history: HashMap<String, Vec<IndicatorHistoryPoint>> = HashMap::new();
+        for row in &rows {
+            history.entry(row.get("symbol")).or_default().push(indicator_history_point(row)?);
+        }
+        Ok(history)
+    }
+
+    pub async fn cleanup_old_indicator_history(&self, older_than_days: i32) -> Result<usize> {
+        let query = r#"
+        DELETE FROM indicator_history
+        WHERE date < date('now', '-' || ? || ' days')
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(older_than_days)
+            .execute(&self.pool)
+            .await?;
+
+        tracing::info!("Cleaned up {} old indicator history rows", result.rows_affected());
+        self.optimize().await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    /// Add `symbol` to the watchlist, or put a removed one back. Returns
+    /// false if it was already there.
+    pub async fn add_to_watchlist(&self, symbol: &str, source_ip: Option<&str>) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let existing = watchlist_entry(&mut tx, symbol).await?;
+        if existing.as_ref().is_some_and(|entry| entry.deleted_at.is_none()) {
+            return Ok(false);
+        }
+        let entry = WatchlistEntry { symbol: symbol.to_string(), added_at: Utc::now(), note: None, deleted_at: None };
+        sqlx::query(
+            "INSERT INTO watchlist (symbol, added_at) VALUES (?, ?) \
+             ON CONFLICT(symbol) DO UPDATE SET added_at = excluded.added_at, note = NULL, deleted_at = NULL",
+        )
+        .bind(symbol)
+        .bind(entry.added_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+        insert_audit(&mut tx, AuditResource::Watchlist, symbol, AuditAction::Create, existing.as_ref(), Some(&entry), source_ip).await?;
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Soft-delete `symbol` from the watchlist. Returns false if it was not on it.
+    pub async fn remove_from_watchlist(&self, symbol: &str, source_ip: Option<&str>) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let Some(entry) = watchlist_entry(&mut tx, symbol).await?.filter(|entry| entry.deleted_at.is_none()) else {
+            return Ok(false);
+        };
+        let removed = WatchlistEntry { deleted_at: Some(Utc::now()), ..entry.clone() };
+        sqlx::query("UPDATE watchlist SET deleted_at = ? WHERE symbol = ?")
+            .bind(removed.deleted_at.map(|t| t.to_rfc3339()))
+            .bind(symbol)
+            .execute(&mut *tx)
+            .await?;
+        insert_audit(&mut tx, AuditResource::Watchlist, symbol, AuditAction::Delete, Some(&entry), Some(&removed), source_ip).await?;
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Put a removed symbol back on the watchlist. `None` if it was not removed.
+    pub async fn restore_watchlist_entry(&self, symbol: &str, source_ip: Option<&str>) -> Result<Option<WatchlistEntry>> {
+        let mut tx = self.pool.begin().await?;
+        let Some(entry) = watchlist_entry(&mut tx, symbol).await?.filter(|entry| entry.deleted_at.is_some()) else {
+            return Ok(None);
+        };
+        let restored = WatchlistEntry { deleted_at: None, ..entry.clone() };
+        sqlx::query("UPDATE watchlist SET deleted_at = NULL WHERE symbol = ?")
+            .bind(symbol)
+            .execute(&mut *tx)
+            .await?;
+        insert_audit(&mut tx, AuditResource::Watchlist, symbol, AuditAction::Restore, Some(&entry), Some(&restored), source_ip).await?;
+        tx.commit().await?;
+
+        Ok(Some(restored))
+    }
+
+    /// Add every entry not already on the watchlist in one transaction,
+    /// putting removed ones back with the new entry's note and time.
+    /// Returns the symbols added, in the order given.
+    pub async fn import_watchlist(&self, entries: &[WatchlistEntry], source_ip: Option<&str>) -> Result<Vec<String>> {
+        let mut tx = self.pool.begin().await?;
+        let mut added = Vec::new();
+        for entry in entries {
+            let existing = watchlist_entry(&mut tx, &entry.symbol).await?;
+            if existing.as_ref().is_some_and(|existing| existing.deleted_at.is_none()) {
+                continue;
+            }
+            sqlx::query(
+                "INSERT INTO watchlist (symbol, added_at, note) VALUES (?, ?, ?) \
+                 ON CONFLICT(symbol) DO UPDATE SET added_at = excluded.added_at, note = excluded.note, deleted_at = NULL",
+            )
+            .bind(&entry.symbol)
+            .bind(entry.added_at.to_rfc3339())
+            .bind(&entry.note)
+            .execute(&mut *tx)
+            .await?;
+            insert_audit(&mut tx, AuditResource::Watchlist, &entry.symbol, AuditAction::Create, existing.as_ref(), Some(entry), source_ip).await?;
+            added.push(entry.symbol.clone());
+        }
+        tx.commit().await?;
+
+        Ok(added)
+    }
+
+    /// Watchlist symbols, oldest first; removed ones only with `include_deleted`
+    pub async fn get_watchlist(&self, include_deleted: bool) -> Result<Vec<WatchlistEntry>> {
+        let rows = sqlx::query("SELECT symbol, added_at, note, deleted_at FROM watchlist WHERE ? OR deleted_at IS NULL ORDER BY added_at, symbol")
+            .bind(include_deleted)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(watchlist_from_row).collect()
+    }
+
+    /// Store `filter` and its opportunity rules under `name`, replacing any
+    /// preset with that name; a deleted one comes back with the new filter
+    pub async fn save_preset(
+        &self,
+        name: &str,
+        filter: &StockFilter,
+        opportunity_rules: Option<&[OpportunityRule]>,
+        source_ip: Option<&str>,
+    ) -> Result<FilterPreset> {
+        let saved = FilterPreset {
+            name: name.to_string(),
+            filter: filter.clone(),
+            opportunity_rules: opportunity_rules.map(<[OpportunityRule]>::to_vec),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        };
+        let mut tx = self.pool.begin().await?;
+        let existing = preset(&mut tx, name).await?;
+        sqlx::query(
+            "INSERT INTO filter_presets (name, filter, updated_at, opportunity_rules) VALUES (?, ?, ?, ?) \
+             ON CONFLICT(name) DO UPDATE SET filter = excluded.filter, updated_at = excluded.updated_at, \
+             opportunity_rules = excluded.opportunity_rules, deleted_at = NULL",
+        )
+        .bind(name)
+        .bind(serde_json::to_string(filter)?)
+        .bind(saved.updated_at.to_rfc3339())
+        .bind(opportunity_rules.map(serde_json::to_string).transpose()?)
+        .execute(&mut *tx)
+        .await?;
+        let action = match existing {
+            Some(ref existing) if existing.deleted_at.is_none() => AuditAction::Update,
+            _ => AuditAction::Create,
+        };
+        insert_audit(&mut tx, AuditResource::Presets, name, action, existing.as_ref(), Some(&saved), source_ip).await?;
+        tx.commit().await?;
+
+        Ok(saved)
+    }
+
+    /// Every preset, by name; deleted ones only with `include_deleted`
+    pub async fn list_presets(&self, include_deleted: bool) -> Result<Vec<FilterPreset>> {
+        let rows = sqlx::query(
+            "SELECT name, filter, updated_at, opportunity_rules, deleted_at FROM filter_presets \
+             WHERE ? OR deleted_at IS NULL ORDER BY name",
+        )
+        .bind(include_deleted)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(preset_from_row).collect()
+    }
+
+    /// The preset called `name`, unless it was deleted
+    pub async fn get_preset(&self, name: &str) -> Result<Option<FilterPreset>> {
+        let mut conn = self.pool.acquire().await?;
+        Ok(preset(&mut conn, name).await?.filter(|preset| preset.deleted_at.is_none()))
+    }
+
+    /// Soft-delete a preset. Returns false if there was no preset called `name`.
+    pub async fn delete_preset(&self, name: &str, source_ip: Option<&str>) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let Some(existing) = preset(&mut tx, name).await?.filter(|preset| preset.deleted_at.is_none()) else {
+            return Ok(false);
+        };
+        let deleted = FilterPreset { deleted_at: Some(Utc::now()), ..existing.clone() };
+        sqlx::query("UPDATE filter_presets SET deleted_at = ? WHERE name = ?")
+            .bind(deleted.deleted_at.map(|t| t.to_rfc3339()))
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+        insert_audit(&mut tx, AuditResource::Presets, name, AuditAction::Delete, Some(&existing), Some(&deleted), source_ip).await?;
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Undo a preset's deletion. `None` if it was not deleted.
+    pub async fn restore_preset(&self, name: &str, source_ip: Option<&str>) -> Result<Option<FilterPreset>> {
+        let mut tx = self.pool.begin().await?;
+        let Some(existing) = preset(&mut tx, name).await?.filter(|preset| preset.deleted_at.is_some()) else {
+            return Ok(None);
+        };
+        let restored = FilterPreset { deleted_at: None, ..existing.clone() };
+        sqlx::query("UPDATE filter_presets SET deleted_at = NULL WHERE name = ?")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+        insert_audit(&mut tx, AuditResource::Presets, name, AuditAction::Restore, Some(&existing), Some(&restored), source_ip).await?;
+        tx.commit().await?;
+
+        Ok(Some(restored))
+    }
+
+    pub async fn create_alert(&self, alert: &NewAlert, source_ip: Option<&str>) -> Result<Alert> {
+        let created_at = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query(
+            "INSERT INTO alerts (symbol, condition_type, condition_value, is_active, is_triggered, created_at) \
+             VALUES (?, ?, ?, 1, 0, ?)",
+        )
+        .bind(&alert.symbol)
+        .bind(alert.condition_type.as_str())
+        .bind(alert.condition_value)
+        .bind(created_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+
+        let alert = Alert {
+            id: result.last_insert_rowid(),
+            symbol: alert.symbol.clone(),
+            condition_type: alert.condition_type,
+            condition_value: alert.condition_value,
+            is_active: true,
+            is_triggered: false,
+            created_at,
+            deleted_at: None,
+        };
+        insert_audit(&mut tx, AuditResource::Alerts, &alert.id.to_string(), AuditAction::Create, None, Some(&alert), source_ip).await?;
+        tx.commit().await?;
+
+        Ok(alert)
+    }
+
+    /// All alerts, oldest first; deleted ones only with `include_deleted`
+    pub async fn get_alerts(&self, include_deleted: bool) -> Result<Vec<Alert>> {
+        let rows = sqlx::query(
+            "SELECT id, symbol, condition_type, condition_value, is_active, is_triggered, created_at, deleted_at \
+             FROM alerts WHERE ? OR deleted_at IS NULL ORDER BY id",
+        )
+        .bind(include_deleted)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(alert_from_row).collect()
+    }
+
+    /// Active alerts watching `symbol`
+    pub async fn get_active_alerts(&self, symbol: &str) -> Result<Vec<Alert>> {
+        let rows = sqlx::query(
+            "SELECT id, symbol, condition_type, condition_value, is_active, is_triggered, created_at, deleted_at \
+             FROM alerts WHERE symbol = ? AND is_active = 1 AND deleted_at IS NULL ORDER BY id",
+        )
+        .bind(symbol)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(alert_from_row).collect()
+    }
+
+    /// Soft-delete an alert, keeping its trigger history for a restore.
+    /// Returns false if it did not exist.
+    pub async fn delete_alert(&self, id: i64, source_ip: Option<&str>) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let Some(existing) = alert(&mut tx, id).await?.filter(|alert| alert.deleted_at.is_none()) else {
+            return Ok(false);
+        };
+        let deleted = Alert { deleted_at: Some(Utc::now()), ..existing.clone() };
+        sqlx::query("UPDATE alerts SET deleted_at = ? WHERE id = ?")
+            .bind(deleted.deleted_at.map(|t| t.to_rfc3339()))
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        insert_audit(&mut tx, AuditResource::Alerts, &id.to_string(), AuditAction::Delete, Some(&existing), Some(&deleted), source_ip).await?;
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Undo an alert's deletion. `None` if it was not deleted.
+    pub async fn restore_alert(&self, id: i64, source_ip: Option<&str>) -> Result<Option<Alert>> {
+        let mut tx = self.pool.begin().await?;
+        let Some(existing) = alert(&mut tx, id).await?.filter(|alert| alert.deleted_at.is_some()) else {
+            return Ok(None);
+        };
+        let restored = Alert { deleted_at: None, ..existing.clone() };
+        sqlx::query("UPDATE alerts SET deleted_at = NULL WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        insert_audit(&mut tx, AuditResource::Alerts, &id.to_string(), AuditAction::Restore, Some(&existing), Some(&restored), source_ip).await?;
+        tx.commit().await?;
+
+        Ok(Some(restored))
+    }
+
+    pub async fn create_note(&self, note: &NewNote, source_ip: Option<&str>) -> Result<TickerNote> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query("INSERT INTO ticker_notes (symbol, note, created_at, updated_at, expires_at) VALUES (?, ?, ?, ?, ?)")
+            .bind(&note.symbol)
+            .bind(&note.note)
+            .bind(now.to_rfc3339())
+            .bind(now.to_rfc3339())
+            .bind(note.expires_at.map(|t| t.to_rfc3339()))
+            .execute(&mut *tx)
+            .await?;
+
+        let note = TickerNote {
+            id: result.last_insert_rowid(),
+            symbol: note.symbol.clone(),
+            note: note.note.clone(),
+            created_at: now,
+            updated_at: now,
+            expires_at: note.expires_at,
+        };
+        insert_audit(&mut tx, AuditResource::Notes, &note.id.to_string(), AuditAction::Create, None, Some(&note), source_ip).await?;
+        tx.commit().await?;
+
+        Ok(note)
+    }
+
+    /// Notes, oldest first, optionally on one symbol; expired ones only with
+    /// `include_expired`
+    pub async fn get_notes(&self, symbol: Option<&str>, include_expired: bool) -> Result<Vec<TickerNote>> {
+        let rows = sqlx::query(
+            "SELECT id, symbol, note, created_at, updated_at, expires_at FROM ticker_notes \
+             WHERE (? IS NULL OR symbol = ?) ORDER BY id",
+        )
+        .bind(symbol)
+        .bind(symbol)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let now = Utc::now();
+        let notes = rows.iter().map(note_from_row).collect::<Result<Vec<_>>>()?;
+        Ok(notes.into_iter().filter(|note| include_expired || note.is_active(now)).collect())
+    }
+
+    /// The note to show with each symbol's results, keyed by symbol
+    pub async fn get_active_notes(&self) -> Result<HashMap<String, String>> {
+        Ok(active_notes(&self.get_notes(None, false).await?, Utc::now()))
+    }
+
+    /// The note to show with `symbol`'s results, if it has one
+    pub async fn get_active_note(&self, symbol: &str) -> Result<Option<String>> {
+        let mut notes = active_notes(&self.get_notes(Some(symbol), false).await?, Utc::now());
+        Ok(notes.remove(symbol))
+    }
+
+    /// Replace a note's text and expiry. `None` if it does not exist.
+    pub async fn update_note(&self, id: i64, update: &NoteUpdate, source_ip: Option<&str>) -> Result<Option<TickerNote>> {
+        let mut tx = self.pool.begin().await?;
+        let Some(existing) = ticker_note(&mut tx, id).await? else {
+            return Ok(None);
+        };
+        let updated = TickerNote {
+            note: update.note.clone(),
+            expires_at: update.expires_at,
+            updated_at: Utc::now(),
+            ..existing.clone()
+        };
+        sqlx::query("UPDATE ticker_notes SET note = ?, updated_at = ?, expires_at = ? WHERE id = ?")
+            .bind(&updated.note)
+            .bind(updated.updated_at.to_rfc3339())
+            .bind(updated.expires_at.map(|t| t.to_rfc3339()))
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        insert_audit(&mut tx, AuditResource::Notes, &id.to_string(), AuditAction::Update, Some(&existing), Some(&updated), source_ip).await?;
+        tx.commit().await?;
+
+        Ok(Some(updated))
+    }
+
+    /// Delete a note; the audit log keeps its text. Returns false if it did
+    /// not exist.
+    pub async fn delete_note(&self, id: i64, source_ip: Option<&str>) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let Some(existing) = ticker_note(&mut tx, id).await? else {
+            return Ok(false);
+        };
+        sqlx::query("DELETE FROM ticker_notes WHERE id = ?").bind(id).execute(&mut *tx).await?;
+        insert_audit::<TickerNote>(&mut tx, AuditResource::Notes, &id.to_string(), AuditAction::Delete, Some(&existing), None, source_ip).await?;
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Store a custom indicator under its name, replacing the expression of
+    /// any with that name
+    pub async fn save_custom_indicator(&self, indicator: &NewCustomIndicator, source_ip: Option<&str>) -> Result<CustomIndicator> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        let existing = custom_indicator(&mut tx, &indicator.name).await?;
+        let saved = CustomIndicator {
+            name: indicator.name.clone(),
+            expression: indicator.expression.clone(),
+            created_at: existing.as_ref().map_or(now, |existing| existing.created_at),
+            updated_at: now,
+        };
+        sqlx::query(
+            "INSERT INTO custom_indicators (name, expression, created_at, updated_at) VALUES (?, ?, ?, ?) \
+             ON CONFLICT(name) DO UPDATE SET expression = excluded.expression, updated_at = excluded.updated_at",
+        )
+        .bind(&saved.name)
+        .bind(&saved.expression)
+        .bind(saved.created_at.to_rfc3339())
+        .bind(saved.updated_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+        let action = if existing.is_some() { AuditAction::Update } else { AuditAction::Create };
+        insert_audit(&mut tx, AuditResource::CustomIndicators, &saved.name, action, existing.as_ref(), Some(&saved), source_ip).await?;
+        tx.commit().await?;
+
+        Ok(saved)
+    }
+
+    /// Every custom indicator, by name
+    pub async fn get_custom_indicators(&self) -> Result<Vec<CustomIndicator>> {
+        let rows = sqlx::query("SELECT name, expression, created_at, updated_at FROM custom_indicators ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(custom_indicator_from_row).collect()
+    }
+
+    /// Delete a custom indicator; results already stored keep its values.
+    /// Returns false if there was none called `name`.
+    pub async fn delete_custom_indicator(&self, name: &str, source_ip: Option<&str>) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let Some(existing) = custom_indicator(&mut tx, name).await? else {
+            return Ok(false);
+        };
+        sqlx::query("DELETE FROM custom_indicators WHERE name = ?").bind(name).execute(&mut *tx).await?;
+        insert_audit::<CustomIndicator>(&mut tx, AuditResource::CustomIndicators, name, AuditAction::Delete, Some(&existing), None, source_ip)
+            .await?;
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Changes made through the API, newest first, optionally to one
+    /// resource type or one resource
+    pub async fn get_audit_log(&self, resource: Option<AuditResource>, resource_id: Option<&str>, limit: u32) -> Result<Vec<AuditEntry>> {
+        let rows = sqlx::query(
+            "SELECT id, resource, resource_id, action, old_value, new_value, source_ip, created_at FROM audit_log \
+             WHERE (? IS NULL OR resource = ?) AND (? IS NULL OR resource_id = ?) ORDER BY id DESC LIMIT ?",
+        )
+        .bind(resource.map(|resource| resource.as_str()))
+        .bind(resource.map(|resource| resource.as_str()))
+        .bind(resource_id)
+        .bind(resource_id)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let resource: String = row.get("resource");
+                let action: String = row.get("action");
+                let old_value: Option<String> = row.get("old_value");
+                let new_value: Option<String> = row.get("new_value");
+                let created_at: String = row.get("created_at");
+                Ok(AuditEntry {
+                    id: row.get("id"),
+                    resource: resource.parse().map_err(anyhow::Error::msg)?,
+                    resource_id: row.get("resource_id"),
+                    action: action.parse().map_err(anyhow::Error::msg)?,
+                    old_value: old_value.as_deref().map(serde_json::from_str).transpose()?,
+                    new_value: new_value.as_deref().map(serde_json::from_str).transpose()?,
+                    source_ip: row.get("source_ip"),
+                    created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+                })
+            })
+            .collect()
+    }
+
+    /// Record a firing and mark the alert triggered so it waits to re-arm
+    pub async fn record_alert_trigger(&self, trigger: &AlertTrigger) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO alert_triggers (alert_id, symbol, condition_type, condition_value, observed_value, triggered_at) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(trigger.alert_id)
+        .bind(&trigger.symbol)
+        .bind(trigger.condition_type.as_str())
+        .bind(trigger.condition_value)
+        .bind(trigger.observed_value)
+        .bind(trigger.triggered_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE alerts SET is_triggered = 1 WHERE id = ?")
+            .bind(trigger.alert_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Allow a triggered alert to fire again
+    pub async fn rearm_alert(&self, id: i64) -> Result<()> {
+        sqlx::query("UPDATE alerts SET is_triggered = 0 WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Firings of one alert, oldest first
+    pub async fn get_alert_triggers(&self, alert_id: i64) -> Result<Vec<AlertTrigger>> {
+        let rows = sqlx::query(
+            "SELECT alert_id, symbol, condition_type, condition_value, observed_value, triggered_at \
+             FROM alert_triggers WHERE alert_id = ? ORDER BY id",
+        )
+        .bind(alert_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let condition_type: String = row.get("condition_type");
+                let triggered_at: String = row.get("triggered_at");
+                Ok(AlertTrigger {
+                    alert_id: row.get("alert_id"),
+                    symbol: row.get("symbol"),
+                    condition_type: condition_type.parse().map_err(anyhow::Error::msg)?,
+                    condition_value: row.get("condition_value"),
+                    observed_value: row.get("observed_value"),
+                    triggered_at: DateTime::parse_from_rfc3339(&triggered_at)?.with_timezone(&Utc),
+                })
+            })
+            .collect()
+    }
+
+    pub async fn record_delivery(&self, delivery: &Delivery) -> Result<()> {
+        let notification = &delivery.notification;
+        sqlx::query(
+            "INSERT INTO notification_log (kind, symbol, title, message, channel, status, attempts, error, created_at, finished_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(notification.kind.as_str())
+        .bind(&notification.symbol)
+        .bind(&notification.title)
+        .bind(&notification.message)
+        .bind(&delivery.channel)
+        .bind(delivery.status.as_str())
+        .bind(delivery.attempts as i64)
+        .bind(&delivery.error)
+        .bind(notification.created_at.to_rfc3339())
+        .bind(delivery.finished_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Logged deliveries, newest first
+    pub async fn get_notification_log(&self, limit: Option<i32>) -> Result<Vec<Delivery>> {
+        // A negative LIMIT means "no limit" in SQLite
+        let rows = sqlx::query("SELECT * FROM notification_log ORDER BY id DESC LIMIT ?")
+            .bind(limit.unwrap_or(-1))
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let kind: String = row.get("kind");
+                let status: String = row.get("status");
+                let created_at: String = row.get("created_at");
+                let finished_at: String = row.get("finished_at");
+                Ok(Delivery {
+                    notification: Notification {
+                        kind: kind.parse().map_err(anyhow::Error::msg)?,
+                        symbol: row.get("symbol"),
+                        title: row.get("title"),
+                        message: row.get("message"),
+                        created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+                    },
+                    channel: row.get("channel"),
+                    status: status.parse().map_err(anyhow::Error::msg)?,
+                    attempts: row.get::<i64, _>("attempts") as u32,
+                    error: row.get("error"),
+                    finished_at: DateTime::parse_from_rfc3339(&finished_at)?.with_timezone(&Utc),
+                })
+            })
+            .collect()
+    }
+
+    /// Delete the results `policy` no longer keeps as of `now`: everything
+    /// past the retention window, and before the full-detail window every
+    /// session but the latest `sessions_per_day` of each day. Runs on a
+    /// connection of its own, one chunk per statement, so writes through the
+    /// pool wait for a chunk at most. Returns the expired and thinned counts.
+    pub async fn prune_results(&self, policy: &RetentionPolicy, now: DateTime<Utc>) -> Result<(u64, u64)> {
+        let (expire_before, thin_before) = policy.cutoffs(now);
+        let (expire_before, thin_before) = (expire_before.to_rfc3339(), thin_before.to_rfc3339());
+        let mut conn = self.pool.connect_options().connect().await?;
+
+        let expired = delete_results_in_chunks(&mut conn, "timestamp < ?", &[&expire_before]).await?;
+
+        // Days are compared on the date part of the stored RFC 3339 timestamps
+        let excess: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT analysis_session, day FROM (
+                SELECT analysis_session, substr(timestamp, 1, 10) AS day,
+                    ROW_NUMBER() OVER (PARTITION BY substr(timestamp, 1, 10) ORDER BY MAX(timestamp) DESC) AS rank
+                FROM analysis_results
+                WHERE timestamp < ?
+                GROUP BY analysis_session, day
+            )
+            WHERE rank > ?
+            "#,
+        )
+        .bind(&thin_before)
+        .bind(policy.sessions_per_day)
+        .fetch_all(&mut conn)
+        .await?;
+        let mut thinned = 0;
+        for (session, day) in &excess {
+            let condition = "analysis_session = ? AND substr(timestamp, 1, 10) = ? AND timestamp < ?";
+            thinned += delete_results_in_chunks(&mut conn, condition, &[session, day, &thin_before]).await?;
+        }
+
+        conn.close().await?;
+        Ok((expired, thinned))
+    }
+
+    /// Rebuild the file to hand freed pages back to the filesystem, then
+    /// let SQLite refresh its statistics
+    pub async fn vacuum(&self) -> Result<()> {
+        let mut conn = self.pool.connect_options().connect().await?;
+        sqlx::query("VACUUM").execute(&mut conn).await?;
+        sqlx::query("PRAGMA optimize").execute(&mut conn).await?;
+        conn.close().await?;
+        Ok(())
+    }
+
+    /// Size of the database file, free pages included
+    pub async fn size_bytes(&self) -> Result<u64> {
+        let pages: i64 = sqlx::query_scalar("PRAGMA page_count").fetch_one(&self.pool).await?;
+        let page_size: i64 = sqlx::query_scalar("PRAGMA page_size").fetch_one(&self.pool).await?;
+        Ok((pages * page_size) as u64)
+    }
+
+    pub async fn record_maintenance(&self, report: &MaintenanceReport) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO maintenance_runs (started_at, finished_at, expired_results, thinned_results, bytes_before, bytes_after) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(report.started_at.to_rfc3339())
+        .bind(report.finished_at.to_rfc3339())
+        .bind(report.expired_results as i64)
+        .bind(report.thinned_results as i64)
+        .bind(report.bytes_before as i64)
+        .bind(report.bytes_after as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn last_maintenance(&self) -> Result<Option<MaintenanceReport>> {
+        let Some(row) = sqlx::query("SELECT * FROM maintenance_runs ORDER BY id DESC LIMIT 1")
+            .fetch_optional(&self.pool)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let started_at: String = row.get("started_at");
+        let finished_at: String = row.get("finished_at");
+        Ok(Some(MaintenanceReport {
+            started_at: DateTime::parse_from_rfc3339(&started_at)?.with_timezone(&Utc),
+            finished_at: DateTime::parse_from_rfc3339(&finished_at)?.with_timezone(&Utc),
+            expired_results: row.get::<i64, _>("expired_results") as u64,
+            thinned_results: row.get::<i64, _>("thinned_results") as u64,
+            bytes_before: row.get::<i64, _>("bytes_before") as u64,
+            bytes_after: row.get::<i64, _>("bytes_after") as u64,
+        }))
+    }
+
+    pub async fn get_analysis_stats(&self) -> Result<AnalysisStats> {
+        let query = r#"
+        SELECT 
+            COUNT(*) as total_results,
+            COUNT(DISTINCT ticker) as unique_tickers,
+            COUNT(DISTINCT analysis_session) as total_sessions,
+            SUM(CASE WHEN is_opportunity = 1 THEN 1 ELSE 0 END) as opportunities,
+            AVG(rsi) as avg_rsi,
+            MIN(timestamp) as oldest_result,
+            MAX(timestamp) as newest_result
+        FROM analysis_results
+        "#;
+
+        let row = sqlx::query(query).fetch_one(&self.pool).await?;
+
+        // A damaged timestamp sorts like any other string, so it can end up
+        // as the bound; leave that bound out rather than fail the stats
+        let bound = |column: &str| {
+            timestamp_column(&row, column).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring {} in database stats: {}", column, e);
+                None
+            })
+        };
+
+        Ok(AnalysisStats {
+            total_results: row.get::<i64, _>("total_results") as u64,
+            unique_tickers: row.get::<i64, _>("unique_tickers") as u64,
+            total_sessions: row.get::<i64, _>("total_sessions") as u64,
+            // SUM over no rows is NULL
+            opportunities: row.get::<Option<i64>, _>("opportunities").unwrap_or(0) as u64,
+            avg_rsi: row.get("avg_rsi"),
+            oldest_result: bound("oldest_result"),
+            newest_result: bound("newest_result"),
+            size_bytes: self.size_bytes().await?,
+            last_maintenance: self.last_maintenance().await?,
+            corrupt_rows: self.corrupt_rows.lock().unwrap().len() as u64,
+        })
+    }
+
+    /// Run SQLite's `PRAGMA integrity_check` and decode every stored
+    /// analysis result, reporting the rows reads would patch up or skip
+    pub async fn integrity_check(&self) -> Result<IntegrityReport> {
+        let sqlite: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check").fetch_all(&self.pool).await?;
+
+        let mut rows = sqlx::query("SELECT * FROM analysis_results ORDER BY rowid").fetch(&self.pool);
+        let mut rows_checked = 0;
+        let mut corrupt_row_count = 0;
+        let mut corrupt_rows = Vec::new();
+        while let Some(row) = rows.try_next().await? {
+            rows_checked += 1;
+            let mut problems = Vec::new();
+            let decoded = Self::row_to_result(&row, &mut problems);
+            let skipped = decoded.is_err();
+            if let Err(e) = decoded {
+                problems.push(e.to_string());
+            }
+            if problems.is_empty() {
+                continue;
+            }
+            corrupt_row_count += 1;
+            if corrupt_rows.len() < MAX_REPORTED_CORRUPT_ROWS {
+                corrupt_rows.push(CorruptRow {
+                    id: row.get("id"),
+                    ticker: row.get("ticker"),
+                    problems,
+                    skipped,
+                });
+            }
+        }
+
+        Ok(IntegrityReport {
+            ok: sqlite == ["ok"] && corrupt_row_count == 0,
+            sqlite,
+            rows_checked,
+            corrupt_row_count,
+            corrupt_rows,
+        })
+    }
+}
+
+/// Delete the analysis results matching `condition` `DELETE_CHUNK_SIZE` rows
+/// per statement, each its own transaction
+async fn delete_results_in_chunks(conn: &mut SqliteConnection, condition: &str, binds: &[&String]) -> Result<u64> {
+    let sql = format!(
+        "DELETE FROM analysis_results WHERE rowid IN (SELECT rowid FROM analysis_results WHERE {} LIMIT {})",
+        condition, DELETE_CHUNK_SIZE
+    );
+    let mut deleted = 0;
+    loop {
+        let query = binds.iter().fold(sqlx::query(&sql), |query, bind| query.bind(*bind));
+        let rows = query.execute(&mut *conn).await?.rows_affected();
+        deleted += rows;
+        if rows < u64::from(DELETE_CHUNK_SIZE) {
+            return Ok(deleted);
+        }
+        tokio::task::yield_now().await;
+    }
+}
+
+fn alert_from_row(row: &SqliteRow) -> Result<Alert> {
+    let condition_type: String = row.get("condition_type");
+    let created_at: String = row.get("created_at");
+    Ok(Alert {
+        id: row.get("id"),
+        symbol: row.get("symbol"),
+        condition_type: condition_type.parse().map_err(anyhow::Error::msg)?,
+        condition_value: row.get("condition_value"),
+        is_active: row.get::<i32, _>("is_active") != 0,
+        is_triggered: row.get::<i32, _>("is_triggered") != 0,
+        created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+        deleted_at: deleted_at(row)?,
+    })
+}
+
+fn note_from_row(row: &SqliteRow) -> Result<TickerNote> {
+    let timestamp = |column: &str| -> Result<DateTime<Utc>> {
+        let value: String = row.get(column);
+        Ok(DateTime::parse_from_rfc3339(&value)?.with_timezone(&Utc))
+    };
+    let expires_at: Option<String> = row.get("expires_at");
+    Ok(TickerNote {
+        id: row.get("id"),
+        symbol: row.get("symbol"),
+        note: row.get("note"),
+        created_at: timestamp("created_at")?,
+        updated_at: timestamp("updated_at")?,
+        expires_at: expires_at.as_deref().map(DateTime::parse_from_rfc3339).transpose()?.map(|t| t.with_timezone(&Utc)),
+    })
+}
+
+fn stock_data_from_row(row: &SqliteRow) -> Result<StockData> {
+    let timestamp: i64 = row.get("timestamp");
+    let volume: i64 = row.get("volume");
+    Ok(StockData {
+        symbol: row.get("symbol"),
+        timestamp: DateTime::from_timestamp(timestamp, 0).ok_or_else(|| anyhow::anyhow!("invalid bar timestamp {}", timestamp))?,
+        open: row.get("open"),
+        high: row.get("high"),
+        low: row.get("low"),
+        close: row.get("close"),
+        volume: volume.max(0) as u64,
+        adjusted_close: None,
+    })
+}
+
+fn corporate_event_from_row(row: &SqliteRow) -> Result<CorporateEvent> {
+    let timestamp: i64 = row.get("timestamp");
+    let kind: String = row.get("kind");
+    let value: f64 = row.get("value");
+    let date = DateTime::from_timestamp(timestamp, 0).ok_or_else(|| anyhow::anyhow!("invalid event timestamp {}", timestamp))?;
+    match kind.as_str() {
+        "split" => Ok(CorporateEvent::Split { ratio: value, date }),
+        "dividend" => Ok(CorporateEvent::Dividend { amount: value, date }),
+        other => Err(anyhow::anyhow!("unknown corporate event {:?}", other)),
+    }
+}
+
+fn custom_indicator_from_row(row: &SqliteRow) -> Result<CustomIndicator> {
+    let timestamp = |column: &str| -> Result<DateTime<Utc>> {
+        let value: String = row.get(column);
+        Ok(DateTime::parse_from_rfc3339(&value)?.with_timezone(&Utc))
+    };
+    Ok(CustomIndicator {
+        name: row.get("name"),
+        expression: row.get("expression"),
+        created_at: timestamp("created_at")?,
+        updated_at: timestamp("updated_at")?,
+    })
+}
+
+fn watchlist_from_row(row: &SqliteRow) -> Result<WatchlistEntry> {
+    let added_at: String = row.get("added_at");
+    Ok(WatchlistEntry {
+        symbol: row.get("symbol"),
+        added_at: DateTime::parse_from_rfc3339(&added_at)?.with_timezone(&Utc),
+        note: row.get("note"),
+        deleted_at: deleted_at(row)?,
+    })
+}
+
+fn preset_from_row(row: &SqliteRow) -> Result<FilterPreset> {
+    let filter: String = row.get("filter");
+    let updated_at: String = row.get("updated_at");
+    let opportunity_rules: Option<String> = row.get("opportunity_rules");
+    Ok(FilterPreset {
+        name: row.get("name"),
+        filter: serde_json::from_str(&filter)?,
+        opportunity_rules: opportunity_rules.as_deref().map(serde_json::from_str).transpose()?,
+        updated_at: DateTime::parse_from_rfc3339(&updated_at)?.with_timezone(&Utc),
+        deleted_at: deleted_at(row)?,
+    })
+}
+
+fn deleted_at(row: &SqliteRow) -> Result<Option<DateTime<Utc>>> {
+    let deleted_at: Option<String> = row.get("deleted_at");
+    Ok(deleted_at.as_deref().map(DateTime::parse_from_rfc3339).transpose()?.map(|t| t.with_timezone(&Utc)))
+}
+
+/// The watchlist row for `symbol`, removed or not
+async fn watchlist_entry(conn: &mut SqliteConnection, symbol: &str) -> Result<Option<WatchlistEntry>> {
+    let row = sqlx::query("SELECT symbol, added_at, note, deleted_at FROM watchlist WHERE symbol = ?")
+        .bind(symbol)
+        .fetch_optional(conn)
+        .await?;
+    row.as_ref().map(watchlist_from_row).transpose()
+}
+
+/// The preset called `name`, deleted or not
+async fn preset(conn: &mut SqliteConnection, name: &str) -> Result<Option<FilterPreset>> {
+    let row = sqlx::query("SELECT name, filter, updated_at, opportunity_rules, deleted_at FROM filter_presets WHERE name = ?")
+        .bind(name)
+        .fetch_optional(conn)
+        .await?;
+    row.as_ref().map(preset_from_row).transpose()
+}
+
+/// The alert with `id`, deleted or not
+async fn alert(conn: &mut SqliteConnection, id: i64) -> Result<Option<Alert>> {
+    let row = sqlx::query(
+        "SELECT id, symbol, condition_type, condition_value, is_active, is_triggered, created_at, deleted_at \
+         FROM alerts WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await?;
+    row.as_ref().map(alert_from_row).transpose()
+}
+
+/// The custom indicator called `name`
+async fn custom_indicator(conn: &mut SqliteConnection, name: &str) -> Result<Option<CustomIndicator>> {
+    let row = sqlx::query("SELECT name, expression, created_at, updated_at FROM custom_indicators WHERE name = ?")
+        .bind(name)
+        .fetch_optional(&mut *conn)
+        .await?;
+    row.as_ref().map(custom_indicator_from_row).transpose()
+}
+
+/// The note with `id`, expired or not
+async fn ticker_note(conn: &mut SqliteConnection, id: i64) -> Result<Option<TickerNote>> {
+    let row = sqlx::query("SELECT id, symbol, note, created_at, updated_at, expires_at FROM ticker_notes WHERE id = ?")
+        .bind(id)
+        .fetch_optional(conn)
+        .await?;
+    row.as_ref().map(note_from_row).transpose()
+}
+
+/// Record a change in `audit_log`. Called inside the change's own
+/// transaction so the two commit or roll back together.
+async fn insert_audit<T: Serialize>(
+    conn: &mut SqliteConnection,
+    resource: AuditResource,
+    resource_id: &str,
+    action: AuditAction,
+    old_value: Option<&T>,
+    new_value: Option<&T>,
+    source_ip: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO audit_log (resource, resource_id, action, old_value, new_value, source_ip, created_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(resource.as_str())
+    .bind(resource_id)
+    .bind(action.as_str())
+    .bind(old_value.map(serde_json::to_string).transpose()?)
+    .bind(new_value.map(serde_json::to_string).transpose()?)
+    .bind(source_ip)
+    .bind(Utc::now().to_rfc3339())
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+fn indicator_history_point(row: &SqliteRow) -> Result<IndicatorHistoryPoint> {
+    let date: String = row.get("date");
+    Ok(IndicatorHistoryPoint {
+        date: date.parse()?,
+        rsi: row.get("rsi"),
+        sma_20: row.get("sma_20"),
+        sma_50: row.get("sma_50"),
+        macd: row.get("macd"),
+        macd_signal: row.get("macd_signal"),
+        macd_histogram: row.get("macd_histogram"),
+    })
+}
+
+/// Parse the JSON in `column`, reading a damaged value as the default and
+/// describing it in `problems`
+fn json_column<T: DeserializeOwned + Default>(row: &SqliteRow, column: &str, problems: &mut Vec<String>) -> T {
+    match row.get::<Option<String>, _>(column) {
+        Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            problems.push(format!("malformed {column} JSON: {e}"));
+            T::default()
+        }),
+        None => T::default(),
+    }
+}
+
+fn timestamp_column(row: &SqliteRow, column: &str) -> Result<Option<DateTime<Utc>>> {
+    row.get::<Option<String>, _>(column)
+        .map(|s| {
+            DateTime::parse_from_rfc3339(&s)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|e| anyhow::anyhow!("malformed {column} timestamp {s:?}: {e}"))
+        })
+        .transpose()
+}
+
+async fn insert_result(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, result: &StockAnalysisResult, session: &str) -> Result<()> {
+    let id = Uuid::new_v4().to_string();
+    let signals_json = serde_json::to_string(&result.signals)?;
+    // NULL rather than "[]" so the crossover filter is a plain IS NOT NULL
+    let crossovers_json = match result.crossovers.is_empty() {
+        true => None,
+        false => Some(serde_json::to_string(&result.crossovers)?),
+    };
+    let data_quality_json = result.data_quality.as_ref().map(serde_json::to_string).transpose()?;
+    // NULL for a believable result, which keeps it out of idx_anomalous_results
+    let anomalies_json = match result.anomalies.is_empty() {
+        true => None,
+        false => Some(serde_json::to_string(&result.anomalies)?),
+    };
+    let custom_json = match result.custom.is_empty() {
+        true => None,
+        false => Some(serde_json::to_string(&result.custom)?),
+    };
+    
+    let query = r#"
+    INSERT OR REPLACE INTO analysis_results (
+        id, ticker, name, current_price, rsi, sma_20, sma_50, macd, macd_signal, 
+        macd_histogram, volume, pct_change, market_cap, is_opportunity, signals, 
+        timestamp, analysis_session, summary, data_source, data_fetched_at,
+        analyst_target, upside_to_target_pct, sector, industry, obv, vwap, volume_vs_avg, crossovers,
+        high_52w, low_52w, pct_from_high, pct_from_low, range_window_complete,
+        rs_1w, rs_1m, rs_3m, rs_rank, exchange, market_cap_value, data_quality, repaired_bar_ratio,
+        adx, plus_di, minus_di, last_split, timeframe, anomalies, rsi_oversold_used, rsi_overbought_used,
+        rsi_sector_zscore, rsi_industry_zscore, opportunity_state, opportunity_streak, opportunity_since, custom_indicators,
+        bars_used
+    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    "#;
+    
+    sqlx::query(query)
+        .bind(id)
+        .bind(&result.ticker)
+        .bind(&result.name)
+        .bind(result.current_price)
+        .bind(result.rsi)
+        .bind(result.sma_20)
+        .bind(result.sma_50)
+        .bind(result.macd)
+        .bind(result.macd_signal)
+        .bind(result.macd_histogram)
+        .bind(result.volume.map(|v| v as i64))
+        .bind(result.pct_change)
+        .bind(&result.market_cap)
+        .bind(result.is_opportunity)
+        .bind(signals_json)
+        .bind(result.timestamp.to_rfc3339())
+        .bind(session)
+        .bind(&result.summary)
+        .bind(&result.data_source)
+        .bind(result.data_fetched_at.map(|t| t.to_rfc3339()))
+        .bind(result.analyst_target)
+        .bind(result.upside_to_target_pct)
+        .bind(&result.sector)
+        .bind(&result.industry)
+        .bind(result.obv)
+        .bind(result.vwap)
+        .bind(result.volume_vs_avg)
+        .bind(crossovers_json)
+        .bind(result.high_52w)
+        .bind(result.low_52w)
+        .bind(result.pct_from_high)
+        .bind(result.pct_from_low)
+        .bind(result.range_window_complete)
+        .bind(result.rs_1w)
+        .bind(result.rs_1m)
+        .bind(result.rs_3m)
+        .bind(result.rs_rank)
+        .bind(&result.exchange)
+        .bind(parse_field(result.market_cap.as_deref(), parse_market_cap))
+        .bind(data_quality_json)
+        .bind(result.data_quality.map(|quality| quality.repaired_ratio()))
+        .bind(result.adx)
+        .bind(result.plus_di)
+        .bind(result.minus_di)
+        .bind(result.last_split.map(|t| t.to_rfc3339()))
+        .bind(result.timeframe.as_str())
+        .bind(anomalies_json)
+        .bind(result.rsi_oversold_used)
+        .bind(result.rsi_overbought_used)
+        .bind(result.rsi_sector_zscore)
+        .bind(result.rsi_industry_zscore)
+        .bind(result.opportunity_state.as_str())
+        .bind(result.opportunity_streak as i64)
+        .bind(result.opportunity_since.map(|t| t.to_rfc3339()))
+        .bind(custom_json)
+        .bind(result.bars_used.map(|bars| bars as i64))
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+async fn insert_bar(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, bar: &StockData) -> Result<()> {
+    let query = r#"
+    INSERT INTO market_data (symbol, timestamp, open, high, low, close, volume)
+    VALUES (?, ?, ?, ?, ?, ?, ?)
+    ON CONFLICT(symbol, timestamp) DO UPDATE SET
+        open = excluded.open,
+        high = excluded.high,
+        low = excluded.low,
+        close = excluded.close,
+        volume = excluded.volume
+    "#;
+
+    sqlx::query(query)
+        .bind(&bar.symbol)
+        .bind(bar.timestamp.timestamp())
+        .bind(bar.open)
+        .bind(bar.high)
+        .bind(bar.low)
+        .bind(bar.close)
+        .bind(bar.volume as i64)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// `FROM` and `WHERE` clauses selecting the latest daily result per ticker that
+/// passes any of `filters`; with no filters every ticker matches
+fn push_latest_matching(query: &mut QueryBuilder<'_, Sqlite>, filters: &[StockFilter]) {
+    query.push(
+        r#"
+        FROM analysis_results r
+        JOIN (
+            SELECT ticker, MAX(timestamp) AS latest_timestamp
+            FROM analysis_results
+            WHERE timeframe = 'daily'
+            GROUP BY ticker
+        ) latest ON r.ticker = latest.ticker AND r.timestamp = latest.latest_timestamp AND r.timeframe = 'daily'
+        "#,
+    );
+    if filters.is_empty() {
+        return;
+    }
+
+    query.push(" WHERE ");
+    for (i, filter) in filters.iter().enumerate() {
+        if i > 0 {
+            query.push(" OR ");
+        }
+        // NULL fails every comparison, just as a missing value fails the filter
+        query.push("(1 = 1");
+        push_condition(query, " AND r.market_cap_value >= ", filter.min_market_cap);
+        push_condition(query, " AND r.market_cap_value <= ", filter.max_market_cap);
+        push_condition(query, " AND r.rsi >= ", filter.min_rsi);
+        push_condition(query, " AND r.rsi <= ", filter.max_rsi);
+        push_condition(query, " AND r.current_price >= ", filter.min_price);
+        push_condition(query, " AND r.current_price <= ", filter.max_price);
+        push_condition(query, " AND r.volume >= ", filter.min_volume.map(|v| v as i64));
+        push_condition(query, " AND r.volume <= ", filter.max_volume.map(|v| v as i64));
+        push_condition(query, " AND r.pct_change >= ", filter.min_pct_change);
+        push_condition(query, " AND r.pct_change <= ", filter.max_pct_change);
+        push_condition(query, " AND r.volume_vs_avg >= ", filter.min_volume_vs_avg);
+        push_condition(query, " AND r.pct_from_low <= ", filter.max_pct_from_low);
+        push_condition(query, " AND r.pct_from_high >= ", filter.min_pct_from_high);
+        push_condition(query, " AND r.rs_rank >= ", filter.min_rs_rank);
+        push_condition(query, " AND r.rsi_sector_zscore <= ", filter.max_rsi_sector_zscore);
+        push_condition(query, " AND r.adx >= ", filter.min_adx);
+        if let Some(days) = filter.exclude_split_within_days {
+            query.push(" AND (r.last_split IS NULL OR julianday(r.timestamp) - julianday(r.last_split) > ");
+            query.push_bind(f64::from(days));
+            query.push(")");
+        }
+        push_contains_any(query, "r.sector", filter.sectors.as_deref());
+        push_contains_any(query, "r.industry", filter.industries.as_deref());
+        push_contains_any(query, "r.exchange", filter.exchanges.as_deref());
+        if filter.recent_crossovers_only {
+            query.push(" AND r.crossovers IS NOT NULL");
+        }
+        if filter.has_note {
+            query.push(" AND EXISTS (SELECT 1 FROM ticker_notes n WHERE n.symbol = r.ticker AND (n.expires_at IS NULL OR n.expires_at > ");
+            query.push_bind(Utc::now().to_rfc3339());
+            query.push("))");
+        }
+        if let Some(ref states) = filter.opportunity_states {
+            push_one_of(query, "r.opportunity_state", states.iter().map(|state| state.as_str()));
+        }
+        if let Some(hours) = filter.opportunity_within_hours {
+            query.push(" AND julianday(r.timestamp) - julianday(r.opportunity_since) <= ");
+            query.push_bind(f64::from(hours) / 24.0);
+        }
+        for (name, &(min, max)) in &filter.custom_indicator_ranges {
+            push_custom_indicator_range(query, name, min, max);
+        }
+        // Results stored without a bar count fail, as in `result_passes`
+        push_condition(query, " AND r.bars_used >= ", filter.min_history_bars.map(|bars| bars as i64));
+        if let Some(max_ratio) = filter.max_repaired_bar_ratio {
+            // Results stored without data quality pass, as in `filter_results`
+            query.push(" AND (r.repaired_bar_ratio IS NULL OR r.repaired_bar_ratio <= ");
+            query.push_bind(max_ratio);
+            query.push(")");
+        }
+        if let Some(min_upside) = filter.min_upside_to_target_pct {
+            // Stale targets are not evidence of upside
+            push_condition(query, " AND r.upside_to_target_pct >= ", Some(min_upside));
+            push_condition(query, " AND r.upside_to_target_pct <= ", Some(STALE_TARGET_UPSIDE_PCT));
+        }
+        query.push(")");
+    }
+}
+
+fn push_condition<'args, T>(query: &mut QueryBuilder<'args, Sqlite>, condition: &str, value: Option<T>)
+where
+    T: 'args + Encode<'args, Sqlite> + Type<Sqlite> + Send,
+{
+    if let Some(value) = value {
+        query.push(condition).push_bind(value);
+    }
+}
+
+/// `column` contains any of `allowed`, ignoring case, as the listing
+/// filters match sectors; an empty list matches nothing
+fn push_contains_any(query: &mut QueryBuilder<'_, Sqlite>, column: &str, allowed: Option<&[String]>) {
+    let Some(allowed) = allowed else { return };
+    query.push(" AND (0 = 1");
+    for value in allowed {
+        query
+            .push(format!(" OR instr(LOWER({}), ", column))
+            .push_bind(value.to_lowercase())
+            .push(") > 0");
+    }
+    query.push(")");
+}
+
+/// The result's custom indicator `name` lies within `min..=max`; results
+/// without it fail. Names are checked by `StockFilter::validate`, so they
+/// need no quoting in the JSON path.
+fn push_custom_indicator_range(query: &mut QueryBuilder<'_, Sqlite>, name: &str, min: Option<f64>, max: Option<f64>) {
+    let path = format!("$.{}", name);
+    query.push(" AND json_extract(r.custom_indicators, ").push_bind(path.clone()).push(") IS NOT NULL");
+    for (comparison, bound) in [(") >= ", min), (") <= ", max)] {
+        if let Some(bound) = bound {
+            query.push(" AND json_extract(r.custom_indicators, ").push_bind(path.clone()).push(comparison).push_bind(bound);
+        }
+    }
+}
+
+/// `column` equals one of `allowed`; an empty list matches nothing
+fn push_one_of<'a>(query: &mut QueryBuilder<'_, Sqlite>, column: &str, allowed: impl IntoIterator<Item = &'a str>) {
+    query.push(format!(" AND {} IN (NULL", column));
+    for value in allowed {
+        query.push(", ").push_bind(value.to_string());
+    }
+    query.push(")");
+}
+
+/// Column in `analysis_results` a sort field reads
+fn sort_column(field: SortField) -> &'static str {
+    match field {
+        SortField::Rsi => "r.rsi",
+        SortField::PctChange => "r.pct_change",
+        SortField::Ticker => "r.ticker",
+        SortField::Timestamp => "r.timestamp",
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnalysisStats {
+    pub total_results: u64,
+    pub unique_tickers: u64,
+    pub total_sessions: u64,
+    pub opportunities: u64,
+    pub avg_rsi: Option<f64>,
+    pub oldest_result: Option<DateTime<Utc>>,
     pub newest_result: Option<DateTime<Utc>>,
+    /// Database file size in bytes
+    pub size_bytes: u64,
+    pub last_maintenance: Option<MaintenanceReport>,
+    /// Distinct damaged result rows met by reads since the server
+    /// connected. Rows with malformed JSON are served with those fields
+    /// empty; rows with malformed timestamps are left out.
+    #[serde(default)]
+    pub corrupt_rows: u64,
+}
+
+/// Outcome of `Database::integrity_check`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// Whether SQLite found no problems and every result row decoded cleanly
+    pub ok: bool,
+    /// Messages from `PRAGMA integrity_check`, just `["ok"]` for a sound file
+    pub sqlite: Vec<String>,
+    pub rows_checked: u64,
+    pub corrupt_row_count: u64,
+    /// The first `MAX_REPORTED_CORRUPT_ROWS` damaged rows
+    pub corrupt_rows: Vec<CorruptRow>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CorruptRow {
+    pub id: String,
+    pub ticker: String,
+    pub problems: Vec<String>,
+    /// Whether reads leave the row out rather than serve it patched up
+    pub skipped: bool,
 }
\ No newline at end of file
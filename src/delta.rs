@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+
+use crate::web_api::StockAnalysisResult;
+
+/// Relative tolerance below which two values count as unchanged, so
+/// recomputing the same history doesn't report floating-point noise
+pub const CHANGE_EPSILON: f64 = 1e-6;
+
+/// How a ticker's fresh result differs from its previous stored one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultDelta {
+    pub ticker: String,
+    /// Names of the numeric fields that moved, in declaration order
+    pub changed_fields: Vec<String>,
+    pub old_rsi: Option<f64>,
+    pub new_rsi: Option<f64>,
+    pub became_opportunity: bool,
+    pub lost_opportunity: bool,
+}
+
+impl ResultDelta {
+    /// `None` when every numeric field is within `CHANGE_EPSILON` of its
+    /// previous value and the opportunity flag is unchanged
+    pub fn between(previous: &StockAnalysisResult, current: &StockAnalysisResult) -> Option<Self> {
+        let changed_fields: Vec<String> = numeric_fields(previous)
+            .into_iter()
+            .zip(numeric_fields(current))
+            .filter(|((_, old), (_, new))| !same_value(*old, *new))
+            .map(|((name, _), _)| name.to_string())
+            .collect();
+        let became_opportunity = current.is_opportunity && !previous.is_opportunity;
+        let lost_opportunity = previous.is_opportunity && !current.is_opportunity;
+        if changed_fields.is_empty() && !became_opportunity && !lost_opportunity {
+            return None;
+        }
+        Some(Self {
+            ticker: current.ticker.clone(),
+            changed_fields,
+            old_rsi: previous.rsi,
+            new_rsi: current.rsi,
+            became_opportunity,
+            lost_opportunity,
+        })
+    }
+}
+
+fn numeric_fields(result: &StockAnalysisResult) -> [(&'static str, Option<f64>); 23] {
+    [
+        ("current_price", result.current_price),
+        ("rsi", result.rsi),
+        ("sma_20", result.sma_20),
+        ("sma_50", result.sma_50),
+        ("macd", result.macd),
+        ("macd_signal", result.macd_signal),
+        ("macd_histogram", result.macd_histogram),
+        ("volume", result.volume.map(|volume| volume as f64)),
+        ("pct_change", result.pct_change),
+        ("analyst_target", result.analyst_target),
+        ("upside_to_target_pct", result.upside_to_target_pct),
+        ("obv", result.obv),
+        ("vwap", result.vwap),
+        ("volume_vs_avg", result.volume_vs_avg),
+        ("high_52w", result.high_52w),
+        ("low_52w", result.low_52w),
+        ("pct_from_high", result.pct_from_high),
+        ("pct_from_low", result.pct_from_low),
+        ("rs_1w", result.rs_1w),
+        ("rs_1m", result.rs_1m),
+        ("rs_3m", result.rs_3m),
+        ("rs_rank", result.rs_rank),
+        ("adx", result.adx),
+    ]
+}
+
+/// Equal within `CHANGE_EPSILON` of the larger magnitude (or of 1.0 near
+/// zero); a value appearing or disappearing is a change
+fn same_value(old: Option<f64>, new: Option<f64>) -> bool {
+    match (old, new) {
+        (Some(old), Some(new)) => (old - new).abs() <= CHANGE_EPSILON * old.abs().max(new.abs()).max(1.0),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn result(rsi: f64, price: f64, is_opportunity: bool) -> StockAnalysisResult {
+        serde_json::from_value(serde_json::json!({
+            "ticker": "AAPL",
+            "name": "Apple Inc.",
+            "current_price": price,
+            "rsi": rsi,
+            "sma_20": 150.0,
+            "sma_50": null,
+            "macd": null,
+            "macd_signal": null,
+            "macd_histogram": null,
+            "volume": 1_000_000,
+            "pct_change": 1.5,
+            "market_cap": null,
+            "is_opportunity": is_opportunity,
+            "signals": [],
+            "timestamp": Utc::now(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_unchanged_result_is_suppressed() {
+        let previous = result(45.0, 150.0, false);
+        // Noise from recomputing the same history is not a change
+        let current = result(45.0 + 1e-9, 150.0 * (1.0 + 1e-9), false);
+        assert_eq!(ResultDelta::between(&previous, &current), None);
+    }
+
+    #[test]
+    fn test_changed_fields_and_opportunity_flips() {
+        let previous = result(35.0, 150.0, false);
+        let current = result(28.0, 150.0, true);
+        let delta = ResultDelta::between(&previous, &current).unwrap();
+        assert_eq!(delta.changed_fields, ["rsi"]);
+        assert_eq!((delta.old_rsi, delta.new_rsi), (Some(35.0), Some(28.0)));
+        assert!(delta.became_opportunity && !delta.lost_opportunity);
+
+        let back = ResultDelta::between(&current, &previous).unwrap();
+        assert!(back.lost_opportunity && !back.became_opportunity);
+
+        // A value disappearing counts, even with the same RSI
+        let mut no_price = previous.clone();
+        no_price.current_price = None;
+        assert_eq!(ResultDelta::between(&previous, &no_price).unwrap().changed_fields, ["current_price"]);
+    }
+}
@@ -0,0 +1,239 @@
+use std::sync::Arc;
+
+use crate::cache::CacheManager;
+use crate::providers::analyst::is_stale_upside;
+use crate::providers::{AnalystTarget, AnalystTargetSource, ProviderError};
+use crate::web_api::StockAnalysisResult;
+
+/// Adds analyst targets to analysis results.
+///
+/// Lookups are cached for 24 hours (including "no coverage" answers) and at
+/// most `budget` uncached lookups are made per enricher, so a long cycle
+/// cannot hammer the upstream. Once the budget is spent, only cached targets
+/// are applied.
+pub struct AnalystEnricher {
+    source: Arc<dyn AnalystTargetSource>,
+    cache: CacheManager,
+    remaining: usize,
+}
+
+impl AnalystEnricher {
+    pub fn new(source: Arc<dyn AnalystTargetSource>, cache: CacheManager, budget: usize) -> Self {
+        Self {
+            source,
+            cache,
+            remaining: budget,
+        }
+    }
+
+    /// Uncached lookups still allowed
+    pub fn remaining_budget(&self) -> usize {
+        self.remaining
+    }
+
+    pub async fn enrich(&mut self, result: &mut StockAnalysisResult) {
+        let target = match self.cache.get_analyst_target(&result.ticker).await {
+            Some(cached) => cached,
+            None => {
+                if self.remaining == 0 {
+                    return;
+                }
+                self.remaining -= 1;
+
+                let target = match self.source.analyst_target(&result.ticker).await {
+                    Ok(target) => target,
+                    Err(ProviderError::NotFound { .. }) => None,
+                    Err(e) => {
+                        // Transient failures are not cached so the next cycle retries
+                        tracing::debug!("No analyst target for {}: {}", result.ticker, e);
+                        return;
+                    }
+                };
+                self.cache.cache_analyst_target(result.ticker.clone(), target).await;
+                target
+            }
+        };
+
+        apply_analyst_target(result, target);
+    }
+}
+
+/// Set the target fields on a result, flagging targets that imply an
+/// implausibly large upside
+pub fn apply_analyst_target(result: &mut StockAnalysisResult, target: Option<AnalystTarget>) {
+    result.analyst_target = target.map(|t| t.target_mean_price);
+    result.upside_to_target_pct = target.zip(result.current_price).and_then(|(t, price)| t.upside_pct(price));
+
+    if let Some(upside) = result.upside_to_target_pct.filter(|upside| is_stale_upside(*upside)) {
+        result
+            .signals
+            .push(format!("Analyst target looks stale ({:.0}% implied upside)", upside));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use crate::opportunity_state::OpportunityState;
+    use crate::resample::Timeframe;
+    use chrono::Utc;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockTargets {
+        targets: HashMap<&'static str, Result<Option<AnalystTarget>, ProviderError>>,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl AnalystTargetSource for MockTargets {
+        async fn analyst_target(&self, symbol: &str) -> Result<Option<AnalystTarget>, ProviderError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.targets.get(symbol).cloned().unwrap_or(Ok(None))
+        }
+    }
+
+    fn target(price: f64) -> Result<Option<AnalystTarget>, ProviderError> {
+        Ok(Some(AnalystTarget {
+            target_mean_price: price,
+            analyst_count: Some(10),
+        }))
+    }
+
+    fn result(ticker: &str, price: f64) -> StockAnalysisResult {
+        StockAnalysisResult {
+            ticker: ticker.to_string(),
+            name: format!("{} Inc", ticker),
+            current_price: Some(price),
+            rsi: None,
+            sma_20: None,
+            sma_50: None,
+            macd: None,
+            macd_signal: None,
+            macd_histogram: None,
+            volume: None,
+            pct_change: None,
+            market_cap: None,
+            sector: None,
+            industry: None,
+            exchange: None,
+            is_opportunity: false,
+            signals: Vec::new(),
+            timestamp: Utc::now(),
+            data_source: None,
+            data_fetched_at: None,
+            summary: None,
+            analyst_target: None,
+            upside_to_target_pct: None,
+            obv: None,
+            vwap: None,
+            volume_vs_avg: None,
+            high_52w: None,
+            low_52w: None,
+            pct_from_high: None,
+            pct_from_low: None,
+            range_window_complete: false,
+            rs_1w: None,
+            rs_1m: None,
+            rs_3m: None,
+            rs_rank: None,
+            moving_averages: HashMap::new(),
+            crossovers: Vec::new(),
+            data_quality: None,
+            adx: None,
+            plus_di: None,
+            minus_di: None,
+            last_split: None,
+            timeframe: Timeframe::Daily,
+            anomalies: Vec::new(),
+            rsi_oversold_used: None,
+            rsi_overbought_used: None,
+            rsi_sector_zscore: None,
+            rsi_industry_zscore: None,
+            note: None,
+            opportunity_state: OpportunityState::None,
+            opportunity_streak: 0,
+            opportunity_since: None,
+            custom: HashMap::new(),
+            bars_used: None,
+        }
+    }
+
+    fn mock() -> Arc<MockTargets> {
+        Arc::new(MockTargets {
+            targets: HashMap::from([
+                ("AAPL", target(220.0)),
+                ("PENNY", target(10.0)),
+                ("GONE", Err(ProviderError::NotFound { provider: "mock".into(), symbol: "GONE".into() })),
+                ("FLAKY", Err(ProviderError::Upstream { provider: "mock".into(), message: "503".into() })),
+            ]),
+            calls: AtomicUsize::new(0),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_enrich_sets_target_and_caches() {
+        let source = mock();
+        let cache = CacheManager::new();
+        let mut enricher = AnalystEnricher::new(source.clone(), cache.clone(), 10);
+
+        let mut aapl = result("AAPL", 200.0);
+        enricher.enrich(&mut aapl).await;
+        assert_eq!(aapl.analyst_target, Some(220.0));
+        assert!((aapl.upside_to_target_pct.unwrap() - 10.0).abs() < 1e-9);
+        assert!(aapl.signals.is_empty());
+
+        // A second enricher (next cycle) is served from the 24h cache
+        let mut next_cycle = AnalystEnricher::new(source.clone(), cache.clone(), 10);
+        let mut again = result("AAPL", 176.0);
+        next_cycle.enrich(&mut again).await;
+        assert!((again.upside_to_target_pct.unwrap() - 25.0).abs() < 1e-9);
+        assert_eq!(source.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(next_cycle.remaining_budget(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_enrich_tolerates_missing_and_stale_targets() {
+        let source = mock();
+        let cache = CacheManager::new();
+        let mut enricher = AnalystEnricher::new(source.clone(), cache.clone(), 10);
+
+        let mut small_cap = result("TINY", 3.0);
+        enricher.enrich(&mut small_cap).await;
+        assert_eq!(small_cap.analyst_target, None);
+        assert_eq!(small_cap.upside_to_target_pct, None);
+
+        let mut gone = result("GONE", 3.0);
+        enricher.enrich(&mut gone).await;
+        assert_eq!(gone.analyst_target, None);
+
+        let mut penny = result("PENNY", 2.0);
+        enricher.enrich(&mut penny).await;
+        assert_eq!(penny.upside_to_target_pct, Some(400.0));
+        assert_eq!(penny.signals, vec!["Analyst target looks stale (400% implied upside)"]);
+
+        // Upstream failures are retried later rather than cached as "no coverage"
+        let mut flaky = result("FLAKY", 5.0);
+        enricher.enrich(&mut flaky).await;
+        assert_eq!(flaky.analyst_target, None);
+        assert_eq!(cache.get_analyst_target("FLAKY").await, None);
+        assert_eq!(cache.get_analyst_target("TINY").await, Some(None));
+    }
+
+    #[tokio::test]
+    async fn test_enrich_respects_budget() {
+        let source = mock();
+        let mut enricher = AnalystEnricher::new(source.clone(), CacheManager::new(), 1);
+
+        let mut first = result("AAPL", 200.0);
+        let mut second = result("PENNY", 2.0);
+        enricher.enrich(&mut first).await;
+        enricher.enrich(&mut second).await;
+
+        assert_eq!(first.analyst_target, Some(220.0));
+        assert_eq!(second.analyst_target, None);
+        assert_eq!(source.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(enricher.remaining_budget(), 0);
+    }
+}
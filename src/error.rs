@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use crate::providers::ProviderError;
+
+/// Failure from the analyzer's fetch methods, classified so callers can tell
+/// throttling, unknown symbols and outages apart without reading the message
+#[derive(Debug, thiserror::Error)]
+pub enum AnalyzerError {
+    #[error("rate limited{}", retry_after_suffix(.retry_after))]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("symbol not found: {0}")]
+    SymbolNotFound(String),
+    #[error("no data: {0}")]
+    NoData(String),
+    #[error("{0}")]
+    Upstream(String),
+    /// The upstream didn't connect or respond within the configured timeout
+    #[error("{0}")]
+    Timeout(String),
+    #[error("{0}")]
+    Parse(String),
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+    #[error("database error: {0}")]
+    Database(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T, E = AnalyzerError> = std::result::Result<T, E>;
+
+fn retry_after_suffix(retry_after: &Option<Duration>) -> String {
+    retry_after.map_or_else(String::new, |delay| format!(", retry after {:?}", delay))
+}
+
+impl AnalyzerError {
+    /// Classify a free-form upstream failure by its text, for sources that
+    /// only report a message (Yahoo client errors, combined fallback errors)
+    pub fn from_message(message: impl Into<String>) -> Self {
+        let message = message.into();
+        let lower = message.to_lowercase();
+        if lower.contains("429") || lower.contains("too many requests") || lower.contains("rate limit") {
+            AnalyzerError::RateLimited { retry_after: None }
+        } else if is_missing_data(&lower) {
+            AnalyzerError::NoData(message)
+        } else {
+            AnalyzerError::Upstream(message)
+        }
+    }
+
+    /// Wrap a storage failure from the database layer
+    pub fn database(error: impl std::fmt::Display) -> Self {
+        AnalyzerError::Database(format!("{:#}", error))
+    }
+
+    /// Short snake_case name of the variant, for logs and API responses
+    pub fn category(&self) -> &'static str {
+        match self {
+            AnalyzerError::RateLimited { .. } => "rate_limited",
+            AnalyzerError::SymbolNotFound(_) => "symbol_not_found",
+            AnalyzerError::NoData(_) => "no_data",
+            AnalyzerError::Upstream(_) => "upstream",
+            AnalyzerError::Timeout(_) => "timeout",
+            AnalyzerError::Parse(_) => "parse",
+            AnalyzerError::InvalidRequest(_) => "invalid_request",
+            AnalyzerError::Database(_) => "database",
+            AnalyzerError::Io(_) => "io",
+        }
+    }
+
+    /// Whether the symbol has no data at all (unknown or delisted), as
+    /// opposed to a fetch that failed on the way
+    pub fn is_missing_symbol(&self) -> bool {
+        matches!(self, AnalyzerError::SymbolNotFound(_) | AnalyzerError::NoData(_))
+    }
+}
+
+/// I/O errors are not `Clone`; the copy keeps the kind and message, which is
+/// all a caller sharing another's failed fetch gets to see
+impl Clone for AnalyzerError {
+    fn clone(&self) -> Self {
+        match self {
+            AnalyzerError::RateLimited { retry_after } => AnalyzerError::RateLimited { retry_after: *retry_after },
+            AnalyzerError::SymbolNotFound(symbol) => AnalyzerError::SymbolNotFound(symbol.clone()),
+            AnalyzerError::NoData(message) => AnalyzerError::NoData(message.clone()),
+            AnalyzerError::Upstream(message) => AnalyzerError::Upstream(message.clone()),
+            AnalyzerError::Timeout(message) => AnalyzerError::Timeout(message.clone()),
+            AnalyzerError::Parse(message) => AnalyzerError::Parse(message.clone()),
+            AnalyzerError::InvalidRequest(message) => AnalyzerError::InvalidRequest(message.clone()),
+            AnalyzerError::Database(message) => AnalyzerError::Database(message.clone()),
+            AnalyzerError::Io(error) => AnalyzerError::Io(std::io::Error::new(error.kind(), error.to_string())),
+        }
+    }
+}
+
+fn is_missing_data(message: &str) -> bool {
+    ["no data", "not found", "delisted", "404"].iter().any(|needle| message.contains(needle))
+}
+
+impl From<ProviderError> for AnalyzerError {
+    fn from(error: ProviderError) -> Self {
+        match error {
+            ProviderError::NotFound { symbol, .. } => AnalyzerError::SymbolNotFound(symbol),
+            ProviderError::RateLimited { .. } => AnalyzerError::RateLimited { retry_after: None },
+            ProviderError::Parse { .. } => AnalyzerError::Parse(error.to_string()),
+            ProviderError::Timeout { .. } => AnalyzerError::Timeout(error.to_string()),
+            ProviderError::Upstream { .. } => AnalyzerError::from_message(error.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for AnalyzerError {
+    fn from(error: reqwest::Error) -> Self {
+        match error.status() {
+            Some(reqwest::StatusCode::TOO_MANY_REQUESTS) => AnalyzerError::RateLimited { retry_after: None },
+            _ if error.is_timeout() => AnalyzerError::Timeout(error.to_string()),
+            _ if error.is_decode() => AnalyzerError::Parse(error.to_string()),
+            _ => AnalyzerError::Upstream(error.to_string()),
+        }
+    }
+}
+
+/// The indicator constructors only fail on invalid periods
+impl From<ta::errors::TaError> for AnalyzerError {
+    fn from(error: ta::errors::TaError) -> Self {
+        AnalyzerError::InvalidRequest(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_errors_map_to_variants() {
+        let not_found = ProviderError::NotFound { provider: "yahoo".into(), symbol: "GONE".into() };
+        assert!(matches!(AnalyzerError::from(not_found), AnalyzerError::SymbolNotFound(s) if s == "GONE"));
+
+        let throttled = ProviderError::RateLimited { provider: "yahoo".into() };
+        assert!(matches!(AnalyzerError::from(throttled), AnalyzerError::RateLimited { retry_after: None }));
+
+        let upstream = |message: &str| AnalyzerError::from(ProviderError::Upstream { provider: "yahoo".into(), message: message.into() });
+        assert!(matches!(upstream("No data found, symbol may be delisted"), AnalyzerError::NoData(_)));
+        assert!(matches!(upstream("HTTP 429 Too Many Requests"), AnalyzerError::RateLimited { .. }));
+        assert!(matches!(upstream("error sending request"), AnalyzerError::Upstream(_)));
+
+        let parse = ProviderError::Parse { provider: "stooq".into(), message: "bad csv".into() };
+        assert_eq!(AnalyzerError::from(parse).to_string(), "stooq returned an unreadable response: bad csv");
+    }
+
+    #[test]
+    fn test_rate_limited_message_includes_retry_after() {
+        assert_eq!(AnalyzerError::RateLimited { retry_after: None }.to_string(), "rate limited");
+        let error = AnalyzerError::RateLimited { retry_after: Some(Duration::from_secs(10)) };
+        assert_eq!(error.to_string(), "rate limited, retry after 10s");
+        assert!(!error.is_missing_symbol());
+        assert!(AnalyzerError::NoData("GONE".into()).is_missing_symbol());
+    }
+}
@@ -0,0 +1,250 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::{StockData, TechnicalIndicators};
+
+/// Default number of most recent bars whose crossovers are surfaced in
+/// results (`AppConfig::crossover_lookback_bars`); five daily bars is a
+/// trading week
+pub const DEFAULT_CROSSOVER_LOOKBACK: usize = 5;
+
+/// Which pair of lines crossed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CrossoverKind {
+    /// SMA20 against SMA50: golden cross upwards, death cross downwards
+    #[serde(rename = "sma_20_50")]
+    Sma20Sma50,
+    /// MACD line against its signal line
+    #[serde(rename = "macd_signal")]
+    MacdSignal,
+    /// RSI against the oversold boundary
+    #[serde(rename = "rsi_30")]
+    Rsi30,
+    /// RSI against the overbought boundary
+    #[serde(rename = "rsi_70")]
+    Rsi70,
+    /// Closing price against SMA50
+    #[serde(rename = "price_sma_50")]
+    PriceSma50,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossDirection {
+    /// The first line moved from below the second to above it
+    Up,
+    Down,
+}
+
+/// One line crossing another, dated by the bar the cross was confirmed on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrossoverEvent {
+    pub kind: CrossoverKind,
+    pub direction: CrossDirection,
+    pub timestamp: DateTime<Utc>,
+    /// The crossing line at that bar (SMA20, MACD, RSI or the close)
+    pub value: f64,
+    /// The line it crossed (SMA50, the signal line, 30, 70 or SMA50)
+    pub reference: f64,
+}
+
+impl CrossoverEvent {
+    pub fn label(&self) -> &'static str {
+        use CrossDirection::*;
+        use CrossoverKind::*;
+
+        match (self.kind, self.direction) {
+            (Sma20Sma50, Up) => "Golden Cross - SMA20 Crossed Above SMA50",
+            (Sma20Sma50, Down) => "Death Cross - SMA20 Crossed Below SMA50",
+            (MacdSignal, Up) => "MACD Crossed Above Signal - Bullish",
+            (MacdSignal, Down) => "MACD Crossed Below Signal - Bearish",
+            (Rsi30, Up) => "RSI Crossed Above 30 - Leaving Oversold",
+            (Rsi30, Down) => "RSI Crossed Below 30 - Oversold",
+            (Rsi70, Up) => "RSI Crossed Above 70 - Overbought",
+            (Rsi70, Down) => "RSI Crossed Below 70 - Leaving Overbought",
+            (PriceSma50, Up) => "Price Crossed Above SMA50 - Bullish",
+            (PriceSma50, Down) => "Price Crossed Below SMA50 - Bearish",
+        }
+    }
+
+    /// The event as a result signal, e.g. "Golden Cross - SMA20 Crossed
+    /// Above SMA50 (2024-03-04)"
+    pub fn signal(&self) -> String {
+        format!("{} ({})", self.label(), self.timestamp.format("%Y-%m-%d"))
+    }
+}
+
+/// The (line, reference) pair a crossover kind compares at one bar
+type LinePair = fn(&StockData, &TechnicalIndicators) -> Option<(f64, f64)>;
+
+/// Every crossover over the whole series, oldest first.
+///
+/// A cross is reported on the first bar that closes strictly on the other
+/// side of the reference line. Bars where the two lines are exactly equal
+/// don't count as either side, so touching the line and turning back is no
+/// event and touching it on the way through is a single one.
+pub fn detect_crossovers(stock_data: &[StockData], indicators: &[TechnicalIndicators]) -> Vec<CrossoverEvent> {
+    let bars: Vec<(&StockData, &TechnicalIndicators)> = stock_data.iter().zip(indicators).collect();
+    let pairs: [(CrossoverKind, LinePair); 5] = [
+        (CrossoverKind::Sma20Sma50, |_, i| i.sma_20.zip(i.sma_50)),
+        (CrossoverKind::MacdSignal, |_, i| i.macd.map(|(macd, signal, _)| (macd, signal))),
+        (CrossoverKind::Rsi30, |_, i| i.rsi.map(|rsi| (rsi, 30.0))),
+        (CrossoverKind::Rsi70, |_, i| i.rsi.map(|rsi| (rsi, 70.0))),
+        (CrossoverKind::PriceSma50, |d, i| i.sma_50.map(|sma| (d.close, sma))),
+    ];
+
+    let mut events = Vec::new();
+    for (kind, lines) in pairs {
+        let points = bars.iter().filter_map(|&(data, indicator)| {
+            lines(data, indicator).map(|(value, reference)| ((data.timestamp, value, reference), value, reference))
+        });
+        events.extend(line_crosses(points).into_iter().map(|((timestamp, value, reference), direction)| CrossoverEvent {
+            kind,
+            direction,
+            timestamp,
+            value,
+            reference,
+        }));
+    }
+
+    events.sort_by_key(|event| event.timestamp);
+    events
+}
+
+/// The points of a `(point, line, reference)` series where the line
+/// crossed the reference, by the rule `detect_crossovers` describes
+pub fn line_crosses<T>(points: impl IntoIterator<Item = (T, f64, f64)>) -> Vec<(T, CrossDirection)> {
+    let mut crosses = Vec::new();
+    // The side of the reference the line was last strictly on
+    let mut side = None;
+    for (point, value, reference) in points {
+        let current = match value.partial_cmp(&reference) {
+            Some(Ordering::Equal) | None => continue,
+            current => current,
+        };
+        if side.is_some() && side != current {
+            let direction = if current == Some(Ordering::Greater) { CrossDirection::Up } else { CrossDirection::Down };
+            crosses.push((point, direction));
+        }
+        side = current;
+    }
+    crosses
+}
+
+/// Crossovers confirmed within the last `lookback` bars of the series
+pub fn recent_crossovers(stock_data: &[StockData], indicators: &[TechnicalIndicators], lookback: usize) -> Vec<CrossoverEvent> {
+    if lookback == 0 || stock_data.is_empty() {
+        return Vec::new();
+    }
+    let since = stock_data[stock_data.len().saturating_sub(lookback)].timestamp;
+    detect_crossovers(stock_data, indicators)
+        .into_iter()
+        .filter(|event| event.timestamp >= since)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn bar(day: i64, close: f64) -> StockData {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        StockData {
+            symbol: "TEST".to_string(),
+            timestamp: start + Duration::days(day),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1_000,
+            adjusted_close: None,
+        }
+    }
+
+    /// Bars and indicators with only the SMA pair set
+    fn sma_series(pairs: &[(f64, f64)]) -> (Vec<StockData>, Vec<TechnicalIndicators>) {
+        let data = (0..pairs.len()).map(|day| bar(day as i64, 100.0)).collect();
+        let indicators = pairs
+            .iter()
+            .map(|&(sma_20, sma_50)| TechnicalIndicators {
+                sma_20: Some(sma_20),
+                sma_50: Some(sma_50),
+                ..Default::default()
+            })
+            .collect();
+        (data, indicators)
+    }
+
+    fn sma_events(events: &[CrossoverEvent]) -> Vec<(CrossDirection, i64)> {
+        let start = bar(0, 0.0).timestamp;
+        events
+            .iter()
+            .filter(|event| event.kind == CrossoverKind::Sma20Sma50)
+            .map(|event| (event.direction, (event.timestamp - start).num_days()))
+            .collect()
+    }
+
+    #[test]
+    fn test_each_cross_fires_once() {
+        // Golden cross on day 2, death cross on day 4; staying above or below emits nothing
+        let (data, indicators) = sma_series(&[(9.0, 10.0), (9.5, 10.0), (10.5, 10.0), (11.0, 10.0), (9.0, 10.0), (8.0, 10.0)]);
+        let events = detect_crossovers(&data, &indicators);
+        assert_eq!(sma_events(&events), vec![(CrossDirection::Up, 2), (CrossDirection::Down, 4)]);
+
+        let golden = &events[0];
+        assert_eq!(golden.value, 10.5);
+        assert_eq!(golden.reference, 10.0);
+        assert_eq!(golden.signal(), "Golden Cross - SMA20 Crossed Above SMA50 (2023-11-16)");
+    }
+
+    #[test]
+    fn test_touching_the_line() {
+        // Touching and turning back is not a cross
+        let (data, indicators) = sma_series(&[(9.0, 10.0), (10.0, 10.0), (9.0, 10.0)]);
+        assert_eq!(sma_events(&detect_crossovers(&data, &indicators)), vec![]);
+
+        // Passing through an exact touch is one cross, dated when it clears the line
+        let (data, indicators) = sma_series(&[(9.0, 10.0), (10.0, 10.0), (10.0, 10.0), (11.0, 10.0), (12.0, 10.0)]);
+        assert_eq!(sma_events(&detect_crossovers(&data, &indicators)), vec![(CrossDirection::Up, 3)]);
+
+        // A series that starts on the line has nothing to cross from
+        let (data, indicators) = sma_series(&[(10.0, 10.0), (11.0, 10.0)]);
+        assert_eq!(sma_events(&detect_crossovers(&data, &indicators)), vec![]);
+    }
+
+    #[test]
+    fn test_rsi_macd_and_price_crosses() {
+        let data: Vec<StockData> = [50.0, 52.0, 48.0].iter().enumerate().map(|(day, &close)| bar(day as i64, close)).collect();
+        let indicators = [(35.0, (0.5, 1.0)), (25.0, (1.5, 1.0)), (72.0, (1.5, 1.0))]
+            .iter()
+            .map(|&(rsi, (macd, signal))| TechnicalIndicators {
+                rsi: Some(rsi),
+                macd: Some((macd, signal, macd - signal)),
+                sma_50: Some(50.0),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let kinds: Vec<_> = detect_crossovers(&data, &indicators).iter().map(|e| (e.kind, e.direction)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (CrossoverKind::MacdSignal, CrossDirection::Up),
+                (CrossoverKind::Rsi30, CrossDirection::Down),
+                (CrossoverKind::Rsi30, CrossDirection::Up),
+                (CrossoverKind::Rsi70, CrossDirection::Up),
+                (CrossoverKind::PriceSma50, CrossDirection::Down),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recent_crossovers_lookback() {
+        let (data, indicators) = sma_series(&[(9.0, 10.0), (11.0, 10.0), (12.0, 10.0), (13.0, 10.0), (9.0, 10.0)]);
+        assert_eq!(sma_events(&recent_crossovers(&data, &indicators, 1)), vec![(CrossDirection::Down, 4)]);
+        assert_eq!(sma_events(&recent_crossovers(&data, &indicators, 4)), vec![(CrossDirection::Up, 1), (CrossDirection::Down, 4)]);
+        assert!(recent_crossovers(&data, &indicators, 0).is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AnalyzerError;
+
+/// Dependency probes run at most this often; `/api/health` serves the last
+/// results in between
+pub const HEALTH_PROBE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+/// A probe that takes longer than this counts as failed
+pub const PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// State of one dependency, or of the service as a whole
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    #[default]
+    Ok,
+    /// Answering, but rate limiting us; or for the service, running with a
+    /// failing upstream or a stalled analysis loop
+    Degraded,
+    Down,
+}
+
+impl HealthStatus {
+    /// How a failed probe reflects on its dependency: throttling means it
+    /// is up but holding us back
+    pub fn from_error(error: &AnalyzerError) -> Self {
+        match error {
+            AnalyzerError::RateLimited { .. } => HealthStatus::Degraded,
+            _ => HealthStatus::Down,
+        }
+    }
+}
+
+/// Result of the latest probe of a dependency
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DependencyHealth {
+    pub status: HealthStatus,
+    pub checked_at: Option<DateTime<Utc>>,
+    /// Kept across failures, so it shows how long the dependency has been failing
+    pub last_success: Option<DateTime<Utc>>,
+    /// Error of the latest probe; cleared once a probe succeeds
+    pub last_error: Option<String>,
+}
+
+impl DependencyHealth {
+    /// Record a probe's outcome at `now`
+    pub fn record(&mut self, outcome: Result<(), AnalyzerError>, now: DateTime<Utc>) {
+        self.checked_at = Some(now);
+        match outcome {
+            Ok(()) => {
+                self.status = HealthStatus::Ok;
+                self.last_success = Some(now);
+                self.last_error = None;
+            }
+            Err(e) => {
+                self.status = HealthStatus::from_error(&e);
+                self.last_error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+/// Latest probe results of the services the analyzer depends on
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DependencyProbes {
+    /// Nasdaq screener, or whatever serves the ticker listing
+    pub listing: DependencyHealth,
+    /// A quote fetch for the canary symbol through the shared rate limiter
+    pub quotes: DependencyHealth,
+    /// Absent when the server runs without a database
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database: Option<DependencyHealth>,
+}
+
+impl DependencyProbes {
+    /// Whether the probes are due again at `now`
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        let interval = Duration::from_std(HEALTH_PROBE_INTERVAL).unwrap_or_default();
+        self.listing.checked_at.is_none_or(|checked| now - checked >= interval)
+    }
+
+    /// The service is down when its own database fails; a failing upstream
+    /// only degrades it, since every instance would share that failure
+    pub fn overall(&self) -> HealthStatus {
+        if self.database.as_ref().is_some_and(|db| db.status == HealthStatus::Down) {
+            return HealthStatus::Down;
+        }
+        let worst = [Some(&self.listing), Some(&self.quotes), self.database.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|dependency| dependency.status)
+            .max()
+            .unwrap_or_default();
+        worst.min(HealthStatus::Degraded)
+    }
+}
+
+/// Whether a running or waiting continuous loop has stopped making
+/// progress: no status update for two cycle intervals, and no scheduled
+/// next cycle within one interval's grace.
+pub fn is_stalled(last_update: DateTime<Utc>, next_run_at: Option<DateTime<Utc>>, cycle_interval: std::time::Duration, now: DateTime<Utc>) -> bool {
+    let interval = Duration::from_std(cycle_interval).unwrap_or_default();
+    let deadline = match next_run_at {
+        Some(next_run) => (last_update + interval * 2).max(next_run + interval),
+        None => last_update + interval * 2,
+    };
+    now > deadline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 4, 15, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_dependency_records_failures_and_recovery() {
+        let mut quotes = DependencyHealth::default();
+        quotes.record(Ok(()), at(0));
+        quotes.record(Err(AnalyzerError::RateLimited { retry_after: None }), at(1));
+        assert_eq!(quotes.status, HealthStatus::Degraded);
+        assert_eq!((quotes.checked_at, quotes.last_success), (Some(at(1)), Some(at(0))));
+        assert_eq!(quotes.last_error.as_deref(), Some("rate limited"));
+
+        quotes.record(Err(AnalyzerError::Upstream("connection refused".to_string())), at(2));
+        assert_eq!(quotes.status, HealthStatus::Down);
+        quotes.record(Ok(()), at(3));
+        assert_eq!((quotes.status, quotes.last_error.clone()), (HealthStatus::Ok, None));
+    }
+
+    #[test]
+    fn test_overall_status() {
+        let mut probes = DependencyProbes { database: Some(DependencyHealth::default()), ..DependencyProbes::default() };
+        assert_eq!(probes.overall(), HealthStatus::Ok);
+        assert!(probes.is_stale(at(0)));
+
+        probes.listing.record(Err(AnalyzerError::Upstream("403 Forbidden".to_string())), at(0));
+        assert_eq!(probes.listing.status, HealthStatus::Down);
+        assert_eq!(probes.overall(), HealthStatus::Degraded, "an upstream outage only degrades the service");
+        assert!(!probes.is_stale(at(0)) && probes.is_stale(at(1)));
+
+        probes.database.as_mut().unwrap().record(Err(AnalyzerError::Database("readonly database".to_string())), at(1));
+        assert_eq!(probes.overall(), HealthStatus::Down);
+    }
+
+    #[test]
+    fn test_stall_detection() {
+        let interval = std::time::Duration::from_secs(5 * 60);
+        assert!(!is_stalled(at(0), None, interval, at(10)));
+        assert!(is_stalled(at(0), None, interval, at(11)));
+        // Waiting for a far-off market open is not a stall until that run is overdue
+        assert!(!is_stalled(at(0), Some(at(30)), interval, at(34)));
+        assert!(is_stalled(at(0), Some(at(30)), interval, at(36)));
+    }
+}
@@ -1,51 +1,74 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
 use crate::StockData;
 
+/// Average True Range with Wilder smoothing
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AverageTrueRange {
     pub period: usize,
+    prev_close: Option<f64>,
+    seed_count: usize,
+    seed_sum: f64,
+    atr: Option<f64>,
 }
 
+/// Opaque snapshot of an `AverageTrueRange`'s smoothing state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AtrState(AverageTrueRange);
+
 impl AverageTrueRange {
     pub fn new(period: usize) -> Self {
-        Self { period }
+        Self {
+            period,
+            prev_close: None,
+            seed_count: 0,
+            seed_sum: 0.0,
+            atr: None,
+        }
     }
 
     pub fn calculate(&self, data: &[StockData]) -> Vec<Option<f64>> {
-        if data.len() < 2 {
-            return vec![None; data.len()];
+        let mut atr = Self::new(self.period);
+        data.iter().map(|d| atr.next(d)).collect()
+    }
+
+    /// Feed one candle; the first candle only provides the previous close
+    pub fn next(&mut self, data: &StockData) -> Option<f64> {
+        let prev_close = self.prev_close.replace(data.close)?;
+        if self.period == 0 {
+            return None;
         }
 
-        let mut true_ranges = Vec::new();
-        let mut results = vec![None]; // First value is None
-
-        // Calculate True Range for each period
-        for i in 1..data.len() {
-            let high = data[i].high;
-            let low = data[i].low;
-            let prev_close = data[i-1].close;
-            
-            let tr = (high - low)
-                .max((high - prev_close).abs())
-                .max((low - prev_close).abs());
-            
-            true_ranges.push(tr);
-            
-            // Calculate ATR once we have enough TR values
-            if true_ranges.len() >= self.period {
-                let atr = if true_ranges.len() == self.period {
-                    // First ATR is simple average
-                    true_ranges.iter().sum::<f64>() / self.period as f64
-                } else {
-                    // Subsequent ATRs use smoothing
-                    let prev_atr = results.last().unwrap().unwrap();
-                    (prev_atr * (self.period - 1) as f64 + tr) / self.period as f64
-                };
-                results.push(Some(atr));
-            } else {
-                results.push(None);
+        let tr = (data.high - data.low)
+            .max((data.high - prev_close).abs())
+            .max((data.low - prev_close).abs());
+
+        self.atr = match self.atr {
+            // Subsequent ATRs use smoothing
+            Some(prev_atr) => Some((prev_atr * (self.period - 1) as f64 + tr) / self.period as f64),
+            // First ATR is simple average
+            None => {
+                self.seed_count += 1;
+                self.seed_sum += tr;
+                (self.seed_count == self.period).then(|| self.seed_sum / self.period as f64)
             }
-        }
+        };
+        self.atr
+    }
 
-        results
+    /// Capture the streaming state so processing can resume later
+    pub fn snapshot(&self) -> AtrState {
+        AtrState(self.clone())
+    }
+
+    /// Resume from a snapshot taken with `snapshot`
+    pub fn restore(&mut self, state: &AtrState) {
+        *self = state.0.clone();
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.period);
     }
 
     /// Calculate volatility percentile based on ATR
@@ -72,51 +95,76 @@ impl Default for AverageTrueRange {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommodityChannelIndex {
     pub period: usize,
     pub factor: f64,
+    typical_prices: VecDeque<f64>,
 }
 
+/// Opaque snapshot of a `CommodityChannelIndex` window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CciState(CommodityChannelIndex);
+
 impl CommodityChannelIndex {
     pub fn new(period: usize, factor: f64) -> Self {
-        Self { period, factor }
+        Self {
+            period,
+            factor,
+            typical_prices: VecDeque::with_capacity(period),
+        }
     }
 
     pub fn calculate(&self, data: &[StockData]) -> Vec<Option<f64>> {
-        if data.len() < self.period {
-            return vec![None; data.len()];
-        }
+        let mut cci = Self::new(self.period, self.factor);
+        data.iter().map(|d| cci.next(d)).collect()
+    }
 
-        let mut results = vec![None; self.period - 1];
-
-        for i in (self.period - 1)..data.len() {
-            let window = &data[i - self.period + 1..=i];
-            
-            // Calculate Typical Price for each day
-            let typical_prices: Vec<f64> = window.iter()
-                .map(|d| (d.high + d.low + d.close) / 3.0)
-                .collect();
-            
-            // Calculate Simple Moving Average of Typical Price
-            let sma_tp = typical_prices.iter().sum::<f64>() / self.period as f64;
-            
-            // Calculate Mean Deviation
-            let mean_deviation = typical_prices.iter()
-                .map(|&tp| (tp - sma_tp).abs())
-                .sum::<f64>() / self.period as f64;
-            
-            // Calculate CCI
-            let current_tp = typical_prices.last().unwrap();
-            let cci = if mean_deviation != 0.0 {
-                (current_tp - sma_tp) / (self.factor * mean_deviation)
-            } else {
-                0.0
-            };
-            
-            results.push(Some(cci));
+    /// Feed one candle; `None` until a full window has been seen
+    pub fn next(&mut self, data: &StockData) -> Option<f64> {
+        if self.period == 0 {
+            return None;
+        }
+        // Typical Price for the day
+        let current_tp = (data.high + data.low + data.close) / 3.0;
+        self.typical_prices.push_back(current_tp);
+        if self.typical_prices.len() > self.period {
+            self.typical_prices.pop_front();
+        }
+        if self.typical_prices.len() < self.period {
+            return None;
         }
 
-        results
+        // Calculate Simple Moving Average of Typical Price
+        let sma_tp = self.typical_prices.iter().sum::<f64>() / self.period as f64;
+
+        // Calculate Mean Deviation
+        let mean_deviation = self.typical_prices.iter()
+            .map(|&tp| (tp - sma_tp).abs())
+            .sum::<f64>() / self.period as f64;
+
+        // Calculate CCI
+        let cci = if mean_deviation != 0.0 {
+            (current_tp - sma_tp) / (self.factor * mean_deviation)
+        } else {
+            0.0
+        };
+
+        Some(cci)
+    }
+
+    /// Capture the streaming state so processing can resume later
+    pub fn snapshot(&self) -> CciState {
+        CciState(self.clone())
+    }
+
+    /// Resume from a snapshot taken with `snapshot`
+    pub fn restore(&mut self, state: &CciState) {
+        *self = state.0.clone();
+    }
+
+    pub fn reset(&mut self) {
+        self.typical_prices.clear();
     }
 
     /// Generate trading signals based on CCI
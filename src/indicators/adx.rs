@@ -0,0 +1,278 @@
+use serde::{Deserialize, Serialize};
+
+use crate::StockData;
+
+/// ADX below this means the market is not trending
+pub const WEAK_TREND_ADX: f64 = 20.0;
+/// ADX at or above this means a strong trend
+pub const STRONG_TREND_ADX: f64 = 40.0;
+
+/// ADX with the directional indicators it is built from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AdxValue {
+    pub adx: f64,
+    pub plus_di: f64,
+    pub minus_di: f64,
+}
+
+/// How strongly the market trends, whichever the direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendStrength {
+    Strong,
+    Weak,
+    NoTrend,
+}
+
+impl TrendStrength {
+    /// Classify by `WEAK_TREND_ADX` and `STRONG_TREND_ADX`
+    pub fn from_adx(adx: f64) -> Self {
+        if adx >= STRONG_TREND_ADX {
+            TrendStrength::Strong
+        } else if adx >= WEAK_TREND_ADX {
+            TrendStrength::Weak
+        } else {
+            TrendStrength::NoTrend
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TrendStrength::Strong => "Strong trend",
+            TrendStrength::Weak => "Weak trend",
+            TrendStrength::NoTrend => "No trend",
+        }
+    }
+}
+
+/// Welles Wilder's Average Directional Index.
+///
+/// True range and directional movement are summed over the first `period`
+/// moves and Wilder-smoothed after that; DX is averaged the same way. The
+/// first candle has no move, so the first ADX comes on candle `2 × period`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AverageDirectionalIndex {
+    pub period: usize,
+    /// High, low and close of the previous candle
+    prev: Option<(f64, f64, f64)>,
+    seed_count: usize,
+    /// Smoothed (true range, +DM, -DM), once `period` moves have been seen
+    smoothed: (f64, f64, f64),
+    dx_count: usize,
+    dx_sum: f64,
+    adx: Option<f64>,
+}
+
+/// Opaque snapshot of an `AverageDirectionalIndex`'s smoothing state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdxState(AverageDirectionalIndex);
+
+impl AverageDirectionalIndex {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            prev: None,
+            seed_count: 0,
+            smoothed: (0.0, 0.0, 0.0),
+            dx_count: 0,
+            dx_sum: 0.0,
+            adx: None,
+        }
+    }
+
+    pub fn calculate(&self, data: &[StockData]) -> Vec<Option<AdxValue>> {
+        let mut adx = Self::new(self.period);
+        data.iter().map(|d| adx.next(d)).collect()
+    }
+
+    /// Feed one candle; `None` until `2 × period` candles have been seen
+    pub fn next(&mut self, data: &StockData) -> Option<AdxValue> {
+        let (prev_high, prev_low, prev_close) = self.prev.replace((data.high, data.low, data.close))?;
+        if self.period == 0 {
+            return None;
+        }
+
+        let tr = (data.high - data.low)
+            .max((data.high - prev_close).abs())
+            .max((data.low - prev_close).abs());
+        let up_move = data.high - prev_high;
+        let down_move = prev_low - data.low;
+        let plus_dm = if up_move > down_move && up_move > 0.0 { up_move } else { 0.0 };
+        let minus_dm = if down_move > up_move && down_move > 0.0 { down_move } else { 0.0 };
+
+        let period = self.period as f64;
+        let (tr_sum, plus_sum, minus_sum) = &mut self.smoothed;
+        if self.seed_count < self.period {
+            // The first values are plain sums
+            self.seed_count += 1;
+            *tr_sum += tr;
+            *plus_sum += plus_dm;
+            *minus_sum += minus_dm;
+            if self.seed_count < self.period {
+                return None;
+            }
+        } else {
+            *tr_sum = *tr_sum - *tr_sum / period + tr;
+            *plus_sum = *plus_sum - *plus_sum / period + plus_dm;
+            *minus_sum = *minus_sum - *minus_sum / period + minus_dm;
+        }
+
+        let (plus_di, minus_di) = if *tr_sum > 0.0 {
+            (100.0 * *plus_sum / *tr_sum, 100.0 * *minus_sum / *tr_sum)
+        } else {
+            (0.0, 0.0)
+        };
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum > 0.0 { 100.0 * (plus_di - minus_di).abs() / di_sum } else { 0.0 };
+
+        self.adx = match self.adx {
+            Some(prev_adx) => Some((prev_adx * (period - 1.0) + dx) / period),
+            // First ADX is the simple average of the first `period` DX values
+            None => {
+                self.dx_count += 1;
+                self.dx_sum += dx;
+                (self.dx_count == self.period).then(|| self.dx_sum / period)
+            }
+        };
+        self.adx.map(|adx| AdxValue { adx, plus_di, minus_di })
+    }
+
+    /// Capture the streaming state so processing can resume later
+    pub fn snapshot(&self) -> AdxState {
+        AdxState(self.clone())
+    }
+
+    /// Resume from a snapshot taken with `snapshot`
+    pub fn restore(&mut self, state: &AdxState) {
+        *self = state.0.clone();
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+
+    /// Trend strength, plus the leading direction when there is a trend
+    pub fn generate_signals(&self, value: Option<&AdxValue>) -> Vec<String> {
+        let Some(value) = value else {
+            return Vec::new();
+        };
+        let strength = TrendStrength::from_adx(value.adx);
+        let mut signals = vec![format!("ADX {} ({:.1})", strength.as_str(), value.adx)];
+        if strength != TrendStrength::NoTrend {
+            if value.plus_di > value.minus_di {
+                signals.push("ADX Uptrend: +DI above -DI".to_string());
+            } else if value.minus_di > value.plus_di {
+                signals.push("ADX Downtrend: -DI above +DI".to_string());
+            }
+        }
+        signals
+    }
+}
+
+impl Default for AverageDirectionalIndex {
+    fn default() -> Self {
+        Self::new(14)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration};
+
+    /// Daily candles from (high, low, close) rows
+    fn candles(rows: &[(f64, f64, f64)]) -> Vec<StockData> {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        rows.iter()
+            .enumerate()
+            .map(|(i, &(high, low, close))| StockData {
+                symbol: "TEST".to_string(),
+                timestamp: start + Duration::days(i as i64),
+                open: close,
+                high,
+                low,
+                close,
+                volume: 1_000,
+                adjusted_close: None,
+            })
+            .collect()
+    }
+
+    /// Worked by hand with period 3. True ranges 1.5, 2, 3, 2, 2.5, 3.5, 2, 2;
+    /// +DM 1, 0, 1.5, 1, 0, 1.5, 1, 1; -DM 0, 1, 0, 0, 1, 0, 0, 0. The first
+    /// sums (6.5, 2.5, 1) give +DI 38.46, -DI 15.38 and DX 42.86; DX 60 and
+    /// 10.34 follow, so the first ADX is their mean, 37.73.
+    #[test]
+    fn test_worked_example() {
+        let data = candles(&[
+            (30.0, 29.0, 29.5),
+            (31.0, 29.5, 30.5),
+            (30.5, 28.5, 29.0),
+            (32.0, 29.0, 31.5),
+            (33.0, 31.0, 32.5),
+            (32.5, 30.0, 30.5),
+            (34.0, 31.0, 33.5),
+            (35.0, 33.0, 34.5),
+            (36.0, 34.0, 35.5),
+        ]);
+        let values = AverageDirectionalIndex::new(3).calculate(&data);
+
+        // 2 × period candles before the first value
+        assert!(values[..5].iter().all(Option::is_none));
+        let expected = [
+            (37.73399014778325, 26.446280991735538, 21.487603305785125),
+            (40.892034040992556, 33.64269141531322, 12.064965197215777),
+            (48.124665379846356, 38.11129848229342, 8.768971332209107),
+            (56.7389300279493, 41.566985645933016, 6.220095693779904),
+        ];
+        for (value, (adx, plus_di, minus_di)) in values[5..].iter().zip(expected) {
+            let value = value.unwrap();
+            assert!((value.adx - adx).abs() < 1e-9, "{:?}", value);
+            assert!((value.plus_di - plus_di).abs() < 1e-9, "{:?}", value);
+            assert!((value.minus_di - minus_di).abs() < 1e-9, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn test_streaming_matches_batch_and_flat_series() {
+        let rows: Vec<(f64, f64, f64)> = (0..40)
+            .map(|i| {
+                let close = 100.0 + (i as f64 * 0.4).sin() * 6.0;
+                (close + 1.0, close - 1.5, close)
+            })
+            .collect();
+        let data = candles(&rows);
+        let batch = AverageDirectionalIndex::default().calculate(&data);
+        assert!(batch[26].is_none() && batch[27].is_some());
+
+        let mut streaming = AverageDirectionalIndex::default();
+        for candle in &data[..20] {
+            streaming.next(candle);
+        }
+        let state = streaming.snapshot();
+        let mut resumed = AverageDirectionalIndex::default();
+        resumed.restore(&state);
+        let rest: Vec<_> = data[20..].iter().map(|d| resumed.next(d)).collect();
+        assert_eq!(rest, batch[20..]);
+
+        // No movement at all: no division by zero, just no trend
+        let flat = candles(&[(10.0, 10.0, 10.0); 8]);
+        let last = AverageDirectionalIndex::new(3).calculate(&flat)[7].unwrap();
+        assert_eq!((last.adx, last.plus_di, last.minus_di), (0.0, 0.0, 0.0));
+        assert_eq!(TrendStrength::from_adx(last.adx), TrendStrength::NoTrend);
+    }
+
+    #[test]
+    fn test_trend_classification() {
+        assert_eq!(TrendStrength::from_adx(19.9), TrendStrength::NoTrend);
+        assert_eq!(TrendStrength::from_adx(20.0), TrendStrength::Weak);
+        assert_eq!(TrendStrength::from_adx(40.0), TrendStrength::Strong);
+
+        let adx = AverageDirectionalIndex::default();
+        let value = AdxValue { adx: 45.0, plus_di: 12.0, minus_di: 30.0 };
+        assert_eq!(adx.generate_signals(Some(&value)), ["ADX Strong trend (45.0)", "ADX Downtrend: -DI above +DI"]);
+        let value = AdxValue { adx: 12.0, ..value };
+        assert_eq!(adx.generate_signals(Some(&value)), ["ADX No trend (12.0)"]);
+        assert!(adx.generate_signals(None).is_empty());
+    }
+}
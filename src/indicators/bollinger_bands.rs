@@ -1,11 +1,16 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
 use crate::StockData;
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BollingerBands {
     pub period: usize,
     pub std_dev_multiplier: f64,
+    closes: VecDeque<f64>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BollingerBandsValue {
     pub upper_band: f64,
     pub middle_band: f64, // SMA
@@ -14,62 +19,86 @@ pub struct BollingerBandsValue {
     pub percent_b: f64, // Position within bands (0-1)
 }
 
+/// Opaque snapshot of a `BollingerBands` window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BollingerState(BollingerBands);
+
 impl BollingerBands {
     pub fn new(period: usize, std_dev_multiplier: f64) -> Self {
         Self {
             period,
             std_dev_multiplier,
+            closes: VecDeque::with_capacity(period),
         }
     }
 
     pub fn calculate(&self, data: &[StockData]) -> Vec<Option<BollingerBandsValue>> {
-        if data.len() < self.period {
-            return vec![None; data.len()];
-        }
+        let mut bands = Self::new(self.period, self.std_dev_multiplier);
+        data.iter().map(|d| bands.next(d)).collect()
+    }
 
-        let mut results = vec![None; self.period - 1];
-        
-        for i in (self.period - 1)..data.len() {
-            let window = &data[i - self.period + 1..=i];
-            
-            // Calculate SMA (middle band)
-            let sma: f64 = window.iter().map(|d| d.close).sum::<f64>() / self.period as f64;
-            
-            // Calculate standard deviation
-            let variance: f64 = window.iter()
-                .map(|d| (d.close - sma).powi(2))
-                .sum::<f64>() / self.period as f64;
-            let std_dev = variance.sqrt();
-            
-            // Calculate bands
-            let upper_band = sma + (self.std_dev_multiplier * std_dev);
-            let lower_band = sma - (self.std_dev_multiplier * std_dev);
-            
-            // Calculate bandwidth (volatility measure)
-            let bandwidth = if sma != 0.0 {
-                (upper_band - lower_band) / sma * 100.0
-            } else {
-                0.0
-            };
-            
-            // Calculate %B (position within bands)
-            let current_price = data[i].close;
-            let percent_b = if upper_band != lower_band {
-                (current_price - lower_band) / (upper_band - lower_band)
-            } else {
-                0.5
-            };
-            
-            results.push(Some(BollingerBandsValue {
-                upper_band,
-                middle_band: sma,
-                lower_band,
-                bandwidth,
-                percent_b,
-            }));
+    /// Feed one candle; `None` until a full window has been seen
+    pub fn next(&mut self, data: &StockData) -> Option<BollingerBandsValue> {
+        if self.period == 0 {
+            return None;
         }
-        
-        results
+        self.closes.push_back(data.close);
+        if self.closes.len() > self.period {
+            self.closes.pop_front();
+        }
+        if self.closes.len() < self.period {
+            return None;
+        }
+
+        // Calculate SMA (middle band)
+        let sma: f64 = self.closes.iter().sum::<f64>() / self.period as f64;
+
+        // Calculate standard deviation
+        let variance: f64 = self.closes.iter()
+            .map(|close| (close - sma).powi(2))
+            .sum::<f64>() / self.period as f64;
+        let std_dev = variance.sqrt();
+
+        // Calculate bands
+        let upper_band = sma + (self.std_dev_multiplier * std_dev);
+        let lower_band = sma - (self.std_dev_multiplier * std_dev);
+
+        // Calculate bandwidth (volatility measure)
+        let bandwidth = if sma != 0.0 {
+            (upper_band - lower_band) / sma * 100.0
+        } else {
+            0.0
+        };
+
+        // Calculate %B (position within bands)
+        let current_price = data.close;
+        let percent_b = if upper_band != lower_band {
+            (current_price - lower_band) / (upper_band - lower_band)
+        } else {
+            0.5
+        };
+
+        Some(BollingerBandsValue {
+            upper_band,
+            middle_band: sma,
+            lower_band,
+            bandwidth,
+            percent_b,
+        })
+    }
+
+    /// Capture the streaming state so processing can resume later
+    pub fn snapshot(&self) -> BollingerState {
+        BollingerState(self.clone())
+    }
+
+    /// Resume from a snapshot taken with `snapshot`
+    pub fn restore(&mut self, state: &BollingerState) {
+        *self = state.0.clone();
+    }
+
+    pub fn reset(&mut self) {
+        self.closes.clear();
     }
 
     /// Generate trading signals based on Bollinger Bands
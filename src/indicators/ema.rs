@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+/// Exponential Moving Average matching TradingView's `ta.ema`
+/// Seeded with the simple average of the first `period` values, then
+/// smoothed with alpha = 2 / (period + 1)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExponentialMovingAverage {
+    period: usize,
+    alpha: f64,
+    value: Option<f64>,
+    seed_sum: f64,
+    count: usize,
+}
+
+/// Opaque snapshot of an `ExponentialMovingAverage`'s accumulators
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmaState(ExponentialMovingAverage);
+
+impl ExponentialMovingAverage {
+    pub fn new(period: usize) -> Result<Self, ta::errors::TaError> {
+        if period == 0 {
+            return Err(ta::errors::TaError::InvalidParameter);
+        }
+        Ok(Self {
+            period,
+            alpha: 2.0 / (period as f64 + 1.0),
+            value: None,
+            seed_sum: 0.0,
+            count: 0,
+        })
+    }
+
+    /// Feed one value; `None` until `period` values have been seen
+    pub fn next(&mut self, input: f64) -> Option<f64> {
+        match self.value {
+            Some(previous) => {
+                self.value = Some(self.alpha * input + (1.0 - self.alpha) * previous);
+            }
+            None => {
+                self.seed_sum += input;
+                self.count += 1;
+                if self.count == self.period {
+                    self.value = Some(self.seed_sum / self.period as f64);
+                }
+            }
+        }
+        self.value
+    }
+
+    /// Capture the streaming state so processing can resume later
+    pub fn snapshot(&self) -> EmaState {
+        EmaState(self.clone())
+    }
+
+    /// Resume from a snapshot taken with `snapshot`
+    pub fn restore(&mut self, state: &EmaState) {
+        *self = state.0.clone();
+    }
+
+    pub fn reset(&mut self) {
+        self.value = None;
+        self.seed_sum = 0.0;
+        self.count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ema_creation() {
+        assert!(ExponentialMovingAverage::new(9).is_ok());
+        assert!(ExponentialMovingAverage::new(0).is_err());
+    }
+
+    #[test]
+    fn test_ema_seeds_with_sma_then_smooths() {
+        let mut ema = ExponentialMovingAverage::new(3).unwrap();
+
+        assert_eq!(ema.next(10.0), None);
+        assert_eq!(ema.next(20.0), None);
+        // Seed: (10 + 20 + 30) / 3 = 20
+        assert_eq!(ema.next(30.0), Some(20.0));
+        // alpha = 0.5: 0.5 * 40 + 0.5 * 20 = 30
+        assert_eq!(ema.next(40.0), Some(30.0));
+    }
+
+    #[test]
+    fn test_ema_reset_and_restore() {
+        let mut ema = ExponentialMovingAverage::new(2).unwrap();
+        ema.next(1.0);
+        let state = ema.snapshot();
+        let resumed = ema.next(3.0);
+
+        ema.reset();
+        assert_eq!(ema.next(5.0), None);
+
+        ema.restore(&state);
+        assert_eq!(ema.next(3.0), resumed);
+    }
+}
@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use super::{
+    AdxState, AtrState, BollingerState, CciState, EmaState, MacdState, ObvState, RangeState, RelativeVolumeState, RsiState, SmaState,
+    StochasticState, VwapState,
+};
+use crate::{StockData, TechnicalIndicators};
+
+/// Identity of a processed candle series: its length, last timestamp and a
+/// hash chained over every candle, so any revision of past data is detected
+/// and the identity of a longer series follows from the shorter one's.
+///
+/// The hash is only stable within one build; a fingerprint persisted by an
+/// older build reads as a revision and forces a recompute.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeriesFingerprint {
+    pub len: usize,
+    pub last_timestamp: Option<DateTime<Utc>>,
+    pub hash: u64,
+}
+
+impl SeriesFingerprint {
+    pub fn of(data: &[StockData]) -> Self {
+        Self { len: 0, last_timestamp: None, hash: 0 }.extend(data)
+    }
+
+    /// Fingerprint of the processed series followed by `data`
+    pub fn extend(&self, data: &[StockData]) -> Self {
+        let hash = data.iter().fold(self.hash, |hash, candle| {
+            let mut hasher = DefaultHasher::new();
+            hash.hash(&mut hasher);
+            candle.timestamp.timestamp().hash(&mut hasher);
+            for value in [candle.open, candle.high, candle.low, candle.close] {
+                value.to_bits().hash(&mut hasher);
+            }
+            candle.volume.hash(&mut hasher);
+            hasher.finish()
+        });
+
+        Self {
+            len: self.len + data.len(),
+            last_timestamp: data.last().map(|c| c.timestamp).or(self.last_timestamp),
+            hash,
+        }
+    }
+}
+
+/// Why the indicator series has to be recomputed from the first candle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecomputeReason {
+    /// No state has been retained for this symbol and settings
+    NoState,
+    /// A previously processed candle changed
+    Revised,
+    /// The new series no longer covers everything that was processed, or
+    /// its new candles are not strictly later than the processed ones
+    Gap,
+}
+
+/// How to bring retained indicator state up to date with a new series
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdatePlan {
+    /// Same series as last time; reuse the retained outputs
+    Unchanged,
+    /// The series strictly extends the processed one; feed candles from `from`
+    Extend { from: usize },
+    Recompute(RecomputeReason),
+}
+
+/// Decide whether `data` can be processed incrementally on top of a series
+/// with fingerprint `processed`
+pub fn plan_update(processed: Option<&SeriesFingerprint>, data: &[StockData]) -> UpdatePlan {
+    let processed = match processed {
+        Some(processed) => processed,
+        None => return UpdatePlan::Recompute(RecomputeReason::NoState),
+    };
+
+    if data.len() < processed.len {
+        return UpdatePlan::Recompute(RecomputeReason::Gap);
+    }
+
+    if SeriesFingerprint::of(&data[..processed.len]) != *processed {
+        return UpdatePlan::Recompute(RecomputeReason::Revised);
+    }
+
+    if data.len() == processed.len {
+        return UpdatePlan::Unchanged;
+    }
+
+    let strictly_later = data[processed.len..]
+        .iter()
+        .try_fold(processed.last_timestamp, |previous, candle| match previous {
+            Some(previous) if candle.timestamp <= previous => None,
+            _ => Some(Some(candle.timestamp)),
+        })
+        .is_some();
+
+    if strictly_later {
+        UpdatePlan::Extend { from: processed.len }
+    } else {
+        UpdatePlan::Recompute(RecomputeReason::Gap)
+    }
+}
+
+/// Retained state of one configured moving average
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MovingAverageState {
+    Simple(SmaState),
+    Exponential(EmaState),
+}
+
+/// Outputs kept with indicator state persisted to the database: enough for
+/// the signals and crossovers of the latest candles
+pub const PERSISTED_OUTPUTS: usize = 64;
+
+/// Streaming indicator state retained after processing a series, together
+/// with the outputs produced so far
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorSnapshot {
+    pub fingerprint: SeriesFingerprint,
+    /// In the order of the configured moving averages
+    pub moving_averages: Vec<MovingAverageState>,
+    pub rsi: RsiState,
+    pub macd: MacdState,
+    pub bollinger: BollingerState,
+    pub stochastic: StochasticState,
+    pub atr: AtrState,
+    pub cci: CciState,
+    pub obv: ObvState,
+    pub vwap: VwapState,
+    pub relative_volume: RelativeVolumeState,
+    pub range: RangeState,
+    pub adx: AdxState,
+    /// Outputs for the last `outputs.len()` processed candles: all of them
+    /// in memory, the last `PERSISTED_OUTPUTS` once persisted
+    pub outputs: Arc<Vec<TechnicalIndicators>>,
+}
+
+impl IndicatorSnapshot {
+    /// Whether `outputs` covers every processed candle
+    pub fn is_complete(&self) -> bool {
+        self.outputs.len() == self.fingerprint.len
+    }
+
+    /// The same state with only the last `keep` outputs
+    pub fn trimmed(&self, keep: usize) -> Self {
+        let skip = self.outputs.len().saturating_sub(keep);
+        Self { outputs: Arc::new(self.outputs[skip..].to_vec()), ..self.clone() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn series(closes: &[f64]) -> Vec<StockData> {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| StockData {
+                symbol: "TEST".to_string(),
+                timestamp: start + Duration::days(i as i64),
+                open: *close,
+                high: close + 1.0,
+                low: close - 1.0,
+                close: *close,
+                volume: 1_000,
+                adjusted_close: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_without_state() {
+        let data = series(&[1.0, 2.0]);
+        assert_eq!(plan_update(None, &data), UpdatePlan::Recompute(RecomputeReason::NoState));
+    }
+
+    #[test]
+    fn test_plan_unchanged_and_extend() {
+        let data = series(&[1.0, 2.0, 3.0, 4.0]);
+        let processed = SeriesFingerprint::of(&data[..3]);
+
+        assert_eq!(plan_update(Some(&processed), &data[..3]), UpdatePlan::Unchanged);
+        assert_eq!(plan_update(Some(&processed), &data), UpdatePlan::Extend { from: 3 });
+    }
+
+    #[test]
+    fn test_fingerprint_extends() {
+        let data = series(&[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(SeriesFingerprint::of(&data[..1]).extend(&data[1..]), SeriesFingerprint::of(&data));
+        assert_eq!(SeriesFingerprint::of(&data).extend(&[]), SeriesFingerprint::of(&data));
+        assert_ne!(SeriesFingerprint::of(&data[1..]).hash, SeriesFingerprint::of(&data).hash);
+    }
+
+    #[test]
+    fn test_plan_revision_forces_recompute() {
+        let data = series(&[1.0, 2.0, 3.0, 4.0]);
+        let processed = SeriesFingerprint::of(&data[..3]);
+
+        let mut revised = data.clone();
+        revised[1].close = 2.5;
+        assert_eq!(
+            plan_update(Some(&processed), &revised),
+            UpdatePlan::Recompute(RecomputeReason::Revised)
+        );
+    }
+
+    #[test]
+    fn test_plan_gap_forces_recompute() {
+        let data = series(&[1.0, 2.0, 3.0, 4.0]);
+        let processed = SeriesFingerprint::of(&data);
+
+        // Shorter than what was processed
+        assert_eq!(
+            plan_update(Some(&processed), &data[..2]),
+            UpdatePlan::Recompute(RecomputeReason::Gap)
+        );
+
+        // New candle not after the last processed one
+        let mut extended = data.clone();
+        let mut stale = data[3].clone();
+        stale.timestamp = data[2].timestamp;
+        extended.push(stale);
+        assert_eq!(
+            plan_update(Some(&processed), &extended),
+            UpdatePlan::Recompute(RecomputeReason::Gap)
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use ta::indicators::MovingAverageConvergenceDivergence as TaMACD;
 use ta::{Next, Reset};
 
@@ -9,31 +10,57 @@ pub struct MACDOutput {
     pub histogram: f64,
 }
 
+/// Opaque snapshot of a MACD's internal EMAs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacdState(MovingAverageConvergenceDivergence);
+
 /// MACD (Moving Average Convergence Divergence) wrapper
-/// Currently uses the ta crate implementation but can be extended with custom logic
-#[derive(Debug, Clone)]
+/// Uses the ta crate implementation, but reports nothing until the slow EMA
+/// and then the signal line have seen a full period each
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MovingAverageConvergenceDivergence {
     inner: TaMACD,
+    warm_up: usize,
+    count: usize,
 }
 
 impl MovingAverageConvergenceDivergence {
     pub fn new(fast_period: usize, slow_period: usize, signal_period: usize) -> Result<Self, ta::errors::TaError> {
         Ok(Self {
             inner: TaMACD::new(fast_period, slow_period, signal_period)?,
+            warm_up: fast_period.max(slow_period) + signal_period - 1,
+            count: 0,
         })
     }
 
-    pub fn next(&mut self, input: f64) -> MACDOutput {
+    /// Feed one value; `None` until the signal line is warmed up
+    pub fn next(&mut self, input: f64) -> Option<MACDOutput> {
         let result = self.inner.next(input);
-        MACDOutput {
+        self.count = (self.count + 1).min(self.warm_up);
+        self.is_warmed_up().then_some(MACDOutput {
             macd: result.macd,
             signal: result.signal,
             histogram: result.histogram,
-        }
+        })
+    }
+
+    pub fn is_warmed_up(&self) -> bool {
+        self.count >= self.warm_up
+    }
+
+    /// Capture the streaming state so processing can resume later
+    pub fn snapshot(&self) -> MacdState {
+        MacdState(self.clone())
+    }
+
+    /// Resume from a snapshot taken with `snapshot`
+    pub fn restore(&mut self, state: &MacdState) {
+        *self = state.0.clone();
     }
 
     pub fn reset(&mut self) {
-        self.inner.reset()
+        self.inner.reset();
+        self.count = 0;
     }
 }
 
@@ -51,13 +78,13 @@ mod tests {
     fn test_macd_calculation() {
         let mut macd = MovingAverageConvergenceDivergence::new(2, 4, 2).unwrap();
         
-        // Feed some data
-        for price in [100.0, 101.0, 102.0, 103.0, 104.0].iter() {
-            let result = macd.next(*price);
-            // Just verify we get valid numeric results
-            assert!(result.macd.is_finite());
-            assert!(result.signal.is_finite());
-            assert!(result.histogram.is_finite());
+        // Slow period 4 plus signal period 2 needs 5 values
+        for price in [100.0, 101.0, 102.0, 103.0] {
+            assert!(macd.next(price).is_none());
         }
+        let result = macd.next(104.0).unwrap();
+        assert!(result.macd.is_finite());
+        assert!(result.signal.is_finite());
+        assert!(result.histogram.is_finite());
     }
 }
@@ -1,7 +1,22 @@
+pub mod adx;
 pub mod rsi;
 pub mod sma;
+pub mod ema;
 pub mod macd;
+pub mod bollinger_bands;
+pub mod stochastic;
+pub mod advanced;
+pub mod volume;
+pub mod range;
+pub mod incremental;
 
-pub use rsi::CustomRSI;
-pub use sma::SimpleMovingAverage;
-pub use macd::MovingAverageConvergenceDivergence;
+pub use rsi::{CustomRSI, RsiState};
+pub use sma::{SimpleMovingAverage, SmaState};
+pub use ema::{EmaState, ExponentialMovingAverage};
+pub use macd::{MacdState, MovingAverageConvergenceDivergence};
+pub use bollinger_bands::{BollingerBands, BollingerBandsValue, BollingerState};
+pub use stochastic::{StochasticOscillator, StochasticState, StochasticValue};
+pub use adx::{AdxState, AdxValue, AverageDirectionalIndex, TrendStrength};
+pub use advanced::{AtrState, AverageTrueRange, CciState, CommodityChannelIndex};
+pub use volume::{ObvState, OnBalanceVolume, RelativeVolume, RelativeVolumeState, Vwap, VwapState};
+pub use range::{RangeState, RangeValue, RollingRange, DEFAULT_RANGE_WINDOW};
@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::StockData;
+
+/// Trading days in a year, the default `RollingRange` window
+pub const DEFAULT_RANGE_WINDOW: usize = 252;
+
+/// Highest high and lowest low over the window, and how far the close sits
+/// from each
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RangeValue {
+    pub high: f64,
+    pub low: f64,
+    /// Percent the close is below the high (0.0 at the high)
+    pub pct_from_high: f64,
+    /// Percent the close is above the low (0.0 at the low)
+    pub pct_from_low: f64,
+    /// Whether a full window of candles was seen; short histories still get
+    /// a range over whatever candles exist
+    pub window_complete: bool,
+}
+
+/// Rolling high/low over the last `period` candles, the current one
+/// included. Monotonic queues keep each step amortized O(1), so a 252-day
+/// window costs no more than a short one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollingRange {
+    period: usize,
+    seen: usize,
+    /// (candle index, high), highs decreasing from the front
+    highs: VecDeque<(usize, f64)>,
+    /// (candle index, low), lows increasing from the front
+    lows: VecDeque<(usize, f64)>,
+}
+
+/// Opaque snapshot of a `RollingRange` window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangeState(RollingRange);
+
+impl RollingRange {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            seen: 0,
+            highs: VecDeque::new(),
+            lows: VecDeque::new(),
+        }
+    }
+
+    pub fn calculate(&self, data: &[StockData]) -> Vec<Option<RangeValue>> {
+        let mut range = Self::new(self.period);
+        data.iter().map(|d| range.next(d)).collect()
+    }
+
+    /// Feed one candle; `None` when the period is zero or the range isn't
+    /// positive, where percentages mean nothing
+    pub fn next(&mut self, data: &StockData) -> Option<RangeValue> {
+        if self.period == 0 {
+            return None;
+        }
+        let index = self.seen;
+        self.seen += 1;
+
+        while self.highs.back().is_some_and(|&(_, high)| high <= data.high) {
+            self.highs.pop_back();
+        }
+        self.highs.push_back((index, data.high));
+        while self.lows.back().is_some_and(|&(_, low)| low >= data.low) {
+            self.lows.pop_back();
+        }
+        self.lows.push_back((index, data.low));
+
+        let oldest = (index + 1).saturating_sub(self.period);
+        while self.highs.front().is_some_and(|&(i, _)| i < oldest) {
+            self.highs.pop_front();
+        }
+        while self.lows.front().is_some_and(|&(i, _)| i < oldest) {
+            self.lows.pop_front();
+        }
+
+        let high = self.highs.front()?.1;
+        let low = self.lows.front()?.1;
+        if high <= 0.0 || low <= 0.0 {
+            return None;
+        }
+        Some(RangeValue {
+            high,
+            low,
+            pct_from_high: (high - data.close) / high * 100.0,
+            pct_from_low: (data.close - low) / low * 100.0,
+            window_complete: self.seen >= self.period,
+        })
+    }
+
+    /// Capture the streaming state so processing can resume later
+    pub fn snapshot(&self) -> RangeState {
+        RangeState(self.clone())
+    }
+
+    /// Resume from a snapshot taken with `snapshot`
+    pub fn restore(&mut self, state: &RangeState) {
+        *self = state.0.clone();
+    }
+
+    pub fn reset(&mut self) {
+        self.seen = 0;
+        self.highs.clear();
+        self.lows.clear();
+    }
+}
+
+impl Default for RollingRange {
+    fn default() -> Self {
+        Self::new(DEFAULT_RANGE_WINDOW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration};
+
+    /// Daily candles from (high, low, close) rows
+    fn candles(rows: &[(f64, f64, f64)]) -> Vec<StockData> {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        rows.iter()
+            .enumerate()
+            .map(|(i, &(high, low, close))| StockData {
+                symbol: "TEST".to_string(),
+                timestamp: start + Duration::days(i as i64),
+                open: close,
+                high,
+                low,
+                close,
+                volume: 1_000,
+                adjusted_close: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_high_on_first_bar_rolls_out_of_the_window() {
+        let data = candles(&[(20.0, 18.0, 19.0), (12.0, 10.0, 11.0), (14.0, 11.0, 12.0), (13.0, 12.0, 12.5)]);
+        let values = RollingRange::new(3).calculate(&data);
+
+        assert_eq!(values[2].unwrap().high, 20.0);
+        assert_eq!(values[2].unwrap().pct_from_high, 40.0);
+
+        let last = values[3].unwrap();
+        assert_eq!((last.high, last.low), (14.0, 10.0));
+        assert!(last.window_complete);
+        assert!((last.pct_from_high - (14.0 - 12.5) / 14.0 * 100.0).abs() < 1e-9);
+        assert!((last.pct_from_low - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_monotonic_series() {
+        // Rising: every close sets the high, the low stays at the window's first candle
+        let rising: Vec<_> = (1..=6).map(|i| (i as f64 + 2.0, i as f64, i as f64 + 2.0)).collect();
+        let values = RollingRange::new(3).calculate(&candles(&rising));
+        for (i, value) in values.iter().enumerate() {
+            let value = value.unwrap();
+            assert_eq!(value.pct_from_high, 0.0);
+            assert_eq!(value.low, rising[i.saturating_sub(2)].1);
+        }
+
+        // Falling: every close sets the low
+        let falling: Vec<_> = rising.iter().rev().copied().map(|(high, _, _)| (high, high - 2.0, high - 2.0)).collect();
+        let values = RollingRange::new(3).calculate(&candles(&falling));
+        for (i, value) in values.iter().enumerate() {
+            let value = value.unwrap();
+            assert_eq!(value.pct_from_low, 0.0);
+            assert_eq!(value.high, falling[i.saturating_sub(2)].0);
+        }
+    }
+
+    #[test]
+    fn test_short_series_uses_available_history() {
+        let data = candles(&[(11.0, 9.0, 10.0), (12.0, 10.0, 11.0)]);
+        let values = RollingRange::new(252).calculate(&data);
+        let last = values[1].unwrap();
+        assert_eq!((last.high, last.low), (12.0, 9.0));
+        assert!(!last.window_complete);
+
+        assert_eq!(RollingRange::new(0).calculate(&data), vec![None, None]);
+    }
+
+    #[test]
+    fn test_reset_and_restore() {
+        let data = candles(&[(11.0, 9.0, 10.0), (15.0, 12.0, 14.0), (13.0, 8.0, 9.0), (10.0, 9.5, 9.8)]);
+        let mut range = RollingRange::new(2);
+        range.next(&data[0]);
+        range.next(&data[1]);
+        let state = range.snapshot();
+        let expected = range.next(&data[2]);
+
+        range.reset();
+        assert_eq!(range.next(&data[3]).map(|v| v.high), Some(10.0));
+
+        range.restore(&state);
+        assert_eq!(range.next(&data[2]), expected);
+    }
+}
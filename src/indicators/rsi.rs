@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 /// Custom RSI implementation that matches TradingView's calculation
 /// Uses Wilder's smoothing method (exponential moving average with alpha = 1/period)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CustomRSI {
     period: usize,
     avg_gain: Option<f64>,
@@ -11,6 +13,10 @@ pub struct CustomRSI {
     initial_losses: Vec<f64>,
 }
 
+/// Opaque snapshot of a `CustomRSI`'s accumulators
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RsiState(CustomRSI);
+
 impl CustomRSI {
     pub fn new(period: usize) -> Self {
         Self {
@@ -64,6 +70,16 @@ impl CustomRSI {
         None
     }
 
+    /// Capture the streaming state so processing can resume later
+    pub fn snapshot(&self) -> RsiState {
+        RsiState(self.clone())
+    }
+
+    /// Resume from a snapshot taken with `snapshot`
+    pub fn restore(&mut self, state: &RsiState) {
+        *self = state.0.clone();
+    }
+
     pub fn reset(&mut self) {
         self.avg_gain = None;
         self.avg_loss = None;
@@ -111,4 +127,20 @@ mod tests {
         assert!(result.is_some());
         assert!(result.unwrap() > 0.0 && result.unwrap() <= 100.0);
     }
+
+    #[test]
+    fn test_rsi_snapshot_restore() {
+        let prices = [100.0, 102.0, 101.0, 104.0, 103.0, 106.0, 105.0];
+        let mut full = CustomRSI::new(3);
+        let expected: Vec<_> = prices.iter().map(|p| full.next(*p)).collect();
+
+        let mut partial = CustomRSI::new(3);
+        prices[..4].iter().for_each(|p| { partial.next(*p); });
+        let state = partial.snapshot();
+
+        let mut resumed = CustomRSI::new(3);
+        resumed.restore(&state);
+        let tail: Vec<_> = prices[4..].iter().map(|p| resumed.next(*p)).collect();
+        assert_eq!(tail, expected[4..]);
+    }
 }
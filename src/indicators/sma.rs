@@ -1,26 +1,54 @@
+use serde::{Deserialize, Serialize};
 use ta::indicators::SimpleMovingAverage as TaSimpleMovingAverage;
 use ta::{Next, Reset};
 
 /// Simple Moving Average wrapper
-/// Currently uses the ta crate implementation but can be extended with custom logic
-#[derive(Debug, Clone)]
+/// Uses the ta crate implementation, but reports nothing until a full
+/// window has been seen (the ta crate averages whatever it has so far)
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimpleMovingAverage {
     inner: TaSimpleMovingAverage,
+    period: usize,
+    count: usize,
 }
 
+/// Opaque snapshot of a `SimpleMovingAverage`'s window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmaState(SimpleMovingAverage);
+
 impl SimpleMovingAverage {
     pub fn new(period: usize) -> Result<Self, ta::errors::TaError> {
         Ok(Self {
             inner: TaSimpleMovingAverage::new(period)?,
+            period,
+            count: 0,
         })
     }
 
-    pub fn next(&mut self, input: f64) -> f64 {
-        self.inner.next(input)
+    /// Feed one value; `None` until `period` values have been seen
+    pub fn next(&mut self, input: f64) -> Option<f64> {
+        let value = self.inner.next(input);
+        self.count = (self.count + 1).min(self.period);
+        self.is_warmed_up().then_some(value)
+    }
+
+    pub fn is_warmed_up(&self) -> bool {
+        self.count >= self.period
+    }
+
+    /// Capture the streaming state so processing can resume later
+    pub fn snapshot(&self) -> SmaState {
+        SmaState(self.clone())
+    }
+
+    /// Resume from a snapshot taken with `snapshot`
+    pub fn restore(&mut self, state: &SmaState) {
+        *self = state.0.clone();
     }
 
     pub fn reset(&mut self) {
-        self.inner.reset()
+        self.inner.reset();
+        self.count = 0;
     }
 }
 
@@ -38,11 +66,15 @@ mod tests {
     fn test_sma_calculation() {
         let mut sma = SimpleMovingAverage::new(3).unwrap();
         
-        let result1 = sma.next(10.0);
-        let result2 = sma.next(20.0);
-        let result3 = sma.next(30.0);
+        assert_eq!(sma.next(10.0), None);
+        assert_eq!(sma.next(20.0), None);
+        let result3 = sma.next(30.0).unwrap();
         
         // After 3 values, SMA should be (10 + 20 + 30) / 3 = 20
         assert!((result3 - 20.0).abs() < 0.001);
+        assert!(sma.is_warmed_up());
+
+        sma.reset();
+        assert_eq!(sma.next(40.0), None);
     }
 }
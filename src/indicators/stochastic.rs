@@ -1,63 +1,95 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
 use crate::StockData;
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StochasticOscillator {
     pub k_period: usize,
     pub d_period: usize,
+    ranges: VecDeque<(f64, f64)>, // (high, low)
+    k_values: VecDeque<f64>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StochasticValue {
     pub k_percent: f64,  // Fast stochastic
     pub d_percent: f64,  // Slow stochastic (SMA of %K)
 }
 
+/// Opaque snapshot of a `StochasticOscillator`'s windows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StochasticState(StochasticOscillator);
+
 impl StochasticOscillator {
     pub fn new(k_period: usize, d_period: usize) -> Self {
-        Self { k_period, d_period }
+        Self {
+            k_period,
+            d_period,
+            ranges: VecDeque::with_capacity(k_period),
+            k_values: VecDeque::with_capacity(d_period),
+        }
     }
 
     pub fn calculate(&self, data: &[StockData]) -> Vec<Option<StochasticValue>> {
-        if data.len() < self.k_period {
-            return vec![None; data.len()];
+        let mut oscillator = Self::new(self.k_period, self.d_period);
+        data.iter().map(|d| oscillator.next(d)).collect()
+    }
+
+    /// Feed one candle; `None` until `k_period` candles have been seen
+    pub fn next(&mut self, data: &StockData) -> Option<StochasticValue> {
+        if self.k_period == 0 {
+            return None;
+        }
+        self.ranges.push_back((data.high, data.low));
+        if self.ranges.len() > self.k_period {
+            self.ranges.pop_front();
+        }
+        if self.ranges.len() < self.k_period {
+            return None;
         }
 
-        let mut k_values = Vec::new();
-        let mut results = vec![None; self.k_period - 1];
-
-        // Calculate %K values
-        for i in (self.k_period - 1)..data.len() {
-            let window = &data[i - self.k_period + 1..=i];
-            
-            let highest_high = window.iter().map(|d| d.high).fold(f64::NEG_INFINITY, f64::max);
-            let lowest_low = window.iter().map(|d| d.low).fold(f64::INFINITY, f64::min);
-            let current_close = data[i].close;
-            
-            let k_percent = if highest_high != lowest_low {
-                ((current_close - lowest_low) / (highest_high - lowest_low)) * 100.0
-            } else {
-                50.0
-            };
-            
-            k_values.push(k_percent);
-            
-            // Calculate %D (SMA of %K) if we have enough %K values
-            if k_values.len() >= self.d_period {
-                let d_start = k_values.len() - self.d_period;
-                let d_percent = k_values[d_start..].iter().sum::<f64>() / self.d_period as f64;
-                
-                results.push(Some(StochasticValue {
-                    k_percent,
-                    d_percent,
-                }));
-            } else {
-                results.push(Some(StochasticValue {
-                    k_percent,
-                    d_percent: k_percent, // Use %K as %D until we have enough data
-                }));
-            }
+        let highest_high = self.ranges.iter().map(|r| r.0).fold(f64::NEG_INFINITY, f64::max);
+        let lowest_low = self.ranges.iter().map(|r| r.1).fold(f64::INFINITY, f64::min);
+        let current_close = data.close;
+
+        let k_percent = if highest_high != lowest_low {
+            ((current_close - lowest_low) / (highest_high - lowest_low)) * 100.0
+        } else {
+            50.0
+        };
+
+        self.k_values.push_back(k_percent);
+        if self.k_values.len() > self.d_period {
+            self.k_values.pop_front();
         }
 
-        results
+        // Calculate %D (SMA of %K) if we have enough %K values
+        let d_percent = if self.d_period > 0 && self.k_values.len() >= self.d_period {
+            self.k_values.iter().sum::<f64>() / self.d_period as f64
+        } else {
+            k_percent // Use %K as %D until we have enough data
+        };
+
+        Some(StochasticValue {
+            k_percent,
+            d_percent,
+        })
+    }
+
+    /// Capture the streaming state so processing can resume later
+    pub fn snapshot(&self) -> StochasticState {
+        StochasticState(self.clone())
+    }
+
+    /// Resume from a snapshot taken with `snapshot`
+    pub fn restore(&mut self, state: &StochasticState) {
+        *self = state.0.clone();
+    }
+
+    pub fn reset(&mut self) {
+        self.ranges.clear();
+        self.k_values.clear();
     }
 
     /// Generate trading signals based on Stochastic Oscillator
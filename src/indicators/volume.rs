@@ -0,0 +1,331 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::StockData;
+
+/// On-Balance Volume: a running total that adds the candle's volume on an up
+/// close, subtracts it on a down close and leaves it alone on an unchanged one
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OnBalanceVolume {
+    prev_close: Option<f64>,
+    obv: f64,
+}
+
+/// Opaque snapshot of an `OnBalanceVolume`'s running total
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObvState(OnBalanceVolume);
+
+impl OnBalanceVolume {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn calculate(&self, data: &[StockData]) -> Vec<Option<f64>> {
+        let mut obv = Self::new();
+        data.iter().map(|d| obv.next(d)).collect()
+    }
+
+    /// Feed one candle; the total starts at zero on the first candle
+    pub fn next(&mut self, data: &StockData) -> Option<f64> {
+        if let Some(prev_close) = self.prev_close.replace(data.close) {
+            if data.close > prev_close {
+                self.obv += data.volume as f64;
+            } else if data.close < prev_close {
+                self.obv -= data.volume as f64;
+            }
+        }
+        Some(self.obv)
+    }
+
+    /// Capture the streaming state so processing can resume later
+    pub fn snapshot(&self) -> ObvState {
+        ObvState(self.clone())
+    }
+
+    /// Resume from a snapshot taken with `snapshot`
+    pub fn restore(&mut self, state: &ObvState) {
+        *self = state.0.clone();
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Divergence between price and OBV from the first to the last candle of
+    /// `data` that has an OBV value
+    pub fn generate_signals(&self, data: &[StockData], values: &[Option<f64>]) -> Vec<String> {
+        let mut points = data.iter().zip(values).filter_map(|(d, v)| v.map(|v| (d.close, v)));
+        let (Some((first_close, first_obv)), Some((last_close, last_obv))) = (points.next(), points.next_back()) else {
+            return Vec::new();
+        };
+
+        let mut signals = Vec::new();
+        if last_close > first_close && last_obv < first_obv {
+            signals.push("OBV Bearish Divergence - Price Rising on Falling Volume".to_string());
+        } else if last_close < first_close && last_obv > first_obv {
+            signals.push("OBV Bullish Divergence - Accumulation While Price Falls".to_string());
+        }
+        signals
+    }
+}
+
+/// Volume-weighted average of the typical price (high + low + close) / 3
+/// over the last `period` candles. Daily bars have no session to anchor a
+/// classic VWAP to, so the window rolls instead of resetting each day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Vwap {
+    pub period: usize,
+    /// (typical price × volume, volume) per candle in the window
+    window: VecDeque<(f64, f64)>,
+}
+
+/// Opaque snapshot of a `Vwap` window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VwapState(Vwap);
+
+impl Vwap {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            window: VecDeque::with_capacity(period),
+        }
+    }
+
+    pub fn calculate(&self, data: &[StockData]) -> Vec<Option<f64>> {
+        let mut vwap = Self::new(self.period);
+        data.iter().map(|d| vwap.next(d)).collect()
+    }
+
+    /// Feed one candle; `None` until a full window has been seen, and while
+    /// no volume traded in the window
+    pub fn next(&mut self, data: &StockData) -> Option<f64> {
+        if self.period == 0 {
+            return None;
+        }
+        let typical_price = (data.high + data.low + data.close) / 3.0;
+        let volume = data.volume as f64;
+        self.window.push_back((typical_price * volume, volume));
+        if self.window.len() > self.period {
+            self.window.pop_front();
+        }
+        if self.window.len() < self.period {
+            return None;
+        }
+
+        let (price_volume, volume) = self
+            .window
+            .iter()
+            .fold((0.0, 0.0), |(pv, v), &(candle_pv, candle_v)| (pv + candle_pv, v + candle_v));
+        (volume > 0.0).then(|| price_volume / volume)
+    }
+
+    /// Capture the streaming state so processing can resume later
+    pub fn snapshot(&self) -> VwapState {
+        VwapState(self.clone())
+    }
+
+    /// Resume from a snapshot taken with `snapshot`
+    pub fn restore(&mut self, state: &VwapState) {
+        *self = state.0.clone();
+    }
+
+    pub fn reset(&mut self) {
+        self.window.clear();
+    }
+
+    /// Signals for the close crossing VWAP between consecutive candles
+    pub fn generate_signals(&self, data: &[StockData], values: &[Option<f64>]) -> Vec<String> {
+        let mut signals = Vec::new();
+
+        for i in 1..data.len().min(values.len()) {
+            if let (Some(current), Some(prev)) = (values[i], values[i - 1]) {
+                if data[i - 1].close <= prev && data[i].close > current {
+                    signals.push("Price Crossed Above VWAP - Bullish".to_string());
+                } else if data[i - 1].close >= prev && data[i].close < current {
+                    signals.push("Price Crossed Below VWAP - Bearish".to_string());
+                }
+            }
+        }
+
+        signals
+    }
+}
+
+impl Default for Vwap {
+    fn default() -> Self {
+        Self::new(20)
+    }
+}
+
+/// The candle's volume as a multiple of the average over the `period`
+/// candles before it (2.0 is twice the usual volume)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelativeVolume {
+    pub period: usize,
+    volumes: VecDeque<f64>,
+}
+
+/// Opaque snapshot of a `RelativeVolume` window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelativeVolumeState(RelativeVolume);
+
+impl RelativeVolume {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            volumes: VecDeque::with_capacity(period),
+        }
+    }
+
+    pub fn calculate(&self, data: &[StockData]) -> Vec<Option<f64>> {
+        let mut relative = Self::new(self.period);
+        data.iter().map(|d| relative.next(d)).collect()
+    }
+
+    /// Feed one candle; `None` until `period` earlier candles have been seen,
+    /// and while their average volume is zero
+    pub fn next(&mut self, data: &StockData) -> Option<f64> {
+        if self.period == 0 {
+            return None;
+        }
+        let volume = data.volume as f64;
+        let ratio = if self.volumes.len() == self.period {
+            let average = self.volumes.iter().sum::<f64>() / self.period as f64;
+            (average > 0.0).then(|| volume / average)
+        } else {
+            None
+        };
+
+        self.volumes.push_back(volume);
+        if self.volumes.len() > self.period {
+            self.volumes.pop_front();
+        }
+        ratio
+    }
+
+    /// Capture the streaming state so processing can resume later
+    pub fn snapshot(&self) -> RelativeVolumeState {
+        RelativeVolumeState(self.clone())
+    }
+
+    /// Resume from a snapshot taken with `snapshot`
+    pub fn restore(&mut self, state: &RelativeVolumeState) {
+        *self = state.0.clone();
+    }
+
+    pub fn reset(&mut self) {
+        self.volumes.clear();
+    }
+}
+
+impl Default for RelativeVolume {
+    fn default() -> Self {
+        Self::new(20)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration};
+
+    /// Daily candles from (close, volume) pairs; `day` lets a test skip dates
+    fn candles(rows: &[(i64, f64, u64)]) -> Vec<StockData> {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        rows.iter()
+            .map(|&(day, close, volume)| StockData {
+                symbol: "TEST".to_string(),
+                timestamp: start + Duration::days(day),
+                open: close,
+                high: close + 1.0,
+                low: close - 1.0,
+                close,
+                volume,
+                adjusted_close: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_obv_flat_series_is_constant() {
+        let flat = candles(&[(0, 10.0, 500), (1, 10.0, 800), (2, 10.0, 300), (3, 10.0, 900)]);
+        assert_eq!(OnBalanceVolume::new().calculate(&flat), vec![Some(0.0); 4]);
+
+        let moving = candles(&[(0, 10.0, 500), (1, 11.0, 800), (2, 10.5, 300), (3, 10.5, 900)]);
+        assert_eq!(
+            OnBalanceVolume::new().calculate(&moving),
+            vec![Some(0.0), Some(800.0), Some(500.0), Some(500.0)]
+        );
+    }
+
+    #[test]
+    fn test_gaps_in_data() {
+        // Missing dates and a halted, zero-volume candle
+        let data = candles(&[(0, 10.0, 1_000), (1, 11.0, 1_000), (4, 12.0, 0), (9, 9.0, 2_000)]);
+
+        // OBV only compares each close with the previous candle present
+        assert_eq!(
+            OnBalanceVolume::new().calculate(&data),
+            vec![Some(0.0), Some(1_000.0), Some(1_000.0), Some(-1_000.0)]
+        );
+
+        // The zero-volume candle carries no weight in the VWAP
+        let vwap = Vwap::new(2).calculate(&data);
+        assert_eq!(vwap[0], None);
+        assert_eq!(vwap[1], Some(10.5));
+        assert_eq!(vwap[2], Some(11.0));
+        assert_eq!(vwap[3], Some(9.0));
+
+        // A window with no volume at all has no VWAP
+        let halted = candles(&[(0, 10.0, 0), (1, 11.0, 0), (2, 12.0, 600)]);
+        assert_eq!(Vwap::new(2).calculate(&halted), vec![None, None, Some(12.0)]);
+        assert_eq!(RelativeVolume::new(2).calculate(&halted), vec![None, None, None]);
+    }
+
+    #[test]
+    fn test_relative_volume_compares_with_earlier_candles() {
+        let data = candles(&[(0, 10.0, 100), (1, 10.0, 300), (2, 10.0, 600), (3, 10.0, 450)]);
+        assert_eq!(
+            RelativeVolume::new(2).calculate(&data),
+            vec![None, None, Some(3.0), Some(1.0)]
+        );
+    }
+
+    #[test]
+    fn test_volume_signals() {
+        // Price creeps higher while heavier volume trades on the down days
+        let data = candles(&[(0, 10.0, 100), (1, 9.0, 1_000), (2, 11.0, 100), (3, 10.5, 1_000), (4, 11.5, 100)]);
+        let obv = OnBalanceVolume::new();
+        let signals = obv.generate_signals(&data, &obv.calculate(&data));
+        assert_eq!(signals, vec!["OBV Bearish Divergence - Price Rising on Falling Volume"]);
+
+        let vwap = Vwap::new(2);
+        let values = vwap.calculate(&data);
+        let signals = vwap.generate_signals(&data, &values);
+        assert!(signals.contains(&"Price Crossed Above VWAP - Bullish".to_string()));
+        assert!(signals.contains(&"Price Crossed Below VWAP - Bearish".to_string()));
+    }
+
+    #[test]
+    fn test_reset_and_restore() {
+        let data = candles(&[(0, 10.0, 100), (1, 11.0, 200), (2, 12.0, 300), (3, 11.0, 400)]);
+
+        let mut vwap = Vwap::new(2);
+        let mut obv = OnBalanceVolume::new();
+        for candle in &data[..2] {
+            vwap.next(candle);
+            obv.next(candle);
+        }
+        let (vwap_state, obv_state) = (vwap.snapshot(), obv.snapshot());
+        let expected = (vwap.next(&data[2]), obv.next(&data[2]));
+
+        vwap.reset();
+        obv.reset();
+        assert_eq!(vwap.next(&data[3]), None);
+        assert_eq!(obv.next(&data[3]), Some(0.0));
+
+        vwap.restore(&vwap_state);
+        obv.restore(&obv_state);
+        assert_eq!((vwap.next(&data[2]), obv.next(&data[2])), expected);
+    }
+}
@@ -1,7 +1,59 @@
+pub mod alerts;
+pub mod analysis_plan;
 pub mod analyzer;
+pub mod anomaly;
+pub mod audit;
+pub mod backtest;
 pub mod cache;
+pub mod cli;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod clock;
+pub mod config;
+pub mod corporate_events;
+pub mod crossover_scan;
+pub mod custom_indicators;
+pub mod data_quality;
 pub mod database;
+pub mod delta;
+pub mod enrichment;
+pub mod error;
+pub mod events;
+pub mod health;
+pub mod http;
 pub mod indicators;
+pub mod live_quotes;
+pub mod maintenance;
+pub mod market_hours;
+pub mod message_log;
+pub mod notes;
+pub mod notifications;
+pub mod opportunity;
+pub mod opportunity_state;
+pub mod parsing;
+pub mod providers;
+pub mod rate_limit;
+pub mod region;
+pub mod relative_strength;
+pub mod report;
+pub mod resample;
+pub mod retry;
+pub mod scheduler;
+pub mod sector_stats;
+pub mod session_events;
+pub mod stats;
+pub mod subscriptions;
+pub mod summary;
+pub mod symbol_health;
+pub mod symbol_universe;
+pub mod symbols;
+pub mod ticker_directory;
+pub mod watchlist_csv;
 pub mod web_api;
 
-pub use analyzer::{StockAnalyzer, StockData, TechnicalIndicators, TickerInfo, StockFilter};
+pub use analyzer::{
+    compute_indicators, compute_indicators_with_config, CompositeFilter, Exchange, FilterSpec, HistoryRange, IndicatorConfig, NasdaqScreener, RankingConfig, StockAnalyzer, StockData, StockFilter, TechnicalIndicators, TickerInfo,
+    TickerSortField,
+};
+pub use corporate_events::{AdjustmentPolicy, CorporateEvent};
+pub use error::AnalyzerError;
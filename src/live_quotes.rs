@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::StockData;
+
+/// An intraday quote for a watchlist symbol, broadcast as
+/// `{"type": "quote", "symbol": ..., "price": ..., "change_pct": ...}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiveQuote {
+    pub symbol: String,
+    pub price: f64,
+    /// Percent change from the session's open; `None` without an open
+    pub change_pct: Option<f64>,
+    /// When the upstream priced the quote
+    pub timestamp: DateTime<Utc>,
+}
+
+impl LiveQuote {
+    pub fn from_quote(symbol: &str, quote: &StockData) -> Self {
+        let change_pct = (quote.open.is_finite() && quote.open > 0.0).then(|| (quote.close - quote.open) / quote.open * 100.0);
+        Self {
+            symbol: symbol.to_string(),
+            price: quote.close,
+            change_pct,
+            timestamp: quote.timestamp,
+        }
+    }
+}
+
+/// The latest quote seen for each polled symbol
+#[derive(Debug, Clone, Default)]
+pub struct LiveQuotes {
+    latest: HashMap<String, LiveQuote>,
+}
+
+impl LiveQuotes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `quote` for `symbol`. Returns it when it is worth
+    /// broadcasting: the symbol's first quote, or a new price.
+    pub fn update(&mut self, symbol: &str, quote: &StockData) -> Option<LiveQuote> {
+        let live = LiveQuote::from_quote(symbol, quote);
+        let moved = self.latest.get(symbol).is_none_or(|last| last.price != live.price);
+        self.latest.insert(symbol.to_string(), live.clone());
+        moved.then_some(live)
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&LiveQuote> {
+        self.latest.get(symbol)
+    }
+
+    /// Forget symbols no longer polled, so one re-added later is
+    /// broadcast again from its first quote
+    pub fn retain(&mut self, symbols: &[String]) {
+        self.latest.retain(|symbol, _| symbols.contains(symbol));
+    }
+
+    pub fn len(&self) -> usize {
+        self.latest.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.latest.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn quote(open: f64, price: f64, minute: u32) -> StockData {
+        StockData {
+            symbol: "NVDA".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 4, 15, minute, 0).unwrap(),
+            open,
+            high: price.max(open),
+            low: price.min(open),
+            close: price,
+            volume: 1_000,
+            adjusted_close: None,
+        }
+    }
+
+    #[test]
+    fn test_only_price_moves_are_broadcast() {
+        let mut quotes = LiveQuotes::new();
+        let first = quotes.update("NVDA", &quote(100.0, 102.0, 0)).unwrap();
+        assert_eq!(first.change_pct, Some(2.0));
+
+        // Same price, later time: refreshed but not worth broadcasting
+        assert_eq!(quotes.update("NVDA", &quote(100.0, 102.0, 1)), None);
+        assert_eq!(quotes.get("NVDA").unwrap().timestamp.format("%H:%M").to_string(), "15:01");
+
+        let moved = quotes.update("NVDA", &quote(100.0, 99.0, 2)).unwrap();
+        assert_eq!((moved.price, moved.change_pct), (99.0, Some(-1.0)));
+
+        quotes.retain(&[]);
+        assert!(quotes.is_empty());
+        assert!(quotes.update("NVDA", &quote(100.0, 99.0, 3)).is_some(), "re-added symbols start afresh");
+    }
+
+    #[test]
+    fn test_change_needs_an_open() {
+        assert_eq!(LiveQuote::from_quote("NVDA", &quote(0.0, 5.0, 0)).change_pct, None);
+        let json = serde_json::to_value(LiveQuote::from_quote("NVDA", &quote(50.0, 55.0, 0))).unwrap();
+        assert_eq!(json["change_pct"], 10.0);
+        assert_eq!(json["timestamp"], "2024-03-04T15:00:00Z");
+    }
+}
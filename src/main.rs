@@ -1,9 +1,39 @@
-use anyhow::Result;
-use auto_analyser::{StockAnalyzer, StockFilter};
+use auto_analyser::cli::{self, Cli, CliError, Command};
+use auto_analyser::opportunity::{matching_rules, Comparison, Condition, HistoryRequirements, OpportunityRule, RuleField, RuleInputs};
+use auto_analyser::report::{format_number, OpportunityReport};
+use auto_analyser::{RankingConfig, StockAnalyzer, StockFilter};
+use clap::Parser;
 use priority_queue::PriorityQueue;
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        None => run_default_screen().await,
+        Some(command) => {
+            if matches!(command, Command::Serve(_)) {
+                tracing_subscriber::fmt()
+                    .with_env_filter(
+                        tracing_subscriber::EnvFilter::try_from_default_env()
+                            .unwrap_or_else(|_| "info,auto_analyser=debug".into())
+                    )
+                    .init();
+            }
+            cli::run(command).await
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::from(cli::EXIT_OK),
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+/// The built-in oversold screen: every listed ticker passing
+/// `create_custom_filter`, analyzed most promising first
+async fn run_default_screen() -> Result<(), CliError> {
     println!("🚀 Auto Stock Analyser - Enhanced with Customizable Filtering");
     println!("{}", "=".repeat(70));
 
@@ -12,6 +42,7 @@ async fn main() -> Result<()> {
 
     // Create customizable filters
     let filter = create_custom_filter();
+    let opportunity_rules = create_opportunity_rules(&filter);
     print_filter_settings(&filter);
 
     println!("📡 Fetching ALL available tickers from Nasdaq...");
@@ -28,8 +59,11 @@ async fn main() -> Result<()> {
                 return Ok(());
             }
 
-            // Add high priority tickers to queue
-            let top_performers = StockAnalyzer::get_top_performers(&filtered_tickers, 50);
+            // Add high priority tickers to queue; movers that barely trade
+            // would only waste analysis slots
+            let ranking = RankingConfig::default().with_min_dollar_volume(Some(1_000_000.0));
+            let mut top_performers = StockAnalyzer::rank_tickers(&filtered_tickers, &ranking);
+            top_performers.truncate(50);
             for ticker in &top_performers {
                 prior.push(ticker.symbol.clone(), 10);
             }
@@ -69,9 +103,14 @@ async fn main() -> Result<()> {
                 if let Some(current_indicator) = ticker_indicators.last() {
                     if let Some(current_rsi) = current_indicator.rsi {
                         // Check if stock meets our opportunity criteria
-                        if is_opportunity(&ticker, current_rsi, &stock_data, &filter) {
+                        let pct_change = filtered_tickers.iter().find(|t| t.symbol == ticker).and_then(|t| t.pct_change_f64);
+                        let inputs = RuleInputs::new(stock_data.last(), current_indicator, pct_change)
+                            .with_history(current_indicator.bars_used, &HistoryRequirements::default());
+                        let matched = matching_rules(&opportunity_rules, &inputs);
+                        if !matched.is_empty() {
                             found_opportunities += 1;
                             print_opportunity(&ticker, current_rsi, &stock_data, current_indicator, priority);
+                            println!("   ✅ Matched: {}", matched.join(", "));
                         }
                     }
                 } else {
@@ -89,9 +128,7 @@ async fn main() -> Result<()> {
             println!("✨ Analysis complete!");
             println!("📈 Found {} investment opportunities out of {} analyzed stocks", found_opportunities, analyzed_count);
         }
-        Err(e) => {
-            println!("❌ Error fetching tickers: {}", e);
-        }
+        Err(e) => return Err(CliError::fetch("ticker listing", e)),
     }
 
     println!("\n� Tip: Modify the `create_custom_filter()` function in main.rs to adjust your filtering criteria!");
@@ -139,6 +176,10 @@ fn print_filter_settings(filter: &StockFilter) {
         println!("🌎 Countries: {}", countries.join(", "));
     }
     
+    if filter.exclude_adrs {
+        println!("🌐 Excluding ADRs");
+    }
+    
     if let Some(min_year) = filter.min_ipo_year {
         println!("🎂 IPO Year: {} or later", min_year);
     }
@@ -150,20 +191,18 @@ fn print_filter_settings(filter: &StockFilter) {
     println!("{}", "-".repeat(50));
 }
 
-fn is_opportunity(_ticker: &str, rsi: f64, _stock_data: &[auto_analyser::StockData], filter: &StockFilter) -> bool {
-    // Check if RSI indicates low/oversold condition (good buying opportunity)
+/// Oversold at the filter's threshold, or RSI at or below 40 (a good
+/// buying opportunity either way)
+fn create_opportunity_rules(filter: &StockFilter) -> Vec<OpportunityRule> {
+    let mut rules = Vec::new();
     if let Some(oversold_threshold) = filter.oversold_rsi_threshold {
-        if rsi <= oversold_threshold {
-            return true;
-        }
+        rules.push(OpportunityRule::new(
+            &format!("Oversold (RSI <= {})", oversold_threshold),
+            Condition::new(RuleField::Rsi, Comparison::Le, oversold_threshold),
+        ));
     }
-
-    // Additional check: RSI below 40 is a good opportunity
-    if rsi <= 40.0 {
-        return true;
-    }
-
-    false
+    rules.push(OpportunityRule::new("RSI at or below 40", Condition::new(RuleField::Rsi, Comparison::Le, 40.0)));
+    rules
 }
 
 fn print_opportunity(
@@ -173,39 +212,7 @@ fn print_opportunity(
     indicators: &auto_analyser::TechnicalIndicators,
     priority: i32
 ) {
-    let latest_data = stock_data.last().unwrap();
-    
-    println!("\n🎯 OPPORTUNITY FOUND: {}", ticker);
-    println!("   💰 Current Price: ${:.2}", latest_data.close);
-    println!("   📉 RSI: {:.2}", rsi);
-    println!("   📊 Volume: {}", format_number(latest_data.volume as f64));
-    
-    if let Some(sma_20) = indicators.sma_20 {
-        println!("   📈 SMA(20): ${:.2}", sma_20);
-    }
-    
-    if let Some(sma_50) = indicators.sma_50 {
-        println!("   📈 SMA(50): ${:.2}", sma_50);
-    }
-    
-    // Calculate recent performance
-    if stock_data.len() >= 7 {
-        let week_ago = &stock_data[stock_data.len() - 7];
-        let week_change = ((latest_data.close - week_ago.close) / week_ago.close) * 100.0;
-        println!("   📅 7-Day Change: {:.2}%", week_change);
-    }
-    
-    println!("   ⭐ Priority: {}", priority);
-}
-
-fn format_number(num: f64) -> String {
-    if num >= 1_000_000_000.0 {
-        format!("{:.1}B", num / 1_000_000_000.0)
-    } else if num >= 1_000_000.0 {
-        format!("{:.1}M", num / 1_000_000.0)
-    } else if num >= 1_000.0 {
-        format!("{:.1}K", num / 1_000.0)
-    } else {
-        format!("{:.0}", num)
+    if let Some(report) = OpportunityReport::new(ticker, rsi, stock_data, indicators, priority) {
+        print!("\n{}", report);
     }
 }
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Default for `RetentionPolicy::retention_days`
+pub const DEFAULT_RETENTION_DAYS: u32 = 30;
+/// Default for `RetentionPolicy::full_detail_days`
+pub const DEFAULT_FULL_DETAIL_DAYS: u32 = 7;
+/// Longest `RetentionPolicy::retention_days` allowed, about a century; far
+/// longer windows would put the cutoff before the earliest representable date
+pub const MAX_RETENTION_DAYS: u32 = 36_500;
+/// Default for `RetentionPolicy::sessions_per_day`
+pub const DEFAULT_SESSIONS_PER_DAY: u32 = 4;
+/// Pause between scheduled maintenance runs
+pub const MAINTENANCE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+/// Rows removed per DELETE, so other writers get the database in between
+pub const DELETE_CHUNK_SIZE: u32 = 5_000;
+
+/// How long stored analysis results are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Results older than this many days are deleted
+    pub retention_days: u32,
+    /// Results from the last this many days are all kept; before that only
+    /// the latest `sessions_per_day` sessions of each day are
+    pub full_detail_days: u32,
+    pub sessions_per_day: u32,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            retention_days: DEFAULT_RETENTION_DAYS,
+            full_detail_days: DEFAULT_FULL_DETAIL_DAYS,
+            sessions_per_day: DEFAULT_SESSIONS_PER_DAY,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.retention_days == 0 {
+            return Err("retention_days must be positive".to_string());
+        }
+        if self.retention_days > MAX_RETENTION_DAYS {
+            return Err(format!("retention_days must be at most {}", MAX_RETENTION_DAYS));
+        }
+        if self.full_detail_days > self.retention_days {
+            return Err(format!(
+                "full_detail_days ({}) is greater than retention_days ({})",
+                self.full_detail_days, self.retention_days
+            ));
+        }
+        if self.sessions_per_day == 0 {
+            return Err("sessions_per_day must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    /// Results before the first cutoff are deleted; results between the two
+    /// are thinned to `sessions_per_day`
+    pub fn cutoffs(&self, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        (
+            now - Duration::days(self.retention_days.into()),
+            now - Duration::days(self.full_detail_days.into()),
+        )
+    }
+}
+
+/// What one maintenance run removed and reclaimed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// Results past the retention window
+    pub expired_results: u64,
+    /// Results of sessions beyond the daily limit outside the full-detail window
+    pub thinned_results: u64,
+    /// Database size before the deletes and after `VACUUM`
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+impl MaintenanceReport {
+    pub fn reclaimed_bytes(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_policy_cutoffs_and_validation() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 31, 2, 0, 0).unwrap();
+        let (expired, thinned) = RetentionPolicy::default().cutoffs(now);
+        assert_eq!(expired, Utc.with_ymd_and_hms(2024, 5, 1, 2, 0, 0).unwrap());
+        assert_eq!(thinned, Utc.with_ymd_and_hms(2024, 5, 24, 2, 0, 0).unwrap());
+        RetentionPolicy::default().validate().unwrap();
+
+        let inverted = RetentionPolicy { retention_days: 5, ..RetentionPolicy::default() };
+        assert_eq!(inverted.validate().unwrap_err(), "full_detail_days (7) is greater than retention_days (5)");
+        assert!(RetentionPolicy { sessions_per_day: 0, ..RetentionPolicy::default() }.validate().is_err());
+
+        let endless = RetentionPolicy { retention_days: u32::MAX, ..RetentionPolicy::default() };
+        assert_eq!(endless.validate().unwrap_err(), "retention_days must be at most 36500");
+        let century = RetentionPolicy { retention_days: MAX_RETENTION_DAYS, ..RetentionPolicy::default() };
+        century.validate().unwrap();
+        century.cutoffs(now);
+    }
+}
@@ -0,0 +1,256 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+
+use crate::clock::to_chrono;
+
+/// Regular NYSE/Nasdaq session, US Eastern time
+const SESSION_OPEN: (u32, u32) = (9, 30);
+const SESSION_CLOSE: (u32, u32) = (16, 0);
+
+/// Whether the regular session is trading at `now`
+pub fn is_market_open(now: DateTime<Utc>) -> bool {
+    current_session_close(now).is_some()
+}
+
+/// Start of the first regular session opening after `now`
+pub fn next_market_open(now: DateTime<Utc>) -> DateTime<Utc> {
+    let mut date = to_eastern(now).date();
+    loop {
+        if is_trading_day(date) {
+            let open = session_time(date, SESSION_OPEN);
+            if open > now {
+                return open;
+            }
+        }
+        date = date.succ_opt().expect("date out of range");
+    }
+}
+
+/// When the session trading at `now` closes; `None` while the market is closed
+pub fn current_session_close(now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let date = to_eastern(now).date();
+    if !is_trading_day(date) {
+        return None;
+    }
+    let close = session_time(date, SESSION_CLOSE);
+    (session_time(date, SESSION_OPEN) <= now && now < close).then_some(close)
+}
+
+/// When the continuous loop should start its next cycle after one that ran
+/// from `started_at` to `finished_at`.
+///
+/// While the market trades, cycles repeat every `interval`, plus one as soon
+/// as the session closes so the closing prices are captured. A cycle that
+/// neither started nor finished during a session is that closing snapshot,
+/// and the loop then waits for the next open.
+pub fn next_cycle_start(
+    started_at: DateTime<Utc>,
+    finished_at: DateTime<Utc>,
+    interval: std::time::Duration,
+) -> DateTime<Utc> {
+    let regular = finished_at + to_chrono(interval);
+    match current_session_close(started_at).or_else(|| current_session_close(finished_at)) {
+        Some(close) => regular.min(close.max(finished_at)),
+        None => next_market_open(finished_at),
+    }
+}
+
+/// A weekday the exchanges are not closed for a holiday
+pub fn is_trading_day(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !is_market_holiday(date)
+}
+
+/// Whether the exchanges are closed all day for a holiday on `date`.
+///
+/// Holidays falling on a Saturday are observed the Friday before and those
+/// on a Sunday the Monday after, except that New Year's Day on a Saturday
+/// is not observed at all rather than closing the last day of the year.
+pub fn is_market_holiday(date: NaiveDate) -> bool {
+    let year = date.year();
+    let new_years = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+    let mut holidays = vec![
+        nth_weekday(year, 1, Weekday::Mon, 3),  // Martin Luther King Jr. Day
+        nth_weekday(year, 2, Weekday::Mon, 3),  // Washington's Birthday
+        easter_sunday(year) - Duration::days(2), // Good Friday
+        last_weekday(year, 5, Weekday::Mon),    // Memorial Day
+        observed(NaiveDate::from_ymd_opt(year, 7, 4).unwrap()),
+        nth_weekday(year, 9, Weekday::Mon, 1),  // Labor Day
+        nth_weekday(year, 11, Weekday::Thu, 4), // Thanksgiving
+        observed(NaiveDate::from_ymd_opt(year, 12, 25).unwrap()),
+    ];
+    if new_years.weekday() != Weekday::Sat {
+        holidays.push(observed(new_years));
+    }
+    if year >= 2022 {
+        holidays.push(observed(NaiveDate::from_ymd_opt(year, 6, 19).unwrap()));
+    }
+    holidays.contains(&date)
+}
+
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date + Duration::days(1),
+        _ => date,
+    }
+}
+
+/// The `n`th `weekday` of the month, counting from 1
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).unwrap()
+}
+
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    let next_month = NaiveDate::from_ymd_opt(year, month + 1, 1).unwrap();
+    let mut date = next_month - Duration::days(1);
+    while date.weekday() != weekday {
+        date -= Duration::days(1);
+    }
+    date
+}
+
+/// Gregorian Easter (the anonymous Gregorian algorithm)
+fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).unwrap()
+}
+
+/// Daylight time runs from 2 AM on the second Sunday in March to 2 AM on
+/// the first Sunday in November
+fn is_daylight_time(date: NaiveDate) -> bool {
+    let year = date.year();
+    date >= nth_weekday(year, 3, Weekday::Sun, 2) && date < nth_weekday(year, 11, Weekday::Sun, 1)
+}
+
+/// Local US Eastern time at `now`
+fn to_eastern(now: DateTime<Utc>) -> NaiveDateTime {
+    let standard = now.naive_utc() - Duration::hours(5);
+    let year = standard.year();
+    let daylight_from = nth_weekday(year, 3, Weekday::Sun, 2).and_hms_opt(2, 0, 0).unwrap();
+    // 2 AM daylight time is 1 AM standard time
+    let daylight_until = nth_weekday(year, 11, Weekday::Sun, 1).and_hms_opt(1, 0, 0).unwrap();
+    if standard >= daylight_from && standard < daylight_until {
+        standard + Duration::hours(1)
+    } else {
+        standard
+    }
+}
+
+/// The instant of an Eastern wall-clock time on a trading day; clocks only
+/// change on Sundays, so the offset holds for the whole day
+fn session_time(date: NaiveDate, (hour, minute): (u32, u32)) -> DateTime<Utc> {
+    let offset = if is_daylight_time(date) { 4 } else { 5 };
+    let local = date.and_time(NaiveTime::from_hms_opt(hour, minute, 0).unwrap());
+    Utc.from_utc_datetime(&(local + Duration::hours(offset)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_regular_hours_follow_daylight_time() {
+        // Winter: 9:30-16:00 EST is 14:30-21:00 UTC
+        assert!(!is_market_open(utc(2024, 3, 4, 14, 29)));
+        assert!(is_market_open(utc(2024, 3, 4, 14, 30)));
+        assert!(is_market_open(utc(2024, 3, 4, 20, 59)));
+        assert!(!is_market_open(utc(2024, 3, 4, 21, 0)));
+
+        // Summer: 9:30-16:00 EDT is 13:30-20:00 UTC
+        assert!(is_market_open(utc(2024, 7, 8, 13, 30)));
+        assert!(!is_market_open(utc(2024, 7, 8, 20, 0)));
+        assert_eq!(current_session_close(utc(2024, 7, 8, 15, 0)), Some(utc(2024, 7, 8, 20, 0)));
+
+        // Monday after the switch to daylight time
+        assert!(is_market_open(utc(2024, 3, 11, 13, 30)));
+        // Monday after the switch back
+        assert!(!is_market_open(utc(2024, 11, 4, 14, 0)));
+        assert!(is_market_open(utc(2024, 11, 4, 14, 30)));
+    }
+
+    #[test]
+    fn test_weekends_and_holidays_are_closed() {
+        assert!(!is_market_open(utc(2024, 3, 9, 16, 0)), "Saturday");
+        assert!(!is_market_open(utc(2024, 3, 10, 16, 0)), "Sunday");
+
+        let holidays_2024 = [
+            date(2024, 1, 1),
+            date(2024, 1, 15),
+            date(2024, 2, 19),
+            date(2024, 3, 29),
+            date(2024, 5, 27),
+            date(2024, 6, 19),
+            date(2024, 7, 4),
+            date(2024, 9, 2),
+            date(2024, 11, 28),
+            date(2024, 12, 25),
+        ];
+        for holiday in holidays_2024 {
+            assert!(is_market_holiday(holiday), "{}", holiday);
+        }
+        assert!(is_trading_day(date(2024, 11, 29)), "the day after Thanksgiving trades");
+        assert!(!is_market_holiday(date(2021, 6, 18)), "Juneteenth was first observed in 2022");
+    }
+
+    #[test]
+    fn test_weekend_holidays_are_observed() {
+        // July 4th 2026 is a Saturday; Christmas 2022 a Sunday
+        assert!(is_market_holiday(date(2026, 7, 3)));
+        assert!(is_market_holiday(date(2022, 12, 26)));
+        assert!(is_market_holiday(date(2023, 1, 2)), "New Year's Day 2023 fell on a Sunday");
+
+        // New Year's Day 2022 was a Saturday; the year's last day still traded
+        assert!(is_trading_day(date(2021, 12, 31)));
+        assert!(!is_market_holiday(date(2022, 1, 3)));
+    }
+
+    #[test]
+    fn test_next_market_open() {
+        // Friday evening to Monday morning
+        assert_eq!(next_market_open(utc(2024, 3, 8, 22, 0)), utc(2024, 3, 11, 13, 30));
+        // During a session the next open is the following day's
+        assert_eq!(next_market_open(utc(2024, 3, 4, 15, 0)), utc(2024, 3, 5, 14, 30));
+        // Early morning opens the same day
+        assert_eq!(next_market_open(utc(2024, 3, 5, 3, 0)), utc(2024, 3, 5, 14, 30));
+        // Thursday before Good Friday waits for Monday
+        assert_eq!(next_market_open(utc(2024, 3, 28, 21, 0)), utc(2024, 4, 1, 13, 30));
+    }
+
+    #[test]
+    fn test_next_cycle_start() {
+        let hour = std::time::Duration::from_secs(3600);
+
+        // Mid-session cycles repeat on the interval
+        assert_eq!(next_cycle_start(utc(2024, 3, 4, 15, 0), utc(2024, 3, 4, 15, 10), hour), utc(2024, 3, 4, 16, 10));
+        // The last cycle of the day is pulled forward to the close
+        assert_eq!(next_cycle_start(utc(2024, 3, 4, 20, 0), utc(2024, 3, 4, 20, 10), hour), utc(2024, 3, 4, 21, 0));
+        // A cycle that overran the close is followed straight away by the snapshot
+        assert_eq!(next_cycle_start(utc(2024, 3, 4, 20, 30), utc(2024, 3, 4, 21, 15), hour), utc(2024, 3, 4, 21, 15));
+        // After the snapshot the loop sleeps until the next open
+        assert_eq!(next_cycle_start(utc(2024, 3, 4, 21, 15), utc(2024, 3, 4, 21, 40), hour), utc(2024, 3, 5, 14, 30));
+        // A Saturday start runs once, then waits for Monday
+        assert_eq!(next_cycle_start(utc(2024, 3, 9, 8, 0), utc(2024, 3, 9, 8, 30), hour), utc(2024, 3, 11, 13, 30));
+        // A cycle that ran into the open carries on with the session
+        assert_eq!(next_cycle_start(utc(2024, 3, 5, 14, 0), utc(2024, 3, 5, 14, 45), hour), utc(2024, 3, 5, 15, 45));
+    }
+}
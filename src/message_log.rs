@@ -0,0 +1,240 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+use crate::alerts::AlertTrigger;
+use crate::delta::ResultDelta;
+use crate::live_quotes::LiveQuote;
+use crate::relative_strength::RankedCycle;
+use crate::ticker_directory::DirectoryUpdate;
+use crate::web_api::{AnalysisStatus, StockAnalysisResult};
+
+/// A broadcast message tagged with its position in the stream.
+/// The payload fields are flattened so existing clients keep working.
+#[derive(Debug, Clone, Serialize)]
+pub struct SequencedMessage {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub message: BroadcastMessage,
+}
+
+impl<'de> Deserialize<'de> for SequencedMessage {
+    /// A payload with a `timestamp` of its own, such as a quote or a result,
+    /// repeats the key after the message's, and JSON parsers keep the last
+    /// one. So the payload is read from the whole object, and `timestamp` is
+    /// the payload's when it has one.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(Deserialize)]
+        struct Position {
+            seq: u64,
+            timestamp: DateTime<Utc>,
+        }
+
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let Position { seq, timestamp } = Position::deserialize(&value).map_err(D::Error::custom)?;
+        let message = BroadcastMessage::deserialize(value).map_err(D::Error::custom)?;
+        Ok(SequencedMessage { seq, timestamp, message })
+    }
+}
+
+/// Payload of a broadcast. Status updates keep their original untagged
+/// shape; every other event carries a `type` field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BroadcastMessage {
+    Event(BroadcastEvent),
+    Status(AnalysisStatus),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BroadcastEvent {
+    /// A freshly analyzed result for one symbol
+    TickerUpdate(Box<StockAnalysisResult>),
+    /// A fresh result flagged as an opportunity in a manual session, or
+    /// one the continuous loop just flagged or stopped flagging (see
+    /// `opportunity_state`)
+    Opportunity(Box<StockAnalysisResult>),
+    /// What moved since the ticker's previous stored result; not sent
+    /// when nothing meaningful changed
+    ResultDelta(ResultDelta),
+    AlertTriggered(AlertTrigger),
+    /// A watchlist symbol's intraday price moved since the last poll
+    Quote(LiveQuote),
+    /// Relative strength ranks of a finished continuous cycle
+    Ranked(RankedCycle),
+    /// Symbols a ticker fetch found newly listed, removed or reclassified
+    TickerDirectoryUpdate(DirectoryUpdate),
+}
+
+impl BroadcastMessage {
+    pub fn as_status(&self) -> Option<&AnalysisStatus> {
+        match self {
+            BroadcastMessage::Status(status) => Some(status),
+            BroadcastMessage::Event(_) => None,
+        }
+    }
+}
+
+impl From<AnalysisStatus> for BroadcastMessage {
+    fn from(status: AnalysisStatus) -> Self {
+        BroadcastMessage::Status(status)
+    }
+}
+
+impl From<BroadcastEvent> for BroadcastMessage {
+    fn from(event: BroadcastEvent) -> Self {
+        BroadcastMessage::Event(event)
+    }
+}
+
+/// Bounded, sequenced buffer of recently broadcast messages.
+///
+/// Every published message gets a monotonically increasing sequence number
+/// (starting at 1). Readers can fetch everything after a known sequence and
+/// wait for the buffer to advance without holding any lock across an await.
+pub struct MessageLog {
+    capacity: usize,
+    buffer: Mutex<VecDeque<SequencedMessage>>,
+    latest_seq: watch::Sender<u64>,
+}
+
+impl MessageLog {
+    pub fn new(capacity: usize) -> Self {
+        let (latest_seq, _) = watch::channel(0);
+        Self {
+            capacity: capacity.max(1),
+            buffer: Mutex::new(VecDeque::new()),
+            latest_seq,
+        }
+    }
+
+    /// Append a message, evicting the oldest entry when full
+    pub fn push(&self, message: impl Into<BroadcastMessage>) -> SequencedMessage {
+        let mut buffer = self.buffer.lock().unwrap();
+        let seq = *self.latest_seq.borrow() + 1;
+        let sequenced = SequencedMessage {
+            seq,
+            timestamp: Utc::now(),
+            message: message.into(),
+        };
+        buffer.push_back(sequenced.clone());
+        while buffer.len() > self.capacity {
+            buffer.pop_front();
+        }
+        self.latest_seq.send_replace(seq);
+        sequenced
+    }
+
+    pub fn latest_seq(&self) -> u64 {
+        *self.latest_seq.borrow()
+    }
+
+    /// Messages with `seq > after_seq`, oldest first, at most `limit` of them
+    pub fn since(&self, after_seq: u64, limit: usize) -> Vec<SequencedMessage> {
+        let buffer = self.buffer.lock().unwrap();
+        buffer
+            .iter()
+            .filter(|m| m.seq > after_seq)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Receiver that is notified whenever a new message is pushed
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.latest_seq.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(id: &str) -> AnalysisStatus {
+        AnalysisStatus {
+            session_id: id.to_string(),
+            status: "running".to_string(),
+            progress: 0.0,
+            analyzed_count: 0,
+            total_count: 0,
+            opportunities_found: 0,
+            error_message: None,
+            results: Vec::new(),
+            results_total: 0,
+            estimated_completion: None,
+            remaining_tickers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_sequence_and_eviction() {
+        let log = MessageLog::new(2);
+        log.push(status("a"));
+        log.push(status("b"));
+        log.push(status("c"));
+
+        assert_eq!(log.latest_seq(), 3);
+        let all = log.since(0, 10);
+        assert_eq!(all.iter().map(|m| m.seq).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(log.since(2, 10)[0].message.as_status().unwrap().session_id, "c");
+        assert_eq!(log.since(0, 1).len(), 1);
+    }
+
+    #[test]
+    fn test_flattened_serialization() {
+        let log = MessageLog::new(4);
+        let msg = log.push(status("continuous"));
+        let json = serde_json::to_value(&msg).unwrap();
+
+        assert_eq!(json["seq"], 1);
+        assert_eq!(json["session_id"], "continuous");
+        assert_eq!(json["status"], "running");
+        assert!(json.get("type").is_none());
+    }
+
+    #[test]
+    fn test_events_are_typed() {
+        use crate::alerts::AlertCondition;
+
+        let log = MessageLog::new(4);
+        log.push(status("continuous"));
+        let trigger = AlertTrigger {
+            alert_id: 7,
+            symbol: "AAPL".to_string(),
+            condition_type: AlertCondition::PriceAbove,
+            condition_value: 200.0,
+            observed_value: 201.5,
+            triggered_at: Utc::now(),
+        };
+        let msg = log.push(BroadcastEvent::AlertTriggered(trigger));
+        let json = serde_json::to_value(&msg).unwrap();
+
+        assert_eq!(json["seq"], 2);
+        assert_eq!(json["type"], "alert_triggered");
+        assert_eq!(json["alert_id"], 7);
+        assert_eq!(json["condition_type"], "price_above");
+
+        let parsed: Vec<SequencedMessage> =
+            serde_json::from_value(serde_json::to_value(log.since(0, 10)).unwrap()).unwrap();
+        assert!(parsed[0].message.as_status().is_some());
+        assert!(matches!(parsed[1].message, BroadcastMessage::Event(BroadcastEvent::AlertTriggered(_))));
+    }
+
+    #[test]
+    fn test_payload_timestamp_round_trips() {
+        let quoted_at = Utc::now() - chrono::Duration::minutes(5);
+        let quote = LiveQuote { symbol: "AAPL".to_string(), price: 190.5, change_pct: None, timestamp: quoted_at };
+        let msg = MessageLog::new(4).push(BroadcastEvent::Quote(quote.clone()));
+
+        let parsed: SequencedMessage = serde_json::from_str(&serde_json::to_string(&msg).unwrap()).unwrap();
+        assert_eq!(parsed.seq, 1);
+        assert_eq!(parsed.timestamp, quoted_at);
+        assert!(matches!(parsed.message, BroadcastMessage::Event(BroadcastEvent::Quote(parsed)) if parsed == quote));
+    }
+}
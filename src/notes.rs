@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::symbols::normalize_symbol;
+use crate::web_api::StockAnalysisResult;
+
+/// Longest note accepted, in characters
+pub const MAX_NOTE_LENGTH: usize = 1000;
+
+/// A free-text note on a ticker, shown with its results until it expires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TickerNote {
+    pub id: i64,
+    pub symbol: String,
+    pub note: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// After this the note is no longer shown; never when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl TickerNote {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+/// Body of `POST /api/notes`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewNote {
+    pub symbol: String,
+    pub note: String,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl NewNote {
+    /// Normalize the symbol and check the note is worth keeping
+    pub fn validate(mut self) -> Result<Self, String> {
+        self.symbol = normalize_symbol(&self.symbol);
+        if self.symbol.is_empty() {
+            return Err("symbol must not be empty".to_string());
+        }
+        self.note = validate_note(&self.note)?;
+        Ok(self)
+    }
+}
+
+/// Body of `PUT /api/notes/:id`; replaces the text and the expiry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteUpdate {
+    pub note: String,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl NoteUpdate {
+    pub fn validate(mut self) -> Result<Self, String> {
+        self.note = validate_note(&self.note)?;
+        Ok(self)
+    }
+}
+
+fn validate_note(note: &str) -> Result<String, String> {
+    let note = note.trim();
+    if note.is_empty() {
+        return Err("note must not be empty".to_string());
+    }
+    if note.chars().count() > MAX_NOTE_LENGTH {
+        return Err(format!("note must be at most {} characters", MAX_NOTE_LENGTH));
+    }
+    Ok(note.to_string())
+}
+
+/// The note to show for each symbol at `now`: the most recently updated
+/// one that has not expired
+pub fn active_notes(notes: &[TickerNote], now: DateTime<Utc>) -> HashMap<String, String> {
+    let mut latest: HashMap<&str, &TickerNote> = HashMap::new();
+    for note in notes.iter().filter(|note| note.is_active(now)) {
+        latest
+            .entry(note.symbol.as_str())
+            .and_modify(|kept| {
+                if (note.updated_at, note.id) > (kept.updated_at, kept.id) {
+                    *kept = note;
+                }
+            })
+            .or_insert(note);
+    }
+    latest.into_iter().map(|(symbol, note)| (symbol.to_string(), note.note.clone())).collect()
+}
+
+/// Set each result's `note` from `notes`, clearing notes that are gone
+pub fn attach_notes(results: &mut [StockAnalysisResult], notes: &HashMap<String, String>) {
+    for result in results {
+        result.note = notes.get(&normalize_symbol(&result.ticker)).cloned();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(id: i64, symbol: &str, text: &str, updated_at: &str, expires_at: Option<&str>) -> TickerNote {
+        TickerNote {
+            id,
+            symbol: symbol.to_string(),
+            note: text.to_string(),
+            created_at: updated_at.parse().unwrap(),
+            updated_at: updated_at.parse().unwrap(),
+            expires_at: expires_at.map(|at| at.parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_latest_unexpired_note_wins() {
+        let now = "2024-03-04T21:00:00Z".parse().unwrap();
+        let notes = [
+            note(1, "AAPL", "old", "2024-03-01T00:00:00Z", None),
+            note(2, "AAPL", "earnings next week, skip", "2024-03-02T00:00:00Z", Some("2024-03-10T00:00:00Z")),
+            note(3, "AAPL", "expired", "2024-03-03T00:00:00Z", Some("2024-03-04T00:00:00Z")),
+            note(4, "XOM", "gone", "2024-03-01T00:00:00Z", Some("2024-03-04T21:00:00Z")),
+        ];
+        let active = active_notes(&notes, now);
+        assert_eq!(active.len(), 1);
+        assert_eq!(active["AAPL"], "earnings next week, skip");
+    }
+
+    #[test]
+    fn test_new_note_is_normalized() {
+        let new = NewNote { symbol: " aapl ".to_string(), note: "  skip  ".to_string(), expires_at: None }.validate().unwrap();
+        assert_eq!((new.symbol.as_str(), new.note.as_str()), ("AAPL", "skip"));
+
+        let blank = NewNote { symbol: "AAPL".to_string(), note: " ".to_string(), expires_at: None };
+        assert_eq!(blank.validate().unwrap_err(), "note must not be empty");
+        let long = NoteUpdate { note: "x".repeat(MAX_NOTE_LENGTH + 1), expires_at: None };
+        assert!(long.validate().is_err());
+    }
+}
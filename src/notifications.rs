@@ -0,0 +1,618 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::alerts::AlertTrigger;
+use crate::clock::SharedClock;
+use crate::web_api::StockAnalysisResult;
+
+/// Default for `NotificationConfig::max_per_cycle`
+pub const DEFAULT_MAX_NOTIFICATIONS_PER_CYCLE: usize = 10;
+/// Default port for `SmtpConfig::port` (submission with STARTTLS)
+pub const DEFAULT_SMTP_PORT: u16 = 587;
+/// How long a webhook or SMTP server gets to answer one attempt
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Held notifications listed by name in a digest; the rest are only counted
+const DIGEST_LINES: usize = 20;
+
+/// Why a notification could not be delivered
+#[derive(Debug, thiserror::Error)]
+pub enum NotifyError {
+    #[error("{channel} answered HTTP {status}")]
+    Status { channel: String, status: u16 },
+    #[error("{channel}: {message}")]
+    Transport { channel: String, message: String },
+    #[error("invalid notification settings: {0}")]
+    Config(String),
+}
+
+/// The `[notifications]` config section. Webhook URLs and the SMTP password
+/// are secrets, so `GET /api/config` leaves them out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    /// Slack- or Discord-compatible incoming webhook URLs
+    #[serde(skip_serializing)]
+    pub webhook_urls: Vec<String>,
+    pub smtp: Option<SmtpConfig>,
+    /// Notifications sent individually per continuous cycle; the rest are
+    /// sent as one digest when the cycle ends. 0 sends only digests.
+    pub max_per_cycle: usize,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            webhook_urls: Vec::new(),
+            smtp: None,
+            max_per_cycle: DEFAULT_MAX_NOTIFICATIONS_PER_CYCLE,
+        }
+    }
+}
+
+impl NotificationConfig {
+    /// Whether any channel is configured
+    pub fn is_enabled(&self) -> bool {
+        !self.webhook_urls.is_empty() || self.smtp.is_some()
+    }
+
+    /// One notifier per configured channel
+    pub fn notifiers(&self) -> Result<Vec<Arc<dyn Notifier>>, NotifyError> {
+        let mut notifiers: Vec<Arc<dyn Notifier>> = Vec::new();
+        for url in &self.webhook_urls {
+            notifiers.push(Arc::new(WebhookNotifier::new(url)?));
+        }
+        if let Some(ref smtp) = self.smtp {
+            notifiers.push(Arc::new(SmtpNotifier::new(smtp)?));
+        }
+        Ok(notifiers)
+    }
+}
+
+/// Mail server notifications are sent through
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    /// Upgrade the connection with STARTTLS; only turn this off for a relay
+    /// on a trusted network
+    pub starttls: bool,
+    pub username: Option<String>,
+    #[serde(skip_serializing)]
+    pub password: Option<String>,
+    /// Sender, e.g. `"Auto Analyser <alerts@example.com>"`
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: DEFAULT_SMTP_PORT,
+            starttls: true,
+            username: None,
+            password: None,
+            from: String::new(),
+            to: Vec::new(),
+        }
+    }
+}
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// A ticker became an opportunity
+    Opportunity,
+    /// A ticker stopped being an opportunity
+    OpportunityExited,
+    /// An alert fired
+    Alert,
+    /// Notifications held back once a cycle's budget was spent
+    Digest,
+}
+
+impl NotificationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NotificationKind::Opportunity => "opportunity",
+            NotificationKind::OpportunityExited => "opportunity_exited",
+            NotificationKind::Alert => "alert",
+            NotificationKind::Digest => "digest",
+        }
+    }
+}
+
+impl FromStr for NotificationKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "opportunity" => Ok(NotificationKind::Opportunity),
+            "opportunity_exited" => Ok(NotificationKind::OpportunityExited),
+            "alert" => Ok(NotificationKind::Alert),
+            "digest" => Ok(NotificationKind::Digest),
+            other => Err(format!("unknown notification kind: {}", other)),
+        }
+    }
+}
+
+/// A message for every configured channel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    pub title: String,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Notification {
+    /// `result` has just become an opportunity
+    pub fn opportunity(result: &StockAnalysisResult, now: DateTime<Utc>) -> Self {
+        let price = result.current_price.map_or("n/a".to_string(), |price| format!("${:.2}", price));
+        let rsi = result.rsi.map_or("n/a".to_string(), |rsi| format!("{:.1}", rsi));
+        let mut message = format!("{} at {}, RSI {}", result.name, price, rsi);
+        match result.summary {
+            Some(ref summary) => message = format!("{}\n{}", message, summary),
+            None if !result.signals.is_empty() => message = format!("{}\n{}", message, result.signals.join("; ")),
+            None => {}
+        }
+        Self {
+            kind: NotificationKind::Opportunity,
+            symbol: Some(result.ticker.clone()),
+            title: format!("New opportunity: {}", result.ticker),
+            message,
+            created_at: now,
+        }
+    }
+
+    /// `result` has just stopped being an opportunity
+    pub fn opportunity_exited(result: &StockAnalysisResult, now: DateTime<Utc>) -> Self {
+        let price = result.current_price.map_or("n/a".to_string(), |price| format!("${:.2}", price));
+        let rsi = result.rsi.map_or("n/a".to_string(), |rsi| format!("{:.1}", rsi));
+        let cycles = match result.opportunity_streak {
+            1 => "1 cycle".to_string(),
+            streak => format!("{} cycles", streak),
+        };
+        Self {
+            kind: NotificationKind::OpportunityExited,
+            symbol: Some(result.ticker.clone()),
+            title: format!("No longer an opportunity: {}", result.ticker),
+            message: format!("{} at {}, RSI {}, after {}", result.name, price, rsi, cycles),
+            created_at: now,
+        }
+    }
+
+    pub fn alert(trigger: &AlertTrigger) -> Self {
+        Self {
+            kind: NotificationKind::Alert,
+            symbol: Some(trigger.symbol.clone()),
+            title: format!(
+                "Alert: {} {} {}",
+                trigger.symbol,
+                trigger.condition_type.as_str(),
+                trigger.condition_value
+            ),
+            message: format!("Alert {} fired with {} observed", trigger.alert_id, trigger.observed_value),
+            created_at: trigger.triggered_at,
+        }
+    }
+
+    /// One notification standing in for `held`
+    pub fn digest(held: &[Notification], now: DateTime<Utc>) -> Self {
+        let mut lines: Vec<String> = held.iter().take(DIGEST_LINES).map(|n| n.title.clone()).collect();
+        if held.len() > DIGEST_LINES {
+            lines.push(format!("...and {} more", held.len() - DIGEST_LINES));
+        }
+        Self {
+            kind: NotificationKind::Digest,
+            symbol: None,
+            title: format!("{} more notifications this cycle", held.len()),
+            message: lines.join("\n"),
+            created_at: now,
+        }
+    }
+
+    /// Title and message as one chat message
+    pub fn text(&self) -> String {
+        format!("*{}*\n{}", self.title, self.message)
+    }
+}
+
+/// A channel notifications are delivered through
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Channel recorded in the notification log, e.g. `webhook:hooks.slack.com`
+    fn name(&self) -> &str;
+
+    async fn send(&self, notification: &Notification) -> Result<(), NotifyError>;
+}
+
+/// POSTs `{"text": ..., "content": ...}`, which Slack and Discord incoming
+/// webhooks both accept, along with the notification itself
+pub struct WebhookNotifier {
+    url: String,
+    name: String,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: &str) -> Result<Self, NotifyError> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| NotifyError::Config(format!("webhook URL {:?}: {}", url, e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(NotifyError::Config(format!("webhook URL {:?} must be http or https", url)));
+        }
+        let client = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .build()
+            .map_err(|e| NotifyError::Config(e.to_string()))?;
+        Ok(Self {
+            url: url.to_string(),
+            // The path usually holds the webhook's secret token
+            name: format!("webhook:{}", parsed.host_str().unwrap_or_default()),
+            client,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<(), NotifyError> {
+        let text = notification.text();
+        let body = serde_json::json!({ "text": text, "content": text, "notification": notification });
+        let response = self.client.post(&self.url).json(&body).send().await.map_err(|e| NotifyError::Transport {
+            channel: self.name.clone(),
+            message: e.without_url().to_string(),
+        })?;
+        if !response.status().is_success() {
+            return Err(NotifyError::Status {
+                channel: self.name.clone(),
+                status: response.status().as_u16(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Sends each notification as a plain-text email to every recipient
+pub struct SmtpNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    name: String,
+}
+
+impl SmtpNotifier {
+    pub fn new(config: &SmtpConfig) -> Result<Self, NotifyError> {
+        let invalid = |message: String| NotifyError::Config(format!("smtp: {}", message));
+        if config.host.is_empty() {
+            return Err(invalid("host must not be empty".to_string()));
+        }
+        if config.to.is_empty() {
+            return Err(invalid("at least one recipient is required".to_string()));
+        }
+        let from: Mailbox = config.from.parse().map_err(|e| invalid(format!("from {:?}: {}", config.from, e)))?;
+        let to = config
+            .to
+            .iter()
+            .map(|to| to.parse().map_err(|e| invalid(format!("to {:?}: {}", to, e))))
+            .collect::<Result<Vec<Mailbox>, _>>()?;
+
+        let builder = if config.starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host).map_err(|e| invalid(e.to_string()))?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+        };
+        let mut builder = builder.port(config.port).timeout(Some(DELIVERY_TIMEOUT));
+        if let Some(ref username) = config.username {
+            builder = builder.credentials(Credentials::new(username.clone(), config.password.clone().unwrap_or_default()));
+        }
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            to,
+            name: format!("smtp:{}", config.host),
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for SmtpNotifier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<(), NotifyError> {
+        let transport_error = |message: String| NotifyError::Transport {
+            channel: self.name.clone(),
+            message,
+        };
+        let builder = self.to.iter().cloned().fold(Message::builder().from(self.from.clone()), |builder, to| builder.to(to));
+        let email = builder
+            .subject(&notification.title)
+            .header(ContentType::TEXT_PLAIN)
+            .body(notification.message.clone())
+            .map_err(|e| transport_error(e.to_string()))?;
+        self.transport.send(email).await.map_err(|e| transport_error(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Whether a delivery got through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Sent,
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeliveryStatus::Sent => "sent",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for DeliveryStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "sent" => Ok(DeliveryStatus::Sent),
+            "failed" => Ok(DeliveryStatus::Failed),
+            other => Err(format!("unknown delivery status: {}", other)),
+        }
+    }
+}
+
+/// One notification's delivery through one channel, as kept in the
+/// `notification_log` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Delivery {
+    #[serde(flatten)]
+    pub notification: Notification,
+    pub channel: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    /// The last attempt's error, when it failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub finished_at: DateTime<Utc>,
+}
+
+/// Send `notification` through `notifier`, retrying once if it fails
+pub async fn deliver(notifier: &dyn Notifier, notification: Notification, clock: &SharedClock) -> Delivery {
+    let mut attempts = 0;
+    let error = loop {
+        attempts += 1;
+        match notifier.send(&notification).await {
+            Ok(()) => break None,
+            Err(e) if attempts < 2 => tracing::warn!("Retrying notification through {}: {}", notifier.name(), e),
+            Err(e) => break Some(e.to_string()),
+        }
+    };
+    Delivery {
+        notification,
+        channel: notifier.name().to_string(),
+        status: if error.is_none() { DeliveryStatus::Sent } else { DeliveryStatus::Failed },
+        attempts,
+        error,
+        finished_at: clock.now(),
+    }
+}
+
+/// Caps how many notifications go out individually per continuous cycle;
+/// the overflow is held for a digest when the cycle ends
+#[derive(Debug, Clone, Default)]
+pub struct NotificationBudget {
+    max_per_cycle: usize,
+    sent: usize,
+    held: Vec<Notification>,
+}
+
+impl NotificationBudget {
+    pub fn new(max_per_cycle: usize) -> Self {
+        Self {
+            max_per_cycle,
+            ..Self::default()
+        }
+    }
+
+    /// `notification` back if it may be sent now, otherwise `None` and it
+    /// is held for the digest
+    pub fn admit(&mut self, notification: Notification) -> Option<Notification> {
+        if self.sent < self.max_per_cycle {
+            self.sent += 1;
+            return Some(notification);
+        }
+        self.held.push(notification);
+        None
+    }
+
+    /// The digest of everything held this cycle, if anything was, and a
+    /// fresh budget for the next
+    pub fn end_cycle(&mut self, now: DateTime<Utc>) -> Option<Notification> {
+        self.sent = 0;
+        let held = std::mem::take(&mut self.held);
+        (!held.is_empty()).then(|| Notification::digest(&held, now))
+    }
+
+    pub fn held(&self) -> usize {
+        self.held.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::TestClock;
+    use chrono::TimeZone;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    fn note(symbol: &str) -> Notification {
+        Notification {
+            kind: NotificationKind::Opportunity,
+            symbol: Some(symbol.to_string()),
+            title: format!("New opportunity: {}", symbol),
+            message: String::new(),
+            created_at: Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_budget_holds_overflow_for_a_digest() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 4, 16, 0, 0).unwrap();
+        let mut budget = NotificationBudget::new(2);
+        assert!(budget.admit(note("AAA")).is_some());
+        assert!(budget.admit(note("BBB")).is_some());
+        assert!(budget.admit(note("CCC")).is_none());
+        assert!(budget.admit(note("DDD")).is_none());
+        assert_eq!(budget.held(), 2);
+
+        let digest = budget.end_cycle(now).unwrap();
+        assert_eq!(digest.kind, NotificationKind::Digest);
+        assert_eq!(digest.title, "2 more notifications this cycle");
+        assert_eq!(digest.message, "New opportunity: CCC\nNew opportunity: DDD");
+
+        // The next cycle starts with a full budget and nothing to digest
+        assert!(budget.admit(note("EEE")).is_some());
+        assert_eq!(budget.end_cycle(now), None);
+        assert!(NotificationBudget::new(0).admit(note("AAA")).is_none(), "0 sends only digests");
+    }
+
+    struct Flaky {
+        failures: usize,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Notifier for Flaky {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn send(&self, _: &Notification) -> Result<(), NotifyError> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(NotifyError::Status { channel: "flaky".to_string(), status: 503 });
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delivery_is_retried_once() {
+        let clock: SharedClock = Arc::new(TestClock::new(Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap()));
+        let once = Flaky { failures: 1, calls: AtomicUsize::new(0) };
+        let delivery = deliver(&once, note("AAA"), &clock).await;
+        assert_eq!((delivery.status, delivery.attempts, delivery.error), (DeliveryStatus::Sent, 2, None));
+
+        let down = Flaky { failures: 5, calls: AtomicUsize::new(0) };
+        let delivery = deliver(&down, note("AAA"), &clock).await;
+        assert_eq!((delivery.status, delivery.attempts), (DeliveryStatus::Failed, 2));
+        assert_eq!(delivery.error.as_deref(), Some("flaky answered HTTP 503"));
+        assert_eq!(down.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_posts_chat_text() {
+        let mut server = mockito::Server::new_async().await;
+        let hook = server
+            .mock("POST", "/services/T000/B000/secret")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "text": "*New opportunity: AAA*\n",
+                "content": "*New opportunity: AAA*\n",
+                "notification": { "kind": "opportunity", "symbol": "AAA" },
+            })))
+            .with_status(200)
+            .create_async()
+            .await;
+        let notifier = WebhookNotifier::new(&format!("{}/services/T000/B000/secret", server.url())).unwrap();
+        assert!(!notifier.name().contains("secret"));
+        notifier.send(&note("AAA")).await.unwrap();
+        hook.assert_async().await;
+
+        let missing = WebhookNotifier::new(&format!("{}/gone", server.url())).unwrap();
+        assert!(matches!(missing.send(&note("AAA")).await, Err(NotifyError::Status { status: 501, .. })));
+        assert!(WebhookNotifier::new("ftp://example.com/hook").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_smtp_sends_plain_text_mail() {
+        // Just enough of an SMTP server to accept one message
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut lines = BufReader::new(read).lines();
+            write.write_all(b"220 test ESMTP\r\n").await.unwrap();
+            let mut data = String::new();
+            let mut in_data = false;
+            while let Some(line) = lines.next_line().await.unwrap() {
+                if in_data {
+                    if line == "." {
+                        in_data = false;
+                        write.write_all(b"250 queued\r\n").await.unwrap();
+                    } else {
+                        data.push_str(&line);
+                        data.push('\n');
+                    }
+                    continue;
+                }
+                let reply: &[u8] = match line.get(..4).unwrap_or_default().to_ascii_uppercase().as_str() {
+                    "DATA" => {
+                        in_data = true;
+                        b"354 go ahead\r\n"
+                    }
+                    "QUIT" => {
+                        write.write_all(b"221 bye\r\n").await.unwrap();
+                        break;
+                    }
+                    _ => b"250 ok\r\n",
+                };
+                write.write_all(reply).await.unwrap();
+            }
+            data
+        });
+
+        let notifier = SmtpNotifier::new(&SmtpConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            starttls: false,
+            from: "Auto Analyser <alerts@example.com>".to_string(),
+            to: vec!["me@example.com".to_string()],
+            ..SmtpConfig::default()
+        })
+        .unwrap();
+        assert_eq!(notifier.name(), "smtp:127.0.0.1");
+        let mut notification = note("AAA");
+        notification.message = "RSI 24.0".to_string();
+        notifier.send(&notification).await.unwrap();
+        drop(notifier);
+
+        let data = server.await.unwrap();
+        assert!(data.contains("Subject: New opportunity: AAA"), "{}", data);
+        assert!(data.contains("To: me@example.com"), "{}", data);
+        assert!(data.contains("RSI 24.0"), "{}", data);
+
+        let no_recipients = SmtpConfig { host: "localhost".to_string(), from: "a@example.com".to_string(), ..SmtpConfig::default() };
+        assert!(matches!(SmtpNotifier::new(&no_recipients), Err(NotifyError::Config(_))));
+    }
+}
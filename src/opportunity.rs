@@ -0,0 +1,541 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::events::{CrossoverEvent, CrossoverKind};
+use crate::stats::RsiThresholds;
+use crate::{FilterSpec, StockData, StockFilter, TechnicalIndicators};
+
+/// RSI at or below which the default rules call a stock oversold
+pub const DEFAULT_OVERSOLD_RSI: f64 = 30.0;
+/// RSI at or above which the default rules call a stock overbought
+pub const DEFAULT_OVERBOUGHT_RSI: f64 = 70.0;
+/// Bars of history RSI and SMA50 readings need before they are acted on
+pub const DEFAULT_MIN_RSI_HISTORY_BARS: usize = 60;
+/// Bars of history MACD readings need before they are acted on
+pub const DEFAULT_MIN_MACD_HISTORY_BARS: usize = 35;
+/// Start of the signal listed instead of buy and sell calls for a series
+/// too short to support them
+pub const INSUFFICIENT_HISTORY_SIGNAL: &str = "Insufficient history";
+
+/// How much history a series needs before its indicators count as
+/// evidence. Shorter series, usually recent listings, still get their
+/// readings, but rules and signals leave those readings out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryRequirements {
+    /// Bars needed by RSI and SMA50 based rules, signals and crossovers
+    pub rsi_bars: usize,
+    /// Bars needed by MACD based rules, signals and crossovers
+    pub macd_bars: usize,
+}
+
+impl Default for HistoryRequirements {
+    fn default() -> Self {
+        Self { rsi_bars: DEFAULT_MIN_RSI_HISTORY_BARS, macd_bars: DEFAULT_MIN_MACD_HISTORY_BARS }
+    }
+}
+
+impl HistoryRequirements {
+    /// Whether `bars` of history support RSI and SMA50 readings
+    pub fn supports_rsi(&self, bars: usize) -> bool {
+        bars >= self.rsi_bars
+    }
+
+    /// Whether `bars` of history support MACD readings
+    pub fn supports_macd(&self, bars: usize) -> bool {
+        bars >= self.macd_bars
+    }
+
+    /// Whether `bars` of history support a crossover of `kind`
+    pub fn supports_crossover(&self, kind: CrossoverKind, bars: usize) -> bool {
+        match kind {
+            CrossoverKind::MacdSignal => self.supports_macd(bars),
+            CrossoverKind::Sma20Sma50 | CrossoverKind::Rsi30 | CrossoverKind::Rsi70 | CrossoverKind::PriceSma50 => {
+                self.supports_rsi(bars)
+            }
+        }
+    }
+
+    /// The `crossovers` of `stock_data` whose bar had enough history for
+    /// their kind; `indicators` are aligned with the end of `stock_data`
+    pub fn supported_crossovers(
+        &self,
+        crossovers: Vec<CrossoverEvent>,
+        stock_data: &[StockData],
+        indicators: &[TechnicalIndicators],
+    ) -> Vec<CrossoverEvent> {
+        let offset = stock_data.len().saturating_sub(indicators.len());
+        crossovers
+            .into_iter()
+            .filter(|event| {
+                let index = stock_data.partition_point(|bar| bar.timestamp < event.timestamp);
+                index
+                    .checked_sub(offset)
+                    .and_then(|index| indicators.get(index))
+                    .is_some_and(|indicator| self.supports_crossover(event.kind, indicator.bars_used))
+            })
+            .collect()
+    }
+
+    /// The insufficient history signal, when `bars` fall short of either
+    /// requirement
+    pub fn shortfall_signal(&self, bars: usize) -> Option<String> {
+        let needed = self.rsi_bars.max(self.macd_bars);
+        (bars < needed).then(|| format!("{}: {} of {} bars", INSUFFICIENT_HISTORY_SIGNAL, bars, needed))
+    }
+}
+
+/// Value a rule condition can read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleField {
+    Rsi,
+    Macd,
+    MacdSignal,
+    MacdHistogram,
+    /// Latest close
+    Close,
+    #[serde(rename = "sma_20")]
+    Sma20,
+    #[serde(rename = "sma_50")]
+    Sma50,
+    Vwap,
+    /// The day's percent change from the screener
+    PctChange,
+    Volume,
+    VolumeVsAvg,
+    PctFromHigh,
+    PctFromLow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    Le,
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    Ge,
+}
+
+impl Comparison {
+    fn holds(self, left: f64, right: f64) -> bool {
+        match self {
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+            Comparison::Gt => left > right,
+            Comparison::Ge => left >= right,
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        })
+    }
+}
+
+/// Right-hand side of a condition: a number, or another field such as
+/// `"sma_50"` in `close > sma_50`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Operand {
+    Value(f64),
+    Field(RuleField),
+}
+
+/// `{"field": "rsi", "op": "<", "value": 30}`. A condition on a value the
+/// stock doesn't have (no MACD yet, no screener change) is false.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Condition {
+    pub field: RuleField,
+    pub op: Comparison,
+    pub value: Operand,
+}
+
+impl Condition {
+    pub fn new(field: RuleField, op: Comparison, value: impl Into<Operand>) -> Self {
+        Self { field, op, value: value.into() }
+    }
+
+    pub fn matches(&self, inputs: &RuleInputs) -> bool {
+        let right = match self.value {
+            Operand::Value(value) => Some(value),
+            Operand::Field(field) => inputs.get(field),
+        };
+        match (inputs.get(self.field), right) {
+            (Some(left), Some(right)) => self.op.holds(left, right),
+            _ => false,
+        }
+    }
+}
+
+impl From<f64> for Operand {
+    fn from(value: f64) -> Self {
+        Operand::Value(value)
+    }
+}
+
+impl From<RuleField> for Operand {
+    fn from(field: RuleField) -> Self {
+        Operand::Field(field)
+    }
+}
+
+/// Conditions combined with AND/OR, nested to any depth:
+/// `{"and": [{"field": "rsi", ...}, {"or": [...]}]}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RuleExpr {
+    And { and: Vec<RuleExpr> },
+    Or { or: Vec<RuleExpr> },
+    Condition(Condition),
+}
+
+impl RuleExpr {
+    pub fn matches(&self, inputs: &RuleInputs) -> bool {
+        match self {
+            RuleExpr::And { and } => and.iter().all(|expr| expr.matches(inputs)),
+            RuleExpr::Or { or } => or.iter().any(|expr| expr.matches(inputs)),
+            RuleExpr::Condition(condition) => condition.matches(inputs),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            RuleExpr::And { and: exprs } | RuleExpr::Or { or: exprs } => {
+                if exprs.is_empty() {
+                    return Err("and/or groups must not be empty".to_string());
+                }
+                exprs.iter().try_for_each(RuleExpr::validate)
+            }
+            RuleExpr::Condition(Condition { value: Operand::Value(value), .. }) if !value.is_finite() => {
+                Err("condition values must be finite".to_string())
+            }
+            RuleExpr::Condition(_) => Ok(()),
+        }
+    }
+}
+
+impl From<Condition> for RuleExpr {
+    fn from(condition: Condition) -> Self {
+        RuleExpr::Condition(condition)
+    }
+}
+
+/// A named definition of "opportunity"; a stock is one when any of the
+/// rules it is checked against matches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpportunityRule {
+    pub name: String,
+    pub when: RuleExpr,
+}
+
+impl OpportunityRule {
+    pub fn new(name: &str, when: impl Into<RuleExpr>) -> Self {
+        Self { name: name.to_string(), when: when.into() }
+    }
+
+    /// RSI at or below 30, or at or above 70
+    pub fn defaults() -> Vec<OpportunityRule> {
+        Self::for_filter(&StockFilter::new().into())
+    }
+
+    /// Oversold and overbought rules at each member filter's RSI thresholds
+    /// (30 and 70 where unset), without repeating equal thresholds
+    pub fn for_filter(filter: &FilterSpec) -> Vec<OpportunityRule> {
+        Self::for_symbol(filter, None)
+    }
+
+    /// Like `for_filter`, but members with `adaptive_rsi` set use the
+    /// symbol's `adaptive` thresholds when it has them
+    pub fn for_symbol(filter: &FilterSpec, adaptive: Option<RsiThresholds>) -> Vec<OpportunityRule> {
+        let mut rules: Vec<OpportunityRule> = Vec::new();
+        for member in filter.members() {
+            let RsiThresholds { oversold, overbought } = member_thresholds(member, adaptive);
+            for (label, op, threshold) in [("Oversold", Comparison::Le, oversold), ("Overbought", Comparison::Ge, overbought)] {
+                let rule = Self::new(
+                    &format!("{} (RSI {} {})", label, op, threshold),
+                    Condition::new(RuleField::Rsi, op, threshold),
+                );
+                if !rules.contains(&rule) {
+                    rules.push(rule);
+                }
+            }
+        }
+        rules
+    }
+
+    pub fn matches(&self, inputs: &RuleInputs) -> bool {
+        self.when.matches(inputs)
+    }
+}
+
+/// The thresholds `member` calls a symbol oversold and overbought at
+fn member_thresholds(member: &StockFilter, adaptive: Option<RsiThresholds>) -> RsiThresholds {
+    match adaptive {
+        Some(thresholds) if member.adaptive_rsi => thresholds,
+        _ => RsiThresholds {
+            oversold: member.oversold_rsi_threshold.unwrap_or(DEFAULT_OVERSOLD_RSI),
+            overbought: member.overbought_rsi_threshold.unwrap_or(DEFAULT_OVERBOUGHT_RSI),
+        },
+    }
+}
+
+/// Thresholds the first member of `filter` with `adaptive_rsi` set applied
+/// to a symbol: its `adaptive` ones, or the member's static ones when the
+/// symbol has too little history. `None` when no member is adaptive.
+pub fn rsi_thresholds_used(filter: &FilterSpec, adaptive: Option<RsiThresholds>) -> Option<RsiThresholds> {
+    filter.members().iter().find(|member| member.adaptive_rsi).map(|member| member_thresholds(member, adaptive))
+}
+
+/// Reject unnamed rules, empty AND/OR groups and non-finite values
+pub fn validate_rules(rules: &[OpportunityRule]) -> Result<(), String> {
+    for rule in rules {
+        if rule.name.trim().is_empty() {
+            return Err("opportunity rule names must not be empty".to_string());
+        }
+        rule.when.validate().map_err(|e| format!("opportunity rule '{}': {}", rule.name, e))?;
+    }
+    Ok(())
+}
+
+/// Names of the rules `inputs` match, in rule order. This is the one check
+/// for "opportunity" shared by the CLI and both server loops.
+pub fn matching_rules<'a>(rules: &'a [OpportunityRule], inputs: &RuleInputs) -> Vec<&'a str> {
+    rules.iter().filter(|rule| rule.matches(inputs)).map(|rule| rule.name.as_str()).collect()
+}
+
+/// Signal recording that `name` matched
+pub fn rule_signal(name: &str) -> String {
+    format!("Opportunity rule: {}", name)
+}
+
+/// The values rules read for one stock
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleInputs {
+    pub rsi: Option<f64>,
+    pub macd: Option<f64>,
+    pub macd_signal: Option<f64>,
+    pub macd_histogram: Option<f64>,
+    pub close: Option<f64>,
+    pub sma_20: Option<f64>,
+    pub sma_50: Option<f64>,
+    pub vwap: Option<f64>,
+    pub pct_change: Option<f64>,
+    pub volume: Option<f64>,
+    pub volume_vs_avg: Option<f64>,
+    pub pct_from_high: Option<f64>,
+    pub pct_from_low: Option<f64>,
+}
+
+impl RuleInputs {
+    /// From the latest candle and its indicators, with the screener's
+    /// percent change when known
+    pub fn new(latest: Option<&StockData>, indicators: &TechnicalIndicators, pct_change: Option<f64>) -> Self {
+        Self {
+            rsi: indicators.rsi,
+            macd: indicators.macd.map(|(macd, _, _)| macd),
+            macd_signal: indicators.macd.map(|(_, signal, _)| signal),
+            macd_histogram: indicators.macd.map(|(_, _, histogram)| histogram),
+            close: latest.map(|bar| bar.close),
+            sma_20: indicators.sma_20,
+            sma_50: indicators.sma_50,
+            vwap: indicators.vwap,
+            pct_change,
+            volume: latest.map(|bar| bar.volume as f64),
+            volume_vs_avg: indicators.volume_vs_avg,
+            pct_from_high: indicators.pct_from_high,
+            pct_from_low: indicators.pct_from_low,
+        }
+    }
+
+    /// Without the readings `bars` of history are too few to support, so
+    /// rules on them don't match
+    pub fn with_history(mut self, bars: usize, requirements: &HistoryRequirements) -> Self {
+        if !requirements.supports_rsi(bars) {
+            self.rsi = None;
+            self.sma_50 = None;
+        }
+        if !requirements.supports_macd(bars) {
+            self.macd = None;
+            self.macd_signal = None;
+            self.macd_histogram = None;
+        }
+        self
+    }
+
+    pub fn get(&self, field: RuleField) -> Option<f64> {
+        let value = match field {
+            RuleField::Rsi => self.rsi,
+            RuleField::Macd => self.macd,
+            RuleField::MacdSignal => self.macd_signal,
+            RuleField::MacdHistogram => self.macd_histogram,
+            RuleField::Close => self.close,
+            RuleField::Sma20 => self.sma_20,
+            RuleField::Sma50 => self.sma_50,
+            RuleField::Vwap => self.vwap,
+            RuleField::PctChange => self.pct_change,
+            RuleField::Volume => self.volume,
+            RuleField::VolumeVsAvg => self.volume_vs_avg,
+            RuleField::PctFromHigh => self.pct_from_high,
+            RuleField::PctFromLow => self.pct_from_low,
+        };
+        value.filter(|value| value.is_finite())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CompositeFilter;
+
+    fn inputs() -> RuleInputs {
+        RuleInputs {
+            rsi: Some(28.0),
+            macd_histogram: Some(0.4),
+            close: Some(105.0),
+            sma_50: Some(100.0),
+            pct_change: Some(-6.0),
+            ..RuleInputs::default()
+        }
+    }
+
+    fn rule(json: serde_json::Value) -> OpportunityRule {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_nested_and_or() {
+        // rsi < 30 AND (macd_histogram > 0 OR close > sma_50)
+        let rebound = rule(serde_json::json!({
+            "name": "Oversold rebound",
+            "when": {"and": [
+                {"field": "rsi", "op": "<", "value": 30},
+                {"or": [
+                    {"field": "macd_histogram", "op": ">", "value": 0},
+                    {"field": "close", "op": ">", "value": "sma_50"}
+                ]}
+            ]}
+        }));
+        assert!(rebound.matches(&inputs()));
+
+        let below_average = RuleInputs { close: Some(95.0), macd_histogram: Some(-0.1), ..inputs() };
+        assert!(!rebound.matches(&below_average));
+        let not_oversold = RuleInputs { rsi: Some(45.0), ..inputs() };
+        assert!(!rebound.matches(&not_oversold));
+
+        let selloff = rule(serde_json::json!({
+            "name": "Selloff",
+            "when": {"field": "pct_change", "op": "<", "value": -5}
+        }));
+        let rules = [rebound, selloff];
+        assert_eq!(matching_rules(&rules, &inputs()), ["Oversold rebound", "Selloff"]);
+        assert_eq!(matching_rules(&rules, &not_oversold), ["Selloff"]);
+    }
+
+    #[test]
+    fn test_missing_fields_are_false() {
+        let no_macd = RuleInputs { macd_histogram: None, ..inputs() };
+        let needs_macd = OpportunityRule::new("MACD up", Condition::new(RuleField::MacdHistogram, Comparison::Gt, 0.0));
+        assert!(!needs_macd.matches(&no_macd));
+
+        // A missing field on the right-hand side is false too
+        let no_average = RuleInputs { sma_50: None, ..inputs() };
+        assert!(!Condition::new(RuleField::Close, Comparison::Gt, RuleField::Sma50).matches(&no_average));
+
+        // Inside an OR the other branch can still match; inside an AND it can't
+        let rsi_low = RuleExpr::from(Condition::new(RuleField::Rsi, Comparison::Lt, 30.0));
+        let macd_up = RuleExpr::from(Condition::new(RuleField::MacdHistogram, Comparison::Gt, 0.0));
+        assert!(RuleExpr::Or { or: vec![macd_up.clone(), rsi_low.clone()] }.matches(&no_macd));
+        assert!(!RuleExpr::And { and: vec![macd_up, rsi_low] }.matches(&no_macd));
+
+        let nan_rsi = RuleInputs { rsi: Some(f64::NAN), ..inputs() };
+        assert!(!Condition::new(RuleField::Rsi, Comparison::Lt, 30.0).matches(&nan_rsi));
+    }
+
+    #[test]
+    fn test_default_rules_match_rsi_thresholds() {
+        let rules = OpportunityRule::defaults();
+        let names: Vec<&str> = rules.iter().map(|rule| rule.name.as_str()).collect();
+        assert_eq!(names, ["Oversold (RSI <= 30)", "Overbought (RSI >= 70)"]);
+        for (rsi, expected) in [(30.0, true), (30.5, false), (69.9, false), (70.0, true)] {
+            let inputs = RuleInputs { rsi: Some(rsi), ..RuleInputs::default() };
+            assert_eq!(!matching_rules(&rules, &inputs).is_empty(), expected, "RSI {}", rsi);
+        }
+
+        let composite = FilterSpec::Composite(CompositeFilter {
+            any_of: vec![StockFilter::new().with_rsi_thresholds(Some(25.0), None), StockFilter::new()],
+        });
+        let names: Vec<String> = OpportunityRule::for_filter(&composite).into_iter().map(|rule| rule.name).collect();
+        assert_eq!(names, ["Oversold (RSI <= 25)", "Overbought (RSI >= 70)", "Oversold (RSI <= 30)"]);
+    }
+
+    #[test]
+    fn test_adaptive_members_use_the_symbols_thresholds() {
+        let adaptive = Some(RsiThresholds { oversold: 41.5, overbought: 78.0 });
+        let composite = FilterSpec::Composite(CompositeFilter {
+            any_of: vec![StockFilter::new().with_adaptive_rsi(true), StockFilter::new().with_rsi_thresholds(Some(25.0), None)],
+        });
+        let names: Vec<String> = OpportunityRule::for_symbol(&composite, adaptive).into_iter().map(|rule| rule.name).collect();
+        assert_eq!(names, ["Oversold (RSI <= 41.5)", "Overbought (RSI >= 78)", "Oversold (RSI <= 25)", "Overbought (RSI >= 70)"]);
+        assert_eq!(rsi_thresholds_used(&composite, adaptive), adaptive);
+
+        // Too little history falls back to the member's static thresholds
+        let static_names: Vec<String> = OpportunityRule::for_symbol(&composite, None).into_iter().map(|rule| rule.name).collect();
+        assert_eq!(static_names, ["Oversold (RSI <= 30)", "Overbought (RSI >= 70)", "Oversold (RSI <= 25)"]);
+        assert_eq!(rsi_thresholds_used(&composite, None), Some(RsiThresholds { oversold: 30.0, overbought: 70.0 }));
+
+        // Filters that aren't adaptive ignore the symbol's thresholds
+        let plain = FilterSpec::from(StockFilter::new());
+        assert_eq!(OpportunityRule::for_symbol(&plain, adaptive), OpportunityRule::defaults());
+        assert_eq!(rsi_thresholds_used(&plain, adaptive), None);
+    }
+
+    #[test]
+    fn test_rule_json_and_validation() {
+        let rules = OpportunityRule::defaults();
+        let json = serde_json::to_value(&rules[0]).unwrap();
+        assert_eq!(json, serde_json::json!({"name": "Oversold (RSI <= 30)", "when": {"field": "rsi", "op": "<=", "value": 30.0}}));
+        assert!(validate_rules(&rules).is_ok());
+
+        let empty_group = rule(serde_json::json!({"name": "Nothing", "when": {"or": []}}));
+        assert!(validate_rules(&[empty_group]).unwrap_err().contains("Nothing"));
+        assert!(validate_rules(&[OpportunityRule { name: " ".to_string(), ..rules[0].clone() }]).is_err());
+        assert!(serde_json::from_value::<OpportunityRule>(serde_json::json!({
+            "name": "Typo", "when": {"field": "rsi", "op": "=<", "value": 30}
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_short_history_blanks_rsi_and_macd_readings() {
+        let requirements = HistoryRequirements::default();
+        let inputs = RuleInputs { macd: Some(0.2), macd_signal: Some(0.1), ..inputs() };
+        let rules = OpportunityRule::defaults();
+
+        let thin = inputs.clone().with_history(20, &requirements);
+        assert_eq!((thin.rsi, thin.sma_50, thin.macd_histogram), (None, None, None));
+        assert_eq!(thin.close, Some(105.0), "price and volume readings need no history");
+        assert!(matching_rules(&rules, &thin).is_empty());
+        assert_eq!(requirements.shortfall_signal(20).as_deref(), Some("Insufficient history: 20 of 60 bars"));
+
+        // Enough for MACD, not yet for RSI
+        let partial = inputs.clone().with_history(40, &requirements);
+        assert_eq!((partial.rsi, partial.macd_histogram), (None, Some(0.4)));
+        assert!(requirements.supports_crossover(CrossoverKind::MacdSignal, 40));
+        assert!(!requirements.supports_crossover(CrossoverKind::Sma20Sma50, 40));
+
+        let full = inputs.clone().with_history(200, &requirements);
+        assert_eq!(full, inputs);
+        assert_eq!(matching_rules(&rules, &full), ["Oversold (RSI <= 30)"]);
+        assert_eq!(requirements.shortfall_signal(60), None);
+    }
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::symbols::normalize_symbol;
+use crate::web_api::StockAnalysisResult;
+
+/// Where a result stands in its symbol's run of consecutive opportunity
+/// flags across continuous cycles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpportunityState {
+    /// Flagged now and not in the symbol's previous result
+    New,
+    /// Flagged now and in the previous result too
+    Continuing,
+    /// Flagged in the previous result but not now
+    Exited,
+    /// Neither, or not tracked (manual sessions)
+    #[default]
+    None,
+}
+
+impl OpportunityState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OpportunityState::New => "new",
+            OpportunityState::Continuing => "continuing",
+            OpportunityState::Exited => "exited",
+            OpportunityState::None => "none",
+        }
+    }
+
+    /// Whether the state is a change worth broadcasting and notifying
+    pub fn is_transition(self) -> bool {
+        matches!(self, OpportunityState::New | OpportunityState::Exited)
+    }
+}
+
+impl FromStr for OpportunityState {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "new" => Ok(OpportunityState::New),
+            "continuing" => Ok(OpportunityState::Continuing),
+            "exited" => Ok(OpportunityState::Exited),
+            "none" => Ok(OpportunityState::None),
+            other => Err(format!("unknown opportunity state: {}", other)),
+        }
+    }
+}
+
+/// Opportunity bookkeeping for a symbol the continuous loop has flagged
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpportunityStreak {
+    pub symbol: String,
+    /// When the latest run of flags began; kept after it ends
+    pub started_at: DateTime<Utc>,
+    /// Consecutive results flagged so far; 0 once the run has ended
+    pub streak: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_exited_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Each symbol's run of opportunity flags, so a symbol that stays oversold
+/// for days is announced once rather than every cycle
+#[derive(Debug, Clone, Default)]
+pub struct OpportunityTracker {
+    entries: HashMap<String, OpportunityStreak>,
+}
+
+impl OpportunityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_entries(entries: Vec<OpportunityStreak>) -> Self {
+        Self {
+            entries: entries.into_iter().map(|entry| (entry.symbol.clone(), entry)).collect(),
+        }
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&OpportunityStreak> {
+        self.entries.get(symbol)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Count `result` against its symbol's run and set its
+    /// `opportunity_state`, `opportunity_streak` and `opportunity_since`.
+    /// Returns the updated entry when there is one to store; results of
+    /// symbols that were not and are not flagged leave the tracker alone.
+    pub fn track(&mut self, result: &mut StockAnalysisResult, now: DateTime<Utc>) -> Option<OpportunityStreak> {
+        let symbol = normalize_symbol(&result.ticker);
+        let running = self.entries.get(&symbol).filter(|entry| entry.streak > 0);
+
+        let (state, entry) = match (result.is_opportunity, running) {
+            (true, Some(entry)) => {
+                let entry = OpportunityStreak { streak: entry.streak + 1, updated_at: now, ..entry.clone() };
+                (OpportunityState::Continuing, entry)
+            }
+            (true, None) => {
+                let last_exited_at = self.entries.get(&symbol).and_then(|entry| entry.last_exited_at);
+                let entry = OpportunityStreak { symbol: symbol.clone(), started_at: now, streak: 1, last_exited_at, updated_at: now };
+                (OpportunityState::New, entry)
+            }
+            (false, Some(entry)) => {
+                // The result keeps the length of the run it ends
+                result.opportunity_streak = entry.streak;
+                let entry = OpportunityStreak { streak: 0, last_exited_at: Some(now), updated_at: now, ..entry.clone() };
+                (OpportunityState::Exited, entry)
+            }
+            (false, None) => {
+                result.opportunity_state = OpportunityState::None;
+                result.opportunity_streak = 0;
+                result.opportunity_since = None;
+                return None;
+            }
+        };
+
+        result.opportunity_state = state;
+        if state != OpportunityState::Exited {
+            result.opportunity_streak = entry.streak;
+        }
+        result.opportunity_since = Some(match state {
+            OpportunityState::Exited => now,
+            _ => entry.started_at,
+        });
+        self.entries.insert(symbol, entry.clone());
+        Some(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(is_opportunity: bool) -> StockAnalysisResult {
+        serde_json::from_value(serde_json::json!({
+            "ticker": "aapl", "name": "Apple Inc.", "current_price": 150.0, "rsi": 25.0,
+            "sma_20": null, "sma_50": null, "macd": null, "macd_signal": null, "macd_histogram": null,
+            "volume": null, "pct_change": null, "market_cap": null, "is_opportunity": is_opportunity,
+            "signals": [], "timestamp": "2024-03-04T21:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_runs_are_announced_once() {
+        let mut tracker = OpportunityTracker::new();
+        let at = |hour: u32| format!("2024-03-04T{:02}:00:00Z", hour).parse::<DateTime<Utc>>().unwrap();
+        let mut states = Vec::new();
+        for (hour, flagged) in [(1, false), (2, true), (3, true), (4, true), (5, false), (6, false), (7, true)] {
+            let mut result = result(flagged);
+            tracker.track(&mut result, at(hour));
+            states.push((result.opportunity_state, result.opportunity_streak, result.opportunity_since));
+        }
+
+        use OpportunityState::*;
+        assert_eq!(
+            states,
+            [
+                (None, 0, Option::None),
+                (New, 1, Some(at(2))),
+                (Continuing, 2, Some(at(2))),
+                (Continuing, 3, Some(at(2))),
+                (Exited, 3, Some(at(5))),
+                (None, 0, Option::None),
+                (New, 1, Some(at(7))),
+            ]
+        );
+        let entry = tracker.get("AAPL").unwrap();
+        assert_eq!((entry.started_at, entry.streak, entry.last_exited_at), (at(7), 1, Some(at(5))));
+    }
+}
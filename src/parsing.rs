@@ -0,0 +1,63 @@
+/// The trimmed field, or `None` for the blanks and placeholders Nasdaq
+/// sends when it has no value ("", "N/A", "NA", "--")
+pub fn field_value(value: &str) -> Option<&str> {
+    let value = value.trim();
+    match value {
+        "" | "--" => None,
+        _ if value.eq_ignore_ascii_case("n/a") || value.eq_ignore_ascii_case("na") => None,
+        _ => Some(value),
+    }
+}
+
+/// Parse an optional screener field; placeholders and unparseable values
+/// come back as `None`
+pub fn parse_field<T, E>(value: Option<&str>, parse: impl Fn(&str) -> Result<T, E>) -> Option<T> {
+    value.and_then(field_value).and_then(|s| parse(s).ok())
+}
+
+/// Parse market cap string (e.g., "$1.5B", "$500M", "$1,234.5M") to float
+pub fn parse_market_cap(market_cap_str: &str) -> Result<f64, std::num::ParseFloatError> {
+    let cleaned = market_cap_str.trim().replace(['$', ','], "").to_uppercase();
+
+    if cleaned.ends_with('T') {
+        let num_str = cleaned.trim_end_matches('T');
+        let num: f64 = num_str.parse()?;
+        Ok(num * 1_000_000_000_000.0)
+    } else if cleaned.ends_with('B') {
+        let num_str = cleaned.trim_end_matches('B');
+        let num: f64 = num_str.parse()?;
+        Ok(num * 1_000_000_000.0)
+    } else if cleaned.ends_with('M') {
+        let num_str = cleaned.trim_end_matches('M');
+        let num: f64 = num_str.parse()?;
+        Ok(num * 1_000_000.0)
+    } else if cleaned.ends_with('K') {
+        let num_str = cleaned.trim_end_matches('K');
+        let num: f64 = num_str.parse()?;
+        Ok(num * 1_000.0)
+    } else {
+        cleaned.parse()
+    }
+}
+
+/// Parse price string (e.g., "$123.45") to float
+pub fn parse_price(price_str: &str) -> Result<f64, std::num::ParseFloatError> {
+    let cleaned = price_str.trim().replace(['$', ','], "");
+    cleaned.parse()
+}
+
+/// Parse volume string (e.g., "1,234,567") to u64
+pub fn parse_volume(volume_str: &str) -> Result<u64, std::num::ParseIntError> {
+    let cleaned = volume_str.trim().replace(',', "");
+    cleaned.parse()
+}
+
+/// Parse percentage string (e.g., "2.5%", "-1.25%") to float. Nasdaq's
+/// "unch" (unchanged) reads as zero.
+pub fn parse_percentage(pct_str: &str) -> Result<f64, std::num::ParseFloatError> {
+    let cleaned = pct_str.trim().replace('%', "");
+    if cleaned.eq_ignore_ascii_case("unch") {
+        return Ok(0.0);
+    }
+    cleaned.parse()
+}
@@ -0,0 +1,202 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::yahoo::yahoo_symbol;
+use super::ProviderError;
+use crate::http::HttpClient;
+
+const NAME: &str = "yahoo-quote-summary";
+const DEFAULT_BASE_URL: &str = "https://query2.finance.yahoo.com";
+
+/// Targets implying more upside than this (in percent) are almost always
+/// left over from before a large price move and are flagged as stale
+pub const STALE_TARGET_UPSIDE_PCT: f64 = 300.0;
+
+/// Consensus analyst price target for a symbol
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnalystTarget {
+    pub target_mean_price: f64,
+    pub analyst_count: Option<u32>,
+}
+
+impl AnalystTarget {
+    /// Percent move from `price` to the target (negative when the price is above it)
+    pub fn upside_pct(&self, price: f64) -> Option<f64> {
+        (price > 0.0).then(|| (self.target_mean_price - price) / price * 100.0)
+    }
+}
+
+/// Whether an upside this large points at a stale target rather than a real call
+pub fn is_stale_upside(upside_pct: f64) -> bool {
+    upside_pct > STALE_TARGET_UPSIDE_PCT
+}
+
+/// Source of analyst price targets
+#[async_trait]
+pub trait AnalystTargetSource: Send + Sync {
+    /// `Ok(None)` when the symbol exists but has no analyst coverage
+    async fn analyst_target(&self, symbol: &str) -> Result<Option<AnalystTarget>, ProviderError>;
+}
+
+/// Yahoo quoteSummary `financialData` module
+pub struct YahooAnalystTargets {
+    client: HttpClient,
+    base_url: String,
+}
+
+impl Default for YahooAnalystTargets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl YahooAnalystTargets {
+    pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    /// Point the source at a different host (used by tests)
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            client: HttpClient::default(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Send requests through `client`, e.g. one with a proxy
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+/// Read `targetMeanPrice` from a quoteSummary response.
+///
+/// Small caps often have no coverage: the module is missing or its fields
+/// are empty objects. Both give `Ok(None)`, as does a non-positive target.
+pub fn parse_quote_summary(symbol: &str, body: &str) -> Result<Option<AnalystTarget>, ProviderError> {
+    let json: Value = serde_json::from_str(body).map_err(|e| ProviderError::Parse {
+        provider: NAME.to_string(),
+        message: e.to_string(),
+    })?;
+
+    let summary = &json["quoteSummary"];
+    if let Some(code) = summary["error"]["code"].as_str() {
+        if code.eq_ignore_ascii_case("not found") {
+            return Err(ProviderError::NotFound {
+                provider: NAME.to_string(),
+                symbol: symbol.to_string(),
+            });
+        }
+        return Err(ProviderError::Upstream {
+            provider: NAME.to_string(),
+            message: summary["error"]["description"].as_str().unwrap_or(code).to_string(),
+        });
+    }
+
+    let financial = &summary["result"][0]["financialData"];
+    let raw = |field: &str| financial[field]["raw"].as_f64();
+
+    Ok(raw("targetMeanPrice")
+        .filter(|target| target.is_finite() && *target > 0.0)
+        .map(|target_mean_price| AnalystTarget {
+            target_mean_price,
+            analyst_count: raw("numberOfAnalystOpinions").map(|count| count as u32),
+        }))
+}
+
+#[async_trait]
+impl AnalystTargetSource for YahooAnalystTargets {
+    async fn analyst_target(&self, symbol: &str) -> Result<Option<AnalystTarget>, ProviderError> {
+        let url = format!(
+            "{}/v10/finance/quoteSummary/{}?modules=financialData",
+            self.base_url,
+            yahoo_symbol(symbol),
+        );
+
+        let upstream = |message: String| ProviderError::Upstream {
+            provider: NAME.to_string(),
+            message,
+        };
+        let response = self.client.get(url).send().await.map_err(|e| ProviderError::request(NAME, e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(ProviderError::RateLimited {
+                provider: NAME.to_string(),
+            });
+        }
+        // Yahoo answers unknown symbols with 404 and a quoteSummary error body
+        let body = response.text().await.map_err(|e| ProviderError::request(NAME, e))?;
+        if !status.is_success() && status != reqwest::StatusCode::NOT_FOUND {
+            return Err(upstream(status.to_string()));
+        }
+
+        parse_quote_summary(symbol, &body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_quote_summary_fixture() {
+        let target = parse_quote_summary("AAPL", include_str!("../../tests/fixtures/yahoo_quote_summary.json"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(target.target_mean_price, 210.62);
+        assert_eq!(target.analyst_count, Some(38));
+
+        let upside = target.upside_pct(189.98).unwrap();
+        assert!((upside - 10.864).abs() < 1e-3);
+        assert!(!is_stale_upside(upside));
+    }
+
+    #[test]
+    fn test_parse_quote_summary_without_coverage() {
+        let body = include_str!("../../tests/fixtures/yahoo_quote_summary_no_coverage.json");
+        assert_eq!(parse_quote_summary("TINY", body).unwrap(), None);
+
+        // Module missing entirely
+        let body = r#"{"quoteSummary":{"result":[{}],"error":null}}"#;
+        assert_eq!(parse_quote_summary("TINY", body).unwrap(), None);
+        let body = r#"{"quoteSummary":{"result":[],"error":null}}"#;
+        assert_eq!(parse_quote_summary("TINY", body).unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_quote_summary_errors() {
+        let body = r#"{"quoteSummary":{"result":null,"error":{"code":"Not Found","description":"Quote not found for ticker symbol: NOPE"}}}"#;
+        assert!(matches!(parse_quote_summary("NOPE", body), Err(ProviderError::NotFound { .. })));
+        assert!(matches!(parse_quote_summary("AAPL", "<html>"), Err(ProviderError::Parse { .. })));
+    }
+
+    #[test]
+    fn test_stale_target_flag() {
+        let target = AnalystTarget {
+            target_mean_price: 12.0,
+            analyst_count: Some(1),
+        };
+        assert!(is_stale_upside(target.upside_pct(2.5).unwrap()));
+        assert!(!is_stale_upside(target.upside_pct(3.0).unwrap()));
+        assert_eq!(target.upside_pct(0.0), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_mock_server() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v10/finance/quoteSummary/BRK-B")
+            .match_query(mockito::Matcher::UrlEncoded("modules".into(), "financialData".into()))
+            .with_status(200)
+            .with_body(include_str!("../../tests/fixtures/yahoo_quote_summary.json"))
+            .create_async()
+            .await;
+
+        let source = YahooAnalystTargets::with_base_url(&server.url());
+        let target = source.analyst_target("BRK.B").await.unwrap().unwrap();
+        assert_eq!(target.target_mean_price, 210.62);
+        mock.assert_async().await;
+    }
+}
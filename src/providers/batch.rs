@@ -0,0 +1,205 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::yahoo::yahoo_symbol;
+use super::ProviderError;
+use crate::http::HttpClient;
+use crate::StockData;
+
+const NAME: &str = "yahoo-quote";
+const DEFAULT_BASE_URL: &str = "https://query1.finance.yahoo.com";
+
+/// Source of latest quotes for many symbols in one request
+#[async_trait]
+pub trait BatchQuoteSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Latest quote for each of `symbols` the upstream knows, keyed by the
+    /// symbol as given. Unknown symbols are simply absent.
+    async fn latest_quotes(&self, symbols: &[String]) -> Result<HashMap<String, StockData>, ProviderError>;
+}
+
+/// Yahoo v7 `finance/quote` endpoint
+pub struct YahooBatchQuotes {
+    client: HttpClient,
+    base_url: String,
+}
+
+impl Default for YahooBatchQuotes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl YahooBatchQuotes {
+    pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    /// Point the source at a different host (used by tests)
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            client: HttpClient::default(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Send requests through `client`, e.g. one with a proxy
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+/// Read a v7 quote response into one bar per requested symbol.
+///
+/// Yahoo spells share classes with a dash, so entries are matched back to
+/// `requested` through `yahoo_symbol`. Entries without a market price, or
+/// for symbols that weren't asked for, are skipped.
+pub fn parse_batch_quotes(requested: &[String], body: &str) -> Result<HashMap<String, StockData>, ProviderError> {
+    let json: Value = serde_json::from_str(body).map_err(|e| ProviderError::Parse {
+        provider: NAME.to_string(),
+        message: e.to_string(),
+    })?;
+
+    let response = &json["quoteResponse"];
+    if let Some(error) = response["error"].as_object() {
+        let message = error.get("description").and_then(Value::as_str).unwrap_or("unknown error");
+        return Err(ProviderError::Upstream {
+            provider: NAME.to_string(),
+            message: message.to_string(),
+        });
+    }
+    let Some(entries) = response["result"].as_array() else {
+        return Err(ProviderError::Parse {
+            provider: NAME.to_string(),
+            message: "missing quoteResponse.result".to_string(),
+        });
+    };
+
+    let by_yahoo_symbol: HashMap<String, &String> = requested.iter().map(|s| (yahoo_symbol(s), s)).collect();
+    let mut quotes = HashMap::new();
+    for entry in entries {
+        let Some(symbol) = entry["symbol"].as_str().and_then(|s| by_yahoo_symbol.get(s)) else {
+            continue;
+        };
+        let Some(price) = entry["regularMarketPrice"].as_f64().filter(|p| p.is_finite()) else {
+            continue;
+        };
+        let field = |name: &str| entry[name].as_f64().filter(|v| v.is_finite()).unwrap_or(price);
+
+        quotes.insert(
+            symbol.to_string(),
+            StockData {
+                symbol: symbol.to_string(),
+                timestamp: entry["regularMarketTime"]
+                    .as_i64()
+                    .and_then(|t| DateTime::from_timestamp(t, 0))
+                    .unwrap_or_else(Utc::now),
+                open: field("regularMarketOpen"),
+                high: field("regularMarketDayHigh"),
+                low: field("regularMarketDayLow"),
+                close: price,
+                volume: entry["regularMarketVolume"].as_u64().unwrap_or(0),
+                adjusted_close: None,
+            },
+        );
+    }
+
+    Ok(quotes)
+}
+
+#[async_trait]
+impl BatchQuoteSource for YahooBatchQuotes {
+    fn name(&self) -> &str {
+        NAME
+    }
+
+    async fn latest_quotes(&self, symbols: &[String]) -> Result<HashMap<String, StockData>, ProviderError> {
+        if symbols.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let joined = symbols.iter().map(|s| yahoo_symbol(s)).collect::<Vec<_>>().join(",");
+
+        let upstream = |message: String| ProviderError::Upstream {
+            provider: NAME.to_string(),
+            message,
+        };
+        let response = self
+            .client
+            .get(format!("{}/v7/finance/quote", self.base_url))
+            .query(&[("symbols", joined)])
+            .send()
+            .await
+            .map_err(|e| ProviderError::request(NAME, e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(ProviderError::RateLimited {
+                provider: NAME.to_string(),
+            });
+        }
+        if !status.is_success() {
+            return Err(upstream(status.to_string()));
+        }
+        let body = response.text().await.map_err(|e| ProviderError::request(NAME, e))?;
+
+        parse_batch_quotes(symbols, &body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_batch_fixture() {
+        let requested = symbols(&["AAPL", "BRK.B", "NOPE"]);
+        let quotes = parse_batch_quotes(&requested, include_str!("../../tests/fixtures/yahoo_quote_batch.json")).unwrap();
+
+        assert_eq!(quotes.len(), 2, "unknown symbols are left out");
+        let apple = &quotes["AAPL"];
+        assert_eq!(apple.close, 189.98);
+        assert_eq!(apple.volume, 52_164_500);
+        assert_eq!(apple.high, 190.32);
+        assert_eq!(apple.timestamp, DateTime::from_timestamp(1_704_229_200, 0).unwrap());
+        // Matched back to the symbol as requested
+        assert_eq!(quotes["BRK.B"].symbol, "BRK.B");
+        assert_eq!(quotes["BRK.B"].close, 362.39);
+    }
+
+    #[test]
+    fn test_parse_batch_errors() {
+        let body = r#"{"quoteResponse":{"result":null,"error":{"code":"Bad Request","description":"Missing value for the \"symbols\" argument"}}}"#;
+        assert!(matches!(parse_batch_quotes(&[], body), Err(ProviderError::Upstream { .. })));
+        assert!(matches!(parse_batch_quotes(&[], "<html>"), Err(ProviderError::Parse { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_mock_server() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v7/finance/quote")
+            .match_query(mockito::Matcher::UrlEncoded("symbols".into(), "AAPL,BRK-B,NOPE".into()))
+            .with_status(200)
+            .with_body(include_str!("../../tests/fixtures/yahoo_quote_batch.json"))
+            .create_async()
+            .await;
+
+        let source = YahooBatchQuotes::with_base_url(&server.url());
+        let quotes = source.latest_quotes(&symbols(&["AAPL", "BRK.B", "NOPE"])).await.unwrap();
+        assert_eq!(quotes.len(), 2);
+        mock.assert_async().await;
+
+        let throttled = server.mock("GET", "/v7/finance/quote").match_query(mockito::Matcher::Any).with_status(429).create_async().await;
+        let error = source.latest_quotes(&symbols(&["MSFT"])).await.unwrap_err();
+        assert!(matches!(error, ProviderError::RateLimited { .. }));
+        throttled.assert_async().await;
+    }
+}
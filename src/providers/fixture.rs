@@ -0,0 +1,178 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::{MarketDataProvider, ProviderError, QuoteProvider, QuoteSeries};
+use crate::corporate_events::CorporateEvent;
+use crate::error::{AnalyzerError, Result};
+use crate::{StockData, TickerInfo};
+
+const NAME: &str = "fixture";
+
+/// Tickers and daily bars served from memory, so the analysis pipeline can
+/// run offline. Fixture files hold
+/// `{"tickers": [...], "history": {"AAPL": [bar, ...]}}` and optionally
+/// `"events": {"AAPL": [{"type": "split", "ratio": 4.0, "date": ...}]}`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FixtureProvider {
+    #[serde(default)]
+    tickers: Vec<TickerInfo>,
+    #[serde(default)]
+    history: HashMap<String, Vec<StockData>>,
+    #[serde(default)]
+    events: HashMap<String, Vec<CorporateEvent>>,
+}
+
+impl FixtureProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let fixture: Self = serde_json::from_str(json).map_err(|e| AnalyzerError::Parse(format!("fixture: {}", e)))?;
+        let provider = Self::new().with_tickers(fixture.tickers);
+        let provider = fixture
+            .history
+            .into_iter()
+            .fold(provider, |provider, (symbol, bars)| provider.with_history(&symbol, bars));
+        Ok(fixture
+            .events
+            .into_iter()
+            .fold(provider, |provider, (symbol, events)| provider.with_events(&symbol, events)))
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Listing returned by `fetch_tickers`, in order
+    pub fn with_tickers(mut self, tickers: Vec<TickerInfo>) -> Self {
+        self.tickers = tickers.into_iter().map(TickerInfo::with_parsed_fields).collect();
+        self
+    }
+
+    /// Daily bars for `symbol`, replacing any it had
+    pub fn with_history(mut self, symbol: &str, mut bars: Vec<StockData>) -> Self {
+        bars.sort_by_key(|bar| bar.timestamp);
+        self.history.insert(symbol.to_string(), bars);
+        self
+    }
+
+    /// Splits and dividends of `symbol`, replacing any it had
+    pub fn with_events(mut self, symbol: &str, mut events: Vec<CorporateEvent>) -> Self {
+        events.sort_by_key(CorporateEvent::date);
+        self.events.insert(symbol.to_string(), events);
+        self
+    }
+
+    fn bars(&self, symbol: &str) -> Result<&[StockData], ProviderError> {
+        self.history.get(symbol).map(Vec::as_slice).ok_or_else(|| ProviderError::NotFound {
+            provider: NAME.to_string(),
+            symbol: symbol.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for FixtureProvider {
+    fn name(&self) -> &str {
+        NAME
+    }
+
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<QuoteSeries, ProviderError> {
+        let bars = self.bars(symbol)?;
+        Ok(QuoteSeries {
+            bars: bars
+                .iter()
+                .filter(|bar| bar.timestamp >= start && bar.timestamp <= end)
+                .cloned()
+                .collect(),
+            source: NAME.to_string(),
+            fetched_at: Utc::now(),
+            quality: None,
+            events: self
+                .events
+                .get(symbol)
+                .into_iter()
+                .flatten()
+                .filter(|event| event.date() >= start && event.date() <= end)
+                .copied()
+                .collect(),
+        })
+    }
+
+    /// The last bar, however old
+    async fn latest_quote(&self, symbol: &str) -> Result<StockData, ProviderError> {
+        self.bars(symbol)?.last().cloned().ok_or_else(|| ProviderError::NotFound {
+            provider: NAME.to_string(),
+            symbol: symbol.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl MarketDataProvider for FixtureProvider {
+    async fn fetch_tickers(&self, count: usize) -> Result<Vec<TickerInfo>> {
+        let count = if count == 0 { self.tickers.len() } else { count };
+        Ok(self.tickers.iter().take(count).cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const FIXTURE: &str = r#"{
+        "tickers": [
+            {"symbol": "AAA", "name": "AAA Corp", "last_sale": "$12.50", "net_change": null, "pct_change": "-1.2%",
+             "market_cap": "$1.5B", "country": null, "ipo_year": null, "volume": "1,200", "sector": "Energy", "industry": null},
+            {"symbol": "BBB", "name": "BBB Corp", "last_sale": null, "net_change": null, "pct_change": null,
+             "market_cap": null, "country": null, "ipo_year": null, "volume": null, "sector": null, "industry": null}
+        ],
+        "history": {
+            "AAA": [
+                {"symbol": "AAA", "timestamp": "2024-01-03T00:00:00Z", "open": 11.0, "high": 12.0, "low": 10.5, "close": 11.5, "volume": 900},
+                {"symbol": "AAA", "timestamp": "2024-01-02T00:00:00Z", "open": 10.0, "high": 11.0, "low": 9.5, "close": 10.5, "volume": 800}
+            ]
+        },
+        "events": {
+            "AAA": [{"type": "dividend", "amount": 0.1, "date": "2024-01-03T00:00:00Z"}]
+        }
+    }"#;
+
+    #[tokio::test]
+    async fn test_fixture_serves_history_in_window() {
+        let provider = FixtureProvider::from_json(FIXTURE).unwrap();
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0).unwrap();
+
+        let all = provider.fetch_history("AAA", DateTime::<Utc>::UNIX_EPOCH, day(31)).await.unwrap();
+        assert_eq!(all.bars.iter().map(|bar| bar.close).collect::<Vec<_>>(), [10.5, 11.5]);
+        assert_eq!(all.source, "fixture");
+        let tail = provider.fetch_history("AAA", day(3), day(31)).await.unwrap();
+        assert_eq!(tail.bars.len(), 1);
+        assert_eq!(tail.events, [CorporateEvent::Dividend { amount: 0.1, date: day(3) }]);
+        assert!(provider.fetch_history("AAA", day(1), day(2)).await.unwrap().events.is_empty());
+
+        assert_eq!(provider.latest_quote("AAA").await.unwrap().close, 11.5);
+        assert!(matches!(provider.latest_quote("BBB").await, Err(ProviderError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_fixture_tickers_are_parsed() {
+        let provider = FixtureProvider::from_json(FIXTURE).unwrap();
+        let tickers = provider.fetch_tickers(0).await.unwrap();
+        assert_eq!(tickers.len(), 2);
+        assert_eq!((tickers[0].last_sale_f64, tickers[0].market_cap_f64), (Some(12.5), Some(1.5e9)));
+        assert_eq!(provider.fetch_tickers(1).await.unwrap().len(), 1);
+
+        assert!(matches!(FixtureProvider::from_json("{\"tickers\": 1}"), Err(AnalyzerError::Parse(_))));
+    }
+}
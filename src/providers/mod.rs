@@ -0,0 +1,503 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::analyzer::NasdaqScreener;
+use crate::corporate_events::CorporateEvent;
+use crate::data_quality::DataQuality;
+use crate::http::HttpClient;
+use crate::{StockAnalyzer, StockData, TickerInfo};
+
+pub mod analyst;
+pub mod batch;
+pub mod fixture;
+pub mod stooq;
+pub mod yahoo;
+
+pub use analyst::{AnalystTarget, AnalystTargetSource, YahooAnalystTargets};
+pub use batch::{BatchQuoteSource, YahooBatchQuotes};
+pub use fixture::FixtureProvider;
+pub use stooq::StooqProvider;
+pub use yahoo::YahooProvider;
+
+/// Failure from a quote provider, classified so callers can decide whether
+/// another provider is worth trying
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ProviderError {
+    #[error("{provider} has no data for {symbol}")]
+    NotFound { provider: String, symbol: String },
+    #[error("{provider} rate limited the request")]
+    RateLimited { provider: String },
+    #[error("{provider} request failed: {message}")]
+    Upstream { provider: String, message: String },
+    #[error("{provider} returned an unreadable response: {message}")]
+    Parse { provider: String, message: String },
+    #[error("{provider} timed out: {message}")]
+    Timeout { provider: String, message: String },
+}
+
+impl ProviderError {
+    /// Whether a different provider might succeed. A symbol the provider
+    /// reports as unknown is treated as genuinely missing.
+    pub fn should_fall_back(&self) -> bool {
+        !matches!(self, ProviderError::NotFound { .. })
+    }
+
+    /// Classify a request that failed before a response arrived
+    pub fn request(provider: &str, error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            ProviderError::Timeout { provider: provider.to_string(), message: error.to_string() }
+        } else {
+            ProviderError::Upstream { provider: provider.to_string(), message: error.to_string() }
+        }
+    }
+}
+
+/// Bar size of a fetched series, named with Yahoo's interval codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Interval {
+    #[serde(rename = "15m")]
+    FifteenMinutes,
+    #[serde(rename = "1h")]
+    Hourly,
+    #[default]
+    #[serde(rename = "1d")]
+    Daily,
+    #[serde(rename = "1wk")]
+    Weekly,
+    #[serde(rename = "1mo")]
+    Monthly,
+}
+
+impl Interval {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Interval::FifteenMinutes => "15m",
+            Interval::Hourly => "1h",
+            Interval::Daily => "1d",
+            Interval::Weekly => "1wk",
+            Interval::Monthly => "1mo",
+        }
+    }
+
+    pub fn is_intraday(&self) -> bool {
+        matches!(self, Interval::FifteenMinutes | Interval::Hourly)
+    }
+
+    /// How many days back Yahoo serves bars of this size; `None` when the
+    /// whole history is available
+    pub fn max_lookback_days(&self) -> Option<u32> {
+        match self {
+            Interval::FifteenMinutes => Some(60),
+            Interval::Hourly => Some(730),
+            Interval::Daily | Interval::Weekly | Interval::Monthly => None,
+        }
+    }
+
+    /// First date whose bars of this size can still be fetched at `now`
+    pub fn earliest_start(&self, now: DateTime<Utc>) -> Option<NaiveDate> {
+        self.max_lookback_days()
+            .map(|days| (now - Duration::days(i64::from(days) - 1)).date_naive())
+    }
+
+    /// Check that bars of this size can be fetched for a Yahoo range code
+    /// such as "5d", "1mo" or "max"
+    pub fn validate_range(&self, range: &str) -> Result<(), String> {
+        let days = range_days(range).ok_or_else(|| format!("unknown range {:?}", range))?;
+        match self.max_lookback_days() {
+            Some(max) if days > max => Err(format!(
+                "{} bars only go back {} days; range {} is too long",
+                self, max, range
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Calendar days covered by a Yahoo range code; "max" is unbounded
+pub fn range_days(range: &str) -> Option<u32> {
+    match range {
+        "1d" => Some(1),
+        "5d" => Some(5),
+        "1mo" => Some(31),
+        "3mo" => Some(92),
+        "6mo" => Some(183),
+        "1y" | "ytd" => Some(366),
+        "2y" => Some(730),
+        "5y" => Some(5 * 365 + 1),
+        "10y" => Some(10 * 365 + 2),
+        "max" => Some(u32::MAX),
+        _ => None,
+    }
+}
+
+impl std::fmt::Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Bars for one symbol along with where and when they were fetched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteSeries {
+    pub bars: Vec<StockData>,
+    pub source: String,
+    pub fetched_at: DateTime<Utc>,
+    /// What the analyzer repaired; `None` for series it did not sanitize
+    #[serde(default)]
+    pub quality: Option<DataQuality>,
+    /// Splits and dividends within the bars' dates, oldest first
+    #[serde(default)]
+    pub events: Vec<CorporateEvent>,
+}
+
+/// A source of daily OHLCV history. Implementations handle their own symbol
+/// conventions; callers always pass plain exchange symbols such as `BRK.B`.
+#[async_trait]
+pub trait QuoteProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Daily bars between `start` and `end`, oldest first
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<QuoteSeries, ProviderError>;
+
+    /// Bars of the given size between `start` and `end`, oldest first.
+    /// Providers that only serve daily bars keep the default, which rejects
+    /// any other interval.
+    async fn fetch_history_interval(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: Interval,
+    ) -> Result<QuoteSeries, ProviderError> {
+        match interval {
+            Interval::Daily => self.fetch_history(symbol, start, end).await,
+            _ => Err(ProviderError::Upstream {
+                provider: self.name().to_string(),
+                message: format!("{} bars are not supported", interval),
+            }),
+        }
+    }
+
+    /// Bars of the given size over a Yahoo range code such as "5d", oldest
+    /// first. Defaults to fetching from that many days before now.
+    async fn fetch_range(&self, symbol: &str, range: &str, interval: Interval) -> Result<QuoteSeries, ProviderError> {
+        let end = Utc::now();
+        let start = match range_days(range) {
+            Some(u32::MAX) => DateTime::<Utc>::UNIX_EPOCH,
+            Some(days) => end - Duration::days(i64::from(days)),
+            None => {
+                return Err(ProviderError::Upstream {
+                    provider: self.name().to_string(),
+                    message: format!("unknown range {:?}", range),
+                })
+            }
+        };
+        self.fetch_history_interval(symbol, start, end, interval).await
+    }
+
+    /// Most recent bar. Defaults to the last bar of the past ten days.
+    async fn latest_quote(&self, symbol: &str) -> Result<StockData, ProviderError> {
+        let end = Utc::now();
+        let series = self.fetch_history(symbol, end - Duration::days(10), end).await?;
+        series.bars.into_iter().last().ok_or_else(|| ProviderError::NotFound {
+            provider: self.name().to_string(),
+            symbol: symbol.to_string(),
+        })
+    }
+}
+
+/// Everything the analysis pipeline reads from the market: history and
+/// quotes, plus the listing of tickers to screen. Live providers keep the
+/// default listing from the Nasdaq screener.
+#[async_trait]
+pub trait MarketDataProvider: QuoteProvider {
+    /// Tickers listed on NASDAQ, NYSE and AMEX; a `count` of 0 means no limit
+    async fn fetch_tickers(&self, count: usize) -> crate::error::Result<Vec<TickerInfo>> {
+        StockAnalyzer::fetch_n_tickers(count).await
+    }
+}
+
+impl MarketDataProvider for YahooProvider {}
+
+impl MarketDataProvider for StooqProvider {}
+
+#[async_trait]
+impl MarketDataProvider for FallbackProvider {
+    async fn fetch_tickers(&self, count: usize) -> crate::error::Result<Vec<TickerInfo>> {
+        self.screener.fetch_tickers(count).await
+    }
+}
+
+/// Tries the primary provider and, when it fails for a reason another
+/// provider could avoid (outage, rate limit, bad response), the secondary.
+pub struct FallbackProvider {
+    name: String,
+    primary: Arc<dyn QuoteProvider>,
+    secondary: Arc<dyn QuoteProvider>,
+    screener: NasdaqScreener,
+}
+
+impl FallbackProvider {
+    pub fn new(primary: Arc<dyn QuoteProvider>, secondary: Arc<dyn QuoteProvider>) -> Self {
+        Self {
+            name: format!("{}+{}", primary.name(), secondary.name()),
+            primary,
+            secondary,
+            screener: NasdaqScreener::new(),
+        }
+    }
+
+    /// List tickers through `screener` instead of a default one
+    pub fn with_screener(mut self, screener: NasdaqScreener) -> Self {
+        self.screener = screener;
+        self
+    }
+
+    fn combine(primary: ProviderError, secondary: ProviderError) -> ProviderError {
+        let provider = "fallback".to_string();
+        let message = format!("{}; {}", primary, secondary);
+        match (primary, secondary) {
+            (ProviderError::Timeout { .. }, ProviderError::Timeout { .. }) => ProviderError::Timeout { provider, message },
+            _ => ProviderError::Upstream { provider, message },
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for FallbackProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<QuoteSeries, ProviderError> {
+        match self.primary.fetch_history(symbol, start, end).await {
+            Err(e) if e.should_fall_back() => {
+                tracing::warn!("{}; falling back to {} for {}", e, self.secondary.name(), symbol);
+                self.secondary
+                    .fetch_history(symbol, start, end)
+                    .await
+                    .map_err(|secondary| Self::combine(e, secondary))
+            }
+            result => result,
+        }
+    }
+
+    async fn fetch_history_interval(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: Interval,
+    ) -> Result<QuoteSeries, ProviderError> {
+        match self.primary.fetch_history_interval(symbol, start, end, interval).await {
+            Err(e) if e.should_fall_back() => {
+                tracing::warn!("{}; falling back to {} for {}", e, self.secondary.name(), symbol);
+                self.secondary
+                    .fetch_history_interval(symbol, start, end, interval)
+                    .await
+                    .map_err(|secondary| Self::combine(e, secondary))
+            }
+            result => result,
+        }
+    }
+
+    async fn fetch_range(&self, symbol: &str, range: &str, interval: Interval) -> Result<QuoteSeries, ProviderError> {
+        match self.primary.fetch_range(symbol, range, interval).await {
+            Err(e) if e.should_fall_back() => {
+                tracing::warn!("{}; falling back to {} for {}", e, self.secondary.name(), symbol);
+                self.secondary
+                    .fetch_range(symbol, range, interval)
+                    .await
+                    .map_err(|secondary| Self::combine(e, secondary))
+            }
+            result => result,
+        }
+    }
+
+    async fn latest_quote(&self, symbol: &str) -> Result<StockData, ProviderError> {
+        match self.primary.latest_quote(symbol).await {
+            Err(e) if e.should_fall_back() => {
+                tracing::warn!("{}; falling back to {} for {}", e, self.secondary.name(), symbol);
+                self.secondary
+                    .latest_quote(symbol)
+                    .await
+                    .map_err(|secondary| Self::combine(e, secondary))
+            }
+            result => result,
+        }
+    }
+}
+
+/// Yahoo Finance backed by Stooq, with Nasdaq screener listings
+pub fn default_provider() -> anyhow::Result<Arc<dyn MarketDataProvider>> {
+    default_provider_with(HttpClient::default())
+}
+
+/// `default_provider` sending every request through `client`
+pub fn default_provider_with(client: HttpClient) -> anyhow::Result<Arc<dyn MarketDataProvider>> {
+    let provider = FallbackProvider::new(
+        Arc::new(YahooProvider::new()?.with_http_client(client.clone())),
+        Arc::new(StooqProvider::new().with_http_client(client.clone())),
+    );
+    Ok(Arc::new(provider.with_screener(NasdaqScreener::new().with_http_client(client))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockProvider {
+        name: &'static str,
+        result: Result<Vec<StockData>, ProviderError>,
+        calls: AtomicUsize,
+    }
+
+    impl MockProvider {
+        fn new(name: &'static str, result: Result<Vec<StockData>, ProviderError>) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                result,
+                calls: AtomicUsize::new(0),
+            })
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl QuoteProvider for MockProvider {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn fetch_history(
+            &self,
+            _symbol: &str,
+            _start: DateTime<Utc>,
+            _end: DateTime<Utc>,
+        ) -> Result<QuoteSeries, ProviderError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.result.clone().map(|bars| QuoteSeries {
+                bars,
+                source: self.name.to_string(),
+                fetched_at: Utc::now(),
+                quality: None,
+                events: Vec::new(),
+            })
+        }
+    }
+
+    fn bar(close: f64) -> StockData {
+        StockData {
+            symbol: "TEST".to_string(),
+            timestamp: Utc::now(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 100,
+            adjusted_close: None,
+        }
+    }
+
+    fn upstream(provider: &str) -> ProviderError {
+        ProviderError::Upstream {
+            provider: provider.to_string(),
+            message: "503 Service Unavailable".to_string(),
+        }
+    }
+
+    async fn fetch(provider: &FallbackProvider) -> Result<QuoteSeries, ProviderError> {
+        provider.fetch_history("TEST", Utc::now() - Duration::days(5), Utc::now()).await
+    }
+
+    #[tokio::test]
+    async fn test_primary_success_skips_secondary() {
+        let primary = MockProvider::new("yahoo", Ok(vec![bar(1.0)]));
+        let secondary = MockProvider::new("stooq", Ok(vec![bar(2.0)]));
+        let fallback = FallbackProvider::new(primary.clone(), secondary.clone());
+
+        let series = fetch(&fallback).await.unwrap();
+        assert_eq!(series.source, "yahoo");
+        assert_eq!(series.bars[0].close, 1.0);
+        assert_eq!(secondary.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_upstream_failures_fall_back() {
+        for error in [
+            upstream("yahoo"),
+            ProviderError::RateLimited { provider: "yahoo".to_string() },
+            ProviderError::Parse { provider: "yahoo".to_string(), message: "bad json".to_string() },
+        ] {
+            let primary = MockProvider::new("yahoo", Err(error));
+            let secondary = MockProvider::new("stooq", Ok(vec![bar(2.0)]));
+            let fallback = FallbackProvider::new(primary.clone(), secondary.clone());
+
+            let series = fetch(&fallback).await.unwrap();
+            assert_eq!(series.source, "stooq");
+            assert_eq!(primary.calls(), 1);
+            assert_eq!(secondary.calls(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_not_found_does_not_fall_back() {
+        let primary = MockProvider::new(
+            "yahoo",
+            Err(ProviderError::NotFound { provider: "yahoo".to_string(), symbol: "TEST".to_string() }),
+        );
+        let secondary = MockProvider::new("stooq", Ok(vec![bar(2.0)]));
+        let fallback = FallbackProvider::new(primary, secondary.clone());
+
+        assert!(matches!(fetch(&fallback).await, Err(ProviderError::NotFound { .. })));
+        assert_eq!(secondary.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_both_failing_reports_both_errors() {
+        let primary = MockProvider::new("yahoo", Err(upstream("yahoo")));
+        let secondary = MockProvider::new("stooq", Err(upstream("stooq")));
+        let fallback = FallbackProvider::new(primary, secondary);
+
+        let message = fetch(&fallback).await.unwrap_err().to_string();
+        assert!(message.contains("yahoo") && message.contains("stooq"), "{}", message);
+        assert_eq!(fallback.name(), "yahoo+stooq");
+    }
+
+    #[tokio::test]
+    async fn test_default_interval_support_is_daily_only() {
+        let provider = MockProvider::new("mock", Ok(vec![bar(1.0)]));
+        let (start, end) = (Utc::now() - Duration::days(5), Utc::now());
+
+        assert!(provider.fetch_history_interval("TEST", start, end, Interval::Daily).await.is_ok());
+        let err = provider
+            .fetch_history_interval("TEST", start, end, Interval::Weekly)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "mock request failed: 1wk bars are not supported");
+        assert_eq!(provider.calls(), 1);
+        assert_eq!(serde_json::from_str::<Interval>("\"1mo\"").unwrap(), Interval::Monthly);
+    }
+
+    #[tokio::test]
+    async fn test_default_latest_quote_uses_last_bar() {
+        let provider = MockProvider::new("mock", Ok(vec![bar(1.0), bar(3.0)]));
+        assert_eq!(provider.latest_quote("TEST").await.unwrap().close, 3.0);
+    }
+}
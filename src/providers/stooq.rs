@@ -0,0 +1,288 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+
+use super::{Interval, ProviderError, QuoteProvider, QuoteSeries};
+use crate::http::HttpClient;
+use crate::StockData;
+
+const NAME: &str = "stooq";
+const DEFAULT_BASE_URL: &str = "https://stooq.com";
+
+/// Stooq's keyless CSV download (daily, weekly or monthly bars)
+pub struct StooqProvider {
+    client: HttpClient,
+    base_url: String,
+}
+
+impl Default for StooqProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StooqProvider {
+    pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    /// Point the provider at a different host (used by tests)
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            client: HttpClient::default(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Send requests through `client`, e.g. one with a proxy
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+/// Stooq symbols are lowercase with a market suffix (`aapl.us`), and share
+/// classes use a dash (`brk-b.us`)
+pub fn stooq_symbol(symbol: &str) -> String {
+    let symbol = symbol.trim().to_lowercase();
+    if symbol.ends_with(".us") {
+        return symbol;
+    }
+    format!("{}.us", symbol.replace(['.', '/'], "-"))
+}
+
+/// Parse a Stooq daily CSV (`Date,Open,High,Low,Close,Volume`) into bars,
+/// oldest first. Index series have no volume column; their volume is 0.
+pub fn parse_csv(symbol: &str, body: &str) -> Result<Vec<StockData>, ProviderError> {
+    let parse_error = |message: String| ProviderError::Parse {
+        provider: NAME.to_string(),
+        message,
+    };
+
+    let mut lines = body.lines().map(str::trim).filter(|line| !line.is_empty());
+    let header = match lines.next() {
+        Some(header) => header,
+        None => {
+            return Err(ProviderError::NotFound {
+                provider: NAME.to_string(),
+                symbol: symbol.to_string(),
+            })
+        }
+    };
+
+    if header.eq_ignore_ascii_case("no data") {
+        return Err(ProviderError::NotFound {
+            provider: NAME.to_string(),
+            symbol: symbol.to_string(),
+        });
+    }
+    if header.to_lowercase().contains("exceeded the daily hits limit") {
+        return Err(ProviderError::RateLimited {
+            provider: NAME.to_string(),
+        });
+    }
+    if !header.starts_with("Date,") {
+        return Err(parse_error(format!("unexpected header: {}", header)));
+    }
+
+    let mut bars = Vec::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() < 5 {
+            return Err(parse_error(format!("short row: {}", line)));
+        }
+
+        let date = NaiveDate::parse_from_str(fields[0], "%Y-%m-%d")
+            .map_err(|e| parse_error(format!("bad date {:?}: {}", fields[0], e)))?;
+        let number = |i: usize| {
+            fields[i]
+                .parse::<f64>()
+                .map_err(|e| parse_error(format!("bad number {:?}: {}", fields[i], e)))
+        };
+        let volume = match fields.get(5) {
+            Some(v) if !v.is_empty() => number(5)?.max(0.0) as u64,
+            _ => 0,
+        };
+
+        bars.push(StockData {
+            symbol: symbol.to_string(),
+            timestamp: DateTime::from_naive_utc_and_offset(date.and_hms_opt(0, 0, 0).unwrap(), Utc),
+            open: number(1)?,
+            high: number(2)?,
+            low: number(3)?,
+            close: number(4)?,
+            volume,
+            adjusted_close: None,
+        });
+    }
+
+    bars.sort_by_key(|bar| bar.timestamp);
+    Ok(bars)
+}
+
+#[async_trait]
+impl QuoteProvider for StooqProvider {
+    fn name(&self) -> &str {
+        NAME
+    }
+
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<QuoteSeries, ProviderError> {
+        self.fetch_history_interval(symbol, start, end, Interval::Daily).await
+    }
+
+    async fn fetch_history_interval(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: Interval,
+    ) -> Result<QuoteSeries, ProviderError> {
+        let interval_code = match interval {
+            Interval::Daily => "d",
+            Interval::Weekly => "w",
+            Interval::Monthly => "m",
+            Interval::FifteenMinutes | Interval::Hourly => {
+                return Err(ProviderError::Upstream {
+                    provider: NAME.to_string(),
+                    message: format!("{} bars are not supported", interval),
+                })
+            }
+        };
+        let url = format!(
+            "{}/q/d/l/?s={}&d1={}&d2={}&i={}",
+            self.base_url,
+            stooq_symbol(symbol),
+            start.format("%Y%m%d"),
+            end.format("%Y%m%d"),
+            interval_code,
+        );
+
+        let upstream = |message: String| ProviderError::Upstream {
+            provider: NAME.to_string(),
+            message,
+        };
+        let response = self.client.get(url).send().await.map_err(|e| ProviderError::request(NAME, e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(ProviderError::NotFound {
+                provider: NAME.to_string(),
+                symbol: symbol.to_string(),
+            });
+        }
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(ProviderError::RateLimited {
+                provider: NAME.to_string(),
+            });
+        }
+        if !status.is_success() {
+            return Err(upstream(status.to_string()));
+        }
+
+        let body = response.text().await.map_err(|e| ProviderError::request(NAME, e))?;
+        Ok(QuoteSeries {
+            bars: parse_csv(symbol, &body)?,
+            source: NAME.to_string(),
+            fetched_at: Utc::now(),
+            quality: None,
+            events: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stooq_symbol_normalization() {
+        assert_eq!(stooq_symbol("AAPL"), "aapl.us");
+        assert_eq!(stooq_symbol("BRK.B"), "brk-b.us");
+        assert_eq!(stooq_symbol("BRK/A"), "brk-a.us");
+        assert_eq!(stooq_symbol("msft.US"), "msft.us");
+    }
+
+    #[test]
+    fn test_parse_daily_csv_fixture() {
+        let bars = parse_csv("AAPL", include_str!("../../tests/fixtures/stooq_daily.csv")).unwrap();
+
+        assert_eq!(bars.len(), 5);
+        assert_eq!(bars[0].timestamp.format("%Y-%m-%d").to_string(), "2024-01-02");
+        assert_eq!(bars[0].open, 187.15);
+        assert_eq!(bars[0].volume, 82_488_674);
+        assert_eq!(bars[4].close, 185.14);
+        assert!(bars.iter().all(|bar| bar.symbol == "AAPL"));
+    }
+
+    #[test]
+    fn test_parse_csv_without_volume() {
+        let body = "Date,Open,High,Low,Close\n2024-01-03,10,11,9,10.5\n2024-01-02,9,10,8,9.5\n";
+        let bars = parse_csv("^SPX", body).unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].close, 9.5);
+        assert_eq!(bars[1].volume, 0);
+    }
+
+    #[test]
+    fn test_parse_csv_errors() {
+        assert!(matches!(parse_csv("NOPE", "No data"), Err(ProviderError::NotFound { .. })));
+        assert!(matches!(parse_csv("NOPE", ""), Err(ProviderError::NotFound { .. })));
+        assert!(matches!(
+            parse_csv("AAPL", "Exceeded the daily hits limit"),
+            Err(ProviderError::RateLimited { .. })
+        ));
+        assert!(matches!(
+            parse_csv("AAPL", "Date,Open,High,Low,Close,Volume\n2024-01-02,abc,1,1,1,1"),
+            Err(ProviderError::Parse { .. })
+        ));
+        assert!(matches!(parse_csv("AAPL", "<html>"), Err(ProviderError::Parse { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_history_from_mock_server() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/q/d/l/")
+            .match_query(mockito::Matcher::UrlEncoded("s".into(), "brk-b.us".into()))
+            .with_body(include_str!("../../tests/fixtures/stooq_daily.csv"))
+            .create_async()
+            .await;
+
+        let provider = StooqProvider::with_base_url(&server.url());
+        let series = provider
+            .fetch_history("BRK.B", Utc::now() - chrono::Duration::days(30), Utc::now())
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(series.source, "stooq");
+        assert_eq!(series.bars.len(), 5);
+        assert_eq!(series.bars[0].symbol, "BRK.B");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_weekly_history_requests_weekly_bars() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/q/d/l/")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("i".into(), "w".into()),
+                mockito::Matcher::UrlEncoded("d1".into(), "20230101".into()),
+            ]))
+            .with_body(include_str!("../../tests/fixtures/stooq_daily.csv"))
+            .create_async()
+            .await;
+
+        let provider = StooqProvider::with_base_url(&server.url());
+        let start = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        provider
+            .fetch_history_interval("AAPL", start, Utc::now(), Interval::Weekly)
+            .await
+            .unwrap();
+        mock.assert_async().await;
+    }
+}
@@ -0,0 +1,274 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use yahoo_finance_api as yahoo;
+
+use super::{Interval, ProviderError, QuoteProvider, QuoteSeries};
+use crate::corporate_events::CorporateEvent;
+use crate::http::HttpClient;
+use crate::StockData;
+
+const NAME: &str = "yahoo";
+const DEFAULT_BASE_URL: &str = "https://query1.finance.yahoo.com";
+/// Corporate events requested alongside every chart
+const CHART_EVENTS: &str = "div|split|capitalGains";
+
+/// Yahoo Finance chart API. Requests go through our own `HttpClient` so
+/// proxy and timeout settings apply; `yahoo_finance_api` only reads the
+/// responses.
+pub struct YahooProvider {
+    client: HttpClient,
+    base_url: String,
+}
+
+impl YahooProvider {
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(Self::with_base_url(DEFAULT_BASE_URL))
+    }
+
+    /// Point the provider at a different host (used by tests)
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            client: HttpClient::default(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Send requests through `client`, e.g. one with a proxy
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Fetch and read a v8 chart response for `symbol`
+    async fn chart(&self, symbol: &str, query: &[(&str, String)]) -> Result<yahoo::YResponse, ProviderError> {
+        let ticker = yahoo_symbol(symbol);
+        let response = self
+            .client
+            .get(format!("{}/v8/finance/chart/{}", self.base_url, ticker))
+            .query(&[("symbol", ticker.as_str()), ("events", CHART_EVENTS)])
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| ProviderError::request(NAME, e))?;
+
+        let status = response.status();
+        if status != reqwest::StatusCode::OK {
+            return Err(classify(symbol, yahoo::YahooError::FetchFailed(status.to_string())));
+        }
+        let body = response.bytes().await.map_err(|e| ProviderError::request(NAME, e))?;
+        let json = serde_json::from_slice(&body).map_err(|e| classify(symbol, yahoo::YahooError::DeserializeFailed(e)))?;
+        yahoo::YResponse::from_json(json).map_err(|e| classify(symbol, e))
+    }
+}
+
+/// Yahoo writes share classes with a dash (`BRK-B`) where exchanges use a
+/// dot or slash (`BRK.B`, `BRK/B`)
+pub fn yahoo_symbol(symbol: &str) -> String {
+    symbol.trim().to_uppercase().replace(['.', '/'], "-")
+}
+
+fn classify(symbol: &str, error: yahoo::YahooError) -> ProviderError {
+    match error {
+        yahoo::YahooError::EmptyDataSet => ProviderError::NotFound {
+            provider: NAME.to_string(),
+            symbol: symbol.to_string(),
+        },
+        yahoo::YahooError::FetchFailed(status) if status.starts_with("404") => ProviderError::NotFound {
+            provider: NAME.to_string(),
+            symbol: symbol.to_string(),
+        },
+        yahoo::YahooError::FetchFailed(status) if status.starts_with("429") => ProviderError::RateLimited {
+            provider: NAME.to_string(),
+        },
+        yahoo::YahooError::DeserializeFailed(e) => ProviderError::Parse {
+            provider: NAME.to_string(),
+            message: e.to_string(),
+        },
+        yahoo::YahooError::InvalidJson | yahoo::YahooError::DataInconsistency => ProviderError::Parse {
+            provider: NAME.to_string(),
+            message: error.to_string(),
+        },
+        other => ProviderError::Upstream {
+            provider: NAME.to_string(),
+            message: other.to_string(),
+        },
+    }
+}
+
+fn to_stock_data(symbol: &str, quote: &yahoo::Quote) -> StockData {
+    StockData {
+        symbol: symbol.to_string(),
+        timestamp: DateTime::from_timestamp(quote.timestamp as i64, 0).unwrap_or(Utc::now()),
+        open: quote.open,
+        high: quote.high,
+        low: quote.low,
+        close: quote.close,
+        volume: quote.volume,
+        adjusted_close: (quote.adjclose > 0.0).then_some(quote.adjclose),
+    }
+}
+
+/// Splits and dividends the chart response carries alongside its quotes,
+/// oldest first
+fn to_events(response: &yahoo::YResponse) -> Result<Vec<CorporateEvent>, yahoo::YahooError> {
+    let date = |timestamp: u64| DateTime::from_timestamp(timestamp as i64, 0).unwrap_or_default();
+    let splits = response.splits()?.into_iter().filter(|split| split.denominator > 0.0).map(|split| CorporateEvent::Split {
+        ratio: split.numerator / split.denominator,
+        date: date(split.date),
+    });
+    let dividends = response.dividends()?.into_iter().map(|dividend| CorporateEvent::Dividend {
+        amount: dividend.amount,
+        date: date(dividend.date),
+    });
+    let mut events: Vec<CorporateEvent> = splits.chain(dividends).collect();
+    events.sort_by_key(CorporateEvent::date);
+    Ok(events)
+}
+
+#[async_trait]
+impl QuoteProvider for YahooProvider {
+    fn name(&self) -> &str {
+        NAME
+    }
+
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<QuoteSeries, ProviderError> {
+        self.fetch_history_interval(symbol, start, end, Interval::Daily).await
+    }
+
+    async fn fetch_history_interval(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: Interval,
+    ) -> Result<QuoteSeries, ProviderError> {
+        let query = [
+            ("period1", start.timestamp().to_string()),
+            ("period2", end.timestamp().to_string()),
+            ("interval", interval.as_str().to_string()),
+        ];
+        let response = self.chart(symbol, &query).await?;
+        let quotes = response.quotes().map_err(|e| classify(symbol, e))?;
+        let events = to_events(&response).map_err(|e| classify(symbol, e))?;
+
+        let mut bars: Vec<StockData> = quotes.iter().map(|q| to_stock_data(symbol, q)).collect();
+        bars.sort_by_key(|bar| bar.timestamp);
+
+        Ok(QuoteSeries {
+            bars,
+            source: NAME.to_string(),
+            fetched_at: Utc::now(),
+            quality: None,
+            events,
+        })
+    }
+
+    async fn fetch_range(&self, symbol: &str, range: &str, interval: Interval) -> Result<QuoteSeries, ProviderError> {
+        let query = [("interval", interval.as_str().to_string()), ("range", range.to_string())];
+        let response = self.chart(symbol, &query).await?;
+        let quotes = response.quotes().map_err(|e| classify(symbol, e))?;
+        let events = to_events(&response).map_err(|e| classify(symbol, e))?;
+
+        let mut bars: Vec<StockData> = quotes.iter().map(|q| to_stock_data(symbol, q)).collect();
+        bars.sort_by_key(|bar| bar.timestamp);
+
+        Ok(QuoteSeries {
+            bars,
+            source: NAME.to_string(),
+            fetched_at: Utc::now(),
+            quality: None,
+            events,
+        })
+    }
+
+    async fn latest_quote(&self, symbol: &str) -> Result<StockData, ProviderError> {
+        let query = [("interval", "1d".to_string()), ("range", "1mo".to_string())];
+        let response = self.chart(symbol, &query).await?;
+        let quote = response.last_quote().map_err(|e| classify(symbol, e))?;
+        Ok(to_stock_data(symbol, &quote))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{HttpClientConfig, HttpClientFactory};
+
+    #[test]
+    fn test_yahoo_symbol_normalization() {
+        assert_eq!(yahoo_symbol("AAPL"), "AAPL");
+        assert_eq!(yahoo_symbol("brk.b"), "BRK-B");
+        assert_eq!(yahoo_symbol("BRK/A"), "BRK-A");
+    }
+
+    #[test]
+    fn test_error_classification() {
+        assert!(!classify("X", yahoo::YahooError::EmptyDataSet).should_fall_back());
+        assert!(!classify("X", yahoo::YahooError::FetchFailed("404 Not Found".into())).should_fall_back());
+        assert!(matches!(
+            classify("X", yahoo::YahooError::FetchFailed("429 Too Many Requests".into())),
+            ProviderError::RateLimited { .. }
+        ));
+        assert!(classify("X", yahoo::YahooError::FetchFailed("503 Service Unavailable".into())).should_fall_back());
+    }
+
+    fn test_client(read_timeout_ms: u64) -> HttpClient {
+        let config = HttpClientConfig {
+            read_timeout_ms,
+            user_agents: vec!["agent-a".to_string(), "agent-b".to_string()],
+            ..HttpClientConfig::default()
+        };
+        HttpClientFactory::new(config).client().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_requests_rotate_user_agents() {
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = Vec::new();
+        for agent in ["agent-a", "agent-b"] {
+            let mock = server
+                .mock("GET", "/v8/finance/chart/BRK-B")
+                .match_query(mockito::Matcher::UrlEncoded("range".into(), "5d".into()))
+                .match_header("user-agent", agent)
+                .with_status(503)
+                .expect(1)
+                .create_async()
+                .await;
+            mocks.push(mock);
+        }
+
+        let provider = YahooProvider::with_base_url(&server.url()).with_http_client(test_client(5_000));
+        for _ in 0..2 {
+            let error = provider.fetch_range("BRK.B", "5d", Interval::Daily).await.unwrap_err();
+            assert!(matches!(error, ProviderError::Upstream { .. }), "{:?}", error);
+        }
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_response_times_out() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/v8/finance/chart/AAPL")
+            .match_query(mockito::Matcher::Any)
+            .with_chunked_body(|body| {
+                std::thread::sleep(std::time::Duration::from_millis(500));
+                body.write_all(b"{}")
+            })
+            .create_async()
+            .await;
+
+        let provider = YahooProvider::with_base_url(&server.url()).with_http_client(test_client(100));
+        let error = provider.latest_quote("AAPL").await.unwrap_err();
+        assert!(matches!(error, ProviderError::Timeout { .. }), "{:?}", error);
+        assert!(error.should_fall_back());
+        assert_eq!(crate::AnalyzerError::from(error).category(), "timeout");
+    }
+}
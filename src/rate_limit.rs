@@ -0,0 +1,268 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::clock::SharedClock;
+
+/// Settings for the limiter shared by every upstream quote request
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitConfig {
+    /// Ceiling on sustained requests per second
+    pub max_rate: f64,
+    /// Requests that may go out back to back after an idle period
+    pub burst: f64,
+    /// Halve the rate whenever the upstream throttles us, then recover gradually
+    pub adaptive: bool,
+    /// Floor the adaptive rate never drops below
+    pub min_rate: f64,
+    /// Throttle-free time required before each recovery step
+    pub recovery_interval: Duration,
+    /// Requests per second regained per recovery step
+    pub recovery_step: f64,
+    /// Requests allowed in flight at once; `None` leaves concurrency to the rate alone
+    pub max_concurrent: Option<usize>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_rate: 10.0,
+            burst: 10.0,
+            adaptive: true,
+            min_rate: 0.25,
+            recovery_interval: Duration::from_secs(30),
+            recovery_step: 1.0,
+            max_concurrent: None,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Default settings with a different ceiling; the burst follows the ceiling
+    pub fn with_max_rate(max_rate: f64) -> Self {
+        let max_rate = max_rate.max(f64::MIN_POSITIVE);
+        Self {
+            max_rate,
+            burst: max_rate.max(1.0),
+            min_rate: Self::default().min_rate.min(max_rate),
+            ..Self::default()
+        }
+    }
+}
+
+/// Snapshot of the limiter for `/api/cache-stats`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RateLimiterStats {
+    pub effective_rate: f64,
+    pub max_rate: f64,
+    pub throttle_events: u64,
+    pub permits_granted: u64,
+}
+
+/// Token bucket limiting requests across all symbols.
+///
+/// In adaptive mode every throttle response halves the refill rate; after
+/// each `recovery_interval` without one, `recovery_step` is added back until
+/// the configured ceiling is reached again.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    clock: SharedClock,
+    state: Mutex<BucketState>,
+    in_flight: Option<Arc<Semaphore>>,
+}
+
+/// Permission to send one request, holding a concurrency slot (when the
+/// limiter caps concurrency) until dropped
+pub struct RequestPermit {
+    _slot: Option<OwnedSemaphorePermit>,
+}
+
+struct BucketState {
+    rate: f64,
+    tokens: f64,
+    refilled_at: DateTime<Utc>,
+    adjusted_at: DateTime<Utc>,
+    throttle_events: u64,
+    permits_granted: u64,
+}
+
+/// Slack for float rounding so a waiter that slept exactly long enough gets its token
+const TOKEN_EPSILON: f64 = 1e-9;
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig, clock: SharedClock) -> Self {
+        let now = clock.now();
+        Self {
+            state: Mutex::new(BucketState {
+                rate: config.max_rate,
+                tokens: config.burst,
+                refilled_at: now,
+                adjusted_at: now,
+                throttle_events: 0,
+                permits_granted: 0,
+            }),
+            in_flight: config.max_concurrent.map(|max| Arc::new(Semaphore::new(max.max(1)))),
+            config,
+            clock,
+        }
+    }
+
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Wait until a request may be sent. Keep the permit for as long as the
+    /// request is in flight.
+    pub async fn acquire(&self) -> RequestPermit {
+        let slot = match self.in_flight {
+            Some(ref in_flight) => Some(in_flight.clone().acquire_owned().await.expect("semaphore is never closed")),
+            None => None,
+        };
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                self.refill(&mut state);
+                if state.tokens >= 1.0 - TOKEN_EPSILON {
+                    state.tokens = (state.tokens - 1.0).max(0.0);
+                    state.permits_granted += 1;
+                    return RequestPermit { _slot: slot };
+                }
+                (1.0 - state.tokens) / state.rate
+            };
+            self.clock.sleep(Duration::from_secs_f64(wait)).await;
+        }
+    }
+
+    /// Note that the upstream rejected a request as too frequent
+    pub fn record_throttle(&self) {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state);
+        state.throttle_events += 1;
+        if self.config.adaptive {
+            state.rate = (state.rate / 2.0).max(self.config.min_rate);
+            state.adjusted_at = self.clock.now();
+            tracing::warn!("Upstream throttled us; request rate lowered to {:.2}/s", state.rate);
+        }
+    }
+
+    pub fn stats(&self) -> RateLimiterStats {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state);
+        RateLimiterStats {
+            effective_rate: state.rate,
+            max_rate: self.config.max_rate,
+            throttle_events: state.throttle_events,
+            permits_granted: state.permits_granted,
+        }
+    }
+
+    fn refill(&self, state: &mut BucketState) {
+        let now = self.clock.now();
+
+        if state.rate < self.config.max_rate {
+            let since_adjusted = (now - state.adjusted_at).to_std().unwrap_or_default();
+            let steps = (since_adjusted.as_secs_f64() / self.config.recovery_interval.as_secs_f64()).floor();
+            if steps >= 1.0 {
+                state.rate = (state.rate + steps * self.config.recovery_step).min(self.config.max_rate);
+                state.adjusted_at += chrono::Duration::from_std(self.config.recovery_interval.mul_f64(steps))
+                    .unwrap_or(chrono::Duration::zero());
+            }
+        } else {
+            state.adjusted_at = now;
+        }
+
+        let elapsed = (now - state.refilled_at).to_std().unwrap_or_default().as_secs_f64();
+        state.tokens = (state.tokens + elapsed * state.rate).min(self.config.burst);
+        state.refilled_at = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::TestClock;
+    use chrono::TimeZone;
+    use std::sync::Arc;
+
+    fn limiter(config: RateLimitConfig) -> (Arc<TestClock>, Arc<RateLimiter>) {
+        let clock = Arc::new(TestClock::new(Utc.with_ymd_and_hms(2024, 1, 2, 14, 30, 0).unwrap()));
+        let limiter = Arc::new(RateLimiter::new(config, clock.clone()));
+        (clock, limiter)
+    }
+
+    #[tokio::test]
+    async fn test_burst_then_waits_for_refill() {
+        let (clock, limiter) = limiter(RateLimitConfig { burst: 2.0, ..RateLimitConfig::with_max_rate(4.0) });
+        limiter.acquire().await;
+        limiter.acquire().await;
+
+        let waiter = {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { limiter.acquire().await })
+        };
+        clock.wait_for_sleepers(1).await;
+        clock.advance(Duration::from_millis(200));
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        clock.advance(Duration::from_millis(50));
+        waiter.await.unwrap();
+        assert_eq!(limiter.stats().permits_granted, 3);
+    }
+
+    #[tokio::test]
+    async fn test_throttle_halves_rate_and_recovers() {
+        let (clock, limiter) = limiter(RateLimitConfig {
+            min_rate: 1.0,
+            recovery_interval: Duration::from_secs(10),
+            recovery_step: 1.0,
+            ..RateLimitConfig::with_max_rate(8.0)
+        });
+
+        limiter.record_throttle();
+        assert_eq!(limiter.stats().effective_rate, 4.0);
+        limiter.record_throttle();
+        limiter.record_throttle();
+        limiter.record_throttle();
+        assert_eq!(limiter.stats().effective_rate, 1.0, "never below the floor");
+        assert_eq!(limiter.stats().throttle_events, 4);
+
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(limiter.stats().effective_rate, 1.0);
+        clock.advance(Duration::from_secs(21));
+        assert_eq!(limiter.stats().effective_rate, 4.0);
+        clock.advance(Duration::from_secs(600));
+        assert_eq!(limiter.stats().effective_rate, 8.0, "recovery stops at the ceiling");
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_holds_until_permit_dropped() {
+        let (_clock, limiter) = limiter(RateLimitConfig {
+            max_concurrent: Some(1),
+            ..RateLimitConfig::with_max_rate(100.0)
+        });
+        let first = limiter.acquire().await;
+
+        let waiter = {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { limiter.acquire().await })
+        };
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        drop(first);
+        waiter.await.unwrap();
+        assert_eq!(limiter.stats().permits_granted, 2);
+    }
+
+    #[tokio::test]
+    async fn test_non_adaptive_keeps_rate() {
+        let (_clock, limiter) = limiter(RateLimitConfig { adaptive: false, ..RateLimitConfig::with_max_rate(3.0) });
+        limiter.record_throttle();
+        let stats = limiter.stats();
+        assert_eq!(stats.effective_rate, 3.0);
+        assert_eq!(stats.throttle_events, 1);
+    }
+}
@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+
+/// Broad region a listing's country belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Region {
+    NorthAmerica,
+    LatinAmerica,
+    Europe,
+    AsiaPacific,
+    MiddleEastAfrica,
+    /// Tax havens foreign issuers incorporate in, such as the Cayman Islands
+    Offshore,
+}
+
+impl Region {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Region::NorthAmerica => "North America",
+            Region::LatinAmerica => "Latin America",
+            Region::Europe => "Europe",
+            Region::AsiaPacific => "Asia Pacific",
+            Region::MiddleEastAfrica => "Middle East & Africa",
+            Region::Offshore => "Offshore",
+        }
+    }
+}
+
+/// Canonical country names with their region and the spellings the Nasdaq
+/// screener and users write them in, compared after `alias_key`
+const COUNTRIES: &[(&str, Region, &[&str])] = &[
+    ("United States", Region::NorthAmerica, &["us", "usa", "united states of america", "america"]),
+    ("Canada", Region::NorthAmerica, &[]),
+    ("Puerto Rico", Region::NorthAmerica, &[]),
+    ("Mexico", Region::LatinAmerica, &[]),
+    ("Brazil", Region::LatinAmerica, &["brasil"]),
+    ("Argentina", Region::LatinAmerica, &[]),
+    ("Chile", Region::LatinAmerica, &[]),
+    ("Colombia", Region::LatinAmerica, &[]),
+    ("Peru", Region::LatinAmerica, &[]),
+    ("Uruguay", Region::LatinAmerica, &[]),
+    ("Panama", Region::LatinAmerica, &[]),
+    ("United Kingdom", Region::Europe, &["uk", "great britain", "britain", "england", "scotland"]),
+    ("Ireland", Region::Europe, &["republic of ireland"]),
+    ("Netherlands", Region::Europe, &["the netherlands", "holland"]),
+    ("Germany", Region::Europe, &[]),
+    ("France", Region::Europe, &[]),
+    ("Switzerland", Region::Europe, &[]),
+    ("Luxembourg", Region::Europe, &[]),
+    ("Belgium", Region::Europe, &[]),
+    ("Spain", Region::Europe, &[]),
+    ("Italy", Region::Europe, &[]),
+    ("Sweden", Region::Europe, &[]),
+    ("Denmark", Region::Europe, &[]),
+    ("Norway", Region::Europe, &[]),
+    ("Finland", Region::Europe, &[]),
+    ("Greece", Region::Europe, &[]),
+    ("Cyprus", Region::Europe, &[]),
+    ("Monaco", Region::Europe, &[]),
+    ("China", Region::AsiaPacific, &["prc", "people's republic of china", "peoples republic of china", "mainland china"]),
+    ("Hong Kong", Region::AsiaPacific, &["hk", "hong kong sar"]),
+    ("Taiwan", Region::AsiaPacific, &["republic of china", "taiwan roc"]),
+    ("Japan", Region::AsiaPacific, &[]),
+    ("South Korea", Region::AsiaPacific, &["korea", "republic of korea", "korea republic of"]),
+    ("Singapore", Region::AsiaPacific, &[]),
+    ("India", Region::AsiaPacific, &[]),
+    ("Australia", Region::AsiaPacific, &[]),
+    ("New Zealand", Region::AsiaPacific, &[]),
+    ("Indonesia", Region::AsiaPacific, &[]),
+    ("Philippines", Region::AsiaPacific, &[]),
+    ("Malaysia", Region::AsiaPacific, &[]),
+    ("Israel", Region::MiddleEastAfrica, &[]),
+    ("United Arab Emirates", Region::MiddleEastAfrica, &["uae"]),
+    ("South Africa", Region::MiddleEastAfrica, &[]),
+    ("Turkey", Region::MiddleEastAfrica, &["turkiye"]),
+    ("Cayman Islands", Region::Offshore, &["cayman"]),
+    ("British Virgin Islands", Region::Offshore, &["virgin islands", "virgin islands british", "bvi"]),
+    ("Bermuda", Region::Offshore, &[]),
+    ("Bahamas", Region::Offshore, &["the bahamas"]),
+    ("Jersey", Region::Offshore, &["channel islands"]),
+    ("Guernsey", Region::Offshore, &[]),
+    ("Isle of Man", Region::Offshore, &[]),
+    ("Marshall Islands", Region::Offshore, &[]),
+];
+
+/// Lowercase with punctuation dropped and whitespace collapsed, so "U.S.A."
+/// and "usa" or "Virgin Islands (British)" and "virgin islands british" meet
+fn alias_key(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace() || *c == '\'' || *c == '&')
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Canonical name and region of a country as the screener or a user spells
+/// it. Unknown countries keep their trimmed spelling with no region; blanks
+/// and placeholders are `None`.
+pub fn normalize_country(country: &str) -> Option<(String, Option<Region>)> {
+    let key = alias_key(country);
+    if key.is_empty() || matches!(key.as_str(), "na" | "n a" | "unknown" | "none") {
+        return None;
+    }
+    let known = COUNTRIES
+        .iter()
+        .find(|(name, _, aliases)| alias_key(name) == key || aliases.contains(&key.as_str()));
+    Some(match known {
+        Some(&(name, region, _)) => (name.to_string(), Some(region)),
+        None => (country.trim().to_string(), None),
+    })
+}
+
+/// Whether a listing in `country` (normalized) and `region` is wanted by a
+/// `countries` filter entry. A known country, in any spelling, must match
+/// exactly; a region name such as "Europe" matches its countries; anything
+/// else matches as a case-insensitive substring.
+pub fn country_matches(country: &str, region: Option<Region>, wanted: &str) -> bool {
+    match normalize_country(wanted) {
+        Some((name, Some(_))) => name == country,
+        _ => {
+            region.is_some_and(|region| alias_key(region.as_str()) == alias_key(wanted))
+                || country.to_lowercase().contains(&wanted.trim().to_lowercase())
+        }
+    }
+}
+
+/// Havens Chinese and other foreign issuers of ADRs incorporate in. Bermuda
+/// is left out: it is home to many US insurers named "... Ltd".
+const ADR_HAVENS: &[&str] = &["Cayman Islands", "British Virgin Islands"];
+
+/// Legal-form words US issuers use; a Cayman or Virgin Islands company
+/// named like this is usually a US business, not a foreign one
+const US_STYLE_SUFFIXES: &[&str] = &["inc", "incorporated", "corp", "corporation", "co", "company", "llc", "lp", "trust"];
+
+/// Whether a listing looks like American depositary receipts or shares of a
+/// foreign company rather than its ordinary shares:
+///
+/// - the name says "ADR", "ADS" or "American Depositary";
+/// - the symbol has five letters ending in Y, the convention for
+///   unsponsored ADRs;
+/// - the company is incorporated in the Cayman or British Virgin Islands
+///   under a name without a US legal form ("... Holdings Ltd"), as Chinese
+///   issuers typically are.
+///
+/// `country` is the normalized country.
+pub fn looks_like_adr(symbol: &str, name: &str, country: Option<&str>) -> bool {
+    let words: Vec<String> = alias_key(name).split(' ').map(str::to_string).collect();
+    if words.iter().any(|word| word == "adr" || word == "ads" || word == "adrs")
+        || alias_key(name).contains("american depositary")
+    {
+        return true;
+    }
+    if symbol.len() == 5 && symbol.ends_with('Y') && symbol.chars().all(|c| c.is_ascii_uppercase()) {
+        return true;
+    }
+    country.is_some_and(|country| ADR_HAVENS.contains(&country)) && !words.iter().any(|word| US_STYLE_SUFFIXES.contains(&word.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalized(country: &str) -> Option<String> {
+        normalize_country(country).map(|(name, _)| name)
+    }
+
+    #[test]
+    fn test_country_aliases() {
+        for alias in ["United States", "USA", "U.S.A.", "us", " united  states ", "United States of America"] {
+            assert_eq!(normalized(alias).as_deref(), Some("United States"), "{:?}", alias);
+        }
+        assert_eq!(normalized("UK").as_deref(), Some("United Kingdom"));
+        assert_eq!(normalized("Virgin Islands (British)").as_deref(), Some("British Virgin Islands"));
+        assert_eq!(normalized("Korea, Republic of").as_deref(), Some("South Korea"));
+        assert_eq!(normalized("People's Republic of China").as_deref(), Some("China"));
+        assert_eq!(normalize_country("Cayman Islands"), Some(("Cayman Islands".to_string(), Some(Region::Offshore))));
+        assert_eq!(normalize_country("Hong Kong"), Some(("Hong Kong".to_string(), Some(Region::AsiaPacific))));
+
+        // Unknown spellings survive without a region; blanks are dropped
+        assert_eq!(normalize_country(" Atlantis "), Some(("Atlantis".to_string(), None)));
+        assert_eq!(normalize_country(""), None);
+        assert_eq!(normalize_country("N/A"), None);
+    }
+
+    #[test]
+    fn test_country_filter_entries() {
+        assert!(country_matches("United States", Some(Region::NorthAmerica), "USA"));
+        assert!(!country_matches("United States", Some(Region::NorthAmerica), "UK"));
+        assert!(country_matches("United Kingdom", Some(Region::Europe), "united"));
+        assert!(country_matches("Germany", Some(Region::Europe), "europe"));
+        assert!(!country_matches("Cayman Islands", Some(Region::Offshore), "China"));
+        assert!(country_matches("Atlantis", None, "atlan"));
+    }
+
+    #[test]
+    fn test_adr_heuristics() {
+        let cayman = Some("Cayman Islands");
+        let us = Some("United States");
+        // Named as depositary shares
+        assert!(looks_like_adr("BABA", "Alibaba Group Holding Limited American Depositary Shares", cayman));
+        assert!(looks_like_adr("TSM", "Taiwan Semiconductor Manufacturing Company Ltd. ADR", Some("Taiwan")));
+        assert!(looks_like_adr("SNY", "Sanofi ADS", Some("France")));
+        // Five-letter Y symbols
+        assert!(looks_like_adr("TCEHY", "Tencent Holdings", None));
+        assert!(looks_like_adr("NSRGY", "Nestle S.A.", Some("Switzerland")));
+        // Haven incorporation under a foreign-style name
+        assert!(looks_like_adr("BIDU", "Baidu Holdings Ltd", cayman));
+        assert!(looks_like_adr("LX", "LexinFintech Holdings Ltd", Some("British Virgin Islands")));
+
+        assert!(!looks_like_adr("ETSY", "Etsy Inc. Common Stock", us));
+        assert!(!looks_like_adr("EBAY", "eBay Inc. Common Stock", us));
+        assert!(!looks_like_adr("FRO", "Frontline plc Ordinary Shares", Some("Cyprus")));
+        assert!(!looks_like_adr("ACGL", "Arch Capital Group Ltd. Common Stock", Some("Bermuda")));
+        // A haven, but named like a US company
+        assert!(!looks_like_adr("GLBE", "Globe Holdings Inc", cayman));
+    }
+}
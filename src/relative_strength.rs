@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+
+use crate::StockData;
+
+/// Index fund each stock's relative strength is measured against
+/// (`AppConfig::benchmark_symbol`)
+pub const DEFAULT_BENCHMARK_SYMBOL: &str = "SPY";
+/// Trading days in the 1-week window
+pub const RS_1W_BARS: usize = 5;
+/// Trading days in the 1-month window
+pub const RS_1M_BARS: usize = 21;
+/// Trading days in the 3-month window, the one the cycle ranks on
+pub const RS_3M_BARS: usize = 63;
+
+/// A stock's return relative to the benchmark's over the same dates: the
+/// stock's growth (end close / start close) divided by the benchmark's.
+/// Above 1.0 the stock outperformed; 1.1 means it grew 10% more.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RelativeStrength {
+    pub rs_1w: Option<f64>,
+    pub rs_1m: Option<f64>,
+    pub rs_3m: Option<f64>,
+}
+
+impl RelativeStrength {
+    /// Ratios over each window that both series cover. Both are daily
+    /// candles in ascending order; the benchmark close used for a date is
+    /// the latest one on or before it, so holidays on either side line up.
+    pub fn compute(stock: &[StockData], benchmark: &[StockData]) -> Self {
+        Self {
+            rs_1w: relative_return(stock, benchmark, RS_1W_BARS),
+            rs_1m: relative_return(stock, benchmark, RS_1M_BARS),
+            rs_3m: relative_return(stock, benchmark, RS_3M_BARS),
+        }
+    }
+}
+
+fn relative_return(stock: &[StockData], benchmark: &[StockData], bars: usize) -> Option<f64> {
+    let end = stock.last()?;
+    let start = stock.len().checked_sub(bars + 1).map(|i| &stock[i])?;
+    let benchmark_end = close_on_or_before(benchmark, end)?;
+    let benchmark_start = close_on_or_before(benchmark, start)?;
+    if start.close <= 0.0 || benchmark_start <= 0.0 || benchmark_end <= 0.0 {
+        return None;
+    }
+    Some((end.close / start.close) / (benchmark_end / benchmark_start))
+}
+
+fn close_on_or_before(series: &[StockData], bar: &StockData) -> Option<f64> {
+    let date = bar.timestamp.date_naive();
+    let count = series.partition_point(|candidate| candidate.timestamp.date_naive() <= date);
+    count.checked_sub(1).map(|i| series[i].close)
+}
+
+/// One symbol's place among the relative strength scores of a cycle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RsRank {
+    pub ticker: String,
+    /// Percentile, 0-100: the share of the other symbols it outperformed
+    pub rs_rank: f64,
+}
+
+/// Broadcast once a continuous cycle has ranked its results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankedCycle {
+    pub session_id: String,
+    pub ranks: Vec<RsRank>,
+}
+
+/// Percentile rank of each score among all of them: the share of the other
+/// scores it beats, with ties counting half. A lone score ranks 100.
+/// Non-finite scores are left out.
+pub fn percentile_ranks(scores: &[(String, f64)]) -> Vec<RsRank> {
+    let scores: Vec<&(String, f64)> = scores.iter().filter(|(_, score)| score.is_finite()).collect();
+    let mut sorted: Vec<f64> = scores.iter().map(|(_, score)| *score).collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let others = sorted.len().saturating_sub(1);
+
+    scores
+        .into_iter()
+        .map(|(ticker, score)| {
+            let below = sorted.partition_point(|other| other < score);
+            let tied = sorted.partition_point(|other| other <= score) - below - 1;
+            let rs_rank = match others {
+                0 => 100.0,
+                _ => (below as f64 + tied as f64 / 2.0) / others as f64 * 100.0,
+            };
+            RsRank { ticker: ticker.clone(), rs_rank }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration};
+
+    /// Daily candles closing at `closes`, starting `offset` days after the first date
+    fn series(closes: &[f64], offset: i64) -> Vec<StockData> {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| StockData {
+                symbol: "TEST".to_string(),
+                timestamp: start + Duration::days(offset + i as i64),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 1_000,
+                adjusted_close: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_relative_return_windows() {
+        // The stock doubles over the last week while the benchmark rises 25%
+        let mut stock_closes = vec![50.0; 60];
+        stock_closes.extend([50.0, 60.0, 70.0, 80.0, 90.0, 100.0]);
+        let benchmark: Vec<f64> = (0..66).map(|i| if i < 61 { 100.0 } else { 125.0 }).collect();
+        let rs = RelativeStrength::compute(&series(&stock_closes, 0), &series(&benchmark, 0));
+
+        assert!((rs.rs_1w.unwrap() - 2.0 / 1.25).abs() < 1e-9);
+        assert!((rs.rs_1m.unwrap() - 2.0 / 1.25).abs() < 1e-9);
+        assert!((rs.rs_3m.unwrap() - 2.0 / 1.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_short_or_unaligned_history() {
+        let stock = series(&[10.0; 10], 0);
+        let rs = RelativeStrength::compute(&stock, &series(&[100.0; 10], 0));
+        assert!(rs.rs_1w.is_some());
+        assert_eq!((rs.rs_1m, rs.rs_3m), (None, None));
+
+        // A benchmark that starts after the window does not cover it
+        assert_eq!(RelativeStrength::compute(&stock, &series(&[100.0; 10], 7)).rs_1w, None);
+        // A gap in the benchmark uses the close before it
+        let gapped: Vec<StockData> = series(&[100.0, 100.0, 100.0, 100.0, 80.0, 80.0, 80.0, 80.0, 80.0, 80.0], 0)
+            .into_iter()
+            .filter(|bar| bar.timestamp != stock[4].timestamp)
+            .collect();
+        assert_eq!(RelativeStrength::compute(&stock, &gapped).rs_1w, Some(1.0 / 0.8));
+        assert_eq!(RelativeStrength::compute(&stock, &[]), RelativeStrength::default());
+    }
+
+    #[test]
+    fn test_percentile_ranks() {
+        let scores: Vec<(String, f64)> =
+            [("LOW", 0.8), ("MID", 1.0), ("TIE", 1.0), ("HIGH", 1.5), ("BAD", f64::NAN)]
+                .into_iter()
+                .map(|(ticker, score)| (ticker.to_string(), score))
+                .collect();
+        let ranks: Vec<(String, f64)> = percentile_ranks(&scores).into_iter().map(|r| (r.ticker, r.rs_rank)).collect();
+        assert_eq!(
+            ranks,
+            [("LOW".to_string(), 0.0), ("MID".to_string(), 50.0), ("TIE".to_string(), 50.0), ("HIGH".to_string(), 100.0)]
+        );
+
+        assert_eq!(percentile_ranks(&[("ONLY".to_string(), 0.5)])[0].rs_rank, 100.0);
+        assert!(percentile_ranks(&[]).is_empty());
+    }
+}
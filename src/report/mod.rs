@@ -0,0 +1,296 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::io::{Cursor, Write};
+
+use crate::database::AnalysisSession;
+use crate::web_api::{summarize_sectors, AnalysisRequest, AnalysisStatus, SectorSummary, StockAnalysisResult};
+use crate::FilterSpec;
+
+pub mod table;
+pub mod text;
+
+pub use table::{Align, Column, TableFormatter};
+pub use text::{format_number, ticker_table, OpportunityReport, SymbolReport};
+
+/// Default number of opportunities listed in `SessionReport::top_opportunities`
+pub const DEFAULT_TOP_OPPORTUNITIES: usize = 10;
+
+/// A self-contained record of one analysis session, for archiving or sharing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionReport {
+    pub session_id: String,
+    /// "running", "completed" or "error"
+    pub status: String,
+    /// The session is still running, or never finished storing its
+    /// results, and the results are those found so far
+    pub partial: bool,
+    pub generated_at: DateTime<Utc>,
+    /// The request that started the session and the filter it resolved to.
+    /// These and the timings are only known when the server has a database.
+    pub request: Option<AnalysisRequest>,
+    pub filter: Option<FilterSpec>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub analyzed_count: u64,
+    pub total_count: u64,
+    pub opportunities_found: u64,
+    pub error_message: Option<String>,
+    /// Opportunities with the most extreme RSI first
+    pub top_opportunities: Vec<StockAnalysisResult>,
+    pub sectors: Vec<SectorSummary>,
+    pub results: Vec<StockAnalysisResult>,
+}
+
+impl SessionReport {
+    /// Report on a session from its in-memory status, if the server still
+    /// has it, and its stored record; the live status wins where both
+    /// describe the same thing
+    pub fn new(
+        session_id: &str,
+        status: Option<&AnalysisStatus>,
+        stored: Option<AnalysisSession>,
+        results: Vec<StockAnalysisResult>,
+        top: usize,
+        generated_at: DateTime<Utc>,
+    ) -> Self {
+        let state = status.map(|s| s.status.clone()).or_else(|| stored.as_ref().map(|s| s.status.clone()));
+        let status_counts = status.map(|s| (s.analyzed_count as u64, s.total_count as u64, s.opportunities_found as u64));
+        let stored_counts = stored.as_ref().map(|s| (s.analyzed_count, s.total_count, s.opportunities_found));
+        let (analyzed_count, total_count, opportunities_found) = status_counts.or(stored_counts).unwrap_or_default();
+        let error_message = match status {
+            Some(status) => status.error_message.clone(),
+            None => stored.as_ref().and_then(|s| s.error_message.clone()),
+        };
+        let state = state.unwrap_or_else(|| "completed".to_string());
+
+        Self {
+            session_id: session_id.to_string(),
+            partial: state == "running",
+            status: state,
+            generated_at,
+            request: stored.as_ref().map(|s| s.request.clone()),
+            filter: stored.as_ref().map(|s| s.filter.clone()),
+            started_at: stored.as_ref().map(|s| s.started_at),
+            finished_at: stored.and_then(|s| s.finished_at),
+            analyzed_count,
+            total_count,
+            opportunities_found,
+            error_message,
+            top_opportunities: top_opportunities(&results, top),
+            sectors: summarize_sectors(&results, 0),
+            results,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// The report as `report.json` plus `results.csv` in a zip archive
+    pub fn to_zip(&self) -> Result<Vec<u8>> {
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        archive.start_file("report.json", options)?;
+        archive.write_all(self.to_json()?.as_bytes())?;
+        archive.start_file("results.csv", options)?;
+        archive.write_all(&results_csv(&self.results)?)?;
+        Ok(archive.finish()?.into_inner())
+    }
+}
+
+/// Up to `n` opportunities, those with RSI furthest from 50 first; ties
+/// and results without an RSI go by ticker
+pub fn top_opportunities(results: &[StockAnalysisResult], n: usize) -> Vec<StockAnalysisResult> {
+    let extremity = |result: &StockAnalysisResult| result.rsi.filter(|rsi| rsi.is_finite()).map(|rsi| (rsi - 50.0).abs());
+    let mut opportunities: Vec<&StockAnalysisResult> = results.iter().filter(|r| r.is_opportunity).collect();
+    opportunities.sort_by(|a, b| {
+        let by_rsi = match (extremity(a), extremity(b)) {
+            (Some(a), Some(b)) => b.partial_cmp(&a).unwrap_or(Ordering::Equal),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        by_rsi.then_with(|| a.ticker.cmp(&b.ticker))
+    });
+    opportunities.into_iter().take(n).cloned().collect()
+}
+
+/// One CSV line per result; signals are joined with "; "
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    ticker: &'a str,
+    name: &'a str,
+    current_price: Option<f64>,
+    rsi: Option<f64>,
+    sma_20: Option<f64>,
+    sma_50: Option<f64>,
+    macd: Option<f64>,
+    macd_signal: Option<f64>,
+    macd_histogram: Option<f64>,
+    volume: Option<u64>,
+    pct_change: Option<f64>,
+    market_cap: Option<&'a str>,
+    sector: Option<&'a str>,
+    industry: Option<&'a str>,
+    exchange: Option<&'a str>,
+    is_opportunity: bool,
+    analyst_target: Option<f64>,
+    upside_to_target_pct: Option<f64>,
+    volume_vs_avg: Option<f64>,
+    pct_from_high: Option<f64>,
+    pct_from_low: Option<f64>,
+    rs_3m: Option<f64>,
+    rs_rank: Option<f64>,
+    note: Option<&'a str>,
+    timestamp: DateTime<Utc>,
+    signals: String,
+}
+
+/// The results as CSV with a header row
+pub fn results_csv(results: &[StockAnalysisResult]) -> Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for result in results {
+        writer.serialize(CsvRow {
+            ticker: &result.ticker,
+            name: &result.name,
+            current_price: result.current_price,
+            rsi: result.rsi,
+            sma_20: result.sma_20,
+            sma_50: result.sma_50,
+            macd: result.macd,
+            macd_signal: result.macd_signal,
+            macd_histogram: result.macd_histogram,
+            volume: result.volume,
+            pct_change: result.pct_change,
+            market_cap: result.market_cap.as_deref(),
+            sector: result.sector.as_deref(),
+            industry: result.industry.as_deref(),
+            exchange: result.exchange.as_deref(),
+            is_opportunity: result.is_opportunity,
+            analyst_target: result.analyst_target,
+            upside_to_target_pct: result.upside_to_target_pct,
+            volume_vs_avg: result.volume_vs_avg,
+            pct_from_high: result.pct_from_high,
+            pct_from_low: result.pct_from_low,
+            rs_3m: result.rs_3m,
+            rs_rank: result.rs_rank,
+            note: result.note.as_deref(),
+            timestamp: result.timestamp,
+            signals: result.signals.join("; "),
+        })?;
+    }
+    Ok(writer.into_inner()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opportunity_state::OpportunityState;
+    use crate::resample::Timeframe;
+    use std::collections::HashMap;
+    use std::io::Read;
+
+    fn result(ticker: &str, rsi: Option<f64>, is_opportunity: bool) -> StockAnalysisResult {
+        StockAnalysisResult {
+            ticker: ticker.to_string(),
+            name: format!("{}, Inc.", ticker),
+            current_price: Some(10.0),
+            rsi,
+            sma_20: None,
+            sma_50: None,
+            macd: None,
+            macd_signal: None,
+            macd_histogram: None,
+            volume: Some(1_000),
+            pct_change: None,
+            market_cap: None,
+            sector: Some("Technology".to_string()),
+            industry: None,
+            exchange: None,
+            is_opportunity,
+            signals: vec!["Oversold - Potential Buy".to_string(), "MACD Bullish".to_string()],
+            timestamp: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            data_source: None,
+            data_fetched_at: None,
+            summary: None,
+            analyst_target: None,
+            upside_to_target_pct: None,
+            obv: None,
+            vwap: None,
+            volume_vs_avg: None,
+            high_52w: None,
+            low_52w: None,
+            pct_from_high: None,
+            pct_from_low: None,
+            range_window_complete: false,
+            rs_1w: None,
+            rs_1m: None,
+            rs_3m: None,
+            rs_rank: None,
+            moving_averages: HashMap::new(),
+            crossovers: Vec::new(),
+            data_quality: None,
+            adx: None,
+            plus_di: None,
+            minus_di: None,
+            last_split: None,
+            timeframe: Timeframe::Daily,
+            anomalies: Vec::new(),
+            rsi_oversold_used: None,
+            rsi_overbought_used: None,
+            rsi_sector_zscore: None,
+            rsi_industry_zscore: None,
+            note: None,
+            opportunity_state: OpportunityState::None,
+            opportunity_streak: 0,
+            opportunity_since: None,
+            custom: HashMap::new(),
+            bars_used: None,
+        }
+    }
+
+    #[test]
+    fn test_top_opportunities_by_rsi_extremity() {
+        let results = vec![
+            result("MILD", Some(29.0), true),
+            result("HOT", Some(85.0), true),
+            result("COLD", Some(12.0), true),
+            result("NONE", None, true),
+            result("CALM", Some(50.0), false),
+        ];
+        let tickers = |n| top_opportunities(&results, n).into_iter().map(|r| r.ticker).collect::<Vec<_>>();
+        assert_eq!(tickers(10), vec!["COLD", "HOT", "MILD", "NONE"]);
+        assert_eq!(tickers(2), vec!["COLD", "HOT"]);
+    }
+
+    #[test]
+    fn test_zip_holds_report_and_csv() {
+        let report = SessionReport::new(
+            "s1",
+            None,
+            None,
+            vec![result("AAA", Some(25.0), true)],
+            DEFAULT_TOP_OPPORTUNITIES,
+            Utc::now(),
+        );
+        assert!(!report.partial);
+        assert_eq!(report.sectors[0].sector, "Technology");
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(report.to_zip().unwrap())).unwrap();
+        let mut csv = String::new();
+        archive.by_name("results.csv").unwrap().read_to_string(&mut csv).unwrap();
+        let mut lines = csv.lines();
+        assert!(lines.next().unwrap().starts_with("ticker,name,current_price,rsi,"));
+        let row = lines.next().unwrap();
+        assert!(row.starts_with("AAA,\"AAA, Inc.\",10.0,25.0,,"), "{}", row);
+        assert!(row.ends_with(",Oversold - Potential Buy; MACD Bullish"), "{}", row);
+
+        let mut json = String::new();
+        archive.by_name("report.json").unwrap().read_to_string(&mut json).unwrap();
+        let parsed: SessionReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.results.len(), 1);
+        assert_eq!(parsed.top_opportunities[0].ticker, "AAA");
+    }
+}
@@ -0,0 +1,242 @@
+use std::fmt;
+
+use unicode_width::UnicodeWidthStr;
+
+/// Marks a cell cut short to fit its column
+const ELLIPSIS: char = '…';
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+}
+
+/// One table column: its header, its width in terminal cells and how its
+/// cells are aligned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub header: String,
+    pub width: usize,
+    pub align: Align,
+}
+
+impl Column {
+    pub fn new(header: &str, width: usize) -> Self {
+        Self { header: header.to_string(), width, align: Align::Left }
+    }
+
+    pub fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+}
+
+/// Fixed-width text table. Widths are measured in terminal cells, so CJK
+/// characters and emoji (two cells each) keep the columns aligned, and
+/// cells too wide for their column end in "…".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableFormatter {
+    columns: Vec<Column>,
+    rows: Vec<Vec<String>>,
+    max_rows: Option<usize>,
+}
+
+impl TableFormatter {
+    pub fn new(columns: Vec<Column>) -> Self {
+        Self { columns, rows: Vec::new(), max_rows: None }
+    }
+
+    /// Render at most `max` rows, then a line counting the rest
+    pub fn with_max_rows(mut self, max: usize) -> Self {
+        self.max_rows = Some(max);
+        self
+    }
+
+    /// Add a row; missing cells are left blank and extra cells dropped
+    pub fn push_row<I, S>(&mut self, cells: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut row: Vec<String> = cells.into_iter().take(self.columns.len()).map(Into::into).collect();
+        row.resize(self.columns.len(), String::new());
+        self.rows.push(row);
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Total width of a rendered line, separators included
+    pub fn width(&self) -> usize {
+        self.columns.iter().map(|column| column.width).sum::<usize>() + self.columns.len().saturating_sub(1)
+    }
+
+    /// Header, a rule, then the rows, with trailing spaces trimmed
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.push_line(&mut out, self.columns.iter().map(|column| column.header.as_str()));
+        out.push_str(&"-".repeat(self.width()));
+        out.push('\n');
+        for row in self.shown_rows() {
+            self.push_line(&mut out, row.iter().map(String::as_str));
+        }
+        if let Some(more) = self.hidden_rows() {
+            out.push_str(&format!("... and {} more rows\n", more));
+        }
+        out
+    }
+
+    /// GitHub-flavoured Markdown. Cells are not truncated, since the
+    /// renderer wraps them; pipes are escaped and line breaks flattened.
+    pub fn render_markdown(&self) -> String {
+        let mut out = String::new();
+        push_markdown_line(&mut out, self.columns.iter().map(|column| column.header.as_str()));
+        push_markdown_line(
+            &mut out,
+            self.columns.iter().map(|column| match column.align {
+                Align::Left => "---",
+                Align::Right => "---:",
+            }),
+        );
+        for row in self.shown_rows() {
+            push_markdown_line(&mut out, row.iter().map(String::as_str));
+        }
+        if let Some(more) = self.hidden_rows() {
+            // A blank line ends the table; otherwise the note becomes a row
+            out.push_str(&format!("\n_... and {} more rows_\n", more));
+        }
+        out
+    }
+
+    fn shown_rows(&self) -> &[Vec<String>] {
+        &self.rows[..self.rows.len().min(self.max_rows.unwrap_or(usize::MAX))]
+    }
+
+    fn hidden_rows(&self) -> Option<usize> {
+        let more = self.rows.len() - self.shown_rows().len();
+        (more > 0).then_some(more)
+    }
+
+    fn push_line<'a>(&self, out: &mut String, cells: impl Iterator<Item = &'a str>) {
+        let cells: Vec<String> = self.columns.iter().zip(cells).map(|(column, cell)| fit(cell, column.width, column.align)).collect();
+        out.push_str(cells.join(" ").trim_end());
+        out.push('\n');
+    }
+}
+
+impl fmt::Display for TableFormatter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+/// `text` padded or truncated to exactly `width` terminal cells. Control
+/// characters become spaces so a stray newline can't break the row.
+pub fn fit(text: &str, width: usize, align: Align) -> String {
+    let text: String = text.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+    let text = if text.width() <= width { text } else { truncate(&text, width) };
+    let padding = " ".repeat(width.saturating_sub(text.width()));
+    match align {
+        Align::Left => text + &padding,
+        Align::Right => padding + &text,
+    }
+}
+
+/// The longest prefix of `text` that fits in `width - 1` cells, plus "…".
+/// A double-width character that would straddle the edge is dropped, so
+/// the result may be a cell short.
+fn truncate(text: &str, width: usize) -> String {
+    if width == 0 {
+        return String::new();
+    }
+    let mut out = String::new();
+    for c in text.chars() {
+        out.push(c);
+        if out.width() > width - 1 {
+            out.pop();
+            break;
+        }
+    }
+    out.push(ELLIPSIS);
+    out
+}
+
+fn push_markdown_line<'a>(out: &mut String, cells: impl Iterator<Item = &'a str>) {
+    out.push('|');
+    for cell in cells {
+        let cell: String = cell.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+        out.push(' ');
+        out.push_str(&cell.replace('|', "\\|"));
+        out.push_str(" |");
+    }
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names_table() -> TableFormatter {
+        let mut table = TableFormatter::new(vec![
+            Column::new("Symbol", 6),
+            Column::new("Name", 10),
+            Column::new("Price", 8).with_align(Align::Right),
+        ]);
+        table.push_row(["TM", "トヨタ自動車株式会社", "$182.10"]);
+        table.push_row(["ROKT", "🚀 Rocket", "$3.05"]);
+        table.push_row(["ABC", "ABC Corp", "$10.00"]);
+        table
+    }
+
+    #[test]
+    fn test_columns_align_with_cjk_and_emoji() {
+        let table = names_table();
+        let rendered = table.render();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 5);
+        // The right-aligned last column makes every line full width
+        for line in &lines {
+            assert_eq!(line.width(), table.width(), "{:?}", line);
+        }
+        assert_eq!(lines[2], "TM     トヨタ自…   $182.10");
+        assert_eq!(lines[3], "ROKT   🚀 Rocket     $3.05");
+    }
+
+    #[test]
+    fn test_fit_pads_and_truncates_by_display_width() {
+        assert_eq!(fit("abc", 5, Align::Left), "abc  ");
+        assert_eq!(fit("abc", 5, Align::Right), "  abc");
+        assert_eq!(fit("abcdef", 4, Align::Left), "abc…");
+        // 漢字 is four cells; a two-cell character can't straddle the edge
+        assert_eq!(fit("漢字漢字", 4, Align::Left), "漢… ");
+        assert_eq!(fit("a\nb", 3, Align::Left), "a b");
+        assert_eq!(fit("anything", 0, Align::Left), "");
+    }
+
+    #[test]
+    fn test_max_rows_and_short_rows() {
+        let mut table = TableFormatter::new(vec![Column::new("A", 3), Column::new("B", 3)]).with_max_rows(1);
+        table.push_row(["x"]);
+        table.push_row(["y", "z", "dropped"]);
+        assert_eq!(table.render(), "A   B\n-------\nx\n... and 1 more rows\n");
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let mut table = TableFormatter::new(vec![Column::new("Name", 4), Column::new("Price", 5).with_align(Align::Right)])
+            .with_max_rows(2);
+        table.push_row(["A|B Holdings 🚀", "$1.00"]);
+        table.push_row(["トヨタ自動車", "$2.00"]);
+        table.push_row(["Hidden", "$3.00"]);
+        assert_eq!(
+            table.render_markdown(),
+            "| Name | Price |\n| --- | ---: |\n| A\\|B Holdings 🚀 | $1.00 |\n| トヨタ自動車 | $2.00 |\n\n_... and 1 more rows_\n"
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use std::fmt;
+
+use super::table::{Align, Column, TableFormatter};
+use crate::{StockData, TechnicalIndicators, TickerInfo};
+
+/// Compact count such as "1.5M" or "980"
+pub fn format_number(num: f64) -> String {
+    if num >= 1_000_000_000.0 {
+        format!("{:.1}B", num / 1_000_000_000.0)
+    } else if num >= 1_000_000.0 {
+        format!("{:.1}M", num / 1_000_000.0)
+    } else if num >= 1_000.0 {
+        format!("{:.1}K", num / 1_000.0)
+    } else {
+        format!("{:.0}", num)
+    }
+}
+
+/// Screener listing as a table of symbol, name, last sale, change,
+/// market cap and sector; "N/A" where the listing has no value
+pub fn ticker_table(tickers: &[TickerInfo]) -> TableFormatter {
+    let mut table = TableFormatter::new(vec![
+        Column::new("Symbol", 8),
+        Column::new("Name", 30),
+        Column::new("Last Sale", 12).with_align(Align::Right),
+        Column::new("Change%", 10).with_align(Align::Right),
+        Column::new("Market Cap", 15).with_align(Align::Right),
+        Column::new("Sector", 20),
+    ]);
+    for ticker in tickers {
+        let or_na = |value: &Option<String>| value.as_deref().unwrap_or("N/A").to_string();
+        table.push_row([
+            ticker.symbol.clone(),
+            ticker.name.clone(),
+            or_na(&ticker.last_sale),
+            or_na(&ticker.pct_change),
+            or_na(&ticker.market_cap),
+            or_na(&ticker.sector),
+        ]);
+    }
+    table
+}
+
+/// Latest price, indicator values and signals for one symbol
+#[derive(Debug, Clone)]
+pub struct SymbolReport {
+    pub symbol: String,
+    /// Latest candle; `None` when there was no data or no indicators
+    pub latest: Option<StockData>,
+    /// Indicator label and formatted value, e.g. ("RSI(14)", "28.40"),
+    /// for each indicator that has a value
+    pub indicators: Vec<(String, String)>,
+    pub signals: Vec<String>,
+}
+
+impl SymbolReport {
+    pub fn new(symbol: &str, stock_data: &[StockData], indicators: &[TechnicalIndicators], signals: Vec<String>) -> Self {
+        let latest = indicators.last().and(stock_data.last()).cloned();
+        Self {
+            symbol: symbol.to_string(),
+            latest,
+            indicators: indicators.last().map(indicator_lines).unwrap_or_default(),
+            signals,
+        }
+    }
+
+    /// The report as a Markdown section with the price, volume and
+    /// indicators in one table
+    pub fn render_markdown(&self) -> String {
+        let mut out = format!("### Stock Analysis for {}\n", self.symbol);
+        let Some(ref latest) = self.latest else {
+            out.push_str("\nNo data.\n");
+            return out;
+        };
+        out.push_str(&format!("\nLatest data ({}):\n\n", latest.timestamp.format("%Y-%m-%d")));
+        let mut table = TableFormatter::new(vec![Column::new("Indicator", 0), Column::new("Value", 0).with_align(Align::Right)]);
+        table.push_row(["Price".to_string(), format!("${:.2}", latest.close)]);
+        table.push_row(["Volume".to_string(), latest.volume.to_string()]);
+        for (label, value) in &self.indicators {
+            table.push_row([label.as_str(), value.as_str()]);
+        }
+        out.push_str(&table.render_markdown());
+        out.push_str("\n**Signals**\n\n");
+        if self.signals.is_empty() {
+            out.push_str("No strong signals detected\n");
+        }
+        for signal in &self.signals {
+            out.push_str(&format!("- {}\n", signal));
+        }
+        out
+    }
+}
+
+fn indicator_lines(latest: &TechnicalIndicators) -> Vec<(String, String)> {
+    let mut lines = Vec::new();
+    let mut line = |label: &str, value: String| lines.push((label.to_string(), value));
+    if let Some(sma_20) = latest.sma_20 {
+        line("SMA(20)", format!("${:.2}", sma_20));
+    }
+    if let Some(sma_50) = latest.sma_50 {
+        line("SMA(50)", format!("${:.2}", sma_50));
+    }
+    if let Some(rsi) = latest.rsi {
+        line("RSI(14)", format!("{:.2}", rsi));
+    }
+    if let Some((macd, signal, histogram)) = latest.macd {
+        line("MACD", format!("{:.4}, Signal: {:.4}, Histogram: {:.4}", macd, signal, histogram));
+    }
+    if let Some(bb) = &latest.bollinger {
+        line("Bollinger(20, 2)", format!("{:.2} / {:.2} / {:.2}", bb.lower_band, bb.middle_band, bb.upper_band));
+    }
+    if let Some(stoch) = &latest.stochastic {
+        line("Stochastic(14, 3)", format!("%K {:.2}, %D {:.2}", stoch.k_percent, stoch.d_percent));
+    }
+    if let Some(atr) = latest.atr {
+        line("ATR(14)", format!("{:.4}", atr));
+    }
+    if let Some(cci) = latest.cci {
+        line("CCI(20)", format!("{:.2}", cci));
+    }
+    if let Some(obv) = latest.obv {
+        line("OBV", format!("{:.0}", obv));
+    }
+    if let Some(vwap) = latest.vwap {
+        line("VWAP(20)", format!("${:.2}", vwap));
+    }
+    if let Some(ratio) = latest.volume_vs_avg {
+        line("Volume vs 20-day average", format!("{:.2}x", ratio));
+    }
+    lines
+}
+
+impl fmt::Display for SymbolReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "=== Stock Analysis for {} ===", self.symbol)?;
+        let Some(ref latest) = self.latest else {
+            return Ok(());
+        };
+        writeln!(f, "Latest Data ({}):", latest.timestamp.format("%Y-%m-%d"))?;
+        writeln!(f, "  Price: ${:.2}", latest.close)?;
+        writeln!(f, "  Volume: {}", latest.volume)?;
+
+        writeln!(f, "\nTechnical Indicators:")?;
+        for (label, value) in &self.indicators {
+            writeln!(f, "  {}: {}", label, value)?;
+        }
+
+        writeln!(f, "\nSignals:")?;
+        if self.signals.is_empty() {
+            writeln!(f, "  No strong signals detected")?;
+        }
+        for signal in &self.signals {
+            writeln!(f, "  • {}", signal)?;
+        }
+        Ok(())
+    }
+}
+
+/// A symbol the CLI flagged as a buying opportunity
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpportunityReport {
+    pub symbol: String,
+    pub price: f64,
+    pub rsi: f64,
+    pub volume: u64,
+    pub sma_20: Option<f64>,
+    pub sma_50: Option<f64>,
+    /// Percent change from the close seven candles back, counting the
+    /// latest, when there are that many
+    pub week_change_pct: Option<f64>,
+    pub priority: i32,
+}
+
+impl OpportunityReport {
+    /// `None` when `stock_data` is empty
+    pub fn new(symbol: &str, rsi: f64, stock_data: &[StockData], indicators: &TechnicalIndicators, priority: i32) -> Option<Self> {
+        let latest = stock_data.last()?;
+        let week_change_pct = stock_data
+            .len()
+            .checked_sub(7)
+            .map(|i| &stock_data[i])
+            .map(|week_ago| (latest.close - week_ago.close) / week_ago.close * 100.0);
+        Some(Self {
+            symbol: symbol.to_string(),
+            price: latest.close,
+            rsi,
+            volume: latest.volume,
+            sma_20: indicators.sma_20,
+            sma_50: indicators.sma_50,
+            week_change_pct,
+            priority,
+        })
+    }
+
+    /// (icon, label, value) for each line of the report
+    fn lines(&self) -> Vec<(&'static str, &'static str, String)> {
+        let mut lines = vec![
+            ("💰", "Current Price", format!("${:.2}", self.price)),
+            ("📉", "RSI", format!("{:.2}", self.rsi)),
+            ("📊", "Volume", format_number(self.volume as f64)),
+        ];
+        if let Some(sma_20) = self.sma_20 {
+            lines.push(("📈", "SMA(20)", format!("${:.2}", sma_20)));
+        }
+        if let Some(sma_50) = self.sma_50 {
+            lines.push(("📈", "SMA(50)", format!("${:.2}", sma_50)));
+        }
+        if let Some(change) = self.week_change_pct {
+            lines.push(("📅", "7-Day Change", format!("{:.2}%", change)));
+        }
+        lines.push(("⭐", "Priority", self.priority.to_string()));
+        lines
+    }
+
+    /// The report as a Markdown section with one table row per line
+    pub fn render_markdown(&self) -> String {
+        let mut table = TableFormatter::new(vec![Column::new("Metric", 0), Column::new("Value", 0).with_align(Align::Right)]);
+        for (_, label, value) in self.lines() {
+            table.push_row([label.to_string(), value]);
+        }
+        format!("### 🎯 Opportunity: {}\n\n{}", self.symbol, table.render_markdown())
+    }
+}
+
+impl fmt::Display for OpportunityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "🎯 OPPORTUNITY FOUND: {}", self.symbol)?;
+        for (icon, label, value) in self.lines() {
+            writeln!(f, "   {} {}: {}", icon, label, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration};
+    use unicode_width::UnicodeWidthStr;
+
+    fn candles(closes: &[f64]) -> Vec<StockData> {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| StockData {
+                symbol: "TEST".to_string(),
+                timestamp: start + Duration::days(i as i64),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 2_500_000,
+                adjusted_close: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ticker_table_aligns_wide_names() {
+        let listed = |symbol: &str, name: &str, sector: &str| TickerInfo {
+            name: name.to_string(),
+            last_sale: Some("$12.50".to_string()),
+            pct_change: Some("1.20%".to_string()),
+            sector: Some(sector.to_string()),
+            ..TickerInfo::unlisted(symbol)
+        };
+        let tickers = [
+            listed("TM", "トヨタ自動車株式会社 Toyota Motor Corporation ADR", "Consumer Discretionary"),
+            listed("BABA", "阿里巴巴集团 Alibaba Group Holding Limited", "消费者非必需品"),
+            listed("ROKT", "🚀🚀 Rocket Companies", "Finance"),
+        ];
+        let rendered = ticker_table(&tickers).render();
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        // Every row's market cap column ends in the same terminal cell
+        let market_cap_end = 8 + 1 + 30 + 1 + 12 + 1 + 10 + 1 + 15;
+        for line in &lines[2..] {
+            let (before_sector, _) = line.split_at(line.find("N/A").unwrap() + "N/A".len());
+            assert_eq!(before_sector.width(), market_cap_end, "{:?}", line);
+        }
+        assert!(lines[2].contains("トヨタ自動車株式会社 Toyota M…"), "{:?}", lines[2]);
+        assert!(lines[3].ends_with("消费者非必需品"));
+    }
+
+    #[test]
+    fn test_symbol_report() {
+        let data = candles(&[10.0, 11.0]);
+        let indicators = crate::compute_indicators(&data);
+        let report = SymbolReport::new("ABC", &data, &indicators, vec!["Volume spike".to_string()]);
+        let text = report.to_string();
+        assert!(text.starts_with("=== Stock Analysis for ABC ===\nLatest Data (2023-11-15):\n  Price: $11.00\n"), "{}", text);
+        assert!(text.ends_with("\nSignals:\n  • Volume spike\n"), "{}", text);
+
+        let markdown = report.render_markdown();
+        assert!(markdown.contains("| Indicator | Value |\n| --- | ---: |\n| Price | $11.00 |\n"), "{}", markdown);
+        assert!(markdown.ends_with("**Signals**\n\n- Volume spike\n"));
+
+        let empty = SymbolReport::new("NONE", &[], &[], Vec::new());
+        assert_eq!(empty.to_string(), "=== Stock Analysis for NONE ===\n");
+    }
+
+    #[test]
+    fn test_opportunity_report() {
+        let data = candles(&[100.0, 100.0, 100.0, 100.0, 100.0, 100.0, 100.0, 90.0]);
+        let indicators = TechnicalIndicators { sma_20: Some(95.5), ..crate::compute_indicators(&data).pop().unwrap() };
+        let report = OpportunityReport::new("DIP", 28.456, &data, &indicators, 10).unwrap();
+        assert_eq!(
+            report.to_string(),
+            "🎯 OPPORTUNITY FOUND: DIP\n   💰 Current Price: $90.00\n   📉 RSI: 28.46\n   📊 Volume: 2.5M\n   📈 SMA(20): $95.50\n   📅 7-Day Change: -10.00%\n   ⭐ Priority: 10\n"
+        );
+        assert!(report.render_markdown().contains("| 7-Day Change | -10.00% |"));
+        assert!(OpportunityReport::new("NONE", 30.0, &[], &indicators, 1).is_none());
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(2_500_000.0), "2.5M");
+        assert_eq!(format_number(1_200.0), "1.2K");
+        assert_eq!(format_number(3e9), "3.0B");
+        assert_eq!(format_number(999.0), "999");
+    }
+}
@@ -0,0 +1,230 @@
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::StockData;
+
+/// Bar size indicators are computed on, resampled from daily bars
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Timeframe {
+    #[default]
+    Daily,
+    /// Monday to Friday, by ISO week, so the days around New Year belong to
+    /// whichever year's week 1 or 52/53 contains them
+    Weekly,
+    Monthly,
+}
+
+impl Timeframe {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Timeframe::Daily => "daily",
+            Timeframe::Weekly => "weekly",
+            Timeframe::Monthly => "monthly",
+        }
+    }
+
+    /// Key shared by every date in the same period
+    fn period_of(&self, date: NaiveDate) -> (i32, u32) {
+        match self {
+            Timeframe::Daily => (date.year(), date.ordinal()),
+            Timeframe::Weekly => (date.iso_week().year(), date.iso_week().week()),
+            Timeframe::Monthly => (date.year(), date.month()),
+        }
+    }
+
+    /// Last weekday of the period containing `date`
+    fn last_trading_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut last = match self {
+            Timeframe::Daily => return date,
+            Timeframe::Weekly => {
+                let week = date.iso_week();
+                NaiveDate::from_isoywd_opt(week.year(), week.week(), Weekday::Fri).unwrap()
+            }
+            Timeframe::Monthly => {
+                let first = date.with_day(1).unwrap();
+                first.checked_add_months(chrono::Months::new(1)).unwrap().pred_opt().unwrap()
+            }
+        };
+        while matches!(last.weekday(), Weekday::Sat | Weekday::Sun) {
+            last = last.pred_opt().unwrap();
+        }
+        last
+    }
+}
+
+impl std::fmt::Display for Timeframe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Timeframe {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "daily" => Ok(Timeframe::Daily),
+            "weekly" => Ok(Timeframe::Weekly),
+            "monthly" => Ok(Timeframe::Monthly),
+            other => Err(format!("unknown timeframe: {}", other)),
+        }
+    }
+}
+
+/// Aggregate ascending daily bars into bars of `timeframe`: the first open,
+/// the highest high, the lowest low, the last close and the summed volume.
+/// Each bar carries the timestamp of the first daily bar in its period, so
+/// a week starting on a holiday is stamped with its Tuesday. Missing days
+/// are simply absent; the last bar covers whatever part of its period the
+/// data reaches.
+pub fn resample(data: &[StockData], timeframe: Timeframe) -> Vec<StockData> {
+    let mut bars: Vec<StockData> = Vec::new();
+    let mut current = None;
+    for bar in data {
+        let period = timeframe.period_of(bar.timestamp.date_naive());
+        match bars.last_mut() {
+            Some(last) if current == Some(period) => {
+                last.high = last.high.max(bar.high);
+                last.low = last.low.min(bar.low);
+                last.close = bar.close;
+                last.volume = last.volume.saturating_add(bar.volume);
+                last.adjusted_close = bar.adjusted_close;
+            }
+            _ => {
+                bars.push(bar.clone());
+                current = Some(period);
+            }
+        }
+    }
+    bars
+}
+
+/// `resample`, dropping the last bar when `include_partial` is false and
+/// its period is still trading on `today`: a week is complete once its
+/// Friday has passed, a month once its last weekday has
+pub fn resample_as_of(data: &[StockData], timeframe: Timeframe, today: NaiveDate, include_partial: bool) -> Vec<StockData> {
+    let mut bars = resample(data, timeframe);
+    let partial = bars
+        .last()
+        .is_some_and(|bar| timeframe.last_trading_day(bar.timestamp.date_naive()) >= today);
+    if partial && !include_partial {
+        bars.pop();
+    }
+    bars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(year: i32, month: u32, day: u32, close: f64) -> StockData {
+        StockData {
+            symbol: "TEST".to_string(),
+            timestamp: NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(14, 30, 0).unwrap().and_utc(),
+            open: close - 1.0,
+            high: close + 2.0,
+            low: close - 2.0,
+            close,
+            volume: 100,
+            adjusted_close: None,
+        }
+    }
+
+    fn dates(bars: &[StockData]) -> Vec<NaiveDate> {
+        bars.iter().map(|bar| bar.timestamp.date_naive()).collect()
+    }
+
+    #[test]
+    fn test_weekly_bars_aggregate_ohlcv() {
+        let daily = vec![day(2024, 3, 4, 10.0), day(2024, 3, 5, 14.0), day(2024, 3, 6, 8.0), day(2024, 3, 7, 11.0), day(2024, 3, 8, 12.0)];
+        let weekly = resample(&daily, Timeframe::Weekly);
+
+        assert_eq!(weekly.len(), 1);
+        let week = &weekly[0];
+        assert_eq!(week.timestamp, daily[0].timestamp);
+        assert_eq!((week.open, week.high, week.low, week.close, week.volume), (9.0, 16.0, 6.0, 12.0, 500));
+    }
+
+    #[test]
+    fn test_weeks_follow_iso_boundaries_across_new_year() {
+        // Mon 30 Dec 2024 to Fri 3 Jan 2025 is ISO week 1 of 2025, with New
+        // Year's Day missing; the week before ends on Fri 27 Dec 2024
+        let daily = vec![
+            day(2024, 12, 26, 10.0),
+            day(2024, 12, 27, 11.0),
+            day(2024, 12, 30, 12.0),
+            day(2024, 12, 31, 13.0),
+            day(2025, 1, 2, 14.0),
+            day(2025, 1, 3, 15.0),
+            day(2025, 1, 6, 16.0),
+        ];
+        let weekly = resample(&daily, Timeframe::Weekly);
+
+        assert_eq!(
+            dates(&weekly),
+            vec![
+                NaiveDate::from_ymd_opt(2024, 12, 26).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 12, 30).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 1, 6).unwrap(),
+            ]
+        );
+        assert_eq!((weekly[1].open, weekly[1].close, weekly[1].volume), (11.0, 15.0, 400));
+
+        // Thu 1 Jan 2026 falls in ISO week 1 of 2026 with Mon 29 Dec 2025
+        let daily = vec![day(2025, 12, 29, 10.0), day(2026, 1, 2, 12.0)];
+        assert_eq!(resample(&daily, Timeframe::Weekly).len(), 1);
+        // Fri 1 Jan 2021 is in week 53 of 2020
+        let daily = vec![day(2020, 12, 28, 10.0), day(2021, 1, 1, 12.0), day(2021, 1, 4, 13.0)];
+        assert_eq!(resample(&daily, Timeframe::Weekly).len(), 2);
+    }
+
+    #[test]
+    fn test_holiday_weeks_are_stamped_with_their_first_bar() {
+        // Memorial Day, Mon 27 May 2024, has no bar
+        let daily = vec![day(2024, 5, 24, 10.0), day(2024, 5, 28, 11.0), day(2024, 5, 29, 12.0)];
+        let weekly = resample(&daily, Timeframe::Weekly);
+
+        assert_eq!(dates(&weekly)[1], NaiveDate::from_ymd_opt(2024, 5, 28).unwrap());
+        assert_eq!((weekly[1].open, weekly[1].close, weekly[1].volume), (10.0, 12.0, 200));
+    }
+
+    #[test]
+    fn test_monthly_bars_split_on_calendar_months() {
+        let daily = vec![day(2024, 1, 30, 10.0), day(2024, 1, 31, 9.0), day(2024, 2, 1, 12.0), day(2024, 2, 29, 20.0), day(2024, 3, 1, 21.0)];
+        let monthly = resample(&daily, Timeframe::Monthly);
+
+        assert_eq!(monthly.len(), 3);
+        assert_eq!((monthly[0].open, monthly[0].high, monthly[0].low, monthly[0].close), (9.0, 12.0, 7.0, 9.0));
+        assert_eq!((monthly[1].close, monthly[1].volume), (20.0, 200));
+    }
+
+    #[test]
+    fn test_partial_periods() {
+        let daily = vec![day(2024, 3, 4, 10.0), day(2024, 3, 11, 11.0), day(2024, 3, 12, 12.0)];
+        let on = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        // Mid-week the last week is still forming
+        assert_eq!(resample_as_of(&daily, Timeframe::Weekly, on(2024, 3, 13), true).len(), 2);
+        assert_eq!(resample_as_of(&daily, Timeframe::Weekly, on(2024, 3, 13), false).len(), 1);
+        // On its Friday too, but not once the week has closed
+        assert_eq!(resample_as_of(&daily, Timeframe::Weekly, on(2024, 3, 15), false).len(), 1);
+        assert_eq!(resample_as_of(&daily, Timeframe::Weekly, on(2024, 3, 16), false).len(), 2);
+
+        // March 2024 ends on a Sunday, so its last trading day is Friday the 29th
+        assert_eq!(resample_as_of(&daily, Timeframe::Monthly, on(2024, 3, 29), false).len(), 0);
+        assert_eq!(resample_as_of(&daily, Timeframe::Monthly, on(2024, 3, 30), false).len(), 1);
+    }
+
+    #[test]
+    fn test_daily_timeframe_keeps_the_series() {
+        let daily = vec![day(2024, 3, 4, 10.0), day(2024, 3, 5, 11.0)];
+        let resampled = resample(&daily, Timeframe::Daily);
+
+        assert_eq!(dates(&resampled), dates(&daily));
+        assert_eq!("weekly".parse::<Timeframe>(), Ok(Timeframe::Weekly));
+        assert!("1wk".parse::<Timeframe>().is_err());
+    }
+}
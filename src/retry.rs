@@ -0,0 +1,246 @@
+use std::future::Future;
+use std::time::Duration;
+
+use crate::clock::Clock;
+use crate::error::{AnalyzerError, Result};
+
+/// How a failed fetch should be treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchErrorKind {
+    /// The upstream asked us to slow down (HTTP 429)
+    RateLimited,
+    /// Timeouts, dropped connections and 5xx responses
+    Transient,
+    /// Unknown or delisted symbols and unreadable data; retrying won't help
+    Permanent,
+}
+
+impl FetchErrorKind {
+    pub fn is_retryable(self) -> bool {
+        !matches!(self, FetchErrorKind::Permanent)
+    }
+}
+
+/// How the retry loop should treat `error`
+pub fn classify_error(error: &AnalyzerError) -> FetchErrorKind {
+    match error {
+        AnalyzerError::RateLimited { .. } => FetchErrorKind::RateLimited,
+        AnalyzerError::Upstream(_) | AnalyzerError::Timeout(_) | AnalyzerError::Io(_) => FetchErrorKind::Transient,
+        AnalyzerError::SymbolNotFound(_)
+        | AnalyzerError::NoData(_)
+        | AnalyzerError::Parse(_)
+        | AnalyzerError::InvalidRequest(_)
+        | AnalyzerError::Database(_) => FetchErrorKind::Permanent,
+    }
+}
+
+/// Whether the error says the symbol has no data at all (unknown or
+/// delisted), as opposed to a fetch that failed on the way
+pub fn is_delisting_error(error: &AnalyzerError) -> bool {
+    error.is_missing_symbol()
+}
+
+/// Exponential backoff with jitter for retryable fetch errors
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first; 1 disables retries
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Up to this fraction of each delay is added at random so many tickers
+    /// failing together don't retry in lockstep
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    pub fn none() -> Self {
+        Self { max_attempts: 1, ..Self::default() }
+    }
+
+    /// Delay before retry number `retry` (0 for the first retry), given a
+    /// jitter sample in `[0, 1)`
+    pub fn delay(&self, retry: u32, jitter_sample: f64) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        backoff.mul_f64(1.0 + self.jitter * jitter_sample.clamp(0.0, 1.0))
+    }
+
+    /// Run `operation` until it succeeds, fails permanently or runs out of
+    /// attempts, sleeping on `clock` between tries. The operation receives
+    /// the zero-based attempt number. A rate limit that names its own
+    /// retry delay waits at least that long, up to `max_delay`.
+    pub async fn run<T, F, Fut>(&self, clock: &dyn Clock, mut operation: F) -> Result<T>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let error = match operation(attempt).await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+
+            let kind = classify_error(&error);
+            if !kind.is_retryable() || attempt + 1 >= self.max_attempts {
+                return Err(error);
+            }
+
+            let mut delay = self.delay(attempt, rand::random::<f64>());
+            if let AnalyzerError::RateLimited { retry_after: Some(after) } = error {
+                delay = delay.max(after.min(self.max_delay));
+            }
+            tracing::warn!("Fetch attempt {} failed ({:?}): {}; retrying in {:?}", attempt + 1, kind, error, delay);
+            clock.sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::ProviderError;
+    use async_trait::async_trait;
+    use chrono::{DateTime, TimeZone, Utc};
+    use std::sync::Mutex;
+
+    /// Records requested sleeps and returns immediately
+    #[derive(Default)]
+    struct RecordingClock {
+        sleeps: Mutex<Vec<Duration>>,
+    }
+
+    #[async_trait]
+    impl Clock for RecordingClock {
+        fn now(&self) -> DateTime<Utc> {
+            Utc.with_ymd_and_hms(2024, 1, 2, 14, 30, 0).unwrap()
+        }
+
+        async fn sleep_until(&self, deadline: DateTime<Utc>) {
+            self.sleeps.lock().unwrap().push((deadline - self.now()).to_std().unwrap());
+        }
+    }
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(350),
+            jitter: 0.0,
+        }
+    }
+
+    fn rate_limited() -> AnalyzerError {
+        AnalyzerError::RateLimited { retry_after: None }
+    }
+
+    #[test]
+    fn test_backoff_schedule() {
+        let policy = policy(5);
+        let delays: Vec<_> = (0..4).map(|retry| policy.delay(retry, 0.9)).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 350, 350].map(Duration::from_millis).to_vec(),
+            "doubles from the base delay and stops at the cap"
+        );
+
+        let jittered = RetryPolicy { jitter: 0.5, ..policy };
+        assert_eq!(jittered.delay(1, 0.0), Duration::from_millis(200));
+        assert_eq!(jittered.delay(1, 0.5), Duration::from_millis(250));
+        assert!(jittered.delay(1, 0.999) < Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_classify_error() {
+        assert_eq!(classify_error(&rate_limited()), FetchErrorKind::RateLimited);
+        let not_found: AnalyzerError = ProviderError::NotFound { provider: "yahoo".into(), symbol: "GONE".into() }.into();
+        assert_eq!(classify_error(&not_found), FetchErrorKind::Permanent);
+        let upstream = |message: &str| -> AnalyzerError {
+            ProviderError::Upstream { provider: "yahoo".into(), message: message.into() }.into()
+        };
+        assert_eq!(classify_error(&upstream("error sending request")), FetchErrorKind::Transient);
+        assert_eq!(classify_error(&upstream("503 Service Unavailable")), FetchErrorKind::Transient);
+        assert_eq!(classify_error(&upstream("No data found, symbol may be delisted")), FetchErrorKind::Permanent);
+        assert_eq!(classify_error(&upstream("HTTP 429 Too Many Requests")), FetchErrorKind::RateLimited);
+        assert_eq!(classify_error(&AnalyzerError::Parse("bad json".into())), FetchErrorKind::Permanent);
+
+        // Only missing data counts as delisted; an unreadable response does not
+        assert!(is_delisting_error(&not_found));
+        assert!(is_delisting_error(&upstream("No data found, symbol may be delisted")));
+        assert!(!is_delisting_error(&upstream("error sending request")));
+        assert!(!is_delisting_error(&AnalyzerError::Parse("bad json".into())));
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_errors_with_backoff() {
+        let clock = RecordingClock::default();
+        let calls = Mutex::new(0);
+        let result = policy(4)
+            .run(&clock, |attempt| {
+                *calls.lock().unwrap() += 1;
+                async move {
+                    if attempt < 3 {
+                        Err(rate_limited())
+                    } else {
+                        Ok("bars")
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), "bars");
+        assert_eq!(*calls.lock().unwrap(), 4);
+        assert_eq!(*clock.sleeps.lock().unwrap(), [100, 200, 350].map(Duration::from_millis).to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_upstream_retry_after() {
+        let clock = RecordingClock::default();
+        let result: Result<()> = policy(2)
+            .run(&clock, |_| async {
+                Err(AnalyzerError::RateLimited { retry_after: Some(Duration::from_millis(300)) })
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(*clock.sleeps.lock().unwrap(), vec![Duration::from_millis(300)]);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let clock = RecordingClock::default();
+        let result: Result<()> = policy(2).run(&clock, |_| async { Err(rate_limited()) }).await;
+        assert!(result.is_err());
+        assert_eq!(clock.sleeps.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_missing_data() {
+        let clock = RecordingClock::default();
+        let calls = Mutex::new(0);
+        let result: Result<()> = policy(5)
+            .run(&clock, |_| {
+                *calls.lock().unwrap() += 1;
+                async { Err(AnalyzerError::NoData("No data found for symbol, it may be delisted".into())) }
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(*calls.lock().unwrap(), 1);
+        assert!(clock.sleeps.lock().unwrap().is_empty());
+    }
+}
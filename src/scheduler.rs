@@ -0,0 +1,298 @@
+use chrono::{DateTime, Utc};
+use priority_queue::PriorityQueue;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, Notify};
+
+use crate::error::AnalyzerError;
+use crate::symbols::normalize_symbol;
+use crate::web_api::StockAnalysisResult;
+use crate::TickerInfo;
+
+/// Points for being on the watchlist; more than momentum and staleness can add up to
+const WATCHLIST_POINTS: i64 = 1_000_000;
+/// Points per percent of the day's move, either way
+const MOMENTUM_POINTS_PER_PCT: f64 = 1_000.0;
+/// Momentum stops counting past a 100% move
+const MAX_MOMENTUM_POINTS: i64 = 100_000;
+/// Points per minute since the last analysis, capped at a day; never
+/// analyzed counts as a full day
+const MAX_STALENESS_MINUTES: i64 = 24 * 60;
+
+/// How urgently a symbol should be fetched; higher goes first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FetchPriority(i64);
+
+impl FetchPriority {
+    /// Explicit requests, ahead of anything a cycle queues
+    pub const TOP: FetchPriority = FetchPriority(i64::MAX);
+
+    /// Watchlist symbols first, then the biggest movers, with staleness
+    /// breaking ties among quiet names: a 2% mover outranks a symbol last
+    /// analyzed a day ago.
+    pub fn for_ticker(
+        ticker: &TickerInfo,
+        on_watchlist: bool,
+        last_analyzed: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let watchlist = if on_watchlist { WATCHLIST_POINTS } else { 0 };
+        let momentum = ticker
+            .pct_change_f64
+            .map_or(0, |pct| ((pct.abs() * MOMENTUM_POINTS_PER_PCT) as i64).min(MAX_MOMENTUM_POINTS));
+        let staleness = last_analyzed.map_or(MAX_STALENESS_MINUTES, |at| (now - at).num_minutes().clamp(0, MAX_STALENESS_MINUTES));
+        FetchPriority(watchlist + momentum + staleness)
+    }
+}
+
+/// A fetch job's outcome: the analyzed result (`None` when the history was
+/// too short for indicators) or the fetch failure
+pub type FetchResult = Result<Option<StockAnalysisResult>, Arc<AnalyzerError>>;
+
+/// Sent back to whoever queued the job once a worker has finished it
+#[derive(Debug)]
+pub struct FetchOutcome {
+    pub ticker: TickerInfo,
+    pub result: FetchResult,
+}
+
+/// One symbol waiting for a worker to fetch and analyze its history
+#[derive(Debug)]
+pub struct FetchJob {
+    pub ticker: TickerInfo,
+    pub priority: FetchPriority,
+    /// Continuous cycle that queued the job; `None` for explicit requests
+    pub cycle: Option<u64>,
+    reply: mpsc::UnboundedSender<FetchOutcome>,
+}
+
+impl FetchJob {
+    pub fn new(ticker: TickerInfo, priority: FetchPriority, reply: mpsc::UnboundedSender<FetchOutcome>) -> Self {
+        Self { ticker, priority, cycle: None, reply }
+    }
+
+    pub fn for_cycle(mut self, cycle: u64) -> Self {
+        self.cycle = Some(cycle);
+        self
+    }
+
+    /// Report the outcome; a requester that has gone away is ignored
+    pub fn complete(self, result: FetchResult) {
+        let _ = self.reply.send(FetchOutcome { ticker: self.ticker, result });
+    }
+}
+
+/// What `FetchScheduler::enqueue` did with a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Enqueued {
+    Queued,
+    /// The symbol was already waiting; it keeps its place unless the new
+    /// job's priority is higher, and the earlier requester gets the outcome
+    AlreadyQueued,
+}
+
+#[derive(Default)]
+struct QueueState {
+    /// Priority with the enqueue sequence reversed, so equal priorities
+    /// come out first in, first out
+    order: PriorityQueue<String, (FetchPriority, Reverse<u64>)>,
+    jobs: HashMap<String, FetchJob>,
+    next_sequence: u64,
+}
+
+/// Pending symbol fetches shared by the continuous loop, explicit
+/// analyze-now requests and the worker tasks draining them. Each symbol is
+/// queued at most once.
+#[derive(Default)]
+pub struct FetchScheduler {
+    state: Mutex<QueueState>,
+    ready: Notify,
+    workers_started: AtomicBool,
+}
+
+impl FetchScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enqueue(&self, job: FetchJob) -> Enqueued {
+        let symbol = normalize_symbol(&job.ticker.symbol);
+        let mut state = self.state.lock().unwrap();
+        if let Some(queued) = state.jobs.get_mut(&symbol) {
+            if job.priority > queued.priority {
+                queued.priority = job.priority;
+                let priority = job.priority;
+                state.order.change_priority_by(&symbol, |(current, _)| *current = priority);
+            }
+            return Enqueued::AlreadyQueued;
+        }
+
+        let sequence = state.next_sequence;
+        state.next_sequence += 1;
+        state.order.push(symbol.clone(), (job.priority, Reverse(sequence)));
+        state.jobs.insert(symbol, job);
+        drop(state);
+        self.ready.notify_one();
+        Enqueued::Queued
+    }
+
+    /// The most urgent job, if any is waiting
+    pub fn pop(&self) -> Option<FetchJob> {
+        let mut state = self.state.lock().unwrap();
+        let (symbol, _) = state.order.pop()?;
+        state.jobs.remove(&symbol)
+    }
+
+    /// Wait for the most urgent job
+    pub async fn next_job(&self) -> FetchJob {
+        loop {
+            let notified = self.ready.notified();
+            if let Some(job) = self.pop() {
+                return job;
+            }
+            notified.await;
+        }
+    }
+
+    /// Take back every job `cycle` queued that no worker has started, most
+    /// urgent first, so a paused or stopped cycle stops fetching
+    pub fn withdraw_cycle(&self, cycle: u64) -> Vec<FetchJob> {
+        let mut state = self.state.lock().unwrap();
+        let mut withdrawn: Vec<(FetchPriority, Reverse<u64>, FetchJob)> = Vec::new();
+        let symbols: Vec<String> = state
+            .jobs
+            .iter()
+            .filter(|(_, job)| job.cycle == Some(cycle))
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+        for symbol in symbols {
+            if let (Some((_, (priority, sequence))), Some(job)) = (state.order.remove(&symbol), state.jobs.remove(&symbol)) {
+                withdrawn.push((priority, sequence, job));
+            }
+        }
+        withdrawn.sort_by_key(|&(priority, sequence, _)| Reverse((priority, sequence)));
+        withdrawn.into_iter().map(|(_, _, job)| job).collect()
+    }
+
+    pub fn is_queued(&self, symbol: &str) -> bool {
+        self.state.lock().unwrap().jobs.contains_key(&normalize_symbol(symbol))
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// True exactly once, for whoever should spawn the worker pool
+    pub fn claim_workers(&self) -> bool {
+        !self.workers_started.swap(true, Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn ticker(symbol: &str, pct_change: Option<f64>) -> TickerInfo {
+        TickerInfo { pct_change_f64: pct_change, ..TickerInfo::unlisted(symbol) }
+    }
+
+    fn job(symbol: &str, priority: FetchPriority) -> (FetchJob, mpsc::UnboundedReceiver<FetchOutcome>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (FetchJob::new(ticker(symbol, None), priority, tx), rx)
+    }
+
+    fn drain(scheduler: &FetchScheduler) -> Vec<String> {
+        std::iter::from_fn(|| scheduler.pop()).map(|job| job.ticker.symbol).collect()
+    }
+
+    #[test]
+    fn test_priority_ordering() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap();
+        let an_hour_ago = Some(now - chrono::Duration::hours(1));
+        let priority = |symbol: &str, pct: Option<f64>, watched: bool, last: Option<DateTime<Utc>>| {
+            FetchPriority::for_ticker(&ticker(symbol, pct), watched, last, now)
+        };
+
+        let scheduler = FetchScheduler::new();
+        let mut receivers = Vec::new();
+        for (symbol, priority) in [
+            ("QUIET", priority("QUIET", Some(0.1), false, an_hour_ago)),
+            ("STALE", priority("STALE", Some(0.1), false, None)),
+            ("WATCHED", priority("WATCHED", Some(0.0), true, Some(now))),
+            ("MOVER", priority("MOVER", Some(-8.0), false, an_hour_ago)),
+            ("ALSOQUIET", priority("ALSOQUIET", Some(0.1), false, an_hour_ago)),
+            ("NOW", FetchPriority::TOP),
+        ] {
+            let (job, rx) = job(symbol, priority);
+            assert_eq!(scheduler.enqueue(job), Enqueued::Queued);
+            receivers.push(rx);
+        }
+
+        // Equal priorities keep their enqueue order
+        assert_eq!(drain(&scheduler), ["NOW", "WATCHED", "MOVER", "STALE", "QUIET", "ALSOQUIET"]);
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn test_queued_symbol_is_deduplicated() {
+        let scheduler = FetchScheduler::new();
+        let (first, mut first_rx) = job("DUP", FetchPriority(10));
+        let (other, _other_rx) = job("OTHER", FetchPriority(20));
+        assert_eq!(scheduler.enqueue(first), Enqueued::Queued);
+        assert_eq!(scheduler.enqueue(other), Enqueued::Queued);
+
+        // Same symbol, any case: still one job, moved up by the higher priority
+        let (again, mut again_rx) = job("dup", FetchPriority::TOP);
+        assert_eq!(scheduler.enqueue(again), Enqueued::AlreadyQueued);
+        let (lower, _) = job("DUP", FetchPriority(0));
+        assert_eq!(scheduler.enqueue(lower), Enqueued::AlreadyQueued);
+        assert_eq!(scheduler.len(), 2);
+        assert!(scheduler.is_queued("dup"));
+
+        let popped = scheduler.pop().unwrap();
+        assert_eq!((popped.ticker.symbol.as_str(), popped.priority), ("DUP", FetchPriority::TOP));
+        popped.complete(Ok(None));
+        assert_eq!(first_rx.try_recv().unwrap().ticker.symbol, "DUP", "the first requester gets the outcome");
+        assert!(again_rx.try_recv().is_err());
+
+        // Once a worker has taken it, the symbol can be queued again
+        let (requeued, _) = job("DUP", FetchPriority(5));
+        assert_eq!(scheduler.enqueue(requeued), Enqueued::Queued);
+    }
+
+    #[test]
+    fn test_withdraw_cycle_keeps_other_jobs() {
+        let scheduler = FetchScheduler::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        for (symbol, priority) in [("LOW", 1), ("HIGH", 3)] {
+            scheduler.enqueue(FetchJob::new(ticker(symbol, None), FetchPriority(priority), tx.clone()).for_cycle(7));
+        }
+        scheduler.enqueue(FetchJob::new(ticker("ASKED", None), FetchPriority::TOP, tx));
+
+        let withdrawn: Vec<String> = scheduler.withdraw_cycle(7).into_iter().map(|job| job.ticker.symbol).collect();
+        assert_eq!(withdrawn, ["HIGH", "LOW"]);
+        assert_eq!(drain(&scheduler), ["ASKED"]);
+    }
+
+    #[tokio::test]
+    async fn test_next_job_waits_for_enqueue() {
+        let scheduler = Arc::new(FetchScheduler::new());
+        let waiter = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move { scheduler.next_job().await.ticker.symbol }
+        });
+        tokio::task::yield_now().await;
+        let (job, _rx) = job("LATE", FetchPriority(1));
+        scheduler.enqueue(job);
+        assert_eq!(waiter.await.unwrap(), "LATE");
+        assert!(scheduler.claim_workers());
+        assert!(!scheduler.claim_workers());
+    }
+}
@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::web_api::StockAnalysisResult;
+
+/// Fewest names with an RSI a sector or industry needs for its own
+/// statistics; smaller groups are scored against the whole market
+pub const MIN_GROUP_NAMES: usize = 5;
+
+/// What a row of statistics covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupScope {
+    Market,
+    Sector,
+    Industry,
+}
+
+impl GroupScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupScope::Market => "market",
+            GroupScope::Sector => "sector",
+            GroupScope::Industry => "industry",
+        }
+    }
+}
+
+impl std::str::FromStr for GroupScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "market" => Ok(GroupScope::Market),
+            "sector" => Ok(GroupScope::Sector),
+            "industry" => Ok(GroupScope::Industry),
+            other => Err(format!("unknown group scope '{}'", other)),
+        }
+    }
+}
+
+/// RSI and percent change spread across one group of a cycle's results.
+/// Standard deviations are population ones.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupStats {
+    pub scope: GroupScope,
+    /// Sector or industry name; empty for the market
+    pub name: String,
+    /// Results with an RSI
+    pub names: usize,
+    pub rsi_mean: f64,
+    pub rsi_stddev: f64,
+    /// `None` when no result in the group had a percent change
+    pub pct_change_mean: Option<f64>,
+    pub pct_change_stddev: Option<f64>,
+}
+
+impl GroupStats {
+    /// Statistics of `results`, or `None` when none has an RSI
+    fn of<'a>(scope: GroupScope, name: &str, results: impl IntoIterator<Item = &'a StockAnalysisResult>) -> Option<Self> {
+        let (rsi, pct_change): (Vec<f64>, Vec<Option<f64>>) = results
+            .into_iter()
+            .filter_map(|result| result.rsi.filter(|rsi| rsi.is_finite()).map(|rsi| (rsi, result.pct_change)))
+            .unzip();
+        let (rsi_mean, rsi_stddev) = mean_stddev(&rsi)?;
+        let pct_change: Vec<f64> = pct_change.into_iter().flatten().filter(|pct| pct.is_finite()).collect();
+        let pct_change_stats = mean_stddev(&pct_change);
+        Some(Self {
+            scope,
+            name: name.to_string(),
+            names: rsi.len(),
+            rsi_mean,
+            rsi_stddev,
+            pct_change_mean: pct_change_stats.map(|(mean, _)| mean),
+            pct_change_stddev: pct_change_stats.map(|(_, stddev)| stddev),
+        })
+    }
+
+    /// Standard deviations `rsi` lies from the group's mean RSI; 0 when
+    /// every name in the group has the same RSI
+    pub fn rsi_zscore(&self, rsi: f64) -> f64 {
+        match self.rsi_stddev > 0.0 {
+            true => (rsi - self.rsi_mean) / self.rsi_stddev,
+            false => 0.0,
+        }
+    }
+}
+
+/// Mean and population standard deviation, or `None` for no values
+fn mean_stddev(values: &[f64]) -> Option<(f64, f64)> {
+    if values.is_empty() {
+        return None;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / n;
+    Some((mean, variance.sqrt()))
+}
+
+/// One result's RSI relative to its sector's and its industry's
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupZScores {
+    pub ticker: String,
+    pub rsi_sector_zscore: Option<f64>,
+    pub rsi_industry_zscore: Option<f64>,
+}
+
+/// Everything a finished cycle learned about its groups
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CycleGroupStats {
+    /// The market row first, then sectors and industries with at least
+    /// `MIN_GROUP_NAMES` names, by name
+    pub stats: Vec<GroupStats>,
+    pub zscores: Vec<GroupZScores>,
+}
+
+/// Group a cycle's results by sector and by industry and score each
+/// result's RSI against its groups. A result in a group too small for its
+/// own statistics, or without a sector or industry, is scored against the
+/// whole market; one without an RSI gets no scores.
+pub fn group_stats(results: &[StockAnalysisResult]) -> CycleGroupStats {
+    let Some(market) = GroupStats::of(GroupScope::Market, "", results) else {
+        return CycleGroupStats::default();
+    };
+
+    let mut groups: Vec<GroupStats> = Vec::new();
+    for (scope, key) in [
+        (GroupScope::Sector, (|result| result.sector.as_deref()) as fn(&StockAnalysisResult) -> Option<&str>),
+        (GroupScope::Industry, |result| result.industry.as_deref()),
+    ] {
+        let mut members: HashMap<&str, Vec<&StockAnalysisResult>> = HashMap::new();
+        for result in results {
+            if let Some(name) = key(result).filter(|name| !name.trim().is_empty()) {
+                members.entry(name).or_default().push(result);
+            }
+        }
+        let mut stats: Vec<GroupStats> = members
+            .into_iter()
+            .filter_map(|(name, members)| GroupStats::of(scope, name, members))
+            .filter(|stats| stats.names >= MIN_GROUP_NAMES)
+            .collect();
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+        groups.extend(stats);
+    }
+
+    let find = |scope: GroupScope, name: Option<&str>| {
+        name.and_then(|name| groups.iter().find(|stats| stats.scope == scope && stats.name == name)).unwrap_or(&market)
+    };
+    let zscores = results
+        .iter()
+        .filter_map(|result| {
+            let rsi = result.rsi.filter(|rsi| rsi.is_finite())?;
+            Some(GroupZScores {
+                ticker: result.ticker.clone(),
+                rsi_sector_zscore: Some(find(GroupScope::Sector, result.sector.as_deref()).rsi_zscore(rsi)),
+                rsi_industry_zscore: Some(find(GroupScope::Industry, result.industry.as_deref()).rsi_zscore(rsi)),
+            })
+        })
+        .collect();
+
+    let mut stats = vec![market];
+    stats.extend(groups);
+    CycleGroupStats { stats, zscores }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(ticker: &str, sector: &str, rsi: Option<f64>, pct_change: Option<f64>) -> StockAnalysisResult {
+        let mut result: StockAnalysisResult = serde_json::from_value(serde_json::json!({
+            "ticker": ticker, "name": ticker, "current_price": 10.0, "rsi": rsi, "sma_20": null, "sma_50": null,
+            "macd": null, "macd_signal": null, "macd_histogram": null, "volume": null, "pct_change": pct_change,
+            "market_cap": null, "is_opportunity": false, "signals": [], "timestamp": "2024-03-04T21:00:00Z",
+        }))
+        .unwrap();
+        result.sector = Some(sector.to_string()).filter(|sector| !sector.is_empty());
+        result
+    }
+
+    fn zscore(stats: &CycleGroupStats, ticker: &str) -> Option<f64> {
+        stats.zscores.iter().find(|z| z.ticker == ticker).and_then(|z| z.rsi_sector_zscore)
+    }
+
+    #[test]
+    fn test_zscores_against_the_sector() {
+        // Energy: RSI 20, 25, 30, 35, 40 has mean 30 and stddev sqrt(50)
+        let mut results: Vec<StockAnalysisResult> = [20.0, 25.0, 30.0, 35.0, 40.0]
+            .iter()
+            .enumerate()
+            .map(|(i, rsi)| result(&format!("E{}", i), "Energy", Some(*rsi), Some(-2.0 + i as f64)))
+            .collect();
+        // Technology: 60, 60, 60, 60, 70 has mean 62 and stddev 4
+        results.extend([60.0, 60.0, 60.0, 60.0, 70.0].iter().enumerate().map(|(i, rsi)| result(&format!("T{}", i), "Technology", Some(*rsi), None)));
+
+        let stats = group_stats(&results);
+        let energy = stats.stats.iter().find(|s| s.name == "Energy").unwrap();
+        assert_eq!((energy.names, energy.rsi_mean), (5, 30.0));
+        assert!((energy.rsi_stddev - 50f64.sqrt()).abs() < 1e-12);
+        assert_eq!((energy.pct_change_mean, energy.pct_change_stddev), (Some(0.0), Some(2f64.sqrt())));
+        let technology = stats.stats.iter().find(|s| s.name == "Technology").unwrap();
+        assert_eq!((technology.rsi_mean, technology.rsi_stddev), (62.0, 4.0));
+        assert_eq!(technology.pct_change_mean, None);
+
+        // The most oversold energy name is further below its sector than a
+        // technology name 40 points higher is below its own
+        assert!((zscore(&stats, "E0").unwrap() + 10.0 / 50f64.sqrt()).abs() < 1e-12);
+        assert_eq!(zscore(&stats, "E2"), Some(0.0));
+        assert_eq!(zscore(&stats, "T0"), Some(-0.5));
+        assert_eq!(zscore(&stats, "T4"), Some(2.0));
+        assert_eq!(stats.stats[0].scope, GroupScope::Market);
+        assert_eq!(stats.stats[0].names, 10);
+    }
+
+    #[test]
+    fn test_small_and_missing_groups_use_the_market() {
+        // Market RSI: 10, 20, ..., 80 has mean 45 and stddev sqrt(525)
+        let mut results: Vec<StockAnalysisResult> =
+            (1..=5).map(|i| result(&format!("E{}", i), "Energy", Some(i as f64 * 10.0), None)).collect();
+        results.push(result("U1", "Utilities", Some(60.0), None));
+        results.push(result("U2", "Utilities", Some(70.0), None));
+        results.push(result("NONE", "", Some(80.0), None));
+        results.push(result("NORSI", "Energy", None, None));
+
+        let stats = group_stats(&results);
+        assert_eq!(stats.stats.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), ["", "Energy"]);
+        let market = &stats.stats[0];
+        assert_eq!((market.names, market.rsi_mean, market.rsi_stddev), (8, 45.0, 525f64.sqrt()));
+        assert_eq!(zscore(&stats, "U1"), Some(15.0 / 525f64.sqrt()));
+        assert_eq!(zscore(&stats, "NONE"), Some(35.0 / 525f64.sqrt()));
+        assert_eq!(zscore(&stats, "NORSI"), None);
+        // No industries at all: every industry score is the market one
+        assert!(stats.zscores.iter().all(|z| z.rsi_industry_zscore == Some(stats.stats[0].rsi_zscore(
+            results.iter().find(|r| r.ticker == z.ticker).unwrap().rsi.unwrap()
+        ))));
+    }
+
+    #[test]
+    fn test_identical_rsi_and_empty_cycles() {
+        let results: Vec<StockAnalysisResult> = (0..5).map(|i| result(&format!("S{}", i), "Energy", Some(42.0), None)).collect();
+        let stats = group_stats(&results);
+        assert!(stats.zscores.iter().all(|z| z.rsi_sector_zscore == Some(0.0)));
+
+        assert_eq!(group_stats(&[]), CycleGroupStats::default());
+        assert_eq!(group_stats(&[result("X", "Energy", None, None)]), CycleGroupStats::default());
+    }
+}
@@ -1,6 +1,5 @@
 use anyhow::Result;
 use auto_analyser::web_api;
-use tracing_subscriber;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -0,0 +1,400 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+use crate::error::AnalyzerError;
+
+/// Events kept in memory per session; older ones are dropped and counted
+pub const DEFAULT_EVENTS_PER_SESSION: usize = 2_000;
+/// Sessions whose events are kept in memory; the oldest session is
+/// forgotten first (its events stay in the database, when there is one)
+pub const DEFAULT_SESSIONS_KEPT: usize = 50;
+/// Events recorded but not yet drained; a burst past this is dropped
+const PENDING_CAPACITY: usize = 8_192;
+
+/// Severity of a session event. Filters keep events at or above a level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl EventLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventLevel::Debug => "debug",
+            EventLevel::Info => "info",
+            EventLevel::Warn => "warn",
+            EventLevel::Error => "error",
+        }
+    }
+
+    /// This level and every more severe one
+    pub fn and_above(self) -> impl Iterator<Item = EventLevel> {
+        [EventLevel::Debug, EventLevel::Info, EventLevel::Warn, EventLevel::Error]
+            .into_iter()
+            .filter(move |level| *level >= self)
+    }
+}
+
+impl FromStr for EventLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "debug" => Ok(EventLevel::Debug),
+            "info" => Ok(EventLevel::Info),
+            "warn" | "warning" => Ok(EventLevel::Warn),
+            "error" => Ok(EventLevel::Error),
+            other => Err(format!("unknown event level '{}' (expected debug, info, warn or error)", other)),
+        }
+    }
+}
+
+/// What happened to a ticker during a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SessionEventKind {
+    /// Analysis of the ticker began
+    Started,
+    /// Price history arrived; `cache_hit` when it was served from the cache
+    /// rather than fetched for this ticker
+    Fetched { bars: usize, ms: u64, source: String, cache_hit: bool },
+    /// Re-priced from a batched quote without fetching history
+    Requoted { price: f64 },
+    /// Indicators were computed but something about them is off
+    IndicatorWarning { message: String },
+    /// The fetch was throttled, so the ticker was skipped
+    RateLimited { retry_after_ms: Option<u64> },
+    /// Left out without a fetch, e.g. while sitting out failures
+    Skipped { reason: String },
+    /// The fetch or analysis failed; `category` is `AnalyzerError::category`
+    Failed { category: String, message: String },
+    /// A result was produced
+    Analyzed { opportunity: bool, ms: u64 },
+}
+
+impl SessionEventKind {
+    pub fn level(&self) -> EventLevel {
+        match self {
+            SessionEventKind::Started | SessionEventKind::Fetched { .. } => EventLevel::Debug,
+            SessionEventKind::Requoted { .. } | SessionEventKind::Skipped { .. } | SessionEventKind::Analyzed { .. } => {
+                EventLevel::Info
+            }
+            SessionEventKind::IndicatorWarning { .. } | SessionEventKind::RateLimited { .. } => EventLevel::Warn,
+            SessionEventKind::Failed { .. } => EventLevel::Error,
+        }
+    }
+
+    /// The event for a failed fetch: throttling is a skip, anything else a failure
+    pub fn from_error(error: &AnalyzerError) -> Self {
+        match error {
+            AnalyzerError::RateLimited { retry_after } => SessionEventKind::RateLimited {
+                retry_after_ms: retry_after.map(|delay| delay.as_millis() as u64),
+            },
+            error => SessionEventKind::Failed { category: error.category().to_string(), message: error.to_string() },
+        }
+    }
+}
+
+/// One event in a session's log, numbered from 1 within the session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionEvent {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticker: Option<String>,
+    pub level: EventLevel,
+    #[serde(flatten)]
+    pub kind: SessionEventKind,
+}
+
+/// Which events of a session to return: those after `after_seq`, for
+/// `ticker` and at `min_level` or above, at most `limit` of them
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub ticker: Option<String>,
+    pub min_level: Option<EventLevel>,
+    pub after_seq: u64,
+    pub limit: usize,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &SessionEvent) -> bool {
+        event.seq > self.after_seq
+            && self.min_level.is_none_or(|level| event.level >= level)
+            && self
+                .ticker
+                .as_deref()
+                .is_none_or(|ticker| event.ticker.as_deref().is_some_and(|t| t.eq_ignore_ascii_case(ticker)))
+    }
+}
+
+/// A page of a session's events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionEventsPage {
+    pub session_id: String,
+    /// Events evicted from the in-memory log, or lost to a full queue
+    pub dropped: u64,
+    /// Sequence number of the session's newest event
+    pub latest_seq: u64,
+    /// Pass as `after_seq` for the next page; absent on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_after_seq: Option<u64>,
+    pub events: Vec<SessionEvent>,
+}
+
+/// An event recorded but not yet numbered
+struct PendingEvent {
+    session_id: Arc<str>,
+    ticker: Option<Arc<str>>,
+    timestamp: DateTime<Utc>,
+    kind: SessionEventKind,
+}
+
+#[derive(Default)]
+struct SessionLog {
+    events: VecDeque<SessionEvent>,
+    latest_seq: u64,
+    dropped: u64,
+}
+
+#[derive(Default)]
+struct Logs {
+    sessions: HashMap<Arc<str>, SessionLog>,
+    /// Session ids, oldest first, for forgetting whole sessions
+    order: VecDeque<Arc<str>>,
+}
+
+impl Logs {
+    fn session(&mut self, session_id: &Arc<str>, sessions_kept: usize) -> &mut SessionLog {
+        if !self.sessions.contains_key(session_id) {
+            self.order.push_back(session_id.clone());
+            while self.order.len() > sessions_kept {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.sessions.remove(&oldest);
+                }
+            }
+        }
+        self.sessions.entry(session_id.clone()).or_default()
+    }
+}
+
+/// Bounded per-session event logs.
+///
+/// Recording is a non-blocking channel send, cheap enough for the analysis
+/// loops. `drain` numbers the queued events into each session's ring buffer
+/// and hands them back so the caller can store them in one batch.
+pub struct SessionEvents {
+    capacity: usize,
+    sessions_kept: usize,
+    sender: mpsc::Sender<PendingEvent>,
+    receiver: Mutex<mpsc::Receiver<PendingEvent>>,
+    logs: Mutex<Logs>,
+}
+
+impl SessionEvents {
+    pub fn new(capacity: usize, sessions_kept: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(PENDING_CAPACITY);
+        Self {
+            capacity: capacity.max(1),
+            sessions_kept: sessions_kept.max(1),
+            sender,
+            receiver: Mutex::new(receiver),
+            logs: Mutex::new(Logs::default()),
+        }
+    }
+
+    /// Queue an event; when the queue is full it is counted as dropped instead
+    pub fn record(&self, session_id: &Arc<str>, ticker: Option<&Arc<str>>, kind: SessionEventKind) {
+        let event = PendingEvent {
+            session_id: session_id.clone(),
+            ticker: ticker.cloned(),
+            timestamp: Utc::now(),
+            kind,
+        };
+        if self.sender.try_send(event).is_err() {
+            self.logs.lock().unwrap().session(session_id, self.sessions_kept).dropped += 1;
+        }
+    }
+
+    /// Number `session_id`'s next events after `latest_seq`, so a session
+    /// resumed after a restart doesn't reuse the sequence numbers it stored
+    pub fn continue_from(&self, session_id: &str, latest_seq: u64) {
+        let session_id: Arc<str> = session_id.into();
+        let mut logs = self.logs.lock().unwrap();
+        let log = logs.session(&session_id, self.sessions_kept);
+        log.latest_seq = log.latest_seq.max(latest_seq);
+    }
+
+    /// Move queued events into their sessions' logs, returning them along
+    /// with their session ids
+    pub fn drain(&self) -> Vec<(String, SessionEvent)> {
+        let mut receiver = self.receiver.lock().unwrap();
+        let mut logs = self.logs.lock().unwrap();
+        let mut drained = Vec::new();
+        while let Ok(pending) = receiver.try_recv() {
+            let log = logs.session(&pending.session_id, self.sessions_kept);
+            log.latest_seq += 1;
+            let event = SessionEvent {
+                seq: log.latest_seq,
+                timestamp: pending.timestamp,
+                ticker: pending.ticker.map(|ticker| ticker.to_string()),
+                level: pending.kind.level(),
+                kind: pending.kind,
+            };
+            log.events.push_back(event.clone());
+            while log.events.len() > self.capacity {
+                log.events.pop_front();
+                log.dropped += 1;
+            }
+            drained.push((pending.session_id.to_string(), event));
+        }
+        drained
+    }
+
+    /// A page of `session_id`'s events held in memory, if any are. Drain
+    /// first to include the latest.
+    pub fn page(&self, session_id: &str, filter: &EventFilter) -> Option<SessionEventsPage> {
+        let logs = self.logs.lock().unwrap();
+        let log = logs.sessions.get(session_id)?;
+        let mut matching = log.events.iter().filter(|event| filter.matches(event));
+        let events: Vec<SessionEvent> = matching.by_ref().take(filter.limit).cloned().collect();
+        let next_after_seq = match matching.next() {
+            Some(_) => events.last().map(|event| event.seq),
+            None => None,
+        };
+        Some(SessionEventsPage {
+            session_id: session_id.to_string(),
+            dropped: log.dropped,
+            latest_seq: log.latest_seq,
+            next_after_seq,
+            events,
+        })
+    }
+}
+
+/// Records the events of one ticker in one session
+#[derive(Clone)]
+pub struct TickerTrace {
+    events: Arc<SessionEvents>,
+    session_id: Arc<str>,
+    ticker: Arc<str>,
+}
+
+impl TickerTrace {
+    pub fn new(events: Arc<SessionEvents>, session_id: &str, ticker: &str) -> Self {
+        Self { events, session_id: session_id.into(), ticker: ticker.into() }
+    }
+
+    pub fn record(&self, kind: SessionEventKind) {
+        self.events.record(&self.session_id, Some(&self.ticker), kind);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn record(events: &SessionEvents, session: &str, ticker: &str, kind: SessionEventKind) {
+        events.record(&session.into(), Some(&ticker.into()), kind);
+    }
+
+    fn filter(limit: usize) -> EventFilter {
+        EventFilter { limit, ..Default::default() }
+    }
+
+    #[test]
+    fn test_ring_buffer_drops_oldest() {
+        let events = SessionEvents::new(3, 10);
+        for _ in 0..5 {
+            record(&events, "s1", "AAPL", SessionEventKind::Started);
+        }
+        record(&events, "s2", "MSFT", SessionEventKind::Started);
+        assert_eq!(events.drain().len(), 6);
+
+        let page = events.page("s1", &filter(10)).unwrap();
+        assert_eq!(page.events.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert_eq!(page.dropped, 2);
+        assert_eq!(page.latest_seq, 5);
+        assert_eq!(events.page("s2", &filter(10)).unwrap().events[0].seq, 1);
+        assert!(events.page("s3", &filter(10)).is_none());
+    }
+
+    #[test]
+    fn test_oldest_session_is_forgotten() {
+        let events = SessionEvents::new(10, 2);
+        for session in ["a", "b", "c"] {
+            record(&events, session, "AAPL", SessionEventKind::Started);
+        }
+        events.drain();
+        assert!(events.page("a", &filter(10)).is_none());
+        assert!(events.page("c", &filter(10)).is_some());
+    }
+
+    #[test]
+    fn test_filter_and_paginate() {
+        let events = SessionEvents::new(100, 10);
+        record(&events, "s", "AAPL", SessionEventKind::Started);
+        record(&events, "s", "AAPL", SessionEventKind::IndicatorWarning { message: "short".into() });
+        record(&events, "s", "MSFT", SessionEventKind::from_error(&AnalyzerError::RateLimited { retry_after: Some(Duration::from_secs(2)) }));
+        record(&events, "s", "MSFT", SessionEventKind::from_error(&AnalyzerError::NoData("gone".into())));
+        record(&events, "s", "AAPL", SessionEventKind::Analyzed { opportunity: false, ms: 3 });
+        events.drain();
+
+        let warnings = EventFilter { min_level: Some(EventLevel::Warn), ..filter(2) };
+        let first = events.page("s", &warnings).unwrap();
+        assert_eq!(first.events.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(first.next_after_seq, Some(3));
+        let second = events.page("s", &EventFilter { after_seq: 3, ..warnings }).unwrap();
+        assert_eq!(second.events.len(), 1);
+        assert_eq!(second.events[0].level, EventLevel::Error);
+        assert_eq!(second.next_after_seq, None);
+
+        let aapl = EventFilter { ticker: Some("aapl".into()), ..filter(10) };
+        assert_eq!(events.page("s", &aapl).unwrap().events.len(), 3);
+    }
+
+    #[test]
+    fn test_compact_serialization() {
+        let events = SessionEvents::new(10, 10);
+        events.continue_from("s", 41);
+        record(&events, "s", "AAPL", SessionEventKind::Fetched { bars: 250, ms: 12, source: "yahoo".into(), cache_hit: true });
+        record(&events, "s", "GONE", SessionEventKind::from_error(&AnalyzerError::SymbolNotFound("GONE".into())));
+        let drained = events.drain();
+
+        let json = serde_json::to_value(&drained[0].1).unwrap();
+        assert_eq!(json["seq"], 42);
+        assert_eq!(json["event"], "fetched");
+        assert_eq!(json["level"], "debug");
+        assert_eq!(json["bars"], 250);
+        assert_eq!(json["cache_hit"], true);
+        let json = serde_json::to_value(&drained[1].1).unwrap();
+        assert_eq!(json["event"], "failed");
+        assert_eq!(json["category"], "symbol_not_found");
+
+        let parsed: SessionEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, drained[1].1);
+        assert_eq!("warning".parse::<EventLevel>(), Ok(EventLevel::Warn));
+        assert!("loud".parse::<EventLevel>().is_err());
+    }
+
+    #[test]
+    fn test_full_queue_counts_drops() {
+        let events = SessionEvents::new(10, 10);
+        for _ in 0..PENDING_CAPACITY + 3 {
+            record(&events, "s", "AAPL", SessionEventKind::Started);
+        }
+        events.drain();
+        let page = events.page("s", &filter(1)).unwrap();
+        assert_eq!(page.dropped, 3 + PENDING_CAPACITY as u64 - 10);
+        assert_eq!(page.latest_seq, PENDING_CAPACITY as u64);
+    }
+}
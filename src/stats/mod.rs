@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+
+pub mod performance;
+pub mod rsi_thresholds;
+pub mod seasonality;
+
+pub use performance::{align_closes, annualized_volatility_pct, max_drawdown_pct, rebase_to_100, total_return_pct};
+pub use rsi_thresholds::{adaptive_rsi_thresholds, rsi_series, RsiThresholds};
+pub use seasonality::{seasonality, Confidence, PeriodReturns, Seasonality};
+
+/// Summary statistics for a single numeric field across a result set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldStats {
+    pub field: String,
+    pub count: usize,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    pub p10: Option<f64>,
+    pub p50: Option<f64>,
+    pub p90: Option<f64>,
+}
+
+/// Nearest-rank percentile of an ascending-sorted slice.
+///
+/// `pct` is in the range 0-100. The result is always one of the input values:
+/// the element at rank `ceil(pct / 100 * n)` (1-based), clamped to the first
+/// element for `pct == 0`. Returns `None` for an empty slice.
+pub fn percentile(sorted: &[f64], pct: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+
+    let pct = pct.clamp(0.0, 100.0);
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.max(1) - 1])
+}
+
+/// Median of an ascending-sorted slice, averaging the two middle values
+/// when the length is even. Returns `None` for an empty slice.
+pub fn median(sorted: &[f64]) -> Option<f64> {
+    let n = sorted.len();
+    match n {
+        0 => None,
+        _ if n % 2 == 1 => Some(sorted[n / 2]),
+        _ => Some((sorted[n / 2 - 1] + sorted[n / 2]) / 2.0),
+    }
+}
+
+/// Compute count/min/max/mean and p10/p50/p90 for a field.
+///
+/// `None` and non-finite values are ignored. A field with no usable values
+/// is still reported, with `count == 0` and every statistic set to `None`.
+pub fn field_stats<I>(field: &str, values: I) -> FieldStats
+where
+    I: IntoIterator<Item = Option<f64>>,
+{
+    let mut sorted: Vec<f64> = values
+        .into_iter()
+        .flatten()
+        .filter(|v| v.is_finite())
+        .collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let count = sorted.len();
+    let mean = if count > 0 {
+        Some(sorted.iter().sum::<f64>() / count as f64)
+    } else {
+        None
+    };
+
+    FieldStats {
+        field: field.to_string(),
+        count,
+        min: sorted.first().copied(),
+        max: sorted.last().copied(),
+        mean,
+        p10: percentile(&sorted, 10.0),
+        p50: percentile(&sorted, 50.0),
+        p90: percentile(&sorted, 90.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let data: Vec<f64> = (1..=10).map(|v| v as f64).collect();
+
+        assert_eq!(percentile(&data, 0.0), Some(1.0));
+        assert_eq!(percentile(&data, 10.0), Some(1.0));
+        assert_eq!(percentile(&data, 50.0), Some(5.0));
+        assert_eq!(percentile(&data, 90.0), Some(9.0));
+        assert_eq!(percentile(&data, 100.0), Some(10.0));
+    }
+
+    #[test]
+    fn test_percentile_small_and_empty() {
+        assert_eq!(percentile(&[], 50.0), None);
+        assert_eq!(percentile(&[42.0], 10.0), Some(42.0));
+        assert_eq!(percentile(&[1.0, 2.0], 50.0), Some(1.0));
+        assert_eq!(percentile(&[1.0, 2.0], 51.0), Some(2.0));
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[3.0]), Some(3.0));
+        assert_eq!(median(&[1.0, 2.0, 9.0]), Some(2.0));
+        assert_eq!(median(&[1.0, 2.0, 4.0, 9.0]), Some(3.0));
+    }
+
+    #[test]
+    fn test_field_stats_ignores_missing_values() {
+        let stats = field_stats("rsi", vec![Some(30.0), None, Some(70.0), Some(f64::NAN), Some(50.0)]);
+
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.min, Some(30.0));
+        assert_eq!(stats.max, Some(70.0));
+        assert_eq!(stats.mean, Some(50.0));
+        assert_eq!(stats.p50, Some(50.0));
+    }
+
+    #[test]
+    fn test_field_stats_all_null_reports_zero_count() {
+        let stats = field_stats("atr", vec![None, None]);
+
+        assert_eq!(stats.field, "atr");
+        assert_eq!(stats.count, 0);
+        assert!(stats.min.is_none());
+        assert!(stats.mean.is_none());
+        assert!(stats.p90.is_none());
+    }
+}
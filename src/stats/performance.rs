@@ -0,0 +1,135 @@
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::StockData;
+
+/// Trading days in a year, for annualizing daily volatility
+pub const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+/// `closes` rescaled so the first one is 100. Empty when there is no
+/// positive first close to rescale from.
+pub fn rebase_to_100(closes: &[f64]) -> Vec<f64> {
+    match closes.first() {
+        Some(&first) if first > 0.0 => closes.iter().map(|close| close / first * 100.0).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Percent change from the first close to the last
+pub fn total_return_pct(closes: &[f64]) -> Option<f64> {
+    match (closes.first(), closes.last()) {
+        (Some(&first), Some(&last)) if closes.len() >= 2 && first > 0.0 => Some((last / first - 1.0) * 100.0),
+        _ => None,
+    }
+}
+
+/// Largest fall from a running peak to a later close, in percent of the
+/// peak; 0 for a series that never falls
+pub fn max_drawdown_pct(closes: &[f64]) -> Option<f64> {
+    if closes.is_empty() {
+        return None;
+    }
+    let mut peak = f64::MIN;
+    let mut drawdown: f64 = 0.0;
+    for &close in closes {
+        peak = peak.max(close);
+        if peak > 0.0 {
+            drawdown = drawdown.max((peak - close) / peak * 100.0);
+        }
+    }
+    Some(drawdown)
+}
+
+/// Sample standard deviation of the daily returns, scaled to a year of
+/// `TRADING_DAYS_PER_YEAR` and in percent. Needs at least two returns.
+pub fn annualized_volatility_pct(closes: &[f64]) -> Option<f64> {
+    let returns: Vec<f64> = closes
+        .windows(2)
+        .filter(|pair| pair[0] > 0.0)
+        .map(|pair| pair[1] / pair[0] - 1.0)
+        .collect();
+    if returns.len() < 2 {
+        return None;
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    Some(variance.sqrt() * TRADING_DAYS_PER_YEAR.sqrt() * 100.0)
+}
+
+/// The dates every one of `series` has a bar on, oldest first, with each
+/// series' closes on those dates. A series with two bars on one day keeps
+/// the later.
+pub fn align_closes(series: &[&[StockData]]) -> (Vec<NaiveDate>, Vec<Vec<f64>>) {
+    let by_date: Vec<BTreeMap<NaiveDate, f64>> = series
+        .iter()
+        .map(|bars| bars.iter().map(|bar| (bar.timestamp.date_naive(), bar.close)).collect())
+        .collect();
+    let Some((first, rest)) = by_date.split_first() else {
+        return (Vec::new(), Vec::new());
+    };
+    let common: BTreeSet<NaiveDate> = first.keys().filter(|date| rest.iter().all(|closes| closes.contains_key(date))).copied().collect();
+    let closes = by_date.iter().map(|closes| common.iter().map(|date| closes[date]).collect()).collect();
+    (common.into_iter().collect(), closes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, TimeZone, Utc};
+
+    fn bars(days: &[(u32, f64)]) -> Vec<StockData> {
+        days.iter()
+            .map(|&(day, close)| StockData {
+                symbol: "TEST".to_string(),
+                timestamp: Utc.with_ymd_and_hms(2024, 3, day, 21, 0, 0).unwrap(),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 1_000,
+                adjusted_close: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rebase_and_total_return() {
+        assert_eq!(rebase_to_100(&[40.0, 50.0, 30.0]), [100.0, 125.0, 75.0]);
+        assert!(rebase_to_100(&[0.0, 1.0]).is_empty());
+        assert!(rebase_to_100(&[]).is_empty());
+        assert!((total_return_pct(&[50.0, 45.0, 60.0]).unwrap() - 20.0).abs() < 1e-9);
+        assert_eq!(total_return_pct(&[50.0]), None);
+    }
+
+    #[test]
+    fn test_max_drawdown() {
+        assert_eq!(max_drawdown_pct(&[10.0, 11.0, 12.0, 13.0]), Some(0.0));
+        // Halves, then recovers past the old peak
+        assert_eq!(max_drawdown_pct(&[100.0, 80.0, 50.0, 75.0, 120.0]), Some(50.0));
+        // The deeper fall is from the later, higher peak
+        assert_eq!(max_drawdown_pct(&[100.0, 90.0, 200.0, 150.0]), Some(25.0));
+        assert_eq!(max_drawdown_pct(&[]), None);
+    }
+
+    #[test]
+    fn test_annualized_volatility() {
+        assert_eq!(annualized_volatility_pct(&[10.0, 10.0, 10.0]), Some(0.0));
+        // Returns of +10% and -10%: sample deviation of 0.1 * sqrt(2)
+        let volatility = annualized_volatility_pct(&[100.0, 110.0, 99.0]).unwrap();
+        assert!((volatility - 0.1 * 2f64.sqrt() * 252f64.sqrt() * 100.0).abs() < 1e-9, "{}", volatility);
+        assert_eq!(annualized_volatility_pct(&[100.0, 110.0]), None);
+    }
+
+    #[test]
+    fn test_align_keeps_common_dates() {
+        let a = bars(&[(1, 10.0), (4, 11.0), (5, 12.0), (6, 13.0)]);
+        let b = bars(&[(4, 20.0), (5, 21.0), (6, 22.0), (7, 23.0)]);
+        let c = bars(&[(1, 30.0), (4, 31.0), (6, 32.0)]);
+        let (dates, closes) = align_closes(&[&a, &b, &c]);
+
+        let days: Vec<u32> = dates.iter().map(|date| date.day()).collect();
+        assert_eq!(days, [4, 6]);
+        assert_eq!(closes, [vec![11.0, 13.0], vec![20.0, 22.0], vec![31.0, 32.0]]);
+        assert_eq!(align_closes(&[]), (Vec::new(), Vec::new()));
+    }
+}
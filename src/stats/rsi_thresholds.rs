@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use super::percentile;
+use crate::indicators::CustomRSI;
+
+/// Latest RSI values whose distribution sets a symbol's adaptive
+/// thresholds, about a year of daily bars
+pub const ADAPTIVE_RSI_LOOKBACK: usize = 252;
+/// Fewest RSI values adaptive thresholds are computed from; a symbol with
+/// less history keeps the static thresholds
+pub const ADAPTIVE_RSI_MIN_VALUES: usize = 120;
+/// Percentile of a symbol's RSI at or below which it is oversold
+pub const ADAPTIVE_OVERSOLD_PERCENTILE: f64 = 10.0;
+/// Percentile of a symbol's RSI at or above which it is overbought
+pub const ADAPTIVE_OVERBOUGHT_PERCENTILE: f64 = 90.0;
+
+/// RSI levels at which a symbol counts as oversold and overbought
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RsiThresholds {
+    pub oversold: f64,
+    pub overbought: f64,
+}
+
+/// The symbol's own thresholds: the 10th and 90th nearest-rank percentiles
+/// of the last `ADAPTIVE_RSI_LOOKBACK` values of `rsi`, oldest first,
+/// rounded to hundredths. `None` with fewer than `ADAPTIVE_RSI_MIN_VALUES`
+/// finite values among them.
+pub fn adaptive_rsi_thresholds(rsi: &[f64]) -> Option<RsiThresholds> {
+    let window = &rsi[rsi.len().saturating_sub(ADAPTIVE_RSI_LOOKBACK)..];
+    let mut sorted: Vec<f64> = window.iter().copied().filter(|value| value.is_finite()).collect();
+    if sorted.len() < ADAPTIVE_RSI_MIN_VALUES {
+        return None;
+    }
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let round = |value: f64| (value * 100.0).round() / 100.0;
+    Some(RsiThresholds {
+        oversold: round(percentile(&sorted, ADAPTIVE_OVERSOLD_PERCENTILE)?),
+        overbought: round(percentile(&sorted, ADAPTIVE_OVERBOUGHT_PERCENTILE)?),
+    })
+}
+
+/// `period` RSI of every close past the warm-up, oldest first
+pub fn rsi_series(closes: impl IntoIterator<Item = f64>, period: usize) -> Vec<f64> {
+    let mut rsi = CustomRSI::new(period);
+    closes.into_iter().filter_map(|close| rsi.next(close)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thresholds_of_a_uniform_distribution() {
+        // 1.0, 1.5, ... 100.5 shuffled: the 10th percentile is the 20th
+        // smallest value and the 90th the 180th
+        let mut rsi: Vec<f64> = (0..200).map(|i| 1.0 + i as f64 * 0.5).collect();
+        rsi.reverse();
+        rsi.swap(3, 150);
+
+        let thresholds = adaptive_rsi_thresholds(&rsi).unwrap();
+        assert_eq!(thresholds, RsiThresholds { oversold: 10.5, overbought: 90.5 });
+    }
+
+    #[test]
+    fn test_only_the_lookback_counts() {
+        // A year of RSI between 40 and 80 after an older spell at 10
+        let old = vec![10.0; 300];
+        let recent: Vec<f64> = (0..ADAPTIVE_RSI_LOOKBACK).map(|i| 40.0 + (i % 41) as f64).collect();
+        let rsi = [old, recent].concat();
+
+        let thresholds = adaptive_rsi_thresholds(&rsi).unwrap();
+        assert_eq!(thresholds, RsiThresholds { oversold: 43.0, overbought: 76.0 });
+    }
+
+    #[test]
+    fn test_too_little_history_has_no_thresholds() {
+        assert_eq!(adaptive_rsi_thresholds(&[]), None);
+        assert_eq!(adaptive_rsi_thresholds(&[50.0; ADAPTIVE_RSI_MIN_VALUES - 1]), None);
+
+        let mut with_gaps = vec![50.0; ADAPTIVE_RSI_MIN_VALUES];
+        with_gaps[0] = f64::NAN;
+        assert_eq!(adaptive_rsi_thresholds(&with_gaps), None, "NaN values do not count");
+        with_gaps.push(55.0);
+        assert!(adaptive_rsi_thresholds(&with_gaps).is_some());
+    }
+
+    #[test]
+    fn test_rsi_series_skips_the_warm_up() {
+        let closes: Vec<f64> = (0..30).map(|i| 100.0 + i as f64).collect();
+        let rsi = rsi_series(closes, 14);
+        assert_eq!(rsi.len(), 16);
+        assert!(rsi.iter().all(|value| *value == 100.0), "{:?}", rsi);
+    }
+}
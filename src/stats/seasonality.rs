@@ -0,0 +1,289 @@
+use chrono::{Datelike, Weekday};
+use serde::{Deserialize, Serialize};
+
+use super::median;
+use crate::StockData;
+
+/// Complete months of history below which the statistics are marked low confidence
+pub const MIN_CONFIDENT_MONTHS: usize = 12;
+/// Years a calendar month must have been seen before the hint names it
+pub const MIN_HINT_SAMPLES: usize = 2;
+
+const MONTH_NAMES: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November",
+    "December",
+];
+const WEEKDAYS: [Weekday; 5] = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Confidence {
+    /// Under `MIN_CONFIDENT_MONTHS` complete months; every month of the year
+    /// has been seen at most once
+    Low,
+    Normal,
+}
+
+/// Returns of one calendar month or weekday across the history, in percent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeriodReturns {
+    /// "September" or "Monday"
+    pub period: String,
+    pub count: usize,
+    pub average_pct: Option<f64>,
+    pub median_pct: Option<f64>,
+    /// Share of the returns above zero, in percent
+    pub positive_pct: Option<f64>,
+}
+
+impl PeriodReturns {
+    fn new(period: &str, mut returns: Vec<f64>) -> Self {
+        returns.sort_by(|a, b| a.total_cmp(b));
+        let count = returns.len();
+        let share = |n: usize| (count > 0).then(|| n as f64 / count as f64 * 100.0);
+        Self {
+            period: period.to_string(),
+            count,
+            average_pct: (count > 0).then(|| returns.iter().sum::<f64>() / count as f64),
+            median_pct: median(&returns),
+            positive_pct: share(returns.iter().filter(|r| **r > 0.0).count()),
+        }
+    }
+}
+
+/// One calendar month's return, from the previous month's last close to its own
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MonthReturn {
+    pub year: i32,
+    /// 1 to 12
+    pub month: u32,
+    pub return_pct: f64,
+}
+
+/// When in the year and week a symbol has tended to rise or fall
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Seasonality {
+    pub symbol: String,
+    /// Complete months behind the monthly statistics
+    pub months_counted: usize,
+    pub confidence: Confidence,
+    /// January to December
+    pub by_month: Vec<PeriodReturns>,
+    /// Monday to Friday, each bar's close against the bar before
+    pub by_weekday: Vec<PeriodReturns>,
+    /// Share of the complete months that closed higher, in percent
+    pub positive_months_pct: Option<f64>,
+    pub best_month: Option<MonthReturn>,
+    pub worst_month: Option<MonthReturn>,
+}
+
+/// Monthly and weekday return statistics over daily `bars`, oldest first.
+///
+/// A month counts once its return is known from end to end: the first
+/// month has no previous close, and the month of the last bar only counts
+/// once the history reaches the next month. A month following one without
+/// bars is skipped too.
+pub fn seasonality(symbol: &str, bars: &[StockData]) -> Seasonality {
+    // (year, month, last close) of each month with bars
+    let mut month_closes: Vec<(i32, u32, f64)> = Vec::new();
+    for bar in bars {
+        let (year, month) = (bar.timestamp.year(), bar.timestamp.month());
+        match month_closes.last_mut() {
+            Some(last) if (last.0, last.1) == (year, month) => last.2 = bar.close,
+            _ => month_closes.push((year, month, bar.close)),
+        }
+    }
+    let complete = month_closes.len().saturating_sub(1);
+    let months: Vec<MonthReturn> = month_closes[..complete]
+        .windows(2)
+        .filter(|pair| {
+            let (previous, current) = (pair[0], pair[1]);
+            let next_month = if previous.1 == 12 { (previous.0 + 1, 1) } else { (previous.0, previous.1 + 1) };
+            (current.0, current.1) == next_month && previous.2 > 0.0
+        })
+        .map(|pair| MonthReturn {
+            year: pair[1].0,
+            month: pair[1].1,
+            return_pct: (pair[1].2 / pair[0].2 - 1.0) * 100.0,
+        })
+        .collect();
+
+    let mut daily: Vec<(Weekday, f64)> = Vec::new();
+    for pair in bars.windows(2) {
+        if pair[0].close > 0.0 {
+            daily.push((pair[1].timestamp.weekday(), (pair[1].close / pair[0].close - 1.0) * 100.0));
+        }
+    }
+
+    let by_month = MONTH_NAMES
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let returns = months.iter().filter(|m| m.month as usize == i + 1).map(|m| m.return_pct).collect();
+            PeriodReturns::new(name, returns)
+        })
+        .collect();
+    let by_weekday = WEEKDAYS
+        .iter()
+        .map(|&weekday| {
+            let returns = daily.iter().filter(|(day, _)| *day == weekday).map(|(_, r)| *r).collect();
+            PeriodReturns::new(weekday_name(weekday), returns)
+        })
+        .collect();
+
+    let by_return = |a: &&MonthReturn, b: &&MonthReturn| a.return_pct.total_cmp(&b.return_pct);
+    Seasonality {
+        symbol: symbol.to_string(),
+        months_counted: months.len(),
+        confidence: if months.len() < MIN_CONFIDENT_MONTHS { Confidence::Low } else { Confidence::Normal },
+        by_month,
+        by_weekday,
+        positive_months_pct: PeriodReturns::new("", months.iter().map(|m| m.return_pct).collect()).positive_pct,
+        best_month: months.iter().max_by(by_return).copied(),
+        worst_month: months.iter().min_by(by_return).copied(),
+    }
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+impl Seasonality {
+    /// The calendar month with the largest average move, as "historically
+    /// weak in September" or "historically strong in November". Only a
+    /// month seen at least `MIN_HINT_SAMPLES` times whose average and share
+    /// of up years agree counts; low-confidence statistics give no hint.
+    pub fn hint(&self) -> Option<String> {
+        if self.confidence == Confidence::Low {
+            return None;
+        }
+        self.by_month
+            .iter()
+            .filter(|month| month.count >= MIN_HINT_SAMPLES)
+            .filter_map(|month| {
+                let (average, positive) = (month.average_pct?, month.positive_pct?);
+                match (average > 0.0, positive > 50.0, average < 0.0, positive < 50.0) {
+                    (true, true, _, _) => Some(("strong", average, &month.period)),
+                    (_, _, true, true) => Some(("weak", average, &month.period)),
+                    _ => None,
+                }
+            })
+            .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+            .map(|(strength, _, month)| format!("historically {} in {}", strength, month))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+
+    /// Weekday bars from `start` to `end`, each moving by `daily_pct(date)`
+    fn series(start: NaiveDate, end: NaiveDate, daily_pct: impl Fn(NaiveDate) -> f64) -> Vec<StockData> {
+        let mut bars = Vec::new();
+        let mut close = 100.0;
+        let mut date = start;
+        while date <= end {
+            if !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+                close *= 1.0 + daily_pct(date) / 100.0;
+                let timestamp: DateTime<Utc> = Utc.from_utc_datetime(&date.and_hms_opt(14, 30, 0).unwrap());
+                bars.push(StockData {
+                    symbol: "SEAS".to_string(),
+                    timestamp,
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: 1_000,
+                    adjusted_close: None,
+                });
+            }
+            date += Duration::days(1);
+        }
+        bars
+    }
+
+    fn month(seasonality: &Seasonality, name: &str) -> PeriodReturns {
+        seasonality.by_month.iter().find(|m| m.period == name).unwrap().clone()
+    }
+
+    /// Flat except for every September's 10 trading days of -0.5% and every
+    /// November's 10 days of +0.8%
+    fn cyclical(date: NaiveDate) -> f64 {
+        match (date.month(), date.day()) {
+            (9, 1..=14) => -0.5,
+            (11, 1..=14) => 0.8,
+            _ => 0.0,
+        }
+    }
+
+    #[test]
+    fn test_known_winning_and_losing_months() {
+        let bars = series(NaiveDate::from_ymd_opt(2019, 12, 2).unwrap(), NaiveDate::from_ymd_opt(2023, 1, 5).unwrap(), cyclical);
+        let stats = seasonality("SEAS", &bars);
+
+        // January 2020 to December 2022; December 2019 has no previous close
+        // and January 2023 isn't over
+        assert_eq!(stats.months_counted, 36);
+        assert_eq!(stats.confidence, Confidence::Normal);
+        let september = month(&stats, "September");
+        let november = month(&stats, "November");
+        assert_eq!((september.count, november.count), (3, 3));
+        assert!(september.average_pct.unwrap() < -4.0 && september.positive_pct == Some(0.0));
+        assert!(november.average_pct.unwrap() > 7.0 && november.median_pct.unwrap() > 7.0);
+        assert_eq!(month(&stats, "March").average_pct, Some(0.0));
+
+        assert_eq!(stats.best_month.unwrap().month, 11);
+        assert_eq!(stats.worst_month.unwrap().month, 9);
+        // Three Novembers out of 36 months rose
+        assert!((stats.positive_months_pct.unwrap() - 100.0 / 12.0).abs() < 1e-9);
+        assert_eq!(stats.hint().as_deref(), Some("historically strong in November"));
+
+        let bearish = series(NaiveDate::from_ymd_opt(2019, 12, 2).unwrap(), NaiveDate::from_ymd_opt(2023, 1, 5).unwrap(), |date| {
+            if date.month() == 9 { -1.0 } else { cyclical(date) }
+        });
+        assert_eq!(seasonality("SEAS", &bearish).hint().as_deref(), Some("historically weak in September"));
+    }
+
+    #[test]
+    fn test_weekday_returns() {
+        let bars = series(NaiveDate::from_ymd_opt(2022, 1, 3).unwrap(), NaiveDate::from_ymd_opt(2022, 6, 30).unwrap(), |date| {
+            if date.weekday() == Weekday::Mon { 1.0 } else { 0.0 }
+        });
+        let stats = seasonality("SEAS", &bars);
+
+        let names: Vec<&str> = stats.by_weekday.iter().map(|d| d.period.as_str()).collect();
+        assert_eq!(names, ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday"]);
+        let monday = &stats.by_weekday[0];
+        // The first Monday has no bar before it
+        assert_eq!(monday.count, 25);
+        assert!((monday.average_pct.unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(monday.positive_pct, Some(100.0));
+        assert_eq!(stats.by_weekday[3].average_pct, Some(0.0));
+    }
+
+    #[test]
+    fn test_short_history_is_low_confidence() {
+        let bars = series(NaiveDate::from_ymd_opt(2023, 3, 15).unwrap(), NaiveDate::from_ymd_opt(2023, 11, 20).unwrap(), cyclical);
+        let stats = seasonality("SEAS", &bars);
+
+        // April to October
+        assert_eq!(stats.months_counted, 7);
+        assert_eq!(stats.confidence, Confidence::Low);
+        assert_eq!(month(&stats, "September").count, 1);
+        assert_eq!(month(&stats, "November").count, 0);
+        assert_eq!(month(&stats, "November").average_pct, None);
+        assert_eq!(stats.hint(), None);
+
+        let empty = seasonality("NONE", &[]);
+        assert_eq!((empty.months_counted, empty.best_month, empty.positive_months_pct), (0, None, None));
+    }
+}
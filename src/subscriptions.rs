@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::message_log::{BroadcastEvent, BroadcastMessage};
+use crate::symbols::normalize_symbol;
+
+/// A stream a WebSocket client can subscribe to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "topic", rename_all = "snake_case")]
+pub enum Topic {
+    /// Progress of analysis sessions and the continuous cycle, each
+    /// cycle's relative strength ranks once it finishes, and changes to the
+    /// ticker listing
+    Status,
+    /// Results, result changes, intraday quotes and alert firings for one
+    /// symbol
+    Ticker { symbol: String },
+    /// Every result flagged as an opportunity, and every result that
+    /// became or stopped being one
+    Opportunities,
+    /// Every alert firing
+    Alerts,
+}
+
+impl Topic {
+    fn normalized(self) -> Self {
+        match self {
+            Topic::Ticker { symbol } => Topic::Ticker { symbol: normalize_symbol(&symbol) },
+            topic => topic,
+        }
+    }
+}
+
+/// Compact form used in the `/ws?topics=` query: `status`, `opportunities`,
+/// `alerts` or `ticker:AAPL`
+impl FromStr for Topic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().split_once(':') {
+            Some(("ticker", symbol)) if !symbol.trim().is_empty() => Ok(Topic::Ticker { symbol: normalize_symbol(symbol) }),
+            Some(_) => Err(format!("unknown topic '{}'", s)),
+            None => match s.trim() {
+                "status" => Ok(Topic::Status),
+                "opportunities" => Ok(Topic::Opportunities),
+                "alerts" => Ok(Topic::Alerts),
+                other => Err(format!("unknown topic '{}'", other)),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Topic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Topic::Status => write!(f, "status"),
+            Topic::Ticker { symbol } => write!(f, "ticker:{}", symbol),
+            Topic::Opportunities => write!(f, "opportunities"),
+            Topic::Alerts => write!(f, "alerts"),
+        }
+    }
+}
+
+/// Message sent by a WebSocket client, e.g.
+/// `{"action":"subscribe","topic":"ticker","symbol":"AAPL"}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ClientCommand {
+    Subscribe(Topic),
+    Unsubscribe(Topic),
+}
+
+/// Acknowledgement sent back for each client message and query topic
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerReply {
+    Subscribed(Topic),
+    Unsubscribed(Topic),
+    Error { message: String },
+    /// Sent once a `?since_seq=` replay is done. `through_seq` is the last
+    /// sequence number replayed or skipped; `complete` is false when the
+    /// replay buffer no longer held every message after `since_seq`.
+    Replayed { since_seq: u64, through_seq: u64, complete: bool },
+}
+
+/// Topics one WebSocket connection has asked for. A new connection has
+/// none and receives only the status snapshot sent on connect.
+#[derive(Debug, Clone, Default)]
+pub struct Subscriptions {
+    topics: HashSet<Topic>,
+}
+
+impl Subscriptions {
+    /// Parse and apply a client message, returning the reply to send
+    pub fn handle(&mut self, text: &str) -> ServerReply {
+        match serde_json::from_str::<ClientCommand>(text) {
+            Ok(command) => self.apply(command),
+            Err(e) => ServerReply::Error { message: format!("invalid command: {}", e) },
+        }
+    }
+
+    pub fn apply(&mut self, command: ClientCommand) -> ServerReply {
+        match command {
+            ClientCommand::Subscribe(topic) => {
+                let topic = topic.normalized();
+                if matches!(&topic, Topic::Ticker { symbol } if symbol.is_empty()) {
+                    return ServerReply::Error { message: "symbol must not be empty".to_string() };
+                }
+                self.topics.insert(topic.clone());
+                ServerReply::Subscribed(topic)
+            }
+            ClientCommand::Unsubscribe(topic) => {
+                let topic = topic.normalized();
+                self.topics.remove(&topic);
+                ServerReply::Unsubscribed(topic)
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.topics.is_empty()
+    }
+
+    /// Whether `message` belongs to any subscribed topic
+    pub fn wants(&self, message: &BroadcastMessage) -> bool {
+        match message {
+            BroadcastMessage::Status(_)
+            | BroadcastMessage::Event(BroadcastEvent::Ranked(_))
+            | BroadcastMessage::Event(BroadcastEvent::TickerDirectoryUpdate(_)) => {
+                self.topics.contains(&Topic::Status)
+            }
+            BroadcastMessage::Event(BroadcastEvent::TickerUpdate(result)) => self.follows(&result.ticker),
+            BroadcastMessage::Event(BroadcastEvent::Opportunity(_)) => self.topics.contains(&Topic::Opportunities),
+            BroadcastMessage::Event(BroadcastEvent::ResultDelta(delta)) => {
+                let flipped = delta.became_opportunity || delta.lost_opportunity;
+                (flipped && self.topics.contains(&Topic::Opportunities)) || self.follows(&delta.ticker)
+            }
+            BroadcastMessage::Event(BroadcastEvent::Quote(quote)) => self.follows(&quote.symbol),
+            BroadcastMessage::Event(BroadcastEvent::AlertTriggered(trigger)) => {
+                self.topics.contains(&Topic::Alerts) || self.follows(&trigger.symbol)
+            }
+        }
+    }
+
+    fn follows(&self, symbol: &str) -> bool {
+        self.topics.contains(&Topic::Ticker { symbol: normalize_symbol(symbol) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_wire_format() {
+        let mut subscriptions = Subscriptions::default();
+
+        let reply = subscriptions.handle(r#"{"action":"subscribe","topic":"ticker","symbol":"aapl"}"#);
+        assert_eq!(reply, ServerReply::Subscribed(Topic::Ticker { symbol: "AAPL".to_string() }));
+        assert_eq!(
+            serde_json::to_value(&reply).unwrap(),
+            serde_json::json!({"type": "subscribed", "topic": "ticker", "symbol": "AAPL"})
+        );
+
+        assert_eq!(
+            subscriptions.handle(r#"{"action":"subscribe","topic":"status"}"#),
+            ServerReply::Subscribed(Topic::Status)
+        );
+        assert!(matches!(subscriptions.handle(r#"{"action":"subscribe","topic":"news"}"#), ServerReply::Error { .. }));
+        assert!(matches!(subscriptions.handle("not json"), ServerReply::Error { .. }));
+
+        subscriptions.handle(r#"{"action":"unsubscribe","topic":"ticker","symbol":"AAPL"}"#);
+        subscriptions.handle(r#"{"action":"unsubscribe","topic":"status"}"#);
+        assert!(subscriptions.is_empty());
+    }
+
+    #[test]
+    fn test_query_topics() {
+        assert_eq!("status".parse::<Topic>(), Ok(Topic::Status));
+        assert_eq!(" opportunities ".parse::<Topic>(), Ok(Topic::Opportunities));
+        assert_eq!("ticker:brk.b".parse::<Topic>(), Ok(Topic::Ticker { symbol: "BRK.B".to_string() }));
+        assert!("ticker:".parse::<Topic>().is_err());
+        assert!("news".parse::<Topic>().is_err());
+        assert!("status:AAPL".parse::<Topic>().is_err());
+        for topic in [Topic::Status, Topic::Ticker { symbol: "AAPL".to_string() }, Topic::Opportunities, Topic::Alerts] {
+            assert_eq!(topic.to_string().parse::<Topic>(), Ok(topic));
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use crate::providers::analyst::is_stale_upside;
+use crate::web_api::StockAnalysisResult;
+
+/// Sentence templates used to build result summaries. Placeholders in braces
+/// are filled from the result; a template is only chosen when every value it
+/// needs is present, so no sentence ever mentions missing data.
+#[derive(Debug, Clone, Copy)]
+enum Template {
+    PriceMove,
+    PriceFlat,
+    PriceOnly,
+    MoveOnly,
+    NoPrice,
+    RsiTrend,
+    RsiOnly,
+    TrendOnly,
+    Macd,
+    Target,
+}
+
+impl Template {
+    fn text(self) -> &'static str {
+        match self {
+            Template::PriceMove => "{ticker} trades at ${price}, {direction} {pct}% on the day.",
+            Template::PriceFlat => "{ticker} trades at ${price}, unchanged on the day.",
+            Template::PriceOnly => "{ticker} trades at ${price}.",
+            Template::MoveOnly => "{ticker} is {direction} {pct}% on the day.",
+            Template::NoPrice => "{ticker} has no recent price data.",
+            Template::RsiTrend => "RSI is {rsi} ({band}) with the price {distance}% {side} its {period}-day average.",
+            Template::RsiOnly => "RSI is {rsi} ({band}).",
+            Template::TrendOnly => "The price is {distance}% {side} its {period}-day average.",
+            Template::Macd => "MACD is {bias}.",
+            Template::Target => "The mean analyst target of ${target} implies {pct}% {direction}.",
+        }
+    }
+
+    fn render(self, values: &[(&str, String)]) -> String {
+        values.iter().fold(self.text().to_string(), |text, (key, value)| {
+            text.replace(&format!("{{{}}}", key), value)
+        })
+    }
+}
+
+/// RSI descriptions, checked in order: the first band whose upper bound the
+/// value is below wins
+const RSI_BANDS: &[(f64, &str)] = &[
+    (30.0, "oversold"),
+    (45.0, "weak"),
+    (55.0, "neutral"),
+    (70.0, "firm"),
+    (f64::INFINITY, "overbought"),
+];
+
+/// Daily moves smaller than this (in percent) are described as unchanged
+const FLAT_MOVE_PCT: f64 = 0.05;
+
+fn rsi_band(rsi: f64) -> &'static str {
+    RSI_BANDS
+        .iter()
+        .find(|(upper, _)| rsi < *upper)
+        .map(|(_, band)| *band)
+        .unwrap_or("overbought")
+}
+
+/// Turn a result into a short plain-English summary (one to four sentences).
+///
+/// Only fields already on the result are used and the wording is fully
+/// determined by the templates above, so the same result always produces the
+/// same text.
+pub fn generate_summary(result: &StockAnalysisResult) -> String {
+    let mut sentences = Vec::new();
+    let ticker = result.ticker.clone();
+
+    let price = result.current_price.map(|p| format!("{:.2}", p));
+    let movement = result.pct_change.map(|pct| {
+        let direction = if pct >= 0.0 { "up" } else { "down" };
+        (pct.abs() < FLAT_MOVE_PCT, direction.to_string(), format!("{:.1}", pct.abs()))
+    });
+
+    sentences.push(match (price, movement) {
+        (Some(price), Some((true, _, _))) => {
+            Template::PriceFlat.render(&[("ticker", ticker), ("price", price)])
+        }
+        (Some(price), Some((false, direction, pct))) => Template::PriceMove.render(&[
+            ("ticker", ticker),
+            ("price", price),
+            ("direction", direction),
+            ("pct", pct),
+        ]),
+        (Some(price), None) => Template::PriceOnly.render(&[("ticker", ticker), ("price", price)]),
+        (None, Some((_, direction, pct))) => {
+            Template::MoveOnly.render(&[("ticker", ticker), ("direction", direction), ("pct", pct)])
+        }
+        (None, None) => Template::NoPrice.render(&[("ticker", ticker)]),
+    });
+
+    let rsi = result
+        .rsi
+        .map(|rsi| (format!("{:.0}", rsi), rsi_band(rsi).to_string()));
+    let trend = result.current_price.and_then(|price| {
+        let (average, period) = match (result.sma_50, result.sma_20) {
+            (Some(sma), _) => (sma, "50"),
+            (None, Some(sma)) => (sma, "20"),
+            (None, None) => return None,
+        };
+        if average <= 0.0 {
+            return None;
+        }
+        let distance = (price - average) / average * 100.0;
+        let side = if distance >= 0.0 { "above" } else { "below" };
+        Some((format!("{:.1}", distance.abs()), side.to_string(), period.to_string()))
+    });
+
+    match (rsi, trend) {
+        (Some((rsi, band)), Some((distance, side, period))) => sentences.push(Template::RsiTrend.render(&[
+            ("rsi", rsi),
+            ("band", band),
+            ("distance", distance),
+            ("side", side),
+            ("period", period),
+        ])),
+        (Some((rsi, band)), None) => sentences.push(Template::RsiOnly.render(&[("rsi", rsi), ("band", band)])),
+        (None, Some((distance, side, period))) => sentences.push(Template::TrendOnly.render(&[
+            ("distance", distance),
+            ("side", side),
+            ("period", period),
+        ])),
+        (None, None) => {}
+    }
+
+    if let Some(histogram) = result.macd_histogram {
+        let bias = if histogram > 0.0 {
+            "bullish"
+        } else if histogram < 0.0 {
+            "bearish"
+        } else {
+            "flat"
+        };
+        sentences.push(Template::Macd.render(&[("bias", bias.to_string())]));
+    }
+
+    // Stale targets are flagged in the signals instead of being repeated here
+    if let (Some(target), Some(upside)) = (result.analyst_target, result.upside_to_target_pct) {
+        if !is_stale_upside(upside) {
+            let direction = if upside >= 0.0 { "upside" } else { "downside" };
+            sentences.push(Template::Target.render(&[
+                ("target", format!("{:.2}", target)),
+                ("pct", format!("{:.1}", upside.abs())),
+                ("direction", direction.to_string()),
+            ]));
+        }
+    }
+
+    sentences.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opportunity_state::OpportunityState;
+    use crate::resample::Timeframe;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    fn empty_result(ticker: &str) -> StockAnalysisResult {
+        StockAnalysisResult {
+            ticker: ticker.to_string(),
+            name: format!("{} Inc", ticker),
+            current_price: None,
+            rsi: None,
+            sma_20: None,
+            sma_50: None,
+            macd: None,
+            macd_signal: None,
+            macd_histogram: None,
+            volume: None,
+            pct_change: None,
+            market_cap: None,
+            sector: None,
+            industry: None,
+            exchange: None,
+            is_opportunity: false,
+            signals: Vec::new(),
+            timestamp: Utc::now(),
+            data_source: None,
+            data_fetched_at: None,
+            summary: None,
+            analyst_target: None,
+            upside_to_target_pct: None,
+            obv: None,
+            vwap: None,
+            volume_vs_avg: None,
+            high_52w: None,
+            low_52w: None,
+            pct_from_high: None,
+            pct_from_low: None,
+            range_window_complete: false,
+            rs_1w: None,
+            rs_1m: None,
+            rs_3m: None,
+            rs_rank: None,
+            moving_averages: HashMap::new(),
+            crossovers: Vec::new(),
+            data_quality: None,
+            adx: None,
+            plus_di: None,
+            minus_di: None,
+            last_split: None,
+            timeframe: Timeframe::Daily,
+            anomalies: Vec::new(),
+            rsi_oversold_used: None,
+            rsi_overbought_used: None,
+            rsi_sector_zscore: None,
+            rsi_industry_zscore: None,
+            note: None,
+            opportunity_state: OpportunityState::None,
+            opportunity_streak: 0,
+            opportunity_since: None,
+            custom: HashMap::new(),
+            bars_used: None,
+        }
+    }
+
+    #[test]
+    fn test_full_summary() {
+        let result = StockAnalysisResult {
+            current_price: Some(96.0),
+            pct_change: Some(-3.31),
+            rsi: Some(28.4),
+            sma_20: Some(99.0),
+            sma_50: Some(100.0),
+            macd: Some(-1.2),
+            macd_signal: Some(-0.8),
+            macd_histogram: Some(-0.4),
+            ..empty_result("AMD")
+        };
+
+        assert_eq!(
+            generate_summary(&result),
+            "AMD trades at $96.00, down 3.3% on the day. \
+             RSI is 28 (oversold) with the price 4.0% below its 50-day average. \
+             MACD is bearish."
+        );
+    }
+
+    #[test]
+    fn test_summary_with_every_sentence() {
+        let result = StockAnalysisResult {
+            current_price: Some(96.0),
+            pct_change: Some(-3.31),
+            rsi: Some(28.4),
+            sma_50: Some(100.0),
+            macd_histogram: Some(-0.4),
+            analyst_target: Some(120.0),
+            upside_to_target_pct: Some(25.0),
+            ..empty_result("AMD")
+        };
+
+        assert_eq!(
+            generate_summary(&result),
+            "AMD trades at $96.00, down 3.3% on the day. \
+             RSI is 28 (oversold) with the price 4.0% below its 50-day average. \
+             MACD is bearish. \
+             The mean analyst target of $120.00 implies 25.0% upside."
+        );
+    }
+
+    #[test]
+    fn test_summary_falls_back_to_20_day_average() {
+        let result = StockAnalysisResult {
+            current_price: Some(110.0),
+            pct_change: Some(0.01),
+            rsi: Some(72.0),
+            sma_20: Some(100.0),
+            macd_histogram: Some(0.3),
+            ..empty_result("NVDA")
+        };
+
+        assert_eq!(
+            generate_summary(&result),
+            "NVDA trades at $110.00, unchanged on the day. \
+             RSI is 72 (overbought) with the price 10.0% above its 20-day average. \
+             MACD is bullish."
+        );
+    }
+
+    #[test]
+    fn test_summary_with_partial_fields() {
+        let result = StockAnalysisResult {
+            pct_change: Some(2.0),
+            rsi: Some(50.0),
+            ..empty_result("MSFT")
+        };
+        assert_eq!(generate_summary(&result), "MSFT is up 2.0% on the day. RSI is 50 (neutral).");
+
+        let result = StockAnalysisResult {
+            current_price: Some(12.5),
+            sma_50: Some(10.0),
+            ..empty_result("F")
+        };
+        assert_eq!(
+            generate_summary(&result),
+            "F trades at $12.50. The price is 25.0% above its 50-day average."
+        );
+    }
+
+    #[test]
+    fn test_summary_with_analyst_target() {
+        let result = StockAnalysisResult {
+            current_price: Some(190.0),
+            analyst_target: Some(209.0),
+            upside_to_target_pct: Some(10.0),
+            ..empty_result("AAPL")
+        };
+        assert_eq!(
+            generate_summary(&result),
+            "AAPL trades at $190.00. The mean analyst target of $209.00 implies 10.0% upside."
+        );
+
+        let stale = StockAnalysisResult {
+            current_price: Some(4.0),
+            analyst_target: Some(40.0),
+            upside_to_target_pct: Some(900.0),
+            ..result
+        };
+        assert_eq!(generate_summary(&stale), "AAPL trades at $4.00.");
+    }
+
+    #[test]
+    fn test_summary_with_no_data() {
+        assert_eq!(generate_summary(&empty_result("XYZ")), "XYZ has no recent price data.");
+    }
+
+    #[test]
+    fn test_rsi_bands() {
+        assert_eq!(rsi_band(10.0), "oversold");
+        assert_eq!(rsi_band(30.0), "weak");
+        assert_eq!(rsi_band(50.0), "neutral");
+        assert_eq!(rsi_band(69.9), "firm");
+        assert_eq!(rsi_band(70.0), "overbought");
+    }
+}
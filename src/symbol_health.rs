@@ -0,0 +1,165 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::error::AnalyzerError;
+use crate::retry::is_delisting_error;
+
+/// Consecutive failures before the continuous loop starts skipping a symbol
+pub const SKIP_AFTER_FAILURES: u32 = 3;
+/// Cycles skipped at the `SKIP_AFTER_FAILURES`th failure; doubles with each
+/// failure after that
+pub const BASE_SKIP_CYCLES: u64 = 1;
+/// Longest skip, applied straight away to symbols that look delisted
+pub const MAX_SKIP_CYCLES: u64 = 24;
+
+/// Failure bookkeeping for a symbol the continuous loop could not fetch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolHealth {
+    pub symbol: String,
+    pub consecutive_failures: u32,
+    pub last_error: String,
+    pub last_attempt: DateTime<Utc>,
+    /// First cycle that tries the symbol again; `None` while it is still
+    /// attempted every cycle
+    pub skip_until_cycle: Option<u64>,
+}
+
+impl SymbolHealth {
+    pub fn is_skipped(&self, cycle: u64) -> bool {
+        self.skip_until_cycle.is_some_and(|until| cycle < until)
+    }
+}
+
+/// Symbols that failed their last fetch in the continuous loop
+#[derive(Debug, Clone, Default)]
+pub struct FailedSymbols {
+    entries: HashMap<String, SymbolHealth>,
+}
+
+impl FailedSymbols {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_entries(entries: Vec<SymbolHealth>) -> Self {
+        Self {
+            entries: entries.into_iter().map(|entry| (entry.symbol.clone(), entry)).collect(),
+        }
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&SymbolHealth> {
+        self.entries.get(symbol)
+    }
+
+    /// Whether `cycle` should leave `symbol` alone
+    pub fn should_skip(&self, symbol: &str, cycle: u64) -> bool {
+        self.entries.get(symbol).is_some_and(|entry| entry.is_skipped(cycle))
+    }
+
+    /// Count a failed fetch in `cycle` and return the updated entry. Errors
+    /// saying the symbol has no data skip it for the longest stretch at once.
+    pub fn record_failure(&mut self, symbol: &str, error: &AnalyzerError, cycle: u64, now: DateTime<Utc>) -> SymbolHealth {
+        let entry = self.entries.entry(symbol.to_string()).or_insert_with(|| SymbolHealth {
+            symbol: symbol.to_string(),
+            consecutive_failures: 0,
+            last_error: String::new(),
+            last_attempt: now,
+            skip_until_cycle: None,
+        });
+        entry.consecutive_failures += 1;
+        entry.last_error = error.to_string();
+        entry.last_attempt = now;
+
+        let skip = if is_delisting_error(error) {
+            Some(MAX_SKIP_CYCLES)
+        } else {
+            skip_cycles(entry.consecutive_failures)
+        };
+        entry.skip_until_cycle = skip.map(|cycles| cycle + 1 + cycles);
+        entry.clone()
+    }
+
+    /// Forget the symbol's failures, after a successful fetch or a manual
+    /// reset; returns the entry if it had one
+    pub fn clear(&mut self, symbol: &str) -> Option<SymbolHealth> {
+        self.entries.remove(symbol)
+    }
+
+    /// Symbols `cycle` skips, most failures first
+    pub fn skipped(&self, cycle: u64) -> Vec<SymbolHealth> {
+        let mut skipped: Vec<SymbolHealth> = self.entries.values().filter(|entry| entry.is_skipped(cycle)).cloned().collect();
+        skipped.sort_by(|a, b| {
+            b.consecutive_failures
+                .cmp(&a.consecutive_failures)
+                .then_with(|| a.symbol.cmp(&b.symbol))
+        });
+        skipped
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Cycles to skip after `failures` consecutive failures, if any
+fn skip_cycles(failures: u32) -> Option<u64> {
+    let doublings = failures.checked_sub(SKIP_AFTER_FAILURES)?;
+    Some(BASE_SKIP_CYCLES.saturating_mul(1u64 << doublings.min(32)).min(MAX_SKIP_CYCLES))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::ProviderError;
+
+    fn timeout() -> AnalyzerError {
+        ProviderError::Upstream { provider: "yahoo".into(), message: "operation timed out".into() }.into()
+    }
+
+    #[test]
+    fn test_skips_grow_after_repeated_failures() {
+        let now = Utc::now();
+        let mut failed = FailedSymbols::new();
+
+        // The first failures are retried every cycle
+        assert_eq!(failed.record_failure("FLAKY", &timeout(), 1, now).skip_until_cycle, None);
+        assert_eq!(failed.record_failure("FLAKY", &timeout(), 2, now).skip_until_cycle, None);
+        assert!(!failed.should_skip("FLAKY", 3));
+
+        // Third failure in cycle 3: skip cycle 4, retry in 5
+        assert_eq!(failed.record_failure("FLAKY", &timeout(), 3, now).skip_until_cycle, Some(5));
+        assert!(failed.should_skip("FLAKY", 4));
+        assert!(!failed.should_skip("FLAKY", 5));
+
+        // Then two cycles, four, ... up to the cap
+        assert_eq!(failed.record_failure("FLAKY", &timeout(), 5, now).skip_until_cycle, Some(8));
+        assert_eq!(failed.record_failure("FLAKY", &timeout(), 8, now).skip_until_cycle, Some(13));
+        for cycle in 13..30 {
+            failed.record_failure("FLAKY", &timeout(), cycle, now);
+        }
+        assert_eq!(failed.get("FLAKY").unwrap().skip_until_cycle, Some(30 + MAX_SKIP_CYCLES));
+        assert_eq!(failed.skipped(30).len(), 1);
+
+        // A success clears the record
+        assert!(failed.clear("FLAKY").is_some());
+        assert!(!failed.should_skip("FLAKY", 30));
+        assert!(failed.is_empty());
+    }
+
+    #[test]
+    fn test_delisted_symbols_are_skipped_at_once() {
+        let gone: AnalyzerError = ProviderError::NotFound { provider: "yahoo".into(), symbol: "GONE".into() }.into();
+        let mut failed = FailedSymbols::new();
+
+        let entry = failed.record_failure("GONE", &gone, 7, Utc::now());
+        assert_eq!(entry.consecutive_failures, 1);
+        assert_eq!(entry.skip_until_cycle, Some(8 + MAX_SKIP_CYCLES));
+        assert_eq!(entry.last_error, "symbol not found: GONE");
+        assert_eq!(failed.skipped(8).into_iter().map(|e| e.symbol).collect::<Vec<_>>(), vec!["GONE"]);
+    }
+}
@@ -0,0 +1,268 @@
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::symbols::normalize_symbol;
+use crate::TickerInfo;
+
+/// Quiet period after a change to the universe file before it is read, so
+/// a file written in several steps is read once it is complete
+pub const UNIVERSE_RELOAD_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Where the tickers each analysis screens come from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolSource {
+    /// Every NASDAQ, NYSE and AMEX listing from the Nasdaq screener
+    #[default]
+    Nasdaq,
+    /// The symbols in a file, reread whenever it changes if `watch` is set
+    File {
+        path: PathBuf,
+        #[serde(default)]
+        watch: bool,
+    },
+}
+
+/// Why a universe file could not be used
+#[derive(Debug, thiserror::Error)]
+pub enum UniverseError {
+    #[error("failed to read symbol file {}: {source}", .path.display())]
+    Read { path: PathBuf, source: std::io::Error },
+    #[error("invalid symbol file {}: {message}", .path.display())]
+    Parse { path: PathBuf, message: String },
+    #[error("symbol file {} lists no symbols", .path.display())]
+    Empty { path: PathBuf },
+}
+
+/// Where the columns of a universe file are
+struct Columns {
+    symbol: usize,
+    name: Option<usize>,
+    sector: Option<usize>,
+    industry: Option<usize>,
+    exchange: Option<usize>,
+    country: Option<usize>,
+    market_cap: Option<usize>,
+}
+
+impl Columns {
+    /// `symbol`, then an optional name and sector, for files without a header row
+    const POSITIONAL: Columns = Columns {
+        symbol: 0,
+        name: Some(1),
+        sector: Some(2),
+        industry: None,
+        exchange: None,
+        country: None,
+        market_cap: None,
+    };
+
+    /// Columns named by a header row, or `None` when `record` is not one
+    fn from_header(record: &csv::StringRecord) -> Option<Columns> {
+        let position = |name: &str| record.iter().position(|field| field.eq_ignore_ascii_case(name));
+        Some(Columns {
+            symbol: position("symbol")?,
+            name: position("name"),
+            sector: position("sector"),
+            industry: position("industry"),
+            exchange: position("exchange"),
+            country: position("country"),
+            market_cap: position("market_cap"),
+        })
+    }
+}
+
+/// Tickers of a universe file, in file order. The file is one symbol per
+/// line, optionally followed by a name and a sector, or CSV with a header
+/// row naming a `symbol` column and any of `name`, `sector`, `industry`,
+/// `exchange`, `country` and `market_cap`. Blank lines and lines starting
+/// with `#` are skipped, and only the first line of a symbol is kept.
+pub fn parse_universe(text: &str) -> Result<Vec<TickerInfo>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_reader(text.as_bytes());
+
+    let mut columns = None;
+    let mut seen = HashSet::new();
+    let mut tickers = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("invalid CSV: {}", e))?;
+        let columns = match columns {
+            Some(ref columns) => columns,
+            None => match Columns::from_header(&record) {
+                Some(header) => {
+                    columns = Some(header);
+                    continue;
+                }
+                None => columns.insert(Columns::POSITIONAL),
+            },
+        };
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .filter(|field| !field.is_empty())
+                .map(str::to_string)
+        };
+        let Some(symbol) = field(Some(columns.symbol)).map(|symbol| normalize_symbol(&symbol)) else {
+            continue;
+        };
+        if !seen.insert(symbol.clone()) {
+            continue;
+        }
+        let unlisted = TickerInfo::unlisted(&symbol);
+        tickers.push(
+            TickerInfo {
+                name: field(columns.name).unwrap_or(unlisted.name.clone()),
+                sector: field(columns.sector),
+                industry: field(columns.industry),
+                exchange: field(columns.exchange),
+                country: field(columns.country),
+                market_cap: field(columns.market_cap),
+                ..unlisted
+            }
+            .with_parsed_fields(),
+        );
+    }
+    Ok(tickers)
+}
+
+/// The tickers listed in `path`; a file listing none is an error
+pub fn read_universe(path: &Path) -> Result<Vec<TickerInfo>, UniverseError> {
+    let text = std::fs::read_to_string(path).map_err(|source| UniverseError::Read { path: path.to_path_buf(), source })?;
+    let tickers = parse_universe(&text).map_err(|message| UniverseError::Parse { path: path.to_path_buf(), message })?;
+    if tickers.is_empty() {
+        return Err(UniverseError::Empty { path: path.to_path_buf() });
+    }
+    Ok(tickers)
+}
+
+/// The ticker listing read from a universe file, replacing the Nasdaq
+/// screener. Reloading swaps in the file's current contents.
+#[derive(Debug)]
+pub struct SymbolUniverse {
+    path: PathBuf,
+    tickers: RwLock<Arc<Vec<TickerInfo>>>,
+}
+
+impl SymbolUniverse {
+    /// Read the universe in `path`, which must list at least one symbol
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, UniverseError> {
+        let path = path.into();
+        let tickers = read_universe(&path)?;
+        Ok(Self { path, tickers: RwLock::new(Arc::new(tickers)) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The tickers read last
+    pub fn tickers(&self) -> Arc<Vec<TickerInfo>> {
+        self.tickers.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// Read the file again, returning whether its symbols changed. A file
+    /// that is missing, unreadable or empty leaves the last good listing
+    /// in place.
+    pub fn reload(&self) -> Result<bool, UniverseError> {
+        let tickers = read_universe(&self.path)?;
+        let mut current = self.tickers.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Only the columns a universe file can carry
+        let key = |ticker: &TickerInfo| {
+            (
+                ticker.symbol.clone(),
+                ticker.name.clone(),
+                ticker.sector.clone(),
+                ticker.industry.clone(),
+                ticker.exchange.clone(),
+                ticker.country.clone(),
+                ticker.market_cap.clone(),
+            )
+        };
+        let changed = !current.iter().map(key).eq(tickers.iter().map(key));
+        *current = Arc::new(tickers);
+        Ok(changed)
+    }
+
+    /// Watch the universe file, sending on the returned channel whenever
+    /// it may have changed. The file's directory is watched, since
+    /// pipelines often replace a file by renaming a new one over it. The
+    /// watch lasts as long as the returned watcher.
+    pub fn watch(&self) -> notify::Result<(RecommendedWatcher, mpsc::UnboundedReceiver<()>)> {
+        let (sender, changes) = mpsc::unbounded_channel();
+        let file_name = self.path.file_name().map(|name| name.to_os_string());
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event) if event.kind.is_access() => {}
+            Ok(event) => {
+                if event.paths.iter().any(|path| path.file_name() == file_name.as_deref()) {
+                    let _ = sender.send(());
+                }
+            }
+            Err(e) => tracing::warn!("Symbol file watch error: {}", e),
+        })?;
+        let directory = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        watcher.watch(directory, RecursiveMode::NonRecursive)?;
+        Ok((watcher, changes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(tickers: &[TickerInfo]) -> Vec<&str> {
+        tickers.iter().map(|ticker| ticker.symbol.as_str()).collect()
+    }
+
+    #[test]
+    fn test_plain_list() {
+        let tickers = parse_universe("aapl\n\n# energy\nXOM, Exxon Mobil, Energy\n  msft  \nAAPL\ntsla\n").unwrap();
+        assert_eq!(symbols(&tickers), ["AAPL", "XOM", "MSFT", "TSLA"]);
+        assert_eq!(tickers[0].name, "AAPL");
+        assert_eq!((tickers[1].name.as_str(), tickers[1].sector.as_deref()), ("Exxon Mobil", Some("Energy")));
+        assert_eq!(tickers[2].sector, None);
+    }
+
+    #[test]
+    fn test_csv_with_header() {
+        let text = "Symbol,Name,Sector,Industry,Market_Cap\nNVDA,NVIDIA,Technology,Semiconductors,$3.2T\n\"BRK.B\",\"Berkshire Hathaway, Inc.\",Finance,,\n";
+        let tickers = parse_universe(text).unwrap();
+        assert_eq!(symbols(&tickers), ["NVDA", "BRK.B"]);
+        assert_eq!(tickers[0].industry.as_deref(), Some("Semiconductors"));
+        assert_eq!(tickers[0].market_cap_f64, Some(3.2e12));
+        assert_eq!(tickers[1].name, "Berkshire Hathaway, Inc.");
+        assert_eq!(tickers[1].industry, None);
+    }
+
+    #[test]
+    fn test_reload_keeps_the_last_good_listing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("universe.txt");
+        assert!(matches!(SymbolUniverse::load(&path), Err(UniverseError::Read { .. })));
+        std::fs::write(&path, "# nothing yet\n").unwrap();
+        assert!(matches!(SymbolUniverse::load(&path), Err(UniverseError::Empty { .. })));
+
+        std::fs::write(&path, "AAA\nBBB\n").unwrap();
+        let universe = SymbolUniverse::load(&path).unwrap();
+        assert!(!universe.reload().unwrap());
+
+        std::fs::write(&path, "AAA\nCCC\n").unwrap();
+        assert!(universe.reload().unwrap());
+        assert_eq!(symbols(&universe.tickers()), ["AAA", "CCC"]);
+
+        std::fs::write(&path, "").unwrap();
+        assert!(universe.reload().is_err());
+        assert_eq!(symbols(&universe.tickers()), ["AAA", "CCC"]);
+    }
+}
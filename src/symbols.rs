@@ -0,0 +1,198 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A ticker change: `old_symbol` trades as `new_symbol` from `effective_date` on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolAlias {
+    pub old_symbol: String,
+    pub new_symbol: String,
+    pub effective_date: NaiveDate,
+}
+
+impl SymbolAlias {
+    pub fn new(old_symbol: &str, new_symbol: &str, effective_date: NaiveDate) -> Self {
+        Self {
+            old_symbol: normalize_symbol(old_symbol),
+            new_symbol: normalize_symbol(new_symbol),
+            effective_date,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum AliasError {
+    #[error("symbol must not be empty")]
+    EmptySymbol,
+    #[error("{0} cannot be an alias of itself")]
+    SelfAlias(String),
+    #[error("alias cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+pub fn normalize_symbol(symbol: &str) -> String {
+    symbol.trim().to_uppercase()
+}
+
+/// Old -> new symbol mappings, kept free of cycles
+#[derive(Debug, Clone, Default)]
+pub struct SymbolAliases {
+    by_old: HashMap<String, SymbolAlias>,
+}
+
+impl SymbolAliases {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build from stored aliases, rejecting any set that contains a cycle
+    pub fn from_aliases(aliases: impl IntoIterator<Item = SymbolAlias>) -> Result<Self, AliasError> {
+        let mut table = Self::new();
+        for alias in aliases {
+            table.insert(alias)?;
+        }
+        Ok(table)
+    }
+
+    /// Add or replace the alias for `alias.old_symbol`. The table is left
+    /// unchanged if the alias would create a cycle.
+    pub fn insert(&mut self, alias: SymbolAlias) -> Result<(), AliasError> {
+        let alias = SymbolAlias::new(&alias.old_symbol, &alias.new_symbol, alias.effective_date);
+        if alias.old_symbol.is_empty() || alias.new_symbol.is_empty() {
+            return Err(AliasError::EmptySymbol);
+        }
+        if alias.old_symbol == alias.new_symbol {
+            return Err(AliasError::SelfAlias(alias.old_symbol));
+        }
+
+        let previous = self.by_old.insert(alias.old_symbol.clone(), alias.clone());
+        if let Err(e) = self.chain(&alias.old_symbol) {
+            match previous {
+                Some(previous) => self.by_old.insert(alias.old_symbol, previous),
+                None => self.by_old.remove(&alias.old_symbol),
+            };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, old_symbol: &str) -> Option<&SymbolAlias> {
+        self.by_old.get(&normalize_symbol(old_symbol))
+    }
+
+    pub fn all(&self) -> Vec<SymbolAlias> {
+        let mut aliases: Vec<_> = self.by_old.values().cloned().collect();
+        aliases.sort_by(|a, b| a.old_symbol.cmp(&b.old_symbol));
+        aliases
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_old.is_empty()
+    }
+
+    /// Every symbol from `symbol` to its current name, e.g. `[A, B, C]` for A -> B -> C
+    pub fn chain(&self, symbol: &str) -> Result<Vec<String>, AliasError> {
+        self.chain_until(symbol, None)
+    }
+
+    /// The symbol `symbol` trades under today
+    pub fn resolve(&self, symbol: &str) -> Result<String, AliasError> {
+        Ok(self.chain(symbol)?.pop().unwrap_or_default())
+    }
+
+    /// The symbol `symbol` traded under on `date`; renames effective after it are ignored
+    pub fn resolve_as_of(&self, symbol: &str, date: NaiveDate) -> Result<String, AliasError> {
+        Ok(self.chain_until(symbol, Some(date))?.pop().unwrap_or_default())
+    }
+
+    /// The rename that retired `symbol`, if it had taken effect by `at`
+    pub fn retired_at(&self, symbol: &str, at: DateTime<Utc>) -> Option<&SymbolAlias> {
+        self.get(symbol).filter(|alias| alias.effective_date <= at.date_naive())
+    }
+
+    fn chain_until(&self, symbol: &str, date: Option<NaiveDate>) -> Result<Vec<String>, AliasError> {
+        let mut current = normalize_symbol(symbol);
+        let mut seen = HashSet::new();
+        let mut chain = Vec::new();
+
+        loop {
+            if !seen.insert(current.clone()) {
+                chain.push(current);
+                return Err(AliasError::Cycle(chain));
+            }
+            chain.push(current.clone());
+
+            match self.by_old.get(&current) {
+                Some(alias) if date.is_none_or(|date| alias.effective_date <= date) => {
+                    current = alias.new_symbol.clone();
+                }
+                _ => return Ok(chain),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_resolve_chained_aliases() {
+        let aliases = SymbolAliases::from_aliases(vec![
+            SymbolAlias::new("a", "b", date("2020-01-01")),
+            SymbolAlias::new("B", "C", date("2022-06-01")),
+        ])
+        .unwrap();
+
+        assert_eq!(aliases.chain("A").unwrap(), vec!["A", "B", "C"]);
+        assert_eq!(aliases.resolve("a").unwrap(), "C");
+        assert_eq!(aliases.resolve("C").unwrap(), "C");
+        assert_eq!(aliases.resolve("UNKNOWN").unwrap(), "UNKNOWN");
+
+        assert_eq!(aliases.resolve_as_of("A", date("2019-12-31")).unwrap(), "A");
+        assert_eq!(aliases.resolve_as_of("A", date("2021-01-01")).unwrap(), "B");
+        assert_eq!(aliases.resolve_as_of("A", date("2022-06-01")).unwrap(), "C");
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        let mut aliases = SymbolAliases::from_aliases(vec![
+            SymbolAlias::new("A", "B", date("2020-01-01")),
+            SymbolAlias::new("B", "C", date("2021-01-01")),
+        ])
+        .unwrap();
+
+        let err = aliases.insert(SymbolAlias::new("C", "A", date("2022-01-01"))).unwrap_err();
+        assert_eq!(err, AliasError::Cycle(vec!["C".into(), "A".into(), "B".into(), "C".into()]));
+        assert!(aliases.get("C").is_none(), "rejected alias must not be kept");
+
+        // Replacing an alias with one that closes a loop restores the old mapping
+        let err = aliases.insert(SymbolAlias::new("B", "A", date("2022-01-01"))).unwrap_err();
+        assert!(matches!(err, AliasError::Cycle(_)));
+        assert_eq!(aliases.resolve("A").unwrap(), "C");
+
+        assert_eq!(
+            aliases.insert(SymbolAlias::new("X", "x", date("2022-01-01"))),
+            Err(AliasError::SelfAlias("X".into()))
+        );
+        assert_eq!(
+            aliases.insert(SymbolAlias::new(" ", "X", date("2022-01-01"))),
+            Err(AliasError::EmptySymbol)
+        );
+    }
+
+    #[test]
+    fn test_retired_at() {
+        let aliases = SymbolAliases::from_aliases(vec![SymbolAlias::new("FB", "META", date("2022-06-09"))]).unwrap();
+        let before = date("2022-06-08").and_hms_opt(20, 0, 0).unwrap().and_utc();
+        let after = date("2022-06-09").and_hms_opt(14, 0, 0).unwrap().and_utc();
+
+        assert!(aliases.retired_at("FB", before).is_none());
+        assert_eq!(aliases.retired_at("fb", after).unwrap().new_symbol, "META");
+        assert!(aliases.retired_at("META", after).is_none());
+    }
+}
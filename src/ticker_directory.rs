@@ -0,0 +1,252 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::symbols::normalize_symbol;
+use crate::TickerInfo;
+
+/// Default for `AppConfig::delist_after_missing_fetches`
+pub const DEFAULT_DELIST_AFTER_MISSING_FETCHES: u32 = 3;
+
+/// A symbol's latest listing and when the ticker fetch first and last
+/// returned it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListedTicker {
+    #[serde(flatten)]
+    pub info: TickerInfo,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// Fetches in a row the symbol has been missing from
+    pub missing_fetches: u32,
+}
+
+/// A symbol whose sector or industry changed between fetches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reclassification {
+    pub symbol: String,
+    pub previous_sector: Option<String>,
+    pub sector: Option<String>,
+    pub previous_industry: Option<String>,
+    pub industry: Option<String>,
+}
+
+/// What one ticker fetch changed, broadcast as
+/// `{"type": "ticker_directory_update", "added": [...], "removed": [...], "reclassified": [...]}`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DirectoryUpdate {
+    /// Symbols listed for the first time, or again after being removed
+    pub added: Vec<String>,
+    /// Symbols that have now been missing for more than the configured
+    /// number of fetches; candidates for delisting
+    pub removed: Vec<String>,
+    pub reclassified: Vec<Reclassification>,
+}
+
+impl DirectoryUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.reclassified.is_empty()
+    }
+}
+
+/// Every symbol the ticker fetch has returned, keyed by normalized symbol
+#[derive(Debug, Clone, Default)]
+pub struct TickerDirectory {
+    entries: HashMap<String, ListedTicker>,
+    delist_after: u32,
+    /// Added symbols the continuous loop has not picked up yet
+    new_listings: Vec<String>,
+}
+
+impl TickerDirectory {
+    pub fn new(delist_after: u32) -> Self {
+        Self {
+            delist_after,
+            ..Self::default()
+        }
+    }
+
+    pub fn from_entries(entries: Vec<ListedTicker>, delist_after: u32) -> Self {
+        Self {
+            entries: entries
+                .into_iter()
+                .map(|entry| (normalize_symbol(&entry.info.symbol), entry))
+                .collect(),
+            ..Self::new(delist_after)
+        }
+    }
+
+    /// When the last applied fetch was made
+    pub fn last_fetch(&self) -> Option<DateTime<Utc>> {
+        self.entries.values().map(|entry| entry.last_seen).max()
+    }
+
+    /// Record the full listing fetched at `fetched_at`. A listing no newer
+    /// than the last one applied, such as one served from cache, changes
+    /// nothing and returns `None`. The first listing only sets the baseline,
+    /// so it reports no additions.
+    pub fn apply(&mut self, tickers: &[TickerInfo], fetched_at: DateTime<Utc>) -> Option<DirectoryUpdate> {
+        if self.last_fetch().is_some_and(|last| fetched_at <= last) {
+            return None;
+        }
+        let baseline = self.entries.is_empty();
+        let mut update = DirectoryUpdate::default();
+        let mut listed = HashSet::with_capacity(tickers.len());
+        for ticker in tickers {
+            let key = normalize_symbol(&ticker.symbol);
+            match self.entries.get_mut(&key) {
+                Some(entry) => {
+                    if entry.missing_fetches > self.delist_after {
+                        update.added.push(ticker.symbol.clone());
+                    }
+                    if entry.info.sector != ticker.sector || entry.info.industry != ticker.industry {
+                        update.reclassified.push(Reclassification {
+                            symbol: ticker.symbol.clone(),
+                            previous_sector: entry.info.sector.clone(),
+                            sector: ticker.sector.clone(),
+                            previous_industry: entry.info.industry.clone(),
+                            industry: ticker.industry.clone(),
+                        });
+                    }
+                    entry.info = ticker.clone();
+                    entry.last_seen = fetched_at;
+                    entry.missing_fetches = 0;
+                }
+                None => {
+                    if !baseline {
+                        update.added.push(ticker.symbol.clone());
+                    }
+                    self.entries.insert(
+                        key.clone(),
+                        ListedTicker {
+                            info: ticker.clone(),
+                            first_seen: fetched_at,
+                            last_seen: fetched_at,
+                            missing_fetches: 0,
+                        },
+                    );
+                }
+            }
+            listed.insert(key);
+        }
+
+        for (_, entry) in self.entries.iter_mut().filter(|(key, _)| !listed.contains(*key)) {
+            entry.missing_fetches += 1;
+            if entry.missing_fetches == self.delist_after + 1 {
+                update.removed.push(entry.info.symbol.clone());
+            }
+        }
+        update.removed.sort();
+        for symbol in &update.added {
+            let symbol = normalize_symbol(symbol);
+            if !self.new_listings.contains(&symbol) {
+                self.new_listings.push(symbol);
+            }
+        }
+        Some(update)
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&ListedTicker> {
+        self.entries.get(&normalize_symbol(symbol))
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &ListedTicker> {
+        self.entries.values()
+    }
+
+    /// Symbols first listed after `since`, oldest first
+    pub fn listed_since(&self, since: DateTime<Utc>) -> Vec<&ListedTicker> {
+        let mut listed: Vec<&ListedTicker> = self.entries.values().filter(|entry| entry.first_seen > since).collect();
+        listed.sort_by(|a, b| (a.first_seen, &a.info.symbol).cmp(&(b.first_seen, &b.info.symbol)));
+        listed
+    }
+
+    /// Symbols missing for more than the configured number of fetches, by symbol
+    pub fn removed(&self) -> Vec<&ListedTicker> {
+        let mut removed: Vec<&ListedTicker> = self
+            .entries
+            .values()
+            .filter(|entry| entry.missing_fetches > self.delist_after)
+            .collect();
+        removed.sort_by(|a, b| a.info.symbol.cmp(&b.info.symbol));
+        removed
+    }
+
+    /// Added symbols since the last call, for the continuous loop's next cycle
+    pub fn take_new_listings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.new_listings)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn listed(symbol: &str, sector: &str) -> TickerInfo {
+        TickerInfo {
+            sector: Some(sector.to_string()),
+            ..TickerInfo::unlisted(symbol)
+        }
+    }
+
+    #[test]
+    fn test_first_fetch_is_the_baseline() {
+        let start = Utc.with_ymd_and_hms(2024, 5, 1, 14, 0, 0).unwrap();
+        let mut directory = TickerDirectory::new(2);
+
+        let update = directory.apply(&[listed("AAPL", "Technology"), listed("XOM", "Energy")], start).unwrap();
+        assert!(update.is_empty());
+        assert_eq!(directory.len(), 2);
+        assert!(directory.take_new_listings().is_empty());
+
+        // A cached listing is not a new fetch
+        assert_eq!(directory.apply(&[listed("AAPL", "Technology")], start), None);
+        assert_eq!(directory.get("XOM").unwrap().missing_fetches, 0);
+    }
+
+    #[test]
+    fn test_additions_removals_and_reclassifications() {
+        let start = Utc.with_ymd_and_hms(2024, 5, 1, 14, 0, 0).unwrap();
+        let fetch = |hours: i64| start + Duration::hours(hours);
+        let mut directory = TickerDirectory::new(2);
+        directory.apply(&[listed("AAPL", "Technology"), listed("XOM", "Energy")], fetch(0));
+
+        let update = directory
+            .apply(&[listed("AAPL", "Consumer Electronics"), listed("ARM", "Technology")], fetch(1))
+            .unwrap();
+        assert_eq!(update.added, ["ARM"]);
+        assert!(update.removed.is_empty(), "one missing fetch is not a removal");
+        assert_eq!(update.reclassified.len(), 1);
+        assert_eq!(
+            (update.reclassified[0].previous_sector.as_deref(), update.reclassified[0].sector.as_deref()),
+            (Some("Technology"), Some("Consumer Electronics"))
+        );
+        assert_eq!(directory.take_new_listings(), ["ARM"]);
+        assert!(directory.take_new_listings().is_empty());
+
+        let both = [listed("AAPL", "Consumer Electronics"), listed("ARM", "Technology")];
+        assert!(directory.apply(&both, fetch(2)).unwrap().removed.is_empty());
+        assert_eq!(directory.apply(&both, fetch(3)).unwrap().removed, ["XOM"]);
+        // Reported once, when it passes the limit
+        assert!(directory.apply(&both, fetch(4)).unwrap().removed.is_empty());
+        assert_eq!(directory.removed().iter().map(|e| e.info.symbol.as_str()).collect::<Vec<_>>(), ["XOM"]);
+
+        // A removed symbol coming back counts as added again
+        let mut relisted = both.to_vec();
+        relisted.push(listed("XOM", "Energy"));
+        assert_eq!(directory.apply(&relisted, fetch(5)).unwrap().added, ["XOM"]);
+        assert!(directory.removed().is_empty());
+
+        let new: Vec<&str> = directory.listed_since(fetch(0)).iter().map(|e| e.info.symbol.as_str()).collect();
+        assert_eq!(new, ["ARM"]);
+        assert_eq!(directory.get("XOM").unwrap().first_seen, fetch(0));
+    }
+}
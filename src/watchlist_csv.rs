@@ -0,0 +1,161 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::symbols::normalize_symbol;
+
+/// One symbol line of a watchlist import
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    /// Normalized symbol
+    pub symbol: String,
+    pub note: Option<String>,
+    /// Kept from an export so a round trip restores the original order
+    pub added_at: Option<DateTime<Utc>>,
+}
+
+/// Where the columns of an import are
+struct Columns {
+    symbol: usize,
+    note: Option<usize>,
+    added_at: Option<usize>,
+}
+
+impl Columns {
+    /// `symbol` then an optional note, for files without a header row
+    const POSITIONAL: Columns = Columns { symbol: 0, note: Some(1), added_at: None };
+
+    /// Columns named by a header row, or `None` when `record` is not one
+    fn from_header(record: &csv::StringRecord) -> Option<Columns> {
+        let position = |name: &str| record.iter().position(|field| field.eq_ignore_ascii_case(name));
+        Some(Columns {
+            symbol: position("symbol")?,
+            note: position("note"),
+            added_at: position("added_at"),
+        })
+    }
+}
+
+/// Symbols of a watchlist import, in file order. The body is CSV with an
+/// optional header row naming `symbol`, `note` and `added_at` columns (an
+/// export reads back as is), or without one a symbol and an optional note
+/// per line, so a plain list of symbols works too. Blank lines and lines
+/// starting with `#` are skipped; duplicates are left for the caller.
+pub fn parse_watchlist_import(body: &str) -> Result<Vec<ImportRow>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_reader(body.as_bytes());
+
+    let mut columns = None;
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("invalid CSV: {}", e))?;
+        let line = record.position().map_or(0, |position| position.line());
+        let columns = match columns {
+            Some(ref columns) => columns,
+            None => match Columns::from_header(&record) {
+                Some(header) => {
+                    columns = Some(header);
+                    continue;
+                }
+                None => columns.insert(Columns::POSITIONAL),
+            },
+        };
+        let field = |index: Option<usize>| index.and_then(|index| record.get(index)).filter(|field| !field.is_empty());
+        let Some(symbol) = field(Some(columns.symbol)) else {
+            continue;
+        };
+        let added_at = field(columns.added_at)
+            .map(|added_at| {
+                DateTime::parse_from_rfc3339(added_at)
+                    .map(|added_at| added_at.with_timezone(&Utc))
+                    .map_err(|e| format!("line {}: invalid added_at '{}': {}", line, added_at, e))
+            })
+            .transpose()?;
+        rows.push(ImportRow {
+            symbol: normalize_symbol(symbol),
+            note: field(columns.note).map(str::to_string),
+            added_at,
+        });
+    }
+    Ok(rows)
+}
+
+/// One line of `GET /api/watchlist/export`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportRow {
+    pub symbol: String,
+    pub note: Option<String>,
+    pub added_at: DateTime<Utc>,
+    /// Latest intraday quote, else the close of the latest result
+    pub last_price: Option<f64>,
+    /// RSI of the latest result
+    pub rsi: Option<f64>,
+}
+
+/// The rows as CSV with a header row
+pub fn watchlist_csv(rows: &[ExportRow]) -> Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    Ok(writer.into_inner()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(rows: &[ImportRow]) -> Vec<&str> {
+        rows.iter().map(|row| row.symbol.as_str()).collect()
+    }
+
+    #[test]
+    fn test_plain_list_with_notes() {
+        let rows = parse_watchlist_import("aapl\n\n# energy\nXOM, integrated major\n  msft  \n\"BRK.B\",\"value, long term\"\n").unwrap();
+        assert_eq!(symbols(&rows), ["AAPL", "XOM", "MSFT", "BRK.B"]);
+        assert_eq!(rows[1].note.as_deref(), Some("integrated major"));
+        assert_eq!(rows[3].note.as_deref(), Some("value, long term"));
+        assert_eq!(rows[0].note, None);
+        assert!(rows.iter().all(|row| row.added_at.is_none()));
+    }
+
+    #[test]
+    fn test_export_reads_back() {
+        let exported = vec![
+            ExportRow {
+                symbol: "AAPL".to_string(),
+                note: Some("core".to_string()),
+                added_at: "2024-03-04T21:00:00Z".parse().unwrap(),
+                last_price: Some(172.5),
+                rsi: Some(41.2),
+            },
+            ExportRow {
+                symbol: "XOM".to_string(),
+                note: None,
+                added_at: "2024-03-05T21:00:00Z".parse().unwrap(),
+                last_price: None,
+                rsi: None,
+            },
+        ];
+        let csv = String::from_utf8(watchlist_csv(&exported).unwrap()).unwrap();
+        assert!(csv.starts_with("symbol,note,added_at,last_price,rsi\n"));
+
+        let rows = parse_watchlist_import(&csv).unwrap();
+        assert_eq!(symbols(&rows), ["AAPL", "XOM"]);
+        assert_eq!((rows[0].note.as_deref(), rows[0].added_at), (Some("core"), Some(exported[0].added_at)));
+        assert_eq!((rows[1].note.as_deref(), rows[1].added_at), (None, Some(exported[1].added_at)));
+    }
+
+    #[test]
+    fn test_header_columns_in_any_order() {
+        let rows = parse_watchlist_import("Note,Symbol\nwatch earnings,nvda\n").unwrap();
+        assert_eq!(rows, [ImportRow { symbol: "NVDA".to_string(), note: Some("watch earnings".to_string()), added_at: None }]);
+
+        let error = parse_watchlist_import("symbol,added_at\nAAPL,yesterday\n").unwrap_err();
+        assert!(error.starts_with("line 2: invalid added_at 'yesterday'"), "{}", error);
+    }
+}
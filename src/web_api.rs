@@ -1,27 +1,209 @@
 use axum::{
-    extract::{Query, State, WebSocketUpgrade},
-    extract::ws::{Message, WebSocket},
-    http::StatusCode,
-    response::Json,
+    extract::{ConnectInfo, Query, RawQuery, Request, State, WebSocketUpgrade},
+    extract::ws::{close_code, CloseFrame, Message, WebSocket},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc, time::Duration};
-use tokio::sync::{broadcast, RwLock};
+use std::{borrow::Cow, cmp::Ordering, collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque}, hash::{Hash, Hasher}, net::SocketAddr, sync::{atomic::{AtomicU64, AtomicUsize}, Arc}, time::Duration};
+use tokio::sync::{broadcast, mpsc, watch, RwLock, Semaphore};
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::{Any, CorsLayer};
 use uuid::Uuid;
 use futures::{sink::SinkExt, stream::StreamExt};
 
-use crate::{StockAnalyzer, StockFilter, TickerInfo};
+use crate::{
+    compute_indicators, FilterSpec, HistoryRange, IndicatorConfig, RankingConfig, StockAnalyzer, StockData, StockFilter, TechnicalIndicators, TickerInfo,
+    TickerSortField,
+};
+use crate::analysis_plan::{completion_at, AnalysisPlan, TickerTimings};
+use crate::analyzer::OBV_DIVERGENCE_WINDOW;
+use crate::anomaly::{detect_anomalies, AnomalyPolicy, DEFAULT_ANOMALY_LIMIT, MAX_ANOMALY_LIMIT};
+use crate::audit::{AuditEntry, AuditResource, DEFAULT_AUDIT_LIMIT, MAX_AUDIT_LIMIT};
+use crate::alerts::{evaluate_alerts, Alert, AlertTrigger, AlertUpdate, NewAlert};
+use crate::backtest::{BacktestReport, Backtester, EntryRule, ExitRule};
 use crate::cache::CacheManager;
-use crate::database::Database;
+use crate::clock::{system_clock, to_chrono, SharedClock};
+use crate::config::{AppConfig, DEFAULT_CYCLE_INTERVAL_SECS, DEFAULT_REPLAY_BUFFER_SIZE};
+use crate::corporate_events::{last_split, recent_event_signals};
+use crate::crossover_scan::{scan_crossovers, CrossoverScan, CrossoverScanReport, ScanSeries};
+use crate::custom_indicators::{CustomIndicator, CustomIndicatorSet, NewCustomIndicator, SharedCustomIndicators};
+use crate::data_quality::DataQuality;
+use crate::delta::ResultDelta;
+use crate::database::{AnalysisCycle, AnomalousResult, PagedResults, Pagination, AnalysisSession, Database, FilterPreset, IndicatorHistoryPoint, SessionDiff, WatchlistEntry};
+use crate::enrichment::AnalystEnricher;
+use crate::maintenance::{MaintenanceReport, MAINTENANCE_INTERVAL};
+use crate::error::AnalyzerError;
+use crate::events::{detect_crossovers, recent_crossovers, CrossoverEvent};
+use crate::health::{is_stalled, DependencyProbes, HealthStatus, PROBE_TIMEOUT};
+use crate::http::{HttpClient, HttpClientFactory};
+use crate::live_quotes::{LiveQuote, LiveQuotes};
+use crate::market_hours::{is_market_open, next_cycle_start, next_market_open};
+use crate::providers::{default_provider_with, AnalystTargetSource, BatchQuoteSource, Interval, MarketDataProvider, QuoteSeries, YahooAnalystTargets, YahooBatchQuotes};
+use crate::providers::analyst::is_stale_upside;
+use crate::ticker_directory::{ListedTicker, TickerDirectory, DEFAULT_DELIST_AFTER_MISSING_FETCHES};
+use crate::relative_strength::{percentile_ranks, RankedCycle, RelativeStrength, DEFAULT_BENCHMARK_SYMBOL};
+use crate::message_log::{BroadcastEvent, BroadcastMessage, MessageLog, SequencedMessage};
+use crate::notes::{attach_notes, NewNote, NoteUpdate, TickerNote};
+use crate::notifications::{deliver, Delivery, Notification, NotificationBudget, Notifier, DEFAULT_MAX_NOTIFICATIONS_PER_CYCLE};
+use crate::opportunity::{matching_rules, rsi_thresholds_used, rule_signal, validate_rules, OpportunityRule, RuleInputs, DEFAULT_OVERBOUGHT_RSI, DEFAULT_OVERSOLD_RSI};
+use crate::opportunity_state::{OpportunityState, OpportunityTracker};
+use crate::parsing::{parse_field, parse_market_cap};
+use crate::report::{SessionReport, DEFAULT_TOP_OPPORTUNITIES};
+use crate::resample::{resample_as_of, Timeframe};
+use crate::scheduler::{Enqueued, FetchJob, FetchPriority, FetchScheduler};
+use crate::session_events::{
+    EventFilter, SessionEvents, SessionEventKind, SessionEventsPage, TickerTrace, DEFAULT_EVENTS_PER_SESSION, DEFAULT_SESSIONS_KEPT,
+};
+use crate::stats::{
+    adaptive_rsi_thresholds, align_closes, annualized_volatility_pct, field_stats, max_drawdown_pct, median, rebase_to_100, rsi_series, seasonality, total_return_pct,
+    FieldStats, RsiThresholds, Seasonality,
+};
+use crate::sector_stats::{group_stats, GroupStats, GroupZScores};
+use crate::subscriptions::{ClientCommand, ServerReply, Subscriptions, Topic};
+use crate::summary::generate_summary;
+use crate::watchlist_csv::{parse_watchlist_import, watchlist_csv, ExportRow};
+use crate::symbol_health::{FailedSymbols, SymbolHealth};
+use crate::symbol_universe::{SymbolSource, SymbolUniverse, UniverseError, UNIVERSE_RELOAD_DEBOUNCE};
+use crate::symbols::{normalize_symbol, AliasError, SymbolAlias, SymbolAliases};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnalysisRequest {
-    pub filter: StockFilter,
+    /// Inline filter; exactly one of `filter` and `preset_name` must be given
+    #[serde(default)]
+    pub filter: Option<FilterSpec>,
+    /// Name of a stored filter preset to analyze with
+    #[serde(default)]
+    pub preset_name: Option<String>,
     pub max_tickers: Option<usize>,
     pub max_analysis: Option<usize>,
+    /// First day of history to analyze (all history when absent)
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+    /// Last day of history to analyze, inclusive (today when absent)
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+    /// Bar size: "1d" (default), "1wk" or "1mo"
+    #[serde(default)]
+    pub interval: Option<Interval>,
+    /// Bar size the indicators are computed on, resampled from the daily
+    /// bars: "daily" (default), "weekly" or "monthly"
+    #[serde(default)]
+    pub timeframe: Option<Timeframe>,
+    /// Whether the week or month still trading counts as the latest bar
+    /// (true when absent)
+    #[serde(default)]
+    pub include_partial: Option<bool>,
+    /// Moving averages and RSI/MACD periods to compute (SMA 20/50, RSI 14
+    /// and MACD 12/26/9 when absent)
+    #[serde(default)]
+    pub indicator_config: Option<IndicatorConfig>,
+    /// What counts as an opportunity; the preset's rules, else oversold or
+    /// overbought RSI at the filter's thresholds, when absent
+    #[serde(default)]
+    pub opportunity_rules: Option<Vec<OpportunityRule>>,
+    /// Return the plan for the request instead of starting a session
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Analyze exactly these tickers, in this order, instead of the ones
+    /// the listing filter passes, such as the ones a budget-exhausted
+    /// session left. The ticker listing is neither fetched nor filtered.
+    #[serde(default)]
+    pub symbols: Option<Vec<String>>,
+    /// Stop starting new tickers once the session has run this long
+    #[serde(default)]
+    pub max_duration_secs: Option<u64>,
+    /// Stop once this many tickers have fetched history upstream rather
+    /// than from the cache
+    #[serde(default)]
+    pub max_upstream_requests: Option<usize>,
+}
+
+impl AnalysisRequest {
+    pub fn history_range(&self) -> HistoryRange {
+        HistoryRange::new(self.start_date, self.end_date, self.interval.unwrap_or_default())
+    }
+
+    pub fn indicator_config(&self) -> IndicatorConfig {
+        self.indicator_config.clone().unwrap_or_default()
+    }
+
+    pub fn timeframe(&self) -> Timeframe {
+        self.timeframe.unwrap_or_default()
+    }
+
+    /// Check `symbols`, when given, names at least one plausible ticker
+    pub fn validate_symbols(&self) -> Result<(), String> {
+        let Some(symbols) = &self.symbols else { return Ok(()) };
+        if symbols.is_empty() {
+            return Err("symbols must not be empty".to_string());
+        }
+        for symbol in symbols {
+            let normalized = normalize_symbol(symbol);
+            if normalized.is_empty() {
+                return Err("symbols must not contain empty entries".to_string());
+            }
+            // Index symbols like ^GSPC and pair notations like BRK/B aren't tickers the providers know
+            if normalized.contains(['^', '/']) || normalized.contains(char::is_whitespace) {
+                return Err(format!("invalid symbol '{}'", symbol));
+            }
+        }
+        Ok(())
+    }
+
+    /// The tickers named by `symbols`, without duplicates, described by the
+    /// ticker directory where it lists them; `None` when the request names
+    /// none and the listing filter picks the tickers instead
+    pub fn requested_tickers(&self, directory: &TickerDirectory) -> Option<Vec<TickerInfo>> {
+        let symbols = self.symbols.as_ref()?;
+        let mut seen = HashSet::new();
+        let tickers = symbols
+            .iter()
+            .map(|symbol| normalize_symbol(symbol))
+            .filter(|symbol| seen.insert(symbol.clone()))
+            .map(|symbol| match directory.get(&symbol) {
+                Some(listed) => listed.info.clone(),
+                None => TickerInfo::unlisted(&symbol),
+            })
+            .collect();
+        Some(tickers)
+    }
+
+    /// Check the budgets leave room for any work
+    pub fn validate_budget(&self) -> Result<(), String> {
+        if self.max_duration_secs == Some(0) {
+            return Err("max_duration_secs must be positive".to_string());
+        }
+        if self.max_upstream_requests == Some(0) {
+            return Err("max_upstream_requests must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    /// Which budget is spent after running for `elapsed` with
+    /// `upstream_requests` fresh fetches, if any
+    pub fn exhausted_budget(&self, elapsed: Duration, upstream_requests: usize) -> Option<String> {
+        if let Some(max) = self.max_duration_secs.filter(|max| elapsed >= Duration::from_secs(*max)) {
+            return Some(format!("max_duration_secs of {} reached", max));
+        }
+        if let Some(max) = self.max_upstream_requests.filter(|max| upstream_requests >= *max) {
+            return Some(format!("max_upstream_requests of {} reached", max));
+        }
+        None
+    }
+
+    /// Check the timeframe resamples daily bars
+    pub fn validate_timeframe(&self) -> Result<(), String> {
+        let interval = self.interval.unwrap_or_default();
+        if self.timeframe() != Timeframe::Daily && interval != Interval::Daily {
+            return Err(format!("the {} timeframe is resampled from daily bars, not {}", self.timeframe(), interval));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,21 +220,222 @@ pub struct StockAnalysisResult {
     pub volume: Option<u64>,
     pub pct_change: Option<f64>,
     pub market_cap: Option<String>,
+    /// Screener classification carried over from `TickerInfo`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sector: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub industry: Option<String>,
+    /// Listing exchange (NASDAQ, NYSE or AMEX) carried over from `TickerInfo`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exchange: Option<String>,
     pub is_opportunity: bool,
     pub signals: Vec<String>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Provider that served the price history (e.g. "yahoo" or "stooq")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_source: Option<String>,
+    /// When that price history was fetched from the provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_fetched_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Plain-English summary from `summary::generate_summary`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Consensus analyst price target, when enrichment is enabled and the symbol has coverage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub analyst_target: Option<f64>,
+    /// Percent move from `current_price` to `analyst_target`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upside_to_target_pct: Option<f64>,
+    /// On-Balance Volume at the latest candle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obv: Option<f64>,
+    /// 20-candle rolling VWAP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vwap: Option<f64>,
+    /// Latest volume as a multiple of the previous 20 candles' average
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume_vs_avg: Option<f64>,
+    /// Highest high over the indicator range window (252 candles by default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub high_52w: Option<f64>,
+    /// Lowest low over the same window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_52w: Option<f64>,
+    /// Percent `current_price` is below `high_52w`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pct_from_high: Option<f64>,
+    /// Percent `current_price` is above `low_52w`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pct_from_low: Option<f64>,
+    /// False when the history was shorter than the range window
+    #[serde(default)]
+    pub range_window_complete: bool,
+    /// Return relative to the benchmark over 5 trading days (stock growth /
+    /// benchmark growth; above 1.0 outperformed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rs_1w: Option<f64>,
+    /// Same over 21 trading days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rs_1m: Option<f64>,
+    /// Same over 63 trading days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rs_3m: Option<f64>,
+    /// Percentile (0-100) of `rs_3m` among the symbols of the continuous
+    /// cycle that produced it, set once the cycle finishes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rs_rank: Option<f64>,
+    /// Configured moving averages keyed like "sma_20" or "ema_9"
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub moving_averages: HashMap<String, f64>,
+    /// Crossovers within the last `AppConfig::crossover_lookback_bars` bars,
+    /// oldest first; each is also listed in `signals`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub crossovers: Vec<CrossoverEvent>,
+    /// Bars dropped, filled or deduplicated when the history was fetched;
+    /// absent when stored history was served without a fetch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_quality: Option<DataQuality>,
+    /// 14-period Average Directional Index; 20 and 40 separate no, weak and
+    /// strong trends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adx: Option<f64>,
+    /// Directional indicators behind `adx`; the larger one gives the direction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plus_di: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minus_di: Option<f64>,
+    /// Ex-date of the latest split in the fetched history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_split: Option<chrono::DateTime<chrono::Utc>>,
+    /// Bar size the indicators were computed on; results of each timeframe
+    /// are stored side by side
+    #[serde(default)]
+    pub timeframe: Timeframe,
+    /// How the result breaks the configured sanity bounds, most likely
+    /// because of a bad upstream bar; empty for a believable result
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub anomalies: Vec<String>,
+    /// RSI the symbol was called oversold at: its own 10th percentile when
+    /// the filter has `adaptive_rsi` set and there was enough history, the
+    /// static threshold otherwise; absent when the filter isn't adaptive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rsi_oversold_used: Option<f64>,
+    /// Same for overbought and the 90th percentile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rsi_overbought_used: Option<f64>,
+    /// Standard deviations the RSI lies from the mean of its sector in the
+    /// continuous cycle that produced it (the market's when the sector had
+    /// fewer than 5 names), set once the cycle finishes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rsi_sector_zscore: Option<f64>,
+    /// Same against its industry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rsi_industry_zscore: Option<f64>,
+    /// Active note on the ticker from `/api/notes`, joined on as the result
+    /// is served rather than stored with it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Whether the continuous loop just flagged the symbol, still has it
+    /// flagged, or just stopped; `none` for manual sessions
+    #[serde(default)]
+    pub opportunity_state: OpportunityState,
+    /// Consecutive continuous results flagged, counting this one; for an
+    /// exited result, the length of the run it ended
+    #[serde(default)]
+    pub opportunity_streak: u32,
+    /// When the run of flags began, or when it ended for an exited result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opportunity_since: Option<chrono::DateTime<chrono::Utc>>,
+    /// User-defined indicators from `/api/custom-indicators` at the latest
+    /// bar, keyed by name; those that could not be computed are left out
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom: HashMap<String, f64>,
+    /// Bars of history the indicators were computed from; `None` for
+    /// results stored before it was recorded
+    #[serde(default)]
+    pub bars_used: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnalysisStatus {
     pub session_id: String,
-    pub status: String, // "running", "completed", "error"
+    pub status: String, // "running", "completed", "budget_exhausted", "error"
     pub progress: f64,  // 0.0 to 1.0
     pub analyzed_count: usize,
     pub total_count: usize,
     pub opportunities_found: usize,
     pub error_message: Option<String>,
+    /// Full results in session state; broadcast frames only carry the most
+    /// recent ones (see `progress_frame`)
     pub results: Vec<StockAnalysisResult>,
+    /// Number of results accumulated so far, including any left out of `results`
+    #[serde(default)]
+    pub results_total: usize,
+    /// When a running session should finish, from recent per-ticker timings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_completion: Option<chrono::DateTime<chrono::Utc>>,
+    /// Tickers left when the session's budget ran out; served by
+    /// `GET /api/analysis/:session_id/remaining` rather than broadcast
+    #[serde(skip)]
+    pub remaining_tickers: Vec<String>,
+}
+
+impl AnalysisStatus {
+    /// Copy of this status for broadcasting, keeping only the last `recent` results
+    pub fn progress_frame(&self, recent: usize) -> AnalysisStatus {
+        let skip = self.results.len().saturating_sub(recent);
+        AnalysisStatus {
+            session_id: self.session_id.clone(),
+            status: self.status.clone(),
+            progress: self.progress,
+            analyzed_count: self.analyzed_count,
+            total_count: self.total_count,
+            opportunities_found: self.opportunities_found,
+            error_message: self.error_message.clone(),
+            results: self.results[skip..].to_vec(),
+            results_total: self.results_total.max(self.results.len()),
+            estimated_completion: self.estimated_completion,
+            remaining_tickers: Vec::new(),
+        }
+    }
+}
+
+/// The last few results of a running cycle plus a count of all of them, so
+/// progress broadcasts stay the same size however long the cycle runs
+#[derive(Debug, Clone)]
+pub struct RecentResults {
+    capacity: usize,
+    items: VecDeque<StockAnalysisResult>,
+    total: usize,
+}
+
+impl RecentResults {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            items: VecDeque::with_capacity(capacity),
+            total: 0,
+        }
+    }
+
+    pub fn push(&mut self, result: StockAnalysisResult) {
+        self.total += 1;
+        if self.capacity == 0 {
+            return;
+        }
+        if self.items.len() == self.capacity {
+            self.items.pop_front();
+        }
+        self.items.push_back(result);
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn to_vec(&self) -> Vec<StockAnalysisResult> {
+        self.items.iter().cloned().collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,22 +443,249 @@ pub struct FilterStats {
     pub total_tickers: usize,
     pub filtered_tickers: usize,
     pub sectors: HashMap<String, usize>,
+    /// Keyed by normalized country, so "USA" and "United States" count together
     pub countries: HashMap<String, usize>,
+    /// Keyed like "North America"; tickers with an unrecognized country are left out
+    pub regions: HashMap<String, usize>,
     pub price_ranges: HashMap<String, usize>,
+    pub exchanges: HashMap<String, usize>,
+    /// Filtered tickers trading within 5% of their 52-week low
+    pub near_52_week_low: usize,
+    /// Filtered tickers flagged as ADRs
+    pub adrs: usize,
+    /// Tickers matched by each member filter (one entry for a single filter)
+    pub member_matches: Vec<usize>,
+}
+
+/// Request body for `POST /api/filters/compare`. Each side is an inline
+/// filter or the name of a stored preset, but not both.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FilterCompareRequest {
+    #[serde(default)]
+    pub filter_a: Option<FilterSpec>,
+    #[serde(default)]
+    pub preset_a: Option<String>,
+    #[serde(default)]
+    pub filter_b: Option<FilterSpec>,
+    #[serde(default)]
+    pub preset_b: Option<String>,
+    /// Maximum tickers listed per group (capped at `MAX_COMPARE_TICKERS`)
+    pub max_tickers: Option<usize>,
+}
+
+/// A group of tickers; `count` is exact even when `tickers` is truncated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerGroup {
+    pub count: usize,
+    pub tickers: Vec<String>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterSideSummary {
+    pub matched: usize,
+    pub opportunities: usize,
+    pub avg_rsi: Option<f64>,
+    pub avg_pct_change: Option<f64>,
+}
+
+/// How two filters differ when applied to the same result set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterComparison {
+    pub total_results: usize,
+    pub only_in_a: TickerGroup,
+    pub only_in_b: TickerGroup,
+    pub in_both: TickerGroup,
+    pub summary_a: FilterSideSummary,
+    pub summary_b: FilterSideSummary,
+}
+
+/// Request body for `POST /api/filter-compare`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TickerCompareRequest {
+    pub filter_a: FilterSpec,
+    pub filter_b: FilterSpec,
+    /// Also apply the indicator criteria to the latest stored results;
+    /// tickers without a result then match neither filter
+    #[serde(default)]
+    pub include_indicators: bool,
+    /// Maximum tickers listed per group (capped at `MAX_COMPARE_TICKERS`)
+    pub max_tickers: Option<usize>,
+}
+
+/// Tickers per side in one sector; `delta` is B's count less A's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectorDelta {
+    pub a: usize,
+    pub b: usize,
+    pub delta: i64,
+}
+
+/// How two filters differ when applied to the ticker listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerFilterComparison {
+    pub total_tickers: usize,
+    pub matched_a: usize,
+    pub matched_b: usize,
+    pub only_in_a: TickerGroup,
+    pub only_in_b: TickerGroup,
+    pub in_both: TickerGroup,
+    /// Keyed by sector, `UNKNOWN_SECTOR` for tickers without one
+    pub sectors: BTreeMap<String, SectorDelta>,
+}
+
+/// Breadth figures for one sector over the latest result per ticker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectorSummary {
+    pub sector: String,
+    pub count: usize,
+    /// Mean over the results with a finite RSI
+    pub avg_rsi: Option<f64>,
+    pub median_pct_change: Option<f64>,
+    pub opportunities: usize,
+    /// Results with MACD above its signal line
+    pub macd_bullish: usize,
+    /// Results with MACD below its signal line
+    pub macd_bearish: usize,
 }
 
+/// Sector label for results without one
+pub const UNKNOWN_SECTOR: &str = "Unknown";
+
+/// Upper bound on tickers listed per group in a filter comparison
+const MAX_COMPARE_TICKERS: usize = 500;
+/// Distance from the 52-week low (in percent) counted as "near the low" in filter stats
+const NEAR_52_WEEK_LOW_PCT: f64 = 5.0;
+/// Results an analysis loop holds before storing them in one batch
+const RESULT_FLUSH_EVERY: usize = 100;
+/// Session events returned per page unless `limit` says otherwise, and the most allowed
+const DEFAULT_SESSION_EVENTS_PER_PAGE: usize = 100;
+const MAX_SESSION_EVENTS_PER_PAGE: usize = 1_000;
+/// Maximum number of messages returned by a single long-poll response
+const LONG_POLL_BATCH_SIZE: usize = 100;
+/// Upper bound on how long a long-poll request may wait
+const LONG_POLL_MAX_TIMEOUT_SECS: u64 = 60;
+/// Maximum number of long-poll requests waiting at the same time
+const MAX_LONG_POLL_WAITERS: usize = 64;
+/// Pause between heartbeats sent to each WebSocket client
+pub const WS_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+/// WebSocket clients silent for longer than this are disconnected
+pub const WS_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+/// Header carrying an API key; admin keys bypass read-only mode
+pub const API_KEY_HEADER: &str = "x-api-key";
+/// Response header with the latest broadcast sequence number as the request
+/// arrived; a poller passes it to `/ws?since_seq=` to switch to the socket
+pub const BROADCAST_SEQ_HEADER: &str = "x-broadcast-seq";
+/// POST endpoints that only query data and stay available in read-only mode
+const READ_ONLY_SAFE_POSTS: &[&str] = &["/api/filter-stats", "/api/filtered-results", "/api/filters/compare", "/api/filter-compare", "/api/scan/crossovers", "/api/backtest"];
+
 #[derive(Clone)]
 pub struct AppState {
     pub sessions: Arc<RwLock<HashMap<String, AnalysisStatus>>>,
-    pub broadcast_tx: broadcast::Sender<AnalysisStatus>,
+    pub broadcast_tx: broadcast::Sender<SequencedMessage>,
+    pub message_log: Arc<MessageLog>,
+    /// What happened to each ticker of recent sessions and cycles
+    pub session_events: Arc<SessionEvents>,
+    pub long_poll_permits: Arc<Semaphore>,
     pub all_results: Arc<RwLock<Vec<StockAnalysisResult>>>,
     pub continuous_analysis_status: Arc<RwLock<ContinuousAnalysisStatus>>,
+    /// Latest command for the continuous loop, checked between tickers
+    pub continuous_control: Arc<watch::Sender<ControlCommand>>,
     pub cache: CacheManager,
     pub database: Option<Arc<Database>>,
+    pub config: Arc<AppConfig>,
+    pub symbol_aliases: Arc<RwLock<SymbolAliases>>,
+    /// Consecutive fetch failures per symbol; the continuous loop skips
+    /// symbols that keep failing for a growing number of cycles
+    pub failed_symbols: Arc<RwLock<FailedSymbols>>,
+    /// Each symbol's run of opportunity flags in the continuous loop, so
+    /// only entering and leaving a run is announced
+    pub opportunity_streaks: Arc<RwLock<OpportunityTracker>>,
+    /// Definitions from `/api/custom-indicators`, computed by every analyzer
+    /// this state hands out
+    pub custom_indicators: SharedCustomIndicators,
+    pub analyst_targets: Option<Arc<dyn AnalystTargetSource>>,
+    /// Latest-quote source that lets the continuous loop re-price tickers
+    /// without refetching history; every ticker fetches history when absent
+    pub batch_quotes: Option<Arc<dyn BatchQuoteSource>>,
+    /// Where analyzers fetch tickers, history and quotes; Yahoo with Stooq
+    /// fallback and the Nasdaq screener when absent
+    pub market_data: Option<Arc<dyn MarketDataProvider>>,
+    /// Ticker listing read from `AppConfig::symbol_source`'s file, used in
+    /// place of `market_data`'s listing when present
+    pub symbol_universe: Option<Arc<SymbolUniverse>>,
+    /// Symbols waiting for a fetch worker, most urgent first
+    pub scheduler: Arc<FetchScheduler>,
+    /// Daily series of `AppConfig::benchmark_symbol`, refreshed at the start
+    /// of each continuous cycle; relative strength is left out while absent
+    pub benchmark: Arc<RwLock<Option<Arc<Vec<StockData>>>>>,
+    /// Latest intraday quote of each watchlist symbol, kept by the quote poller
+    pub live_quotes: Arc<RwLock<LiveQuotes>>,
+    /// Every symbol the ticker fetch has returned, updated on each fresh fetch
+    pub ticker_directory: Arc<RwLock<TickerDirectory>>,
+    /// Channels new opportunities and fired alerts are sent through
+    pub notifiers: Vec<Arc<dyn Notifier>>,
+    /// Notifications sent and held back in the current continuous cycle
+    pub notification_budget: Arc<std::sync::Mutex<NotificationBudget>>,
+    /// Open WebSocket connections, reported by `/api/health`
+    pub websocket_clients: Arc<AtomicUsize>,
+    /// Held while a maintenance run prunes and vacuums the database
+    pub maintenance_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Bumped whenever the latest results change, in memory or in the
+    /// database; the results ETags are derived from it
+    pub results_version: Arc<AtomicU64>,
+    /// Latest dependency probe results; held while a probe run is under way
+    /// so concurrent health checks share it
+    pub dependency_probes: Arc<tokio::sync::Mutex<DependencyProbes>>,
+    /// How long recently analyzed session tickers took, for plans and
+    /// completion estimates
+    pub ticker_timings: Arc<std::sync::Mutex<TickerTimings>>,
+    /// Woken whenever a manual session starts or resumes, so cache warming
+    /// gets out of its way
+    pub session_started: Arc<tokio::sync::Notify>,
+    pub clock: SharedClock,
+}
+
+/// Command for the continuous analysis loop. The latest one stays in
+/// effect: a pause holds until a resume, stop or run-now replaces it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlCommand {
+    Resume,
+    /// Hold the current cycle between tickers
+    Pause,
+    /// Abandon the current cycle and start no new one until resumed
+    Stop,
+    /// Like resume, but also cut the wait before the next cycle short
+    RunNow,
+}
+
+/// What the continuous analysis loop is doing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContinuousState {
+    Running,
+    Paused,
+    #[default]
+    Stopped,
+    /// Between cycles, or waiting to retry the ticker list
+    IdleWaiting,
+}
+
+impl ContinuousState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContinuousState::Running => "running",
+            ContinuousState::Paused => "paused",
+            ContinuousState::Stopped => "stopped",
+            ContinuousState::IdleWaiting => "idle_waiting",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContinuousAnalysisStatus {
+    pub state: ContinuousState,
     pub is_running: bool,
     pub current_cycle: usize,
     pub progress: f64,
@@ -84,11 +694,19 @@ pub struct ContinuousAnalysisStatus {
     pub opportunities_found: usize,
     pub last_update: chrono::DateTime<chrono::Utc>,
     pub error_message: Option<String>,
+    /// When the next cycle is due while the loop waits between cycles
+    pub next_run_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Symbols whose history was refetched ahead of the next cycle during
+    /// the current wait
+    pub symbols_warmed: usize,
+    /// Share of the last completed cycle's history fetches the cache served
+    pub cache_hit_rate_last_cycle: Option<f64>,
 }
 
 impl Default for ContinuousAnalysisStatus {
     fn default() -> Self {
         Self {
+            state: ContinuousState::default(),
             is_running: false,
             current_cycle: 0,
             progress: 0.0,
@@ -97,17 +715,44 @@ impl Default for ContinuousAnalysisStatus {
             opportunities_found: 0,
             last_update: chrono::Utc::now(),
             error_message: None,
+            next_run_at: None,
+            symbols_warmed: 0,
+            cache_hit_rate_last_cycle: None,
+        }
+    }
+}
+
+impl ContinuousAnalysisStatus {
+    /// Broadcast form of this status, carrying the cycle's latest results if given
+    fn to_broadcast(&self, recent: Option<&RecentResults>) -> AnalysisStatus {
+        AnalysisStatus {
+            session_id: "continuous".to_string(),
+            status: self.state.as_str().to_string(),
+            progress: self.progress,
+            analyzed_count: self.analyzed_count,
+            total_count: self.total_count,
+            opportunities_found: self.opportunities_found,
+            error_message: self.error_message.clone(),
+            results: recent.map(RecentResults::to_vec).unwrap_or_default(),
+            results_total: recent.map_or(0, RecentResults::total),
+            estimated_completion: None,
+            remaining_tickers: Vec::new(),
         }
     }
 }
 
 impl AppState {
-    pub async fn new() -> Self {
-        let (broadcast_tx, _) = broadcast::channel(100);
-        let cache = CacheManager::new();
-        
+    /// State for a server run with `config`. Fails if `config` lists the
+    /// tickers from a file that can't be read, rather than quietly
+    /// analyzing the Nasdaq screener's listing instead.
+    pub async fn new(config: AppConfig) -> Result<Self, UniverseError> {
+        let universe = match config.symbol_source {
+            SymbolSource::File { ref path, watch } => Some((SymbolUniverse::load(path)?, watch)),
+            SymbolSource::Nasdaq => None,
+        };
+
         // Try to initialize database, but continue without it if it fails
-        let database = match Database::new("sqlite:analysis.db").await {
+        let database = match Database::new(&config.database_url).await {
             Ok(db) => {
                 if let Err(e) = db.initialize_tables().await {
                     tracing::warn!("Failed to initialize database tables: {}", e);
@@ -123,253 +768,3520 @@ impl AppState {
             }
         };
         
+        // Validated with the rest of the config, so this only falls back
+        // for a state built from unchecked settings
+        let http = HttpClientFactory::new(config.http.clone()).client().unwrap_or_else(|e| {
+            tracing::warn!("Using default HTTP settings: {}", e);
+            HttpClient::default()
+        });
+        let analyst_targets = config.analyst_targets;
+        let mut state = Self::with_database(database)
+            .with_config(config)
+            .with_batch_quotes(Arc::new(YahooBatchQuotes::new().with_http_client(http.clone())));
+        match default_provider_with(http.clone()) {
+            Ok(provider) => state = state.with_market_data(provider),
+            Err(e) => tracing::warn!("Failed to configure market data providers: {}", e),
+        }
+        if analyst_targets {
+            state = state.with_analyst_targets(Arc::new(YahooAnalystTargets::new().with_http_client(http)));
+        }
+        let mut watch_universe = false;
+        if let Some((universe, watch)) = universe {
+            tracing::info!("📄 Listing {} symbols from {}", universe.tickers().len(), universe.path().display());
+            state = state.with_symbol_universe(Arc::new(universe));
+            watch_universe = watch;
+        }
+        state.load_symbol_aliases().await;
+        state.load_symbol_health().await;
+        state.load_opportunity_streaks().await;
+        state.load_custom_indicators().await;
+        state.load_ticker_directory().await;
+        state.interrupt_sessions().await;
+        state.load_cache().await;
+        if state.symbol_universe.is_some() {
+            // The persisted listing may predate the file, or be the screener's
+            state.cache.invalidate_tickers();
+        }
+        if watch_universe {
+            state.watch_symbol_universe();
+        }
+        state.start_maintenance();
+        Ok(state)
+    }
+
+    /// Build application state around an already-initialized (or absent) database
+    pub fn with_database(database: Option<Arc<Database>>) -> Self {
+        let (broadcast_tx, _) = broadcast::channel(100);
+
         Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             broadcast_tx,
+            message_log: Arc::new(MessageLog::new(DEFAULT_REPLAY_BUFFER_SIZE)),
+            session_events: Arc::new(SessionEvents::new(DEFAULT_EVENTS_PER_SESSION, DEFAULT_SESSIONS_KEPT)),
+            long_poll_permits: Arc::new(Semaphore::new(MAX_LONG_POLL_WAITERS)),
             all_results: Arc::new(RwLock::new(Vec::new())),
             continuous_analysis_status: Arc::new(RwLock::new(ContinuousAnalysisStatus::default())),
-            cache,
+            continuous_control: Arc::new(watch::channel(ControlCommand::Resume).0),
+            cache: CacheManager::new(),
             database,
+            config: Arc::new(AppConfig::default()),
+            symbol_aliases: Arc::new(RwLock::new(SymbolAliases::new())),
+            failed_symbols: Arc::new(RwLock::new(FailedSymbols::new())),
+            opportunity_streaks: Arc::new(RwLock::new(OpportunityTracker::new())),
+            custom_indicators: SharedCustomIndicators::default(),
+            analyst_targets: None,
+            batch_quotes: None,
+            market_data: None,
+            symbol_universe: None,
+            scheduler: Arc::new(FetchScheduler::new()),
+            benchmark: Arc::new(RwLock::new(None)),
+            live_quotes: Arc::new(RwLock::new(LiveQuotes::new())),
+            ticker_directory: Arc::new(RwLock::new(TickerDirectory::new(DEFAULT_DELIST_AFTER_MISSING_FETCHES))),
+            notifiers: Vec::new(),
+            notification_budget: Arc::new(std::sync::Mutex::new(NotificationBudget::new(DEFAULT_MAX_NOTIFICATIONS_PER_CYCLE))),
+            websocket_clients: Arc::new(AtomicUsize::new(0)),
+            maintenance_lock: Arc::new(tokio::sync::Mutex::new(())),
+            // Starts at the boot time so ETags from before a restart don't match
+            results_version: Arc::new(AtomicU64::new(chrono::Utc::now().timestamp_micros() as u64)),
+            dependency_probes: Arc::new(tokio::sync::Mutex::new(DependencyProbes::default())),
+            ticker_timings: Arc::new(std::sync::Mutex::new(TickerTimings::new())),
+            session_started: Arc::new(tokio::sync::Notify::new()),
+            clock: system_clock(),
         }
     }
-    
-    pub async fn start_continuous_analysis(&self) {
-        let state = self.clone();
-        tokio::spawn(async move {
-            run_continuous_analysis(state).await;
-        });
+
+    /// Drive the analysis loops and cache freshness from `clock`. The cache
+    /// is replaced, so call this before anything has been cached.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.cache = CacheManager::with_clock(clock.clone())
+            .with_rate_limit(self.config.rate_limit())
+            .with_ttls(self.config.cache_ttls);
+        self.clock = clock;
+        self
     }
-}
 
-pub async fn create_router() -> Router {
-    let state = AppState::new().await;
-    
-    // Start continuous analysis
-    state.start_continuous_analysis().await;
+    /// An analyzer sharing this state's cache and, when present, its
+    /// database for stored price history
+    pub fn analyzer(&self) -> StockAnalyzer {
+        let mut analyzer = StockAnalyzer::new_with_cache(self.cache.clone())
+            .with_quote_batch_size(self.config.quote_batch_size)
+            .with_bad_bar_policy(self.config.bad_bar_policy)
+            .with_custom_indicators(self.custom_indicators.clone())
+            .with_history_requirements(self.config.min_history);
+        if let Some(ref source) = self.batch_quotes {
+            analyzer = analyzer.with_batch_quote_source(source.clone());
+        }
+        if let Some(ref provider) = self.market_data {
+            analyzer = analyzer.with_provider(provider.clone());
+        }
+        if let Some(ref universe) = self.symbol_universe {
+            analyzer = analyzer.with_symbol_universe(universe.clone());
+        }
+        match self.database {
+            Some(ref db) => analyzer.with_database(db.clone()),
+            None => analyzer,
+        }
+    }
 
-    Router::new()
-        .route("/api/health", get(health_check))
-        .route("/api/tickers", get(get_tickers))
-        .route("/api/filter-stats", post(get_filter_stats))
-        .route("/api/analysis", post(start_analysis))
-        .route("/api/analysis/:session_id", get(get_analysis_status))
-        .route("/api/analysis/:session_id/results", get(get_analysis_results))
-        .route("/api/continuous-status", get(get_continuous_status))
-        .route("/api/filtered-results", post(get_filtered_results))
-        .route("/api/cache-stats", get(get_cache_stats))
-        .route("/api/database-stats", get(get_database_stats))
-        .route("/api/clear-cache", post(clear_cache))
-        .route("/ws", get(websocket_handler))
-        .with_state(state)
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        )
-}
+    /// Fetch tickers, history and quotes from `provider`, e.g. a
+    /// `FixtureProvider` to analyze offline
+    pub fn with_market_data(mut self, provider: Arc<dyn MarketDataProvider>) -> Self {
+        self.market_data = Some(provider);
+        self
+    }
 
-async fn get_continuous_status(
-    State(state): State<AppState>,
-) -> Result<Json<ContinuousAnalysisStatus>, StatusCode> {
-    let status = state.continuous_analysis_status.read().await;
-    Ok(Json(status.clone()))
-}
+    /// List tickers from `universe` rather than the market data provider
+    pub fn with_symbol_universe(mut self, universe: Arc<SymbolUniverse>) -> Self {
+        self.symbol_universe = Some(universe);
+        self
+    }
 
-async fn get_filtered_results(
-    State(state): State<AppState>,
-    Json(filter): Json<StockFilter>,
-) -> Result<Json<Vec<StockAnalysisResult>>, StatusCode> {
-    // Try to get from database first if available
-    if let Some(ref db) = state.database {
-        match db.get_latest_results(None).await {
-            Ok(db_results) => {
-                let filtered_results = filter_results(&db_results, &filter);
-                return Ok(Json(filtered_results));
+    /// Re-price tickers in the continuous loop from batched quotes
+    pub fn with_batch_quotes(mut self, source: Arc<dyn BatchQuoteSource>) -> Self {
+        self.batch_quotes = Some(source);
+        self
+    }
+
+    /// Also send notifications through `notifier`
+    pub fn with_notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifiers.push(notifier);
+        self
+    }
+
+    /// Enrich analyzed results with analyst price targets from `source`
+    pub fn with_analyst_targets(mut self, source: Arc<dyn AnalystTargetSource>) -> Self {
+        self.analyst_targets = Some(source);
+        self
+    }
+
+    /// A fresh lookup budget for one analysis session or cycle, if enrichment is enabled
+    pub fn analyst_enricher(&self) -> Option<AnalystEnricher> {
+        self.analyst_targets
+            .clone()
+            .map(|source| AnalystEnricher::new(source, self.cache.clone(), self.config.analyst_target_budget))
+    }
+
+    /// Replace the in-memory alias table with the one stored in the database
+    pub async fn load_symbol_aliases(&self) {
+        let Some(ref db) = self.database else { return };
+        match db.get_symbol_aliases().await.map(SymbolAliases::from_aliases) {
+            Ok(Ok(aliases)) => *self.symbol_aliases.write().await = aliases,
+            Ok(Err(e)) => tracing::warn!("Ignoring stored symbol aliases: {}", e),
+            Err(e) => tracing::warn!("Failed to load symbol aliases: {}", e),
+        }
+    }
+
+    /// Replace the in-memory failure records with the ones stored in the database
+    pub async fn load_symbol_health(&self) {
+        let Some(ref db) = self.database else { return };
+        match db.get_symbol_health().await {
+            Ok(entries) => *self.failed_symbols.write().await = FailedSymbols::from_entries(entries),
+            Err(e) => tracing::warn!("Failed to load symbol health: {}", e),
+        }
+    }
+
+    /// Replace the in-memory opportunity runs with the ones stored in the database
+    pub async fn load_opportunity_streaks(&self) {
+        let Some(ref db) = self.database else { return };
+        match db.get_opportunity_streaks().await {
+            Ok(entries) => *self.opportunity_streaks.write().await = OpportunityTracker::from_entries(entries),
+            Err(e) => tracing::warn!("Failed to load opportunity states: {}", e),
+        }
+    }
+
+    /// Replace the custom indicators analyzers compute with the ones stored
+    /// in the database
+    pub async fn load_custom_indicators(&self) {
+        let Some(ref db) = self.database else { return };
+        match db.get_custom_indicators().await {
+            Ok(definitions) => self.custom_indicators.replace(CustomIndicatorSet::new(&definitions)),
+            Err(e) => tracing::warn!("Failed to load custom indicators: {}", e),
+        }
+    }
+
+    /// Replace the in-memory ticker directory with the one stored in the database
+    pub async fn load_ticker_directory(&self) {
+        let Some(ref db) = self.database else { return };
+        match db.get_listed_tickers().await {
+            Ok(entries) => {
+                *self.ticker_directory.write().await =
+                    TickerDirectory::from_entries(entries, self.config.delist_after_missing_fetches)
             }
-            Err(e) => {
-                tracing::warn!("Failed to get results from database: {}", e);
+            Err(e) => tracing::warn!("Failed to load the ticker directory: {}", e),
+        }
+    }
+
+    /// Move recorded session events into the in-memory logs and store
+    /// them, if there is a database
+    pub async fn flush_session_events(&self) {
+        let events = self.session_events.drain();
+        if events.is_empty() {
+            return;
+        }
+        if let Some(ref db) = self.database {
+            if let Err(e) = db.store_session_events(&events).await {
+                tracing::warn!("Failed to store {} session events: {}", events.len(), e);
             }
         }
     }
-    
-    // Fallback to in-memory results
-    let all_results = state.all_results.read().await;
-    let filtered_results = filter_results(&all_results, &filter);
-    Ok(Json(filtered_results))
-}
 
-async fn get_cache_stats(
-    State(state): State<AppState>,
-) -> Result<Json<crate::cache::CacheStats>, StatusCode> {
-    let stats = state.cache.get_cache_stats().await;
-    Ok(Json(stats))
-}
+    /// Mark the sessions a previous process left running as interrupted;
+    /// `POST /api/analysis/:session_id/resume` picks them back up
+    pub async fn interrupt_sessions(&self) {
+        let Some(ref db) = self.database else { return };
+        match db.interrupt_running_sessions(self.clock.now()).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("⏸️ Marked {} analysis sessions left running as interrupted", count),
+            Err(e) => tracing::warn!("Failed to mark interrupted sessions: {}", e),
+        }
+    }
 
-async fn get_database_stats(
-    State(state): State<AppState>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    if let Some(ref db) = state.database {
-        match db.get_analysis_stats().await {
-            Ok(stats) => Ok(Json(serde_json::to_value(stats).unwrap())),
-            Err(e) => {
-                tracing::error!("Failed to get database stats: {}", e);
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
+    /// The ticker list, cached or fetched, along with when it was fetched.
+    /// A freshly fetched list updates the ticker directory, and what it
+    /// changed is broadcast.
+    pub async fn fetch_tickers(&self, analyzer: &StockAnalyzer) -> Result<(Vec<TickerInfo>, chrono::DateTime<chrono::Utc>), AnalyzerError> {
+        let (tickers, fetched_at) = analyzer.fetch_all_tickers_cached_at().await?;
+        let mut directory = self.ticker_directory.write().await;
+        let Some(update) = directory.apply(&tickers, fetched_at) else {
+            return Ok((tickers, fetched_at));
+        };
+        let entries: Vec<ListedTicker> = directory.entries().cloned().collect();
+        drop(directory);
+
+        if !update.is_empty() {
+            tracing::info!(
+                "📋 Ticker listing: {} added, {} removed, {} reclassified",
+                update.added.len(),
+                update.removed.len(),
+                update.reclassified.len()
+            );
+            self.publish(BroadcastEvent::TickerDirectoryUpdate(update));
+        }
+        if let Some(ref db) = self.database {
+            if let Err(e) = db.save_listed_tickers(&entries).await {
+                tracing::warn!("Failed to store the ticker directory: {}", e);
             }
         }
-    } else {
-        Ok(Json(serde_json::json!({
-            "error": "Database not available"
-        })))
+        Ok((tickers, fetched_at))
     }
-}
 
-async fn clear_cache(
-    State(state): State<AppState>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    state.cache.clear_cache().await;
-    tracing::info!("Cache cleared via API request");
-    Ok(Json(serde_json::json!({
-        "status": "success",
-        "message": "Cache cleared successfully"
-    })))
-}
+    /// Count a failed fetch of `symbol` in continuous cycle `cycle`
+    async fn record_symbol_failure(&self, symbol: &str, error: &AnalyzerError, cycle: u64) {
+        let health = self.failed_symbols.write().await.record_failure(symbol, error, cycle, self.clock.now());
+        if let Some(until) = health.skip_until_cycle {
+            tracing::info!(
+                "⏭️  Skipping {} until cycle {} after {} consecutive failures",
+                symbol,
+                until,
+                health.consecutive_failures
+            );
+        }
+        if let Some(ref db) = self.database {
+            if let Err(e) = db.upsert_symbol_health(&health).await {
+                tracing::warn!("Failed to store symbol health for {}: {}", symbol, e);
+            }
+        }
+    }
 
-async fn websocket_handler(
-    ws: WebSocketUpgrade,
-    State(state): State<AppState>,
-) -> axum::response::Response {
-    ws.on_upgrade(|socket| handle_websocket(socket, state))
-}
+    /// Clear any failures recorded for `symbol` after it analyzed successfully
+    async fn record_symbol_success(&self, symbol: &str) {
+        if self.failed_symbols.read().await.get(symbol).is_none() {
+            return;
+        }
+        self.failed_symbols.write().await.clear(symbol);
+        if let Some(ref db) = self.database {
+            if let Err(e) = db.delete_symbol_health(symbol).await {
+                tracing::warn!("Failed to clear symbol health for {}: {}", symbol, e);
+            }
+        }
+    }
 
-async fn handle_websocket(socket: WebSocket, state: AppState) {
-    tracing::info!("🔌 New WebSocket connection established");
-    let (mut sender, mut receiver) = socket.split();
-    let mut broadcast_rx = state.broadcast_tx.subscribe();
-    
-    // Send current status immediately
-    let status = state.continuous_analysis_status.read().await.clone();
-    let msg = serde_json::to_string(&status).unwrap_or_default();
-    if let Err(e) = sender.send(Message::Text(msg)).await {
-        tracing::warn!("Failed to send initial status: {}", e);
-        return;
+    /// Count a continuous result against its symbol's run of opportunity
+    /// flags, setting its opportunity state and storing the run
+    async fn track_opportunity(&self, result: &mut StockAnalysisResult) {
+        let Some(streak) = self.opportunity_streaks.write().await.track(result, self.clock.now()) else { return };
+        if let Some(ref db) = self.database {
+            if let Err(e) = db.upsert_opportunity_streak(&streak).await {
+                tracing::warn!("Failed to store the opportunity state of {}: {}", streak.symbol, e);
+            }
+        }
+    }
+
+    /// Warm the cache from the configured cache file, if any
+    pub async fn load_cache(&self) {
+        let Some(ref path) = self.config.cache_file else { return };
+        if let Err(e) = self.cache.load_from_disk(path).await {
+            tracing::warn!("Starting with a cold cache: {:#}", e);
+        }
+    }
+
+    /// Save the cache to the configured cache file, if any
+    pub async fn persist_cache(&self) -> Result<usize, ApiError> {
+        let Some(ref path) = self.config.cache_file else {
+            return Err(ApiError::InvalidRequest("Cache persistence is not configured".to_string()));
+        };
+        self.cache.persist_to_disk(path).await.map_err(|e| {
+            tracing::error!("Failed to persist cache: {:#}", e);
+            ApiError::Internal(format!("Failed to persist cache: {}", e))
+        })
+    }
+
+    /// Replace the server settings (defaults to a writable server with no
+    /// admin keys). The cache is rebuilt, so call this before anything has
+    /// been cached.
+    pub fn with_config(mut self, config: AppConfig) -> Self {
+        self.cache = self.cache.with_rate_limit(config.rate_limit()).with_ttls(config.cache_ttls);
+        self.ticker_directory = Arc::new(RwLock::new(TickerDirectory::new(config.delist_after_missing_fetches)));
+        self.notifiers = config.notifications.notifiers().unwrap_or_else(|e| {
+            tracing::warn!("Notifications disabled: {}", e);
+            Vec::new()
+        });
+        self.notification_budget = Arc::new(std::sync::Mutex::new(NotificationBudget::new(config.notifications.max_per_cycle)));
+        self.message_log = Arc::new(MessageLog::new(config.replay_buffer_size));
+        self.config = Arc::new(config);
+        self
     }
     
-    tracing::info!("📡 WebSocket ready to receive broadcasts");
-    
-    // Handle incoming messages and broadcast updates
-    loop {
-        tokio::select! {
-            // Handle incoming messages from client (mostly pings/pongs)
-            msg = receiver.next() => {
-                match msg {
-                    Some(Ok(Message::Text(text))) => {
-                        tracing::debug!("Received WebSocket message: {}", text);
-                        // Echo back or handle client messages if needed
-                    },
-                    Some(Ok(Message::Binary(_))) => {
-                        tracing::debug!("Received binary message (ignored)");
-                    },
-                    Some(Ok(Message::Ping(data))) => {
-                        if let Err(e) = sender.send(Message::Pong(data)).await {
-                            tracing::warn!("Failed to send pong: {}", e);
-                            break;
-                        }
-                    },
-                    Some(Ok(Message::Pong(_))) => {
-                        tracing::debug!("Received pong");
-                    },
-                    Some(Ok(Message::Close(_))) => {
-                        tracing::info!("🔌 WebSocket client initiated close");
-                        break;
-                    },
-                    Some(Err(e)) => {
-                        tracing::warn!("WebSocket receive error: {}", e);
-                        break;
-                    },
-                    None => {
-                        tracing::info!("🔌 WebSocket receiver stream ended");
-                        break;
+    /// Record a status update or event in the message log and broadcast it to WebSocket clients
+    pub fn publish(&self, message: impl Into<BroadcastMessage>) -> SequencedMessage {
+        let message = self.message_log.push(message);
+        let _ = self.broadcast_tx.send(message.clone());
+        message
+    }
+
+    /// Note that the latest results changed. Call after the change is
+    /// stored, so a request tagged with the new version sees it.
+    pub fn bump_results_version(&self) {
+        self.results_version.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Broadcast a fresh result to its ticker's subscribers, and to
+    /// opportunity subscribers when it enters or leaves a run of flags.
+    /// Untracked results (manual sessions) reach them whenever flagged.
+    pub fn publish_result(&self, result: &StockAnalysisResult) {
+        self.publish(BroadcastEvent::TickerUpdate(Box::new(result.clone())));
+        let announce = match result.opportunity_state {
+            OpportunityState::None => result.is_opportunity,
+            state => state.is_transition(),
+        };
+        if announce {
+            self.publish(BroadcastEvent::Opportunity(Box::new(result.clone())));
+        }
+    }
+
+    /// Check a fresh result against the sanity bounds before it is stored
+    /// or broadcast. A believable result comes back as it is and a flagged
+    /// one with its `anomalies`; quarantined and dropped ones do not come
+    /// back.
+    pub async fn screen_result(&self, mut result: StockAnalysisResult, session_id: &str) -> Option<StockAnalysisResult> {
+        result.anomalies = detect_anomalies(&result, &self.config.sanity_bounds);
+        if result.anomalies.is_empty() {
+            return Some(result);
+        }
+        let policy = self.config.anomaly_policy;
+        tracing::warn!("Anomalous result for {} ({}): {}", result.ticker, policy.as_str(), result.anomalies.join("; "));
+        match policy {
+            AnomalyPolicy::Flag => Some(result),
+            AnomalyPolicy::Quarantine => {
+                if let Some(ref db) = self.database {
+                    if let Err(e) = db.quarantine_result(&result, session_id).await {
+                        tracing::warn!("Failed to quarantine the result for {}: {}", result.ticker, e);
                     }
                 }
-            },
-            // Handle broadcast updates from server
-            status = broadcast_rx.recv() => {
-                match status {
-                    Ok(status_update) => {
-                        let msg = serde_json::to_string(&status_update).unwrap_or_default();
-                        if let Err(e) = sender.send(Message::Text(msg)).await {
-                            tracing::warn!("Failed to send broadcast: {}", e);
-                            break;
-                        }
-                    },
-                    Err(e) => {
-                        tracing::debug!("Broadcast channel error (normal on shutdown): {}", e);
-                        break;
+                None
+            }
+            AnomalyPolicy::Drop => None,
+        }
+    }
+
+    /// Send `notification` through every channel, unless this cycle's
+    /// budget is spent and it waits for the cycle's digest instead
+    pub fn notify(&self, notification: Notification) {
+        if self.notifiers.is_empty() {
+            return;
+        }
+        let admitted = self.notification_budget.lock().unwrap().admit(notification);
+        if let Some(notification) = admitted {
+            self.dispatch(notification);
+        }
+    }
+
+    /// Send the digest of notifications held back this cycle, if any, and
+    /// start the next cycle's budget
+    pub fn end_notification_cycle(&self) {
+        let digest = self.notification_budget.lock().unwrap().end_cycle(self.clock.now());
+        if let Some(digest) = digest {
+            self.dispatch(digest);
+        }
+    }
+
+    /// Deliver in the background, so a slow channel never holds up analysis,
+    /// and log each delivery
+    fn dispatch(&self, notification: Notification) {
+        for notifier in &self.notifiers {
+            let notifier = notifier.clone();
+            let notification = notification.clone();
+            let database = self.database.clone();
+            let clock = self.clock.clone();
+            tokio::spawn(async move {
+                let delivery = deliver(notifier.as_ref(), notification, &clock).await;
+                if let Some(ref error) = delivery.error {
+                    tracing::warn!("Failed to send notification \"{}\": {}", delivery.notification.title, error);
+                }
+                if let Some(db) = database {
+                    if let Err(e) = db.record_delivery(&delivery).await {
+                        tracing::warn!("Failed to log notification delivery: {}", e);
+                    }
+                }
+            });
+        }
+    }
+
+    /// Steer the continuous loop; it picks the command up before its next ticker
+    pub fn control_continuous(&self, command: ControlCommand) {
+        self.continuous_control.send_replace(command);
+    }
+
+    /// Spawn `AppConfig::fetch_workers` tasks draining the fetch queue;
+    /// later calls do nothing
+    pub fn start_fetch_workers(&self) {
+        if !self.scheduler.claim_workers() {
+            return;
+        }
+        for _ in 0..self.config.fetch_workers.max(1) {
+            let state = self.clone();
+            tokio::spawn(async move {
+                run_fetch_worker(state).await;
+            });
+        }
+    }
+
+    /// Reread the symbol universe whenever its file changes. A changed
+    /// listing invalidates the cached one, so the next fetch, such as the
+    /// next continuous cycle's, lists the new symbols and reports them to
+    /// the ticker directory.
+    pub fn watch_symbol_universe(&self) {
+        let Some(universe) = self.symbol_universe.clone() else { return };
+        let (watcher, mut changes) = match universe.watch() {
+            Ok(watch) => watch,
+            Err(e) => {
+                tracing::error!("Failed to watch {}: {}", universe.path().display(), e);
+                return;
+            }
+        };
+        let state = self.clone();
+        tokio::spawn(async move {
+            let _watcher = watcher;
+            while changes.recv().await.is_some() {
+                tokio::time::sleep(UNIVERSE_RELOAD_DEBOUNCE).await;
+                while changes.try_recv().is_ok() {}
+                match universe.reload() {
+                    Ok(true) => {
+                        tracing::info!("📄 Reloaded {} symbols from {}", universe.tickers().len(), universe.path().display());
+                        state.cache.invalidate_tickers();
                     }
+                    Ok(false) => {}
+                    Err(e) => tracing::warn!("Keeping the previous symbol universe: {}", e),
                 }
             }
+        });
+    }
+
+    /// Spawn the daily maintenance task, when there is a database to maintain
+    pub fn start_maintenance(&self) {
+        if self.database.is_some() {
+            let state = self.clone();
+            tokio::spawn(async move {
+                run_maintenance_loop(state).await;
+            });
+        }
+    }
+
+    /// Delete results `AppConfig::retention` no longer keeps, then vacuum the
+    /// database. A run already in progress is waited for.
+    pub async fn run_maintenance(&self) -> Result<MaintenanceReport, ApiError> {
+        let db = self.database.as_deref().ok_or(ApiError::DatabaseUnavailable)?;
+        let _running = self.maintenance_lock.lock().await;
+        let maintenance_error = |e: anyhow::Error| {
+            tracing::error!("Database maintenance failed: {}", e);
+            ApiError::Database(format!("Database maintenance failed: {}", e))
+        };
+
+        let started_at = self.clock.now();
+        let bytes_before = db.size_bytes().await.map_err(maintenance_error)?;
+        let (expired_results, thinned_results) =
+            db.prune_results(&self.config.retention, started_at).await.map_err(maintenance_error)?;
+        db.vacuum().await.map_err(maintenance_error)?;
+        let report = MaintenanceReport {
+            started_at,
+            finished_at: self.clock.now(),
+            expired_results,
+            thinned_results,
+            bytes_before,
+            bytes_after: db.size_bytes().await.map_err(maintenance_error)?,
+        };
+        db.record_maintenance(&report).await.map_err(maintenance_error)?;
+        self.bump_results_version();
+        tracing::info!(
+            "🧹 Maintenance deleted {} expired and {} thinned results, reclaiming {} bytes",
+            report.expired_results,
+            report.thinned_results,
+            report.reclaimed_bytes()
+        );
+        Ok(report)
+    }
+
+    /// Spawn the continuous loop, its fetch workers and, when there is a
+    /// database to keep a watchlist in, the watchlist quote poller. The
+    /// control API steers the loop and the poller together.
+    pub async fn start_continuous_analysis(&self) {
+        self.start_fetch_workers();
+        let state = self.clone();
+        tokio::spawn(async move {
+            run_continuous_analysis(state).await;
+        });
+        if self.database.is_some() {
+            let state = self.clone();
+            tokio::spawn(async move {
+                run_quote_poller(state).await;
+            });
+        }
+    }
+}
+
+/// Failure returned by the JSON endpoints, rendered as
+/// `{ "error": { "code": ..., "message": ... } }`
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    Upstream(String),
+    #[error("{message}")]
+    RateLimited { message: String, retry_after_secs: Option<u64> },
+    #[error("{0}")]
+    InvalidFilter(String),
+    #[error("{0}")]
+    InvalidRequest(String),
+    #[error("analysis session {0} not found")]
+    SessionNotFound(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    Unprocessable(String),
+    #[error("{0}")]
+    Database(String),
+    #[error("Database not available")]
+    DatabaseUnavailable,
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            ApiError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::InvalidFilter(_) | ApiError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::SessionNotFound(_) | ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Unprocessable(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Database(_) | ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::DatabaseUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// Stable machine-readable identifier for clients to branch on
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::Upstream(_) => "upstream_error",
+            ApiError::RateLimited { .. } => "rate_limited",
+            ApiError::InvalidFilter(_) => "invalid_filter",
+            ApiError::InvalidRequest(_) => "invalid_request",
+            ApiError::SessionNotFound(_) => "session_not_found",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Unprocessable(_) => "unprocessable",
+            ApiError::Database(_) => "database_error",
+            ApiError::DatabaseUnavailable => "database_unavailable",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+
+    /// Map a failed fetch to a response: throttling is passed on as 429 with
+    /// the upstream's retry delay, anything else is the upstream's failure
+    pub fn from_fetch(context: &str, error: AnalyzerError) -> Self {
+        match error {
+            AnalyzerError::RateLimited { retry_after } => ApiError::RateLimited {
+                message: format!("{}: {}", context, error),
+                // Round up so clients never retry early
+                retry_after_secs: retry_after.map(|delay| delay.as_secs() + u64::from(delay.subsec_nanos() > 0)),
+            },
+            AnalyzerError::InvalidRequest(message) => ApiError::InvalidRequest(message),
+            AnalyzerError::Database(message) => ApiError::Database(message),
+            error => ApiError::Upstream(format!("{}: {}", context, error)),
+        }
+    }
+}
+
+/// Body of an `ApiError` response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub error: ErrorDetail,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorDetail {
+    /// One of `ApiError::code`
+    pub code: String,
+    pub message: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            error: ErrorDetail {
+                code: self.code().to_string(),
+                message: self.to_string(),
+            },
+        };
+        let mut response = (self.status(), Json(body)).into_response();
+        if let ApiError::RateLimited { retry_after_secs: Some(secs), .. } = self {
+            response.headers_mut().insert(axum::http::header::RETRY_AFTER, secs.into());
         }
+        response
     }
+}
+
+pub async fn create_router(config: AppConfig) -> Result<Router, UniverseError> {
+    let state = AppState::new(config).await?;
     
-    tracing::info!("🔌 WebSocket connection closed");
+    // Start continuous analysis
+    state.start_continuous_analysis().await;
+
+    Ok(build_router(state))
+}
+
+/// Assemble the API routes around the given state without starting any background tasks
+pub fn build_router(state: AppState) -> Router {
+    Router::new()
+        .route("/api/health", get(health_check))
+        .route("/api/config", get(get_config))
+        .route("/api/tickers", get(get_tickers))
+        .route("/api/tickers/new", get(get_new_tickers))
+        .route("/api/tickers/removed", get(get_removed_tickers))
+        .route("/api/screener", get(get_screener))
+        .route("/api/filter-stats", post(get_filter_stats))
+        .route("/api/analysis", post(start_analysis))
+        .route("/api/analyze-batch", post(analyze_batch))
+        .route("/api/analysis/:session_id", get(get_analysis_status))
+        .route("/api/analysis/:session_id/resume", post(resume_analysis))
+        .route("/api/analysis/:session_id/results", get(get_analysis_results))
+        .route("/api/analysis/:session_id/remaining", get(get_remaining_tickers))
+        .route("/api/analysis/:session_id/report", get(get_analysis_report))
+        .route("/api/analysis/:session_id/events", get(get_session_events))
+        .route("/api/continuous-status", get(get_continuous_status))
+        .route("/api/continuous/pause", post(pause_continuous))
+        .route("/api/continuous/resume", post(resume_continuous))
+        .route("/api/continuous/stop", post(stop_continuous))
+        .route("/api/continuous/run-now", post(run_continuous_now))
+        .route("/api/analyze-now/:symbol", post(analyze_now))
+        .route("/api/cycles", get(list_cycles))
+        .route("/api/cycles/:session_id/diff", get(diff_cycles))
+        .route("/api/cycles/:session_id/sector-stats", get(get_cycle_group_stats))
+        .route("/api/filtered-results", post(get_filtered_results))
+        .route("/api/filtered-results/changed-since", get(get_results_changed_since))
+        .route("/api/filters/compare", post(compare_filters_handler))
+        .route("/api/filter-compare", post(compare_ticker_filters_handler))
+        .route("/api/scan/crossovers", post(scan_crossovers_handler))
+        .route("/api/results/field-stats", get(get_field_stats))
+        .route("/api/sector-summary", get(get_sector_summary))
+        .route("/api/cache-stats", get(get_cache_stats))
+        .route("/api/database-stats", get(get_database_stats))
+        .route("/api/maintenance/run", post(run_maintenance))
+        .route("/api/clear-cache", post(clear_cache))
+        .route("/api/cache/persist", post(persist_cache))
+        .route("/api/symbols/alias", get(list_symbol_aliases).post(create_symbol_alias))
+        .route("/api/symbol-health", get(list_symbol_health))
+        .route("/api/symbol-health/:symbol/reset", post(reset_symbol_health))
+        .route("/api/watchlist", get(get_watchlist).post(add_to_watchlist))
+        .route("/api/watchlist/import", post(import_watchlist))
+        .route("/api/watchlist/export", get(export_watchlist))
+        .route("/api/watchlist/:symbol", axum::routing::delete(remove_from_watchlist))
+        .route("/api/watchlist/:symbol/restore", post(restore_watchlist_entry))
+        .route("/api/alerts", get(list_alerts).post(create_alert))
+        .route("/api/presets", get(list_presets).post(save_preset))
+        .route("/api/presets/:name", axum::routing::delete(delete_preset))
+        .route("/api/presets/:name/restore", post(restore_preset))
+        .route("/api/alerts/:id", axum::routing::delete(delete_alert))
+        .route("/api/alerts/:id/restore", post(restore_alert))
+        .route("/api/notes", get(list_notes).post(create_note))
+        .route("/api/notes/:id", axum::routing::put(update_note).delete(delete_note))
+        .route("/api/custom-indicators", get(list_custom_indicators).post(save_custom_indicator))
+        .route("/api/custom-indicators/:name", axum::routing::delete(delete_custom_indicator))
+        .route("/api/audit", get(get_audit_log))
+        .route("/api/notifications", get(list_notifications))
+        .route("/api/anomalies", get(list_anomalies))
+        .route("/api/backtest", post(run_backtest))
+        .route("/api/symbol/:ticker", get(get_symbol_analysis))
+        .route("/api/symbol/:ticker/indicators", get(get_indicator_history))
+        .route("/api/symbol/:ticker/seasonality", get(get_seasonality))
+        .route("/api/compare", get(compare_symbols))
+        .route("/api/events", get(get_events))
+        .route("/ws", get(websocket_handler))
+        // Applied outermost so no handler (or future auth layer) runs for a rejected request
+        .layer(middleware::from_fn_with_state(state.clone(), read_only_guard))
+        .layer(middleware::from_fn_with_state(state.clone(), broadcast_seq_header))
+        .with_state(state)
+        .layer(CompressionLayer::new())
+        .layer(
+            CorsLayer::new()
+                .allow_origin(Any)
+                .allow_methods(Any)
+                .allow_headers(Any)
+                .expose_headers([header::ETAG, header::HeaderName::from_static(BROADCAST_SEQ_HEADER)]),
+        )
+}
+
+async fn get_continuous_status(State(state): State<AppState>) -> Json<ContinuousAnalysisStatus> {
+    Json(state.continuous_analysis_status.read().await.clone())
+}
+
+fn accept_control(state: &AppState, command: ControlCommand) -> (StatusCode, Json<serde_json::Value>) {
+    state.control_continuous(command);
+    (StatusCode::ACCEPTED, Json(serde_json::json!({ "command": command })))
+}
+
+async fn pause_continuous(State(state): State<AppState>) -> impl IntoResponse {
+    accept_control(&state, ControlCommand::Pause)
+}
+
+async fn resume_continuous(State(state): State<AppState>) -> impl IntoResponse {
+    accept_control(&state, ControlCommand::Resume)
+}
+
+async fn stop_continuous(State(state): State<AppState>) -> impl IntoResponse {
+    accept_control(&state, ControlCommand::Stop)
+}
+
+async fn run_continuous_now(State(state): State<AppState>) -> impl IntoResponse {
+    accept_control(&state, ControlCommand::RunNow)
+}
+
+/// Session explicit analyze-now results are stored under
+pub const ON_DEMAND_SESSION: &str = "on_demand";
+
+/// Response of `POST /api/analyze-now/:symbol`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzeNowResponse {
+    pub symbol: String,
+    /// False when the symbol was already waiting; it moved to the front and
+    /// its result is published by whoever queued it
+    pub queued: bool,
+    /// Fetch jobs still waiting when the request was answered
+    pub queue_length: usize,
+}
+
+/// Put `symbol` at the front of the fetch queue. The result is broadcast
+/// as a ticker update (and stored) once a worker has analyzed it.
+async fn analyze_now(
+    State(state): State<AppState>,
+    axum::extract::Path(ticker): axum::extract::Path<String>,
+) -> Result<(StatusCode, Json<AnalyzeNowResponse>), ApiError> {
+    let symbol = normalize_symbol(&ticker);
+    if symbol.is_empty() {
+        return Err(ApiError::InvalidRequest("symbol must not be empty".to_string()));
+    }
+    let info = state
+        .cache
+        .get_tickers("all_tickers")
+        .await
+        .and_then(|tickers| tickers.into_iter().find(|t| normalize_symbol(&t.symbol) == symbol))
+        .unwrap_or_else(|| TickerInfo::unlisted(&symbol));
+
+    let (reply, mut outcome) = mpsc::unbounded_channel();
+    let queued = state.scheduler.enqueue(FetchJob::new(info, FetchPriority::TOP, reply)) == Enqueued::Queued;
+    state.start_fetch_workers();
+    if queued {
+        let state = state.clone();
+        let symbol = symbol.clone();
+        tokio::spawn(async move {
+            let Some(outcome) = outcome.recv().await else { return };
+            match outcome.result {
+                Ok(Some(result)) => {
+                    state.record_symbol_success(&symbol).await;
+                    let Some(mut result) = state.screen_result(result, ON_DEMAND_SESSION).await else { return };
+                    result.summary = Some(generate_summary(&result));
+                    // Stored before it becomes the ticker's current result
+                    let mut pending = PendingResults::new(ON_DEMAND_SESSION).with_flush_every(1);
+                    commit_result(&state, result, &mut pending).await;
+                }
+                Ok(None) => tracing::warn!("Not enough history to analyze {}", symbol),
+                Err(e) => tracing::warn!("Failed to analyze {} on demand: {}", symbol, e),
+            }
+        });
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(AnalyzeNowResponse { symbol, queued, queue_length: state.scheduler.len() }),
+    ))
+}
+
+#[derive(Deserialize)]
+struct CyclesQuery {
+    limit: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct CycleDiffQuery {
+    vs: String,
+}
+
+fn cycles_db(state: &AppState) -> Result<&Database, ApiError> {
+    state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)
+}
+
+fn cycle_db_error(e: anyhow::Error) -> ApiError {
+    tracing::error!("Cycle query failed: {}", e);
+    ApiError::Database("Cycle query failed".to_string())
+}
+
+/// Recorded continuous cycles, newest first
+async fn list_cycles(
+    State(state): State<AppState>,
+    Query(params): Query<CyclesQuery>,
+) -> Result<Json<Vec<AnalysisCycle>>, ApiError> {
+    let cycles = cycles_db(&state)?.get_cycles(params.limit).await.map_err(cycle_db_error)?;
+    Ok(Json(cycles))
+}
+
+/// What changed in `session_id` relative to the `vs` session
+async fn diff_cycles(
+    State(state): State<AppState>,
+    axum::extract::Path(session_id): axum::extract::Path<String>,
+    Query(params): Query<CycleDiffQuery>,
+) -> Result<Json<SessionDiff>, ApiError> {
+    cycles_db(&state)?
+        .diff_sessions(&session_id, &params.vs)
+        .await
+        .map_err(cycle_db_error)?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("no stored results for '{}' or '{}'", session_id, params.vs)))
+}
+
+/// RSI and percent change statistics of a finished cycle's sectors and
+/// industries, with the market-wide row first
+async fn get_cycle_group_stats(
+    State(state): State<AppState>,
+    axum::extract::Path(session_id): axum::extract::Path<String>,
+) -> Result<Json<Vec<GroupStats>>, ApiError> {
+    let stats = cycles_db(&state)?.get_group_stats(&session_id).await.map_err(cycle_db_error)?;
+    if stats.is_empty() {
+        return Err(ApiError::NotFound(format!("no sector statistics for '{}'", session_id)));
+    }
+    Ok(Json(stats))
+}
+
+/// Load the latest result per ticker, preferring the database and falling back to memory
+async fn load_latest_results(state: &AppState) -> Vec<StockAnalysisResult> {
+    let aliases = state.symbol_aliases.read().await.clone();
+
+    // Try to get from database first if available
+    if let Some(ref db) = state.database {
+        match db.get_latest_results(None).await {
+            Ok(db_results) => return with_notes(state, suppress_renamed(db_results, &aliases)).await,
+            Err(e) => {
+                tracing::warn!("Failed to get results from database: {}", e);
+            }
+        }
+    }
+
+    // Fallback to in-memory results
+    let results = state.all_results.read().await.clone();
+    with_notes(state, suppress_renamed(results, &aliases)).await
+}
+
+/// `results` with the active ticker notes joined on; unchanged when there
+/// is no database to keep notes in
+async fn with_notes(state: &AppState, mut results: Vec<StockAnalysisResult>) -> Vec<StockAnalysisResult> {
+    if let Some(ref db) = state.database {
+        match db.get_active_notes().await {
+            Ok(notes) => attach_notes(&mut results, &notes),
+            Err(e) => tracing::warn!("Failed to load ticker notes: {}", e),
+        }
+    }
+    results
+}
+
+/// Drop results for symbols analyzed on or after their rename took effect,
+/// noting the former symbol on the result for the new one
+pub fn suppress_renamed(results: Vec<StockAnalysisResult>, aliases: &SymbolAliases) -> Vec<StockAnalysisResult> {
+    if aliases.is_empty() {
+        return results;
+    }
+
+    let mut renamed: HashMap<String, Vec<&SymbolAlias>> = HashMap::new();
+    let mut kept = Vec::with_capacity(results.len());
+    for result in &results {
+        match aliases.retired_at(&result.ticker, result.timestamp) {
+            Some(alias) => {
+                tracing::debug!("Suppressing {} result: renamed to {}", result.ticker, alias.new_symbol);
+                renamed.entry(alias.new_symbol.clone()).or_default().push(alias);
+            }
+            None => kept.push(result.clone()),
+        }
+    }
+
+    for result in &mut kept {
+        if let Some(former) = renamed.get(&result.ticker) {
+            for alias in former {
+                result.signals.push(format!(
+                    "Formerly {} (renamed {})",
+                    alias.old_symbol, alias.effective_date
+                ));
+            }
+        }
+    }
+
+    kept
+}
+
+/// Summaries are only sent to clients that ask for them
+fn apply_summary_flag(mut results: Vec<StockAnalysisResult>, include_summary: bool) -> Vec<StockAnalysisResult> {
+    if !include_summary {
+        for result in &mut results {
+            result.summary = None;
+        }
+    }
+    results
+}
+
+/// Results per page when the client doesn't ask for a page size
+pub const DEFAULT_RESULTS_PER_PAGE: u32 = 100;
+/// Largest page the results endpoints serve
+pub const MAX_RESULTS_PER_PAGE: u32 = 1000;
+
+/// Field a page of results can be ordered by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Rsi,
+    PctChange,
+    Ticker,
+    Timestamp,
 }
 
-async fn health_check() -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "status": "healthy",
-        "timestamp": chrono::Utc::now(),
-        "version": env!("CARGO_PKG_VERSION")
+impl SortField {
+    pub const ALL: [SortField; 4] = [SortField::Rsi, SortField::PctChange, SortField::Ticker, SortField::Timestamp];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortField::Rsi => "rsi",
+            SortField::PctChange => "pct_change",
+            SortField::Ticker => "ticker",
+            SortField::Timestamp => "timestamp",
+        }
+    }
+}
+
+impl std::str::FromStr for SortField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|field| field.as_str() == s).ok_or_else(|| {
+            let valid: Vec<&str> = Self::ALL.iter().map(|field| field.as_str()).collect();
+            format!("unknown sort_by '{}'; valid fields are {}", s, valid.join(", "))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+impl std::str::FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(format!("unknown order '{}'; use asc or desc", s)),
+        }
+    }
+}
+
+/// Ordering for a page of results. Missing values sort last in either
+/// direction and ties fall back to the ticker, matching the SQL ordering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResultSort {
+    pub field: SortField,
+    pub order: SortOrder,
+}
+
+impl ResultSort {
+    pub fn compare(&self, a: &StockAnalysisResult, b: &StockAnalysisResult) -> Ordering {
+        let directed = |ordering: Ordering| match self.order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        };
+        let optional = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => directed(a.total_cmp(&b)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+
+        let ordering = match self.field {
+            SortField::Rsi => optional(a.rsi, b.rsi),
+            SortField::PctChange => optional(a.pct_change, b.pct_change),
+            SortField::Ticker => directed(a.ticker.cmp(&b.ticker)),
+            SortField::Timestamp => directed(a.timestamp.cmp(&b.timestamp)),
+        };
+        ordering.then_with(|| a.ticker.cmp(&b.ticker))
+    }
+}
+
+/// One page of results and how many there are in total
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultsPage {
+    pub total: u64,
+    pub page: u32,
+    pub per_page: u32,
+    pub results: Vec<StockAnalysisResult>,
+    /// Stored rows on this page left out because they couldn't be read
+    #[serde(default)]
+    pub skipped: u64,
+}
+
+#[derive(Deserialize)]
+struct ResultsQuery {
+    #[serde(default)]
+    include_summary: bool,
+    page: Option<u32>,
+    per_page: Option<u32>,
+    sort_by: Option<String>,
+    order: Option<String>,
+}
+
+/// A validated page of a results listing; `page` counts from 1
+struct PageRequest {
+    page: u32,
+    per_page: u32,
+    sort: Option<ResultSort>,
+}
+
+impl ResultsQuery {
+    fn page_request(&self) -> Result<PageRequest, ApiError> {
+        let page = self.page.unwrap_or(1);
+        if page == 0 {
+            return Err(ApiError::InvalidRequest("page must be at least 1".to_string()));
+        }
+        let per_page = self.per_page.unwrap_or(DEFAULT_RESULTS_PER_PAGE);
+        if per_page == 0 {
+            return Err(ApiError::InvalidRequest("per_page must be at least 1".to_string()));
+        }
+
+        // An order on its own sorts by timestamp; neither keeps the listing's own order
+        let sort = match (&self.sort_by, &self.order) {
+            (None, None) => None,
+            (field, order) => Some(ResultSort {
+                field: field
+                    .as_deref()
+                    .map_or(Ok(SortField::Timestamp), str::parse)
+                    .map_err(ApiError::InvalidRequest)?,
+                order: order.as_deref().map_or(Ok(SortOrder::default()), str::parse).map_err(ApiError::InvalidRequest)?,
+            }),
+        };
+
+        Ok(PageRequest {
+            page,
+            per_page: per_page.min(MAX_RESULTS_PER_PAGE),
+            sort,
+        })
+    }
+}
+
+impl PageRequest {
+    fn offset(&self) -> u64 {
+        u64::from(self.page - 1) * u64::from(self.per_page)
+    }
+
+    fn pagination(&self) -> Pagination {
+        Pagination { sort: self.sort, limit: self.per_page, offset: self.offset() }
+    }
+
+    fn envelope(&self, total: u64, results: Vec<StockAnalysisResult>) -> ResultsPage {
+        ResultsPage {
+            total,
+            page: self.page,
+            per_page: self.per_page,
+            results,
+            skipped: 0,
+        }
+    }
+
+    /// Sort and slice results already held in memory
+    fn paginate(&self, mut results: Vec<StockAnalysisResult>) -> ResultsPage {
+        if let Some(sort) = self.sort {
+            results.sort_by(|a, b| sort.compare(a, b));
+        }
+        let total = results.len() as u64;
+        let page = results
+            .into_iter()
+            .skip(self.offset().try_into().unwrap_or(usize::MAX))
+            .take(self.per_page as usize)
+            .collect();
+        self.envelope(total, page)
+    }
+}
+
+/// Tagged with `AppState::results_version`, the filter and the query, so
+/// a client repeating a request while no result changed gets 304 Not Modified
+async fn get_filtered_results(
+    State(state): State<AppState>,
+    Query(params): Query<ResultsQuery>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+    Json(filter): Json<FilterSpec>,
+) -> Result<Response, ApiError> {
+    validate_filter(&filter)?;
+    let request = params.page_request()?;
+
+    // Read before the results, so a change made meanwhile moves the tag on
+    let version = state.results_version.load(std::sync::atomic::Ordering::SeqCst);
+    let filter_json = serde_json::to_string(&filter).map_err(|e| ApiError::Internal(e.to_string()))?;
+    let etag = entity_tag(|hasher| (version, &filter_json, &query).hash(hasher));
+    if etag_matches(&headers, &etag) {
+        return Ok(not_modified(etag));
+    }
+
+    // Renamed symbols are suppressed in Rust, so only alias-free listings
+    // can be filtered and paged by the database
+    if state.symbol_aliases.read().await.is_empty() {
+        if let Some(ref db) = state.database {
+            match db.query_results(filter.members(), request.pagination()).await {
+                Ok(PagedResults { total, skipped, results }) => {
+                    let results = apply_summary_flag(with_notes(&state, results).await, params.include_summary);
+                    let page = ResultsPage { skipped, ..request.envelope(total, results) };
+                    return Ok(with_etag(etag, Json(page)));
+                }
+                Err(e) => tracing::warn!("Failed to page results from database: {}", e),
+            }
+        }
+    }
+
+    let results = load_latest_results(&state).await;
+    let mut page = request.paginate(filter_results(&results, &filter));
+    page.results = apply_summary_flag(page.results, params.include_summary);
+    Ok(with_etag(etag, Json(page)))
+}
+
+/// Weak entity tag over whatever `hash` feeds the hasher. Weak because the
+/// compression layer may re-encode the body.
+fn entity_tag(hash: impl FnOnce(&mut std::collections::hash_map::DefaultHasher)) -> HeaderValue {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    hash(&mut hasher);
+    HeaderValue::from_str(&format!("W/\"{:016x}\"", hasher.finish())).expect("hex is a valid header value")
+}
+
+/// Whether the request's `If-None-Match` lists `etag` or is `*`, compared
+/// weakly as RFC 9110 asks for `If-None-Match`
+fn etag_matches(headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let Ok(etag) = etag.to_str() else { return false };
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag))
+}
+
+fn not_modified(etag: HeaderValue) -> Response {
+    (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response()
+}
+
+fn with_etag(etag: HeaderValue, body: impl IntoResponse) -> Response {
+    ([(header::ETAG, etag)], body).into_response()
+}
+
+/// `body` as JSON tagged with `etag`, or 304 Not Modified when the request
+/// already holds it
+fn conditional_json(headers: &HeaderMap, etag: HeaderValue, body: &impl Serialize) -> Response {
+    if etag_matches(headers, &etag) {
+        return not_modified(etag);
+    }
+    with_etag(etag, Json(body))
+}
+
+#[derive(Deserialize)]
+struct ChangedSinceQuery {
+    timestamp: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    include_summary: bool,
+}
+
+/// Latest result per ticker analyzed after `timestamp`, newest first, so a
+/// client holding earlier results only fetches what was updated since
+async fn get_results_changed_since(
+    State(state): State<AppState>,
+    Query(params): Query<ChangedSinceQuery>,
+) -> Json<Vec<StockAnalysisResult>> {
+    let aliases = state.symbol_aliases.read().await.clone();
+    if let Some(ref db) = state.database {
+        match db.get_results_changed_since(params.timestamp).await {
+            Ok(results) => return Json(apply_summary_flag(suppress_renamed(results, &aliases), params.include_summary)),
+            Err(e) => tracing::warn!("Failed to get changed results from database: {}", e),
+        }
+    }
+
+    let mut results: Vec<StockAnalysisResult> =
+        state.all_results.read().await.iter().filter(|r| r.timestamp > params.timestamp).cloned().collect();
+    results.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then_with(|| a.ticker.cmp(&b.ticker)));
+    Json(apply_summary_flag(suppress_renamed(results, &aliases), params.include_summary))
+}
+
+async fn compare_filters_handler(
+    State(state): State<AppState>,
+    Json(request): Json<FilterCompareRequest>,
+) -> Result<Json<FilterComparison>, ApiError> {
+    let filter_a = resolve_compare_side(&state, "a", request.filter_a, request.preset_a.as_deref()).await?;
+    let filter_b = resolve_compare_side(&state, "b", request.filter_b, request.preset_b.as_deref()).await?;
+
+    let results = load_latest_results(&state).await;
+    let cap = request.max_tickers.unwrap_or(MAX_COMPARE_TICKERS).min(MAX_COMPARE_TICKERS);
+    let comparison = compare_filters(&results, &filter_a, &filter_b, cap);
+
+    if comparison.summary_a.matched == 0 && comparison.summary_b.matched == 0 {
+        return Err(ApiError::Unprocessable(format!(
+            "Neither filter matches any of the {} latest stored results",
+            comparison.total_results
+        )));
+    }
+
+    Ok(Json(comparison))
+}
+
+/// One side of a filter comparison: its inline filter, validated, or the
+/// filter of the preset it names
+async fn resolve_compare_side(
+    state: &AppState,
+    side: &str,
+    filter: Option<FilterSpec>,
+    preset: Option<&str>,
+) -> Result<FilterSpec, ApiError> {
+    match (filter, preset) {
+        (Some(filter), None) => {
+            filter.validate().map_err(|e| ApiError::InvalidFilter(format!("filter_{}: {}", side, e)))?;
+            Ok(filter)
+        }
+        (None, Some(name)) => Ok(load_preset(state, name).await?.filter.into()),
+        (Some(_), Some(_)) => Err(ApiError::InvalidRequest(format!("give either filter_{side} or preset_{side}, not both"))),
+        (None, None) => Err(ApiError::InvalidRequest(format!("either filter_{side} or preset_{side} is required"))),
+    }
+}
+
+async fn compare_ticker_filters_handler(
+    State(state): State<AppState>,
+    Json(request): Json<TickerCompareRequest>,
+) -> Result<Json<TickerFilterComparison>, ApiError> {
+    validate_filter(&request.filter_a)?;
+    validate_filter(&request.filter_b)?;
+
+    let analyzer = state.analyzer();
+    let (tickers, _) = state.fetch_tickers(&analyzer).await.map_err(ticker_fetch_error)?;
+    let results = if request.include_indicators { Some(load_latest_results(&state).await) } else { None };
+    let cap = request.max_tickers.unwrap_or(MAX_COMPARE_TICKERS).min(MAX_COMPARE_TICKERS);
+    Ok(Json(compare_ticker_filters(&tickers, results.as_deref(), &request.filter_a, &request.filter_b, cap)))
+}
+
+/// SMA crossovers across the ticker listing, from stored indicator history
+/// and cached or stored bars only; no price history is fetched
+async fn scan_crossovers_handler(
+    State(state): State<AppState>,
+    Json(scan): Json<CrossoverScan>,
+) -> Result<Json<CrossoverScanReport>, ApiError> {
+    scan.validate().map_err(ApiError::InvalidRequest)?;
+    let scan_db_error = |e: anyhow::Error| {
+        tracing::error!("Crossover scan query failed: {}", e);
+        ApiError::Database("Crossover scan query failed".to_string())
+    };
+
+    let analyzer = state.analyzer();
+    let (tickers, _) = state.fetch_tickers(&analyzer).await.map_err(ticker_fetch_error)?;
+    let as_of = state.clock.now().date_naive();
+    let mut stored_smas = match state.database {
+        Some(ref db) if scan.uses_stored_smas() => db.get_all_indicator_history(scan.history_start(as_of)).await.map_err(scan_db_error)?,
+        _ => HashMap::new(),
+    };
+    // Bars and events of the whole listing in one query each, not one per ticker
+    let start = scan.history_start(as_of).and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let end = as_of.and_hms_opt(23, 59, 59).unwrap().and_utc();
+    let (mut stored_bars, mut stored_events) = match state.database {
+        Some(ref db) => (
+            db.get_all_stock_data(start, end).await.map_err(scan_db_error)?,
+            db.get_all_corporate_events().await.map_err(scan_db_error)?,
+        ),
+        None => (HashMap::new(), HashMap::new()),
+    };
+    let mut series = HashMap::new();
+    for ticker in &tickers {
+        let history = stored_smas.remove(&ticker.symbol);
+        let stored = stored_bars.remove(&ticker.symbol).map(|bars| {
+            let events = stored_events.remove(&ticker.symbol).unwrap_or_default();
+            QuoteSeries { bars, source: "database".to_string(), fetched_at: state.clock.now(), quality: None, events }
+        });
+        if let Some(held) = held_scan_series(&state, &analyzer, &ticker.symbol, history, stored).await {
+            series.insert(ticker.symbol.clone(), held);
+        }
+    }
+    Ok(Json(scan_crossovers(&tickers, &series, &scan, as_of)))
+}
+
+/// What is held for `symbol` to scan: stored SMAs when given, and the
+/// cached daily series, else its `stored` bars, adjusted as the analyzer
+/// adjusts fetched history
+async fn held_scan_series(
+    state: &AppState,
+    analyzer: &StockAnalyzer,
+    symbol: &str,
+    history: Option<Vec<IndicatorHistoryPoint>>,
+    stored: Option<QuoteSeries>,
+) -> Option<ScanSeries> {
+    let cached = state.cache.get_quote_series(&HistoryRange::default().cache_key(symbol)).await;
+    let bars = cached.or(stored).map(|series| analyzer.adjust(series).bars).unwrap_or_default();
+    match history {
+        Some(history) => Some(ScanSeries::Stored { history, bars }),
+        None if !bars.is_empty() => Some(ScanSeries::Bars(bars)),
+        None => None,
+    }
+}
+
+/// Daily bars held for `symbol` without fetching: the cached daily series,
+/// else the stored bars from `start` through `as_of`, adjusted as the
+/// analyzer adjusts fetched history
+async fn held_daily_bars(
+    state: &AppState,
+    analyzer: &StockAnalyzer,
+    symbol: &str,
+    start: chrono::DateTime<chrono::Utc>,
+    as_of: NaiveDate,
+) -> anyhow::Result<Vec<StockData>> {
+    let cached = state.cache.get_quote_series(&HistoryRange::default().cache_key(symbol)).await;
+    let series = match (cached, &state.database) {
+        (Some(series), _) => Some(series),
+        (None, Some(db)) => {
+            let end = as_of.and_hms_opt(23, 59, 59).unwrap().and_utc();
+            let bars = db.get_stock_data(symbol, start, end).await?;
+            let events = match bars.is_empty() {
+                true => Vec::new(),
+                false => db.get_corporate_events(symbol).await?,
+            };
+            Some(QuoteSeries { bars, source: "database".to_string(), fetched_at: state.clock.now(), quality: None, events })
+        }
+        (None, None) => None,
+    };
+    Ok(series.map(|series| analyzer.adjust(series).bars).unwrap_or_default())
+}
+
+/// Most symbols `GET /api/compare` takes at once
+pub const MAX_COMPARE_SYMBOLS: usize = 10;
+/// Calendar days `GET /api/compare` covers unless `days` says otherwise
+pub const DEFAULT_COMPARE_DAYS: u32 = 180;
+/// Fewest closes in the window a symbol needs to be compared
+const MIN_COMPARE_CLOSES: usize = 2;
+
+#[derive(Deserialize)]
+struct CompareQuery {
+    /// Comma-separated, at most `MAX_COMPARE_SYMBOLS`
+    symbols: Option<String>,
+    /// Calendar days back from today, up to `MAX_SYMBOL_HISTORY_DAYS`
+    days: Option<u32>,
+    /// Rebase each series to 100 at the first common date
+    #[serde(default)]
+    normalize: bool,
+}
+
+/// One symbol's closes in a `SymbolComparison`, on its `dates`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparedSeries {
+    pub symbol: String,
+    /// Closes, or closes rebased to 100 when normalized
+    pub values: Vec<f64>,
+    pub total_return_pct: Option<f64>,
+    /// Largest fall from a peak within the window, as a positive percent
+    pub max_drawdown_pct: Option<f64>,
+    pub annualized_volatility_pct: Option<f64>,
+}
+
+/// Response of `GET /api/compare`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolComparison {
+    pub days: u32,
+    pub normalized: bool,
+    /// Dates every compared symbol has a close on, oldest first
+    pub dates: Vec<NaiveDate>,
+    pub series: Vec<ComparedSeries>,
+    /// Symbols with fewer than two cached or stored closes in the window,
+    /// left out of `dates` and `series`
+    pub insufficient_data: Vec<String>,
+}
+
+/// Compare `held` daily bars, already cut to the window, over the dates
+/// they share. Symbols with too few bars are listed rather than compared.
+pub fn compare_held_series(held: &[(String, Vec<StockData>)], days: u32, normalize: bool) -> SymbolComparison {
+    let (compared, insufficient): (Vec<_>, Vec<_>) = held.iter().partition(|(_, bars)| bars.len() >= MIN_COMPARE_CLOSES);
+    let windows: Vec<&[StockData]> = compared.iter().map(|(_, bars)| bars.as_slice()).collect();
+    let (dates, closes) = align_closes(&windows);
+    let series = compared
+        .iter()
+        .zip(closes)
+        .map(|((symbol, _), closes)| ComparedSeries {
+            symbol: symbol.clone(),
+            total_return_pct: total_return_pct(&closes),
+            max_drawdown_pct: max_drawdown_pct(&closes),
+            annualized_volatility_pct: annualized_volatility_pct(&closes),
+            values: if normalize { rebase_to_100(&closes) } else { closes },
+        })
+        .collect();
+    SymbolComparison {
+        days,
+        normalized: normalize,
+        dates,
+        series,
+        insufficient_data: insufficient.into_iter().map(|(symbol, _)| symbol.clone()).collect(),
+    }
+}
+
+/// Daily closes of several symbols aligned on their common dates, with
+/// return, drawdown and volatility over the window. Only cached or stored
+/// history is read; nothing is fetched.
+async fn compare_symbols(
+    State(state): State<AppState>,
+    Query(params): Query<CompareQuery>,
+) -> Result<Json<SymbolComparison>, ApiError> {
+    let mut symbols: Vec<String> = Vec::new();
+    for symbol in params.symbols.as_deref().unwrap_or_default().split(',').map(normalize_symbol) {
+        if !symbol.is_empty() && !symbols.contains(&symbol) {
+            symbols.push(symbol);
+        }
+    }
+    if symbols.is_empty() {
+        return Err(ApiError::InvalidRequest("symbols must list at least one symbol".to_string()));
+    }
+    if symbols.len() > MAX_COMPARE_SYMBOLS {
+        return Err(ApiError::InvalidRequest(format!("at most {} symbols can be compared", MAX_COMPARE_SYMBOLS)));
+    }
+    let days = params.days.unwrap_or(DEFAULT_COMPARE_DAYS);
+    if days == 0 || days > MAX_SYMBOL_HISTORY_DAYS {
+        return Err(ApiError::InvalidRequest(format!("days must be between 1 and {}", MAX_SYMBOL_HISTORY_DAYS)));
+    }
+
+    let analyzer = state.analyzer();
+    let now = state.clock.now();
+    let start = (now - chrono::Duration::days(i64::from(days))).date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let mut held = Vec::with_capacity(symbols.len());
+    for symbol in symbols {
+        let bars = held_daily_bars(&state, &analyzer, &symbol, start, now.date_naive()).await.map_err(|e| {
+            tracing::error!("Failed to load held history for {}: {}", symbol, e);
+            ApiError::Database("Failed to load held history".to_string())
+        })?;
+        let window = bars.into_iter().filter(|bar| bar.timestamp >= start).collect();
+        held.push((symbol, window));
+    }
+    Ok(Json(compare_held_series(&held, days, params.normalize)))
+}
+
+/// Apply both filters to the same results and split the matched tickers
+/// into only-in-A, only-in-B and in-both, listing at most `cap` per group
+pub fn compare_filters(
+    results: &[StockAnalysisResult],
+    filter_a: &FilterSpec,
+    filter_b: &FilterSpec,
+    cap: usize,
+) -> FilterComparison {
+    let matched_a = filter_results(results, filter_a);
+    let matched_b = filter_results(results, filter_b);
+
+    let tickers_a: BTreeSet<&str> = matched_a.iter().map(|r| r.ticker.as_str()).collect();
+    let tickers_b: BTreeSet<&str> = matched_b.iter().map(|r| r.ticker.as_str()).collect();
+
+    FilterComparison {
+        total_results: results.len(),
+        only_in_a: ticker_group(tickers_a.difference(&tickers_b), cap),
+        only_in_b: ticker_group(tickers_b.difference(&tickers_a), cap),
+        in_both: ticker_group(tickers_a.intersection(&tickers_b), cap),
+        summary_a: summarize_side(&matched_a),
+        summary_b: summarize_side(&matched_b),
+    }
+}
+
+/// Apply both filters to the ticker listing and split the matches into
+/// only-in-A, only-in-B and in-both, listing at most `cap` per group. With
+/// `results`, a ticker also needs its latest result to pass the filter.
+pub fn compare_ticker_filters(
+    tickers: &[TickerInfo],
+    results: Option<&[StockAnalysisResult]>,
+    filter_a: &FilterSpec,
+    filter_b: &FilterSpec,
+    cap: usize,
+) -> TickerFilterComparison {
+    let matches = |filter: &FilterSpec| -> BTreeMap<&str, Option<&str>> {
+        let (listed, _) = StockAnalyzer::filter_tickers_spec(tickers, filter);
+        let screened: Option<HashSet<String>> =
+            results.map(|results| filter_results(results, filter).into_iter().map(|r| r.ticker).collect());
+        let listed: HashSet<String> = listed
+            .into_iter()
+            .map(|t| t.symbol)
+            .filter(|symbol| screened.as_ref().is_none_or(|screened| screened.contains(symbol)))
+            .collect();
+        tickers
+            .iter()
+            .filter(|t| listed.contains(&t.symbol))
+            .map(|t| (t.symbol.as_str(), t.sector.as_deref()))
+            .collect()
+    };
+    let matched_a = matches(filter_a);
+    let matched_b = matches(filter_b);
+
+    let mut sectors: BTreeMap<String, SectorDelta> = BTreeMap::new();
+    for (matched, is_a) in [(&matched_a, true), (&matched_b, false)] {
+        for sector in matched.values() {
+            let entry = sectors
+                .entry(sector.unwrap_or(UNKNOWN_SECTOR).to_string())
+                .or_insert(SectorDelta { a: 0, b: 0, delta: 0 });
+            if is_a {
+                entry.a += 1;
+            } else {
+                entry.b += 1;
+            }
+            entry.delta = entry.b as i64 - entry.a as i64;
+        }
+    }
+
+    let symbols_a: BTreeSet<&str> = matched_a.keys().copied().collect();
+    let symbols_b: BTreeSet<&str> = matched_b.keys().copied().collect();
+    TickerFilterComparison {
+        total_tickers: tickers.len(),
+        matched_a: symbols_a.len(),
+        matched_b: symbols_b.len(),
+        only_in_a: ticker_group(symbols_a.difference(&symbols_b), cap),
+        only_in_b: ticker_group(symbols_b.difference(&symbols_a), cap),
+        in_both: ticker_group(symbols_a.intersection(&symbols_b), cap),
+        sectors,
+    }
+}
+
+fn ticker_group<'a>(tickers: impl Iterator<Item = &'a &'a str>, cap: usize) -> TickerGroup {
+    let tickers: Vec<&str> = tickers.copied().collect();
+    TickerGroup {
+        count: tickers.len(),
+        truncated: tickers.len() > cap,
+        tickers: tickers.into_iter().take(cap).map(str::to_string).collect(),
+    }
+}
+
+fn summarize_side(results: &[StockAnalysisResult]) -> FilterSideSummary {
+    let average = |values: Vec<f64>| {
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.iter().sum::<f64>() / values.len() as f64)
+        }
+    };
+
+    FilterSideSummary {
+        matched: results.len(),
+        opportunities: results.iter().filter(|r| r.is_opportunity).count(),
+        avg_rsi: average(results.iter().filter_map(|r| r.rsi).collect()),
+        avg_pct_change: average(results.iter().filter_map(|r| r.pct_change).collect()),
+    }
+}
+
+async fn get_field_stats(State(state): State<AppState>) -> Json<Vec<FieldStats>> {
+    let cache_key = "latest_results";
+    if let Some(stats) = state.cache.get_field_stats(cache_key).await {
+        return Json(stats);
+    }
+
+    let results = load_latest_results(&state).await;
+    let stats = compute_field_stats(&results);
+    state.cache.cache_field_stats(cache_key.to_string(), stats.clone()).await;
+    Json(stats)
+}
+
+/// Reads one numeric field of a result
+type ResultField = fn(&StockAnalysisResult) -> Option<f64>;
+
+/// Every numeric `StockAnalysisResult` field by its serialized name, with
+/// `market_cap` parsed into `market_cap_value`
+const NUMERIC_RESULT_FIELDS: &[(&str, ResultField)] = &[
+    ("current_price", |r| r.current_price),
+    ("rsi", |r| r.rsi),
+    ("sma_20", |r| r.sma_20),
+    ("sma_50", |r| r.sma_50),
+    ("macd", |r| r.macd),
+    ("macd_signal", |r| r.macd_signal),
+    ("macd_histogram", |r| r.macd_histogram),
+    ("volume", |r| r.volume.map(|v| v as f64)),
+    ("pct_change", |r| r.pct_change),
+    ("market_cap_value", |r| r.market_cap.as_deref().and_then(|s| parse_market_cap(s).ok())),
+    ("analyst_target", |r| r.analyst_target),
+    ("upside_to_target_pct", |r| r.upside_to_target_pct),
+    ("obv", |r| r.obv),
+    ("vwap", |r| r.vwap),
+    ("volume_vs_avg", |r| r.volume_vs_avg),
+    ("high_52w", |r| r.high_52w),
+    ("low_52w", |r| r.low_52w),
+    ("pct_from_high", |r| r.pct_from_high),
+    ("pct_from_low", |r| r.pct_from_low),
+    ("rs_1w", |r| r.rs_1w),
+    ("rs_1m", |r| r.rs_1m),
+    ("rs_3m", |r| r.rs_3m),
+    ("rs_rank", |r| r.rs_rank),
+    ("adx", |r| r.adx),
+    ("plus_di", |r| r.plus_di),
+    ("minus_di", |r| r.minus_di),
+    ("rsi_oversold_used", |r| r.rsi_oversold_used),
+    ("rsi_overbought_used", |r| r.rsi_overbought_used),
+    ("rsi_sector_zscore", |r| r.rsi_sector_zscore),
+    ("rsi_industry_zscore", |r| r.rsi_industry_zscore),
+    ("opportunity_streak", |r| Some(r.opportunity_streak as f64)),
+];
+
+/// Per-field statistics over every numeric column of the results.
+/// Fields with no values are kept (count 0) so clients can disable them.
+pub fn compute_field_stats(results: &[StockAnalysisResult]) -> Vec<FieldStats> {
+    NUMERIC_RESULT_FIELDS
+        .iter()
+        .map(|(field, value)| field_stats(field, results.iter().map(value)))
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct SectorSummaryQuery {
+    /// Hide sectors with fewer results than this
+    min_stocks: Option<usize>,
+}
+
+async fn get_sector_summary(
+    State(state): State<AppState>,
+    Query(params): Query<SectorSummaryQuery>,
+) -> Json<Vec<SectorSummary>> {
+    let results = load_latest_results(&state).await;
+    Json(summarize_sectors(&results, params.min_stocks.unwrap_or(0)))
+}
+
+/// Group results by sector, largest sector first. Missing or non-finite
+/// RSI and percent-change values are left out of the averages rather than
+/// counted as zero.
+pub fn summarize_sectors(results: &[StockAnalysisResult], min_stocks: usize) -> Vec<SectorSummary> {
+    let mut by_sector: BTreeMap<&str, Vec<&StockAnalysisResult>> = BTreeMap::new();
+    for result in results {
+        let sector = result
+            .sector
+            .as_deref()
+            .map(str::trim)
+            .filter(|sector| !sector.is_empty())
+            .unwrap_or(UNKNOWN_SECTOR);
+        by_sector.entry(sector).or_default().push(result);
+    }
+
+    let mut summaries: Vec<SectorSummary> = by_sector
+        .into_iter()
+        .filter(|(_, members)| members.len() >= min_stocks)
+        .map(|(sector, members)| {
+            let rsi: Vec<f64> = members.iter().filter_map(|r| r.rsi).filter(|v| v.is_finite()).collect();
+            let mut pct_change: Vec<f64> =
+                members.iter().filter_map(|r| r.pct_change).filter(|v| v.is_finite()).collect();
+            pct_change.sort_by(|a, b| a.total_cmp(b));
+
+            SectorSummary {
+                sector: sector.to_string(),
+                count: members.len(),
+                avg_rsi: if rsi.is_empty() { None } else { Some(rsi.iter().sum::<f64>() / rsi.len() as f64) },
+                median_pct_change: median(&pct_change),
+                opportunities: members.iter().filter(|r| r.is_opportunity).count(),
+                macd_bullish: members.iter().filter(|r| macd_vs_signal(r) == Some(Ordering::Greater)).count(),
+                macd_bearish: members.iter().filter(|r| macd_vs_signal(r) == Some(Ordering::Less)).count(),
+            }
+        })
+        .collect();
+
+    summaries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.sector.cmp(&b.sector)));
+    summaries
+}
+
+fn macd_vs_signal(result: &StockAnalysisResult) -> Option<Ordering> {
+    result.macd?.partial_cmp(&result.macd_signal?)
+}
+
+async fn get_cache_stats(
+    State(state): State<AppState>,
+) -> Result<Json<crate::cache::CacheStats>, ApiError> {
+    let stats = state.cache.get_cache_stats().await;
+    Ok(Json(stats))
+}
+
+/// `?check=true` on the database stats endpoint
+#[derive(Deserialize)]
+struct DatabaseStatsQuery {
+    #[serde(default)]
+    check: bool,
+}
+
+async fn get_database_stats(
+    State(state): State<AppState>,
+    Query(params): Query<DatabaseStatsQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if let Some(ref db) = state.database {
+        let stats = db.get_analysis_stats().await.map_err(|e| {
+            tracing::error!("Failed to get database stats: {}", e);
+            ApiError::Database(format!("Failed to read database stats: {}", e))
+        })?;
+        let mut body = serde_json::to_value(stats).unwrap();
+        if params.check {
+            let report = db.integrity_check().await.map_err(|e| {
+                tracing::error!("Database integrity check failed to run: {}", e);
+                ApiError::Database(format!("Failed to check database integrity: {}", e))
+            })?;
+            body["integrity"] = serde_json::to_value(report).unwrap();
+        }
+        Ok(Json(body))
+    } else {
+        Ok(Json(serde_json::json!({
+            "error": "Database not available"
+        })))
+    }
+}
+
+async fn run_maintenance(State(state): State<AppState>) -> Result<Json<MaintenanceReport>, ApiError> {
+    Ok(Json(state.run_maintenance().await?))
+}
+
+async fn clear_cache(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    state.cache.clear_cache().await;
+    tracing::info!("Cache cleared via API request");
+    Ok(Json(serde_json::json!({
+        "status": "success",
+        "message": "Cache cleared successfully"
+    })))
+}
+
+async fn persist_cache(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let entries = state.persist_cache().await?;
+    Ok(Json(serde_json::json!({
+        "status": "success",
+        "entries": entries,
+    })))
+}
+
+/// `/ws?since_seq=42&topics=status,ticker:AAPL`: topics to subscribe to on
+/// connect, and the last broadcast the client saw before reconnecting
+#[derive(Deserialize)]
+struct WsQuery {
+    since_seq: Option<u64>,
+    topics: Option<String>,
+}
+
+async fn websocket_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(params): Query<WsQuery>,
+) -> Result<Response, ApiError> {
+    let topics = params
+        .topics
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|topic| !topic.trim().is_empty())
+        .map(str::parse::<Topic>)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(ApiError::InvalidRequest)?;
+    Ok(ws.on_upgrade(move |socket| handle_websocket(socket, state, topics, params.since_seq)))
+}
+
+/// Counts a WebSocket connection in `AppState::websocket_clients` while alive
+struct ConnectedClient(Arc<AtomicUsize>);
+
+impl ConnectedClient {
+    fn new(clients: &Arc<AtomicUsize>) -> Self {
+        clients.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Self(clients.clone())
+    }
+}
+
+impl Drop for ConnectedClient {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+/// Serve one WebSocket client. After the status snapshot, `topics` are
+/// subscribed to and, with `since_seq`, the buffered broadcasts on them the
+/// client missed are replayed before live ones. Every `WS_HEARTBEAT_INTERVAL`
+/// the client is sent a Ping and a `{"type": "heartbeat", "ts": ...}` frame;
+/// a client that has sent nothing, not even a Pong, for `WS_IDLE_TIMEOUT` is
+/// closed.
+async fn handle_websocket(socket: WebSocket, state: AppState, topics: Vec<Topic>, since_seq: Option<u64>) {
+    tracing::info!("🔌 New WebSocket connection established");
+    let _client = ConnectedClient::new(&state.websocket_clients);
+    let (mut sender, mut receiver) = socket.split();
+    let mut broadcast_rx = state.broadcast_tx.subscribe();
+    let mut subscriptions = Subscriptions::default();
+    
+    // Send current status immediately, whatever the client goes on to subscribe to
+    let status = state.continuous_analysis_status.read().await.clone();
+    let msg = serde_json::to_string(&status).unwrap_or_default();
+    if let Err(e) = sender.send(Message::Text(msg)).await {
+        tracing::warn!("Failed to send initial status: {}", e);
+        return;
+    }
+    
+    // Query topics are acknowledged like subscribe messages
+    let mut frames: Vec<String> = topics
+        .into_iter()
+        .map(|topic| serde_json::to_string(&subscriptions.apply(ClientCommand::Subscribe(topic))).unwrap_or_default())
+        .collect();
+    // Broadcasts after `since_seq` are replayed from the log. `broadcast_rx`
+    // subscribed first, so it queued every later one; those up to
+    // `replayed_through` were read from the log and are skipped there.
+    let mut replayed_through = 0;
+    if let Some(since_seq) = since_seq {
+        let latest = state.message_log.latest_seq();
+        let missed = state.message_log.since(since_seq, usize::MAX);
+        replayed_through = missed.last().map_or(since_seq.min(latest), |message| message.seq);
+        // A since_seq past the latest comes from before a restart
+        let complete = since_seq <= latest && missed.first().is_none_or(|message| message.seq == since_seq + 1);
+        frames.extend(
+            missed
+                .iter()
+                .filter(|message| subscriptions.wants(&message.message))
+                .map(|message| serde_json::to_string(message).unwrap_or_default()),
+        );
+        let replayed = ServerReply::Replayed { since_seq, through_seq: replayed_through, complete };
+        frames.push(serde_json::to_string(&replayed).unwrap_or_default());
+    }
+    for frame in frames {
+        if let Err(e) = sender.send(Message::Text(frame)).await {
+            tracing::warn!("Failed to send replayed broadcast: {}", e);
+            return;
+        }
+    }
+    
+    tracing::info!("📡 WebSocket ready to receive broadcasts");
+    let heartbeat = to_chrono(WS_HEARTBEAT_INTERVAL);
+    let mut last_activity = state.clock.now();
+    let mut next_heartbeat = last_activity + heartbeat;
+    
+    // Handle incoming messages and broadcast updates
+    loop {
+        tokio::select! {
+            // Handle incoming messages from client (mostly pings/pongs)
+            msg = receiver.next() => {
+                if matches!(msg, Some(Ok(_))) {
+                    last_activity = state.clock.now();
+                }
+                match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        tracing::debug!("Received WebSocket message: {}", text);
+                        let reply = serde_json::to_string(&subscriptions.handle(&text)).unwrap_or_default();
+                        if let Err(e) = sender.send(Message::Text(reply)).await {
+                            tracing::warn!("Failed to send subscription reply: {}", e);
+                            break;
+                        }
+                    },
+                    Some(Ok(Message::Binary(_))) => {
+                        tracing::debug!("Received binary message (ignored)");
+                    },
+                    Some(Ok(Message::Ping(data))) => {
+                        if let Err(e) = sender.send(Message::Pong(data)).await {
+                            tracing::warn!("Failed to send pong: {}", e);
+                            break;
+                        }
+                    },
+                    Some(Ok(Message::Pong(_))) => {
+                        tracing::debug!("Received pong");
+                    },
+                    Some(Ok(Message::Close(_))) => {
+                        tracing::info!("🔌 WebSocket client initiated close");
+                        break;
+                    },
+                    Some(Err(e)) => {
+                        tracing::warn!("WebSocket receive error: {}", e);
+                        break;
+                    },
+                    None => {
+                        tracing::info!("🔌 WebSocket receiver stream ended");
+                        break;
+                    }
+                }
+            },
+            // Handle broadcast updates from server
+            status = broadcast_rx.recv() => {
+                match status {
+                    Ok(message) if message.seq <= replayed_through || !subscriptions.wants(&message.message) => {},
+                    Ok(message) => {
+                        let msg = serde_json::to_string(&message).unwrap_or_default();
+                        if let Err(e) = sender.send(Message::Text(msg)).await {
+                            tracing::warn!("Failed to send broadcast: {}", e);
+                            break;
+                        }
+                    },
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("WebSocket client fell behind; skipped {} broadcasts", skipped);
+                    },
+                    Err(e) => {
+                        tracing::debug!("Broadcast channel error (normal on shutdown): {}", e);
+                        break;
+                    }
+                }
+            }
+            _ = state.clock.sleep_until(next_heartbeat) => {
+                let now = state.clock.now();
+                if now - last_activity > to_chrono(WS_IDLE_TIMEOUT) {
+                    tracing::info!("🔌 Closing WebSocket idle since {}", last_activity);
+                    let close = CloseFrame { code: close_code::AWAY, reason: "idle timeout".into() };
+                    let _ = sender.send(Message::Close(Some(close))).await;
+                    break;
+                }
+                next_heartbeat = now + heartbeat;
+                let frame = serde_json::json!({ "type": "heartbeat", "ts": now }).to_string();
+                let sent = match sender.send(Message::Ping(Vec::new())).await {
+                    Ok(()) => sender.send(Message::Text(frame)).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = sent {
+                    tracing::warn!("Failed to send heartbeat: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+    
+    tracing::info!("🔌 WebSocket connection closed");
+}
+
+#[derive(Deserialize)]
+struct EventsQuery {
+    after_seq: Option<u64>,
+    timeout: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventsResponse {
+    pub latest_seq: u64,
+    pub messages: Vec<SequencedMessage>,
+}
+
+/// Long-poll fallback for clients that cannot open a WebSocket.
+/// Waits up to `timeout` seconds for messages newer than `after_seq`.
+async fn get_events(
+    State(state): State<AppState>,
+    Query(params): Query<EventsQuery>,
+) -> Result<Json<EventsResponse>, ApiError> {
+    let after_seq = params.after_seq.unwrap_or(0);
+    let timeout = Duration::from_secs(params.timeout.unwrap_or(25).min(LONG_POLL_MAX_TIMEOUT_SECS));
+
+    let _permit = state.long_poll_permits.clone().try_acquire_owned().map_err(|_| ApiError::RateLimited {
+        message: "too many clients are waiting for events".to_string(),
+        retry_after_secs: Some(1),
+    })?;
+
+    // Subscribe before checking the buffer so a push in between is not missed
+    let mut updates = state.message_log.subscribe();
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    // An after_seq past the latest comes from before a restart, so the
+    // client is told the new latest_seq at once rather than left waiting
+    let latest_seq = state.message_log.latest_seq();
+    if after_seq > latest_seq {
+        return Ok(Json(EventsResponse { latest_seq, messages: Vec::new() }));
+    }
+
+    loop {
+        let messages = state.message_log.since(after_seq, LONG_POLL_BATCH_SIZE);
+        if !messages.is_empty() {
+            return Ok(Json(EventsResponse {
+                latest_seq: state.message_log.latest_seq(),
+                messages,
+            }));
+        }
+
+        match tokio::time::timeout_at(deadline, updates.changed()).await {
+            Ok(Ok(())) => continue,
+            _ => break,
+        }
+    }
+
+    Ok(Json(EventsResponse {
+        latest_seq: state.message_log.latest_seq(),
+        messages: Vec::new(),
+    }))
+}
+
+async fn list_symbol_aliases(State(state): State<AppState>) -> Json<Vec<SymbolAlias>> {
+    Json(state.symbol_aliases.read().await.all())
+}
+
+/// Record a ticker change. Rejected with 409 if it would make a symbol an
+/// alias of itself through a chain of renames.
+async fn create_symbol_alias(
+    State(state): State<AppState>,
+    Json(alias): Json<SymbolAlias>,
+) -> Result<Json<SymbolAlias>, ApiError> {
+    let alias = SymbolAlias::new(&alias.old_symbol, &alias.new_symbol, alias.effective_date);
+
+    // Held across the database write so concurrent requests are validated in turn
+    let mut aliases = state.symbol_aliases.write().await;
+
+    // Validate against a copy so a failed database write leaves memory untouched
+    let mut updated = aliases.clone();
+    updated.insert(alias.clone()).map_err(|e| match e {
+        AliasError::Cycle(_) => ApiError::Conflict(e.to_string()),
+        _ => ApiError::InvalidRequest(e.to_string()),
+    })?;
+
+    if let Some(ref db) = state.database {
+        db.upsert_symbol_alias(&alias).await.map_err(|e| {
+            tracing::error!("Failed to store symbol alias: {}", e);
+            ApiError::Database("Failed to store symbol alias".to_string())
+        })?;
+    }
+
+    *aliases = updated;
+    drop(aliases);
+    // Renamed symbols drop out of the results listings
+    state.bump_results_version();
+    tracing::info!("{} renamed to {} from {}", alias.old_symbol, alias.new_symbol, alias.effective_date);
+
+    Ok(Json(alias))
+}
+
+/// Symbols the continuous loop is currently skipping after repeated fetch
+/// failures, most failures first
+async fn list_symbol_health(State(state): State<AppState>) -> Json<Vec<SymbolHealth>> {
+    let status = state.continuous_analysis_status.read().await;
+    // Between cycles, report what the upcoming cycle will skip
+    let cycle = if status.is_running { status.current_cycle } else { status.current_cycle + 1 };
+    drop(status);
+    Json(state.failed_symbols.read().await.skipped(cycle as u64))
+}
+
+/// Forget a symbol's failures so the next cycle tries it again
+async fn reset_symbol_health(
+    State(state): State<AppState>,
+    axum::extract::Path(symbol): axum::extract::Path<String>,
+) -> Result<Json<SymbolHealth>, ApiError> {
+    let symbol = normalize_symbol(&symbol);
+    let mut failed = state.failed_symbols.write().await;
+    let Some(health) = failed.get(&symbol).cloned() else {
+        return Err(ApiError::NotFound(format!("no failures recorded for {}", symbol)));
+    };
+
+    if let Some(ref db) = state.database {
+        db.delete_symbol_health(&symbol).await.map_err(|e| {
+            tracing::error!("Failed to reset symbol health: {}", e);
+            ApiError::Database("Failed to reset symbol health".to_string())
+        })?;
+    }
+    failed.clear(&symbol);
+    tracing::info!("Reset failure count for {}", symbol);
+
+    Ok(Json(health))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistRequest {
+    pub symbol: String,
+}
+
+/// Response of `POST /api/watchlist`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchlistAdded {
+    pub symbol: String,
+    /// False when the symbol was already on the watchlist
+    pub added: bool,
+}
+
+fn watchlist_db(state: &AppState) -> Result<&Database, ApiError> {
+    state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)
+}
+
+fn watchlist_error(e: anyhow::Error) -> ApiError {
+    tracing::error!("Watchlist query failed: {}", e);
+    ApiError::Database("Watchlist query failed".to_string())
+}
+
+/// A watchlist entry with the quote poller's latest price for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistItem {
+    #[serde(flatten)]
+    pub entry: WatchlistEntry,
+    pub last_price: Option<f64>,
+    /// When `last_price` was quoted; `None` until the poller has seen the symbol
+    pub quote_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// `?include_deleted=true` on a list endpoint; admin-only when admin keys are configured
+#[derive(Deserialize)]
+struct DeletedQuery {
+    #[serde(default)]
+    include_deleted: bool,
+}
+
+impl DeletedQuery {
+    fn include_deleted(&self, state: &AppState, headers: &HeaderMap) -> Result<bool, ApiError> {
+        if self.include_deleted {
+            require_admin(state, headers)?;
+        }
+        Ok(self.include_deleted)
+    }
+}
+
+/// Refuse the request unless it carries an admin key. Servers without
+/// configured admin keys have no admins to tell apart, so everyone passes.
+fn require_admin(state: &AppState, headers: &HeaderMap) -> Result<(), ApiError> {
+    if state.config.admin_keys.is_empty() {
+        return Ok(());
+    }
+    let is_admin = headers
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|key| state.config.is_admin_key(key));
+    if is_admin {
+        Ok(())
+    } else {
+        Err(ApiError::Forbidden("an admin key is required".to_string()))
+    }
+}
+
+/// Client address recorded in the audit log; absent when the server was
+/// not started with connection info (as in tests)
+type SourceAddr = Option<ConnectInfo<SocketAddr>>;
+
+fn source_ip(source: &SourceAddr) -> Option<String> {
+    source.as_ref().map(|ConnectInfo(addr)| addr.ip().to_string())
+}
+
+async fn get_watchlist(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<DeletedQuery>,
+) -> Result<Json<Vec<WatchlistItem>>, ApiError> {
+    let include_deleted = params.include_deleted(&state, &headers)?;
+    let db = watchlist_db(&state)?;
+    let entries = db.get_watchlist(include_deleted).await.map_err(watchlist_error)?;
+    let live_quotes = state.live_quotes.read().await;
+    let items = entries
+        .into_iter()
+        .map(|entry| {
+            let quote = live_quotes.get(&entry.symbol);
+            WatchlistItem {
+                last_price: quote.map(|quote| quote.price),
+                quote_timestamp: quote.map(|quote| quote.timestamp),
+                entry,
+            }
+        })
+        .collect();
+    Ok(Json(items))
+}
+
+/// Add a symbol to the watchlist: 201 when added, 200 if it was already there
+async fn add_to_watchlist(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    Json(request): Json<WatchlistRequest>,
+) -> Result<(StatusCode, Json<WatchlistAdded>), ApiError> {
+    let symbol = normalize_symbol(&request.symbol);
+    if symbol.is_empty() {
+        return Err(ApiError::InvalidRequest("symbol must not be empty".to_string()));
+    }
+
+    let db = watchlist_db(&state)?;
+    let added = db.add_to_watchlist(&symbol, source_ip(&source).as_deref()).await.map_err(watchlist_error)?;
+    let status = if added { StatusCode::CREATED } else { StatusCode::OK };
+    Ok((status, Json(WatchlistAdded { symbol, added })))
+}
+
+/// Response of `POST /api/watchlist/import`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchlistImport {
+    /// Symbols put on the watchlist, in file order
+    pub added: Vec<String>,
+    /// Symbols already on the watchlist or repeated in the file
+    pub skipped_duplicates: Vec<String>,
+    /// Symbols the ticker directory has never listed; nothing is added for them
+    pub unknown_symbols: Vec<String>,
+}
+
+/// Add the symbols of a CSV or plain-text body to the watchlist in one
+/// transaction. Symbols the ticker directory doesn't know are reported
+/// back rather than added; a body with more rows than
+/// `AppConfig::watchlist_import_max_rows` is refused whole.
+async fn import_watchlist(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    body: String,
+) -> Result<Json<WatchlistImport>, ApiError> {
+    let rows = parse_watchlist_import(&body).map_err(ApiError::InvalidRequest)?;
+    let max_rows = state.config.watchlist_import_max_rows;
+    if rows.len() > max_rows {
+        return Err(ApiError::InvalidRequest(format!(
+            "{} symbol rows is more than the {} one import may have",
+            rows.len(),
+            max_rows
+        )));
+    }
+    let db = watchlist_db(&state)?;
+
+    // Never fetched since the server started: load the ticker list first
+    if state.ticker_directory.read().await.is_empty() {
+        state.fetch_tickers(&state.analyzer()).await.map_err(|e| {
+            tracing::warn!("Failed to load the ticker list for a watchlist import: {}", e);
+            ApiError::from_fetch("The ticker list is unavailable to check symbols against", e)
+        })?;
+    }
+    let on_watchlist: HashSet<String> = db
+        .get_watchlist(false)
+        .await
+        .map_err(watchlist_error)?
+        .into_iter()
+        .map(|entry| entry.symbol)
+        .collect();
+
+    let now = state.clock.now();
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+    let mut report = WatchlistImport { added: Vec::new(), skipped_duplicates: Vec::new(), unknown_symbols: Vec::new() };
+    {
+        let directory = state.ticker_directory.read().await;
+        for row in rows {
+            if !seen.insert(row.symbol.clone()) || on_watchlist.contains(&row.symbol) {
+                report.skipped_duplicates.push(row.symbol);
+            } else if directory.get(&row.symbol).is_none() {
+                report.unknown_symbols.push(row.symbol);
+            } else {
+                entries.push(WatchlistEntry { symbol: row.symbol, added_at: row.added_at.unwrap_or(now), note: row.note, deleted_at: None });
+            }
+        }
+    }
+    report.added = db.import_watchlist(&entries, source_ip(&source).as_deref()).await.map_err(watchlist_error)?;
+    tracing::info!(
+        "📥 Watchlist import: {} added, {} duplicates, {} unknown",
+        report.added.len(),
+        report.skipped_duplicates.len(),
+        report.unknown_symbols.len()
+    );
+    Ok(Json(report))
+}
+
+/// The watchlist as CSV: each symbol with its note, when it was added, its
+/// latest known price and the RSI of its latest result
+async fn export_watchlist(State(state): State<AppState>) -> Result<Response, ApiError> {
+    let db = watchlist_db(&state)?;
+    let entries = db.get_watchlist(false).await.map_err(watchlist_error)?;
+    let latest: HashMap<String, StockAnalysisResult> = db
+        .get_latest_results(None)
+        .await
+        .map_err(watchlist_error)?
+        .into_iter()
+        .map(|result| (result.ticker.clone(), result))
+        .collect();
+    let live_quotes = state.live_quotes.read().await;
+    let rows: Vec<ExportRow> = entries
+        .into_iter()
+        .map(|entry| {
+            let result = latest.get(&entry.symbol);
+            ExportRow {
+                last_price: live_quotes
+                    .get(&entry.symbol)
+                    .map(|quote| quote.price)
+                    .or_else(|| result.and_then(|result| result.current_price)),
+                rsi: result.and_then(|result| result.rsi),
+                symbol: entry.symbol,
+                note: entry.note,
+                added_at: entry.added_at,
+            }
+        })
+        .collect();
+    drop(live_quotes);
+
+    let body = watchlist_csv(&rows).map_err(|e| {
+        tracing::error!("Failed to write the watchlist CSV: {}", e);
+        ApiError::Internal("Failed to write the watchlist CSV".to_string())
+    })?;
+    let disposition = "attachment; filename=\"watchlist.csv\"";
+    Ok(([(header::CONTENT_TYPE, "text/csv"), (header::CONTENT_DISPOSITION, disposition)], body).into_response())
+}
+
+async fn remove_from_watchlist(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    axum::extract::Path(symbol): axum::extract::Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let symbol = normalize_symbol(&symbol);
+    let db = watchlist_db(&state)?;
+    if db.remove_from_watchlist(&symbol, source_ip(&source).as_deref()).await.map_err(watchlist_error)? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::NotFound(format!("{} is not on the watchlist", symbol)))
+    }
+}
+
+async fn restore_watchlist_entry(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    axum::extract::Path(symbol): axum::extract::Path<String>,
+) -> Result<Json<WatchlistEntry>, ApiError> {
+    let symbol = normalize_symbol(&symbol);
+    watchlist_db(&state)?
+        .restore_watchlist_entry(&symbol, source_ip(&source).as_deref())
+        .await
+        .map_err(watchlist_error)?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("{} has not been removed from the watchlist", symbol)))
+}
+
+#[derive(Deserialize)]
+struct NotificationsQuery {
+    limit: Option<i32>,
+}
+
+/// Logged notification deliveries, newest first
+async fn list_notifications(
+    State(state): State<AppState>,
+    Query(params): Query<NotificationsQuery>,
+) -> Result<Json<Vec<Delivery>>, ApiError> {
+    let db = state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)?;
+    let deliveries = db.get_notification_log(params.limit).await.map_err(|e| {
+        tracing::error!("Notification log query failed: {}", e);
+        ApiError::Database("Notification log query failed".to_string())
+    })?;
+    Ok(Json(deliveries))
+}
+
+#[derive(Deserialize)]
+struct AnomaliesQuery {
+    ticker: Option<String>,
+    limit: Option<u32>,
+}
+
+/// Flagged and quarantined results, newest first, for tracking down bad
+/// upstream data
+async fn list_anomalies(
+    State(state): State<AppState>,
+    Query(params): Query<AnomaliesQuery>,
+) -> Result<Json<Vec<AnomalousResult>>, ApiError> {
+    let db = state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)?;
+    let ticker = params.ticker.as_deref().map(normalize_symbol);
+    let limit = params.limit.unwrap_or(DEFAULT_ANOMALY_LIMIT).clamp(1, MAX_ANOMALY_LIMIT);
+    let anomalies = db.get_anomalies(ticker.as_deref(), limit).await.map_err(|e| {
+        tracing::error!("Anomaly query failed: {}", e);
+        ApiError::Database("Anomaly query failed".to_string())
+    })?;
+    Ok(Json(anomalies))
+}
+
+fn alerts_db(state: &AppState) -> Result<&Database, ApiError> {
+    state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)
+}
+
+fn alert_db_error(e: anyhow::Error) -> ApiError {
+    tracing::error!("Alert query failed: {}", e);
+    ApiError::Database("Alert query failed".to_string())
+}
+
+async fn list_alerts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<DeletedQuery>,
+) -> Result<Json<Vec<Alert>>, ApiError> {
+    let include_deleted = params.include_deleted(&state, &headers)?;
+    let alerts = alerts_db(&state)?.get_alerts(include_deleted).await.map_err(alert_db_error)?;
+    Ok(Json(alerts))
+}
+
+async fn create_alert(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    Json(request): Json<NewAlert>,
+) -> Result<(StatusCode, Json<Alert>), ApiError> {
+    let request = request.validate().map_err(ApiError::InvalidRequest)?;
+    let alert = alerts_db(&state)?.create_alert(&request, source_ip(&source).as_deref()).await.map_err(alert_db_error)?;
+    tracing::info!("Created alert {} on {} ({} {})", alert.id, alert.symbol, alert.condition_type.as_str(), alert.condition_value);
+    Ok((StatusCode::CREATED, Json(alert)))
+}
+
+async fn delete_alert(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    axum::extract::Path(id): axum::extract::Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    if alerts_db(&state)?.delete_alert(id, source_ip(&source).as_deref()).await.map_err(alert_db_error)? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::NotFound(format!("alert {} not found", id)))
+    }
+}
+
+async fn restore_alert(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    axum::extract::Path(id): axum::extract::Path<i64>,
+) -> Result<Json<Alert>, ApiError> {
+    alerts_db(&state)?
+        .restore_alert(id, source_ip(&source).as_deref())
+        .await
+        .map_err(alert_db_error)?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("alert {} has not been deleted", id)))
+}
+
+fn note_db_error(e: anyhow::Error) -> ApiError {
+    tracing::error!("Note query failed: {}", e);
+    ApiError::Database("Note query failed".to_string())
+}
+
+#[derive(Deserialize)]
+struct NotesQuery {
+    symbol: Option<String>,
+    #[serde(default)]
+    include_expired: bool,
+}
+
+async fn list_notes(State(state): State<AppState>, Query(params): Query<NotesQuery>) -> Result<Json<Vec<TickerNote>>, ApiError> {
+    let symbol = params.symbol.as_deref().map(normalize_symbol);
+    let db = state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)?;
+    let notes = db.get_notes(symbol.as_deref(), params.include_expired).await.map_err(note_db_error)?;
+    Ok(Json(notes))
+}
+
+async fn create_note(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    Json(request): Json<NewNote>,
+) -> Result<(StatusCode, Json<TickerNote>), ApiError> {
+    let request = request.validate().map_err(ApiError::InvalidRequest)?;
+    let db = state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)?;
+    let note = db.create_note(&request, source_ip(&source).as_deref()).await.map_err(note_db_error)?;
+    // Served results carry the note, so their ETags have to move on
+    state.bump_results_version();
+    tracing::info!("Created note {} on {}", note.id, note.symbol);
+    Ok((StatusCode::CREATED, Json(note)))
+}
+
+async fn update_note(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    axum::extract::Path(id): axum::extract::Path<i64>,
+    Json(request): Json<NoteUpdate>,
+) -> Result<Json<TickerNote>, ApiError> {
+    let request = request.validate().map_err(ApiError::InvalidRequest)?;
+    let db = state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)?;
+    let note = db
+        .update_note(id, &request, source_ip(&source).as_deref())
+        .await
+        .map_err(note_db_error)?
+        .ok_or_else(|| ApiError::NotFound(format!("note {} not found", id)))?;
+    state.bump_results_version();
+    Ok(Json(note))
+}
+
+async fn delete_note(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    axum::extract::Path(id): axum::extract::Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let db = state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)?;
+    if db.delete_note(id, source_ip(&source).as_deref()).await.map_err(note_db_error)? {
+        state.bump_results_version();
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::NotFound(format!("note {} not found", id)))
+    }
+}
+
+fn custom_indicator_db_error(e: anyhow::Error) -> ApiError {
+    tracing::error!("Custom indicator query failed: {}", e);
+    ApiError::Database("Custom indicator query failed".to_string())
+}
+
+async fn list_custom_indicators(State(state): State<AppState>) -> Result<Json<Vec<CustomIndicator>>, ApiError> {
+    let db = state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)?;
+    let indicators = db.get_custom_indicators().await.map_err(custom_indicator_db_error)?;
+    Ok(Json(indicators))
+}
+
+/// Create a custom indicator, or replace the expression of the one with
+/// the same name. Results pick it up as their symbols are next analyzed.
+async fn save_custom_indicator(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    Json(request): Json<NewCustomIndicator>,
+) -> Result<(StatusCode, Json<CustomIndicator>), ApiError> {
+    let request = request.validate().map_err(ApiError::InvalidRequest)?;
+    let db = state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)?;
+    let indicator = db.save_custom_indicator(&request, source_ip(&source).as_deref()).await.map_err(custom_indicator_db_error)?;
+    state.load_custom_indicators().await;
+    tracing::info!("Saved custom indicator '{}' = {}", indicator.name, indicator.expression);
+    Ok((StatusCode::CREATED, Json(indicator)))
+}
+
+async fn delete_custom_indicator(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let db = state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)?;
+    let name = name.trim().to_ascii_lowercase();
+    if db.delete_custom_indicator(&name, source_ip(&source).as_deref()).await.map_err(custom_indicator_db_error)? {
+        state.load_custom_indicators().await;
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::NotFound(format!("custom indicator '{}' not found", name)))
+    }
+}
+
+/// Body of `POST /api/presets`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PresetRequest {
+    pub name: String,
+    pub filter: StockFilter,
+    /// Opportunity rules sessions started from the preset use
+    #[serde(default)]
+    pub opportunity_rules: Option<Vec<OpportunityRule>>,
+}
+
+fn presets_db(state: &AppState) -> Result<&Database, ApiError> {
+    state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)
+}
+
+fn preset_db_error(e: anyhow::Error) -> ApiError {
+    tracing::error!("Preset query failed: {}", e);
+    ApiError::Database("Preset query failed".to_string())
+}
+
+async fn list_presets(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<DeletedQuery>,
+) -> Result<Json<Vec<FilterPreset>>, ApiError> {
+    let include_deleted = params.include_deleted(&state, &headers)?;
+    let presets = presets_db(&state)?.list_presets(include_deleted).await.map_err(preset_db_error)?;
+    Ok(Json(presets))
+}
+
+/// Create or replace a preset
+async fn save_preset(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    Json(request): Json<PresetRequest>,
+) -> Result<(StatusCode, Json<FilterPreset>), ApiError> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::InvalidRequest("preset name must not be empty".to_string()));
+    }
+    request.filter.validate().map_err(ApiError::InvalidFilter)?;
+    if let Some(rules) = &request.opportunity_rules {
+        validate_rules(rules).map_err(ApiError::InvalidRequest)?;
+    }
+    let preset = presets_db(&state)?
+        .save_preset(name, &request.filter, request.opportunity_rules.as_deref(), source_ip(&source).as_deref())
+        .await
+        .map_err(preset_db_error)?;
+    tracing::info!("Saved filter preset '{}'", preset.name);
+    Ok((StatusCode::CREATED, Json(preset)))
+}
+
+async fn delete_preset(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> Result<StatusCode, ApiError> {
+    if presets_db(&state)?.delete_preset(&name, source_ip(&source).as_deref()).await.map_err(preset_db_error)? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::NotFound(format!("preset '{}' not found", name)))
+    }
+}
+
+async fn restore_preset(
+    State(state): State<AppState>,
+    source: SourceAddr,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> Result<Json<FilterPreset>, ApiError> {
+    presets_db(&state)?
+        .restore_preset(&name, source_ip(&source).as_deref())
+        .await
+        .map_err(preset_db_error)?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("preset '{}' has not been deleted", name)))
+}
+
+#[derive(Deserialize)]
+struct AuditQuery {
+    resource: Option<String>,
+    /// Watchlist symbol, alert id or preset name; only with `resource`
+    id: Option<String>,
+    limit: Option<u32>,
+}
+
+/// Changes made through the API, newest first
+async fn get_audit_log(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, ApiError> {
+    require_admin(&state, &headers)?;
+    let resource = params
+        .resource
+        .as_deref()
+        .map(str::parse::<AuditResource>)
+        .transpose()
+        .map_err(ApiError::InvalidRequest)?;
+    if params.id.is_some() && resource.is_none() {
+        return Err(ApiError::InvalidRequest("id requires a resource".to_string()));
+    }
+    let limit = params.limit.unwrap_or(DEFAULT_AUDIT_LIMIT).clamp(1, MAX_AUDIT_LIMIT);
+    let db = state.database.as_deref().ok_or(ApiError::DatabaseUnavailable)?;
+    let entries = db.get_audit_log(resource, params.id.as_deref(), limit).await.map_err(|e| {
+        tracing::error!("Audit log query failed: {}", e);
+        ApiError::Database("Audit log query failed".to_string())
+    })?;
+    Ok(Json(entries))
+}
+
+/// Body of `POST /api/backtest`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestRequest {
+    pub symbol: String,
+    /// Entry conditions; only the per-bar fields apply (see `EntryRule::from_filter`)
+    pub filter: StockFilter,
+    pub exit: ExitRule,
+    /// First day to trade on; earlier history still warms up the indicators
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+    /// Last day to trade on, inclusive; a position still open is closed here
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+}
+
+async fn run_backtest(
+    State(state): State<AppState>,
+    Json(request): Json<BacktestRequest>,
+) -> Result<Json<BacktestReport>, ApiError> {
+    let symbol = normalize_symbol(&request.symbol);
+    if symbol.is_empty() {
+        return Err(ApiError::InvalidRequest("symbol must not be empty".to_string()));
+    }
+    request.filter.validate().map_err(ApiError::InvalidFilter)?;
+    let backtester = Backtester::new(EntryRule::from_filter(&request.filter), request.exit)
+        .with_date_range(request.start_date, request.end_date);
+    backtester.validate().map_err(ApiError::InvalidRequest)?;
+
+    let series = state
+        .analyzer()
+        .fetch_quote_series_cached(&symbol)
+        .await
+        .map_err(|e| history_fetch_error(&symbol, e))?;
+    if series.bars.is_empty() {
+        return Err(ApiError::NotFound(format!("no price history for {}", symbol)));
+    }
+    Ok(Json(backtester.run(&series.bars)))
+}
+
+/// A symbol the provider doesn't know (or no longer lists) is a 404; any
+/// other failure is the upstream's
+fn history_fetch_error(symbol: &str, e: AnalyzerError) -> ApiError {
+    match e {
+        AnalyzerError::SymbolNotFound(_) => ApiError::NotFound(format!("no price history for {}", symbol)),
+        e => ApiError::from_fetch(&format!("Failed to fetch history for {}", symbol), e),
+    }
+}
+
+/// Longest window `GET /api/symbol/:ticker` returns, in calendar days
+pub const MAX_SYMBOL_HISTORY_DAYS: u32 = 5 * 365;
+
+#[derive(Deserialize)]
+struct SymbolQuery {
+    /// Calendar days of history to return, up to `MAX_SYMBOL_HISTORY_DAYS`
+    days: Option<u32>,
+    /// Bar size: "15m", "1h", "1d" (default), "1wk" or "1mo"
+    interval: Option<Interval>,
+    /// "weekly" or "monthly" resamples the daily bars; "daily" by default
+    timeframe: Option<Timeframe>,
+    /// Whether the week or month still trading is returned as the last bar
+    /// (true when absent)
+    include_partial: Option<bool>,
+}
+
+/// One OHLCV bar of `SymbolAnalysis`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolBar {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: u64,
+}
+
+/// Indicator values for the bar with the same timestamp
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorPoint {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub rsi: Option<f64>,
+    pub sma_20: Option<f64>,
+    pub sma_50: Option<f64>,
+    pub macd: Option<f64>,
+    pub macd_signal: Option<f64>,
+    pub macd_histogram: Option<f64>,
+}
+
+/// Response of `GET /api/symbol/:ticker`: chart data plus the latest signals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolAnalysis {
+    pub symbol: String,
+    pub interval: Interval,
+    /// Bar size of `bars` and `indicators`, resampled from daily bars
+    #[serde(default)]
+    pub timeframe: Timeframe,
+    /// Listing details, when the ticker list is cached
+    pub info: Option<TickerInfo>,
+    pub data_source: String,
+    pub data_fetched_at: chrono::DateTime<chrono::Utc>,
+    pub bars: Vec<SymbolBar>,
+    pub indicators: Vec<IndicatorPoint>,
+    pub signals: Vec<String>,
+    /// Every crossover within the requested window, oldest first
+    #[serde(default)]
+    pub crossovers: Vec<CrossoverEvent>,
+    /// "historically weak in September", for daily bars with enough history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seasonality_hint: Option<String>,
+    /// Active note on the symbol from `/api/notes`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+async fn get_symbol_analysis(
+    State(state): State<AppState>,
+    axum::extract::Path(ticker): axum::extract::Path<String>,
+    Query(params): Query<SymbolQuery>,
+) -> Result<Json<SymbolAnalysis>, ApiError> {
+    let symbol = normalize_symbol(&ticker);
+    if symbol.is_empty() {
+        return Err(ApiError::InvalidRequest("symbol must not be empty".to_string()));
+    }
+    let interval = params.interval.unwrap_or_default();
+    // Intraday bars are only served for a limited window
+    let max_days = interval.max_lookback_days().map_or(MAX_SYMBOL_HISTORY_DAYS, |days| days.min(MAX_SYMBOL_HISTORY_DAYS));
+    if interval.is_intraday() && params.days.is_some_and(|days| days > max_days) {
+        return Err(ApiError::InvalidRequest(format!(
+            "{} bars only go back {} days; ask for days={} or fewer",
+            interval, max_days, max_days
+        )));
+    }
+    let days = params.days.unwrap_or(max_days).min(max_days);
+    if days == 0 {
+        return Err(ApiError::InvalidRequest("days must be at least 1".to_string()));
+    }
+    let timeframe = params.timeframe.unwrap_or_default();
+    if timeframe != Timeframe::Daily && interval != Interval::Daily {
+        return Err(ApiError::InvalidRequest(format!(
+            "the {} timeframe is resampled from daily bars, not {}",
+            timeframe, interval
+        )));
+    }
+
+    // The whole available history is fetched so the indicators are warmed up
+    // at the start of the window; the daily series shares the continuous loop's cache
+    let range = HistoryRange::new(None, None, interval);
+    let mut analyzer = state.analyzer();
+    let series = analyzer
+        .fetch_quote_series_range_cached(&symbol, &range)
+        .await
+        .map_err(|e| history_fetch_error(&symbol, e))?;
+    if series.bars.is_empty() {
+        return Err(ApiError::NotFound(format!("no price history for {}", symbol)));
+    }
+    let (all_bars, indicators): (Cow<[StockData]>, _) = match timeframe {
+        Timeframe::Daily if range == HistoryRange::default() => {
+            (Cow::Borrowed(&series.bars), analyzer.calculate_indicators_cached(&symbol, &series.bars).await)
+        }
+        Timeframe::Daily => (Cow::Borrowed(&series.bars), compute_indicators(&series.bars)),
+        _ => {
+            let today = (!params.include_partial.unwrap_or(true)).then(|| state.clock.now().date_naive());
+            let (bars, indicators) = analyzer.calculate_timeframe_indicators(&symbol, &series.bars, timeframe, today);
+            (Cow::Owned(bars), indicators)
+        }
+    };
+    let signals = analyzer.analyze_signals(&all_bars, &indicators);
+    let seasonality_hint = if interval == Interval::Daily {
+        symbol_seasonality(&state, &symbol, &series.bars).await.hint()
+    } else {
+        None
+    };
+
+    let window_start = state.clock.now() - chrono::Duration::days(i64::from(days));
+    let crossovers: Vec<CrossoverEvent> = detect_crossovers(&all_bars, &indicators)
+        .into_iter()
+        .filter(|event| event.timestamp >= window_start)
+        .collect();
+    let first = all_bars.partition_point(|bar| bar.timestamp < window_start);
+    let bars = &all_bars[first..];
+    let indicators = &indicators[first.min(indicators.len())..];
+
+    let info = state
+        .cache
+        .get_tickers("all_tickers")
+        .await
+        .and_then(|tickers| tickers.into_iter().find(|t| normalize_symbol(&t.symbol) == symbol));
+    let note = match state.database {
+        Some(ref db) => db.get_active_note(&symbol).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to load the note on {}: {}", symbol, e);
+            None
+        }),
+        None => None,
+    };
+
+    Ok(Json(SymbolAnalysis {
+        symbol,
+        interval,
+        timeframe,
+        info,
+        data_source: series.source.clone(),
+        data_fetched_at: series.fetched_at,
+        bars: bars
+            .iter()
+            .map(|bar| SymbolBar {
+                timestamp: bar.timestamp,
+                open: bar.open,
+                high: bar.high,
+                low: bar.low,
+                close: bar.close,
+                volume: bar.volume,
+            })
+            .collect(),
+        indicators: bars
+            .iter()
+            .zip(indicators)
+            .map(|(bar, ind)| IndicatorPoint {
+                timestamp: bar.timestamp,
+                rsi: ind.rsi,
+                sma_20: ind.sma_20,
+                sma_50: ind.sma_50,
+                macd: ind.macd.map(|m| m.0),
+                macd_signal: ind.macd.map(|m| m.1),
+                macd_histogram: ind.macd.map(|m| m.2),
+            })
+            .collect(),
+        signals,
+        crossovers,
+        seasonality_hint,
+        note,
+    }))
+}
+
+/// Seasonality of `symbol` from its full daily history, cached per symbol
+async fn symbol_seasonality(state: &AppState, symbol: &str, bars: &[StockData]) -> Seasonality {
+    if let Some(cached) = state.cache.get_seasonality(symbol).await {
+        return cached;
+    }
+    let computed = seasonality(symbol, bars);
+    state.cache.cache_seasonality(symbol.to_string(), computed.clone()).await;
+    computed
+}
+
+/// Average and median returns by month of year and day of week, with the
+/// best and worst months on record
+async fn get_seasonality(
+    State(state): State<AppState>,
+    axum::extract::Path(ticker): axum::extract::Path<String>,
+) -> Result<Json<Seasonality>, ApiError> {
+    let symbol = normalize_symbol(&ticker);
+    if symbol.is_empty() {
+        return Err(ApiError::InvalidRequest("symbol must not be empty".to_string()));
+    }
+    if let Some(cached) = state.cache.get_seasonality(&symbol).await {
+        return Ok(Json(cached));
+    }
+    let series = state
+        .analyzer()
+        .fetch_quote_series_cached(&symbol)
+        .await
+        .map_err(|e| history_fetch_error(&symbol, e))?;
+    if series.bars.is_empty() {
+        return Err(ApiError::NotFound(format!("no price history for {}", symbol)));
+    }
+    Ok(Json(symbol_seasonality(&state, &symbol, &series.bars).await))
+}
+
+/// Store the daily indicator values `database` doesn't have yet for
+/// `symbol`. The newest stored day is rewritten since its bar may have been
+/// partial. Failures are logged; they never fail the analysis.
+async fn store_indicator_history(database: &Database, symbol: &str, bars: &[StockData], indicators: &[TechnicalIndicators]) {
+    let symbol = normalize_symbol(symbol);
+    let latest = match database.latest_indicator_date(&symbol).await {
+        Ok(latest) => latest,
+        Err(e) => {
+            tracing::warn!("Failed to read indicator history for {}: {}", symbol, e);
+            return;
+        }
+    };
+    let series: Vec<(&StockData, &TechnicalIndicators)> = bars
+        .iter()
+        .zip(indicators)
+        .filter(|(bar, _)| latest.is_none_or(|latest| bar.timestamp.date_naive() >= latest))
+        .collect();
+    if series.is_empty() {
+        return;
+    }
+    if let Err(e) = database.store_indicator_series(&symbol, &series).await {
+        tracing::warn!("Failed to store indicator history for {}: {}", symbol, e);
+    }
+}
+
+/// Days of indicator history `GET /api/symbol/:ticker/indicators` returns by default
+pub const DEFAULT_INDICATOR_HISTORY_DAYS: u32 = 90;
+
+#[derive(Deserialize)]
+struct IndicatorHistoryQuery {
+    /// Calendar days of history to return, up to `MAX_SYMBOL_HISTORY_DAYS`
+    days: Option<u32>,
+}
+
+/// Response of `GET /api/symbol/:ticker/indicators`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorHistory {
+    pub symbol: String,
+    /// "database" when served from stored rows, "computed" when the rows
+    /// were missing and the price history was fetched instead
+    pub source: String,
+    pub indicators: Vec<IndicatorHistoryPoint>,
+}
+
+/// Daily RSI, SMA and MACD values over the last `days` days, read from the
+/// indicator history table. With no stored rows in the window (or no
+/// database) they are computed from the price history, and stored for next time.
+async fn get_indicator_history(
+    State(state): State<AppState>,
+    axum::extract::Path(ticker): axum::extract::Path<String>,
+    Query(params): Query<IndicatorHistoryQuery>,
+) -> Result<Json<IndicatorHistory>, ApiError> {
+    let symbol = normalize_symbol(&ticker);
+    if symbol.is_empty() {
+        return Err(ApiError::InvalidRequest("symbol must not be empty".to_string()));
+    }
+    let days = params.days.unwrap_or(DEFAULT_INDICATOR_HISTORY_DAYS).min(MAX_SYMBOL_HISTORY_DAYS);
+    if days == 0 {
+        return Err(ApiError::InvalidRequest("days must be at least 1".to_string()));
+    }
+    let since = (state.clock.now() - chrono::Duration::days(i64::from(days))).date_naive();
+
+    if let Some(ref db) = state.database {
+        let stored = db
+            .get_indicator_history(&symbol, since)
+            .await
+            .map_err(|e| ApiError::Database(format!("Failed to load indicator history: {}", e)))?;
+        if !stored.is_empty() {
+            return Ok(Json(IndicatorHistory {
+                symbol,
+                source: "database".to_string(),
+                indicators: stored,
+            }));
+        }
+    }
+
+    let mut analyzer = state.analyzer();
+    let series = analyzer
+        .fetch_quote_series_cached(&symbol)
+        .await
+        .map_err(|e| history_fetch_error(&symbol, e))?;
+    if series.bars.is_empty() {
+        return Err(ApiError::NotFound(format!("no price history for {}", symbol)));
+    }
+    let indicators = analyzer.calculate_indicators_cached(&symbol, &series.bars).await;
+    if let Some(ref db) = state.database {
+        store_indicator_history(db, &symbol, &series.bars, &indicators).await;
+    }
+
+    Ok(Json(IndicatorHistory {
+        symbol,
+        source: "computed".to_string(),
+        indicators: series
+            .bars
+            .iter()
+            .zip(&indicators)
+            .filter(|(bar, _)| bar.timestamp.date_naive() >= since)
+            .map(|(bar, indicators)| IndicatorHistoryPoint::new(bar, indicators))
+            .collect(),
+    }))
+}
+
+/// Evaluate a fresh result against the active alerts on its symbol,
+/// recording and broadcasting every alert that fires
+pub async fn check_alerts(state: &AppState, result: &StockAnalysisResult) -> Vec<AlertTrigger> {
+    let Some(ref db) = state.database else {
+        return Vec::new();
+    };
+    let mut alerts = match db.get_active_alerts(&normalize_symbol(&result.ticker)).await {
+        Ok(alerts) => alerts,
+        Err(e) => {
+            tracing::warn!("Failed to load alerts for {}: {}", result.ticker, e);
+            return Vec::new();
+        }
+    };
+
+    let mut fired = Vec::new();
+    for update in evaluate_alerts(&mut alerts, result, state.clock.now()) {
+        match update {
+            AlertUpdate::Fired(trigger) => {
+                if let Err(e) = db.record_alert_trigger(&trigger).await {
+                    tracing::warn!("Failed to record alert {}: {}", trigger.alert_id, e);
+                    continue;
+                }
+                tracing::info!(
+                    "🔔 Alert {} fired: {} {} {} (observed {})",
+                    trigger.alert_id,
+                    trigger.symbol,
+                    trigger.condition_type.as_str(),
+                    trigger.condition_value,
+                    trigger.observed_value
+                );
+                state.publish(BroadcastEvent::AlertTriggered(trigger.clone()));
+                state.notify(Notification::alert(&trigger));
+                fired.push(trigger);
+            }
+            AlertUpdate::Rearmed(id) => {
+                if let Err(e) = db.rearm_alert(id).await {
+                    tracing::warn!("Failed to re-arm alert {}: {}", id, e);
+                }
+            }
+        }
+    }
+    fired
+}
+
+/// Tickers passing the continuous loop's filter, if one is configured;
+/// symbols in `keep` (the watchlist and new listings) are kept either way
+fn continuous_universe(tickers: Vec<TickerInfo>, filter: Option<&StockFilter>, keep: &[String]) -> Vec<TickerInfo> {
+    let Some(filter) = filter else { return tickers };
+    let passing: BTreeSet<String> = StockAnalyzer::filter_tickers(&tickers, filter)
+        .into_iter()
+        .map(|ticker| ticker.symbol)
+        .collect();
+    let total = tickers.len();
+    let kept: Vec<TickerInfo> = tickers
+        .into_iter()
+        .filter(|ticker| passing.contains(&ticker.symbol) || keep.contains(&normalize_symbol(&ticker.symbol)))
+        .collect();
+    tracing::info!("Continuous filter kept {} of {} tickers", kept.len(), total);
+    kept
+}
+
+/// Move watchlist symbols to the front, in watchlist order, keeping the
+/// order of everything else
+pub fn prioritize_watchlist(tickers: Vec<TickerInfo>, watchlist: &[String]) -> Vec<TickerInfo> {
+    if watchlist.is_empty() {
+        return tickers;
+    }
+
+    let rank: HashMap<&str, usize> = watchlist.iter().enumerate().map(|(i, s)| (s.as_str(), i)).collect();
+    let (mut watched, rest): (Vec<_>, Vec<_>) = tickers
+        .into_iter()
+        .partition(|ticker| rank.contains_key(normalize_symbol(&ticker.symbol).as_str()));
+    watched.sort_by_key(|ticker| rank[normalize_symbol(&ticker.symbol).as_str()]);
+    watched.extend(rest);
+    watched
+}
+
+/// Overall status from the dependency probes and the continuous loop:
+/// 200 while healthy or degraded, 503 once down
+/// Body of `GET /api/health`, served with 503 when `status` is "down"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// "healthy", "degraded" or "down"
+    pub status: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub version: String,
+    pub read_only: bool,
+    pub websocket_clients: usize,
+    pub dependencies: DependencyProbes,
+    pub continuous_analysis: ContinuousHealth,
+}
+
+/// How the continuous loop looks to `GET /api/health`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContinuousHealth {
+    pub state: ContinuousState,
+    /// Running, but not updated for much longer than a cycle takes
+    pub stalled: bool,
+    pub last_update: chrono::DateTime<chrono::Utc>,
+}
+
+async fn health_check(State(state): State<AppState>) -> (StatusCode, Json<HealthReport>) {
+    let probes = probe_dependencies(&state).await;
+    let continuous = state.continuous_analysis_status.read().await.clone();
+    let stalled = matches!(continuous.state, ContinuousState::Running | ContinuousState::IdleWaiting)
+        && is_stalled(continuous.last_update, continuous.next_run_at, state.config.cycle_interval(), state.clock.now());
+
+    let mut overall = probes.overall();
+    if stalled {
+        overall = overall.max(HealthStatus::Degraded);
+    }
+    let (code, status) = match overall {
+        HealthStatus::Ok => (StatusCode::OK, "healthy"),
+        HealthStatus::Degraded => (StatusCode::OK, "degraded"),
+        HealthStatus::Down => (StatusCode::SERVICE_UNAVAILABLE, "down"),
+    };
+    let body = HealthReport {
+        status: status.to_string(),
+        timestamp: chrono::Utc::now(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        read_only: state.config.read_only,
+        websocket_clients: state.websocket_clients.load(std::sync::atomic::Ordering::SeqCst),
+        dependencies: probes,
+        continuous_analysis: ContinuousHealth {
+            state: continuous.state,
+            stalled,
+            last_update: continuous.last_update,
+        },
+    };
+    (code, Json(body))
+}
+
+/// Probe the ticker listing, a quote for the benchmark symbol and the
+/// database, unless they were probed within `HEALTH_PROBE_INTERVAL`
+pub async fn probe_dependencies(state: &AppState) -> DependencyProbes {
+    let mut probes = state.dependency_probes.lock().await;
+    if !probes.is_stale(state.clock.now()) {
+        return probes.clone();
+    }
+
+    let analyzer = state.analyzer();
+    let canary = state.config.benchmark_symbol.as_deref().unwrap_or(DEFAULT_BENCHMARK_SYMBOL);
+    let (listing, quotes, database) = tokio::join!(
+        with_probe_timeout(state, analyzer.probe_ticker_listing()),
+        with_probe_timeout(state, async { analyzer.get_latest_quote(canary).await.map(|_| ()) }),
+        async {
+            match state.database {
+                Some(ref db) => Some(with_probe_timeout(state, async { db.ping().await.map_err(AnalyzerError::database) }).await),
+                None => None,
+            }
+        },
+    );
+
+    let now = state.clock.now();
+    probes.listing.record(listing, now);
+    probes.quotes.record(quotes, now);
+    match database {
+        Some(outcome) => probes.database.get_or_insert_with(Default::default).record(outcome, now),
+        None => probes.database = None,
+    }
+    for (name, dependency) in [("listing", Some(&probes.listing)), ("quotes", Some(&probes.quotes)), ("database", probes.database.as_ref())] {
+        if let Some(error) = dependency.and_then(|dependency| dependency.last_error.as_ref()) {
+            tracing::warn!("Health probe of {} failed: {}", name, error);
+        }
+    }
+    probes.clone()
+}
+
+/// Fail `probe` once it has taken `PROBE_TIMEOUT`
+async fn with_probe_timeout(state: &AppState, probe: impl std::future::Future<Output = Result<(), AnalyzerError>>) -> Result<(), AnalyzerError> {
+    tokio::select! {
+        result = probe => result,
+        _ = state.clock.sleep(PROBE_TIMEOUT) => Err(AnalyzerError::Upstream(format!("no answer within {:?}", PROBE_TIMEOUT))),
+    }
+}
+
+/// Public server settings, so the dashboard can hide actions it cannot perform
+async fn get_config(State(state): State<AppState>) -> Json<AppConfig> {
+    Json(state.config.as_ref().clone())
+}
+
+/// Whether a request changes server state and must be refused in read-only mode
+fn is_mutating(method: &Method, path: &str) -> bool {
+    match *method {
+        Method::POST => !READ_ONLY_SAFE_POSTS.contains(&path),
+        Method::PUT | Method::PATCH | Method::DELETE => true,
+        _ => false,
+    }
+}
+
+/// Tag every response with the latest broadcast sequence number from before
+/// the request was handled, so a client resuming the socket from it may see
+/// a message twice but never misses one
+async fn broadcast_seq_header(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let seq = state.message_log.latest_seq();
+    let mut response = next.run(request).await;
+    response.headers_mut().insert(BROADCAST_SEQ_HEADER, seq.into());
+    response
+}
+
+/// Reject mutating requests while read-only mode is on, unless the request
+/// carries an allow-listed admin key
+async fn read_only_guard(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if !state.config.read_only || !is_mutating(request.method(), request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let is_admin = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|key| state.config.is_admin_key(key));
+    if is_admin {
+        return next.run(request).await;
+    }
+
+    tracing::info!("Rejected {} {} in read-only mode", request.method(), request.uri().path());
+    (
+        StatusCode::FORBIDDEN,
+        Json(serde_json::json!({
+            "error": "read_only_mode",
+            "message": "This server is running in read-only mode"
+        })),
+    )
+        .into_response()
+}
+
+#[derive(Deserialize)]
+struct TickerQuery {
+    limit: Option<usize>,
+}
+
+/// Tagged with the listing's fetch time and symbols, so a client holding
+/// the current listing gets 304 Not Modified
+async fn get_tickers(
+    State(state): State<AppState>,
+    Query(params): Query<TickerQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let _limit = params.limit.unwrap_or(0); // 0 means fetch all - but we'll fetch all anyway
+    
+    let analyzer = state.analyzer();
+    match state.fetch_tickers(&analyzer).await {
+        Ok((tickers, fetched_at)) => {
+            let etag = entity_tag(|hasher| {
+                fetched_at.hash(hasher);
+                for ticker in &tickers {
+                    ticker.symbol.hash(hasher);
+                }
+            });
+            Ok(conditional_json(&headers, etag, &tickers))
+        }
+        Err(e) => Err(ticker_fetch_error(e)),
+    }
+}
+
+#[derive(Deserialize)]
+struct NewTickersQuery {
+    since: chrono::DateTime<chrono::Utc>,
+}
+
+/// Symbols the ticker fetch first returned after `since`, oldest first
+async fn get_new_tickers(State(state): State<AppState>, Query(params): Query<NewTickersQuery>) -> Json<Vec<ListedTicker>> {
+    let directory = state.ticker_directory.read().await;
+    Json(directory.listed_since(params.since).into_iter().cloned().collect())
+}
+
+/// Symbols missing from more than `delist_after_missing_fetches` ticker
+/// fetches in a row
+async fn get_removed_tickers(State(state): State<AppState>) -> Json<Vec<ListedTicker>> {
+    let directory = state.ticker_directory.read().await;
+    Json(directory.removed().into_iter().cloned().collect())
+}
+
+/// Tickers `GET /api/screener` returns when the client doesn't set a limit
+pub const DEFAULT_SCREENER_LIMIT: usize = 50;
+/// Most tickers `GET /api/screener` returns
+pub const MAX_SCREENER_LIMIT: usize = 1000;
+
+/// Query for `GET /api/screener`: the basic `StockFilter` fields, with
+/// `sectors`, `countries`, `industries` and `exchanges` as comma-separated
+/// lists, plus the ranking
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ScreenerQuery {
+    /// "score" (default), "pct_change", "volume", "market_cap" or "last_sale"
+    pub sort_by: Option<String>,
+    /// "desc" (default) or "asc"
+    pub order: Option<String>,
+    pub limit: Option<usize>,
+    pub min_market_cap: Option<f64>,
+    pub max_market_cap: Option<f64>,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    pub min_volume: Option<u64>,
+    pub max_volume: Option<u64>,
+    pub min_pct_change: Option<f64>,
+    pub max_pct_change: Option<f64>,
+    pub sectors: Option<String>,
+    pub countries: Option<String>,
+    pub industries: Option<String>,
+    pub exchanges: Option<String>,
+    pub min_ipo_year: Option<i32>,
+    pub max_ipo_year: Option<i32>,
+    pub min_beta: Option<f64>,
+    pub max_beta: Option<f64>,
+    pub max_pct_above_52_week_low: Option<f64>,
+    pub require_complete_data: bool,
+    pub exclude_adrs: bool,
+    /// `RankingConfig` overrides, used when sorting by score
+    pub pct_change_weight: Option<f64>,
+    pub dollar_volume_weight: Option<f64>,
+    pub market_cap_weight: Option<f64>,
+    pub min_dollar_volume: Option<f64>,
+}
+
+impl ScreenerQuery {
+    pub fn ranking(&self) -> RankingConfig {
+        let defaults = RankingConfig::default();
+        RankingConfig::default()
+            .with_weights(
+                self.pct_change_weight.unwrap_or(defaults.pct_change_weight),
+                self.dollar_volume_weight.unwrap_or(defaults.dollar_volume_weight),
+                self.market_cap_weight.unwrap_or(defaults.market_cap_weight),
+            )
+            .with_min_dollar_volume(self.min_dollar_volume)
+    }
+
+    pub fn filter(&self) -> StockFilter {
+        let list = |values: &Option<String>| {
+            values.as_ref().map(|values| {
+                values
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+        };
+        StockFilter {
+            min_market_cap: self.min_market_cap,
+            max_market_cap: self.max_market_cap,
+            min_price: self.min_price,
+            max_price: self.max_price,
+            min_volume: self.min_volume,
+            max_volume: self.max_volume,
+            min_pct_change: self.min_pct_change,
+            max_pct_change: self.max_pct_change,
+            sectors: list(&self.sectors),
+            countries: list(&self.countries),
+            industries: list(&self.industries),
+            exchanges: list(&self.exchanges),
+            min_ipo_year: self.min_ipo_year,
+            max_ipo_year: self.max_ipo_year,
+            min_beta: self.min_beta,
+            max_beta: self.max_beta,
+            max_pct_above_52_week_low: self.max_pct_above_52_week_low,
+            require_complete_data: self.require_complete_data,
+            exclude_adrs: self.exclude_adrs,
+            ..StockFilter::default()
+        }
+    }
+}
+
+/// Ranked tickers from `GET /api/screener`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScreenerResponse {
+    /// When the ticker list was fetched from Nasdaq
+    pub tickers_cached_at: chrono::DateTime<chrono::Utc>,
+    /// Tickers passing the filter (and, by score, the dollar-volume
+    /// cutoff), before the limit
+    pub total_matches: usize,
+    pub sort_by: String,
+    pub order: String,
+    pub tickers: Vec<TickerInfo>,
+}
+
+/// `GET /api/screener` sort that ranks by `RankingConfig::score`
+const SCORE_SORT: &str = "score";
+
+/// Filter and rank the cached Nasdaq ticker list without fetching any price
+/// history. By score, tickers without one are left out; by a field, tickers
+/// missing it go last.
+async fn get_screener(
+    State(state): State<AppState>,
+    Query(params): Query<ScreenerQuery>,
+) -> Result<Json<ScreenerResponse>, ApiError> {
+    let sort_field: Option<TickerSortField> = match params.sort_by.as_deref() {
+        None | Some(SCORE_SORT) => None,
+        Some(field) => Some(field.parse().map_err(|e| ApiError::InvalidRequest(format!("{}, or {}", e, SCORE_SORT)))?),
+    };
+    let ranking = params.ranking();
+    ranking.validate().map_err(ApiError::InvalidRequest)?;
+    let order: SortOrder = params
+        .order
+        .as_deref()
+        .map_or(Ok(SortOrder::default()), str::parse)
+        .map_err(ApiError::InvalidRequest)?;
+    let limit = params.limit.unwrap_or(DEFAULT_SCREENER_LIMIT).min(MAX_SCREENER_LIMIT);
+    let filter = params.filter();
+    filter.validate().map_err(ApiError::InvalidFilter)?;
+
+    let (all_tickers, tickers_cached_at) = state
+        .fetch_tickers(&state.analyzer())
+        .await
+        .map_err(ticker_fetch_error)?;
+    let matches = StockAnalyzer::filter_tickers(&all_tickers, &filter);
+    let (total_matches, tickers) = match sort_field {
+        Some(field) => (matches.len(), StockAnalyzer::sort_tickers(&matches, field, order == SortOrder::Desc, limit)),
+        None => {
+            let mut ranked = StockAnalyzer::rank_tickers(&matches, &ranking);
+            if order == SortOrder::Asc {
+                ranked.reverse();
+            }
+            let total = ranked.len();
+            ranked.truncate(limit);
+            (total, ranked)
+        }
+    };
+
+    Ok(Json(ScreenerResponse {
+        tickers_cached_at,
+        total_matches,
+        sort_by: sort_field.map_or(SCORE_SORT, TickerSortField::as_str).to_string(),
+        order: order.as_str().to_string(),
+        tickers,
     }))
 }
 
-#[derive(Deserialize)]
-struct TickerQuery {
-    limit: Option<usize>,
-}
-
-async fn get_tickers(State(state): State<AppState>, Query(params): Query<TickerQuery>) -> Result<Json<Vec<TickerInfo>>, StatusCode> {
-    let _limit = params.limit.unwrap_or(0); // 0 means fetch all - but we'll fetch all anyway
-    
-    let analyzer = StockAnalyzer::new_with_cache(state.cache.clone());
-    match analyzer.fetch_all_tickers_cached().await {
-        Ok(tickers) => Ok(Json(tickers)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+fn ticker_fetch_error(e: AnalyzerError) -> ApiError {
+    tracing::error!("Failed to fetch ticker list: {}", e);
+    ApiError::from_fetch("Failed to fetch ticker list", e)
 }
 
 async fn get_filter_stats(
     State(state): State<AppState>,
-    Json(filter): Json<StockFilter>,
-) -> Result<Json<FilterStats>, StatusCode> {
-    let analyzer = StockAnalyzer::new_with_cache(state.cache.clone());
-    match analyzer.fetch_all_tickers_cached().await {
-        Ok(all_tickers) => {
-            let filtered_tickers = StockAnalyzer::filter_tickers(&all_tickers, &filter);
+    Json(filter): Json<FilterSpec>,
+) -> Result<Json<FilterStats>, ApiError> {
+    validate_filter(&filter)?;
+    let analyzer = state.analyzer();
+    match state.fetch_tickers(&analyzer).await {
+        Ok((all_tickers, _)) => {
+            let (filtered_tickers, member_matches) = StockAnalyzer::filter_tickers_spec(&all_tickers, &filter);
             
             let mut sectors = HashMap::new();
             let mut countries = HashMap::new();
+            let mut regions = HashMap::new();
             let mut price_ranges = HashMap::new();
+            let mut exchanges = HashMap::new();
+            let mut near_52_week_low = 0;
+            let mut adrs = 0;
             
             for ticker in &filtered_tickers {
                 if let Some(sector) = &ticker.sector {
                     *sectors.entry(sector.clone()).or_insert(0) += 1;
                 }
-                if let Some(country) = &ticker.country {
+                if let Some(country) = ticker.country_normalized.as_ref().or(ticker.country.as_ref()) {
                     *countries.entry(country.clone()).or_insert(0) += 1;
                 }
+                if let Some(region) = ticker.region {
+                    *regions.entry(region.as_str().to_string()).or_insert(0) += 1;
+                }
+                if let Some(exchange) = &ticker.exchange {
+                    *exchanges.entry(exchange.clone()).or_insert(0) += 1;
+                }
+                if ticker.pct_above_52_week_low().is_some_and(|pct| pct <= NEAR_52_WEEK_LOW_PCT) {
+                    near_52_week_low += 1;
+                }
+                if ticker.is_adr {
+                    adrs += 1;
+                }
                 
-                if let Some(price_str) = &ticker.last_sale {
-                    if let Ok(price) = price_str.replace('$', "").parse::<f64>() {
-                        let range = match price {
-                            p if p < 10.0 => "Under $10",
-                            p if p < 50.0 => "$10-$50",
-                            p if p < 100.0 => "$50-$100",
-                            p if p < 500.0 => "$100-$500",
-                            _ => "Over $500",
-                        };
-                        *price_ranges.entry(range.to_string()).or_insert(0) += 1;
-                    }
+                if let Some(price) = ticker.last_sale_f64 {
+                    let range = match price {
+                        p if p < 10.0 => "Under $10",
+                        p if p < 50.0 => "$10-$50",
+                        p if p < 100.0 => "$50-$100",
+                        p if p < 500.0 => "$100-$500",
+                        _ => "Over $500",
+                    };
+                    *price_ranges.entry(range.to_string()).or_insert(0) += 1;
                 }
             }
             
@@ -378,17 +4290,197 @@ async fn get_filter_stats(
                 filtered_tickers: filtered_tickers.len(),
                 sectors,
                 countries,
+                regions,
                 price_ranges,
+                exchanges,
+                near_52_week_low,
+                adrs,
+                member_matches,
             }))
         }
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(ticker_fetch_error(e)),
+    }
+}
+
+/// The stored preset called `name`
+async fn load_preset(state: &AppState, name: &str) -> Result<FilterPreset, ApiError> {
+    presets_db(state)?
+        .get_preset(name)
+        .await
+        .map_err(preset_db_error)?
+        .ok_or_else(|| ApiError::NotFound(format!("preset '{}' not found", name)))
+}
+
+/// The filter a request names, looking presets up in the database, along
+/// with the preset's opportunity rules
+async fn resolve_request_filter(
+    state: &AppState,
+    request: &AnalysisRequest,
+) -> Result<(FilterSpec, Option<Vec<OpportunityRule>>), ApiError> {
+    match (&request.filter, &request.preset_name) {
+        (Some(filter), None) => Ok((filter.clone(), None)),
+        (None, Some(name)) => load_preset(state, name).await.map(|preset| (preset.filter.into(), preset.opportunity_rules)),
+        (Some(_), Some(_)) => Err(ApiError::InvalidRequest("give either filter or preset_name, not both".to_string())),
+        (None, None) => Err(ApiError::InvalidRequest("either filter or preset_name is required".to_string())),
     }
 }
 
+/// Response of `POST /api/analysis`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisStarted {
+    pub session_id: String,
+    /// Always "started"
+    pub status: String,
+}
+
+/// Start an analysis session, or with `dry_run` set, return what the
+/// session would do without starting it
 async fn start_analysis(
     State(state): State<AppState>,
-    Json(request): Json<AnalysisRequest>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+    Json(mut request): Json<AnalysisRequest>,
+) -> Result<Response, ApiError> {
+    let filter = prepare_analysis(&state, &mut request).await?;
+    if request.dry_run {
+        return Ok(Json(plan_analysis(&state, &request, &filter).await?).into_response());
+    }
+    let session = begin_session(&state, request, filter).await;
+    let session_id = session.session_id.clone();
+    
+    // Spawn background task for analysis
+    let state_clone = state.clone();
+    tokio::spawn(async move {
+        run_analysis(state_clone, session).await;
+    });
+    
+    Ok(Json(AnalysisStarted {
+        session_id,
+        status: "started".to_string(),
+    })
+    .into_response())
+}
+
+/// Most symbols `POST /api/analyze-batch` analyzes inline
+pub const MAX_BATCH_SYMBOLS: usize = 25;
+
+/// Response of `POST /api/analyze-batch`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchAnalysis {
+    /// The session the batch ran as, kept like any other
+    pub session_id: String,
+    /// "completed", or "budget_exhausted" when a budget stopped it early
+    pub status: String,
+    pub analyzed_count: usize,
+    pub opportunities_found: usize,
+    /// Results passing the request's filter, in the order requested
+    pub results: Vec<StockAnalysisResult>,
+    /// Symbols that produced no result, having no price history or
+    /// failing to fetch
+    pub missing: Vec<String>,
+    pub error_message: Option<String>,
+}
+
+/// Analyze the request's `symbols`, at most `MAX_BATCH_SYMBOLS` of them,
+/// and answer with their results once done instead of a session to poll
+async fn analyze_batch(
+    State(state): State<AppState>,
+    Json(mut request): Json<AnalysisRequest>,
+) -> Result<Json<BatchAnalysis>, ApiError> {
+    let requested = match &request.symbols {
+        None => return Err(ApiError::InvalidRequest("symbols is required; use POST /api/analysis to screen the ticker listing".to_string())),
+        Some(symbols) if symbols.len() > MAX_BATCH_SYMBOLS => {
+            return Err(ApiError::InvalidRequest(format!(
+                "a batch analyzes at most {} symbols, got {}; use POST /api/analysis and poll the session for larger lists",
+                MAX_BATCH_SYMBOLS,
+                symbols.len()
+            )))
+        }
+        Some(symbols) => symbols.iter().map(|symbol| normalize_symbol(symbol)).collect::<Vec<_>>(),
+    };
+    if request.dry_run {
+        return Err(ApiError::InvalidRequest("dry_run is not supported for batches; use POST /api/analysis".to_string()));
+    }
+    let filter = prepare_analysis(&state, &mut request).await?;
+    let session = begin_session(&state, request, filter.clone()).await;
+    let session_id = session.session_id.clone();
+    // Spawned so a client hanging up can't leave the session running forever
+    tokio::spawn(run_analysis(state.clone(), session)).await.map_err(|e| {
+        tracing::error!("Batch analysis {} failed: {}", session_id, e);
+        ApiError::Internal("analysis failed".to_string())
+    })?;
+
+    let status = state
+        .sessions
+        .read()
+        .await
+        .get(&session_id)
+        .cloned()
+        .ok_or_else(|| ApiError::SessionNotFound(session_id.clone()))?;
+    if status.status == "error" {
+        return Err(ApiError::Internal(status.error_message.unwrap_or_else(|| "analysis failed".to_string())));
+    }
+    let analyzed: HashSet<String> = status.results.iter().map(|result| normalize_symbol(&result.ticker)).collect();
+    let mut seen = HashSet::new();
+    let missing = requested
+        .into_iter()
+        .filter(|symbol| seen.insert(symbol.clone()) && !analyzed.contains(symbol))
+        .collect();
+    let results = with_notes(&state, filter_results(&status.results, &filter)).await;
+    Ok(Json(BatchAnalysis {
+        session_id,
+        status: status.status,
+        analyzed_count: status.analyzed_count,
+        opportunities_found: status.opportunities_found,
+        results,
+        missing,
+        error_message: status.error_message,
+    }))
+}
+
+/// Resolve and check everything a request asks for, settling its
+/// opportunity rules, and return the filter it analyzes with
+async fn prepare_analysis(state: &AppState, request: &mut AnalysisRequest) -> Result<FilterSpec, ApiError> {
+    let (filter, preset_rules) = resolve_request_filter(state, request).await?;
+    validate_filter(&filter)?;
+    // Rules sent with the request win over the preset's
+    if request.opportunity_rules.is_none() {
+        request.opportunity_rules = preset_rules;
+    }
+    if let Some(rules) = &request.opportunity_rules {
+        validate_rules(rules).map_err(|e| {
+            tracing::warn!("Rejected opportunity rules: {}", e);
+            ApiError::InvalidRequest(e)
+        })?;
+    }
+    let history_range = request.history_range();
+    history_range
+        .validate()
+        .and_then(|()| history_range.validate_lookback(state.clock.now()))
+        .map_err(|e| {
+            tracing::warn!("Rejected history range: {}", e);
+            ApiError::InvalidRequest(e)
+        })?;
+    request.indicator_config().validate().map_err(|e| {
+        tracing::warn!("Rejected indicator config: {}", e);
+        ApiError::InvalidRequest(e)
+    })?;
+    request.validate_timeframe().map_err(|e| {
+        tracing::warn!("Rejected timeframe: {}", e);
+        ApiError::InvalidRequest(e)
+    })?;
+    request.validate_budget().map_err(|e| {
+        tracing::warn!("Rejected session budget: {}", e);
+        ApiError::InvalidRequest(e)
+    })?;
+    request.validate_symbols().map_err(|e| {
+        tracing::warn!("Rejected symbols: {}", e);
+        ApiError::InvalidRequest(e)
+    })?;
+    Ok(filter)
+}
+
+/// Register a running session for `request` and record it, ready for
+/// `run_analysis`
+async fn begin_session(state: &AppState, request: AnalysisRequest, filter: FilterSpec) -> AnalysisSession {
     let session_id = Uuid::new_v4().to_string();
     
     let initial_status = AnalysisStatus {
@@ -400,48 +4492,346 @@ async fn start_analysis(
         opportunities_found: 0,
         error_message: None,
         results: Vec::new(),
+        results_total: 0,
+        estimated_completion: None,
+        remaining_tickers: Vec::new(),
     };
     
     // Store initial status
     state.sessions.write().await.insert(session_id.clone(), initial_status.clone());
-    
-    // Spawn background task for analysis
+    state.session_started.notify_waiters();
+    // Keep the request with the session so reports can say what produced it
+    let session = AnalysisSession {
+        session_id: session_id.clone(),
+        request,
+        filter,
+        status: initial_status.status.clone(),
+        started_at: state.clock.now(),
+        finished_at: None,
+        analyzed_count: 0,
+        total_count: 0,
+        opportunities_found: 0,
+        error_message: None,
+        remaining_tickers: Vec::new(),
+    };
+    record_session(state, &session).await;
+    session
+}
+
+/// Plan of a dry-run request: the tickers passing its filter, which of them
+/// have their history cached, and how long analyzing them should take
+async fn plan_analysis(state: &AppState, request: &AnalysisRequest, filter: &FilterSpec) -> Result<AnalysisPlan, ApiError> {
+    let requested = request.requested_tickers(&*state.ticker_directory.read().await);
+    let (total_tickers, filtered_tickers) = match requested {
+        Some(tickers) => (tickers.len(), tickers),
+        None => {
+            let analyzer = state.analyzer();
+            let (all_tickers, _) = state.fetch_tickers(&analyzer).await.map_err(ticker_fetch_error)?;
+            (all_tickers.len(), StockAnalyzer::filter_tickers_spec(&all_tickers, filter).0)
+        }
+    };
+    let max_analysis = request.max_analysis.unwrap_or(filtered_tickers.len()).min(filtered_tickers.len());
+    let planned: Vec<&TickerInfo> = filtered_tickers.iter().take(max_analysis).collect();
+    let cache_hit = planned_cache_hits(state, &planned, &request.history_range()).await;
+    let timings = state.ticker_timings.lock().unwrap().clone();
+    Ok(AnalysisPlan::new(total_tickers, filtered_tickers.len(), &cache_hit, &timings, state.clock.now()))
+}
+
+/// Whether each ticker's history for `range` is in the cache now
+async fn planned_cache_hits(state: &AppState, tickers: &[&TickerInfo], range: &HistoryRange) -> Vec<bool> {
+    let mut cache_hit = Vec::with_capacity(tickers.len());
+    for ticker in tickers {
+        cache_hit.push(state.cache.has_quote_series(&range.cache_key(&ticker.symbol)).await);
+    }
+    cache_hit
+}
+
+/// When the tickers left should be done, at recent per-ticker timings
+fn estimate_completion(state: &AppState, cache_hits: usize, fresh_fetches: usize) -> chrono::DateTime<chrono::Utc> {
+    let estimate = state.ticker_timings.lock().unwrap().estimate(cache_hits, fresh_fetches);
+    completion_at(state.clock.now(), estimate)
+}
+
+/// Continue an interrupted session with the tickers it has no stored
+/// result for. Progress picks up from the stored results, and a "resumed"
+/// status frame is broadcast before the session runs again.
+async fn resume_analysis(
+    State(state): State<AppState>,
+    axum::extract::Path(session_id): axum::extract::Path<String>,
+) -> Result<Json<AnalysisStatus>, ApiError> {
+    let db = state.database.clone().ok_or(ApiError::DatabaseUnavailable)?;
+    let resume_db_error = |e: anyhow::Error| {
+        tracing::error!("Failed to load session {} to resume: {}", session_id, e);
+        ApiError::Database("Failed to load the session".to_string())
+    };
+    let mut session = db
+        .get_session(&session_id)
+        .await
+        .map_err(resume_db_error)?
+        .ok_or_else(|| ApiError::SessionNotFound(session_id.clone()))?;
+    if session.status != "interrupted" {
+        return Err(ApiError::InvalidRequest(format!(
+            "session {} is {}; only interrupted sessions can be resumed",
+            session_id, session.status
+        )));
+    }
+    let mut results = db.get_results_by_session(&session_id).await.map_err(resume_db_error)?;
+    // Stored newest first; sessions list results in the order they were analyzed
+    results.reverse();
+
+    let mut status = AnalysisStatus {
+        session_id: session_id.clone(),
+        status: "resumed".to_string(),
+        progress: 0.0,
+        analyzed_count: session.analyzed_count as usize,
+        total_count: session.total_count as usize,
+        opportunities_found: results.iter().filter(|result| result.is_opportunity).count(),
+        error_message: None,
+        results_total: results.len(),
+        results,
+        estimated_completion: None,
+        remaining_tickers: Vec::new(),
+    };
+    if status.total_count > 0 {
+        status.progress = status.analyzed_count as f64 / status.total_count as f64;
+    }
+    let resumed = status.progress_frame(state.config.progress_results);
+    state.publish(resumed.clone());
+    tracing::info!("▶️ Resuming analysis session {} with {} stored results", session_id, status.results.len());
+
+    status.status = "running".to_string();
+    state.sessions.write().await.insert(session_id.clone(), status.clone());
+    state.session_started.notify_waiters();
+    match db.last_session_event_seq(&session_id).await {
+        Ok(latest_seq) => state.session_events.continue_from(&session_id, latest_seq.unwrap_or(0)),
+        Err(e) => tracing::warn!("Failed to load the last event of session {}: {}", session_id, e),
+    }
+    session.status = status.status.clone();
+    session.finished_at = None;
+    session.error_message = None;
+    record_session(&state, &session).await;
+
     let state_clone = state.clone();
-    let session_id_clone = session_id.clone();
     tokio::spawn(async move {
-        run_analysis(state_clone, session_id_clone, request).await;
+        run_analysis(state_clone, session).await;
     });
-    
-    Ok(Json(serde_json::json!({
-        "session_id": session_id,
-        "status": "started"
-    })))
+    Ok(Json(resumed))
 }
 
 async fn get_analysis_status(
     State(state): State<AppState>,
     axum::extract::Path(session_id): axum::extract::Path<String>,
-) -> Result<Json<AnalysisStatus>, StatusCode> {
+) -> Result<Json<AnalysisStatus>, ApiError> {
     let sessions = state.sessions.read().await;
     match sessions.get(&session_id) {
         Some(status) => Ok(Json(status.clone())),
-        None => Err(StatusCode::NOT_FOUND),
+        None => Err(ApiError::SessionNotFound(session_id)),
     }
 }
 
 async fn get_analysis_results(
     State(state): State<AppState>,
     axum::extract::Path(session_id): axum::extract::Path<String>,
-) -> Result<Json<Vec<StockAnalysisResult>>, StatusCode> {
+    Query(params): Query<ResultsQuery>,
+) -> Result<Json<ResultsPage>, ApiError> {
+    let request = params.page_request()?;
     let sessions = state.sessions.read().await;
     match sessions.get(&session_id) {
-        Some(status) => Ok(Json(status.results.clone())),
-        None => Err(StatusCode::NOT_FOUND),
+        Some(status) => {
+            let mut page = request.paginate(status.results.clone());
+            page.results = apply_summary_flag(page.results, params.include_summary);
+            Ok(Json(page))
+        }
+        None => Err(ApiError::SessionNotFound(session_id)),
+    }
+}
+
+/// Response of `GET /api/analysis/:session_id/remaining`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemainingTickers {
+    pub session_id: String,
+    pub status: String,
+    pub analyzed_count: usize,
+    pub remaining_count: usize,
+    /// Pass as `symbols`, with the session's filter, to analyze just these
+    pub tickers: Vec<String>,
+}
+
+/// The tickers a finished session left unanalyzed: those it stopped short
+/// of when its budget ran out, and none for one that got through its list
+async fn get_remaining_tickers(
+    State(state): State<AppState>,
+    axum::extract::Path(session_id): axum::extract::Path<String>,
+) -> Result<Json<RemainingTickers>, ApiError> {
+    let in_memory = state.sessions.read().await.get(&session_id).cloned();
+    let (status, analyzed_count, tickers) = match in_memory {
+        Some(status) => (status.status, status.analyzed_count, status.remaining_tickers),
+        None => {
+            let stored = match state.database {
+                Some(ref db) => db.get_session(&session_id).await.map_err(|e| {
+                    tracing::error!("Failed to load session {}: {}", session_id, e);
+                    ApiError::Database("Failed to load the session".to_string())
+                })?,
+                None => None,
+            };
+            let session = stored.ok_or_else(|| ApiError::SessionNotFound(session_id.clone()))?;
+            (session.status, session.analyzed_count as usize, session.remaining_tickers)
+        }
+    };
+    if status == "running" {
+        return Err(ApiError::InvalidRequest(format!("session {} is still running", session_id)));
+    }
+    Ok(Json(RemainingTickers { session_id, status, analyzed_count, remaining_count: tickers.len(), tickers }))
+}
+
+#[derive(Deserialize)]
+struct SessionEventsQuery {
+    ticker: Option<String>,
+    /// Minimum level: debug, info, warn or error
+    level: Option<String>,
+    #[serde(default)]
+    after_seq: u64,
+    limit: Option<usize>,
+}
+
+/// A session's or continuous cycle's events, oldest first, paged by
+/// `after_seq`. Recent sessions are served from memory; older ones from
+/// the database, which doesn't know how many events were dropped.
+async fn get_session_events(
+    State(state): State<AppState>,
+    axum::extract::Path(session_id): axum::extract::Path<String>,
+    Query(params): Query<SessionEventsQuery>,
+) -> Result<Json<SessionEventsPage>, ApiError> {
+    let filter = EventFilter {
+        ticker: params.ticker.map(|ticker| ticker.trim().to_string()),
+        min_level: params.level.as_deref().map(str::parse).transpose().map_err(ApiError::InvalidRequest)?,
+        after_seq: params.after_seq,
+        limit: params.limit.unwrap_or(DEFAULT_SESSION_EVENTS_PER_PAGE).clamp(1, MAX_SESSION_EVENTS_PER_PAGE),
+    };
+    state.flush_session_events().await;
+    if let Some(page) = state.session_events.page(&session_id, &filter) {
+        return Ok(Json(page));
+    }
+
+    let stored = match state.database {
+        Some(ref db) => {
+            let events_db_error = |e: anyhow::Error| {
+                tracing::error!("Failed to load events of session {}: {}", session_id, e);
+                ApiError::Database("Failed to load session events".to_string())
+            };
+            let mut events = db.get_session_events(&session_id, &filter).await.map_err(events_db_error)?;
+            let latest_seq = db.last_session_event_seq(&session_id).await.map_err(events_db_error)?;
+            latest_seq.map(|latest_seq| {
+                let more = events.len() > filter.limit;
+                events.truncate(filter.limit);
+                SessionEventsPage {
+                    session_id: session_id.clone(),
+                    dropped: 0,
+                    latest_seq,
+                    next_after_seq: if more { events.last().map(|event| event.seq) } else { None },
+                    events,
+                }
+            })
+        }
+        None => None,
+    };
+    match stored {
+        Some(page) => Ok(Json(page)),
+        // A session that hasn't recorded anything yet
+        None if state.sessions.read().await.contains_key(&session_id) => Ok(Json(SessionEventsPage {
+            session_id,
+            dropped: 0,
+            latest_seq: 0,
+            next_after_seq: None,
+            events: Vec::new(),
+        })),
+        None => Err(ApiError::SessionNotFound(session_id)),
+    }
+}
+
+#[derive(Deserialize)]
+struct ReportQuery {
+    /// "json" (default) or "zip"
+    format: Option<String>,
+    /// Opportunities listed in `top_opportunities`
+    top: Option<usize>,
+}
+
+/// The whole session as one pretty-printed JSON report, or with
+/// `?format=zip` that report plus a CSV of the results in a zip archive.
+/// Sessions no longer in memory are rebuilt from the database.
+async fn get_analysis_report(
+    State(state): State<AppState>,
+    axum::extract::Path(session_id): axum::extract::Path<String>,
+    Query(params): Query<ReportQuery>,
+) -> Result<Response, ApiError> {
+    let zip = match params.format.as_deref().unwrap_or("json") {
+        "json" => false,
+        "zip" => true,
+        other => return Err(ApiError::InvalidRequest(format!("unknown format '{}'; use json or zip", other))),
+    };
+
+    let status = state.sessions.read().await.get(&session_id).cloned();
+    let report_db_error = |e: anyhow::Error| {
+        tracing::error!("Failed to load session {} for its report: {}", session_id, e);
+        ApiError::Database("Failed to load the session".to_string())
+    };
+    let stored = match state.database {
+        Some(ref db) => db.get_session(&session_id).await.map_err(report_db_error)?,
+        None => None,
+    };
+    let (results, complete) = match (&status, &state.database) {
+        (Some(status), _) => (status.results.clone(), true),
+        (None, Some(db)) if stored.is_some() => {
+            let mut results = db.get_results_by_session(&session_id).await.map_err(report_db_error)?;
+            // Stored newest first; sessions list results in the order they were analyzed
+            results.reverse();
+            (results, db.is_session_complete(&session_id).await.map_err(report_db_error)?)
+        }
+        _ => return Err(ApiError::SessionNotFound(session_id)),
+    };
+
+    let results = with_notes(&state, results).await;
+    let top = params.top.unwrap_or(DEFAULT_TOP_OPPORTUNITIES);
+    let mut report = SessionReport::new(&session_id, status.as_ref(), stored, results, top, state.clock.now());
+    // Stored results without the completion marker are only part of the session
+    report.partial |= !complete;
+    let build_error = |e: anyhow::Error| {
+        tracing::error!("Failed to build the report for session {}: {}", session_id, e);
+        ApiError::Internal("Failed to build the report".to_string())
+    };
+    if zip {
+        let disposition = format!("attachment; filename=\"analysis-{}.zip\"", session_id);
+        let body = report.to_zip().map_err(build_error)?;
+        Ok(([(header::CONTENT_TYPE, "application/zip".to_string()), (header::CONTENT_DISPOSITION, disposition)], body).into_response())
+    } else {
+        let body = report.to_json().map_err(build_error)?;
+        Ok(([(header::CONTENT_TYPE, "application/json")], body).into_response())
     }
 }
 
-async fn run_analysis(state: AppState, session_id: String, request: AnalysisRequest) {
-    let mut analyzer = StockAnalyzer::new_with_cache(state.cache.clone());
+async fn run_analysis(state: AppState, mut session: AnalysisSession) {
+    let mut analyzer = state.analyzer();
+    let recent = state.config.progress_results;
+    let mut enricher = state.analyst_enricher();
+    let session_id = session.session_id.clone();
+    let request = session.request.clone();
+    let filter = session.filter.clone();
+    let range = request.history_range();
+    let indicator_config = request.indicator_config();
+    let timeframe = request.timeframe();
+    let include_partial = request.include_partial.unwrap_or(true);
+    let opportunity_rules = request
+        .opportunity_rules
+        .clone()
+        .unwrap_or_else(|| OpportunityRule::for_filter(&filter));
+    // Custom rules carry their own thresholds
+    let adaptive_rsi = request.opportunity_rules.is_none() && filter.members().iter().any(|member| member.adaptive_rsi);
+    // Relative strength windows are counted in daily candles
+    let benchmark = match range == HistoryRange::default() {
+        true => state.benchmark.read().await.clone(),
+        false => None,
+    };
     
     // Update status to show we're starting
     let mut current_status = {
@@ -449,53 +4839,147 @@ async fn run_analysis(state: AppState, session_id: String, request: AnalysisRequ
         sessions.get(&session_id).unwrap().clone()
     };
     
-    // Fetch tickers with caching
-    let all_tickers = match analyzer.fetch_all_tickers_cached().await {
-        Ok(tickers) => tickers,
-        Err(e) => {
-            current_status.status = "error".to_string();
-            current_status.error_message = Some(format!("Failed to fetch tickers: {}", e));
-            state.sessions.write().await.insert(session_id, current_status.clone());
-            let _ = state.broadcast_tx.send(current_status);
-            return;
-        }
+    // Named symbols skip the listing; otherwise fetch it with caching and filter it
+    let requested = request.requested_tickers(&*state.ticker_directory.read().await);
+    let filtered_tickers = match requested {
+        Some(tickers) => tickers,
+        None => match state.fetch_tickers(&analyzer).await {
+            Ok((all_tickers, _)) => StockAnalyzer::filter_tickers_spec(&all_tickers, &filter).0,
+            Err(e) => {
+                current_status.status = "error".to_string();
+                current_status.error_message = Some(format!("Failed to fetch tickers: {}", e));
+                finish_session(&state, &mut session, &current_status).await;
+                state.sessions.write().await.insert(session_id, current_status.clone());
+                state.publish(current_status.progress_frame(recent));
+                return;
+            }
+        },
     };
-    
-    // Apply filters
-    let filtered_tickers = StockAnalyzer::filter_tickers(&all_tickers, &request.filter);
     let max_analysis = request.max_analysis.unwrap_or(filtered_tickers.len()).min(filtered_tickers.len());
+    // A resumed session skips the tickers it already has results for
+    let done: HashSet<String> = current_status.results.iter().map(|result| normalize_symbol(&result.ticker)).collect();
+    let remaining: Vec<&TickerInfo> = filtered_tickers
+        .iter()
+        .take(max_analysis)
+        .filter(|ticker| !done.contains(&normalize_symbol(&ticker.symbol)))
+        .collect();
+    
+    // Estimated like a dry run, and counted down as tickers finish
+    let cache_hit = planned_cache_hits(&state, &remaining, &range).await;
+    let mut hits_left = cache_hit.iter().filter(|hit| **hit).count();
+    let mut fetches_left = cache_hit.len() - hits_left;
     
     current_status.total_count = max_analysis;
+    current_status.analyzed_count = max_analysis - remaining.len();
+    current_status.estimated_completion = Some(estimate_completion(&state, hits_left, fetches_left));
+    if max_analysis > 0 {
+        current_status.progress = current_status.analyzed_count as f64 / max_analysis as f64;
+    }
     state.sessions.write().await.insert(session_id.clone(), current_status.clone());
-    let _ = state.broadcast_tx.send(current_status.clone());
+    state.publish(current_status.progress_frame(recent));
+    sync_session(&mut session, &current_status);
+    record_session(&state, &session).await;
+    let mut pending = PendingResults::new(&session_id);
+    
+    // Budgets count from when this run started, resumed or not
+    let run_started = state.clock.now();
+    let mut upstream_requests = 0;
+    let mut exhausted = None;
     
     // Analyze each ticker
-    for (i, ticker_info) in filtered_tickers.iter().take(max_analysis).enumerate() {
+    for (index, (ticker_info, planned_hit)) in remaining.iter().zip(cache_hit).enumerate() {
+        let elapsed = (state.clock.now() - run_started).to_std().unwrap_or_default();
+        if let Some(reason) = request.exhausted_budget(elapsed, upstream_requests) {
+            let left: Vec<String> = remaining[index..].iter().map(|ticker| ticker.symbol.clone()).collect();
+            exhausted = Some((reason, left));
+            break;
+        }
         let ticker = &ticker_info.symbol;
+        let trace = TickerTrace::new(state.session_events.clone(), &session_id, ticker);
+        trace.record(SessionEventKind::Started);
+        let started = std::time::Instant::now();
+        let started_at = chrono::Utc::now();
+        
+        let mut served_from_cache = false;
         
-        match analyzer.fetch_stock_data_cached(ticker).await {
-            Ok(stock_data) => {
+        match analyzer.fetch_quote_series_range_cached(ticker, &range).await {
+            Ok(series) => {
+                trace_fetch(&trace, &series, started, started_at);
+                served_from_cache = series.fetched_at < started_at;
+                let resampled;
+                let stock_data = match timeframe {
+                    Timeframe::Daily => &series.bars,
+                    _ => {
+                        resampled = resample_as_of(&series.bars, timeframe, state.clock.now().date_naive(), include_partial);
+                        &resampled
+                    }
+                };
                 if !stock_data.is_empty() {
-                    let indicators = analyzer.calculate_indicators_cached(ticker, &stock_data).await;
+                    // Retained indicator state tracks the default daily series and settings only
+                    let indicators = if range == HistoryRange::default()
+                        && indicator_config == IndicatorConfig::default()
+                        && timeframe == Timeframe::Daily
+                    {
+                        let indicators = analyzer.calculate_indicators_cached(ticker, stock_data).await;
+                        if let Some(ref db) = state.database {
+                            store_indicator_history(db, ticker, stock_data, &indicators).await;
+                        }
+                        indicators
+                    } else {
+                        // The config was validated when the session started
+                        analyzer
+                            .calculate_indicators_with_config(ticker, stock_data, &indicator_config)
+                            .unwrap_or_default()
+                    };
+                    trace_indicator_warnings(&trace, &series, &indicators);
                     
                     if let Some(latest_indicator) = indicators.last() {
                         let current_price = stock_data.last().map(|quote| quote.close);
-                        let is_opportunity = latest_indicator.rsi.map_or(false, |rsi| {
-                            rsi <= request.filter.oversold_rsi_threshold.unwrap_or(30.0) ||
-                            rsi >= request.filter.overbought_rsi_threshold.unwrap_or(70.0)
-                        });
+                        let history = &state.config.min_history;
+                        let inputs = RuleInputs::new(stock_data.last(), latest_indicator, ticker_info.pct_change_f64)
+                            .with_history(latest_indicator.bars_used, history);
+                        // Adaptive members judge the symbol against its own RSI distribution
+                        let (symbol_rules, thresholds_used) = match adaptive_rsi {
+                            true => {
+                                let closes = stock_data.iter().map(|bar| bar.close);
+                                let adaptive = adaptive_rsi_thresholds(&rsi_series(closes, indicator_config.rsi_period));
+                                (Cow::Owned(OpportunityRule::for_symbol(&filter, adaptive)), rsi_thresholds_used(&filter, adaptive))
+                            }
+                            false => (Cow::Borrowed(&opportunity_rules), None),
+                        };
+                        let matched_rules = matching_rules(&symbol_rules, &inputs);
+                        let is_opportunity = !matched_rules.is_empty();
                         
                         let mut signals = Vec::new();
-                        if let Some(rsi) = latest_indicator.rsi {
-                            if rsi <= 30.0 {
+                        let thresholds = thresholds_used
+                            .unwrap_or(RsiThresholds { oversold: DEFAULT_OVERSOLD_RSI, overbought: DEFAULT_OVERBOUGHT_RSI });
+                        if let Some(rsi) = inputs.rsi {
+                            if rsi <= thresholds.oversold {
                                 signals.push("Oversold - Potential Buy".to_string());
-                            } else if rsi >= 70.0 {
+                            } else if rsi >= thresholds.overbought {
                                 signals.push("Overbought - Potential Sell".to_string());
                             }
                         }
+                        signals.extend(history.shortfall_signal(latest_indicator.bars_used));
+                        signals.extend(matched_rules.into_iter().map(rule_signal));
+                        signals.extend(StockAnalyzer::indicator_signals(stock_data, &indicators));
+                        let crossovers = history.supported_crossovers(
+                            recent_crossovers(stock_data, &indicators, state.config.crossover_lookback_bars),
+                            stock_data,
+                            &indicators,
+                        );
+                        signals.extend(crossovers.iter().map(CrossoverEvent::signal));
+                        if let Some(last) = stock_data.last() {
+                            signals.extend(recent_event_signals(&series.events, last.timestamp));
+                        }
                         
                         let (macd_value, macd_signal_value, macd_histogram_value) = 
                             latest_indicator.macd.unwrap_or((0.0, 0.0, 0.0));
+                        // Relative strength windows are counted in daily bars whatever the timeframe
+                        let relative_strength = benchmark
+                            .as_ref()
+                            .map(|benchmark| RelativeStrength::compute(&series.bars, benchmark))
+                            .unwrap_or_default();
                         
                         let result = StockAnalysisResult {
                             ticker: ticker.clone(),
@@ -508,281 +4992,1429 @@ async fn run_analysis(state: AppState, session_id: String, request: AnalysisRequ
                             macd_signal: if latest_indicator.macd.is_some() { Some(macd_signal_value) } else { None },
                             macd_histogram: if latest_indicator.macd.is_some() { Some(macd_histogram_value) } else { None },
                             volume: stock_data.last().map(|q| q.volume),
-                            pct_change: ticker_info.pct_change.as_ref().and_then(|s| {
-                                s.replace('%', "").parse().ok()
-                            }),
+                            pct_change: ticker_info.pct_change_f64,
                             market_cap: ticker_info.market_cap.clone(),
+                            sector: ticker_info.sector.clone(),
+                            industry: ticker_info.industry.clone(),
+                            exchange: ticker_info.exchange.clone(),
                             is_opportunity,
                             signals,
-                            timestamp: chrono::Utc::now(),
+                            timestamp: state.clock.now(),
+                            data_source: Some(series.source.clone()),
+                            data_fetched_at: Some(series.fetched_at),
+                            summary: None,
+                            analyst_target: None,
+                            upside_to_target_pct: None,
+                            obv: latest_indicator.obv,
+                            vwap: latest_indicator.vwap,
+                            volume_vs_avg: latest_indicator.volume_vs_avg,
+                            high_52w: latest_indicator.high_52w,
+                            low_52w: latest_indicator.low_52w,
+                            pct_from_high: latest_indicator.pct_from_high,
+                            pct_from_low: latest_indicator.pct_from_low,
+                            range_window_complete: latest_indicator.window_complete,
+                            rs_1w: relative_strength.rs_1w,
+                            rs_1m: relative_strength.rs_1m,
+                            rs_3m: relative_strength.rs_3m,
+                            rs_rank: None,
+                            moving_averages: latest_indicator.moving_averages.clone(),
+                            crossovers,
+                            data_quality: series.quality,
+                            adx: latest_indicator.adx.map(|adx| adx.adx),
+                            plus_di: latest_indicator.adx.map(|adx| adx.plus_di),
+                            minus_di: latest_indicator.adx.map(|adx| adx.minus_di),
+                            last_split: last_split(&series.events),
+                            timeframe,
+                            anomalies: Vec::new(),
+                            rsi_oversold_used: thresholds_used.map(|thresholds| thresholds.oversold),
+                            rsi_overbought_used: thresholds_used.map(|thresholds| thresholds.overbought),
+                            rsi_sector_zscore: None,
+                            rsi_industry_zscore: None,
+                            note: None,
+                            opportunity_state: OpportunityState::None,
+                            opportunity_streak: 0,
+                            opportunity_since: None,
+                            custom: latest_indicator.custom.clone(),
+                            bars_used: Some(latest_indicator.bars_used),
                         };
+                        // Checked before it is published or stored
+                        if let Some(mut result) = state.screen_result(result, &session_id).await {
+                            // Every analyzed ticker here already passed the request's filter
+                            if let Some(enricher) = enricher.as_mut() {
+                                enricher.enrich(&mut result).await;
+                            }
+                            result.summary = Some(generate_summary(&result));
+                            trace.record(SessionEventKind::Analyzed { opportunity: is_opportunity, ms: started.elapsed().as_millis() as u64 });
                         
-                        current_status.results.push(result.clone());
-                        if is_opportunity {
-                            current_status.opportunities_found += 1;
-                        }
-
-                        // Store in database if available
-                        if let Some(ref db) = state.database {
-                            if let Err(e) = db.store_analysis_result(&result, &session_id).await {
-                                tracing::warn!("Failed to store result in database: {}", e);
+                            // Deltas are measured against the latest daily result
+                            if timeframe == Timeframe::Daily {
+                                publish_result_delta(&state, &result).await;
                             }
+                            state.publish_result(&result);
+                            current_status.results.push(result.clone());
+                            current_status.results_total = current_status.results.len();
+                            if is_opportunity {
+                                current_status.opportunities_found += 1;
+                            }
+                            pending.push(&state, result).await;
+                            state.bump_results_version();
+                        } else {
+                            trace.record(SessionEventKind::Skipped { reason: "anomalous result withheld".to_string() });
                         }
                     }
                 }
             }
             Err(e) => {
                 tracing::warn!("Failed to analyze {}: {}", ticker, e);
+                trace.record(SessionEventKind::from_error(&e));
             }
         }
         
-        current_status.analyzed_count = i + 1;
-        current_status.progress = (i + 1) as f64 / max_analysis as f64;
+        state.ticker_timings.lock().unwrap().record(served_from_cache, started.elapsed());
+        if !served_from_cache {
+            upstream_requests += 1;
+        }
+        match planned_hit {
+            true => hits_left -= 1,
+            false => fetches_left -= 1,
+        }
+        current_status.analyzed_count += 1;
+        current_status.progress = current_status.analyzed_count as f64 / max_analysis as f64;
+        current_status.estimated_completion = Some(estimate_completion(&state, hits_left, fetches_left));
         
         // Update status every 5 stocks or on the last one
-        if (i + 1) % 5 == 0 || i + 1 == max_analysis {
+        if current_status.analyzed_count % 5 == 0 || current_status.analyzed_count == max_analysis {
             state.sessions.write().await.insert(session_id.clone(), current_status.clone());
-            let _ = state.broadcast_tx.send(current_status.clone());
+            state.publish(current_status.progress_frame(recent));
+            // Stored progress is what a resumed session starts from
+            sync_session(&mut session, &current_status);
+            record_session(&state, &session).await;
+            state.flush_session_events().await;
         }
         
         // Remove delay to process faster
         // tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
     }
     
+    // Stop cleanly: what was analyzed is stored and broadcast, and the rest
+    // is kept for a follow-up session
+    if let Some((reason, left)) = exhausted {
+        tracing::info!(
+            "⏱️  Session {} stopped, {}: {} of {} tickers analyzed, {} remaining",
+            session_id,
+            reason,
+            current_status.analyzed_count,
+            max_analysis,
+            left.len()
+        );
+        current_status.status = "budget_exhausted".to_string();
+        current_status.error_message = Some(format!(
+            "{}: {} of {} tickers analyzed, {} remaining",
+            reason,
+            current_status.analyzed_count,
+            max_analysis,
+            left.len()
+        ));
+        current_status.estimated_completion = None;
+        current_status.remaining_tickers = left.clone();
+        session.remaining_tickers = left;
+        pending.flush(&state).await;
+        state.flush_session_events().await;
+        finish_session(&state, &mut session, &current_status).await;
+        state.publish(current_status.progress_frame(recent));
+        state.sessions.write().await.insert(session_id, current_status);
+        return;
+    }
+    
     current_status.status = "completed".to_string();
     current_status.progress = 1.0;
-    state.sessions.write().await.insert(session_id.clone(), current_status.clone());
-    let _ = state.broadcast_tx.send(current_status);
+    current_status.estimated_completion = None;
+    pending.complete(&state).await;
+    state.flush_session_events().await;
+    finish_session(&state, &mut session, &current_status).await;
+    state.publish(current_status.progress_frame(recent));
+    state.sessions.write().await.insert(session_id, current_status);
+}
+
+async fn record_session(state: &AppState, session: &AnalysisSession) {
+    if let Some(ref db) = state.database {
+        if let Err(e) = db.record_session(session).await {
+            tracing::warn!("Failed to record analysis session {}: {}", session.session_id, e);
+        }
+    }
+}
+
+/// Copy the status and counts of `status` onto the stored session
+fn sync_session(session: &mut AnalysisSession, status: &AnalysisStatus) {
+    session.status = status.status.clone();
+    session.analyzed_count = status.analyzed_count as u64;
+    session.total_count = status.total_count as u64;
+    session.opportunities_found = status.opportunities_found as u64;
+    session.error_message = status.error_message.clone();
+}
+
+/// Record a fetched series. Its fetch time predates the ticker's start
+/// when it came from the cache.
+fn trace_fetch(trace: &TickerTrace, series: &QuoteSeries, started: std::time::Instant, started_at: chrono::DateTime<chrono::Utc>) {
+    trace.record(SessionEventKind::Fetched {
+        bars: series.bars.len(),
+        ms: started.elapsed().as_millis() as u64,
+        source: series.source.clone(),
+        cache_hit: series.fetched_at < started_at,
+    });
+}
+
+/// Record what makes a ticker's indicators less trustworthy: repaired
+/// bars, or too little history for some of them
+fn trace_indicator_warnings(trace: &TickerTrace, series: &QuoteSeries, indicators: &[TechnicalIndicators]) {
+    if let Some(quality) = series.quality.filter(|quality| quality.repaired() > 0) {
+        trace.record(SessionEventKind::IndicatorWarning {
+            message: format!("{} of {} bars repaired", quality.repaired(), quality.total_bars),
+        });
+    }
+    let missing: Vec<&str> = match indicators.last() {
+        None => vec!["all indicators"],
+        Some(latest) => [("RSI", latest.rsi.is_none()), ("MACD", latest.macd.is_none())]
+            .into_iter()
+            .filter_map(|(name, missing)| missing.then_some(name))
+            .collect(),
+    };
+    if !missing.is_empty() {
+        trace.record(SessionEventKind::IndicatorWarning {
+            message: format!("{} missing from {} bars", missing.join(" and "), series.bars.len()),
+        });
+    }
+}
+
+/// Record how the session ended
+async fn finish_session(state: &AppState, session: &mut AnalysisSession, status: &AnalysisStatus) {
+    sync_session(session, status);
+    session.finished_at = Some(state.clock.now());
+    record_session(state, session).await;
+}
+
+/// Reject inconsistent filters before doing any work
+fn validate_filter(filter: &FilterSpec) -> Result<(), ApiError> {
+    filter.validate().map_err(|e| {
+        tracing::warn!("Rejected filter: {}", e);
+        ApiError::InvalidFilter(e)
+    })
 }
 
-fn filter_results(results: &[StockAnalysisResult], filter: &StockFilter) -> Vec<StockAnalysisResult> {
+/// Results passing any member of the filter (a union for composite filters)
+/// The results passing any member of `filter`. `Database::query_results`
+/// applies the same conditions in SQL.
+pub fn filter_results(results: &[StockAnalysisResult], filter: &FilterSpec) -> Vec<StockAnalysisResult> {
     results.iter()
-        .filter(|result| {
-            // Apply RSI filter
-            if let Some(min_rsi) = filter.min_rsi {
-                if result.rsi.map_or(true, |rsi| rsi < min_rsi) {
-                    return false;
-                }
-            }
-            if let Some(max_rsi) = filter.max_rsi {
-                if result.rsi.map_or(true, |rsi| rsi > max_rsi) {
-                    return false;
-                }
-            }
-            
-            // Apply price filter
-            if let Some(min_price) = filter.min_price {
-                if result.current_price.map_or(true, |price| price < min_price) {
-                    return false;
-                }
-            }
-            if let Some(max_price) = filter.max_price {
-                if result.current_price.map_or(true, |price| price > max_price) {
-                    return false;
-                }
-            }
-            
-            // Apply volume filter
-            if let Some(min_volume) = filter.min_volume {
-                if result.volume.map_or(true, |vol| vol < min_volume) {
-                    return false;
-                }
-            }
-            if let Some(max_volume) = filter.max_volume {
-                if result.volume.map_or(true, |vol| vol > max_volume) {
-                    return false;
-                }
-            }
-            
-            // Apply percentage change filter
-            if let Some(min_pct_change) = filter.min_pct_change {
-                if result.pct_change.map_or(true, |pct| pct < min_pct_change) {
-                    return false;
-                }
-            }
-            if let Some(max_pct_change) = filter.max_pct_change {
-                if result.pct_change.map_or(true, |pct| pct > max_pct_change) {
-                    return false;
-                }
-            }
-            
-            true
-        })
+        .filter(|result| filter.members().iter().any(|member| result_passes(result, member)))
         .cloned()
         .collect()
 }
 
+fn result_passes(result: &StockAnalysisResult, filter: &StockFilter) -> bool {
+    fn contains_any(value: &Option<String>, allowed: &Option<Vec<String>>) -> bool {
+        match (allowed, value) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(allowed), Some(value)) => {
+                let value = value.to_lowercase();
+                allowed.iter().any(|a| value.contains(&a.to_lowercase()))
+            }
+        }
+    }
+
+    // Apply market cap filter against the parsed screener value
+    if filter.min_market_cap.is_some() || filter.max_market_cap.is_some() {
+        let market_cap = parse_field(result.market_cap.as_deref(), parse_market_cap);
+        if market_cap.is_none_or(|cap| {
+            filter.min_market_cap.is_some_and(|min| cap < min) || filter.max_market_cap.is_some_and(|max| cap > max)
+        }) {
+            return false;
+        }
+    }
+
+    // Apply sector, industry and exchange filters as the listing filter does
+    if !contains_any(&result.sector, &filter.sectors)
+        || !contains_any(&result.industry, &filter.industries)
+        || !contains_any(&result.exchange, &filter.exchanges)
+    {
+        return false;
+    }
+
+    // Apply RSI filter
+    if let Some(min_rsi) = filter.min_rsi {
+        if result.rsi.is_none_or(|rsi| rsi < min_rsi) {
+            return false;
+        }
+    }
+    if let Some(max_rsi) = filter.max_rsi {
+        if result.rsi.is_none_or(|rsi| rsi > max_rsi) {
+            return false;
+        }
+    }
+    
+    // Apply price filter
+    if let Some(min_price) = filter.min_price {
+        if result.current_price.is_none_or(|price| price < min_price) {
+            return false;
+        }
+    }
+    if let Some(max_price) = filter.max_price {
+        if result.current_price.is_none_or(|price| price > max_price) {
+            return false;
+        }
+    }
+    
+    // Apply volume filter
+    if let Some(min_volume) = filter.min_volume {
+        if result.volume.is_none_or(|vol| vol < min_volume) {
+            return false;
+        }
+    }
+    if let Some(max_volume) = filter.max_volume {
+        if result.volume.is_none_or(|vol| vol > max_volume) {
+            return false;
+        }
+    }
+    
+    // Apply percentage change filter
+    if let Some(min_pct_change) = filter.min_pct_change {
+        if result.pct_change.is_none_or(|pct| pct < min_pct_change) {
+            return false;
+        }
+    }
+    if let Some(max_pct_change) = filter.max_pct_change {
+        if result.pct_change.is_none_or(|pct| pct > max_pct_change) {
+            return false;
+        }
+    }
+
+    if filter.recent_crossovers_only && result.crossovers.is_empty() {
+        return false;
+    }
+    if filter.has_note && result.note.is_none() {
+        return false;
+    }
+
+    // Apply opportunity state filters
+    if filter.opportunity_states.as_ref().is_some_and(|states| !states.contains(&result.opportunity_state)) {
+        return false;
+    }
+    if let Some(hours) = filter.opportunity_within_hours {
+        if result.opportunity_since.is_none_or(|since| result.timestamp - since > chrono::Duration::hours(i64::from(hours))) {
+            return false;
+        }
+    }
+
+    // Apply the history requirement; results without a bar count fail
+    if let Some(min_bars) = filter.min_history_bars {
+        if result.bars_used.is_none_or(|bars| bars < min_bars) {
+            return false;
+        }
+    }
+
+    // Apply custom indicator ranges; results without the indicator fail
+    for (name, &(min, max)) in &filter.custom_indicator_ranges {
+        let Some(&value) = result.custom.get(name) else { return false };
+        if min.is_some_and(|min| value < min) || max.is_some_and(|max| value > max) {
+            return false;
+        }
+    }
+
+    // Apply unusual volume filter
+    if let Some(min_ratio) = filter.min_volume_vs_avg {
+        if result.volume_vs_avg.is_none_or(|ratio| ratio < min_ratio) {
+            return false;
+        }
+    }
+
+    // Apply distance from the range high/low filters
+    if let Some(max_pct) = filter.max_pct_from_low {
+        if result.pct_from_low.is_none_or(|pct| pct > max_pct) {
+            return false;
+        }
+    }
+    if let Some(min_pct) = filter.min_pct_from_high {
+        if result.pct_from_high.is_none_or(|pct| pct < min_pct) {
+            return false;
+        }
+    }
+
+    // Apply relative strength rank filter; unranked results never pass
+    if let Some(min_rank) = filter.min_rs_rank {
+        if result.rs_rank.is_none_or(|rank| rank < min_rank) {
+            return false;
+        }
+    }
+
+    // Apply relative-to-sector RSI filter; unscored results never pass
+    if let Some(max_zscore) = filter.max_rsi_sector_zscore {
+        if result.rsi_sector_zscore.is_none_or(|zscore| zscore > max_zscore) {
+            return false;
+        }
+    }
+
+    // Apply trend strength filter
+    if let Some(min_adx) = filter.min_adx {
+        if result.adx.is_none_or(|adx| adx < min_adx) {
+            return false;
+        }
+    }
+
+    // Apply recent split filter
+    if let Some(days) = filter.exclude_split_within_days {
+        if result.last_split.is_some_and(|split| result.timestamp - split <= chrono::Duration::days(i64::from(days))) {
+            return false;
+        }
+    }
+
+    // Apply data quality filter; results fetched before it was tracked pass
+    if let Some(max_ratio) = filter.max_repaired_bar_ratio {
+        if result.data_quality.is_some_and(|quality| quality.repaired_ratio() > max_ratio) {
+            return false;
+        }
+    }
+
+    // Apply analyst target filter; stale targets are not evidence of upside
+    if let Some(min_upside) = filter.min_upside_to_target_pct {
+        if result
+            .upside_to_target_pct
+            .is_none_or(|upside| upside < min_upside || is_stale_upside(upside))
+        {
+            return false;
+        }
+    }
+    
+    true
+}
+
+/// Default pause between continuous analysis cycles (`AppConfig::cycle_interval_secs`)
+pub const CONTINUOUS_CYCLE_INTERVAL: Duration = Duration::from_secs(DEFAULT_CYCLE_INTERVAL_SECS);
+/// Pause before retrying after the ticker list could not be fetched
+pub const TICKER_RETRY_DELAY: Duration = Duration::from_secs(300);
+
 async fn run_continuous_analysis(state: AppState) {
     tracing::info!("🔄 Starting continuous stock analysis...");
     
-    let mut cycle = 0;
+    let mut control = state.continuous_control.subscribe();
+    // Carry the numbering on from earlier runs so a restart doesn't overwrite their sessions
+    let mut cycle = match state.database {
+        Some(ref db) => db.last_cycle_number().await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to load the last cycle number: {}", e);
+            0
+        }) as usize,
+        None => 0,
+    };
     loop {
+        hold_while_paused_or_stopped(&state, &mut control, ContinuousState::Running).await;
         cycle += 1;
         
         // Update status to running
         {
             let mut status = state.continuous_analysis_status.write().await;
+            status.state = ContinuousState::Running;
             status.is_running = true;
             status.current_cycle = cycle;
             status.progress = 0.0;
             status.analyzed_count = 0;
-            status.last_update = chrono::Utc::now();
+            status.last_update = state.clock.now();
             status.error_message = None;
+            status.next_run_at = None;
         }
         
-        let mut analyzer = StockAnalyzer::new_with_cache(state.cache.clone());
+        let analyzer = state.analyzer();
         
         // Fetch all tickers with caching
-        let all_tickers = match analyzer.fetch_all_tickers_cached().await {
-            Ok(tickers) => tickers,
+        let all_tickers = match state.fetch_tickers(&analyzer).await {
+            Ok((tickers, _)) => tickers,
             Err(e) => {
                 let mut status = state.continuous_analysis_status.write().await;
                 status.error_message = Some(format!("Failed to fetch tickers: {}", e));
                 status.is_running = false;
                 tracing::error!("❌ Failed to fetch tickers: {}", e);
+                drop(status);
                 
                 // Wait 5 minutes before retrying
-                tokio::time::sleep(Duration::from_secs(300)).await;
+                let retry_at = state.clock.now() + to_chrono(TICKER_RETRY_DELAY);
+                wait_for_next_cycle(&state, &mut control, retry_at, &[]).await;
                 continue;
             }
         };
+
+        // Watchlist symbols are analyzed first so they are never stale by an hour
+        let watchlist: Vec<String> = match state.database {
+            Some(ref db) => match db.get_watchlist(false).await {
+                Ok(watchlist) => watchlist.into_iter().map(|entry| entry.symbol).collect(),
+                Err(e) => {
+                    tracing::warn!("Failed to load watchlist: {}", e);
+                    Vec::new()
+                }
+            },
+            None => Vec::new(),
+        };
+        // Symbols listed since the last cycle are analyzed even if the filter would leave them out
+        let new_listings = state.ticker_directory.write().await.take_new_listings();
+        if !new_listings.is_empty() {
+            tracing::info!("🆕 Including {} newly listed symbols", new_listings.len());
+        }
+        let keep: Vec<String> = watchlist.iter().chain(&new_listings).cloned().collect();
+        let all_tickers = continuous_universe(all_tickers, state.config.continuous_filter.as_ref(), &keep);
+        refresh_benchmark(&state, &analyzer).await;
         
         {
             let mut status = state.continuous_analysis_status.write().await;
             status.total_count = all_tickers.len();
         }
         
-        let mut new_results = Vec::new();
-        let mut opportunities_found = 0;
-        
         // Analyze each ticker and update results immediately
-        let session_id = format!("continuous_cycle_{}", cycle);
+        let session_id = cycle_session_id(cycle as u64);
+        let cycle_record = AnalysisCycle {
+            cycle_number: cycle as u64,
+            session_id: session_id.clone(),
+            started_at: state.clock.now(),
+            finished_at: None,
+            tickers_analyzed: 0,
+            opportunities_found: 0,
+            error_count: 0,
+        };
+        record_cycle(&state, &cycle_record).await;
+        // Only the tail is kept for broadcasts; all_results and the database hold the rest
+        let mut tally = CycleTally {
+            pending: PendingResults::new(&cycle_record.session_id),
+            record: cycle_record,
+            recent: RecentResults::new(state.config.progress_results),
+            enricher: state.analyst_enricher(),
+            opportunities_found: 0,
+            done: 0,
+            total: all_tickers.len(),
+            rs_scores: Vec::new(),
+            cycle_results: Vec::new(),
+        };
         
-        for (i, ticker_info) in all_tickers.iter().enumerate() {
-            let ticker = &ticker_info.symbol;
+        // Last cycle's results, re-priced from batched quotes where their
+        // indicators are still current
+        let previous_results: HashMap<String, StockAnalysisResult> = state
+            .all_results
+            .read()
+            .await
+            .iter()
+            .map(|result| (result.ticker.clone(), result.clone()))
+            .collect();
+        let all_tickers = by_fetch_priority(all_tickers, &watchlist, &previous_results, state.clock.now());
+        // History fetched before this moment came from the cache; like the
+        // fetch timestamps, this is wall-clock time
+        let fetches_started_at = chrono::Utc::now();
+        let (mut history_fetches, mut cache_hits) = (0usize, 0usize);
+        let batch_size = state.config.quote_batch_size.max(1);
+        
+        // Re-priced tickers finish here; the rest go to the fetch workers,
+        // most urgent first, and report back on `outcomes`
+        let (reply, mut outcomes) = mpsc::unbounded_channel();
+        let mut pending = 0;
+        let mut stopped = false;
+        for chunk in all_tickers.chunks(batch_size) {
+            if hold_while_paused_or_stopped(&state, &mut control, ContinuousState::Running).await {
+                stopped = true;
+                break;
+            }
             
-            match analyzer.fetch_stock_data_cached(ticker).await {
-                Ok(stock_data) => {
-                    if !stock_data.is_empty() {
-                        let indicators = analyzer.calculate_indicators_cached(ticker, &stock_data).await;
-                        
-                        if let Some(latest_indicator) = indicators.last() {
-                            let current_price = stock_data.last().map(|quote| quote.close);
-                            let is_opportunity = latest_indicator.rsi.map_or(false, |rsi| {
-                                rsi <= 30.0 || rsi >= 70.0
-                            });
-                            
-                            let mut signals = Vec::new();
-                            if let Some(rsi) = latest_indicator.rsi {
-                                if rsi <= 30.0 {
-                                    signals.push("Oversold - Potential Buy".to_string());
-                                } else if rsi >= 70.0 {
-                                    signals.push("Overbought - Potential Sell".to_string());
-                                }
-                            }
-                            
-                            let (macd_value, macd_signal_value, macd_histogram_value) = 
-                                latest_indicator.macd.unwrap_or((0.0, 0.0, 0.0));
-                            
-                            let result = StockAnalysisResult {
-                                ticker: ticker.clone(),
-                                name: ticker_info.name.clone(),
-                                current_price,
-                                rsi: latest_indicator.rsi,
-                                sma_20: latest_indicator.sma_20,
-                                sma_50: latest_indicator.sma_50,
-                                macd: if latest_indicator.macd.is_some() { Some(macd_value) } else { None },
-                                macd_signal: if latest_indicator.macd.is_some() { Some(macd_signal_value) } else { None },
-                                macd_histogram: if latest_indicator.macd.is_some() { Some(macd_histogram_value) } else { None },
-                                volume: stock_data.last().map(|q| q.volume),
-                                pct_change: ticker_info.pct_change.as_ref().and_then(|s| {
-                                    s.replace('%', "").parse().ok()
-                                }),
-                                market_cap: ticker_info.market_cap.clone(),
-                                is_opportunity,
-                                signals,
-                                timestamp: chrono::Utc::now(),
-                            };
-                            
-                            // Add to local results
-                            new_results.push(result.clone());
-                            if is_opportunity {
-                                opportunities_found += 1;
-                            }
-                            
-                            // Store in database if available
-                            if let Some(ref db) = state.database {
-                                if let Err(e) = db.store_analysis_result(&result, &session_id).await {
-                                    tracing::warn!("Failed to store result in database: {}", e);
-                                }
-                            }
-                            
-                            // Immediately update global results with this stock
-                            {
-                                let mut all_results = state.all_results.write().await;
-                                // Remove any existing result for this ticker
-                                all_results.retain(|r| r.ticker != *ticker);
-                                // Add the new result
-                                all_results.push(result);
+            // Symbols that keep failing sit out a few cycles instead of
+            // spending a request (and the request delay) every time
+            let failed = state.failed_symbols.read().await;
+            let skipped: Vec<bool> = chunk
+                .iter()
+                .map(|(_, t)| failed.should_skip(&normalize_symbol(&t.symbol), cycle as u64))
+                .collect();
+            drop(failed);
+            
+            let quotes = match state.batch_quotes {
+                Some(_) => {
+                    let symbols: Vec<String> = chunk
+                        .iter()
+                        .zip(&skipped)
+                        .filter(|(_, skipped)| !**skipped)
+                        .map(|((_, t), _)| t.symbol.clone())
+                        .collect();
+                    analyzer.get_latest_quotes_batch(&symbols).await.unwrap_or_else(|e| {
+                        tracing::warn!("Batch quotes failed; fetching history for the next {} tickers: {}", symbols.len(), e);
+                        HashMap::new()
+                    })
+                }
+                None => HashMap::new(),
+            };
+            
+            for ((priority, ticker_info), skipped) in chunk.iter().zip(skipped) {
+                let ticker = &ticker_info.symbol;
+                let trace = TickerTrace::new(state.session_events.clone(), &session_id, ticker);
+                if skipped {
+                    trace.record(SessionEventKind::Skipped { reason: "sitting out repeated failures".to_string() });
+                    tally.finish(&state, None).await;
+                    continue;
+                }
+                match (quotes.get(ticker), previous_results.get(ticker)) {
+                    (Some(quote), Some(previous)) if !needs_history_refresh(previous, quote) => {
+                        trace.record(SessionEventKind::Requoted { price: quote.close });
+                        let result = requote_result(previous, ticker_info, quote, state.clock.now());
+                        tally.finish(&state, Some(result)).await;
+                    }
+                    _ => {
+                        let job = FetchJob::new(ticker_info.clone(), *priority, reply.clone()).for_cycle(cycle as u64);
+                        match state.scheduler.enqueue(job) {
+                            Enqueued::Queued => pending += 1,
+                            // Asked for explicitly; that request publishes the result
+                            Enqueued::AlreadyQueued => {
+                                trace.record(SessionEventKind::Skipped { reason: "already queued".to_string() });
+                                tally.finish(&state, None).await
                             }
                         }
                     }
                 }
-                Err(e) => {
-                    tracing::warn!("Failed to analyze {}: {}", ticker, e);
-                }
             }
-            
-            // Update progress every 5 stocks for more frequent updates
-            if (i + 1) % 5 == 0 || i + 1 == all_tickers.len() {
-                let mut status = state.continuous_analysis_status.write().await;
-                status.analyzed_count = i + 1;
-                status.progress = (i + 1) as f64 / all_tickers.len() as f64;
-                status.opportunities_found = opportunities_found;
-                status.last_update = chrono::Utc::now();
-                
-                // Broadcast update every 10 stocks for more frequent updates
-                if (i + 1) % 10 == 0 || i + 1 == all_tickers.len() {
-                    let _ = state.broadcast_tx.send(AnalysisStatus {
-                        session_id: "continuous".to_string(),
-                        status: "running".to_string(),
-                        progress: status.progress,
-                        analyzed_count: status.analyzed_count,
-                        total_count: status.total_count,
-                        opportunities_found: status.opportunities_found,
-                        error_message: None,
-                        results: new_results.clone(),
-                    });
+        }
+        drop(reply);
+        
+        let mut control_open = true;
+        while !stopped && pending > 0 {
+            tokio::select! {
+                biased;
+                outcome = outcomes.recv() => {
+                    let Some(outcome) = outcome else { break };
+                    pending -= 1;
+                    let result = match outcome.result {
+                        Ok(None) => {
+                            tally.record.error_count += 1;
+                            None
+                        }
+                        Ok(result) => {
+                            history_fetches += 1;
+                            if result.as_ref().and_then(|result| result.data_fetched_at).is_some_and(|at| at < fetches_started_at) {
+                                cache_hits += 1;
+                            }
+                            result
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to analyze {}: {}", outcome.ticker.symbol, e);
+                            state.record_symbol_failure(&normalize_symbol(&outcome.ticker.symbol), &e, cycle as u64).await;
+                            tally.record.error_count += 1;
+                            None
+                        }
+                    };
+                    tally.finish(&state, result).await;
+                }
+                changed = control.changed(), if control_open => {
+                    if changed.is_err() {
+                        control_open = false;
+                        continue;
+                    }
+                    if !matches!(*control.borrow(), ControlCommand::Pause | ControlCommand::Stop) {
+                        continue;
+                    }
+                    // Jobs a worker already started still report back
+                    let withdrawn = state.scheduler.withdraw_cycle(cycle as u64);
+                    if hold_while_paused_or_stopped(&state, &mut control, ContinuousState::Running).await {
+                        stopped = true;
+                        break;
+                    }
+                    for job in withdrawn {
+                        if state.scheduler.enqueue(job) == Enqueued::AlreadyQueued {
+                            pending -= 1;
+                            tally.finish(&state, None).await;
+                        }
+                    }
                 }
             }
-            
-            // Small delay to avoid overwhelming the API
-            tokio::time::sleep(Duration::from_millis(50)).await;
         }
+        if stopped {
+            tracing::info!("⏹️  Continuous analysis stopped during cycle {}", cycle);
+            state.scheduler.withdraw_cycle(cycle as u64);
+        }
+        let CycleTally { record: mut cycle_record, opportunities_found, rs_scores, cycle_results, mut pending, .. } = tally;
+        // Only a cycle that ran to the end is complete; ranking updates its stored results
+        match stopped {
+            true => pending.flush(&state).await,
+            false => pending.complete(&state).await,
+        }
+        
+        cycle_record.finished_at = Some(state.clock.now());
+        cycle_record.opportunities_found = opportunities_found as u64;
+        record_cycle(&state, &cycle_record).await;
+        state.flush_session_events().await;
+        state.end_notification_cycle();
+        // Keep the saved cache current so a restart resumes warm
+        if state.config.cache_file.is_some() {
+            let _ = state.persist_cache().await;
+        }
+        
+        if stopped {
+            // The top of the loop holds until the stop is lifted, then starts afresh
+            continue;
+        }
+        rank_cycle(&state, &cycle_record.session_id, &rs_scores).await;
+        score_cycle_groups(&state, &cycle_record.session_id, &cycle_results).await;
         
         // Mark cycle as complete
         {
             let mut status = state.continuous_analysis_status.write().await;
             status.is_running = false;
             status.progress = 1.0;
-            status.last_update = chrono::Utc::now();
+            status.last_update = state.clock.now();
+            status.cache_hit_rate_last_cycle = (history_fetches > 0).then(|| cache_hits as f64 / history_fetches as f64);
             
             tracing::info!("✅ Completed analysis cycle {} - {} opportunities found", cycle, opportunities_found);
         }
         
-        // Wait 1 hour before next cycle
-        tracing::info!("⏱️  Waiting 1 hour before next analysis cycle...");
-        tokio::time::sleep(Duration::from_secs(3600)).await;
+        let interval = state.config.cycle_interval();
+        let finished_at = state.clock.now();
+        let next_run = if state.config.ignore_market_hours {
+            finished_at + to_chrono(interval)
+        } else {
+            next_cycle_start(cycle_record.started_at, finished_at, interval)
+        };
+        tracing::info!(
+            "⏱️  Waiting {}s before next analysis cycle...",
+            (next_run - finished_at).num_seconds()
+        );
+        wait_for_next_cycle(&state, &mut control, next_run, &all_tickers).await;
+    }
+}
+
+/// Continuous-cycle bookkeeping as tickers finish, in whatever order the
+/// fetch workers complete them
+struct CycleTally {
+    record: AnalysisCycle,
+    recent: RecentResults,
+    enricher: Option<AnalystEnricher>,
+    opportunities_found: usize,
+    done: usize,
+    total: usize,
+    /// 3-month relative strength of each result, ranked once the cycle ends
+    rs_scores: Vec<(String, f64)>,
+    /// Every stored result, scored against its sector and industry once
+    /// the cycle ends
+    cycle_results: Vec<StockAnalysisResult>,
+    pending: PendingResults,
+}
+
+impl CycleTally {
+    /// Count one ticker of the cycle as done, publishing and storing its
+    /// result when there is one
+    async fn finish(&mut self, state: &AppState, result: Option<StockAnalysisResult>) {
+        let result = match result {
+            Some(result) => {
+                state.record_symbol_success(&normalize_symbol(&result.ticker)).await;
+                self.record.tickers_analyzed += 1;
+                state.screen_result(result, &self.pending.session_id).await
+            }
+            None => None,
+        };
+        if let Some(mut result) = result {
+            // The cycle covers the whole universe; only opportunities are worth a lookup
+            if result.is_opportunity {
+                if let Some(enricher) = self.enricher.as_mut() {
+                    enricher.enrich(&mut result).await;
+                }
+                self.opportunities_found += 1;
+            }
+            result.summary = Some(generate_summary(&result));
+            if let Some(ref db) = state.database {
+                result.note = db.get_active_note(&normalize_symbol(&result.ticker)).await.unwrap_or_else(|e| {
+                    tracing::warn!("Failed to load the note on {}: {}", result.ticker, e);
+                    None
+                });
+            }
+            if let Some(rs_3m) = result.rs_3m {
+                self.rs_scores.push((result.ticker.clone(), rs_3m));
+            }
+            state.track_opportunity(&mut result).await;
+            self.recent.push(result.clone());
+            self.cycle_results.push(result.clone());
+            // Only entering or leaving a run of flags is news
+            let notification = match result.opportunity_state {
+                OpportunityState::New => Some(Notification::opportunity(&result, state.clock.now())),
+                OpportunityState::Exited => Some(Notification::opportunity_exited(&result, state.clock.now())),
+                _ => None,
+            };
+            commit_result(state, result, &mut self.pending).await;
+            if let Some(notification) = notification {
+                state.notify(notification);
+            }
+        }
+        
+        // Update progress every 5 stocks for more frequent updates
+        self.done += 1;
+        let broadcast = self.done.is_multiple_of(state.config.broadcast_every.max(1)) || self.done == self.total;
+        if self.done.is_multiple_of(5) || broadcast {
+            let mut status = state.continuous_analysis_status.write().await;
+            status.analyzed_count = self.done;
+            status.progress = self.done as f64 / self.total.max(1) as f64;
+            status.opportunities_found = self.opportunities_found;
+            status.last_update = state.clock.now();
+            
+            if broadcast {
+                state.publish(status.to_broadcast(Some(&self.recent)));
+            }
+            drop(status);
+            state.flush_session_events().await;
+        }
+    }
+}
+
+/// Fetch the benchmark series the cycle measures relative strength
+/// against. The cache serves it within its TTL; a failed fetch keeps the
+/// previous series.
+async fn refresh_benchmark(state: &AppState, analyzer: &StockAnalyzer) {
+    let Some(ref symbol) = state.config.benchmark_symbol else {
+        return;
+    };
+    match analyzer.fetch_stock_data_cached(symbol).await {
+        Ok(bars) if !bars.is_empty() => *state.benchmark.write().await = Some(Arc::new(bars)),
+        Ok(_) => tracing::warn!("Benchmark {} returned no price history", symbol),
+        Err(e) => tracing::warn!("Failed to fetch benchmark {}: {}", symbol, e),
+    }
+}
+
+/// Rank the finished cycle's results by 3-month relative strength, update
+/// the current and stored results and broadcast the ranks
+async fn rank_cycle(state: &AppState, session_id: &str, scores: &[(String, f64)]) {
+    let ranks = percentile_ranks(scores);
+    if ranks.is_empty() {
+        return;
+    }
+    let by_ticker: HashMap<&str, f64> = ranks.iter().map(|rank| (rank.ticker.as_str(), rank.rs_rank)).collect();
+    for result in state.all_results.write().await.iter_mut() {
+        if let Some(rank) = by_ticker.get(result.ticker.as_str()) {
+            result.rs_rank = Some(*rank);
+        }
+    }
+    if let Some(ref db) = state.database {
+        if let Err(e) = db.update_rs_ranks(session_id, &ranks).await {
+            tracing::warn!("Failed to store relative strength ranks for {}: {}", session_id, e);
+        }
+    }
+    state.bump_results_version();
+    state.publish(BroadcastEvent::Ranked(RankedCycle { session_id: session_id.to_string(), ranks }));
+}
+
+/// Score the finished cycle's RSI against each result's sector and
+/// industry, update the current and stored results and store the groups'
+/// statistics
+async fn score_cycle_groups(state: &AppState, session_id: &str, results: &[StockAnalysisResult]) {
+    let stats = group_stats(results);
+    if stats.zscores.is_empty() {
+        return;
+    }
+    let by_ticker: HashMap<&str, &GroupZScores> = stats.zscores.iter().map(|zscores| (zscores.ticker.as_str(), zscores)).collect();
+    for result in state.all_results.write().await.iter_mut() {
+        if let Some(zscores) = by_ticker.get(result.ticker.as_str()) {
+            result.rsi_sector_zscore = zscores.rsi_sector_zscore;
+            result.rsi_industry_zscore = zscores.rsi_industry_zscore;
+        }
+    }
+    if let Some(ref db) = state.database {
+        if let Err(e) = db.save_group_stats(session_id, &stats).await {
+            tracing::warn!("Failed to store sector statistics for {}: {}", session_id, e);
+        }
+    }
+    state.bump_results_version();
+}
+
+/// The ticker's previous stored result: the database's latest when there
+/// is a database, otherwise the one in memory
+async fn previous_result(state: &AppState, ticker: &str) -> Option<StockAnalysisResult> {
+    if let Some(ref db) = state.database {
+        match db.get_latest_result(ticker).await {
+            Ok(result) => return result,
+            Err(e) => tracing::warn!("Failed to get the previous result for {}: {}", ticker, e),
+        }
+    }
+    state.all_results.read().await.iter().find(|r| r.ticker == ticker).cloned()
+}
+
+/// Broadcast what moved since the ticker's previous stored result, if
+/// anything meaningful did. Call before storing `result`.
+async fn publish_result_delta(state: &AppState, result: &StockAnalysisResult) {
+    let Some(previous) = previous_result(state, &result.ticker).await else { return };
+    if let Some(delta) = ResultDelta::between(&previous, result) {
+        state.publish(BroadcastEvent::ResultDelta(delta));
+    }
+}
+
+/// Results of one session waiting to be stored, written in batches
+/// rather than a transaction per ticker
+struct PendingResults {
+    session_id: String,
+    results: Vec<StockAnalysisResult>,
+    flush_every: usize,
+}
+
+impl PendingResults {
+    fn new(session_id: &str) -> Self {
+        Self { session_id: session_id.to_string(), results: Vec::new(), flush_every: RESULT_FLUSH_EVERY }
+    }
+
+    /// Store every `flush_every` results instead of `RESULT_FLUSH_EVERY`
+    fn with_flush_every(mut self, flush_every: usize) -> Self {
+        self.flush_every = flush_every.max(1);
+        self
+    }
+
+    /// Hold `result`, storing the held results once there are
+    /// `flush_every` of them
+    async fn push(&mut self, state: &AppState, result: StockAnalysisResult) {
+        if state.database.is_none() {
+            return;
+        }
+        self.results.push(result);
+        if self.results.len() >= self.flush_every {
+            self.flush(state).await;
+        }
+    }
+
+    /// Store the held results, if there is a database
+    async fn flush(&mut self, state: &AppState) {
+        let results = std::mem::take(&mut self.results);
+        let Some(ref db) = state.database else { return };
+        if results.is_empty() {
+            return;
+        }
+        if let Err(e) = db.store_analysis_results_batch(&results, &self.session_id).await {
+            tracing::warn!("Failed to store {} results in database: {}", results.len(), e);
+        }
+    }
+
+    /// Store the held results and mark the session complete
+    async fn complete(&mut self, state: &AppState) {
+        let results = std::mem::take(&mut self.results);
+        let Some(ref db) = state.database else { return };
+        if let Err(e) = db.complete_session_results(&results, &self.session_id, state.clock.now()).await {
+            tracing::warn!("Failed to complete the results of session {}: {}", self.session_id, e);
+        }
+    }
+}
+
+/// Publish a finished result, hold it for storing under the pending
+/// session, check alerts against it and make it the ticker's current
+/// result. Returns the ticker's previous result.
+async fn commit_result(state: &AppState, result: StockAnalysisResult, pending: &mut PendingResults) {
+    publish_result_delta(state, &result).await;
+    state.publish_result(&result);
+    pending.push(state, result.clone()).await;
+    
+    check_alerts(state, &result).await;
+    
+    let mut all_results = state.all_results.write().await;
+    all_results.retain(|r| r.ticker != result.ticker);
+    all_results.push(result);
+    drop(all_results);
+    state.bump_results_version();
+}
+
+/// `tickers` paired with their fetch priority, most urgent first; equal
+/// priorities keep their listing order
+fn by_fetch_priority(
+    tickers: Vec<TickerInfo>,
+    watchlist: &[String],
+    previous_results: &HashMap<String, StockAnalysisResult>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Vec<(FetchPriority, TickerInfo)> {
+    let mut prioritized: Vec<(FetchPriority, TickerInfo)> = tickers
+        .into_iter()
+        .map(|ticker| {
+            let on_watchlist = watchlist.contains(&normalize_symbol(&ticker.symbol));
+            let last_analyzed = previous_results.get(&ticker.symbol).map(|result| result.timestamp);
+            (FetchPriority::for_ticker(&ticker, on_watchlist, last_analyzed, now), ticker)
+        })
+        .collect();
+    prioritized.sort_by_key(|&(priority, _)| std::cmp::Reverse(priority));
+    prioritized
+}
+
+/// Run maintenance every `MAINTENANCE_INTERVAL`, counted from the last
+/// recorded run, so restarts neither skip nor repeat a day
+async fn run_maintenance_loop(state: AppState) {
+    let Some(ref db) = state.database else { return };
+    let interval = to_chrono(MAINTENANCE_INTERVAL);
+    let mut next_run = match db.last_maintenance().await {
+        Ok(Some(last)) => last.finished_at + interval,
+        Ok(None) => state.clock.now(),
+        Err(e) => {
+            tracing::warn!("Failed to read the last maintenance run: {}", e);
+            state.clock.now()
+        }
+    };
+    loop {
+        state.clock.sleep_until(next_run).await;
+        // Failures are logged by `run_maintenance`; try again tomorrow
+        let _ = state.run_maintenance().await;
+        next_run = state.clock.now() + interval;
+    }
+}
+
+/// Poll the watchlist's latest quotes every `AppConfig::quote_poll_interval`
+/// while the market trades (always, with `ignore_market_hours`). A pause or
+/// stop of the continuous loop holds the poller too; any new command cuts
+/// its wait short.
+async fn run_quote_poller(state: AppState) {
+    let mut control = state.continuous_control.subscribe();
+    loop {
+        while matches!(*control.borrow_and_update(), ControlCommand::Pause | ControlCommand::Stop) {
+            if control.changed().await.is_err() {
+                return;
+            }
+        }
+        let now = state.clock.now();
+        let next_poll = if state.config.ignore_market_hours || is_market_open(now) {
+            poll_watchlist_quotes(&state).await;
+            now + to_chrono(state.config.quote_poll_interval())
+        } else {
+            next_market_open(now)
+        };
+        tokio::select! {
+            _ = state.clock.sleep_until(next_poll) => {}
+            changed = control.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Fetch the latest quote of every watchlist symbol, through the shared
+/// rate limiter, and broadcast a `quote` for each one whose price moved
+/// since the last poll. Returns how many were broadcast.
+pub async fn poll_watchlist_quotes(state: &AppState) -> usize {
+    let Some(ref db) = state.database else { return 0 };
+    let symbols: Vec<String> = match db.get_watchlist(false).await {
+        Ok(watchlist) => watchlist.into_iter().map(|entry| entry.symbol).collect(),
+        Err(e) => {
+            tracing::warn!("Failed to load watchlist: {}", e);
+            return 0;
+        }
+    };
+    let analyzer = state.analyzer();
+    let quotes = match state.batch_quotes {
+        Some(_) => analyzer.get_latest_quotes_batch(&symbols).await.unwrap_or_else(|e| {
+            tracing::warn!("Watchlist quotes failed: {}", e);
+            HashMap::new()
+        }),
+        None => {
+            let mut quotes = HashMap::new();
+            for symbol in &symbols {
+                match analyzer.get_latest_quote(symbol).await {
+                    Ok(quote) => {
+                        quotes.insert(symbol.clone(), quote);
+                    }
+                    Err(e) => tracing::warn!("Failed to quote {}: {}", symbol, e),
+                }
+            }
+            quotes
+        }
+    };
+
+    let mut live_quotes = state.live_quotes.write().await;
+    live_quotes.retain(&symbols);
+    let moved: Vec<LiveQuote> = symbols
+        .iter()
+        .filter_map(|symbol| live_quotes.update(symbol, quotes.get(symbol)?))
+        .collect();
+    drop(live_quotes);
+    for quote in &moved {
+        state.publish(BroadcastEvent::Quote(quote.clone()));
+    }
+    moved.len()
+}
+
+/// Drain the fetch queue one job at a time. Requests go through the
+/// shared rate limiter; the request delay after each job keeps a pool of
+/// workers from hammering the upstream between limiter refills.
+async fn run_fetch_worker(state: AppState) {
+    let mut analyzer = state.analyzer();
+    let opportunity_rules = OpportunityRule::defaults();
+    loop {
+        let job = state.scheduler.next_job().await;
+        let benchmark = state.benchmark.read().await.clone();
+        // Explicit requests belong to no session and go untraced
+        let trace = job
+            .cycle
+            .map(|cycle| TickerTrace::new(state.session_events.clone(), &cycle_session_id(cycle), &job.ticker.symbol));
+        let started = std::time::Instant::now();
+        let result = analyze_history(
+            &mut analyzer,
+            &state,
+            &job.ticker,
+            benchmark.as_deref().map(Vec::as_slice),
+            &opportunity_rules,
+            trace.as_ref(),
+        )
+        .await;
+        if let Some(ref trace) = trace {
+            match result {
+                Ok(Some(ref result)) => trace.record(SessionEventKind::Analyzed {
+                    opportunity: result.is_opportunity,
+                    ms: started.elapsed().as_millis() as u64,
+                }),
+                // Too little history; already traced as an indicator warning
+                Ok(None) => {}
+                Err(ref e) => trace.record(SessionEventKind::from_error(e)),
+            }
+        }
+        job.complete(result.map_err(Arc::new));
+        state.clock.sleep(state.config.request_delay()).await;
+    }
+}
+
+/// Session id of a continuous cycle's results and events
+fn cycle_session_id(cycle: u64) -> String {
+    format!("continuous_cycle_{}", cycle)
+}
+
+/// Store the cycle's bookkeeping row, if there is a database
+async fn record_cycle(state: &AppState, cycle: &AnalysisCycle) {
+    if let Some(ref db) = state.database {
+        if let Err(e) = db.record_cycle(cycle).await {
+            tracing::warn!("Failed to record analysis cycle {}: {}", cycle.cycle_number, e);
+        }
+    }
+}
+
+/// Record what the continuous loop is doing and broadcast the change
+async fn set_continuous_state(state: &AppState, continuous: ContinuousState) {
+    let mut status = state.continuous_analysis_status.write().await;
+    if status.state == continuous {
+        return;
+    }
+    status.state = continuous;
+    if continuous == ContinuousState::Stopped {
+        status.is_running = false;
+    }
+    status.last_update = state.clock.now();
+    state.publish(status.to_broadcast(None));
+}
+
+/// Block while the latest command is a pause or a stop, then switch to
+/// `resume_as`. Returns whether a stop was seen so the caller can abandon
+/// its cycle.
+async fn hold_while_paused_or_stopped(
+    state: &AppState,
+    control: &mut watch::Receiver<ControlCommand>,
+    resume_as: ContinuousState,
+) -> bool {
+    let mut stopped = false;
+    loop {
+        let held = match *control.borrow_and_update() {
+            ControlCommand::Resume | ControlCommand::RunNow => break,
+            ControlCommand::Pause => ContinuousState::Paused,
+            ControlCommand::Stop => {
+                stopped = true;
+                ContinuousState::Stopped
+            }
+        };
+        set_continuous_state(state, held).await;
+        if control.changed().await.is_err() {
+            break;
+        }
+    }
+    if !stopped {
+        set_continuous_state(state, resume_as).await;
+    }
+    stopped
+}
+
+/// Sleep until `next_run` before the next cycle, warming the cache for
+/// `tickers` meanwhile. A run-now or stop ends the wait early; a pause
+/// suspends it, warming included, until resumed.
+async fn wait_for_next_cycle(
+    state: &AppState,
+    control: &mut watch::Receiver<ControlCommand>,
+    next_run: chrono::DateTime<chrono::Utc>,
+    tickers: &[(FetchPriority, TickerInfo)],
+) {
+    {
+        let mut status = state.continuous_analysis_status.write().await;
+        status.next_run_at = Some(next_run);
+        status.symbols_warmed = 0;
+    }
+    set_continuous_state(state, ContinuousState::IdleWaiting).await;
+    let sleep = state.clock.sleep_until(next_run);
+    tokio::pin!(sleep);
+    // Dropped, cancelling any fetch in flight, as soon as the wait ends
+    let warm = warm_cache(state, tickers, next_run);
+    tokio::pin!(warm);
+    let mut warmed = false;
+    loop {
+        tokio::select! {
+            biased;
+            _ = &mut sleep => return,
+            changed = control.changed() => {
+                if changed.is_err() {
+                    // Nobody can steer the loop any more; just finish the wait
+                    sleep.await;
+                    return;
+                }
+                let command = *control.borrow_and_update();
+                match command {
+                    ControlCommand::Resume => {}
+                    ControlCommand::RunNow | ControlCommand::Stop => return,
+                    ControlCommand::Pause => {
+                        if hold_while_paused_or_stopped(state, control, ContinuousState::IdleWaiting).await {
+                            return;
+                        }
+                    }
+                }
+            }
+            _ = &mut warm, if !warmed => warmed = true,
+        }
+    }
+}
+
+/// Refetch, one ticker per `AppConfig::cache_warm_interval`, the history of
+/// `tickers` whose cached bars would expire before the next cycle reaches
+/// them, so that cycle is served from the cache. Warming starts once a
+/// fetch would still be fresh a quarter TTL past `next_run`, goes in the
+/// cycle's fetch order, and holds off while anything else wants the
+/// upstream; a manual session starting cancels the fetch in flight.
+async fn warm_cache(state: &AppState, tickers: &[(FetchPriority, TickerInfo)], next_run: chrono::DateTime<chrono::Utc>) {
+    let Some(interval) = state.config.cache_warm_interval() else {
+        return;
+    };
+    if tickers.is_empty() {
+        return;
+    }
+    let ttl = to_chrono(state.cache.ttls().stock_data());
+    let fresh_through = next_run + ttl / 4;
+    state.clock.sleep_until(fresh_through - ttl).await;
+
+    let analyzer = state.analyzer();
+    for (_, ticker) in tickers {
+        let cache_key = HistoryRange::default().cache_key(&ticker.symbol);
+        loop {
+            if state.cache.quote_series_fresh_until(&cache_key).await.is_some_and(|until| until >= fresh_through) {
+                break;
+            }
+            // Enabled before the check below so a session starting after it still wakes us
+            let session_started = state.session_started.notified();
+            tokio::pin!(session_started);
+            session_started.as_mut().enable();
+            if warming_should_yield(state).await {
+                state.clock.sleep(interval).await;
+                continue;
+            }
+            tokio::select! {
+                biased;
+                // Try this ticker again once the session is done
+                _ = &mut session_started => continue,
+                refreshed = analyzer.refresh_quote_series_cached(&ticker.symbol) => match refreshed {
+                    Ok(()) => state.continuous_analysis_status.write().await.symbols_warmed += 1,
+                    Err(e) => tracing::debug!("Failed to warm the cache for {}: {}", ticker.symbol, e),
+                },
+            }
+            state.clock.sleep(interval).await;
+            break;
+        }
+    }
+    tracing::debug!("Cache warm for the next cycle");
+}
+
+/// Whether cache warming should hold off: a manual session is running,
+/// explicit requests are waiting for a fetch worker, or the limiter is
+/// still backing off after the upstream throttled us
+async fn warming_should_yield(state: &AppState) -> bool {
+    let limiter = state.cache.request_limiter().stats();
+    limiter.effective_rate < limiter.max_rate
+        || !state.scheduler.is_empty()
+        || state.sessions.read().await.values().any(|status| status.status == "running")
+}
+
+/// Analyze one ticker of the continuous cycle from its (cached) price
+/// history. Errors are fetch failures, which count towards skipping the
+/// symbol in later cycles.
+async fn analyze_history(
+    analyzer: &mut StockAnalyzer,
+    state: &AppState,
+    ticker_info: &TickerInfo,
+    benchmark: Option<&[StockData]>,
+    opportunity_rules: &[OpportunityRule],
+    trace: Option<&TickerTrace>,
+) -> Result<Option<StockAnalysisResult>, AnalyzerError> {
+    let ticker = &ticker_info.symbol;
+    let crossover_lookback = state.config.crossover_lookback_bars;
+    if let Some(trace) = trace {
+        trace.record(SessionEventKind::Started);
+    }
+    let started = std::time::Instant::now();
+    let started_at = chrono::Utc::now();
+    let series = analyzer.fetch_quote_series_cached(ticker).await?;
+    if let Some(trace) = trace {
+        trace_fetch(trace, &series, started, started_at);
+    }
+    let stock_data = &series.bars;
+    if stock_data.is_empty() {
+        return Err(AnalyzerError::NoData(format!("Empty price history for {}", ticker)));
+    }
+
+    // Only bars after the last processed one are computed, and the
+    // indicators cover just the latest bars, as many as the signals need
+    let min_outputs = (crossover_lookback + 1).max(OBV_DIVERGENCE_WINDOW);
+    let indicators = analyzer.calculate_latest_indicators(ticker, stock_data, min_outputs).await;
+    if let Some(trace) = trace {
+        trace_indicator_warnings(trace, &series, &indicators);
+    }
+    let latest_bars = &stock_data[stock_data.len() - indicators.len()..];
+    if let Some(ref database) = state.database {
+        store_indicator_history(database, ticker, latest_bars, &indicators).await;
+    }
+    let Some(latest_indicator) = indicators.last() else {
+        return Ok(None);
+    };
+    let current_price = stock_data.last().map(|quote| quote.close);
+    let history = &state.config.min_history;
+    let inputs = RuleInputs::new(stock_data.last(), latest_indicator, ticker_info.pct_change_f64)
+        .with_history(latest_indicator.bars_used, history);
+    let matched_rules = matching_rules(opportunity_rules, &inputs);
+    let is_opportunity = !matched_rules.is_empty();
+    
+    let mut signals = Vec::new();
+    if let Some(rsi) = inputs.rsi {
+        if rsi <= 30.0 {
+            signals.push("Oversold - Potential Buy".to_string());
+        } else if rsi >= 70.0 {
+            signals.push("Overbought - Potential Sell".to_string());
+        }
+    }
+    signals.extend(history.shortfall_signal(latest_indicator.bars_used));
+    signals.extend(matched_rules.into_iter().map(rule_signal));
+    signals.extend(StockAnalyzer::indicator_signals(latest_bars, &indicators));
+    let crossovers = history.supported_crossovers(
+        recent_crossovers(latest_bars, &indicators, crossover_lookback),
+        latest_bars,
+        &indicators,
+    );
+    signals.extend(crossovers.iter().map(CrossoverEvent::signal));
+    if let Some(last) = stock_data.last() {
+        signals.extend(recent_event_signals(&series.events, last.timestamp));
+    }
+    
+    let (macd_value, macd_signal_value, macd_histogram_value) = 
+        latest_indicator.macd.unwrap_or((0.0, 0.0, 0.0));
+    let relative_strength = benchmark
+        .map(|benchmark| RelativeStrength::compute(stock_data, benchmark))
+        .unwrap_or_default();
+    
+    Ok(Some(StockAnalysisResult {
+        ticker: ticker.clone(),
+        name: ticker_info.name.clone(),
+        current_price,
+        rsi: latest_indicator.rsi,
+        sma_20: latest_indicator.sma_20,
+        sma_50: latest_indicator.sma_50,
+        macd: if latest_indicator.macd.is_some() { Some(macd_value) } else { None },
+        macd_signal: if latest_indicator.macd.is_some() { Some(macd_signal_value) } else { None },
+        macd_histogram: if latest_indicator.macd.is_some() { Some(macd_histogram_value) } else { None },
+        volume: stock_data.last().map(|q| q.volume),
+        pct_change: ticker_info.pct_change_f64,
+        market_cap: ticker_info.market_cap.clone(),
+        sector: ticker_info.sector.clone(),
+        industry: ticker_info.industry.clone(),
+        exchange: ticker_info.exchange.clone(),
+        is_opportunity,
+        signals,
+        timestamp: state.clock.now(),
+        data_source: Some(series.source.clone()),
+        data_fetched_at: Some(series.fetched_at),
+        summary: None,
+        analyst_target: None,
+        upside_to_target_pct: None,
+        obv: latest_indicator.obv,
+        vwap: latest_indicator.vwap,
+        volume_vs_avg: latest_indicator.volume_vs_avg,
+        high_52w: latest_indicator.high_52w,
+        low_52w: latest_indicator.low_52w,
+        pct_from_high: latest_indicator.pct_from_high,
+        pct_from_low: latest_indicator.pct_from_low,
+        range_window_complete: latest_indicator.window_complete,
+        rs_1w: relative_strength.rs_1w,
+        rs_1m: relative_strength.rs_1m,
+        rs_3m: relative_strength.rs_3m,
+        // Ranked against the rest of the cycle once it finishes
+        rs_rank: None,
+        moving_averages: latest_indicator.moving_averages.clone(),
+        crossovers,
+        data_quality: series.quality,
+        adx: latest_indicator.adx.map(|adx| adx.adx),
+        plus_di: latest_indicator.adx.map(|adx| adx.plus_di),
+        minus_di: latest_indicator.adx.map(|adx| adx.minus_di),
+        last_split: last_split(&series.events),
+        timeframe: Timeframe::Daily,
+        anomalies: Vec::new(),
+        rsi_oversold_used: None,
+        rsi_overbought_used: None,
+        rsi_sector_zscore: None,
+        rsi_industry_zscore: None,
+        note: None,
+        opportunity_state: OpportunityState::None,
+        opportunity_streak: 0,
+        opportunity_since: None,
+        custom: latest_indicator.custom.clone(),
+        bars_used: Some(latest_indicator.bars_used),
+    }))
+}
+
+/// Whether `quote` falls on a later day than the history behind `previous`
+/// was fetched, so a new daily bar exists and the indicators must be
+/// recomputed. Within the same day only the price and volume move.
+pub fn needs_history_refresh(previous: &StockAnalysisResult, quote: &StockData) -> bool {
+    previous
+        .data_fetched_at
+        .is_none_or(|fetched_at| quote.timestamp.date_naive() > fetched_at.date_naive())
+}
+
+/// `previous` re-priced from a batched quote. The indicators, signals and
+/// history provenance are kept; the analyst target is dropped because its
+/// upside depends on the price. The range high and low take in the quote's
+/// own high and low, and the distances from them follow the new price.
+pub fn requote_result(
+    previous: &StockAnalysisResult,
+    ticker_info: &TickerInfo,
+    quote: &StockData,
+    now: chrono::DateTime<chrono::Utc>,
+) -> StockAnalysisResult {
+    let high_52w = previous.high_52w.map(|high| high.max(quote.high));
+    let low_52w = previous.low_52w.map(|low| low.min(quote.low));
+    StockAnalysisResult {
+        name: ticker_info.name.clone(),
+        current_price: Some(quote.close),
+        volume: Some(quote.volume),
+        pct_change: ticker_info.pct_change_f64,
+        market_cap: ticker_info.market_cap.clone(),
+        sector: ticker_info.sector.clone(),
+        industry: ticker_info.industry.clone(),
+        exchange: ticker_info.exchange.clone(),
+        timestamp: now,
+        summary: None,
+        analyst_target: None,
+        upside_to_target_pct: None,
+        high_52w,
+        low_52w,
+        pct_from_high: high_52w.filter(|high| *high > 0.0).map(|high| (high - quote.close) / high * 100.0),
+        pct_from_low: low_52w.filter(|low| *low > 0.0).map(|low| (quote.close - low) / low * 100.0),
+        ..previous.clone()
     }
 }
 
 pub async fn start_server() -> Result<(), Box<dyn std::error::Error>> {
-    let app = create_router().await;
+    serve(AppConfig::load()?).await
+}
+
+/// Run the API server with `config` until it fails
+pub async fn serve(config: AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let address = config.socket_address();
+    let app = create_router(config).await?;
     
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:3001").await?;
-    println!("🚀 API Server running on http://127.0.0.1:3001");
+    let listener = tokio::net::TcpListener::bind(&address).await?;
+    println!("🚀 API Server running on http://{}", address);
     println!("📊 Dashboard available at http://127.0.0.1:3000");
     
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
     Ok(())
 }
@@ -11,6 +11,7 @@ fn test_stock_data_creation() {
         low: 149.0,
         close: 154.0,
         volume: 1000000,
+        adjusted_close: None,
     };
 
     assert_eq!(stock_data.symbol, "AAPL");
@@ -28,6 +29,7 @@ fn test_technical_indicators_creation() {
         sma_50: Some(150.0),
         rsi: Some(65.0),
         macd: Some((1.2, 1.0, 0.2)),
+        ..Default::default()
     };
 
     assert_eq!(indicators.sma_20, Some(152.0));
@@ -37,6 +39,7 @@ fn test_technical_indicators_creation() {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn test_stock_analyzer_creation() {
     let _analyzer = StockAnalyzer::new();
     // Test that analyzer can be created without panicking
@@ -44,6 +47,7 @@ fn test_stock_analyzer_creation() {
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn test_stock_analyzer_with_cache_creation() {
     use auto_analyser::cache::CacheManager;
     
@@ -71,6 +75,7 @@ async fn test_technical_indicators_calculation() {
             low: 95.0 + i as f64,
             close: 102.0 + i as f64,
             volume: 1000000,
+            adjusted_close: None,
         });
     }
     
@@ -87,6 +92,49 @@ async fn test_technical_indicators_calculation() {
     assert!(last_indicator.macd.is_some());
 }
 
+#[test]
+fn test_moving_averages_wait_for_full_window() {
+    let data = series("WARM", 60);
+    let indicators = StockAnalyzer::new().calculate_indicators("WARM", &data);
+
+    assert_eq!(indicators[18].sma_20, None);
+    let expected: f64 = data[..20].iter().map(|bar| bar.close).sum::<f64>() / 20.0;
+    assert!((indicators[19].sma_20.unwrap() - expected).abs() < 1e-9);
+    assert!(indicators[48].sma_50.is_none() && indicators[49].sma_50.is_some());
+    // MACD(12,26,9): the signal line needs 26 + 9 - 1 closes
+    assert!(indicators[32].macd.is_none() && indicators[33].macd.is_some());
+
+    // A short history reports no averages rather than partial ones
+    let short = StockAnalyzer::new().calculate_indicators("WARM", &data[..10]);
+    assert!(short.iter().all(|ind| ind.sma_20.is_none() && ind.sma_50.is_none() && ind.macd.is_none()));
+}
+
+#[test]
+fn test_weekly_indicators_run_on_resampled_bars() {
+    use auto_analyser::compute_indicators;
+    use auto_analyser::resample::{resample, Timeframe};
+    use chrono::Datelike;
+
+    let data = series("WEEK", 400);
+    let mut analyzer = StockAnalyzer::new();
+    let (weekly, indicators) = analyzer.calculate_timeframe_indicators("WEEK", &data, Timeframe::Weekly, None);
+
+    assert_eq!(weekly.len(), resample(&data, Timeframe::Weekly).len());
+    assert!(weekly.len() < data.len() / 5 + 2);
+    assert_eq!(indicators.len(), weekly.len());
+    assert_indicators_match(&indicators, &compute_indicators(&weekly));
+    assert!(indicators.last().unwrap().rsi.is_some());
+
+    // Seen from its Friday, the last week is still trading and left out when asked
+    let last = data.last().unwrap().timestamp.date_naive();
+    let today = chrono::NaiveDate::from_isoywd_opt(last.iso_week().year(), last.iso_week().week(), chrono::Weekday::Fri).unwrap();
+    let (complete, _) = analyzer.calculate_timeframe_indicators("WEEK", &data, Timeframe::Weekly, Some(today));
+    assert_eq!(complete.len(), weekly.len() - 1);
+
+    let (daily, _) = analyzer.calculate_timeframe_indicators("WEEK", &data, Timeframe::Daily, None);
+    assert_eq!(daily.len(), data.len());
+}
+
 #[tokio::test]
 async fn test_cache_functionality() {
     use auto_analyser::cache::CacheManager;
@@ -102,6 +150,7 @@ async fn test_cache_functionality() {
         low: 95.0,
         close: 102.0,
         volume: 1000000,
+        adjusted_close: None,
     }];
     
     let cache_key = "test_stock_data".to_string();
@@ -144,6 +193,7 @@ fn test_analyze_signals() {
         low: 95.0,
         close: 102.0,
         volume: 1000000,
+        adjusted_close: None,
     };
     
     // Test oversold condition
@@ -152,9 +202,11 @@ fn test_analyze_signals() {
         sma_50: Some(98.0),
         rsi: Some(25.0), // Oversold
         macd: Some((0.5, 0.3, 0.2)),
+        bars_used: 200,
+        ..Default::default()
     };
     
-    let signals = analyzer.analyze_signals(&stock_data, &oversold_indicators);
+    let signals = analyzer.analyze_signals(std::slice::from_ref(&stock_data), &[oversold_indicators]);
     assert!(signals.iter().any(|s| s.contains("Oversold")));
     
     // Test overbought condition
@@ -163,9 +215,11 @@ fn test_analyze_signals() {
         sma_50: Some(98.0),
         rsi: Some(75.0), // Overbought
         macd: Some((0.5, 0.3, 0.2)),
+        bars_used: 200,
+        ..Default::default()
     };
     
-    let signals = analyzer.analyze_signals(&stock_data, &overbought_indicators);
+    let signals = analyzer.analyze_signals(std::slice::from_ref(&stock_data), &[overbought_indicators]);
     assert!(signals.iter().any(|s| s.contains("Overbought")));
 }
 
@@ -189,33 +243,1391 @@ async fn test_rate_limiting() {
     assert!(!cache.should_rate_limit("test_symbol", Duration::from_millis(100)));
 }
 
+#[tokio::test]
+async fn test_cache_freshness_follows_clock() {
+    use auto_analyser::cache::CacheManager;
+    use auto_analyser::clock::TestClock;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let clock = Arc::new(TestClock::new(Utc::now()));
+    let cache = CacheManager::with_clock(clock.clone());
+
+    cache.cache_tickers("all_tickers".to_string(), vec![]).await;
+    cache.cache_indicators("AAPL".to_string(), vec![]).await;
+    assert!(!cache.should_rate_limit("AAPL", Duration::from_secs(10)));
+
+    clock.advance(Duration::from_secs(299));
+    assert!(cache.get_indicators("AAPL").await.is_some());
+    assert!(!cache.should_rate_limit("AAPL", Duration::from_secs(10)));
+
+    clock.advance(Duration::from_secs(1));
+    assert!(cache.get_indicators("AAPL").await.is_none(), "indicators expire after 5 minutes");
+    assert!(cache.get_tickers("all_tickers").await.is_some());
+    assert!(cache.should_rate_limit("AAPL", Duration::from_secs(10)));
+
+    clock.advance(Duration::from_secs(3300));
+    assert!(cache.get_tickers("all_tickers").await.is_none(), "tickers expire after an hour");
+}
+
+#[tokio::test]
+async fn test_expired_tickers_are_served_stale_while_one_refresh_runs() {
+    use auto_analyser::cache::{CacheManager, Freshness, ALL_TICKERS_KEY};
+    use auto_analyser::clock::TestClock;
+    use auto_analyser::TickerInfo;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::Semaphore;
+
+    let clock = Arc::new(TestClock::new(Utc::now()));
+    let cache = CacheManager::with_clock(clock.clone());
+    let calls = Arc::new(AtomicUsize::new(0));
+    // Each refresh lists one ticker and waits for a permit before finishing
+    let gate = Arc::new(Semaphore::new(0));
+    let get = |symbol: &'static str| {
+        let (cache, calls, gate) = (cache.clone(), calls.clone(), gate.clone());
+        async move {
+            cache
+                .get_or_refresh_tickers(ALL_TICKERS_KEY, move || async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    gate.acquire().await.unwrap().forget();
+                    Ok(vec![TickerInfo::unlisted(symbol)])
+                })
+                .await
+                .map(|(tickers, _)| tickers[0].symbol.clone())
+        }
+    };
+    let wait_for_refresh = || async {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while cache.get_cache_stats().await.tickers_refreshing {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("the refresh never finished");
+    };
+
+    // Nothing cached: every caller waits on the same fetch
+    let waiting = tokio::spawn(futures::future::join_all((0..5).map(|_| get("OLD"))));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    gate.add_permits(1);
+    let listed = waiting.await.unwrap();
+    assert!(listed.iter().all(|symbol| symbol.as_deref().unwrap() == "OLD"));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    wait_for_refresh().await;
+    let stats = cache.get_cache_stats().await;
+    assert_eq!(stats.tickers_freshness, Freshness::Fresh);
+    assert!(stats.last_tickers_refresh_ms.is_some());
+
+    // Expired during a cycle: callers get the stale list at once while one refresh runs
+    clock.advance(Duration::from_secs(3601));
+    let stale = futures::future::join_all((0..5).map(|_| get("NEW"))).await;
+    assert!(stale.iter().all(|symbol| symbol.as_deref().unwrap() == "OLD"));
+    let stats = cache.get_cache_stats().await;
+    assert_eq!(stats.tickers_freshness, Freshness::Stale);
+    assert!(stats.tickers_refreshing);
+    gate.add_permits(1);
+    wait_for_refresh().await;
+    assert_eq!(get("UNUSED").await.unwrap(), "NEW");
+    assert_eq!(cache.tickers_freshness(ALL_TICKERS_KEY).await, Freshness::Fresh);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // Past max_stale: callers wait for the refresh again, still sharing one
+    clock.advance(Duration::from_secs(3600 + 6 * 3600));
+    assert_eq!(cache.tickers_freshness(ALL_TICKERS_KEY).await, Freshness::Missing);
+    let waiting = tokio::spawn(futures::future::join_all((0..5).map(|_| get("NEWEST"))));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    gate.add_permits(1);
+    let listed = waiting.await.unwrap();
+    assert!(listed.iter().all(|symbol| symbol.as_deref().unwrap() == "NEWEST"));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_cache_persistence_skips_expired_entries() {
+    use auto_analyser::cache::CacheManager;
+    use auto_analyser::clock::{Clock, TestClock};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cache.json");
+    let clock = Arc::new(TestClock::new(Utc::now()));
+    let cache = CacheManager::with_clock(clock.clone());
+    let bar = StockData {
+        symbol: "AAPL".to_string(),
+        timestamp: clock.now(),
+        open: 100.0,
+        high: 102.0,
+        low: 99.0,
+        close: 101.0,
+        volume: 1_000,
+        adjusted_close: None,
+    };
+
+    assert_eq!(cache.load_from_disk(&path).await.unwrap(), 0, "a missing file loads nothing");
+    cache.cache_tickers("all_tickers".to_string(), vec![]).await;
+    cache.cache_stock_data("AAPL".to_string(), vec![bar]).await;
+    assert!(cache.get_cache_stats().await.last_persisted_at.is_none());
+    assert_eq!(cache.persist_to_disk(&path).await.unwrap(), 2);
+    assert_eq!(cache.get_cache_stats().await.last_persisted_at, Some(clock.now()));
+
+    // Restarted within both TTLs: everything comes back with its original age
+    clock.advance(Duration::from_secs(240));
+    let restarted = CacheManager::with_clock(clock.clone());
+    assert_eq!(restarted.load_from_disk(&path).await.unwrap(), 2);
+    assert_eq!(restarted.get_stock_data("AAPL").await.unwrap()[0].close, 101.0);
+    clock.advance(Duration::from_secs(60));
+    assert!(restarted.get_stock_data("AAPL").await.is_none(), "loaded entries keep their original age");
+
+    // Restarted after the price TTL ran out: only the ticker list is loaded
+    let restarted = CacheManager::with_clock(clock.clone());
+    assert_eq!(restarted.load_from_disk(&path).await.unwrap(), 1);
+    assert!(restarted.get_tickers("all_tickers").await.is_some());
+    assert!(restarted.get_stock_data("AAPL").await.is_none());
+
+    clock.advance(Duration::from_secs(3300));
+    assert_eq!(CacheManager::with_clock(clock.clone()).load_from_disk(&path).await.unwrap(), 0);
+}
+
 #[test]
 fn test_market_cap_parsing() {
-    use auto_analyser::analyzer::StockAnalyzer;
+    use auto_analyser::parsing::parse_market_cap;
     
     // Test billion parsing
-    assert_eq!(StockAnalyzer::parse_market_cap("$1.5B").unwrap(), 1_500_000_000.0);
+    assert_eq!(parse_market_cap("$1.5B").unwrap(), 1_500_000_000.0);
     
     // Test million parsing
-    assert_eq!(StockAnalyzer::parse_market_cap("$500M").unwrap(), 500_000_000.0);
+    assert_eq!(parse_market_cap("$500M").unwrap(), 500_000_000.0);
     
     // Test thousand parsing
-    assert_eq!(StockAnalyzer::parse_market_cap("$1.2K").unwrap(), 1_200.0);
+    assert_eq!(parse_market_cap("$1.2K").unwrap(), 1_200.0);
     
     // Test direct number
-    assert_eq!(StockAnalyzer::parse_market_cap("$1000").unwrap(), 1000.0);
+    assert_eq!(parse_market_cap("$1000").unwrap(), 1000.0);
+
+    // Thousands separators alongside a unit suffix
+    assert_eq!(parse_market_cap("$1,234.5M").unwrap(), 1_234_500_000.0);
+
+    // Placeholders don't parse
+    assert!(parse_market_cap("N/A").is_err());
+    assert!(parse_market_cap("").is_err());
 }
 
 #[test]
 fn test_percentage_parsing() {
-    use auto_analyser::analyzer::StockAnalyzer;
+    use auto_analyser::parsing::parse_percentage;
     
     // Test positive percentage
-    assert_eq!(StockAnalyzer::parse_percentage("5.5%").unwrap(), 5.5);
+    assert_eq!(parse_percentage("5.5%").unwrap(), 5.5);
     
     // Test negative percentage
-    assert_eq!(StockAnalyzer::parse_percentage("-2.3%").unwrap(), -2.3);
+    assert_eq!(parse_percentage("-2.3%").unwrap(), -2.3);
     
     // Test zero percentage
-    assert_eq!(StockAnalyzer::parse_percentage("0.0%").unwrap(), 0.0);
-}
\ No newline at end of file
+    assert_eq!(parse_percentage("0.0%").unwrap(), 0.0);
+
+    assert_eq!(parse_percentage("-2.5%").unwrap(), -2.5);
+
+    // Nasdaq reports no change as "unch"
+    assert_eq!(parse_percentage("unch").unwrap(), 0.0);
+    assert_eq!(parse_percentage("UNCH").unwrap(), 0.0);
+}
+
+#[test]
+fn test_filter_tickers_with_placeholder_fields() {
+    use auto_analyser::StockFilter;
+
+    let with_change = |symbol: &str, market_cap: &str, pct_change: &str| auto_analyser::TickerInfo {
+        pct_change: Some(pct_change.to_string()),
+        volume: Some("1,000,000".to_string()),
+        ..ticker(symbol, "$20.00", market_cap, "Technology")
+    }
+    .with_parsed_fields();
+    let tickers = vec![
+        with_change("FULL", "$1,234.5M", "-2.5%"),
+        with_change("NA", "N/A", "unch"),
+        with_change("BLANK", "", "1.0%"),
+        auto_analyser::TickerInfo { volume: None, ..with_change("NOVOL", "$2.0B", "0.5%") }.with_parsed_fields(),
+    ];
+    let symbols = |filter: &StockFilter| -> Vec<String> {
+        StockAnalyzer::filter_tickers(&tickers, filter).into_iter().map(|t| t.symbol).collect()
+    };
+
+    // Missing fields are ignored when nothing filters on them
+    assert_eq!(symbols(&StockFilter::new()), ["FULL", "NA", "BLANK", "NOVOL"]);
+
+    // ... and fail a filter that is set on them
+    let cap = StockFilter::new().with_market_cap_range(Some(1_000_000_000.0), None);
+    assert_eq!(symbols(&cap), ["FULL", "NOVOL"]);
+    let cap = StockFilter::new().with_market_cap_range(None, Some(5_000_000_000.0));
+    assert_eq!(symbols(&cap), ["FULL", "NOVOL"]);
+
+    // "unch" counts as a 0% move, "-2.5%" as a loss
+    let flat = StockFilter::new().with_pct_change_range(Some(-1.0), Some(0.75));
+    assert_eq!(symbols(&flat), ["NA", "NOVOL"]);
+    let falling = StockFilter::new().with_pct_change_range(None, Some(-2.0));
+    assert_eq!(symbols(&falling), ["FULL"]);
+
+    // Strict mode drops anything with a gap, filtered on or not
+    let strict = StockFilter::new().with_require_complete_data(true);
+    assert_eq!(symbols(&strict), ["FULL"]);
+}
+
+#[test]
+fn test_filter_tickers_by_normalized_country_and_adrs() {
+    use auto_analyser::region::Region;
+    use auto_analyser::StockFilter;
+
+    let listed = |symbol: &str, name: &str, country: &str| auto_analyser::TickerInfo {
+        name: name.to_string(),
+        country: Some(country.to_string()),
+        ..ticker(symbol, "$20.00", "$1.0B", "Technology")
+    }
+    .with_parsed_fields();
+    let tickers = vec![
+        listed("AAPL", "Apple Inc. Common Stock", "United States"),
+        listed("MSFT", "Microsoft Corporation Common Stock", "USA"),
+        listed("BABA", "Alibaba Group Holding Limited American Depositary Shares", "Cayman Islands"),
+        listed("BIDU", "Baidu Holdings Ltd", "Cayman Islands"),
+        listed("NIO", "NIO Inc. American depositary shares", "China"),
+        listed("SAP", "SAP SE ADS", "Germany"),
+        listed("TCEHY", "Tencent Holdings", ""),
+        listed("SHOP", "Shopify Inc. Class A", "Canada"),
+    ];
+
+    // The raw spelling is kept next to the normalized one
+    assert_eq!(tickers[1].country.as_deref(), Some("USA"));
+    assert_eq!(tickers[1].country_normalized.as_deref(), Some("United States"));
+    assert_eq!(tickers[1].region, Some(Region::NorthAmerica));
+    assert_eq!((tickers[6].country_normalized.clone(), tickers[6].region), (None, None));
+    let adrs: Vec<&str> = tickers.iter().filter(|t| t.is_adr).map(|t| t.symbol.as_str()).collect();
+    assert_eq!(adrs, ["BABA", "BIDU", "NIO", "SAP", "TCEHY"]);
+
+    let symbols = |filter: &StockFilter| -> Vec<String> {
+        StockAnalyzer::filter_tickers(&tickers, filter).into_iter().map(|t| t.symbol).collect()
+    };
+    assert_eq!(symbols(&StockFilter::new().with_countries(vec!["U.S.".to_string()])), ["AAPL", "MSFT"]);
+    assert_eq!(symbols(&StockFilter::new().with_countries(vec!["China".to_string()])), ["NIO"]);
+    assert_eq!(symbols(&StockFilter::new().with_countries(vec!["North America".to_string()])), ["AAPL", "MSFT", "SHOP"]);
+    assert_eq!(symbols(&StockFilter::new().with_exclude_adrs(true)), ["AAPL", "MSFT", "SHOP"]);
+
+    // Tickers that skipped `with_parsed_fields` are normalized on the fly
+    let raw = vec![auto_analyser::TickerInfo {
+        country: Some("usa".to_string()),
+        country_normalized: None,
+        region: None,
+        ..ticker("RAW", "$1.00", "$1.0M", "Energy")
+    }];
+    let raw_with = |filter: &StockFilter| StockAnalyzer::filter_tickers(&raw, filter).len();
+    assert_eq!(raw_with(&StockFilter::new().with_countries(vec!["United States".to_string()])), 1);
+}
+
+#[test]
+fn test_sort_tickers_keeps_missing_values_last_and_ties_in_order() {
+    use auto_analyser::TickerSortField;
+
+    let with_change = |symbol: &str, pct_change: Option<&str>| {
+        auto_analyser::TickerInfo {
+            pct_change: pct_change.map(str::to_string),
+            ..ticker(symbol, "$20.00", "$1.0B", "Technology")
+        }
+        .with_parsed_fields()
+    };
+    let tickers = vec![
+        with_change("NONE1", None),
+        with_change("UP", Some("3.0%")),
+        with_change("TIE1", Some("1.0%")),
+        with_change("NA", Some("N/A")),
+        with_change("DOWN", Some("-2.0%")),
+        with_change("TIE2", Some("1.0%")),
+        with_change("NONE2", None),
+    ];
+    let ranked = |descending: bool, limit: usize| -> Vec<String> {
+        StockAnalyzer::sort_tickers(&tickers, TickerSortField::PctChange, descending, limit)
+            .into_iter()
+            .map(|t| t.symbol)
+            .collect()
+    };
+
+    assert_eq!(ranked(true, 10), ["UP", "TIE1", "TIE2", "DOWN", "NONE1", "NA", "NONE2"]);
+    assert_eq!(ranked(false, 10), ["DOWN", "TIE1", "TIE2", "UP", "NONE1", "NA", "NONE2"]);
+    assert_eq!(ranked(true, 2), ["UP", "TIE1"]);
+
+    // Top performers only ever list tickers with a known change
+    let top: Vec<String> = StockAnalyzer::get_top_performers(&tickers, 10).into_iter().map(|t| t.symbol).collect();
+    assert_eq!(top, ["UP", "TIE1", "TIE2", "DOWN"]);
+    assert_eq!("market_cap".parse::<TickerSortField>(), Ok(TickerSortField::MarketCap));
+    assert!("rsi".parse::<TickerSortField>().is_err());
+}
+
+#[test]
+fn test_rank_tickers_weighs_liquidity_against_the_move() {
+    use auto_analyser::RankingConfig;
+
+    let mover = |symbol: &str, price: &str, market_cap: &str, pct_change: &str, volume: &str| {
+        auto_analyser::TickerInfo {
+            pct_change: Some(pct_change.to_string()),
+            volume: Some(volume.to_string()),
+            ..ticker(symbol, price, market_cap, "Technology")
+        }
+        .with_parsed_fields()
+    };
+    // Up 80% on $6,000 traded, against up 8% on $50M
+    let tickers = vec![
+        mover("PUMP", "$2.00", "$15.0M", "80.0%", "3,000"),
+        mover("SOLID", "$50.00", "$4.0B", "8.0%", "1,000,000"),
+        mover("QUIET", "$50.00", "$4.0B", "0.5%", "1,000,000"),
+        auto_analyser::TickerInfo { volume: Some("1,000,000".to_string()), ..ticker("NOCHG", "$50.00", "$4.0B", "Technology") }
+            .with_parsed_fields(),
+    ];
+    assert_eq!(tickers[1].dollar_volume(), Some(50_000_000.0));
+    let symbols = |ranked: Vec<auto_analyser::TickerInfo>| -> Vec<String> { ranked.into_iter().map(|t| t.symbol).collect() };
+
+    let default = RankingConfig::default();
+    assert_eq!(symbols(StockAnalyzer::rank_tickers(&tickers, &default)), ["SOLID", "PUMP", "QUIET"]);
+    assert_eq!(symbols(StockAnalyzer::get_top_performers(&tickers, 1)), ["SOLID"]);
+
+    // Weighting only the move brings back the old order
+    let momentum_only = default.with_weights(1.0, 0.0, 0.0);
+    assert_eq!(symbols(StockAnalyzer::rank_tickers(&tickers, &momentum_only)), ["PUMP", "SOLID", "QUIET"]);
+
+    // A cutoff leaves the illiquid mover out entirely
+    let liquid = default.with_min_dollar_volume(Some(1_000_000.0));
+    assert_eq!(symbols(StockAnalyzer::rank_tickers(&tickers, &liquid)), ["SOLID", "QUIET"]);
+
+    assert!(default.validate().is_ok());
+    assert!(default.with_weights(0.0, 0.0, 0.0).validate().is_err());
+    assert!(default.with_weights(-1.0, 1.0, 0.0).validate().is_err());
+}
+
+fn ticker(symbol: &str, price: &str, market_cap: &str, sector: &str) -> auto_analyser::TickerInfo {
+    auto_analyser::TickerInfo {
+        symbol: symbol.to_string(),
+        name: format!("{} Inc", symbol),
+        last_sale: Some(price.to_string()),
+        net_change: None,
+        pct_change: None,
+        market_cap: Some(market_cap.to_string()),
+        country: Some("United States".to_string()),
+        ipo_year: None,
+        volume: None,
+        sector: Some(sector.to_string()),
+        industry: None,
+        exchange: None,
+        beta: None,
+        fifty_two_week_high: None,
+        fifty_two_week_low: None,
+        last_sale_f64: None,
+        market_cap_f64: None,
+        pct_change_f64: None,
+        volume_u64: None,
+        country_normalized: None,
+        region: None,
+        is_adr: false,
+    }
+    .with_parsed_fields()
+}
+
+#[test]
+fn test_filter_spec_deserializes_single_filter() {
+    use auto_analyser::FilterSpec;
+
+    let spec: FilterSpec = serde_json::from_str(r#"{"min_price": 10.0, "sectors": ["Technology"]}"#).unwrap();
+    match &spec {
+        FilterSpec::Single(filter) => {
+            assert_eq!(filter.min_price, Some(10.0));
+            assert_eq!(filter.sectors, Some(vec!["Technology".to_string()]));
+        }
+        FilterSpec::Composite(_) => panic!("expected a single filter"),
+    }
+    assert_eq!(spec.members().len(), 1);
+
+    let empty: FilterSpec = serde_json::from_str("{}").unwrap();
+    assert!(matches!(empty, FilterSpec::Single(_)));
+}
+
+#[test]
+fn test_filter_spec_deserializes_composite_filter() {
+    use auto_analyser::FilterSpec;
+
+    let spec: FilterSpec = serde_json::from_str(
+        r#"{"any_of": [{"max_rsi": 30.0, "min_market_cap": 1000000000.0}, {"max_pct_change": -5.0}]}"#,
+    )
+    .unwrap();
+
+    let members = spec.members();
+    assert!(matches!(spec, FilterSpec::Composite(_)));
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[0].max_rsi, Some(30.0));
+    assert_eq!(members[1].max_pct_change, Some(-5.0));
+
+    // Round-trips through the untagged representation
+    let json = serde_json::to_value(&spec).unwrap();
+    assert_eq!(json["any_of"].as_array().unwrap().len(), 2);
+    let again: FilterSpec = serde_json::from_value(json).unwrap();
+    assert_eq!(again.members().len(), 2);
+}
+
+#[test]
+fn test_filter_spec_rejects_malformed_composite() {
+    use auto_analyser::FilterSpec;
+
+    assert!(serde_json::from_str::<FilterSpec>(r#"{"any_of": {"min_price": 1.0}}"#).is_err());
+    assert!(serde_json::from_str::<FilterSpec>(r#"{"any_of": [{"min_price": "cheap"}]}"#).is_err());
+    assert!(serde_json::from_str::<FilterSpec>(r#"{"min_price": "cheap"}"#).is_err());
+}
+
+#[test]
+fn test_filter_spec_validation_recurses_into_members() {
+    use auto_analyser::{CompositeFilter, FilterSpec, StockFilter};
+
+    let empty = FilterSpec::Composite(CompositeFilter { any_of: vec![] });
+    assert!(empty.validate().is_err());
+
+    let spec = FilterSpec::Composite(CompositeFilter {
+        any_of: vec![
+            StockFilter::new().with_price_range(Some(1.0), Some(10.0)),
+            StockFilter::new().with_price_range(Some(50.0), Some(5.0)),
+        ],
+    });
+    let err = spec.validate().unwrap_err();
+    assert!(err.starts_with("any_of[1]"), "{}", err);
+
+    let bad_rsi = FilterSpec::Single(StockFilter::new().with_rsi_thresholds(Some(-1.0), None));
+    assert!(bad_rsi.validate().is_err());
+    assert!(FilterSpec::Single(StockFilter::new()).validate().is_ok());
+}
+
+#[test]
+fn test_composite_filter_is_union_with_member_counts() {
+    use auto_analyser::{CompositeFilter, FilterSpec, StockFilter};
+
+    let tickers = vec![
+        ticker("BIG", "$150.00", "$2.0B", "Technology"),
+        ticker("CHEAP", "$3.00", "$50M", "Health Care"),
+        ticker("BOTH", "$4.00", "$3.0B", "Technology"),
+        ticker("NONE", "$40.00", "$100M", "Energy"),
+    ];
+
+    let spec = FilterSpec::Composite(CompositeFilter {
+        any_of: vec![
+            StockFilter::new().with_market_cap_range(Some(1_000_000_000.0), None),
+            StockFilter::new().with_price_range(None, Some(5.0)),
+        ],
+    });
+
+    let (passed, member_matches) = StockAnalyzer::filter_tickers_spec(&tickers, &spec);
+    let symbols: Vec<&str> = passed.iter().map(|t| t.symbol.as_str()).collect();
+
+    assert_eq!(symbols, vec!["BIG", "CHEAP", "BOTH"]);
+    assert_eq!(member_matches, vec![2, 2]);
+
+    // A single filter behaves exactly like filter_tickers
+    let single = StockFilter::new().with_sectors(vec!["tech".to_string()]);
+    let (passed, member_matches) = StockAnalyzer::filter_tickers_spec(&tickers, &single.clone().into());
+    assert_eq!(passed.len(), StockAnalyzer::filter_tickers(&tickers, &single).len());
+    assert_eq!(member_matches, vec![2]);
+}
+
+fn series(symbol: &str, len: usize) -> Vec<StockData> {
+    let start = Utc::now() - chrono::Duration::days(len as i64);
+    (0..len)
+        .map(|i| {
+            let close = 100.0 + (i as f64 * 0.37).sin() * 8.0 + i as f64 * 0.05;
+            StockData {
+                symbol: symbol.to_string(),
+                timestamp: start + chrono::Duration::days(i as i64),
+                open: close - 0.5,
+                high: close + 1.0,
+                low: close - 1.0,
+                close,
+                volume: 1_000_000 + i as u64,
+                adjusted_close: None,
+            }
+        })
+        .collect()
+}
+
+fn assert_indicators_match(actual: &[TechnicalIndicators], expected: &[TechnicalIndicators]) {
+    assert_eq!(actual.len(), expected.len());
+    for (a, e) in actual.iter().zip(expected) {
+        assert_eq!(a.sma_20, e.sma_20);
+        assert_eq!(a.sma_50, e.sma_50);
+        assert_eq!(a.rsi, e.rsi);
+        assert_eq!(a.macd, e.macd);
+        assert_eq!(a.bollinger, e.bollinger);
+        assert_eq!(a.stochastic, e.stochastic);
+        assert_eq!(a.atr, e.atr);
+        assert_eq!(a.cci, e.cci);
+        assert_eq!(a.obv, e.obv);
+        assert_eq!(a.vwap, e.vwap);
+        assert_eq!(a.volume_vs_avg, e.volume_vs_avg);
+    }
+}
+
+#[test]
+fn test_ohlc_indicators_in_pipeline() {
+    use auto_analyser::indicators::{AverageTrueRange, BollingerBands, CommodityChannelIndex, StochasticOscillator};
+
+    let data = series("OHLC", 80);
+    let indicators = StockAnalyzer::new().calculate_indicators("OHLC", &data);
+
+    let bollinger = BollingerBands::new(20, 2.0).calculate(&data);
+    let stochastic = StochasticOscillator::new(14, 3).calculate(&data);
+    let atr = AverageTrueRange::new(14).calculate(&data);
+    let cci = CommodityChannelIndex::new(20, 0.015).calculate(&data);
+    for (i, ind) in indicators.iter().enumerate() {
+        assert_eq!(ind.bollinger, bollinger[i]);
+        assert_eq!(ind.stochastic, stochastic[i]);
+        assert_eq!(ind.atr, atr[i]);
+        assert_eq!(ind.cci, cci[i]);
+    }
+
+    // Warm-up: the first ATR needs 14 true ranges, so 15 candles
+    assert!(indicators[18].bollinger.is_none() && indicators[19].bollinger.is_some());
+    assert!(indicators[12].stochastic.is_none() && indicators[13].stochastic.is_some());
+    assert!(indicators[13].atr.is_none() && indicators[14].atr.is_some());
+    assert!(indicators[18].cci.is_none() && indicators[19].cci.is_some());
+
+    // The range indicators see the high/low, not just the close
+    let last = indicators.last().unwrap();
+    assert!(last.atr.unwrap() >= 2.0, "ATR is at least the 2.0 high-low range");
+    let stoch = last.stochastic.as_ref().unwrap();
+    assert!((0.0..=100.0).contains(&stoch.k_percent));
+}
+
+#[test]
+fn test_analyze_signals_include_band_indicators() {
+    let mut data = series("BAND", 60);
+    // A sharp three-day slide through the lower band
+    for (bar, drop) in data[57..].iter_mut().zip([10.0, 20.0, 30.0]) {
+        bar.close -= drop;
+        bar.low = bar.close - 1.0;
+    }
+
+    let analyzer = StockAnalyzer::new();
+    let indicators = StockAnalyzer::new().calculate_indicators("BAND", &data);
+    let signals = analyzer.analyze_signals(&data, &indicators);
+
+    assert!(signals.iter().any(|s| s == "Price Near Lower Band - Potential Oversold"), "{:?}", signals);
+    assert!(signals.iter().any(|s| s.starts_with("Stochastic Oversold")), "{:?}", signals);
+    assert!(signals.iter().any(|s| s.starts_with("CCI Oversold")), "{:?}", signals);
+
+    // Only the latest candle is judged, not the whole history
+    assert_eq!(
+        StockAnalyzer::indicator_signals(&data, &indicators),
+        StockAnalyzer::indicator_signals(&data[58..], &indicators[58..])
+    );
+    assert!(StockAnalyzer::indicator_signals(&data[..1], &indicators[..1]).is_empty());
+}
+
+#[tokio::test]
+async fn test_incremental_indicators_match_full_recompute() {
+    use auto_analyser::cache::CacheManager;
+
+    let data = series("INCR", 300);
+    let expected = StockAnalyzer::new().calculate_indicators("INCR", &data);
+
+    let cache = CacheManager::new();
+    let mut analyzer = StockAnalyzer::new_with_cache(cache.clone());
+
+    analyzer.calculate_indicators_cached("INCR", &data[..299]).await;
+    let extended = analyzer.calculate_indicators_cached("INCR", &data).await;
+    assert_indicators_match(&extended, &expected);
+
+    // Unchanged series is served from the retained outputs
+    let unchanged = analyzer.calculate_indicators_cached("INCR", &data).await;
+    assert_indicators_match(&unchanged, &expected);
+
+    // A fresh analyzer sharing the cache restores state rather than starting cold
+    let mut other = StockAnalyzer::new_with_cache(cache.clone());
+    let more = series("INCR", 302);
+    let mut shifted = data.clone();
+    shifted.extend(more[300..].iter().map(|bar| StockData {
+        timestamp: data[299].timestamp + (bar.timestamp - more[299].timestamp),
+        ..bar.clone()
+    }));
+    let expected = StockAnalyzer::new().calculate_indicators("INCR", &shifted);
+    let extended = other.calculate_indicators_cached("INCR", &shifted).await;
+    assert_indicators_match(&extended, &expected);
+}
+
+#[tokio::test]
+async fn test_revised_candle_forces_recompute() {
+    use auto_analyser::cache::CacheManager;
+
+    let mut data = series("REVISE", 120);
+    let mut analyzer = StockAnalyzer::new_with_cache(CacheManager::new());
+    analyzer.calculate_indicators_cached("REVISE", &data[..119]).await;
+
+    // The provider revises an old close and adds a new candle in the same fetch
+    data[100].close += 5.0;
+    let expected = StockAnalyzer::new().calculate_indicators("REVISE", &data);
+    let updated = analyzer.calculate_indicators_cached("REVISE", &data).await;
+    assert_indicators_match(&updated, &expected);
+
+    // A shorter series (history window moved) is also recomputed in full
+    let expected = StockAnalyzer::new().calculate_indicators("REVISE", &data[10..]);
+    let updated = analyzer.calculate_indicators_cached("REVISE", &data[10..]).await;
+    assert_indicators_match(&updated, &expected);
+}
+
+#[tokio::test]
+async fn test_update_indicators_feeds_only_new_bars() {
+    use auto_analyser::cache::CacheManager;
+
+    let data = series("UPDT", 300);
+    let expected = StockAnalyzer::new().calculate_indicators("UPDT", &data);
+    let mut analyzer = StockAnalyzer::new_with_cache(CacheManager::new());
+
+    let first = analyzer.update_indicators("UPDT", &data[..250]).await;
+    assert_indicators_match(&first, &expected[..250]);
+    // Bars already processed are skipped
+    let update = analyzer.update_indicators("UPDT", &data[240..260]).await;
+    assert_indicators_match(&update, &expected[250..260]);
+    assert!(analyzer.update_indicators("UPDT", &data[255..260]).await.is_empty());
+    let update = analyzer.update_indicators("UPDT", &data[260..]).await;
+    assert_indicators_match(&update, &expected[260..]);
+
+    // The retained state still serves the full series
+    let full = analyzer.calculate_indicators_cached("UPDT", &data).await;
+    assert_indicators_match(&full, &expected);
+}
+
+#[tokio::test]
+async fn test_restored_indicator_state_matches_full_recompute() {
+    use auto_analyser::cache::CacheManager;
+    use auto_analyser::database::Database;
+    use std::sync::Arc;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("state.db").display());
+    let db = Arc::new(Database::new(&db_url).await.unwrap());
+    let data = series("RSTR", 400);
+    let expected = StockAnalyzer::new().calculate_indicators("RSTR", &data);
+
+    let mut analyzer = StockAnalyzer::new_with_cache(CacheManager::new()).with_database(db.clone());
+    analyzer.calculate_latest_indicators("RSTR", &data[..390], 30).await;
+
+    // After a restart the state comes from the database, without the early outputs
+    let mut restarted = StockAnalyzer::new_with_cache(CacheManager::new()).with_database(db.clone());
+    let latest = restarted.calculate_latest_indicators("RSTR", &data, 30).await;
+    assert!(latest.len() >= 30 && latest.len() < data.len(), "{} outputs", latest.len());
+    assert_indicators_match(&latest, &expected[data.len() - latest.len()..]);
+    for (actual, expected) in latest.iter().zip(&expected[data.len() - latest.len()..]) {
+        assert_eq!(actual.adx, expected.adx);
+        assert_eq!((actual.high_52w, actual.low_52w), (expected.high_52w, expected.low_52w));
+        assert_eq!(actual.moving_averages, expected.moving_averages);
+    }
+
+    // Unchanged on the next cycle
+    let mut restarted = StockAnalyzer::new_with_cache(CacheManager::new()).with_database(db.clone());
+    let unchanged = restarted.calculate_latest_indicators("RSTR", &data, 30).await;
+    assert_indicators_match(&unchanged, &expected[data.len() - unchanged.len()..]);
+
+    // A split adjusting the history revises processed bars: recomputed in full
+    let mut adjusted = data.clone();
+    for bar in &mut adjusted[..200] {
+        bar.close /= 2.0;
+    }
+    let expected = StockAnalyzer::new().calculate_indicators("RSTR", &adjusted);
+    let mut restarted = StockAnalyzer::new_with_cache(CacheManager::new()).with_database(db.clone());
+    let recomputed = restarted.calculate_latest_indicators("RSTR", &adjusted, 30).await;
+    assert_indicators_match(&recomputed, &expected);
+
+    // Asking for more than the persisted outputs also recomputes
+    let mut restarted = StockAnalyzer::new_with_cache(CacheManager::new()).with_database(db);
+    let longer = restarted.calculate_latest_indicators("RSTR", &adjusted, 100).await;
+    assert_indicators_match(&longer, &expected);
+}
+
+#[test]
+fn test_history_range_bounds_and_cache_keys() {
+    use auto_analyser::providers::Interval;
+    use auto_analyser::HistoryRange;
+    use chrono::{NaiveDate, TimeZone};
+
+    let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+    let now = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+
+    let all = HistoryRange::default();
+    assert_eq!(all.cache_key("AAPL"), "stock_data_AAPL");
+    assert_eq!(all.bounds(now), (chrono::DateTime::<Utc>::UNIX_EPOCH, now));
+
+    let weekly = HistoryRange::new(Some(date("2023-01-01")), Some(date("2023-12-31")), Interval::Weekly);
+    assert_eq!(weekly.cache_key("AAPL"), "stock_data_AAPL_1wk_2023-01-01_2023-12-31");
+    assert_eq!(
+        weekly.bounds(now),
+        (
+            Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        )
+    );
+    assert_ne!(
+        HistoryRange::new(None, None, Interval::Monthly).cache_key("AAPL"),
+        all.cache_key("AAPL")
+    );
+
+    assert!(weekly.validate().is_ok());
+    assert!(HistoryRange::new(Some(date("2024-01-02")), Some(date("2024-01-01")), Interval::Daily)
+        .validate()
+        .is_err());
+
+    // Intraday ranges are keyed apart from daily ones and clamped to what Yahoo serves
+    let intraday = HistoryRange::new(None, None, Interval::FifteenMinutes);
+    assert_eq!(intraday.cache_key("AAPL"), "stock_data_AAPL_15m_-_-");
+    assert_ne!(intraday.cache_key("AAPL"), HistoryRange::new(None, None, Interval::Hourly).cache_key("AAPL"));
+    assert_eq!(intraday.bounds(now), (Utc.with_ymd_and_hms(2024, 4, 3, 0, 0, 0).unwrap(), now));
+    assert!(intraday.validate_lookback(now).is_ok());
+    assert!(weekly.validate_lookback(now).is_ok());
+    let err = HistoryRange::new(Some(date("2024-01-01")), None, Interval::FifteenMinutes)
+        .validate_lookback(now)
+        .unwrap_err();
+    assert_eq!(err, "15m bars only go back 60 days; start_date must be on or after 2024-04-03");
+    assert!(HistoryRange::new(Some(date("2024-01-01")), None, Interval::Hourly)
+        .validate_lookback(now)
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_interval_range_combinations() {
+    use auto_analyser::providers::Interval;
+
+    assert!(Interval::FifteenMinutes.validate_range("5d").is_ok());
+    assert!(Interval::FifteenMinutes.validate_range("1mo").is_ok());
+    assert_eq!(
+        Interval::FifteenMinutes.validate_range("3mo").unwrap_err(),
+        "15m bars only go back 60 days; range 3mo is too long"
+    );
+    assert!(Interval::Hourly.validate_range("1y").is_ok());
+    assert!(Interval::Hourly.validate_range("max").is_err());
+    assert!(Interval::Daily.validate_range("max").is_ok());
+    assert_eq!(Interval::Daily.validate_range("7d").unwrap_err(), "unknown range \"7d\"");
+    assert_eq!(serde_json::to_string(&Interval::Hourly).unwrap(), "\"1h\"");
+
+    // Rejected before any request is made
+    let analyzer = StockAnalyzer::new();
+    let err = analyzer
+        .fetch_stock_data_interval("AAPL", "6mo", Interval::FifteenMinutes)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("only go back 60 days"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_persistent_history_fetches_only_the_tail() {
+    use async_trait::async_trait;
+    use auto_analyser::database::Database;
+    use auto_analyser::providers::{MarketDataProvider, ProviderError, QuoteProvider, QuoteSeries};
+    use chrono::{DateTime, Duration, TimeZone};
+    use std::sync::{Arc, Mutex};
+
+    /// Serves bars from `history` within the requested window and records each request
+    struct RecordingProvider {
+        history: Mutex<Vec<StockData>>,
+        requests: Mutex<Vec<(DateTime<Utc>, DateTime<Utc>)>>,
+    }
+
+    #[async_trait]
+    impl QuoteProvider for RecordingProvider {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn fetch_history(
+            &self,
+            _symbol: &str,
+            start: DateTime<Utc>,
+            end: DateTime<Utc>,
+        ) -> Result<QuoteSeries, ProviderError> {
+            self.requests.lock().unwrap().push((start, end));
+            let bars = self
+                .history
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|bar| bar.timestamp >= start && bar.timestamp <= end)
+                .cloned()
+                .collect();
+            Ok(QuoteSeries {
+                bars,
+                source: "recording".to_string(),
+                fetched_at: Utc::now(),
+                quality: None,
+                events: Vec::new(),
+            })
+        }
+    }
+
+    impl MarketDataProvider for RecordingProvider {}
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("persist.db").to_string_lossy());
+    let db = Arc::new(Database::new(&db_url).await.unwrap());
+    db.initialize_tables().await.unwrap();
+
+    let first_day = Utc.with_ymd_and_hms(2024, 3, 1, 14, 30, 0).unwrap();
+    let bar = |day: i64, close: f64| StockData {
+        symbol: "TAIL".to_string(),
+        timestamp: first_day + Duration::days(day),
+        open: close,
+        high: close + 1.0,
+        low: close - 1.0,
+        close,
+        volume: 1_000,
+        adjusted_close: None,
+    };
+    let provider = Arc::new(RecordingProvider {
+        history: Mutex::new((0..30).map(|day| bar(day, 100.0 + day as f64)).collect()),
+        requests: Mutex::new(Vec::new()),
+    });
+
+    let analyzer = StockAnalyzer::new().with_provider(provider.clone()).with_database(db.clone());
+    let series = analyzer.fetch_quote_series_persistent(&db, "TAIL").await.unwrap();
+    assert_eq!(series.bars.len(), 30);
+    assert_eq!(provider.requests.lock().unwrap()[0].0, DateTime::<Utc>::UNIX_EPOCH);
+
+    // The next day the provider revises the last bar and adds a new one
+    {
+        let mut history = provider.history.lock().unwrap();
+        history[29].close = 150.0;
+        history.push(bar(30, 151.0));
+    }
+    let second = StockAnalyzer::new().with_provider(provider.clone()).with_database(db.clone());
+    let series = second.fetch_quote_series_persistent(&db, "TAIL").await.unwrap();
+
+    let requests = provider.requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 2);
+    let last_stored_day = Utc.with_ymd_and_hms(2024, 3, 30, 0, 0, 0).unwrap();
+    assert_eq!(requests[1].0, last_stored_day, "only the last stored day onward is requested");
+
+    assert_eq!(series.bars.len(), 31);
+    assert_eq!(series.bars[29].close, 150.0);
+    assert_eq!(series.bars[30].close, 151.0);
+    assert_eq!(series.quality.map(|quality| quality.total_bars), Some(31), "stored bars count towards the total");
+    let stored = db.get_stock_data("TAIL", DateTime::<Utc>::UNIX_EPOCH, Utc::now()).await.unwrap();
+    assert_eq!(stored.len(), 31);
+    assert_eq!(stored[29].close, 150.0);
+}
+
+#[tokio::test]
+async fn test_fetched_bad_bars_follow_the_policy() {
+    use auto_analyser::data_quality::{BadBarPolicy, DataQuality};
+    use auto_analyser::providers::{FixtureProvider, Interval};
+    use chrono::{DateTime, Duration, TimeZone};
+    use std::sync::Arc;
+
+    let first_day = Utc.with_ymd_and_hms(2024, 3, 1, 21, 0, 0).unwrap();
+    let mut bars: Vec<StockData> = (0..30)
+        .map(|day| StockData {
+            symbol: "ADR".to_string(),
+            timestamp: first_day + Duration::days(day),
+            open: 20.0,
+            high: 21.0,
+            low: 19.0,
+            close: 20.0 + day as f64 / 10.0,
+            volume: 5_000,
+            adjusted_close: None,
+        })
+        .collect();
+    bars[10].close = f64::NAN;
+    bars[20].close = 0.0;
+    bars.push(bars[25].clone());
+    let provider = Arc::new(FixtureProvider::new().with_history("ADR", bars));
+    let fetch = |policy| {
+        let analyzer = StockAnalyzer::new().with_provider(provider.clone()).with_bad_bar_policy(policy);
+        async move {
+            analyzer
+                .fetch_quote_series("ADR", DateTime::<Utc>::UNIX_EPOCH, Utc::now(), Interval::Daily)
+                .await
+        }
+    };
+
+    let dropped = fetch(BadBarPolicy::Drop).await.unwrap();
+    assert_eq!(dropped.bars.len(), 28);
+    assert_eq!(dropped.quality, Some(DataQuality { total_bars: 31, dropped: 2, filled: 0, duplicates: 1 }));
+
+    let filled = fetch(BadBarPolicy::ForwardFill).await.unwrap();
+    assert_eq!(filled.bars.len(), 30);
+    assert_eq!((filled.bars[10].close, filled.bars[20].close), (filled.bars[9].close, filled.bars[19].close));
+    assert!(filled.bars.iter().all(|bar| bar.close.is_finite() && bar.close > 0.0));
+
+    assert!(fetch(BadBarPolicy::Error).await.is_err());
+}
+
+#[tokio::test]
+async fn test_split_adjusted_indicators_stay_continuous() {
+    use auto_analyser::database::Database;
+    use auto_analyser::providers::{FixtureProvider, Interval};
+    use auto_analyser::{compute_indicators, AdjustmentPolicy, CorporateEvent};
+    use chrono::DateTime;
+    use std::sync::Arc;
+
+    // A 2:1 split on bar 80: prices halve and volume doubles from then on
+    let scaled = |bar: &StockData| StockData {
+        open: bar.open * 0.5,
+        high: bar.high * 0.5,
+        low: bar.low * 0.5,
+        close: bar.close * 0.5,
+        volume: bar.volume * 2,
+        ..bar.clone()
+    };
+    let base = series("SPLT", 120);
+    let raw: Vec<StockData> = base.iter().enumerate().map(|(i, bar)| if i < 80 { bar.clone() } else { scaled(bar) }).collect();
+    let never_split: Vec<StockData> = base.iter().map(scaled).collect();
+    let split = CorporateEvent::Split { ratio: 2.0, date: DateTime::from_timestamp(raw[80].timestamp.timestamp(), 0).unwrap() };
+    let provider = Arc::new(FixtureProvider::new().with_history("SPLT", raw).with_events("SPLT", vec![split]));
+    let fetch = |policy| {
+        let analyzer = StockAnalyzer::new().with_provider(provider.clone()).with_adjustment_policy(policy);
+        async move { analyzer.fetch_stock_data("SPLT", DateTime::<Utc>::UNIX_EPOCH, Utc::now(), Interval::Daily).await.unwrap() }
+    };
+
+    let adjusted = fetch(AdjustmentPolicy::Adjusted).await;
+    assert_eq!(adjusted.iter().map(|bar| (bar.close, bar.volume)).collect::<Vec<_>>(), never_split.iter().map(|bar| (bar.close, bar.volume)).collect::<Vec<_>>());
+    assert_indicators_match(&compute_indicators(&adjusted), &compute_indicators(&never_split));
+
+    // As traded, the halving reads as a crash
+    let traded = compute_indicators(&fetch(AdjustmentPolicy::Raw).await);
+    let continuous = compute_indicators(&adjusted);
+    assert!(traded[80].rsi.unwrap() < 20.0 && continuous[80].rsi.unwrap() > 30.0, "{:?} {:?}", traded[80].rsi, continuous[80].rsi);
+    assert!(traded[85].sma_20.unwrap() > base[85].close * 0.5 * 1.5);
+
+    // Stored history keeps its split after the tail is fetched without it
+    let temp_dir = tempfile::tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("split.db").to_string_lossy());
+    let db = Arc::new(Database::new(&db_url).await.unwrap());
+    db.initialize_tables().await.unwrap();
+    for _ in 0..2 {
+        let analyzer = StockAnalyzer::new().with_provider(provider.clone()).with_database(db.clone());
+        let series = analyzer.fetch_quote_series_cached("SPLT").await.unwrap();
+        assert_eq!(series.events, [split]);
+        assert_eq!(series.bars[0].close, never_split[0].close);
+    }
+    assert_eq!(db.get_corporate_events("SPLT").await.unwrap(), [split]);
+}
+
+#[tokio::test]
+async fn test_offline_screen_with_fixture_provider() {
+    use auto_analyser::opportunity::{matching_rules, OpportunityRule, RuleInputs};
+    use auto_analyser::providers::FixtureProvider;
+    use auto_analyser::{AnalyzerError, StockFilter};
+    use std::sync::Arc;
+
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/market_data.json");
+    let mut analyzer = StockAnalyzer::new().with_provider(Arc::new(FixtureProvider::from_file(path).unwrap()));
+
+    let tickers = analyzer.fetch_all_tickers_cached().await.unwrap();
+    assert_eq!(tickers.len(), 4);
+    // GHOST's market cap is a placeholder, so it fails the lower bound
+    let filter = StockFilter::new()
+        .with_sectors(vec!["Technology".to_string()])
+        .with_market_cap_range(Some(5e8), None);
+    let screened = StockAnalyzer::filter_tickers(&tickers, &filter);
+    assert_eq!(screened.iter().map(|t| t.symbol.as_str()).collect::<Vec<_>>(), ["DIPS", "RALLY"]);
+
+    let rules = OpportunityRule::for_filter(&filter.into());
+    let mut matches = Vec::new();
+    for ticker in &screened {
+        let bars = analyzer.fetch_stock_data_cached(&ticker.symbol).await.unwrap();
+        assert_eq!(bars.len(), 60);
+        let indicators = analyzer.calculate_indicators(&ticker.symbol, &bars);
+        let inputs = RuleInputs::new(bars.last(), indicators.last().unwrap(), ticker.pct_change_f64);
+        let matched: Vec<String> = matching_rules(&rules, &inputs).into_iter().map(str::to_string).collect();
+        matches.push((ticker.symbol.clone(), matched));
+    }
+    assert_eq!(
+        matches,
+        [
+            ("DIPS".to_string(), vec!["Oversold (RSI <= 30)".to_string()]),
+            ("RALLY".to_string(), vec!["Overbought (RSI >= 70)".to_string()]),
+        ]
+    );
+
+    let err = analyzer.fetch_stock_data_cached("GHOST").await.unwrap_err();
+    assert!(matches!(err, AnalyzerError::SymbolNotFound(ref symbol) if symbol == "GHOST"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_throttle_response_lowers_shared_request_rate() {
+    use async_trait::async_trait;
+    use auto_analyser::cache::CacheManager;
+    use auto_analyser::providers::{MarketDataProvider, ProviderError, QuoteProvider, QuoteSeries};
+    use auto_analyser::rate_limit::RateLimitConfig;
+    use auto_analyser::retry::RetryPolicy;
+    use chrono::DateTime;
+    use std::sync::Arc;
+
+    struct ThrottledProvider;
+
+    #[async_trait]
+    impl QuoteProvider for ThrottledProvider {
+        fn name(&self) -> &str {
+            "throttled"
+        }
+
+        async fn fetch_history(
+            &self,
+            _symbol: &str,
+            _start: DateTime<Utc>,
+            _end: DateTime<Utc>,
+        ) -> Result<QuoteSeries, ProviderError> {
+            Err(ProviderError::RateLimited { provider: "throttled".to_string() })
+        }
+    }
+
+    impl MarketDataProvider for ThrottledProvider {}
+
+    let cache = CacheManager::new().with_rate_limit(RateLimitConfig::with_max_rate(8.0));
+    let analyzer = StockAnalyzer::new_with_cache(cache.clone())
+        .with_provider(Arc::new(ThrottledProvider))
+        .with_retry_policy(RetryPolicy::none());
+
+    assert!(analyzer.fetch_stock_data_cached("AAPL").await.is_err());
+    assert!(analyzer.fetch_stock_data_cached("MSFT").await.is_err());
+
+    let stats = cache.get_cache_stats().await.request_rate;
+    assert_eq!(stats.throttle_events, 2);
+    assert_eq!(stats.permits_granted, 2);
+    assert_eq!(stats.effective_rate, 2.0);
+    assert_eq!(stats.max_rate, 8.0);
+}
+
+/// Counts history requests, serving each once `gate` hands out a permit,
+/// or failing while `fail` is set
+struct GatedProvider {
+    calls: std::sync::atomic::AtomicUsize,
+    gate: tokio::sync::Semaphore,
+    fail: std::sync::atomic::AtomicBool,
+}
+
+impl GatedProvider {
+    fn new(permits: usize) -> Self {
+        Self {
+            calls: std::sync::atomic::AtomicUsize::new(0),
+            gate: tokio::sync::Semaphore::new(permits),
+            fail: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[async_trait::async_trait]
+impl auto_analyser::providers::QuoteProvider for GatedProvider {
+    fn name(&self) -> &str {
+        "gated"
+    }
+
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        start: chrono::DateTime<Utc>,
+        _end: chrono::DateTime<Utc>,
+    ) -> Result<auto_analyser::providers::QuoteSeries, auto_analyser::providers::ProviderError> {
+        use std::sync::atomic::Ordering;
+
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.gate.acquire().await.unwrap().forget();
+        if self.fail.load(Ordering::SeqCst) {
+            return Err(auto_analyser::providers::ProviderError::Upstream {
+                provider: "gated".to_string(),
+                message: "connection reset".to_string(),
+            });
+        }
+        Ok(auto_analyser::providers::QuoteSeries {
+            bars: vec![StockData {
+                symbol: symbol.to_string(),
+                timestamp: start,
+                open: 100.0,
+                high: 101.0,
+                low: 99.0,
+                close: 100.5,
+                volume: 1_000,
+                adjusted_close: None,
+            }],
+            source: "gated".to_string(),
+            fetched_at: Utc::now(),
+            quality: None,
+            events: Vec::new(),
+        })
+    }
+}
+
+impl auto_analyser::providers::MarketDataProvider for GatedProvider {}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_fetches_of_a_symbol_share_one_request() {
+    use auto_analyser::cache::CacheManager;
+    use std::sync::Arc;
+
+    let provider = Arc::new(GatedProvider::new(0));
+    let cache = CacheManager::new();
+    let analyzer = Arc::new(StockAnalyzer::new_with_cache(cache.clone()).with_provider(provider.clone()));
+
+    let callers: Vec<_> = (0..50)
+        .map(|_| {
+            let analyzer = analyzer.clone();
+            tokio::spawn(async move { analyzer.fetch_stock_data_cached("AAPL").await })
+        })
+        .collect();
+    while provider.calls() == 0 {
+        tokio::task::yield_now().await;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    provider.gate.add_permits(1);
+
+    for caller in callers {
+        assert_eq!(caller.await.unwrap().unwrap().len(), 1);
+    }
+    assert_eq!(provider.calls(), 1);
+    assert_eq!(cache.in_flight_fetches(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_shared_fetch_errors_and_timeouts_reach_every_caller() {
+    use auto_analyser::cache::CacheManager;
+    use auto_analyser::clock::TestClock;
+    use auto_analyser::error::AnalyzerError;
+    use auto_analyser::retry::RetryPolicy;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    let clock = Arc::new(TestClock::new(Utc::now()));
+    let provider = Arc::new(GatedProvider::new(0));
+    let cache = CacheManager::with_clock(clock.clone()).with_fetch_timeout(std::time::Duration::from_secs(10));
+    let analyzer = Arc::new(
+        StockAnalyzer::new_with_cache(cache.clone())
+            .with_provider(provider.clone())
+            .with_retry_policy(RetryPolicy::none()),
+    );
+    let spawn_callers = |symbol: &'static str, count: usize| -> Vec<_> {
+        (0..count)
+            .map(|_| {
+                let analyzer = analyzer.clone();
+                tokio::spawn(async move { analyzer.fetch_stock_data_cached(symbol).await })
+            })
+            .collect()
+    };
+
+    // A failed fetch is reported to everyone waiting on it
+    provider.fail.store(true, Ordering::SeqCst);
+    let callers = spawn_callers("AAPL", 10);
+    clock.wait_for_sleepers(1).await;
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    provider.gate.add_permits(1);
+    for caller in callers {
+        let err = caller.await.unwrap().unwrap_err();
+        assert!(matches!(err, AnalyzerError::Upstream(ref message) if message.contains("connection reset")), "{}", err);
+    }
+    assert_eq!(provider.calls(), 1);
+
+    // ...but not cached, so the next call fetches again
+    provider.fail.store(false, Ordering::SeqCst);
+    provider.gate.add_permits(1);
+    clock.advance(std::time::Duration::from_secs(1));
+    assert_eq!(analyzer.fetch_stock_data_cached("AAPL").await.unwrap().len(), 1);
+    assert_eq!(provider.calls(), 2);
+
+    // A hung fetch fails everyone once the timeout passes
+    let callers = spawn_callers("MSFT", 5);
+    clock.wait_for_sleepers(1).await;
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    clock.advance(std::time::Duration::from_secs(11));
+    for caller in callers {
+        let err = caller.await.unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "fetch of stock_data_MSFT timed out after 10s");
+    }
+    assert_eq!(provider.calls(), 3);
+    assert_eq!(cache.in_flight_fetches(), 0);
+}
+
+#[test]
+fn test_indicator_config_moving_averages() {
+    use auto_analyser::{compute_indicators, IndicatorConfig};
+
+    let data: Vec<StockData> = (0..60)
+        .map(|i| StockData {
+            symbol: "CFG".to_string(),
+            timestamp: Utc::now() + chrono::Duration::days(i),
+            open: 100.0,
+            high: 101.0,
+            low: 99.0,
+            close: 100.0 + (i as f64 * 0.3).sin() * 5.0,
+            volume: 1_000,
+            adjusted_close: None,
+        })
+        .collect();
+
+    let mut analyzer = StockAnalyzer::new();
+    let config = IndicatorConfig::default().with_sma_periods(vec![10]).with_ema_periods(vec![9, 21]);
+    let indicators = analyzer.calculate_indicators_with_config("CFG", &data, &config).unwrap();
+
+    let latest = indicators.last().unwrap();
+    let mut keys: Vec<&str> = latest.moving_averages.keys().map(String::as_str).collect();
+    keys.sort();
+    assert_eq!(keys, vec!["ema_21", "ema_9", "sma_10"]);
+    assert_eq!(latest.sma_20, None, "the fixed fields follow the configured averages");
+    let expected_sma_10 = data[50..].iter().map(|d| d.close).sum::<f64>() / 10.0;
+    assert!((latest.moving_averages["sma_10"] - expected_sma_10).abs() < 1e-9);
+    // EMAs only report once they have a full seed window
+    assert!(!indicators[7].moving_averages.contains_key("ema_9"));
+    assert!(indicators[8].moving_averages.contains_key("ema_9"));
+
+    // The default config reproduces the fixed set
+    let defaults = analyzer.calculate_indicators_with_config("CFG", &data, &IndicatorConfig::default()).unwrap();
+    let fixed = compute_indicators(&data);
+    assert_eq!(defaults.last().unwrap().sma_20, fixed.last().unwrap().sma_20);
+    assert_eq!(defaults.last().unwrap().moving_averages["sma_50"], fixed.last().unwrap().sma_50.unwrap());
+
+    let invalid = IndicatorConfig::default().with_macd(26, 12, 9);
+    assert!(analyzer.calculate_indicators_with_config("CFG", &data, &invalid).is_err());
+    assert!(IndicatorConfig::default().with_ema_periods(vec![0]).validate().is_err());
+    assert!(IndicatorConfig::default().with_range_window(0).validate().is_err());
+}
+
+#[test]
+fn test_indicator_config_range_window() {
+    use auto_analyser::{compute_indicators, compute_indicators_with_config, IndicatorConfig};
+
+    let data: Vec<StockData> = (0..30)
+        .map(|i| StockData {
+            symbol: "RNG".to_string(),
+            timestamp: Utc::now() + chrono::Duration::days(i),
+            open: 100.0,
+            high: 100.0 + i as f64,
+            low: 90.0 + i as f64,
+            close: 95.0 + i as f64,
+            volume: 1_000,
+            adjusted_close: None,
+        })
+        .collect();
+
+    // 30 candles never fill the default 252-candle window
+    let latest = compute_indicators(&data).pop().unwrap();
+    assert_eq!((latest.high_52w, latest.low_52w), (Some(129.0), Some(90.0)));
+    assert!(!latest.window_complete);
+
+    let config = IndicatorConfig::default().with_range_window(10);
+    let latest = compute_indicators_with_config(&data, &config).unwrap().pop().unwrap();
+    assert_eq!((latest.high_52w, latest.low_52w), (Some(129.0), Some(110.0)));
+    assert!(latest.window_complete);
+    assert!((latest.pct_from_high.unwrap() - 5.0 / 129.0 * 100.0).abs() < 1e-9);
+    assert!((latest.pct_from_low.unwrap() - 14.0 / 110.0 * 100.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_batch_quotes_are_chunked_and_partial() {
+    use async_trait::async_trait;
+    use auto_analyser::providers::{BatchQuoteSource, ProviderError};
+    use auto_analyser::retry::RetryPolicy;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// Quotes every requested symbol except `MISSING`, failing any chunk that contains `FAIL`
+    struct ChunkRecorder {
+        requests: Mutex<Vec<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl BatchQuoteSource for ChunkRecorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        async fn latest_quotes(&self, symbols: &[String]) -> Result<HashMap<String, StockData>, ProviderError> {
+            self.requests.lock().unwrap().push(symbols.to_vec());
+            if symbols.iter().any(|s| s == "FAIL") {
+                return Err(ProviderError::Parse { provider: "recorder".to_string(), message: "bad body".to_string() });
+            }
+            Ok(symbols
+                .iter()
+                .filter(|s| *s != "MISSING")
+                .map(|s| {
+                    let quote = StockData {
+                        symbol: s.clone(),
+                        timestamp: Utc::now(),
+                        open: 10.0,
+                        high: 11.0,
+                        low: 9.0,
+                        close: 10.5,
+                        volume: 100,
+                        adjusted_close: None,
+                    };
+                    (s.clone(), quote)
+                })
+                .collect())
+        }
+    }
+
+    let symbols = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let source = Arc::new(ChunkRecorder { requests: Mutex::new(Vec::new()) });
+    let analyzer = StockAnalyzer::new()
+        .with_batch_quote_source(source.clone())
+        .with_quote_batch_size(2)
+        .with_retry_policy(RetryPolicy::none());
+
+    let quotes = analyzer
+        .get_latest_quotes_batch(&symbols(&["AAA", "BBB", "MISSING", "FAIL", "EEE"]))
+        .await
+        .unwrap();
+    assert_eq!(
+        *source.requests.lock().unwrap(),
+        vec![symbols(&["AAA", "BBB"]), symbols(&["MISSING", "FAIL"]), symbols(&["EEE"])]
+    );
+    let mut found: Vec<&str> = quotes.keys().map(String::as_str).collect();
+    found.sort();
+    assert_eq!(found, vec!["AAA", "BBB", "EEE"], "a failed chunk does not sink the others");
+
+    assert!(analyzer.get_latest_quotes_batch(&symbols(&["FAIL"])).await.is_err());
+    assert!(analyzer.get_latest_quotes_batch(&symbols(&["MISSING"])).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_builder_routes_requests_through_the_proxy() {
+    use auto_analyser::providers::Interval;
+    use auto_analyser::AnalyzerError;
+    use std::time::{Duration, Instant};
+    use tokio::io::AsyncReadExt;
+
+    // A proxy that notes each request line and then never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy = format!("http://{}", listener.local_addr().unwrap());
+    let (requests_tx, mut requests) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let requests_tx = requests_tx.clone();
+            tokio::spawn(async move {
+                let mut buffer = vec![0; 1024];
+                let read = socket.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]).lines().next().unwrap_or_default().to_string();
+                let _ = requests_tx.send(request);
+                tokio::time::sleep(Duration::from_secs(30)).await;
+            });
+        }
+    });
+
+    let analyzer = StockAnalyzer::builder()
+        .proxy(&proxy)
+        .timeout(Duration::from_millis(200))
+        .build()
+        .unwrap();
+    let started = Instant::now();
+    let end = Utc::now();
+    let error = analyzer
+        .fetch_quote_series("AAPL", end - chrono::Duration::days(30), end, Interval::Daily)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AnalyzerError::Timeout(_)), "{:?}", error);
+    assert!(started.elapsed() < Duration::from_secs(5), "took {:?}", started.elapsed());
+
+    // Yahoo and then its Stooq fallback both went through the proxy
+    assert!(requests.recv().await.unwrap().starts_with("CONNECT query1.finance.yahoo.com:443"));
+    assert!(requests.recv().await.unwrap().starts_with("CONNECT stooq.com:443"));
+
+    let invalid = StockAnalyzer::builder().proxy("not a proxy").build();
+    assert!(matches!(invalid, Err(AnalyzerError::InvalidRequest(_))));
+}
@@ -0,0 +1,193 @@
+use auto_analyser::alerts::{AlertCondition, AlertTrigger, NewAlert};
+use auto_analyser::client::{AutoAnalyserClient, ClientError, WsMessage, WsStream};
+use auto_analyser::database::Database;
+use auto_analyser::live_quotes::LiveQuote;
+use auto_analyser::message_log::{BroadcastEvent, BroadcastMessage};
+use auto_analyser::providers::FixtureProvider;
+use auto_analyser::subscriptions::{ServerReply, Topic};
+use auto_analyser::web_api::{build_router, AnalysisRequest, AnalysisStatus, AppState};
+use auto_analyser::{FilterSpec, StockFilter};
+use chrono::Utc;
+use futures::StreamExt;
+use reqwest::StatusCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Fixture-backed state with a database in `dir`
+async fn client_state(dir: &tempfile::TempDir) -> AppState {
+    let db = Database::new(&format!("sqlite:{}", dir.path().join("client.db").to_string_lossy())).await.unwrap();
+    db.initialize_tables().await.unwrap();
+    let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/market_data.json");
+    AppState::with_database(Some(Arc::new(db))).with_market_data(Arc::new(FixtureProvider::from_file(fixtures).unwrap()))
+}
+
+/// Serve the real router for `state` on a local port, and a client for it
+async fn serve(state: AppState) -> AutoAnalyserClient {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, build_router(state)).await.unwrap() });
+    AutoAnalyserClient::new(&format!("http://{}", addr)).unwrap()
+}
+
+fn technology() -> FilterSpec {
+    FilterSpec::Single(StockFilter { sectors: Some(vec!["Technology".to_string()]), ..StockFilter::default() })
+}
+
+async fn next_message(stream: &mut WsStream) -> WsMessage {
+    tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("timed out waiting for a WebSocket message")
+        .expect("WebSocket closed")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_client_runs_an_analysis() {
+    let dir = tempfile::tempdir().unwrap();
+    let client = serve(client_state(&dir).await).await;
+
+    let health = client.health().await.unwrap();
+    assert_eq!(health.version, env!("CARGO_PKG_VERSION"));
+    assert!(!health.read_only);
+
+    let tickers = client.tickers().await.unwrap();
+    assert_eq!(tickers.iter().map(|t| t.symbol.as_str()).collect::<Vec<_>>(), ["DIPS", "RALLY", "CHOP", "GHOST"]);
+    let stats = client.filter_stats(&technology()).await.unwrap();
+    assert_eq!((stats.total_tickers, stats.filtered_tickers), (4, 3));
+
+    let request: AnalysisRequest = serde_json::from_value(serde_json::json!({ "filter": { "sectors": ["Technology"] } })).unwrap();
+    let session = client.start_analysis(&request).await.unwrap();
+    let finished = tokio::time::timeout(Duration::from_secs(5), session.wait(Duration::from_millis(10))).await.unwrap().unwrap();
+    assert_eq!(finished.status, "completed");
+    assert_eq!(finished.session_id, session.session_id);
+    assert_eq!(finished.results.iter().map(|r| r.ticker.as_str()).collect::<Vec<_>>(), ["DIPS", "RALLY"]);
+    assert_eq!(client.session_status(&session.session_id).await.unwrap().opportunities_found, 2);
+
+    let page = client.filtered_results(&technology()).await.unwrap();
+    assert_eq!(page.total, 2);
+    let second = client.filtered_results_page(&technology(), 2, 1).await.unwrap();
+    assert_eq!((second.page, second.per_page, second.results.len()), (2, 1, 1));
+    let changed = client.results_changed_since(Utc::now() - chrono::Duration::hours(1)).await.unwrap();
+    assert_eq!(changed.len(), 2);
+
+    let request: AnalysisRequest = serde_json::from_value(serde_json::json!({ "filter": {}, "symbols": ["rally", "ghost"] })).unwrap();
+    let batch = client.analyze_batch(&request).await.unwrap();
+    assert_eq!(batch.results.iter().map(|r| r.ticker.as_str()).collect::<Vec<_>>(), ["RALLY"]);
+    assert_eq!(batch.missing, ["GHOST"]);
+}
+
+#[tokio::test]
+async fn test_client_manages_watchlist_and_alerts() {
+    let dir = tempfile::tempdir().unwrap();
+    let client = serve(client_state(&dir).await).await;
+
+    let added = client.add_to_watchlist("aapl").await.unwrap();
+    assert_eq!((added.symbol.as_str(), added.added), ("AAPL", true));
+    assert!(!client.add_to_watchlist("AAPL").await.unwrap().added);
+    let watchlist = client.watchlist().await.unwrap();
+    assert_eq!(watchlist.iter().map(|item| item.entry.symbol.as_str()).collect::<Vec<_>>(), ["AAPL"]);
+    client.remove_from_watchlist("AAPL").await.unwrap();
+    assert!(client.watchlist().await.unwrap().is_empty());
+
+    // Plain-text error bodies come back as the message, JSON ones with their code
+    let missing = client.remove_from_watchlist("AAPL").await.unwrap_err();
+    assert_eq!(missing.status(), Some(StatusCode::NOT_FOUND));
+    assert!(matches!(&missing, ClientError::Api { code: None, message, .. } if message == "AAPL is not on the watchlist"), "{:?}", missing);
+    let unknown = client.session_status("nope").await.unwrap_err();
+    assert_eq!(unknown.code(), Some("session_not_found"));
+
+    let alert = client
+        .create_alert(&NewAlert { symbol: "msft".to_string(), condition_type: AlertCondition::RsiBelow, condition_value: 30.0 })
+        .await
+        .unwrap();
+    assert_eq!(alert.symbol, "MSFT");
+    assert_eq!(client.alerts().await.unwrap(), std::slice::from_ref(&alert));
+    client.delete_alert(alert.id).await.unwrap();
+    assert!(client.alerts().await.unwrap().is_empty());
+    let invalid = NewAlert { symbol: "MSFT".to_string(), condition_type: AlertCondition::RsiAbove, condition_value: 140.0 };
+    assert_eq!(client.create_alert(&invalid).await.unwrap_err().code(), Some("invalid_request"));
+}
+
+#[tokio::test]
+async fn test_client_streams_subscribed_broadcasts() {
+    let state = AppState::with_database(None);
+    let client = serve(state.clone()).await;
+
+    let mut stream = client
+        .subscribe_ws([Topic::Status, Topic::Ticker { symbol: "aapl".to_string() }, Topic::Alerts])
+        .await
+        .unwrap();
+    assert!(matches!(next_message(&mut stream).await, WsMessage::Snapshot(status) if !status.is_running));
+    for topic in [Topic::Status, Topic::Ticker { symbol: "AAPL".to_string() }, Topic::Alerts] {
+        assert!(matches!(next_message(&mut stream).await, WsMessage::Reply(ServerReply::Subscribed(acked)) if acked == topic));
+    }
+
+    let now = Utc::now();
+    state.publish(AnalysisStatus {
+        session_id: "s1".to_string(),
+        status: "running".to_string(),
+        progress: 0.5,
+        analyzed_count: 1,
+        total_count: 2,
+        opportunities_found: 0,
+        error_message: None,
+        results: Vec::new(),
+        results_total: 0,
+        estimated_completion: None,
+        remaining_tickers: Vec::new(),
+    });
+    state.publish(BroadcastEvent::Quote(LiveQuote { symbol: "MSFT".to_string(), price: 400.0, change_pct: None, timestamp: now }));
+    state.publish(BroadcastEvent::Quote(LiveQuote { symbol: "AAPL".to_string(), price: 190.5, change_pct: Some(1.2), timestamp: now }));
+    state.publish(BroadcastEvent::AlertTriggered(AlertTrigger {
+        alert_id: 7,
+        symbol: "NVDA".to_string(),
+        condition_type: AlertCondition::PriceAbove,
+        condition_value: 100.0,
+        observed_value: 101.0,
+        triggered_at: now,
+    }));
+
+    let WsMessage::Broadcast(progress) = next_message(&mut stream).await else { panic!("expected the session's progress") };
+    assert_eq!(progress.message.as_status().unwrap().session_id, "s1");
+    // MSFT's quote is not subscribed to
+    let WsMessage::Broadcast(quote) = next_message(&mut stream).await else { panic!("expected AAPL's quote") };
+    assert!(matches!(quote.message, BroadcastMessage::Event(BroadcastEvent::Quote(ref q)) if q.symbol == "AAPL" && q.price == 190.5));
+    assert!(quote.seq > progress.seq);
+    let WsMessage::Broadcast(alert) = next_message(&mut stream).await else { panic!("expected the alert") };
+    assert!(matches!(alert.message, BroadcastMessage::Event(BroadcastEvent::AlertTriggered(ref t)) if t.alert_id == 7));
+
+    stream.unsubscribe(Topic::Alerts).await.unwrap();
+    assert!(matches!(next_message(&mut stream).await, WsMessage::Reply(ServerReply::Unsubscribed(Topic::Alerts))));
+    stream.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_client_resumes_the_stream_where_it_left_off() {
+    let state = AppState::with_database(None);
+    let client = serve(state.clone()).await;
+
+    let seen = state.publish(BroadcastEvent::Quote(LiveQuote { symbol: "AAPL".to_string(), price: 190.0, change_pct: None, timestamp: Utc::now() }));
+    for price in [191.0, 192.0] {
+        state.publish(BroadcastEvent::Quote(LiveQuote { symbol: "AAPL".to_string(), price, change_pct: None, timestamp: Utc::now() }));
+    }
+
+    let mut stream = client.resume_ws([Topic::Ticker { symbol: "aapl".to_string() }], seen.seq).await.unwrap();
+    assert!(matches!(next_message(&mut stream).await, WsMessage::Snapshot(_)));
+    assert!(matches!(next_message(&mut stream).await, WsMessage::Reply(ServerReply::Subscribed(Topic::Ticker { .. }))));
+    for price in [191.0, 192.0] {
+        let WsMessage::Broadcast(quote) = next_message(&mut stream).await else { panic!("expected a missed quote") };
+        assert!(matches!(quote.message, BroadcastMessage::Event(BroadcastEvent::Quote(ref q)) if q.price == price));
+    }
+    assert!(matches!(
+        next_message(&mut stream).await,
+        WsMessage::Reply(ServerReply::Replayed { through_seq, complete: true, .. }) if through_seq == seen.seq + 2
+    ));
+}
+
+#[test]
+fn test_client_rejects_non_http_urls() {
+    assert!(matches!(AutoAnalyserClient::new("localhost:3001"), Err(ClientError::InvalidUrl(_))));
+    assert!(matches!(AutoAnalyserClient::new("ftp://example.com"), Err(ClientError::InvalidUrl(_))));
+    let client = AutoAnalyserClient::new("http://example.com/analyser/").unwrap();
+    assert_eq!(client.base_url().path(), "/analyser/");
+}
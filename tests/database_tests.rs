@@ -1,6 +1,10 @@
-use auto_analyser::database::Database;
-use auto_analyser::web_api::StockAnalysisResult;
+use auto_analyser::database::{Database, PagedResults, Pagination};
+use auto_analyser::opportunity_state::OpportunityState;
+use auto_analyser::resample::Timeframe;
+use auto_analyser::web_api::{ResultSort, SortField, SortOrder, StockAnalysisResult};
+use auto_analyser::StockFilter;
 use chrono::Utc;
+use std::collections::HashMap;
 use tempfile::tempdir;
 
 #[tokio::test]
@@ -40,11 +44,70 @@ async fn test_store_and_retrieve_analysis_result() {
         volume: Some(1000000),
         pct_change: Some(2.5),
         market_cap: Some("$1B".to_string()),
+        sector: None,
+        industry: None,
+        exchange: None,
         is_opportunity: false,
         signals: vec!["Test signal".to_string()],
         timestamp: Utc::now(),
+        data_source: None,
+        data_fetched_at: None,
+        summary: None,
+        analyst_target: None,
+        upside_to_target_pct: None,
+        obv: None,
+        vwap: None,
+        volume_vs_avg: None,
+        high_52w: None,
+        low_52w: None,
+        pct_from_high: None,
+        pct_from_low: None,
+        range_window_complete: false,
+        rs_1w: None,
+        rs_1m: None,
+        rs_3m: None,
+        rs_rank: None,
+        moving_averages: HashMap::new(),
+        crossovers: Vec::new(),
+        data_quality: None,
+        adx: None,
+        plus_di: None,
+        minus_di: None,
+        last_split: None,
+        timeframe: Timeframe::Daily,
+        anomalies: Vec::new(),
+        rsi_oversold_used: None,
+        rsi_overbought_used: None,
+        rsi_sector_zscore: None,
+        rsi_industry_zscore: None,
+        note: None,
+        opportunity_state: OpportunityState::None,
+        opportunity_streak: 0,
+        opportunity_since: None,
+        custom: HashMap::new(),
+        bars_used: None,
     };
     
+    let test_result = StockAnalysisResult {
+        data_source: Some("stooq".to_string()),
+        data_fetched_at: Some(Utc::now()),
+        summary: Some("TEST trades at $100.00, up 2.5% on the day.".to_string()),
+        analyst_target: Some(120.0),
+        upside_to_target_pct: Some(20.0),
+        obv: Some(-1_250_000.0),
+        vwap: Some(99.25),
+        volume_vs_avg: Some(2.4),
+        high_52w: Some(125.0),
+        low_52w: Some(80.0),
+        pct_from_high: Some(20.0),
+        pct_from_low: Some(25.0),
+        range_window_complete: true,
+        sector: Some("Technology".to_string()),
+        industry: Some("Software".to_string()),
+        exchange: Some("NYSE".to_string()),
+        ..test_result
+    };
+
     // Store the result
     let store_result = db.store_analysis_result(&test_result, "test_session").await;
     assert!(store_result.is_ok());
@@ -54,6 +117,20 @@ async fn test_store_and_retrieve_analysis_result() {
     assert_eq!(retrieved.len(), 1);
     assert_eq!(retrieved[0].ticker, "TEST");
     assert_eq!(retrieved[0].name, "Test Company");
+    assert_eq!(retrieved[0].summary, test_result.summary);
+    assert_eq!(retrieved[0].data_source.as_deref(), Some("stooq"));
+    assert!(retrieved[0].data_fetched_at.is_some());
+    assert_eq!(retrieved[0].analyst_target, Some(120.0));
+    assert_eq!(retrieved[0].upside_to_target_pct, Some(20.0));
+    assert_eq!(retrieved[0].sector.as_deref(), Some("Technology"));
+    assert_eq!(retrieved[0].industry.as_deref(), Some("Software"));
+    assert_eq!(retrieved[0].exchange.as_deref(), Some("NYSE"));
+    assert_eq!(retrieved[0].obv, Some(-1_250_000.0));
+    assert_eq!(retrieved[0].vwap, Some(99.25));
+    assert_eq!(retrieved[0].volume_vs_avg, Some(2.4));
+    assert_eq!((retrieved[0].high_52w, retrieved[0].low_52w), (Some(125.0), Some(80.0)));
+    assert_eq!((retrieved[0].pct_from_high, retrieved[0].pct_from_low), (Some(20.0), Some(25.0)));
+    assert!(retrieved[0].range_window_complete);
 }
 
 #[tokio::test]
@@ -80,9 +157,48 @@ async fn test_get_analysis_stats() {
             volume: Some(1000000),
             pct_change: Some(2.5),
             market_cap: Some("$1B".to_string()),
+            sector: None,
+            industry: None,
+            exchange: None,
             is_opportunity: i % 2 == 0, // Every other one is an opportunity
             signals: vec![],
             timestamp: Utc::now(),
+            data_source: None,
+            data_fetched_at: None,
+            summary: None,
+            analyst_target: None,
+            upside_to_target_pct: None,
+            obv: None,
+            vwap: None,
+            volume_vs_avg: None,
+            high_52w: None,
+            low_52w: None,
+            pct_from_high: None,
+            pct_from_low: None,
+            range_window_complete: false,
+            rs_1w: None,
+            rs_1m: None,
+            rs_3m: None,
+            rs_rank: None,
+            moving_averages: HashMap::new(),
+            crossovers: Vec::new(),
+            data_quality: None,
+            adx: None,
+            plus_di: None,
+            minus_di: None,
+            last_split: None,
+            timeframe: Timeframe::Daily,
+            anomalies: Vec::new(),
+            rsi_oversold_used: None,
+            rsi_overbought_used: None,
+            rsi_sector_zscore: None,
+            rsi_industry_zscore: None,
+            note: None,
+            opportunity_state: OpportunityState::None,
+            opportunity_streak: 0,
+            opportunity_since: None,
+            custom: HashMap::new(),
+            bars_used: None,
         };
         
         db.store_analysis_result(&result, "test_session").await.unwrap();
@@ -119,9 +235,48 @@ async fn test_cleanup_old_results() {
         volume: Some(1000000),
         pct_change: Some(2.5),
         market_cap: Some("$1B".to_string()),
+        sector: None,
+        industry: None,
+        exchange: None,
         is_opportunity: false,
         signals: vec![],
         timestamp: Utc::now(),
+        data_source: None,
+        data_fetched_at: None,
+        summary: None,
+        analyst_target: None,
+        upside_to_target_pct: None,
+        obv: None,
+        vwap: None,
+        volume_vs_avg: None,
+        high_52w: None,
+        low_52w: None,
+        pct_from_high: None,
+        pct_from_low: None,
+        range_window_complete: false,
+        rs_1w: None,
+        rs_1m: None,
+        rs_3m: None,
+        rs_rank: None,
+        moving_averages: HashMap::new(),
+        crossovers: Vec::new(),
+        data_quality: None,
+        adx: None,
+        plus_di: None,
+        minus_di: None,
+        last_split: None,
+        timeframe: Timeframe::Daily,
+        anomalies: Vec::new(),
+        rsi_oversold_used: None,
+        rsi_overbought_used: None,
+        rsi_sector_zscore: None,
+        rsi_industry_zscore: None,
+        note: None,
+        opportunity_state: OpportunityState::None,
+        opportunity_streak: 0,
+        opportunity_since: None,
+        custom: HashMap::new(),
+        bars_used: None,
     };
     
     db.store_analysis_result(&result, "cleanup_session").await.unwrap();
@@ -165,9 +320,48 @@ async fn test_get_results_by_session() {
             volume: Some(1000000),
             pct_change: Some(2.5),
             market_cap: Some("$1B".to_string()),
+            sector: None,
+            industry: None,
+            exchange: None,
             is_opportunity: false,
             signals: vec![],
             timestamp: Utc::now(),
+            data_source: None,
+            data_fetched_at: None,
+            summary: None,
+            analyst_target: None,
+            upside_to_target_pct: None,
+            obv: None,
+            vwap: None,
+            volume_vs_avg: None,
+            high_52w: None,
+            low_52w: None,
+            pct_from_high: None,
+            pct_from_low: None,
+            range_window_complete: false,
+            rs_1w: None,
+            rs_1m: None,
+            rs_3m: None,
+            rs_rank: None,
+            moving_averages: HashMap::new(),
+            crossovers: Vec::new(),
+            data_quality: None,
+            adx: None,
+            plus_di: None,
+            minus_di: None,
+            last_split: None,
+            timeframe: Timeframe::Daily,
+            anomalies: Vec::new(),
+            rsi_oversold_used: None,
+            rsi_overbought_used: None,
+            rsi_sector_zscore: None,
+            rsi_industry_zscore: None,
+            note: None,
+            opportunity_state: OpportunityState::None,
+            opportunity_streak: 0,
+            opportunity_since: None,
+            custom: HashMap::new(),
+            bars_used: None,
         };
         
         db.store_analysis_result(&result, session).await.unwrap();
@@ -209,9 +403,48 @@ async fn test_duplicate_ticker_handling() {
         volume: Some(1000000),
         pct_change: Some(2.5),
         market_cap: Some("$1B".to_string()),
+        sector: None,
+        industry: None,
+        exchange: None,
         is_opportunity: false,
         signals: vec![],
         timestamp: Utc::now(),
+        data_source: None,
+        data_fetched_at: None,
+        summary: None,
+        analyst_target: None,
+        upside_to_target_pct: None,
+        obv: None,
+        vwap: None,
+        volume_vs_avg: None,
+        high_52w: None,
+        low_52w: None,
+        pct_from_high: None,
+        pct_from_low: None,
+        range_window_complete: false,
+        rs_1w: None,
+        rs_1m: None,
+        rs_3m: None,
+        rs_rank: None,
+        moving_averages: HashMap::new(),
+        crossovers: Vec::new(),
+        data_quality: None,
+        adx: None,
+        plus_di: None,
+        minus_di: None,
+        last_split: None,
+        timeframe: Timeframe::Daily,
+        anomalies: Vec::new(),
+        rsi_oversold_used: None,
+        rsi_overbought_used: None,
+        rsi_sector_zscore: None,
+        rsi_industry_zscore: None,
+        note: None,
+        opportunity_state: OpportunityState::None,
+        opportunity_streak: 0,
+        opportunity_since: None,
+        custom: HashMap::new(),
+        bars_used: None,
     };
     
     db.store_analysis_result(&result1, session).await.unwrap();
@@ -230,9 +463,48 @@ async fn test_duplicate_ticker_handling() {
         volume: Some(1100000),
         pct_change: Some(3.0),
         market_cap: Some("$1.1B".to_string()),
+        sector: None,
+        industry: None,
+        exchange: None,
         is_opportunity: true,
         signals: vec!["Updated signal".to_string()],
         timestamp: Utc::now(),
+        data_source: None,
+        data_fetched_at: None,
+        summary: None,
+        analyst_target: None,
+        upside_to_target_pct: None,
+        obv: None,
+        vwap: None,
+        volume_vs_avg: None,
+        high_52w: None,
+        low_52w: None,
+        pct_from_high: None,
+        pct_from_low: None,
+        range_window_complete: false,
+        rs_1w: None,
+        rs_1m: None,
+        rs_3m: None,
+        rs_rank: None,
+        moving_averages: HashMap::new(),
+        crossovers: Vec::new(),
+        data_quality: None,
+        adx: None,
+        plus_di: None,
+        minus_di: None,
+        last_split: None,
+        timeframe: Timeframe::Daily,
+        anomalies: Vec::new(),
+        rsi_oversold_used: None,
+        rsi_overbought_used: None,
+        rsi_sector_zscore: None,
+        rsi_industry_zscore: None,
+        note: None,
+        opportunity_state: OpportunityState::None,
+        opportunity_streak: 0,
+        opportunity_since: None,
+        custom: HashMap::new(),
+        bars_used: None,
     };
     
     db.store_analysis_result(&result2, session).await.unwrap();
@@ -242,5 +514,868 @@ async fn test_duplicate_ticker_handling() {
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].name, "Duplicate Test Updated");
     assert_eq!(results[0].current_price, Some(105.0));
-    assert_eq!(results[0].is_opportunity, true);
-}
\ No newline at end of file
+    assert!(results[0].is_opportunity);
+
+    // A weekly result for the same ticker and session sits beside the daily one
+    let weekly = StockAnalysisResult { timeframe: Timeframe::Weekly, current_price: Some(99.0), ..result2 };
+    db.store_analysis_result(&weekly, session).await.unwrap();
+    let mut results = db.get_results_by_session(session).await.unwrap();
+    results.sort_by_key(|result| result.timeframe.as_str());
+    assert_eq!(results.len(), 2);
+    assert_eq!((results[0].timeframe, results[0].current_price), (Timeframe::Daily, Some(105.0)));
+    assert_eq!((results[1].timeframe, results[1].current_price), (Timeframe::Weekly, Some(99.0)));
+    let latest = db.get_latest_result("DUPLICATE").await.unwrap().unwrap();
+    assert_eq!(latest.timeframe, Timeframe::Daily);
+}
+
+#[tokio::test]
+async fn test_latest_results_matches_correlated_query_at_scale() {
+    use sqlx::Row;
+    use std::collections::BTreeSet;
+    use std::time::{Duration, Instant};
+
+    let temp_dir = tempdir().unwrap();
+    let db_path = temp_dir.path().join("test_latest_scale.db");
+    let db_url = format!("sqlite:{}", db_path.to_string_lossy());
+
+    let db = Database::new(&db_url).await.unwrap();
+    db.initialize_tables().await.unwrap();
+
+    // 1000 tickers x 50 sessions, with some tickers missing from later
+    // sessions so the latest timestamp differs between tickers
+    let pool = sqlx::SqlitePool::connect(&db_url).await.unwrap();
+    sqlx::query(
+        r#"
+        WITH RECURSIVE seq(n) AS (SELECT 0 UNION ALL SELECT n + 1 FROM seq WHERE n < 49999)
+        INSERT INTO analysis_results (
+            id, ticker, name, current_price, rsi, volume, is_opportunity, signals,
+            timestamp, analysis_session
+        )
+        SELECT
+            'row-' || n,
+            'T' || (n % 1000),
+            'Ticker ' || (n % 1000),
+            10.0 + (n % 97),
+            (n * 7) % 100,
+            n * 10,
+            ((n * 7) % 100) < 30,
+            '[]',
+            strftime('%Y-%m-%dT%H:%M:%S+00:00', '2024-01-01', '+' || (n / 1000) || ' hours', '+' || (n % 1000) || ' seconds'),
+            'session-' || (n / 1000)
+        FROM seq
+        WHERE (n % 1000) % 7 != 0 OR (n / 1000) < 25
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Reference: the original correlated-subquery form, with the inner table aliased
+    let reference: BTreeSet<(String, String)> = sqlx::query(
+        r#"
+        SELECT ticker, timestamp FROM analysis_results outer_r
+        WHERE timestamp = (
+            SELECT MAX(timestamp) FROM analysis_results inner_r WHERE inner_r.ticker = outer_r.ticker
+        )
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap()
+    .iter()
+    .map(|row| (row.get("ticker"), row.get("timestamp")))
+    .collect();
+
+    let started = Instant::now();
+    let latest = db.get_latest_results(None).await.unwrap();
+    let elapsed = started.elapsed();
+
+    let actual: BTreeSet<(String, String)> = latest
+        .iter()
+        .map(|r| (r.ticker.clone(), r.timestamp.to_rfc3339()))
+        .collect();
+
+    assert_eq!(latest.len(), 1000);
+    assert_eq!(actual, reference);
+    assert!(latest.windows(2).all(|w| w[0].timestamp >= w[1].timestamp));
+    assert!(elapsed < Duration::from_secs(5), "latest results took {:?}", elapsed);
+
+    let limited = db.get_latest_results(Some(10)).await.unwrap();
+    assert_eq!(limited.len(), 10);
+    assert_eq!(limited[0].ticker, latest[0].ticker);
+
+    // Filtering, sorting and paging in SQL agrees with doing it in memory
+    let sort = ResultSort { field: SortField::Rsi, order: SortOrder::Desc };
+    let oversold = StockFilter::new().with_rsi_range(None, Some(30.0));
+    let mut expected: Vec<_> = latest.iter().filter(|r| r.rsi.is_some_and(|rsi| rsi <= 30.0)).collect();
+    expected.sort_by(|a, b| sort.compare(a, b));
+
+    let PagedResults { total, results: page, .. } = db
+        .query_results(std::slice::from_ref(&oversold), Pagination { sort: Some(sort), limit: 25, offset: 50 })
+        .await
+        .unwrap();
+    assert_eq!(total as usize, expected.len());
+    let page_tickers: Vec<&str> = page.iter().map(|r| r.ticker.as_str()).collect();
+    let expected_tickers: Vec<&str> = expected[50..75].iter().map(|r| r.ticker.as_str()).collect();
+    assert_eq!(page_tickers, expected_tickers);
+
+    let PagedResults { total, results: unsorted, .. } = db.query_results(&[], Pagination { sort: None, limit: 10, offset: 0 }).await.unwrap();
+    assert_eq!(total, 1000);
+    assert_eq!(unsorted[0].ticker, latest[0].ticker);
+
+    db.optimize().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_query_results_matches_in_memory_filter() {
+    use auto_analyser::web_api::filter_results;
+    use auto_analyser::{CompositeFilter, FilterSpec};
+    use std::time::{Duration, Instant};
+
+    let temp_dir = tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("query_results.db").to_string_lossy());
+    Database::new(&db_url).await.unwrap();
+
+    // 3000 tickers x 2 sessions with gaps in every column the filters read.
+    // Rows go in without market_cap_value, as if stored before the column
+    // existed, so reconnecting has to parse them.
+    let pool = sqlx::SqlitePool::connect(&db_url).await.unwrap();
+    sqlx::query(
+        r#"
+        WITH RECURSIVE seq(n) AS (SELECT 0 UNION ALL SELECT n + 1 FROM seq WHERE n < 5999)
+        INSERT INTO analysis_results (
+            id, ticker, name, current_price, rsi, volume, pct_change, market_cap, is_opportunity, signals,
+            timestamp, analysis_session, sector, industry, exchange, volume_vs_avg, pct_from_low, pct_from_high,
+            rs_rank, upside_to_target_pct, data_quality, repaired_bar_ratio, adx
+        )
+        SELECT
+            'row-' || n,
+            'T' || (n % 3000),
+            'Ticker ' || (n % 3000),
+            CASE WHEN n % 53 = 0 THEN NULL ELSE 1.0 + (n * 13) % 400 END,
+            CASE WHEN n % 41 = 0 THEN NULL ELSE (n * 7) % 100 + 0.5 END,
+            (n * 37) % 5000000,
+            CASE WHEN n % 29 = 0 THEN NULL ELSE ((n * 11) % 200) / 10.0 - 10.0 END,
+            CASE n % 6
+                WHEN 0 THEN NULL
+                WHEN 1 THEN 'NA'
+                WHEN 2 THEN '$' || (n % 900) || '.5M'
+                WHEN 3 THEN '$' || (n % 60) || 'B'
+                WHEN 4 THEN '$1,' || (n % 1000) || 'M'
+                ELSE '$2.' || (n % 10) || 'T'
+            END,
+            0,
+            '[]',
+            strftime('%Y-%m-%dT%H:%M:%S+00:00', '2024-01-01', '+' || (n / 3000) || ' hours', '+' || (n % 3000) || ' seconds'),
+            'session-' || (n / 3000),
+            CASE n % 5 WHEN 0 THEN 'Technology' WHEN 1 THEN 'Health Care' WHEN 2 THEN 'Energy' WHEN 3 THEN 'Consumer Technology' ELSE NULL END,
+            CASE n % 4 WHEN 0 THEN 'Semiconductors' WHEN 1 THEN 'Biotechnology' WHEN 2 THEN 'Oil & Gas' ELSE NULL END,
+            CASE n % 7 WHEN 0 THEN 'NASDAQ' WHEN 1 THEN 'NYSE' WHEN 2 THEN 'AMEX' WHEN 3 THEN NULL ELSE 'NASDAQ' END,
+            CASE WHEN n % 3 = 0 THEN NULL ELSE ((n * 17) % 40) / 10.0 END,
+            CASE WHEN n % 9 = 0 THEN NULL ELSE (n * 19) % 120 END,
+            CASE WHEN n % 8 = 0 THEN NULL ELSE (n * 23) % 80 END,
+            CASE WHEN n % 2 = 0 THEN NULL ELSE (n * 31) % 101 END,
+            CASE WHEN n % 10 = 0 THEN NULL ELSE (n * 43) % 1500 - 100.0 END,
+            CASE WHEN n % 4 = 0 THEN NULL ELSE json_object('total_bars', 100, 'dropped', n % 13, 'filled', 0, 'duplicates', 0) END,
+            CASE WHEN n % 4 = 0 THEN NULL ELSE (n % 13) / 100.0 END,
+            CASE WHEN n % 6 = 0 THEN NULL ELSE (n * 29) % 70 END
+        FROM seq
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    pool.close().await;
+
+    let db = Database::new(&db_url).await.unwrap();
+    let latest = db.get_latest_results(None).await.unwrap();
+    assert_eq!(latest.len(), 3000);
+
+    let filters: Vec<FilterSpec> = vec![
+        StockFilter::new().into(),
+        StockFilter::new().with_rsi_range(Some(20.0), Some(40.0)).with_price_range(Some(50.0), None).into(),
+        StockFilter::new().with_market_cap_range(Some(2e9), Some(1.5e12)).into(),
+        StockFilter::new().with_market_cap_range(None, Some(5e8)).into(),
+        StockFilter {
+            sectors: Some(vec!["tech".to_string()]),
+            exchanges: Some(vec!["nasdaq".to_string(), "AMEX".to_string()]),
+            ..StockFilter::new()
+        }
+        .into(),
+        StockFilter { industries: Some(vec![]), ..StockFilter::new() }.into(),
+        StockFilter {
+            min_volume: Some(1_000_000),
+            max_pct_change: Some(0.0),
+            min_volume_vs_avg: Some(2.0),
+            ..StockFilter::new()
+        }
+        .into(),
+        StockFilter {
+            min_upside_to_target_pct: Some(25.0),
+            min_rs_rank: Some(50.0),
+            max_pct_from_low: Some(60.0),
+            min_pct_from_high: Some(10.0),
+            ..StockFilter::new()
+        }
+        .into(),
+        StockFilter::new().with_max_repaired_bar_ratio(Some(0.05)).into(),
+        StockFilter::new().with_min_adx(Some(25.0)).with_rsi_range(None, Some(50.0)).into(),
+        FilterSpec::Composite(CompositeFilter {
+            any_of: vec![
+                StockFilter { sectors: Some(vec!["Energy".to_string()]), ..StockFilter::new().with_rsi_range(None, Some(30.0)) },
+                StockFilter::new().with_market_cap_range(Some(1e12), None),
+            ],
+        }),
+    ];
+
+    let by_ticker = ResultSort { field: SortField::Ticker, order: SortOrder::Asc };
+    let everything = Pagination { sort: Some(by_ticker), limit: u32::MAX, offset: 0 };
+    let (mut sql_time, mut memory_time) = (Duration::ZERO, Duration::ZERO);
+    for filter in &filters {
+        let started = Instant::now();
+        let PagedResults { total, results: from_sql, .. } = db.query_results(filter.members(), everything).await.unwrap();
+        sql_time += started.elapsed();
+
+        let started = Instant::now();
+        let mut in_memory = filter_results(&db.get_latest_results(None).await.unwrap(), filter);
+        in_memory.sort_by(|a, b| by_ticker.compare(a, b));
+        memory_time += started.elapsed();
+
+        let tickers = |results: &[StockAnalysisResult]| results.iter().map(|r| r.ticker.clone()).collect::<Vec<_>>();
+        assert_eq!(tickers(&from_sql), tickers(&in_memory), "{:?}", filter);
+        assert_eq!(total as usize, in_memory.len());
+    }
+    eprintln!("{} filters over 3000 results: SQL {:?}, in memory {:?}", filters.len(), sql_time, memory_time);
+    assert!(sql_time < Duration::from_secs(5), "SQL filtering took {:?}", sql_time);
+
+    // The market cap filters have something on both sides of their bounds
+    let matched = db.query_results(filters[2].members(), everything).await.unwrap().total;
+    assert!(matched > 0 && (matched as usize) < latest.len());
+
+    // Paging a sorted listing takes the same slice as paging in memory
+    let by_rsi = ResultSort { field: SortField::Rsi, order: SortOrder::Desc };
+    let page = db
+        .query_results(filters[4].members(), Pagination { sort: Some(by_rsi), limit: 50, offset: 100 })
+        .await
+        .unwrap()
+        .results;
+    let mut expected = filter_results(&latest, &filters[4]);
+    expected.sort_by(|a, b| by_rsi.compare(a, b));
+    let page_tickers: Vec<&str> = page.iter().map(|r| r.ticker.as_str()).collect();
+    let expected_tickers: Vec<&str> = expected[100..150].iter().map(|r| r.ticker.as_str()).collect();
+    assert_eq!(page_tickers, expected_tickers);
+}
+
+#[tokio::test]
+async fn test_symbol_alias_roundtrip() {
+    use auto_analyser::symbols::SymbolAlias;
+    use chrono::NaiveDate;
+
+    let temp_dir = tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("aliases.db").to_string_lossy());
+    let db = Database::new(&db_url).await.unwrap();
+    db.initialize_tables().await.unwrap();
+
+    let renamed = NaiveDate::from_ymd_opt(2022, 6, 9).unwrap();
+    db.upsert_symbol_alias(&SymbolAlias::new("FB", "META", renamed)).await.unwrap();
+    db.upsert_symbol_alias(&SymbolAlias::new("TWTR", "X", renamed)).await.unwrap();
+    // Re-recording a rename replaces the earlier row
+    db.upsert_symbol_alias(&SymbolAlias::new("TWTR", "XCORP", renamed)).await.unwrap();
+
+    let aliases = db.get_symbol_aliases().await.unwrap();
+    assert_eq!(
+        aliases,
+        vec![SymbolAlias::new("FB", "META", renamed), SymbolAlias::new("TWTR", "XCORP", renamed)]
+    );
+}
+
+#[tokio::test]
+async fn test_symbol_health_roundtrip() {
+    use auto_analyser::symbol_health::SymbolHealth;
+    use chrono::TimeZone;
+
+    let temp_dir = tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("health.db").to_string_lossy());
+    let db = Database::new(&db_url).await.unwrap();
+    db.initialize_tables().await.unwrap();
+
+    let health = |symbol: &str, failures: u32, skip_until_cycle: Option<u64>| SymbolHealth {
+        symbol: symbol.to_string(),
+        consecutive_failures: failures,
+        last_error: "request failed".to_string(),
+        last_attempt: Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap(),
+        skip_until_cycle,
+    };
+    db.upsert_symbol_health(&health("ZZZ", 1, None)).await.unwrap();
+    db.upsert_symbol_health(&health("GONE", 1, Some(30))).await.unwrap();
+    // Later failures update the existing row
+    db.upsert_symbol_health(&health("ZZZ", 3, Some(12))).await.unwrap();
+
+    assert_eq!(
+        db.get_symbol_health().await.unwrap(),
+        vec![health("GONE", 1, Some(30)), health("ZZZ", 3, Some(12))]
+    );
+
+    assert!(db.delete_symbol_health("GONE").await.unwrap());
+    assert!(!db.delete_symbol_health("GONE").await.unwrap());
+    assert_eq!(db.get_symbol_health().await.unwrap(), vec![health("ZZZ", 3, Some(12))]);
+}
+
+#[tokio::test]
+async fn test_watchlist_roundtrip() {
+    let temp_dir = tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("watchlist.db").to_string_lossy());
+    let db = Database::new(&db_url).await.unwrap();
+    db.initialize_tables().await.unwrap();
+
+    assert!(db.add_to_watchlist("NVDA", None).await.unwrap());
+    assert!(db.add_to_watchlist("AAPL", None).await.unwrap());
+    assert!(!db.add_to_watchlist("NVDA", None).await.unwrap(), "duplicates are a no-op");
+
+    let symbols: Vec<_> = db.get_watchlist(false).await.unwrap().into_iter().map(|e| e.symbol).collect();
+    assert_eq!(symbols, vec!["NVDA", "AAPL"]);
+
+    assert!(db.remove_from_watchlist("NVDA", None).await.unwrap());
+    assert!(!db.remove_from_watchlist("NVDA", None).await.unwrap());
+    let symbols: Vec<_> = db.get_watchlist(false).await.unwrap().into_iter().map(|e| e.symbol).collect();
+    assert_eq!(symbols, vec!["AAPL"]);
+}
+
+#[tokio::test]
+async fn test_market_data_roundtrip() {
+    use auto_analyser::StockData;
+    use chrono::{Duration, TimeZone};
+
+    let temp_dir = tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("market.db").to_string_lossy());
+    let db = Database::new(&db_url).await.unwrap();
+    db.initialize_tables().await.unwrap();
+
+    let start = Utc.with_ymd_and_hms(2024, 1, 2, 14, 30, 0).unwrap();
+    let bar = |day: i64, close: f64| StockData {
+        symbol: "BARS".to_string(),
+        timestamp: start + Duration::days(day),
+        open: close - 1.0,
+        high: close + 1.0,
+        low: close - 2.0,
+        close,
+        volume: 5_000_000_000,
+        adjusted_close: None,
+    };
+
+    db.store_stock_data(&[bar(0, 10.0), bar(1, 11.0), bar(2, 12.0)]).await.unwrap();
+    // Same (symbol, timestamp) updates in place
+    db.store_stock_data(&[bar(2, 12.5)]).await.unwrap();
+
+    let all = db.get_stock_data("BARS", start, start + Duration::days(10)).await.unwrap();
+    assert_eq!(all.iter().map(|b| b.close).collect::<Vec<_>>(), vec![10.0, 11.0, 12.5]);
+    assert_eq!(all[0].timestamp, start);
+    assert_eq!(all[0].volume, 5_000_000_000);
+    assert!(db.get_stock_data("OTHER", start, start + Duration::days(10)).await.unwrap().is_empty());
+
+    let middle = db.get_stock_data("BARS", start + Duration::days(1), start + Duration::days(1)).await.unwrap();
+    assert_eq!(middle.len(), 1);
+
+    // A refreshed tail replaces the last day even if its timestamp moved
+    let midnight = Utc.with_ymd_and_hms(2024, 1, 4, 0, 0, 0).unwrap();
+    let restamped = StockData { timestamp: midnight, ..bar(2, 12.75) };
+    db.replace_stock_data_from("BARS", midnight, &[restamped, bar(3, 13.0)]).await.unwrap();
+    let all = db.get_stock_data("BARS", start, start + Duration::days(10)).await.unwrap();
+    assert_eq!(all.iter().map(|b| b.close).collect::<Vec<_>>(), vec![10.0, 11.0, 12.75, 13.0]);
+
+
+    // Every symbol's bars and events at once
+    let other = StockData { symbol: "OTHER".to_string(), ..bar(1, 50.0) };
+    db.store_stock_data(&[other]).await.unwrap();
+    let split = auto_analyser::corporate_events::CorporateEvent::Split { ratio: 2.0, date: start + Duration::days(1) };
+    db.store_corporate_events("BARS", std::slice::from_ref(&split)).await.unwrap();
+    let grouped = db.get_all_stock_data(start + Duration::days(1), start + Duration::days(10)).await.unwrap();
+    assert_eq!(grouped["BARS"].iter().map(|b| b.close).collect::<Vec<_>>(), vec![11.0, 12.75, 13.0]);
+    assert_eq!(grouped["OTHER"].len(), 1);
+    let events = db.get_all_corporate_events().await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events["BARS"], [split]);
+}
+
+#[tokio::test]
+async fn test_indicator_history_roundtrip() {
+    use auto_analyser::{StockData, TechnicalIndicators};
+    use chrono::{Duration, NaiveDate};
+
+    let temp_dir = tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("indicators.db").to_string_lossy());
+    let db = Database::new(&db_url).await.unwrap();
+    db.initialize_tables().await.unwrap();
+
+    let today = Utc::now();
+    let bar = |days_ago: i64| StockData {
+        symbol: "IND".to_string(),
+        timestamp: today - Duration::days(days_ago),
+        open: 10.0,
+        high: 11.0,
+        low: 9.0,
+        close: 10.5,
+        volume: 1_000,
+        adjusted_close: None,
+    };
+    let indicators = |rsi: f64| TechnicalIndicators {
+        rsi: Some(rsi),
+        sma_20: Some(10.0),
+        macd: Some((0.5, 0.25, 0.25)),
+        ..Default::default()
+    };
+
+    let (old, recent, latest) = (bar(400), bar(2), bar(0));
+    let (a, b, c) = (indicators(40.0), indicators(50.0), indicators(60.0));
+    assert_eq!(db.store_indicator_series("IND", &[(&old, &a), (&recent, &b), (&latest, &c)]).await.unwrap(), 3);
+    // The same day is updated in place
+    let revised = indicators(65.0);
+    db.store_indicator_series("IND", &[(&latest, &revised)]).await.unwrap();
+
+    assert_eq!(db.latest_indicator_date("IND").await.unwrap(), Some(today.date_naive()));
+    assert_eq!(db.latest_indicator_date("NONE").await.unwrap(), None);
+
+    let all = db.get_indicator_history("IND", NaiveDate::MIN).await.unwrap();
+    assert_eq!(all.iter().map(|p| p.rsi).collect::<Vec<_>>(), vec![Some(40.0), Some(50.0), Some(65.0)]);
+    assert_eq!(all[2].macd_signal, Some(0.25));
+    assert_eq!(all[2].sma_50, None);
+    let window = db.get_indicator_history("IND", (today - Duration::days(30)).date_naive()).await.unwrap();
+    assert_eq!(window.len(), 2);
+
+    assert_eq!(db.cleanup_old_indicator_history(365).await.unwrap(), 1);
+    assert_eq!(db.get_indicator_history("IND", NaiveDate::MIN).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_filter_preset_roundtrip() {
+    let temp_dir = tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("presets.db").to_string_lossy());
+    let db = Database::new(&db_url).await.unwrap();
+    db.initialize_tables().await.unwrap();
+
+    // Every optional field set, so a dropped field shows up as a mismatch
+    let full = StockFilter {
+        min_market_cap: Some(1e9),
+        max_market_cap: Some(5e11),
+        min_price: Some(5.0),
+        max_price: Some(500.0),
+        min_volume: Some(100_000),
+        max_volume: Some(50_000_000),
+        min_pct_change: Some(-5.0),
+        max_pct_change: Some(5.0),
+        min_rsi: Some(10.0),
+        max_rsi: Some(35.0),
+        sectors: Some(vec!["Technology".to_string(), "Energy".to_string()]),
+        countries: Some(vec!["United States".to_string()]),
+        industries: Some(vec!["Semiconductors".to_string()]),
+        min_ipo_year: Some(1990),
+        max_ipo_year: Some(2020),
+        oversold_rsi_threshold: Some(25.0),
+        overbought_rsi_threshold: Some(75.0),
+        exchanges: Some(vec!["NASDAQ".to_string()]),
+        min_beta: Some(0.5),
+        max_beta: Some(1.5),
+        max_pct_above_52_week_low: Some(5.0),
+        min_upside_to_target_pct: Some(15.0),
+        min_volume_vs_avg: Some(2.0),
+        max_pct_from_low: Some(10.0),
+        min_pct_from_high: Some(30.0),
+        min_rs_rank: Some(80.0),
+        max_rsi_sector_zscore: Some(-1.5),
+        min_adx: Some(25.0),
+        exclude_split_within_days: Some(30),
+        max_repaired_bar_ratio: Some(0.1),
+        recent_crossovers_only: true,
+        has_note: true,
+        opportunity_states: Some(vec![OpportunityState::New, OpportunityState::Exited]),
+        opportunity_within_hours: Some(24),
+        custom_indicator_ranges: HashMap::from([("stretch".to_string(), (None, Some(-2.0)))]),
+        min_history_bars: Some(60),
+        require_complete_data: true,
+        exclude_adrs: true,
+        adaptive_rsi: true,
+    };
+    db.save_preset("everything", &full, None, None).await.unwrap();
+    let rules = auto_analyser::opportunity::OpportunityRule::defaults();
+    db.save_preset("empty", &StockFilter::new(), Some(&rules), None).await.unwrap();
+
+    let presets = db.list_presets(false).await.unwrap();
+    assert_eq!(presets.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), ["empty", "everything"]);
+    assert_eq!(presets[1].filter, full);
+    assert_eq!(presets[0].filter, StockFilter::new());
+    assert_eq!(presets[0].opportunity_rules.as_ref(), Some(&rules));
+    assert_eq!(presets[1].opportunity_rules, None);
+
+    // Saving under an existing name replaces the filter
+    let narrower = StockFilter { max_rsi: Some(25.0), ..full };
+    db.save_preset("everything", &narrower, None, None).await.unwrap();
+    assert_eq!(db.get_preset("everything").await.unwrap().unwrap().filter, narrower);
+
+    assert!(db.delete_preset("everything", None).await.unwrap());
+    assert!(!db.delete_preset("everything", None).await.unwrap());
+    assert!(db.get_preset("everything").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_soft_delete_and_audit_log() {
+    use auto_analyser::audit::{AuditAction, AuditResource};
+
+    let temp_dir = tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("audit.db").to_string_lossy());
+    let db = Database::new(&db_url).await.unwrap();
+    db.initialize_tables().await.unwrap();
+
+    let filter = StockFilter { max_rsi: Some(30.0), ..StockFilter::new() };
+    db.save_preset("oversold", &filter, None, Some("10.0.0.1")).await.unwrap();
+    db.save_preset("oversold", &StockFilter::new(), None, None).await.unwrap();
+    assert!(db.delete_preset("oversold", None).await.unwrap());
+
+    // Deleted presets are kept but hidden
+    assert!(db.get_preset("oversold").await.unwrap().is_none());
+    assert!(db.list_presets(false).await.unwrap().is_empty());
+    let all = db.list_presets(true).await.unwrap();
+    assert_eq!(all.len(), 1);
+    assert!(all[0].deleted_at.is_some());
+
+    let restored = db.restore_preset("oversold", None).await.unwrap().unwrap();
+    assert_eq!(restored.deleted_at, None);
+    assert_eq!(restored.filter, StockFilter::new());
+    assert!(db.restore_preset("oversold", None).await.unwrap().is_none(), "nothing left to restore");
+
+    let log = db.get_audit_log(Some(AuditResource::Presets), Some("oversold"), 50).await.unwrap();
+    let actions: Vec<_> = log.iter().map(|entry| entry.action).collect();
+    assert_eq!(actions, [AuditAction::Restore, AuditAction::Delete, AuditAction::Update, AuditAction::Create]);
+    assert_eq!(log[3].source_ip.as_deref(), Some("10.0.0.1"));
+    assert_eq!(log[3].old_value, None);
+    assert_eq!(log[2].old_value.as_ref().unwrap()["filter"]["max_rsi"], 30.0);
+
+    // Re-adding a removed symbol revives it as a new entry
+    assert!(db.add_to_watchlist("NVDA", None).await.unwrap());
+    assert!(db.remove_from_watchlist("NVDA", None).await.unwrap());
+    assert!(db.get_watchlist(false).await.unwrap().is_empty());
+    assert_eq!(db.get_watchlist(true).await.unwrap().len(), 1);
+    assert!(db.add_to_watchlist("NVDA", None).await.unwrap());
+    assert_eq!(db.get_watchlist(false).await.unwrap().len(), 1);
+
+    let log = db.get_audit_log(Some(AuditResource::Watchlist), None, 50).await.unwrap();
+    assert_eq!(log.len(), 3);
+    assert_eq!(log[0].action, AuditAction::Create);
+    assert!(log[0].old_value.as_ref().unwrap()["deleted_at"].is_string());
+
+    assert_eq!(db.get_audit_log(None, None, 2).await.unwrap().len(), 2);
+    assert_eq!(db.get_audit_log(None, None, 50).await.unwrap().len(), 7);
+}
+
+#[tokio::test]
+async fn test_alert_storage() {
+    use auto_analyser::alerts::{AlertCondition, AlertTrigger, NewAlert};
+
+    let temp_dir = tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("alerts.db").to_string_lossy());
+    let db = Database::new(&db_url).await.unwrap();
+    db.initialize_tables().await.unwrap();
+
+    let new = |symbol: &str, condition_type, condition_value| NewAlert {
+        symbol: symbol.to_string(),
+        condition_type,
+        condition_value,
+    };
+    let above = db.create_alert(&new("AAPL", AlertCondition::PriceAbove, 200.0), None).await.unwrap();
+    let below = db.create_alert(&new("MSFT", AlertCondition::RsiBelow, 30.0), None).await.unwrap();
+    assert_ne!(above.id, below.id);
+
+    let alerts = db.get_alerts(false).await.unwrap();
+    assert_eq!(alerts, vec![above.clone(), below.clone()]);
+    assert_eq!(db.get_active_alerts("AAPL").await.unwrap(), vec![above.clone()]);
+
+    let trigger = AlertTrigger {
+        alert_id: above.id,
+        symbol: "AAPL".to_string(),
+        condition_type: AlertCondition::PriceAbove,
+        condition_value: 200.0,
+        observed_value: 204.5,
+        triggered_at: Utc::now(),
+    };
+    db.record_alert_trigger(&trigger).await.unwrap();
+    assert!(db.get_active_alerts("AAPL").await.unwrap()[0].is_triggered);
+    assert_eq!(db.get_alert_triggers(above.id).await.unwrap().len(), 1);
+
+    db.rearm_alert(above.id).await.unwrap();
+    assert!(!db.get_active_alerts("AAPL").await.unwrap()[0].is_triggered);
+
+    assert!(db.delete_alert(above.id, None).await.unwrap());
+    assert!(!db.delete_alert(above.id, None).await.unwrap());
+    // Deleting is soft, so the trigger history survives for a restore
+    assert_eq!(db.get_alert_triggers(above.id).await.unwrap().len(), 1);
+    assert_eq!(db.get_alerts(false).await.unwrap(), vec![below]);
+}
+
+fn cycle_result(ticker: &str, rsi: Option<f64>, is_opportunity: bool) -> StockAnalysisResult {
+    StockAnalysisResult {
+        ticker: ticker.to_string(),
+        name: format!("{} Inc", ticker),
+        current_price: Some(50.0),
+        rsi,
+        sma_20: None,
+        sma_50: None,
+        macd: None,
+        macd_signal: None,
+        macd_histogram: None,
+        volume: None,
+        pct_change: None,
+        market_cap: None,
+        sector: None,
+        industry: None,
+        exchange: None,
+        is_opportunity,
+        signals: vec![],
+        timestamp: Utc::now(),
+        data_source: None,
+        data_fetched_at: None,
+        summary: None,
+        analyst_target: None,
+        upside_to_target_pct: None,
+        obv: None,
+        vwap: None,
+        volume_vs_avg: None,
+        high_52w: None,
+        low_52w: None,
+        pct_from_high: None,
+        pct_from_low: None,
+        range_window_complete: false,
+        rs_1w: None,
+        rs_1m: None,
+        rs_3m: None,
+        rs_rank: None,
+        moving_averages: HashMap::new(),
+        crossovers: Vec::new(),
+        data_quality: None,
+        adx: None,
+        plus_di: None,
+        minus_di: None,
+        last_split: None,
+        timeframe: Timeframe::Daily,
+        anomalies: Vec::new(),
+        rsi_oversold_used: None,
+        rsi_overbought_used: None,
+        rsi_sector_zscore: None,
+        rsi_industry_zscore: None,
+        note: None,
+        opportunity_state: OpportunityState::None,
+        opportunity_streak: 0,
+        opportunity_since: None,
+        custom: HashMap::new(),
+        bars_used: None,
+    }
+}
+
+#[tokio::test]
+async fn test_session_diff() {
+    let db = Database::new("sqlite::memory:").await.unwrap();
+
+    for result in [
+        cycle_result("AAA", Some(45.0), false),
+        cycle_result("BBB", Some(25.0), true),
+        cycle_result("CCC", Some(50.0), false),
+        cycle_result("GONE", Some(20.0), true),
+    ] {
+        db.store_analysis_result(&result, "continuous_cycle_3").await.unwrap();
+    }
+    for result in [
+        cycle_result("AAA", Some(28.0), true),
+        cycle_result("BBB", Some(40.0), false),
+        cycle_result("CCC", None, false),
+        cycle_result("NEW", Some(22.0), true),
+    ] {
+        db.store_analysis_result(&result, "continuous_cycle_7").await.unwrap();
+    }
+
+    let diff = db.diff_sessions("continuous_cycle_7", "continuous_cycle_3").await.unwrap().unwrap();
+    // GONE and NEW were only analyzed in one of the two cycles
+    assert_eq!(diff.compared_tickers, 3);
+    assert_eq!(diff.new_opportunities, ["AAA"]);
+    assert_eq!(diff.dropped_opportunities, ["BBB"]);
+    let moves: Vec<(&str, f64)> = diff.rsi_changes.iter().map(|c| (c.ticker.as_str(), c.delta)).collect();
+    assert_eq!(moves, [("AAA", -17.0), ("BBB", 15.0)]);
+    assert_eq!((diff.rsi_changes[0].from, diff.rsi_changes[0].to), (45.0, 28.0));
+
+    // Reversed, the same tickers move the other way
+    let reversed = db.diff_sessions("continuous_cycle_3", "continuous_cycle_7").await.unwrap().unwrap();
+    assert_eq!(reversed.new_opportunities, ["BBB"]);
+    assert_eq!(reversed.dropped_opportunities, ["AAA"]);
+
+    assert!(db.diff_sessions("continuous_cycle_7", "continuous_cycle_99").await.unwrap().is_none());
+
+    let sessions = db.get_sessions().await.unwrap();
+    assert_eq!(sessions.len(), 2);
+    let cycle_7 = sessions.iter().find(|s| s.session_id == "continuous_cycle_7").unwrap();
+    assert_eq!((cycle_7.result_count, cycle_7.opportunities), (4, 2));
+}
+
+#[tokio::test]
+async fn test_latest_and_changed_since_results() {
+    let db = Database::new("sqlite::memory:").await.unwrap();
+    let start = Utc::now() - chrono::Duration::hours(3);
+    let at = |hours: i64| start + chrono::Duration::hours(hours);
+
+    for (ticker, rsi, timestamp, session) in [
+        ("AAA", 45.0, at(0), "s1"),
+        ("BBB", 60.0, at(0), "s1"),
+        ("AAA", 30.0, at(2), "s2"),
+        ("CCC", 55.0, at(3), "s2"),
+    ] {
+        let result = StockAnalysisResult { timestamp, ..cycle_result(ticker, Some(rsi), false) };
+        db.store_analysis_result(&result, session).await.unwrap();
+    }
+
+    assert_eq!(db.get_latest_result("AAA").await.unwrap().unwrap().rsi, Some(30.0));
+    assert!(db.get_latest_result("ZZZ").await.unwrap().is_none());
+
+    let changed = db.get_results_changed_since(at(1)).await.unwrap();
+    let tickers: Vec<(&str, Option<f64>)> = changed.iter().map(|r| (r.ticker.as_str(), r.rsi)).collect();
+    assert_eq!(tickers, [("CCC", Some(55.0)), ("AAA", Some(30.0))]);
+    // Strictly after: a result stored at the cutoff itself is not new
+    assert_eq!(db.get_results_changed_since(at(3)).await.unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_analysis_cycle_records() {
+    use auto_analyser::database::AnalysisCycle;
+
+    let db = Database::new("sqlite::memory:").await.unwrap();
+    assert_eq!(db.last_cycle_number().await.unwrap(), 0);
+
+    let started_at = Utc::now();
+    let running = AnalysisCycle {
+        cycle_number: 1,
+        session_id: "continuous_cycle_1".to_string(),
+        started_at,
+        finished_at: None,
+        tickers_analyzed: 0,
+        opportunities_found: 0,
+        error_count: 0,
+    };
+    db.record_cycle(&running).await.unwrap();
+    db.record_cycle(&AnalysisCycle { cycle_number: 2, session_id: "continuous_cycle_2".to_string(), ..running.clone() })
+        .await
+        .unwrap();
+
+    // Finishing a cycle updates its row in place
+    let finished = AnalysisCycle {
+        finished_at: Some(started_at + chrono::Duration::minutes(40)),
+        tickers_analyzed: 4890,
+        opportunities_found: 312,
+        error_count: 7,
+        ..running
+    };
+    db.record_cycle(&finished).await.unwrap();
+
+    let cycles = db.get_cycles(None).await.unwrap();
+    assert_eq!(cycles.iter().map(|c| c.cycle_number).collect::<Vec<_>>(), [2, 1]);
+    assert_eq!(cycles[1].tickers_analyzed, 4890);
+    assert_eq!(cycles[1].error_count, 7);
+    assert_eq!(cycles[1].finished_at.map(|t| t.timestamp()), finished.finished_at.map(|t| t.timestamp()));
+    assert!(cycles[0].finished_at.is_none());
+    assert_eq!(db.get_cycles(Some(1)).await.unwrap().len(), 1);
+    assert_eq!(db.last_cycle_number().await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_prune_results_applies_retention_policy() {
+    use auto_analyser::maintenance::{MaintenanceReport, RetentionPolicy};
+    use chrono::{Duration, TimeZone};
+
+    let temp_dir = tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("retention.db").to_string_lossy());
+    let db = Database::new(&db_url).await.unwrap();
+    db.initialize_tables().await.unwrap();
+
+    let now = Utc.with_ymd_and_hms(2024, 5, 31, 12, 0, 0).unwrap();
+    let day = |days_ago: i64, hour: u32| (now - Duration::days(days_ago)).date_naive().and_hms_opt(hour, 0, 0).unwrap().and_utc();
+    let sessions = [
+        ("expired", day(15, 9)),
+        ("thinned", day(5, 8)),
+        ("kept_1", day(5, 12)),
+        ("kept_2", day(5, 16)),
+        ("recent_1", day(1, 8)),
+        ("recent_2", day(1, 12)),
+        ("recent_3", day(1, 16)),
+    ];
+    for (session, timestamp) in sessions {
+        for ticker in ["AAA", "BBB"] {
+            let result = StockAnalysisResult { timestamp, ..cycle_result(ticker, Some(40.0), false) };
+            db.store_analysis_result(&result, session).await.unwrap();
+        }
+    }
+
+    let policy = RetentionPolicy { retention_days: 10, full_detail_days: 3, sessions_per_day: 2 };
+    assert_eq!(db.prune_results(&policy, now).await.unwrap(), (2, 2));
+    assert_eq!(db.get_analysis_stats().await.unwrap().total_sessions, 5);
+    assert_eq!(db.prune_results(&policy, now).await.unwrap(), (0, 0), "a second run finds nothing");
+
+    db.vacuum().await.unwrap();
+    let report = MaintenanceReport {
+        started_at: now,
+        finished_at: now + Duration::seconds(3),
+        expired_results: 2,
+        thinned_results: 2,
+        bytes_before: 81_920,
+        bytes_after: db.size_bytes().await.unwrap(),
+    };
+    db.record_maintenance(&report).await.unwrap();
+    let stats = db.get_analysis_stats().await.unwrap();
+    assert_eq!(stats.total_results, 10);
+    assert!(stats.size_bytes > 0);
+    assert_eq!(stats.last_maintenance, Some(report));
+}
+
+#[tokio::test]
+async fn test_batch_store_outpaces_per_row_inserts() {
+    use std::time::Instant;
+
+    let temp_dir = tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("test_batch.db").to_string_lossy());
+    let db = Database::new(&db_url).await.unwrap();
+    db.initialize_tables().await.unwrap();
+
+    let results: Vec<StockAnalysisResult> = (0..3000)
+        .map(|n| cycle_result(&format!("T{}", n), Some((n % 100) as f64), n % 10 == 0))
+        .collect();
+
+    let started = Instant::now();
+    for result in &results {
+        db.store_analysis_result(result, "per-row").await.unwrap();
+    }
+    let per_row = started.elapsed();
+
+    let started = Instant::now();
+    db.store_analysis_results_batch(&results, "batched").await.unwrap();
+    let batched = started.elapsed();
+
+    assert_eq!(db.get_results_by_session("per-row").await.unwrap().len(), 3000);
+    assert_eq!(db.get_results_by_session("batched").await.unwrap().len(), 3000);
+    assert!(batched * 3 < per_row, "batched {:?} vs per-row {:?}", batched, per_row);
+}
+
+#[tokio::test]
+async fn test_session_complete_marker() {
+    let temp_dir = tempdir().unwrap();
+    let db_url = format!("sqlite:{}", temp_dir.path().join("test_complete.db").to_string_lossy());
+    let db = Database::new(&db_url).await.unwrap();
+    db.initialize_tables().await.unwrap();
+
+    let results: Vec<StockAnalysisResult> = (0..600).map(|n| cycle_result(&format!("T{}", n), None, false)).collect();
+
+    // Flushed batches alone leave the session partial
+    db.store_analysis_results_batch(&results[..400], "cycle-1").await.unwrap();
+    assert!(!db.is_session_complete("cycle-1").await.unwrap());
+
+    db.complete_session_results(&results[400..], "cycle-1", Utc::now()).await.unwrap();
+    assert!(db.is_session_complete("cycle-1").await.unwrap());
+    assert_eq!(db.get_results_by_session("cycle-1").await.unwrap().len(), 600);
+
+    // A session with nothing left to store still completes
+    db.complete_session_results(&[], "empty", Utc::now()).await.unwrap();
+    assert!(db.is_session_complete("empty").await.unwrap());
+    assert!(!db.is_session_complete("unknown").await.unwrap());
+}
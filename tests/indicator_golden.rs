@@ -0,0 +1,164 @@
+//! Golden-file regression test for the full indicator stack.
+//!
+//! `tests/fixtures/indicator_series.json` holds 250 daily candles for one
+//! symbol. `tests/fixtures/indicator_golden.json` holds the expected SMA20/50,
+//! RSI14, MACD(12,26,9), Bollinger(20,2), Stochastic(14,3), ATR14, CCI20 and
+//! ADX14 values at a few fixed indices. The test recomputes everything from
+//! the fixture and requires every value to match within `TOLERANCE`.
+//!
+//! Updating the goldens: only do this when an algorithm change is meant to
+//! move the numbers.
+//!
+//! 1. Run `UPDATE_INDICATOR_GOLDENS=1 cargo test --test indicator_golden`.
+//!    This rewrites the golden file from the current code.
+//! 2. Review the diff of `indicator_golden.json` value by value. Check the
+//!    changed numbers against an independent reference implementation.
+//! 3. Commit the new goldens together with the algorithm change, and explain
+//!    in the commit message why the values moved.
+
+use auto_analyser::{compute_indicators, StockData};
+use chrono::{NaiveDate, TimeZone, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+const TOLERANCE: f64 = 1e-6;
+const REGENERATE_ENV: &str = "UPDATE_INDICATOR_GOLDENS";
+/// Indices checked: the first full SMA20 window (SMA50 and MACD still warming
+/// up), the first MACD signal, the first full SMA50 window, and points in the
+/// uptrend, near the turn and at the end
+const GOLDEN_INDICES: &[usize] = &[19, 33, 49, 120, 175, 249];
+
+#[derive(Deserialize)]
+struct Fixture {
+    symbol: String,
+    candles: Vec<Candle>,
+}
+
+#[derive(Deserialize)]
+struct Candle {
+    date: NaiveDate,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: u64,
+}
+
+type GoldenRow = BTreeMap<String, Option<f64>>;
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+fn load_series() -> Vec<StockData> {
+    let raw = std::fs::read_to_string(fixture_path("indicator_series.json")).unwrap();
+    let fixture: Fixture = serde_json::from_str(&raw).unwrap();
+    fixture
+        .candles
+        .into_iter()
+        .map(|candle| StockData {
+            symbol: fixture.symbol.clone(),
+            timestamp: Utc.from_utc_datetime(&candle.date.and_hms_opt(0, 0, 0).unwrap()),
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            volume: candle.volume,
+            adjusted_close: None,
+        })
+        .collect()
+}
+
+/// Run the full compute path and collect the observed values at each golden index
+fn observe(series: &[StockData]) -> BTreeMap<String, GoldenRow> {
+    let indicators = compute_indicators(series);
+
+    GOLDEN_INDICES
+        .iter()
+        .map(|&index| {
+            let ind = &indicators[index];
+            let bb = ind.bollinger.as_ref();
+            let stoch = ind.stochastic.as_ref();
+            let adx = ind.adx.as_ref();
+            let row: GoldenRow = [
+                ("close", Some(series[index].close)),
+                ("sma_20", ind.sma_20),
+                ("sma_50", ind.sma_50),
+                ("rsi_14", ind.rsi),
+                ("macd", ind.macd.map(|m| m.0)),
+                ("macd_signal", ind.macd.map(|m| m.1)),
+                ("macd_histogram", ind.macd.map(|m| m.2)),
+                ("bb_upper", bb.map(|b| b.upper_band)),
+                ("bb_middle", bb.map(|b| b.middle_band)),
+                ("bb_lower", bb.map(|b| b.lower_band)),
+                ("stoch_k", stoch.map(|s| s.k_percent)),
+                ("stoch_d", stoch.map(|s| s.d_percent)),
+                ("atr_14", ind.atr),
+                ("cci_20", ind.cci),
+                ("obv", ind.obv),
+                ("vwap_20", ind.vwap),
+                ("volume_vs_avg_20", ind.volume_vs_avg),
+                ("adx_14", adx.map(|a| a.adx)),
+                ("plus_di_14", adx.map(|a| a.plus_di)),
+                ("minus_di_14", adx.map(|a| a.minus_di)),
+            ]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+            (format!("{:03}", index), row)
+        })
+        .collect()
+}
+
+#[test]
+fn test_indicator_goldens() {
+    let series = load_series();
+    assert_eq!(series.len(), 250);
+    let observed = observe(&series);
+    let golden_path = fixture_path("indicator_golden.json");
+
+    if std::env::var_os(REGENERATE_ENV).is_some() {
+        let json = serde_json::to_string_pretty(&observed).unwrap();
+        std::fs::write(&golden_path, json + "\n").unwrap();
+        eprintln!("Rewrote {}; review the diff before committing", golden_path.display());
+        return;
+    }
+
+    let raw = std::fs::read_to_string(&golden_path).unwrap();
+    let golden: BTreeMap<String, GoldenRow> = serde_json::from_str(&raw).unwrap();
+    assert_eq!(
+        golden.keys().collect::<Vec<_>>(),
+        observed.keys().collect::<Vec<_>>(),
+        "golden indices changed; regenerate with {}=1",
+        REGENERATE_ENV
+    );
+
+    let mut mismatches = Vec::new();
+    for (index, expected_row) in &golden {
+        let observed_row = &observed[index];
+        assert_eq!(
+            expected_row.keys().collect::<Vec<_>>(),
+            observed_row.keys().collect::<Vec<_>>(),
+            "golden fields changed at index {}",
+            index
+        );
+        for (field, expected) in expected_row {
+            let actual = observed_row[field];
+            let matches = match (expected, actual) {
+                (Some(expected), Some(actual)) => (expected - actual).abs() <= TOLERANCE,
+                (None, None) => true,
+                _ => false,
+            };
+            if !matches {
+                mismatches.push(format!("[{}] {}: expected {:?}, got {:?}", index, field, expected, actual));
+            }
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "indicator values drifted from the goldens:\n{}",
+        mismatches.join("\n")
+    );
+}
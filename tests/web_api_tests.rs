@@ -0,0 +1,5010 @@
+use auto_analyser::clock::{Clock, TestClock};
+use auto_analyser::cache::CacheTtls;
+use auto_analyser::config::AppConfig;
+use auto_analyser::opportunity_state::OpportunityState;
+use auto_analyser::resample::Timeframe;
+use auto_analyser::web_api::{
+    build_router, compare_filters, compare_ticker_filters, needs_history_refresh, requote_result, summarize_sectors, AnalysisStatus, ApiError, AppState, RecentResults,
+    ContinuousAnalysisStatus, ContinuousState, SectorDelta, StockAnalysisResult, API_KEY_HEADER, CONTINUOUS_CYCLE_INTERVAL,
+};
+use auto_analyser::{FilterSpec, StockFilter};
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use chrono::{TimeZone, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tower::ServiceExt;
+
+fn sample_result(ticker: &str, price: f64, rsi: Option<f64>, volume: u64) -> StockAnalysisResult {
+    StockAnalysisResult {
+        ticker: ticker.to_string(),
+        name: format!("{} Inc", ticker),
+        current_price: Some(price),
+        rsi,
+        sma_20: Some(price * 0.98),
+        sma_50: Some(price * 0.95),
+        macd: Some(0.5),
+        macd_signal: Some(0.3),
+        macd_histogram: Some(0.2),
+        volume: Some(volume),
+        pct_change: Some(1.5),
+        market_cap: Some("$1.5B".to_string()),
+        sector: None,
+        industry: None,
+        exchange: None,
+        is_opportunity: false,
+        signals: vec![],
+        timestamp: Utc::now(),
+        data_source: None,
+        data_fetched_at: None,
+        summary: None,
+        analyst_target: None,
+        upside_to_target_pct: None,
+        obv: None,
+        vwap: None,
+        volume_vs_avg: None,
+        high_52w: None,
+        low_52w: None,
+        pct_from_high: None,
+        pct_from_low: None,
+        range_window_complete: false,
+        rs_1w: None,
+        rs_1m: None,
+        rs_3m: None,
+        rs_rank: None,
+        moving_averages: HashMap::new(),
+        crossovers: Vec::new(),
+        data_quality: None,
+        adx: None,
+        plus_di: None,
+        minus_di: None,
+        last_split: None,
+        timeframe: Timeframe::Daily,
+        anomalies: Vec::new(),
+        rsi_oversold_used: None,
+        rsi_overbought_used: None,
+        rsi_sector_zscore: None,
+        rsi_industry_zscore: None,
+        note: None,
+        opportunity_state: OpportunityState::None,
+        opportunity_streak: 0,
+        opportunity_since: None,
+        custom: HashMap::new(),
+        bars_used: None,
+    }
+}
+
+async fn get_json(state: AppState, uri: &str) -> (StatusCode, serde_json::Value) {
+    let response = build_router(state)
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json = if body.is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::from_slice(&body).unwrap()
+    };
+    (status, json)
+}
+
+#[tokio::test]
+async fn test_field_stats_endpoint() {
+    let state = AppState::with_database(None);
+    {
+        let mut results = state.all_results.write().await;
+        results.push(sample_result("AAA", 10.0, Some(25.0), 1_000));
+        results.push(sample_result("BBB", 20.0, None, 5_000));
+        results.push(sample_result("CCC", 30.0, Some(75.0), 180_000_000));
+    }
+
+    let (status, body) = get_json(state, "/api/results/field-stats").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let fields = body.as_array().unwrap();
+    let field = |name: &str| fields.iter().find(|f| f["field"] == name).unwrap().clone();
+
+    let price = field("current_price");
+    assert_eq!(price["count"], 3);
+    assert_eq!(price["min"], 10.0);
+    assert_eq!(price["max"], 30.0);
+    assert_eq!(price["p50"], 20.0);
+
+    let rsi = field("rsi");
+    assert_eq!(rsi["count"], 2);
+    assert_eq!(rsi["mean"], 50.0);
+
+    let volume = field("volume");
+    assert_eq!(volume["max"], 180_000_000.0);
+
+    assert_eq!(field("market_cap_value")["min"], 1_500_000_000.0);
+}
+
+#[tokio::test]
+async fn test_field_stats_cover_every_numeric_field() {
+    let state = AppState::with_database(None);
+    let result = StockAnalysisResult {
+        analyst_target: Some(12.0),
+        upside_to_target_pct: Some(20.0),
+        obv: Some(5_000.0),
+        vwap: Some(9.8),
+        volume_vs_avg: Some(1.4),
+        high_52w: Some(14.0),
+        low_52w: Some(8.0),
+        pct_from_high: Some(28.6),
+        pct_from_low: Some(25.0),
+        rs_1w: Some(1.01),
+        rs_1m: Some(1.05),
+        rs_3m: Some(1.1),
+        rs_rank: Some(80.0),
+        adx: Some(25.0),
+        plus_di: Some(22.0),
+        minus_di: Some(18.0),
+        rsi_oversold_used: Some(28.0),
+        rsi_overbought_used: Some(72.0),
+        rsi_sector_zscore: Some(-1.2),
+        rsi_industry_zscore: Some(-0.8),
+        opportunity_streak: 3,
+        ..sample_result("AAA", 10.0, Some(40.0), 1_000)
+    };
+    let numeric: Vec<String> = serde_json::to_value(&result)
+        .unwrap()
+        .as_object()
+        .unwrap()
+        .iter()
+        .filter(|(_, value)| value.is_number())
+        .map(|(key, _)| key.clone())
+        .collect();
+    state.all_results.write().await.push(result);
+
+    let (status, body) = get_json(state, "/api/results/field-stats").await;
+    assert_eq!(status, StatusCode::OK);
+    let counts: HashMap<&str, u64> =
+        body.as_array().unwrap().iter().map(|f| (f["field"].as_str().unwrap(), f["count"].as_u64().unwrap())).collect();
+    assert_eq!(numeric.len() + 1, counts.len(), "plus market_cap_value");
+    for field in numeric.iter().map(String::as_str).chain(["market_cap_value"]) {
+        assert_eq!(counts.get(field), Some(&1), "{field} is missing from the field stats");
+    }
+}
+
+#[tokio::test]
+async fn test_field_stats_reports_empty_fields() {
+    let state = AppState::with_database(None);
+    let (status, body) = get_json(state, "/api/results/field-stats").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let fields = body.as_array().unwrap();
+    assert!(!fields.is_empty());
+    assert!(fields.iter().all(|f| f["count"] == 0 && f["min"].is_null()));
+}
+
+async fn post_json(state: AppState, uri: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = build_router(state).oneshot(request).await.unwrap();
+
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+#[tokio::test]
+async fn test_filtered_results_accepts_composite_filter() {
+    let state = AppState::with_database(None);
+    {
+        let mut results = state.all_results.write().await;
+        results.push(sample_result("LOWRSI", 50.0, Some(25.0), 1_000));
+        results.push(sample_result("CHEAP", 2.0, Some(55.0), 1_000));
+        results.push(sample_result("OTHER", 50.0, Some(55.0), 1_000));
+    }
+
+    let filter = serde_json::json!({
+        "any_of": [{ "max_rsi": 30.0 }, { "max_price": 5.0 }]
+    });
+    let (status, body) = post_json(state.clone(), "/api/filtered-results", filter).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let tickers: Vec<&str> = body["results"].as_array().unwrap().iter().map(|r| r["ticker"].as_str().unwrap()).collect();
+    assert_eq!(tickers, vec!["LOWRSI", "CHEAP"]);
+
+    // Plain single filters are still accepted
+    let (status, body) = post_json(state, "/api/filtered-results", serde_json::json!({ "max_price": 5.0 })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["results"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_filtered_results_rejects_invalid_member() {
+    let state = AppState::with_database(None);
+    let filter = serde_json::json!({
+        "any_of": [{ "max_rsi": 30.0 }, { "min_price": 10.0, "max_price": 1.0 }]
+    });
+    let (status, body) = post_json(state, "/api/filtered-results", filter).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_filter");
+    assert!(body["error"]["message"].as_str().unwrap().contains("price"));
+}
+
+#[tokio::test]
+async fn test_unknown_session_returns_json_error() {
+    let state = AppState::with_database(None);
+    for uri in ["/api/analysis/no-such-session", "/api/analysis/no-such-session/results"] {
+        let (status, body) = get_json(state.clone(), uri).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "error": {
+                    "code": "session_not_found",
+                    "message": "analysis session no-such-session not found"
+                }
+            })
+        );
+    }
+}
+
+#[test]
+fn test_api_error_status_mapping() {
+    let cases = [
+        (ApiError::Upstream("nasdaq down".into()), StatusCode::BAD_GATEWAY, "upstream_error"),
+        (ApiError::InvalidFilter("bad".into()), StatusCode::BAD_REQUEST, "invalid_filter"),
+        (ApiError::SessionNotFound("x".into()), StatusCode::NOT_FOUND, "session_not_found"),
+        (ApiError::Conflict("cycle".into()), StatusCode::CONFLICT, "conflict"),
+        (ApiError::Unprocessable("no matches".into()), StatusCode::UNPROCESSABLE_ENTITY, "unprocessable"),
+        (ApiError::Database("locked".into()), StatusCode::INTERNAL_SERVER_ERROR, "database_error"),
+    ];
+    for (error, status, code) in cases {
+        assert_eq!(error.status(), status);
+        assert_eq!(error.code(), code);
+    }
+}
+
+#[test]
+fn test_api_error_from_fetch_failures() {
+    use auto_analyser::AnalyzerError;
+    use axum::response::IntoResponse;
+
+    let throttled = ApiError::from_fetch(
+        "Failed to fetch ticker list",
+        AnalyzerError::RateLimited { retry_after: Some(Duration::from_millis(1500)) },
+    );
+    assert_eq!(throttled.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(throttled.code(), "rate_limited");
+    let response = throttled.into_response();
+    assert_eq!(response.headers()["retry-after"], "2");
+
+    let down = ApiError::from_fetch("Failed to fetch ticker list", AnalyzerError::Upstream("connection reset".into()));
+    assert_eq!(down, ApiError::Upstream("Failed to fetch ticker list: connection reset".into()));
+    let invalid = ApiError::from_fetch("Failed to fetch history", AnalyzerError::InvalidRequest("bad range".into()));
+    assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+}
+
+fn compare_fixture() -> Vec<StockAnalysisResult> {
+    vec![
+        sample_result("AAA", 5.0, Some(20.0), 1_000),
+        sample_result("BBB", 15.0, Some(25.0), 1_000),
+        sample_result("CCC", 25.0, Some(45.0), 1_000),
+        sample_result("DDD", 35.0, Some(80.0), 1_000),
+        sample_result("EEE", 45.0, None, 1_000),
+    ]
+}
+
+#[test]
+fn test_compare_filters_splits_sets() {
+    let results = compare_fixture();
+    let a: FilterSpec = StockFilter::new().with_rsi_range(None, Some(30.0)).into();
+    let b: FilterSpec = StockFilter::new().with_price_range(Some(10.0), Some(30.0)).into();
+
+    let comparison = compare_filters(&results, &a, &b, 10);
+
+    assert_eq!(comparison.total_results, 5);
+    assert_eq!(comparison.only_in_a.tickers, vec!["AAA"]);
+    assert_eq!(comparison.only_in_b.tickers, vec!["CCC"]);
+    assert_eq!(comparison.in_both.tickers, vec!["BBB"]);
+    assert_eq!(comparison.summary_a.matched, 2);
+    assert_eq!(comparison.summary_a.avg_rsi, Some(22.5));
+    assert_eq!(comparison.summary_b.matched, 2);
+}
+
+#[test]
+fn test_compare_filters_truncates_with_exact_counts() {
+    let results = compare_fixture();
+    let everything: FilterSpec = StockFilter::new().into();
+    let nothing: FilterSpec = StockFilter::new().with_price_range(Some(1_000.0), None).into();
+
+    let comparison = compare_filters(&results, &everything, &nothing, 2);
+
+    assert_eq!(comparison.only_in_a.count, 5);
+    assert_eq!(comparison.only_in_a.tickers, vec!["AAA", "BBB"]);
+    assert!(comparison.only_in_a.truncated);
+    assert_eq!(comparison.only_in_b.count, 0);
+    assert!(!comparison.only_in_b.truncated);
+    assert_eq!(comparison.in_both.count, 0);
+}
+
+fn in_sector(sector: Option<&str>, result: StockAnalysisResult) -> StockAnalysisResult {
+    StockAnalysisResult { sector: sector.map(str::to_string), ..result }
+}
+
+#[test]
+fn test_summarize_sectors_skips_missing_values() {
+    let results = vec![
+        in_sector(Some("Technology"), StockAnalysisResult { pct_change: Some(-2.0), ..sample_result("AAA", 10.0, Some(20.0), 1_000) }),
+        in_sector(Some("Technology"), StockAnalysisResult { pct_change: Some(4.0), ..sample_result("BBB", 10.0, Some(f64::NAN), 1_000) }),
+        in_sector(
+            Some("Technology"),
+            StockAnalysisResult {
+                pct_change: None,
+                macd: Some(0.1),
+                macd_signal: Some(0.4),
+                is_opportunity: true,
+                ..sample_result("CCC", 10.0, None, 1_000)
+            },
+        ),
+        in_sector(Some("Technology"), StockAnalysisResult { pct_change: Some(1.0), ..sample_result("DDD", 10.0, Some(40.0), 1_000) }),
+        in_sector(Some("Energy"), StockAnalysisResult { rsi: None, macd: None, ..sample_result("EEE", 10.0, None, 1_000) }),
+    ];
+
+    let summaries = summarize_sectors(&results, 0);
+    assert_eq!(summaries.iter().map(|s| s.sector.as_str()).collect::<Vec<_>>(), ["Technology", "Energy"]);
+
+    let tech = &summaries[0];
+    assert_eq!(tech.count, 4);
+    assert_eq!(tech.avg_rsi, Some(30.0), "NaN and missing RSI are not averaged in");
+    assert_eq!(tech.median_pct_change, Some(1.0));
+    assert_eq!(tech.opportunities, 1);
+    assert_eq!((tech.macd_bullish, tech.macd_bearish), (3, 1));
+
+    let energy = &summaries[1];
+    assert_eq!(energy.avg_rsi, None);
+    assert_eq!(energy.median_pct_change, Some(1.5));
+    assert_eq!((energy.macd_bullish, energy.macd_bearish), (0, 0));
+}
+
+#[test]
+fn test_summarize_sectors_min_stocks_and_unknown() {
+    let results = vec![
+        in_sector(Some("Utilities"), sample_result("AAA", 10.0, Some(50.0), 1_000)),
+        in_sector(None, sample_result("BBB", 10.0, Some(50.0), 1_000)),
+        in_sector(Some("  "), sample_result("CCC", 10.0, Some(50.0), 1_000)),
+    ];
+
+    let summaries = summarize_sectors(&results, 2);
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].sector, "Unknown");
+    assert_eq!(summaries[0].count, 2);
+}
+
+#[tokio::test]
+async fn test_sector_summary_endpoint() {
+    let state = AppState::with_database(None);
+    state.all_results.write().await.extend([
+        in_sector(Some("Technology"), sample_result("AAA", 10.0, Some(40.0), 1_000)),
+        in_sector(Some("Technology"), sample_result("BBB", 10.0, Some(60.0), 1_000)),
+        in_sector(Some("Energy"), sample_result("CCC", 10.0, Some(50.0), 1_000)),
+    ]);
+
+    let (status, body) = get_json(state.clone(), "/api/sector-summary").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+    assert_eq!(body[0]["sector"], "Technology");
+    assert_eq!(body[0]["avg_rsi"], 50.0);
+
+    let (_, body) = get_json(state, "/api/sector-summary?min_stocks=2").await;
+    assert_eq!(body.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_compare_endpoint_reports_when_nothing_matches() {
+    let state = AppState::with_database(None);
+    state.all_results.write().await.extend(compare_fixture());
+
+    let body = serde_json::json!({
+        "filter_a": { "min_price": 1000.0 },
+        "filter_b": { "any_of": [{ "min_rsi": 99.0 }] }
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/filters/compare")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = build_router(state.clone()).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let message = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error: serde_json::Value = serde_json::from_slice(&message).unwrap();
+    assert_eq!(error["error"]["code"], "unprocessable");
+    assert!(error["error"]["message"].as_str().unwrap().starts_with("Neither filter"));
+
+    let inverted = serde_json::json!({ "filter_a": { "min_rsi": 80.0, "max_rsi": 20.0 }, "filter_b": {} });
+    let (status, error) = post_json(state.clone(), "/api/filters/compare", inverted).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(error["error"]["code"], "invalid_filter");
+    assert!(error["error"]["message"].as_str().unwrap().starts_with("filter_a: "));
+
+    let body = serde_json::json!({
+        "filter_a": { "max_rsi": 30.0 },
+        "filter_b": { "min_price": 10.0, "max_price": 30.0 },
+        "max_tickers": 1
+    });
+    let (status, json) = post_json(state, "/api/filters/compare", body).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["in_both"]["tickers"], serde_json::json!(["BBB"]));
+    assert_eq!(json["only_in_a"]["count"], 1);
+}
+
+#[tokio::test]
+async fn test_compare_endpoint_accepts_presets() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let db = state.database.as_ref().unwrap();
+    for result in compare_fixture() {
+        db.store_analysis_result(&result, "s1").await.unwrap();
+    }
+    let (status, _) =
+        post_json(state.clone(), "/api/presets", serde_json::json!({ "name": "oversold", "filter": { "max_rsi": 30.0 } })).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let body = serde_json::json!({ "preset_a": "oversold", "filter_b": { "min_price": 10.0, "max_price": 30.0 } });
+    let (status, json) = post_json(state.clone(), "/api/filters/compare", body).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["only_in_a"]["tickers"], serde_json::json!(["AAA"]));
+    assert_eq!(json["in_both"]["tickers"], serde_json::json!(["BBB"]));
+    assert_eq!(json["only_in_b"]["tickers"], serde_json::json!(["CCC"]));
+
+    let (status, json) =
+        post_json(state.clone(), "/api/filters/compare", serde_json::json!({ "preset_a": "oversold", "preset_b": "missing" })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(json["error"]["code"], "not_found");
+
+    let both = serde_json::json!({ "filter_a": {}, "preset_a": "oversold", "filter_b": {} });
+    let (status, json) = post_json(state.clone(), "/api/filters/compare", both).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"]["code"], "invalid_request");
+    let (status, _) = post_json(state, "/api/filters/compare", serde_json::json!({ "filter_a": {} })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+fn listed(symbol: &str, sector: Option<&str>, price: f64) -> auto_analyser::TickerInfo {
+    auto_analyser::TickerInfo { sector: sector.map(str::to_string), last_sale_f64: Some(price), ..ticker_info(symbol) }
+}
+
+fn compare_listing() -> Vec<auto_analyser::TickerInfo> {
+    vec![
+        listed("AAA", Some("Technology"), 5.0),
+        listed("BBB", Some("Technology"), 15.0),
+        listed("CCC", Some("Energy"), 25.0),
+        listed("DDD", None, 35.0),
+        listed("EEE", Some("Energy"), 45.0),
+    ]
+}
+
+#[test]
+fn test_compare_ticker_filters_splits_listing() {
+    let tickers = compare_listing();
+    let a: FilterSpec = StockFilter::new().with_price_range(None, Some(20.0)).into();
+    let b: FilterSpec = StockFilter::new().with_price_range(Some(10.0), Some(40.0)).into();
+
+    let comparison = compare_ticker_filters(&tickers, None, &a, &b, 10);
+    assert_eq!(comparison.total_tickers, 5);
+    assert_eq!((comparison.matched_a, comparison.matched_b), (2, 3));
+    assert_eq!(comparison.only_in_a.tickers, vec!["AAA"]);
+    assert_eq!(comparison.only_in_b.tickers, vec!["CCC", "DDD"]);
+    assert_eq!(comparison.in_both.tickers, vec!["BBB"]);
+    assert_eq!(comparison.sectors["Technology"], SectorDelta { a: 2, b: 1, delta: -1 });
+    assert_eq!(comparison.sectors["Energy"], SectorDelta { a: 0, b: 1, delta: 1 });
+    assert_eq!(comparison.sectors["Unknown"], SectorDelta { a: 0, b: 1, delta: 1 });
+
+    let comparison = compare_ticker_filters(&tickers, None, &b, &StockFilter::new().into(), 1);
+    assert_eq!(comparison.only_in_b.count, 2);
+    assert_eq!(comparison.only_in_b.tickers, vec!["AAA"]);
+    assert!(comparison.only_in_b.truncated);
+    assert_eq!((comparison.in_both.count, comparison.in_both.tickers.len()), (3, 1));
+}
+
+#[test]
+fn test_compare_ticker_filters_with_indicators() {
+    let tickers = compare_listing();
+    // EEE has no RSI and DDD is overbought; nothing is stored for AAA
+    let results: Vec<StockAnalysisResult> = compare_fixture().into_iter().filter(|r| r.ticker != "AAA").collect();
+    let a: FilterSpec = StockFilter::new().with_rsi_range(None, Some(50.0)).into();
+    let b: FilterSpec = StockFilter::new().into();
+
+    // Without indicators an RSI band screens nothing out of the listing
+    let comparison = compare_ticker_filters(&tickers, None, &a, &b, 10);
+    assert_eq!(comparison.in_both.count, 5);
+
+    let comparison = compare_ticker_filters(&tickers, Some(&results), &a, &b, 10);
+    assert_eq!(comparison.in_both.tickers, vec!["BBB", "CCC"]);
+    assert_eq!(comparison.only_in_b.tickers, vec!["DDD", "EEE"]);
+    assert_eq!(comparison.only_in_a.count, 0);
+}
+
+#[tokio::test]
+async fn test_filter_compare_endpoint_uses_cached_listing() {
+    let state = AppState::with_database(None);
+    state.cache.cache_tickers("all_tickers".to_string(), compare_listing()).await;
+    state.all_results.write().await.extend(compare_fixture());
+
+    let body = serde_json::json!({
+        "filter_a": { "min_rsi": 30.0, "max_rsi": 40.0 },
+        "filter_b": { "min_rsi": 25.0, "max_rsi": 35.0 },
+        "include_indicators": true
+    });
+    let (status, json) = post_json(state.clone(), "/api/filter-compare", body).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["only_in_a"]["tickers"], serde_json::json!([]));
+    assert_eq!(json["only_in_b"]["tickers"], serde_json::json!(["BBB"]));
+    assert_eq!(json["sectors"]["Technology"]["delta"], 1);
+
+    let body = serde_json::json!({ "filter_a": {}, "filter_b": { "sectors": ["Energy"] }, "max_tickers": 2 });
+    let (status, json) = post_json(state.clone(), "/api/filter-compare", body).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["only_in_a"]["count"], 3);
+    assert_eq!(json["only_in_a"]["truncated"], true);
+    assert_eq!(json["in_both"]["tickers"], serde_json::json!(["CCC", "EEE"]));
+
+    let body = serde_json::json!({ "filter_a": { "min_rsi": 80.0, "max_rsi": 20.0 }, "filter_b": {} });
+    let (status, _) = post_json(state, "/api/filter-compare", body).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_compare_aligns_held_series() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let db = state.database.clone().unwrap();
+    let today = Utc::now().date_naive().and_hms_opt(12, 0, 0).unwrap().and_utc();
+    let start = today - chrono::Duration::days(39);
+
+    // AAPL is cached and only rises, but misses one day; MSFT is stored and halves, then recovers
+    let mut rising = bars("AAPL", start, 1, &(0..40).map(|i| 100.0 + i as f64).collect::<Vec<_>>());
+    rising.remove(35);
+    let aapl = QuoteSeries { bars: rising, source: "fixture".to_string(), fetched_at: today, quality: None, events: Vec::new() };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("AAPL"), aapl).await;
+    let dip: Vec<f64> = (0..40).map(|i| if i < 25 { 100.0 } else if i < 30 { 50.0 } else { 110.0 }).collect();
+    db.store_stock_data(&bars("MSFT", start, 1, &dip)).await.unwrap();
+    db.store_stock_data(&bars("ONE", today, 1, &[10.0])).await.unwrap();
+
+    let (status, body) = get_json(state.clone(), "/api/compare?symbols=aapl,MSFT,NVDA,ONE,AAPL&days=30&normalize=true").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["insufficient_data"], serde_json::json!(["NVDA", "ONE"]));
+    // 31 days in the window, less the one AAPL misses
+    assert_eq!(body["dates"].as_array().unwrap().len(), 30);
+    assert_eq!(body["dates"][0], (today - chrono::Duration::days(30)).date_naive().to_string());
+    let series = body["series"].as_array().unwrap();
+    assert_eq!(series.iter().map(|s| s["symbol"].as_str().unwrap()).collect::<Vec<_>>(), ["AAPL", "MSFT"]);
+    assert!(series.iter().all(|s| s["values"][0] == 100.0 && s["values"].as_array().unwrap().len() == 30));
+    assert_eq!((series[0]["max_drawdown_pct"].as_f64(), series[1]["max_drawdown_pct"].as_f64()), (Some(0.0), Some(50.0)));
+    assert!((series[1]["total_return_pct"].as_f64().unwrap() - 10.0).abs() < 1e-9);
+    assert!(series[1]["annualized_volatility_pct"].as_f64().unwrap() > series[0]["annualized_volatility_pct"].as_f64().unwrap());
+
+    // Without normalize the closes come back as they are
+    let (_, body) = get_json(state.clone(), "/api/compare?symbols=MSFT&days=30").await;
+    assert_eq!((body["normalized"].as_bool(), body["series"][0]["values"][0].as_f64()), (Some(false), Some(100.0)));
+    assert_eq!(body["series"][0]["values"].as_array().unwrap().last().unwrap(), 110.0);
+
+    let eleven: Vec<String> = (0..11).map(|i| format!("S{}", i)).collect();
+    let (status, _) = get_json(state.clone(), &format!("/api/compare?symbols={}", eleven.join(","))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get_json(state.clone(), "/api/compare?symbols=,").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_crossover_scan_uses_held_data_only() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::{HistoryRange, TechnicalIndicators};
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let db = state.database.clone().unwrap();
+    let today = Utc::now().date_naive().and_hms_opt(12, 0, 0).unwrap().and_utc();
+    let series = |symbol: &str, closes: &[f64]| bars(symbol, today - chrono::Duration::days(closes.len() as i64 - 1), 1, closes);
+    let with_cap = |symbol: &str, cap: f64| auto_analyser::TickerInfo { market_cap_f64: Some(cap), ..ticker_info(symbol) };
+    state
+        .cache
+        .cache_tickers(
+            "all_tickers".to_string(),
+            vec![with_cap("DOWN", 1e9), with_cap("UP", 5e9), with_cap("HIST", 2e9), ticker_info("GONE")],
+        )
+        .await;
+
+    // UP is cached and turned up five days ago; DOWN only has stored bars and turned down
+    let v_shape: Vec<f64> = (0..20).map(|i| 100.0 - i as f64).chain((0..8).map(|i| 81.0 + 2.0 * i as f64)).collect();
+    let peak: Vec<f64> = (0..20).map(|i| 50.0 + i as f64).chain((0..8).map(|i| 69.0 - 2.0 * i as f64)).collect();
+    let up = QuoteSeries { bars: series("UP", &v_shape), source: "fixture".to_string(), fetched_at: today, quality: None, events: Vec::new() };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("UP"), up).await;
+    db.store_stock_data(&series("DOWN", &peak)).await.unwrap();
+
+    // HIST has stored SMA 20 and 50 values crossing up yesterday
+    let hist_bars = series("HIST", &[10.0, 10.0, 10.0, 11.0]);
+    let smas: Vec<TechnicalIndicators> = [(49.0, 50.0), (49.5, 50.0), (50.5, 50.0), (51.0, 50.0)]
+        .iter()
+        .map(|&(sma_20, sma_50)| TechnicalIndicators { sma_20: Some(sma_20), sma_50: Some(sma_50), ..Default::default() })
+        .collect();
+    let pairs: Vec<_> = hist_bars.iter().zip(&smas).collect();
+    db.store_indicator_series("HIST", &pairs).await.unwrap();
+    db.store_stock_data(&hist_bars).await.unwrap();
+
+    let scan = serde_json::json!({ "fast_period": 5, "slow_period": 10, "within_days": 7 });
+    let (status, body) = post_json(state.clone(), "/api/scan/crossovers", scan).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let found: Vec<(&str, &str)> = body["matches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| (m["symbol"].as_str().unwrap(), m["direction"].as_str().unwrap()))
+        .collect();
+    assert_eq!(found, [("UP", "up"), ("DOWN", "down")]);
+    assert!(body["matches"][0]["pct_since_cross"].as_f64().unwrap() > 0.0);
+    assert_eq!(body["matches"][0]["source"], "bars");
+    // Neither HIST's four bars nor GONE's nothing are enough, and GONE was not fetched
+    assert_eq!(body["insufficient_data"], serde_json::json!(["HIST", "GONE"]));
+    assert!(state.cache.get_quote_series(&HistoryRange::default().cache_key("GONE")).await.is_none());
+
+    let (_, body) = post_json(state.clone(), "/api/scan/crossovers", serde_json::json!({ "fast_period": 5, "slow_period": 10, "direction": "down" })).await;
+    assert_eq!(body["matches"].as_array().unwrap().len(), 1);
+    assert_eq!(body["matches"][0]["symbol"], "DOWN");
+
+    // A 20/50 scan reads the stored SMAs
+    let (_, body) = post_json(state.clone(), "/api/scan/crossovers", serde_json::json!({})).await;
+    assert_eq!(body["matches"].as_array().unwrap().len(), 1, "{}", body);
+    let hist = &body["matches"][0];
+    assert_eq!((hist["symbol"].as_str(), hist["source"].as_str()), (Some("HIST"), Some("indicator_history")));
+    assert_eq!(hist["cross_date"], (today - chrono::Duration::days(1)).date_naive().to_string());
+    assert!((hist["pct_since_cross"].as_f64().unwrap() - 10.0).abs() < 1e-9);
+    assert_eq!(body["insufficient_data"], serde_json::json!(["DOWN", "UP", "GONE"]));
+
+    let (status, _) = post_json(state, "/api/scan/crossovers", serde_json::json!({ "fast_period": 50, "slow_period": 20 })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_filter_stats_normalize_countries() {
+    let listed = |symbol: &str, name: &str, country: &str| {
+        auto_analyser::TickerInfo { name: name.to_string(), country: Some(country.to_string()), ..ticker_info(symbol) }.with_parsed_fields()
+    };
+    let state = AppState::with_database(None);
+    let tickers = vec![
+        listed("AAPL", "Apple Inc. Common Stock", "United States"),
+        listed("MSFT", "Microsoft Corporation Common Stock", "USA"),
+        listed("BIDU", "Baidu Holdings Ltd", "Cayman Islands"),
+        listed("SAP", "SAP SE ADS", "Germany"),
+    ];
+    state.cache.cache_tickers("all_tickers".to_string(), tickers).await;
+
+    let (status, body) = post_json(state.clone(), "/api/filter-stats", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["countries"], serde_json::json!({ "United States": 2, "Cayman Islands": 1, "Germany": 1 }));
+    assert_eq!(body["regions"], serde_json::json!({ "North America": 2, "Offshore": 1, "Europe": 1 }));
+    assert_eq!(body["adrs"], 2);
+
+    let (_, body) = post_json(state, "/api/filter-stats", serde_json::json!({ "exclude_adrs": true, "countries": ["usa"] })).await;
+    assert_eq!(body["filtered_tickers"], 2);
+    assert_eq!(body["adrs"], 0);
+}
+
+#[tokio::test]
+async fn test_filtered_results_include_summary_flag() {
+    let state = AppState::with_database(None);
+    state.all_results.write().await.push(StockAnalysisResult {
+        summary: Some("AAA trades at $10.00.".to_string()),
+        ..sample_result("AAA", 10.0, Some(40.0), 1_000)
+    });
+
+    let (_, body) = post_json(state.clone(), "/api/filtered-results", serde_json::json!({})).await;
+    assert!(body["results"][0].get("summary").is_none());
+
+    let (_, body) = post_json(state, "/api/filtered-results?include_summary=true", serde_json::json!({})).await;
+    assert_eq!(body["results"][0]["summary"], "AAA trades at $10.00.");
+}
+
+fn running_status(session_id: &str) -> AnalysisStatus {
+    AnalysisStatus {
+        session_id: session_id.to_string(),
+        status: "running".to_string(),
+        progress: 0.5,
+        analyzed_count: 5,
+        total_count: 10,
+        opportunities_found: 1,
+        error_message: None,
+        results: Vec::new(),
+        results_total: 0,
+        estimated_completion: None,
+        remaining_tickers: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn test_long_poll_returns_when_message_published() {
+    let state = AppState::with_database(None);
+    let publisher = state.clone();
+
+    let started = Instant::now();
+    let poll = tokio::spawn(get_json(state, "/api/events?after_seq=0&timeout=10"));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    publisher.publish(running_status("continuous"));
+
+    let (status, body) = poll.await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(body["latest_seq"], 1);
+    assert_eq!(body["messages"][0]["seq"], 1);
+    assert_eq!(body["messages"][0]["session_id"], "continuous");
+}
+
+#[tokio::test]
+async fn test_long_poll_times_out_empty_when_current() {
+    let state = AppState::with_database(None);
+    state.publish(running_status("continuous"));
+
+    let started = Instant::now();
+    let (status, body) = get_json(state, "/api/events?after_seq=1&timeout=1").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_eq!(body["latest_seq"], 1);
+    assert!(body["messages"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_long_poll_returns_backlog_immediately() {
+    let state = AppState::with_database(None);
+    state.publish(running_status("a"));
+    state.publish(running_status("b"));
+
+    let (status, body) = get_json(state, "/api/events?after_seq=1&timeout=10").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let messages = body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["session_id"], "b");
+}
+
+#[tokio::test]
+async fn test_long_poll_after_restart_returns_latest_seq_immediately() {
+    let state = AppState::with_database(None);
+    state.publish(running_status("continuous"));
+
+    // A client still holding a sequence number from before the restart
+    let started = Instant::now();
+    let (status, body) = get_json(state, "/api/events?after_seq=40&timeout=10").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(body["latest_seq"], 1);
+    assert!(body["messages"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_long_poll_rejects_waiters_over_the_limit_with_json() {
+    let state = AppState::with_database(None);
+    let permits = state.long_poll_permits.clone();
+    let _held = permits.acquire_many(permits.available_permits() as u32).await.unwrap();
+
+    let (status, body) = get_json(state, "/api/events?after_seq=0&timeout=10").await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["error"]["code"], "rate_limited");
+}
+
+fn read_only_state() -> AppState {
+    AppState::with_database(None).with_config(
+        AppConfig::new()
+            .with_read_only(true)
+            .with_admin_keys(vec!["admin-secret".to_string()]),
+    )
+}
+
+async fn post_with_key(state: AppState, uri: &str, key: Option<&str>) -> (StatusCode, serde_json::Value) {
+    let mut request = Request::builder().method("POST").uri(uri);
+    if let Some(key) = key {
+        request = request.header(API_KEY_HEADER, key);
+    }
+    let response = build_router(state)
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+}
+
+#[tokio::test]
+async fn test_read_only_mode_blocks_mutations() {
+    let (status, body) = post_with_key(read_only_state(), "/api/clear-cache", None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"], "read_only_mode");
+
+    // A key that is not allow-listed as admin does not help
+    let (status, _) = post_with_key(read_only_state(), "/api/clear-cache", Some("guest")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, body) = post_json(
+        read_only_state(),
+        "/api/analysis",
+        serde_json::json!({ "filter": {}, "max_tickers": 1 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"], "read_only_mode");
+}
+
+#[tokio::test]
+async fn test_read_only_mode_allows_reads() {
+    let state = read_only_state();
+    state.all_results.write().await.push(sample_result("READ", 10.0, Some(25.0), 1_000));
+
+    let (status, body) = get_json(state.clone(), "/api/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["read_only"], true);
+
+    let (status, body) = get_json(state.clone(), "/api/config").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["read_only"], true);
+    assert!(body.get("admin_keys").is_none());
+
+    // Query-only POST endpoints stay available
+    let (status, body) = post_json(state, "/api/filtered-results", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["results"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_read_only_mode_admin_override() {
+    let (status, body) = post_with_key(read_only_state(), "/api/clear-cache", Some("admin-secret")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "success");
+
+    // Without read-only mode nothing is blocked
+    let (status, body) = get_json(AppState::with_database(None), "/api/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["read_only"], false);
+    let (status, _) = post_with_key(AppState::with_database(None), "/api/clear-cache", None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+/// Listing and quotes that fail with whatever error the test last set
+#[derive(Default)]
+struct ProbeProvider {
+    listing_error: std::sync::Mutex<Option<auto_analyser::AnalyzerError>>,
+    quote_error: std::sync::Mutex<Option<auto_analyser::providers::ProviderError>>,
+    quotes: std::sync::atomic::AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl auto_analyser::providers::QuoteProvider for ProbeProvider {
+    fn name(&self) -> &str {
+        "probe"
+    }
+
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        _start: chrono::DateTime<Utc>,
+        _end: chrono::DateTime<Utc>,
+    ) -> Result<auto_analyser::providers::QuoteSeries, auto_analyser::providers::ProviderError> {
+        Err(auto_analyser::providers::ProviderError::NotFound { provider: "probe".to_string(), symbol: symbol.to_string() })
+    }
+
+    async fn latest_quote(&self, symbol: &str) -> Result<auto_analyser::StockData, auto_analyser::providers::ProviderError> {
+        self.quotes.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        match self.quote_error.lock().unwrap().clone() {
+            Some(e) => Err(e),
+            None => Ok(bars(symbol, Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap(), 1, &[500.0]).remove(0)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl auto_analyser::providers::MarketDataProvider for ProbeProvider {
+    async fn fetch_tickers(&self, _count: usize) -> auto_analyser::error::Result<Vec<auto_analyser::TickerInfo>> {
+        match self.listing_error.lock().unwrap().clone() {
+            Some(e) => Err(e),
+            None => Ok(vec![ticker_info("AAPL")]),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_health_probes_dependencies() {
+    use auto_analyser::providers::ProviderError;
+
+    let dir = tempfile::tempdir().unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let provider = Arc::new(ProbeProvider::default());
+    let state = watchlist_state(&dir)
+        .await
+        .with_config(AppConfig::new().with_cycle_interval(Duration::from_secs(300)))
+        .with_clock(clock.clone())
+        .with_market_data(provider.clone());
+
+    let (status, body) = get_json(state.clone(), "/api/health").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["status"], "healthy");
+    for dependency in ["listing", "quotes", "database"] {
+        assert_eq!(body["dependencies"][dependency]["status"], "ok", "{}", dependency);
+        assert_eq!(body["dependencies"][dependency]["last_success"], serde_json::json!(start));
+    }
+    assert_eq!(body["continuous_analysis"]["stalled"], false);
+
+    // Nasdaq starts throttling and the quote source goes away, but the
+    // probes only rerun once a minute
+    *provider.listing_error.lock().unwrap() = Some(auto_analyser::AnalyzerError::RateLimited { retry_after: None });
+    *provider.quote_error.lock().unwrap() =
+        Some(ProviderError::Upstream { provider: "probe".to_string(), message: "connection refused".to_string() });
+    let (_, body) = get_json(state.clone(), "/api/health").await;
+    assert_eq!(body["status"], "healthy");
+    assert_eq!(provider.quotes.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    clock.advance(Duration::from_secs(60));
+    let (status, body) = get_json(state.clone(), "/api/health").await;
+    assert_eq!(status, StatusCode::OK, "an upstream failure degrades the service without taking it down");
+    assert_eq!(body["status"], "degraded");
+    let dependencies = &body["dependencies"];
+    assert_eq!(dependencies["listing"]["status"], "degraded");
+    assert_eq!(dependencies["listing"]["last_error"], "rate limited");
+    assert_eq!(dependencies["quotes"]["status"], "down");
+    assert_eq!(dependencies["quotes"]["last_error"], "probe request failed: connection refused");
+    assert_eq!(dependencies["quotes"]["last_success"], serde_json::json!(start));
+    assert_eq!(dependencies["database"]["status"], "ok");
+
+    // Everything answers again, but the continuous loop has stopped reporting
+    *provider.listing_error.lock().unwrap() = None;
+    *provider.quote_error.lock().unwrap() = None;
+    {
+        let mut continuous = state.continuous_analysis_status.write().await;
+        continuous.state = ContinuousState::Running;
+        continuous.last_update = start;
+    }
+    clock.advance(Duration::from_secs(10 * 60));
+    let (status, body) = get_json(state, "/api/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "degraded");
+    assert_eq!(body["dependencies"]["quotes"]["status"], "ok");
+    assert_eq!(body["continuous_analysis"]["state"], "running");
+    assert_eq!(body["continuous_analysis"]["stalled"], true);
+}
+
+#[tokio::test]
+async fn test_persist_cache_endpoint() {
+    let (status, body) = post_json(AppState::with_database(None), "/api/cache/persist", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_request");
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cache.json");
+    let state = AppState::with_database(None).with_config(AppConfig::new().with_cache_file(Some(path.clone())));
+    state.cache.cache_tickers("all_tickers".to_string(), vec![ticker_info("AAPL")]).await;
+
+    let (_, stats) = get_json(state.clone(), "/api/cache-stats").await;
+    assert!(stats["last_persisted_at"].is_null());
+    let (status, body) = post_json(state.clone(), "/api/cache/persist", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["entries"], 1);
+    let (_, stats) = get_json(state, "/api/cache-stats").await;
+    assert!(stats["last_persisted_at"].is_string());
+
+    // A restarted server picks the ticker list back up
+    let restarted = AppState::with_database(None).with_config(AppConfig::new().with_cache_file(Some(path)));
+    restarted.load_cache().await;
+    let tickers = restarted.cache.get_tickers("all_tickers").await.unwrap();
+    assert_eq!(tickers[0].symbol, "AAPL");
+}
+
+#[tokio::test]
+async fn test_symbol_alias_suppresses_old_symbol_results() {
+    let state = AppState::with_database(None);
+    {
+        let mut results = state.all_results.write().await;
+        results.push(sample_result("FB", 300.0, Some(40.0), 1_000));
+        results.push(sample_result("META", 310.0, Some(42.0), 1_000));
+        results.push(sample_result("AAPL", 190.0, Some(55.0), 1_000));
+    }
+
+    let (status, body) = post_json(
+        state.clone(),
+        "/api/symbols/alias",
+        serde_json::json!({ "old_symbol": "fb", "new_symbol": "META", "effective_date": "2022-06-09" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["old_symbol"], "FB");
+
+    let (status, body) = post_json(state.clone(), "/api/filtered-results", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    let results = body["results"].as_array().unwrap();
+    let tickers: Vec<&str> = results.iter().map(|r| r["ticker"].as_str().unwrap()).collect();
+    assert_eq!(tickers, vec!["META", "AAPL"]);
+    let signals = results[0]["signals"].as_array().unwrap();
+    assert!(signals.iter().any(|s| s == "Formerly FB (renamed 2022-06-09)"));
+
+    let (status, body) = get_json(state, "/api/symbols/alias").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_symbol_alias_rejects_cycles() {
+    let state = AppState::with_database(None);
+    let alias = |old: &str, new: &str| {
+        serde_json::json!({ "old_symbol": old, "new_symbol": new, "effective_date": "2023-01-01" })
+    };
+
+    let (status, _) = post_json(state.clone(), "/api/symbols/alias", alias("A", "B")).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = post_json(state.clone(), "/api/symbols/alias", alias("B", "C")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = post_json(state.clone(), "/api/symbols/alias", alias("C", "A")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["error"]["code"], "conflict");
+    assert!(body["error"]["message"].is_string());
+    let (status, body) = post_json(state.clone(), "/api/symbols/alias", alias("D", "d")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_request");
+
+    assert_eq!(state.symbol_aliases.read().await.resolve("A").unwrap(), "C");
+}
+
+/// Upper bound on a serialized progress frame; one result is roughly 500 bytes
+const MAX_PROGRESS_FRAME_BYTES: usize = 16 * 1024;
+
+#[test]
+fn test_progress_frames_stay_small_for_large_cycles() {
+    let results: Vec<StockAnalysisResult> = (0..5000)
+        .map(|i| sample_result(&format!("T{:04}", i), 10.0 + i as f64, Some(50.0), 1_000))
+        .collect();
+
+    // Continuous cycle: only the recent tail is retained for broadcasting
+    let mut recent = RecentResults::new(10);
+    for result in &results {
+        recent.push(result.clone());
+    }
+    let frame = AnalysisStatus {
+        results: recent.to_vec(),
+        results_total: recent.total(),
+        ..running_status("continuous")
+    };
+    let state = AppState::with_database(None);
+    let bytes = serde_json::to_vec(&state.publish(frame)).unwrap();
+    assert!(bytes.len() < MAX_PROGRESS_FRAME_BYTES, "frame was {} bytes", bytes.len());
+
+    let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(json["results_total"], 5000);
+    assert_eq!(json["results"].as_array().unwrap().len(), 10);
+    assert_eq!(json["results"][9]["ticker"], "T4999");
+
+    // Session analysis: the status keeps everything, the broadcast frame does not
+    let session = AnalysisStatus {
+        results: results.clone(),
+        results_total: results.len(),
+        ..running_status("session")
+    };
+    let frame = session.progress_frame(10);
+    assert_eq!(frame.results.len(), 10);
+    assert_eq!(frame.results_total, 5000);
+    assert_eq!(frame.results[0].ticker, "T4990");
+    assert!(serde_json::to_vec(&frame).unwrap().len() < MAX_PROGRESS_FRAME_BYTES);
+    assert_eq!(session.results.len(), 5000);
+}
+
+#[tokio::test]
+async fn test_filtered_results_min_upside_to_target() {
+    let state = AppState::with_database(None);
+    {
+        let mut results = state.all_results.write().await;
+        let with_upside = |ticker: &str, upside: Option<f64>| StockAnalysisResult {
+            analyst_target: upside.map(|u| 10.0 * (1.0 + u / 100.0)),
+            upside_to_target_pct: upside,
+            ..sample_result(ticker, 10.0, Some(50.0), 1_000)
+        };
+        results.push(with_upside("UP", Some(25.0)));
+        results.push(with_upside("FLAT", Some(5.0)));
+        results.push(with_upside("STALE", Some(450.0)));
+        results.push(with_upside("NOCOVER", None));
+    }
+
+    let (status, body) = post_json(state, "/api/filtered-results", serde_json::json!({ "min_upside_to_target_pct": 15.0 })).await;
+    assert_eq!(status, StatusCode::OK);
+    let tickers: Vec<&str> = body["results"].as_array().unwrap().iter().map(|r| r["ticker"].as_str().unwrap()).collect();
+    assert_eq!(tickers, vec!["UP"]);
+    assert_eq!(body["results"][0]["upside_to_target_pct"], 25.0);
+}
+
+#[tokio::test]
+async fn test_filtered_results_min_volume_vs_avg() {
+    let state = AppState::with_database(None);
+    {
+        let mut results = state.all_results.write().await;
+        let with_ratio = |ticker: &str, ratio: Option<f64>| StockAnalysisResult {
+            volume_vs_avg: ratio,
+            ..sample_result(ticker, 10.0, Some(50.0), 1_000)
+        };
+        results.push(with_ratio("SPIKE", Some(3.2)));
+        results.push(with_ratio("USUAL", Some(1.1)));
+        results.push(with_ratio("NEW", None));
+    }
+
+    let (status, body) = post_json(state.clone(), "/api/filtered-results", serde_json::json!({ "min_volume_vs_avg": 2.0 })).await;
+    assert_eq!(status, StatusCode::OK);
+    let tickers: Vec<&str> = body["results"].as_array().unwrap().iter().map(|r| r["ticker"].as_str().unwrap()).collect();
+    assert_eq!(tickers, vec!["SPIKE"]);
+    assert_eq!(body["results"][0]["volume_vs_avg"], 3.2);
+
+    let (status, _) = post_json(state, "/api/filtered-results", serde_json::json!({ "min_volume_vs_avg": -1.0 })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_filtered_results_distance_from_range() {
+    let with_range = |ticker: &str, from_low: f64, from_high: f64| StockAnalysisResult {
+        high_52w: Some(20.0),
+        low_52w: Some(8.0),
+        pct_from_low: Some(from_low),
+        pct_from_high: Some(from_high),
+        range_window_complete: true,
+        ..sample_result(ticker, 10.0, Some(50.0), 1_000)
+    };
+    let results = [
+        with_range("NEARLOW", 4.0, 48.0),
+        with_range("MIDDLE", 40.0, 30.0),
+        with_range("NEARHIGH", 140.0, 2.0),
+        sample_result("NEW", 10.0, Some(50.0), 1_000),
+    ];
+
+    // Both the in-memory results and the SQL path over stored results
+    let memory = AppState::with_database(None);
+    memory.all_results.write().await.extend(results.clone());
+    let dir = tempfile::tempdir().unwrap();
+    let stored = watchlist_state(&dir).await;
+    for result in &results {
+        stored.database.as_ref().unwrap().store_analysis_result(result, "s1").await.unwrap();
+    }
+
+    for state in [memory, stored] {
+        let (status, body) = post_json(state.clone(), "/api/filtered-results", serde_json::json!({ "max_pct_from_low": 50.0 })).await;
+        assert_eq!(status, StatusCode::OK);
+        let mut tickers = page_tickers(&body);
+        tickers.sort();
+        assert_eq!(tickers, vec!["MIDDLE", "NEARLOW"]);
+
+        let filter = serde_json::json!({ "max_pct_from_low": 50.0, "min_pct_from_high": 35.0 });
+        let (status, body) = post_json(state.clone(), "/api/filtered-results", filter).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page_tickers(&body), vec!["NEARLOW"]);
+
+        let (status, _) = post_json(state, "/api/filtered-results", serde_json::json!({ "min_pct_from_high": -5.0 })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn test_filtered_results_exclude_recent_splits() {
+    let with_split = |ticker: &str, days_ago: Option<i64>| StockAnalysisResult {
+        last_split: days_ago.map(|days| Utc::now() - chrono::Duration::days(days)),
+        ..sample_result(ticker, 10.0, Some(50.0), 1_000)
+    };
+    let results = [with_split("FRESH", Some(5)), with_split("SETTLED", Some(90)), with_split("NEVER", None)];
+
+    let memory = AppState::with_database(None);
+    memory.all_results.write().await.extend(results.clone());
+    let dir = tempfile::tempdir().unwrap();
+    let stored = watchlist_state(&dir).await;
+    for result in &results {
+        stored.database.as_ref().unwrap().store_analysis_result(result, "s1").await.unwrap();
+    }
+
+    for state in [memory, stored] {
+        let (status, body) = post_json(state, "/api/filtered-results", serde_json::json!({ "exclude_split_within_days": 30 })).await;
+        assert_eq!(status, StatusCode::OK);
+        let mut tickers = page_tickers(&body);
+        tickers.sort();
+        assert_eq!(tickers, vec!["NEVER", "SETTLED"]);
+    }
+}
+
+#[tokio::test]
+async fn test_analysis_adjusts_for_a_recent_split() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::{CorporateEvent, HistoryRange};
+
+    let state = AppState::with_database(None);
+    state.cache.cache_tickers("all_tickers".to_string(), vec![ticker_info("SPLT")]).await;
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    // A steady climb, halved by a 2:1 split at the 50th bar
+    let closes: Vec<f64> = (0..60).map(|i| if i < 50 { 100.0 + i as f64 } else { (100.0 + i as f64) / 2.0 }).collect();
+    let split_date = start + chrono::Duration::days(50);
+    let series = QuoteSeries {
+        bars: bars("SPLT", start, 1, &closes),
+        source: "fixture".to_string(),
+        fetched_at: Utc::now(),
+        quality: None,
+        events: vec![CorporateEvent::Split { ratio: 2.0, date: split_date }],
+    };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("SPLT"), series).await;
+
+    let (status, body) = post_json(state.clone(), "/api/analysis", serde_json::json!({ "filter": {} })).await;
+    assert_eq!(status, StatusCode::OK);
+    let session = wait_for_session(&state, body["session_id"].as_str().unwrap()).await;
+
+    let result = &session.results[0];
+    assert_eq!(result.current_price, Some(79.5));
+    // Adjusted closes keep climbing through the split
+    assert!((result.sma_20.unwrap() - 74.75).abs() < 1e-9);
+    assert!(result.rsi.unwrap() > 90.0);
+    assert!(result.signals.iter().any(|s| s == "Recent 2:1 split (2023-02-21)"), "{:?}", result.signals);
+    assert_eq!(result.last_split, Some(split_date));
+}
+
+#[tokio::test]
+async fn test_filtered_results_recent_crossovers_only() {
+    use auto_analyser::events::{CrossDirection, CrossoverEvent, CrossoverKind};
+
+    let golden = CrossoverEvent {
+        kind: CrossoverKind::Sma20Sma50,
+        direction: CrossDirection::Up,
+        timestamp: Utc.with_ymd_and_hms(2024, 3, 4, 21, 0, 0).unwrap(),
+        value: 101.5,
+        reference: 101.2,
+    };
+    let results = [
+        StockAnalysisResult {
+            signals: vec![golden.signal()],
+            crossovers: vec![golden.clone()],
+            ..sample_result("FRESH", 10.0, Some(50.0), 1_000)
+        },
+        sample_result("QUIET", 10.0, Some(50.0), 1_000),
+    ];
+
+    // Both the in-memory results and the SQL path over stored results
+    let memory = AppState::with_database(None);
+    memory.all_results.write().await.extend(results.clone());
+    let dir = tempfile::tempdir().unwrap();
+    let stored = watchlist_state(&dir).await;
+    for result in &results {
+        stored.database.as_ref().unwrap().store_analysis_result(result, "s1").await.unwrap();
+    }
+
+    for state in [memory, stored] {
+        let (status, body) = post_json(state.clone(), "/api/filtered-results", serde_json::json!({ "recent_crossovers_only": true })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page_tickers(&body), vec!["FRESH"]);
+        assert_eq!(body["results"][0]["crossovers"][0]["kind"], "sma_20_50");
+        assert_eq!(body["results"][0]["crossovers"][0]["direction"], "up");
+        assert_eq!(body["results"][0]["signals"][0], "Golden Cross - SMA20 Crossed Above SMA50 (2024-03-04)");
+
+        let (_, body) = post_json(state, "/api/filtered-results", serde_json::json!({})).await;
+        assert_eq!(body["total"], 2);
+    }
+}
+
+#[tokio::test]
+async fn test_ticker_notes_are_joined_onto_results() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let db = state.database.clone().unwrap();
+    for result in [
+        sample_result("AAPL", 190.0, Some(28.0), 1_000),
+        sample_result("XOM", 110.0, Some(45.0), 1_000),
+        sample_result("MSFT", 410.0, Some(60.0), 1_000),
+    ] {
+        db.store_analysis_result(&result, "s1").await.unwrap();
+    }
+
+    let tomorrow = Utc::now() + chrono::Duration::days(1);
+    let (status, skip) = post_json(
+        state.clone(),
+        "/api/notes",
+        serde_json::json!({ "symbol": "aapl", "note": " earnings next week, skip ", "expires_at": tomorrow }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", skip);
+    assert_eq!((skip["symbol"].as_str(), skip["note"].as_str()), (Some("AAPL"), Some("earnings next week, skip")));
+    let yesterday = Utc::now() - chrono::Duration::days(1);
+    let (status, _) = post_json(state.clone(), "/api/notes", serde_json::json!({ "symbol": "XOM", "note": "stale", "expires_at": yesterday })).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = post_json(state.clone(), "/api/notes", serde_json::json!({ "symbol": "MSFT", "note": "  " })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let notes_of = |body: &serde_json::Value| -> Vec<(String, Option<String>)> {
+        body["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| (r["ticker"].as_str().unwrap().to_string(), r["note"].as_str().map(str::to_string)))
+            .collect()
+    };
+    let sorted = "/api/filtered-results?sort_by=rsi&order=asc";
+    let expected = vec![
+        ("AAPL".to_string(), Some("earnings next week, skip".to_string())),
+        ("XOM".to_string(), None),
+        ("MSFT".to_string(), None),
+    ];
+    // Paged by SQL while there are no aliases, then filtered in Rust once there are
+    for in_rust in [false, true] {
+        if in_rust {
+            let alias = serde_json::json!({ "old_symbol": "FB", "new_symbol": "META", "effective_date": "2022-06-09" });
+            let (status, _) = post_json(state.clone(), "/api/symbols/alias", alias).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (status, body) = post_json(state.clone(), sorted, serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(notes_of(&body), expected, "in rust: {}", in_rust);
+        let (_, body) = post_json(state.clone(), sorted, serde_json::json!({ "has_note": true })).await;
+        assert_eq!(page_tickers(&body), vec!["AAPL"], "in rust: {}", in_rust);
+    }
+
+    let (_, active) = get_json(state.clone(), "/api/notes").await;
+    assert_eq!(active.as_array().unwrap().len(), 1);
+    let (_, all) = get_json(state.clone(), "/api/notes?include_expired=true&symbol=xom").await;
+    assert_eq!(all[0]["note"], "stale");
+
+    // Editing and deleting show up straight away
+    let id = skip["id"].as_i64().unwrap();
+    let request = Request::builder()
+        .method("PUT")
+        .uri(format!("/api/notes/{}", id))
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::json!({ "note": "earnings done" }).to_string()))
+        .unwrap();
+    let (status, _, body) = send(state.clone(), request).await;
+    assert_eq!(status, StatusCode::OK, "{:?}", body);
+    let (_, body) = post_json(state.clone(), sorted, serde_json::json!({})).await;
+    assert_eq!(body["results"][0]["note"], "earnings done");
+    assert_eq!(delete(state.clone(), &format!("/api/notes/{}", id)).await, StatusCode::NO_CONTENT);
+    assert_eq!(delete(state.clone(), &format!("/api/notes/{}", id)).await, StatusCode::NOT_FOUND);
+    let (_, body) = post_json(state.clone(), sorted, serde_json::json!({ "has_note": true })).await;
+    assert_eq!(body["total"], 0);
+    let (_, audit) = get_json(state, "/api/audit?resource=notes").await;
+    assert_eq!(audit.as_array().unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn test_custom_indicators_are_computed_and_filtered() {
+    use auto_analyser::providers::FixtureProvider;
+
+    let dir = tempfile::tempdir().unwrap();
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    // Every bar spans 2, so ATR is 2 and the close is 1 from its 3-bar average
+    let rising: Vec<f64> = (0..60).map(|i| 100.0 + i as f64).collect();
+    let falling: Vec<f64> = (0..60).map(|i| 200.0 - i as f64).collect();
+    let provider = FixtureProvider::new()
+        .with_tickers(vec![ticker_info("UP"), ticker_info("DOWN")])
+        .with_history("UP", bars("UP", start, 1, &rising))
+        .with_history("DOWN", bars("DOWN", start, 1, &falling));
+    let state = watchlist_state(&dir).await.with_market_data(Arc::new(provider));
+
+    for (expression, error) in [("(close - sma_3) / foo", "unknown identifier \"foo\""), ("close / (2 - 2)", "division by a constant zero")] {
+        let (status, body) = post_json(state.clone(), "/api/custom-indicators", serde_json::json!({ "name": "bad", "expression": expression })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"]["message"].as_str().unwrap().starts_with(error), "{}", body);
+    }
+    let (status, created) = post_json(state.clone(), "/api/custom-indicators", serde_json::json!({ "name": "Stretch", "expression": "close" })).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", created);
+    // Saving under the same name replaces the expression
+    let stretch = serde_json::json!({ "name": "stretch", "expression": "(close - sma_3) / atr_14" });
+    let (_, replaced) = post_json(state.clone(), "/api/custom-indicators", stretch).await;
+    assert_eq!(replaced["created_at"], created["created_at"]);
+    let slow = serde_json::json!({ "name": "slow", "expression": "close - sma_100" });
+    assert_eq!(post_json(state.clone(), "/api/custom-indicators", slow).await.0, StatusCode::CREATED);
+    let (_, listed) = get_json(state.clone(), "/api/custom-indicators").await;
+    let names: Vec<_> = listed.as_array().unwrap().iter().map(|indicator| indicator["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["slow", "stretch"]);
+
+    let (_, body) = post_json(state.clone(), "/api/analysis", serde_json::json!({ "filter": {} })).await;
+    let session = wait_for_session(&state, body["session_id"].as_str().unwrap()).await;
+    let custom = |ticker: &str| session.results.iter().find(|r| r.ticker == ticker).unwrap().custom.clone();
+    // 100 bars of history aren't there for `slow`, which is left out
+    assert_eq!(custom("UP"), HashMap::from([("stretch".to_string(), 0.5)]));
+    assert_eq!(custom("DOWN"), HashMap::from([("stretch".to_string(), -0.5)]));
+
+    let stretched = serde_json::json!({ "custom_indicator_ranges": { "stretch": [0.25, null] } });
+    // Filtered by SQL while there are no aliases, then in Rust once there are
+    for in_rust in [false, true] {
+        if in_rust {
+            let alias = serde_json::json!({ "old_symbol": "FB", "new_symbol": "META", "effective_date": "2022-06-09" });
+            assert_eq!(post_json(state.clone(), "/api/symbols/alias", alias).await.0, StatusCode::OK);
+        }
+        let (status, body) = post_json(state.clone(), "/api/filtered-results", stretched.clone()).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(page_tickers(&body), vec!["UP"], "in rust: {}", in_rust);
+        assert_eq!(body["results"][0]["custom"]["stretch"], 0.5);
+        let (_, body) = post_json(state.clone(), "/api/filtered-results", serde_json::json!({ "custom_indicator_ranges": { "slow": [null, null] } })).await;
+        assert_eq!(body["total"], 0, "in rust: {}", in_rust);
+    }
+    let inverted = serde_json::json!({ "custom_indicator_ranges": { "stretch": [1.0, 0.0] } });
+    assert_eq!(post_json(state.clone(), "/api/filtered-results", inverted).await.0, StatusCode::BAD_REQUEST);
+
+    assert_eq!(delete(state.clone(), "/api/custom-indicators/STRETCH").await, StatusCode::NO_CONTENT);
+    assert_eq!(delete(state.clone(), "/api/custom-indicators/stretch").await, StatusCode::NOT_FOUND);
+    assert_eq!(state.custom_indicators.current().len(), 1);
+    let (_, audit) = get_json(state, "/api/audit?resource=custom_indicators").await;
+    assert_eq!(audit.as_array().unwrap().len(), 4);
+}
+
+fn page_tickers(body: &serde_json::Value) -> Vec<&str> {
+    body["results"].as_array().unwrap().iter().map(|r| r["ticker"].as_str().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn test_filtered_results_pagination() {
+    let state = AppState::with_database(None);
+    {
+        let mut results = state.all_results.write().await;
+        results.push(sample_result("AAA", 10.0, Some(45.0), 1_000));
+        results.push(sample_result("BBB", 10.0, None, 1_000));
+        results.push(sample_result("CCC", 10.0, Some(20.0), 1_000));
+        results.push(sample_result("DDD", 10.0, Some(60.0), 1_000));
+        results.push(sample_result("EEE", 10.0, Some(20.0), 1_000));
+    }
+
+    let (status, body) = post_json(state.clone(), "/api/filtered-results?sort_by=rsi&order=asc&per_page=2&page=2", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 5);
+    assert_eq!(body["page"], 2);
+    assert_eq!(body["per_page"], 2);
+    assert_eq!(page_tickers(&body), vec!["AAA", "DDD"]);
+
+    // Missing RSI sorts last in either direction
+    let (_, body) = post_json(state.clone(), "/api/filtered-results?sort_by=rsi&page=3&per_page=2", serde_json::json!({})).await;
+    assert_eq!(page_tickers(&body), vec!["BBB"]);
+
+    let (_, body) = post_json(state.clone(), "/api/filtered-results?page=9", serde_json::json!({})).await;
+    assert_eq!(body["total"], 5);
+    assert_eq!(body["per_page"], 100);
+    assert!(body["results"].as_array().unwrap().is_empty());
+
+    let (_, body) = post_json(state.clone(), "/api/filtered-results?per_page=5000", serde_json::json!({})).await;
+    assert_eq!(body["per_page"], 1000);
+
+    let (status, body) = post_json(state.clone(), "/api/filtered-results?sort_by=score", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_request");
+    assert_eq!(
+        body["error"]["message"],
+        "unknown sort_by 'score'; valid fields are rsi, pct_change, ticker, timestamp"
+    );
+
+    let (status, _) = post_json(state, "/api/filtered-results?page=0", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_session_results_pagination() {
+    let state = AppState::with_database(None);
+    let mut status = running_status("paged");
+    status.results = ["MSFT", "AAPL", "NVDA"]
+        .into_iter()
+        .map(|ticker| sample_result(ticker, 10.0, Some(50.0), 1_000))
+        .collect();
+    state.sessions.write().await.insert("paged".to_string(), status);
+
+    let (status, body) = get_json(state.clone(), "/api/analysis/paged/results?sort_by=ticker&order=asc&per_page=2").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 3);
+    assert_eq!(page_tickers(&body), vec!["AAPL", "MSFT"]);
+
+    let (status, body) = get_json(state, "/api/analysis/paged/results?order=sideways").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"]["message"].as_str().unwrap().contains("asc or desc"));
+}
+
+#[tokio::test]
+async fn test_filtered_results_page_from_database() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let db = state.database.clone().unwrap();
+    for (ticker, rsi, pct_change) in [("AAA", 25.0, Some(1.5)), ("BBB", 28.0, None), ("CCC", 55.0, Some(9.0)), ("DDD", 22.0, Some(-3.0))] {
+        let result = StockAnalysisResult {
+            pct_change,
+            ..sample_result(ticker, 10.0, Some(rsi), 1_000)
+        };
+        db.store_analysis_result(&result, "s1").await.unwrap();
+    }
+
+    let (status, body) = post_json(
+        state.clone(),
+        "/api/filtered-results?sort_by=pct_change&per_page=2",
+        serde_json::json!({ "max_rsi": 30.0 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 3);
+    assert_eq!(page_tickers(&body), vec!["AAA", "DDD"]);
+
+    let (_, body) = post_json(state, "/api/filtered-results?sort_by=pct_change&per_page=2&page=2", serde_json::json!({ "max_rsi": 30.0 })).await;
+    assert_eq!(page_tickers(&body), vec!["BBB"]);
+}
+
+async fn wait_for_cycle(state: &AppState, cycle: usize) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while state.continuous_analysis_status.read().await.current_cycle < cycle {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .expect("continuous analysis did not start the next cycle");
+}
+
+#[tokio::test]
+async fn test_continuous_cycle_waits_for_interval() {
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let state = AppState::with_database(None)
+        .with_config(AppConfig::new().with_benchmark_symbol(None))
+        .with_clock(clock.clone());
+    // An empty, fresh ticker list and no benchmark keep the loop off the network
+    state.cache.cache_tickers("all_tickers".to_string(), vec![]).await;
+
+    state.start_continuous_analysis().await;
+    clock.wait_for_sleepers(1).await;
+    {
+        let status = state.continuous_analysis_status.read().await;
+        assert_eq!(status.current_cycle, 1);
+        assert!(!status.is_running);
+        assert_eq!(status.last_update, start);
+    }
+
+    clock.advance(CONTINUOUS_CYCLE_INTERVAL - Duration::from_secs(1));
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(state.continuous_analysis_status.read().await.current_cycle, 1);
+
+    state.cache.cache_tickers("all_tickers".to_string(), vec![]).await;
+    clock.advance(Duration::from_secs(1));
+    wait_for_cycle(&state, 2).await;
+    clock.wait_for_sleepers(1).await;
+    let status = state.continuous_analysis_status.read().await;
+    assert_eq!(status.last_update, start + chrono::Duration::hours(1));
+    assert_eq!(clock.now(), status.last_update);
+}
+
+#[tokio::test]
+async fn test_continuous_loop_waits_for_market_open() {
+    // Saturday morning: one snapshot cycle, then nothing until Monday's open
+    let start = Utc.with_ymd_and_hms(2024, 3, 9, 8, 0, 0).unwrap();
+    let monday_open = Utc.with_ymd_and_hms(2024, 3, 11, 13, 30, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    // The ticker list has to outlast the weekend
+    let config = AppConfig {
+        cache_ttls: CacheTtls { tickers_secs: 7 * 24 * 3600, ..CacheTtls::default() },
+        benchmark_symbol: None,
+        ..AppConfig::new()
+    };
+    let state = AppState::with_database(None).with_config(config).with_clock(clock.clone());
+    state.cache.cache_tickers("all_tickers".to_string(), vec![]).await;
+
+    state.start_continuous_analysis().await;
+    clock.wait_for_sleepers(1).await;
+    assert_eq!(state.continuous_analysis_status.read().await.next_run_at, Some(monday_open));
+    let (_, body) = get_json(state.clone(), "/api/continuous-status").await;
+    assert_eq!(body["next_run_at"], "2024-03-11T13:30:00Z");
+
+    clock.advance(Duration::from_secs(6 * 3600));
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(state.continuous_analysis_status.read().await.current_cycle, 1);
+
+    clock.advance((monday_open - clock.now()).to_std().unwrap());
+    wait_for_cycle(&state, 2).await;
+    clock.wait_for_sleepers(1).await;
+    let status = state.continuous_analysis_status.read().await;
+    assert_eq!(status.last_update, monday_open);
+    assert_eq!(status.next_run_at, Some(monday_open + chrono::Duration::hours(1)));
+}
+
+#[tokio::test]
+async fn test_ignore_market_hours_keeps_hourly_cycles() {
+    let start = Utc.with_ymd_and_hms(2024, 3, 9, 8, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let state = AppState::with_database(None)
+        .with_config(AppConfig::new().with_ignore_market_hours(true).with_benchmark_symbol(None))
+        .with_clock(clock.clone());
+    state.cache.cache_tickers("all_tickers".to_string(), vec![]).await;
+
+    state.start_continuous_analysis().await;
+    clock.wait_for_sleepers(1).await;
+    assert_eq!(
+        state.continuous_analysis_status.read().await.next_run_at,
+        Some(start + chrono::Duration::hours(1))
+    );
+
+    state.cache.cache_tickers("all_tickers".to_string(), vec![]).await;
+    clock.advance(CONTINUOUS_CYCLE_INTERVAL);
+    wait_for_cycle(&state, 2).await;
+}
+
+#[tokio::test]
+async fn test_continuous_cycle_follows_config() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let config = AppConfig::new()
+        .with_cycle_interval(Duration::from_secs(120))
+        .with_benchmark_symbol(None)
+        .with_continuous_filter(Some(StockFilter::new().with_price_range(Some(50.0), None)));
+    let state = AppState::with_database(None).with_config(config).with_clock(clock.clone());
+
+    // Only BIG has cached history; analyzing PENNY would go to the network
+    let priced = |symbol: &str, price: &str| {
+        auto_analyser::TickerInfo {
+            last_sale: Some(price.to_string()),
+            ..ticker_info(symbol)
+        }
+        .with_parsed_fields()
+    };
+    state
+        .cache
+        .cache_tickers("all_tickers".to_string(), vec![priced("BIG", "$120.00"), priced("PENNY", "$0.80")])
+        .await;
+    let closes: Vec<f64> = (0..40).map(|i| 100.0 + i as f64).collect();
+    let series = QuoteSeries {
+        bars: bars("BIG", start - chrono::Duration::days(40), 1, &closes),
+        source: "fixture".to_string(),
+        fetched_at: start,
+        quality: None,
+        events: Vec::new(),
+    };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("BIG"), series).await;
+
+    state.start_continuous_analysis().await;
+    // The per-ticker delay, then the wait for the next cycle
+    clock.wait_for_sleepers(1).await;
+    clock.advance(Duration::from_millis(50));
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while state.continuous_analysis_status.read().await.is_running {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .expect("continuous cycle did not finish");
+    clock.wait_for_sleepers(1).await;
+    {
+        let status = state.continuous_analysis_status.read().await;
+        assert_eq!(status.total_count, 1, "the continuous filter skips PENNY");
+        assert_eq!(status.analyzed_count, 1);
+    }
+    // The cycle's trace shows BIG's history came from the cache
+    let (_, trace) = get_json(state.clone(), "/api/analysis/continuous_cycle_1/events?ticker=BIG").await;
+    let kinds: Vec<&str> = trace["events"].as_array().unwrap().iter().map(|e| e["event"].as_str().unwrap()).collect();
+    assert_eq!(kinds, ["started", "fetched", "analyzed"], "{}", trace);
+    assert_eq!(trace["events"][1]["cache_hit"], true);
+
+    clock.advance(Duration::from_secs(119));
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(state.continuous_analysis_status.read().await.current_cycle, 1);
+    clock.advance(Duration::from_secs(1));
+    wait_for_cycle(&state, 2).await;
+
+    let (status, body) = get_json(state, "/api/config").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["cycle_interval_secs"], 120);
+    assert_eq!(body["request_delay_ms"], 50);
+    assert_eq!(body["continuous_filter"]["min_price"], 50.0);
+    assert_eq!(body["cache_ttls"]["tickers_secs"], 3600);
+    assert_eq!(body["database_url"], "sqlite:analysis.db");
+}
+
+#[tokio::test]
+async fn test_screener_ranks_cached_tickers() {
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let state = AppState::with_database(None).with_clock(clock.clone());
+
+    let listed = |symbol: &str, market_cap: &str, pct_change: Option<&str>, volume: &str| {
+        auto_analyser::TickerInfo {
+            last_sale: Some("$25.00".to_string()),
+            market_cap: Some(market_cap.to_string()),
+            pct_change: pct_change.map(str::to_string),
+            volume: Some(volume.to_string()),
+            sector: Some("Technology".to_string()),
+            ..ticker_info(symbol)
+        }
+        .with_parsed_fields()
+    };
+    state
+        .cache
+        .cache_tickers(
+            "all_tickers".to_string(),
+            vec![
+                listed("NOCHG", "$5.0B", None, "900"),
+                listed("GAIN", "$3.0B", Some("4.5%"), "100"),
+                listed("SMALL", "$200.0M", Some("9.0%"), "5,000"),
+                listed("LOSS", "$2.0B", Some("-3.0%"), "300"),
+                listed("FLAT", "$1.5B", Some("unch"), "200"),
+                listed("BLANK", "$8.0B", Some(""), "700"),
+            ],
+        )
+        .await;
+    clock.advance(Duration::from_secs(90));
+
+    let symbols = |body: &serde_json::Value| -> Vec<String> {
+        body["tickers"].as_array().unwrap().iter().map(|t| t["symbol"].as_str().unwrap().to_string()).collect()
+    };
+
+    // Ranked by score over $1B by default; tickers without a change can't be scored
+    let (status, body) = get_json(state.clone(), "/api/screener?min_market_cap=1000000000").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(symbols(&body), ["GAIN", "FLAT", "LOSS"]);
+    assert_eq!(body["total_matches"], 3);
+    assert_eq!(body["sort_by"], "score");
+    assert_eq!(body["order"], "desc");
+    assert_eq!(body["tickers_cached_at"], serde_json::to_value(start).unwrap());
+    assert_eq!(body["tickers"][0]["pct_change_f64"], 4.5);
+    assert_eq!(body["tickers"][0]["market_cap_f64"], 3_000_000_000.0);
+
+    // None of them trades $100k a day
+    let (_, body) = get_json(state.clone(), "/api/screener?min_dollar_volume=100000").await;
+    assert_eq!(symbols(&body), ["SMALL"]);
+    assert_eq!(body["total_matches"], 1);
+
+    // Biggest gainers over $1B; missing changes go last in listing order
+    let (_, body) = get_json(state.clone(), "/api/screener?min_market_cap=1000000000&sort_by=pct_change").await;
+    assert_eq!(symbols(&body), ["GAIN", "FLAT", "LOSS", "NOCHG", "BLANK"]);
+    assert_eq!(body["total_matches"], 5);
+    assert_eq!(body["sort_by"], "pct_change");
+
+    // Losers first, still with the gaps last
+    let (_, body) = get_json(state.clone(), "/api/screener?min_market_cap=1000000000&sort_by=pct_change&order=asc&limit=4").await;
+    assert_eq!(symbols(&body), ["LOSS", "FLAT", "GAIN", "NOCHG"]);
+    assert_eq!(body["total_matches"], 5);
+
+    let (_, body) = get_json(state.clone(), "/api/screener?sort_by=volume&limit=2&sectors=Technology,Energy").await;
+    assert_eq!(symbols(&body), ["SMALL", "NOCHG"]);
+
+    let (status, body) = get_json(state.clone(), "/api/screener?sort_by=rsi").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_request");
+    let (status, _) = get_json(state.clone(), "/api/screener?pct_change_weight=-1").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, body) = get_json(state, "/api/screener?min_price=10&max_price=5").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_filter");
+}
+
+fn bars(symbol: &str, start: chrono::DateTime<Utc>, step_days: i64, closes: &[f64]) -> Vec<auto_analyser::StockData> {
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| auto_analyser::StockData {
+            symbol: symbol.to_string(),
+            timestamp: start + chrono::Duration::days(i as i64 * step_days),
+            open: close,
+            high: close + 1.0,
+            low: close - 1.0,
+            close,
+            volume: 1_000,
+            adjusted_close: None,
+        })
+        .collect()
+}
+
+fn ticker_info(symbol: &str) -> auto_analyser::TickerInfo {
+    auto_analyser::TickerInfo {
+        symbol: symbol.to_string(),
+        name: format!("{} Inc", symbol),
+        last_sale: None,
+        net_change: None,
+        pct_change: None,
+        market_cap: None,
+        country: None,
+        ipo_year: None,
+        volume: None,
+        sector: None,
+        industry: None,
+        exchange: None,
+        beta: None,
+        fifty_two_week_high: None,
+        fifty_two_week_low: None,
+        last_sale_f64: None,
+        market_cap_f64: None,
+        pct_change_f64: None,
+        volume_u64: None,
+        country_normalized: None,
+        region: None,
+        is_adr: false,
+    }
+}
+
+async fn wait_for_session(state: &AppState, session_id: &str) -> AnalysisStatus {
+    wait_for_session_status(state, session_id, |status| status == "completed").await
+}
+
+/// Poll until the session's status satisfies `done`
+async fn wait_for_session_status(state: &AppState, session_id: &str, done: impl Fn(&str) -> bool) -> AnalysisStatus {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            if let Some(session) = state.sessions.read().await.get(session_id).filter(|s| done(&s.status)) {
+                return session.clone();
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("analysis did not reach the expected status")
+}
+
+fn fixture_state() -> AppState {
+    use auto_analyser::providers::FixtureProvider;
+
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/market_data.json");
+    AppState::with_database(None).with_market_data(Arc::new(FixtureProvider::from_file(path).unwrap()))
+}
+
+#[tokio::test]
+async fn test_analysis_runs_offline_with_fixture_provider() {
+    let state = fixture_state();
+    let body = serde_json::json!({ "filter": { "sectors": ["Technology"] } });
+    let (status, body) = post_json(state.clone(), "/api/analysis", body).await;
+    assert_eq!(status, StatusCode::OK);
+    let session = wait_for_session(&state, body["session_id"].as_str().unwrap()).await;
+
+    // GHOST is listed but has no history
+    assert_eq!(session.total_count, 3);
+    let tickers: Vec<&str> = session.results.iter().map(|r| r.ticker.as_str()).collect();
+    assert_eq!(tickers, ["DIPS", "RALLY"]);
+    assert_eq!(session.opportunities_found, 2);
+
+    let dips = &session.results[0];
+    assert!(dips.rsi.unwrap() <= 30.0, "{:?}", dips.rsi);
+    assert!(dips.signals.contains(&"Opportunity rule: Oversold (RSI <= 30)".to_string()), "{:?}", dips.signals);
+    assert_eq!(dips.data_source.as_deref(), Some("fixture"));
+    assert_eq!((dips.exchange.as_deref(), dips.industry.as_deref()), (Some("NASDAQ"), Some("Semiconductors")));
+    assert!(session.results[1].signals.contains(&"Opportunity rule: Overbought (RSI >= 70)".to_string()));
+}
+
+#[tokio::test]
+async fn test_analyze_batch_returns_results_inline() {
+    let state = fixture_state();
+    let body = serde_json::json!({ "filter": { "max_rsi": 50.0 }, "symbols": ["rally", "DIPS", "dips", "GHOST", "NOPE"] });
+    let (status, batch) = post_json(state.clone(), "/api/analyze-batch", body).await;
+    assert_eq!(status, StatusCode::OK, "{}", batch);
+    assert_eq!(batch["status"], "completed");
+    assert_eq!(batch["analyzed_count"], 4);
+    // RALLY was analyzed but its overbought RSI fails the filter
+    let tickers: Vec<&str> = batch["results"].as_array().unwrap().iter().map(|r| r["ticker"].as_str().unwrap()).collect();
+    assert_eq!(tickers, ["DIPS"]);
+    assert_eq!(batch["opportunities_found"], 2);
+    assert_eq!(batch["missing"], serde_json::json!(["GHOST", "NOPE"]));
+    // The listing was never fetched, so nothing describes the symbols
+    assert!(state.ticker_directory.read().await.is_empty());
+    assert!(batch["results"][0]["exchange"].is_null());
+
+    // Kept as a session like any other
+    let session_id = batch["session_id"].as_str().unwrap();
+    let (status, session) = get_json(state.clone(), &format!("/api/analysis/{}", session_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(session["results"].as_array().unwrap().len(), 2);
+
+    let too_many: Vec<String> = (0..26).map(|i| format!("S{}", i)).collect();
+    let (status, body) = post_json(state.clone(), "/api/analyze-batch", serde_json::json!({ "filter": {}, "symbols": too_many })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"]["message"].as_str().unwrap().contains("POST /api/analysis"), "{}", body);
+    for symbols in [serde_json::json!(["^GSPC"]), serde_json::json!(["BRK/B"]), serde_json::json!([" "]), serde_json::json!([])] {
+        let (status, body) = post_json(state.clone(), "/api/analyze-batch", serde_json::json!({ "filter": {}, "symbols": symbols })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    }
+    let (status, _) = post_json(state.clone(), "/api/analyze-batch", serde_json::json!({ "filter": {} })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = post_json(state, "/api/analysis", serde_json::json!({ "filter": {}, "symbols": ["^VIX"] })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_offline_analysis_applies_request_rules() {
+    let state = fixture_state();
+    let body = serde_json::json!({
+        "filter": { "exchanges": ["NYSE", "NASDAQ"] },
+        "opportunity_rules": [{ "name": "Below SMA 50", "when": { "field": "close", "op": "<", "value": "sma_50" } }],
+    });
+    let (status, body) = post_json(state.clone(), "/api/analysis", body).await;
+    assert_eq!(status, StatusCode::OK);
+    let session = wait_for_session(&state, body["session_id"].as_str().unwrap()).await;
+
+    let flagged: Vec<(&str, bool)> = session.results.iter().map(|r| (r.ticker.as_str(), r.is_opportunity)).collect();
+    assert_eq!(flagged, [("DIPS", true), ("RALLY", false), ("CHOP", false)]);
+    assert!(session.results[0].signals.contains(&"Opportunity rule: Below SMA 50".to_string()));
+    // The request's rules replace the RSI defaults
+    assert!(!session.results[1].signals.iter().any(|s| s.starts_with("Opportunity rule")));
+}
+
+#[tokio::test]
+async fn test_interrupted_session_resumes_where_it_left_off() {
+    use auto_analyser::database::AnalysisSession;
+    use auto_analyser::providers::FixtureProvider;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/market_data.json");
+    let state = watchlist_state(&dir)
+        .await
+        .with_config(AppConfig::new().with_benchmark_symbol(None).with_request_delay(Duration::ZERO))
+        .with_market_data(Arc::new(FixtureProvider::from_file(path).unwrap()));
+    let db = state.database.clone().unwrap();
+
+    // A previous process analyzed DIPS before it stopped
+    let filter = serde_json::json!({ "exchanges": ["NYSE", "NASDAQ"] });
+    let session = AnalysisSession {
+        session_id: "before-restart".to_string(),
+        request: serde_json::from_value(serde_json::json!({ "filter": filter })).unwrap(),
+        filter: serde_json::from_value(filter).unwrap(),
+        status: "running".to_string(),
+        started_at: Utc::now(),
+        finished_at: None,
+        analyzed_count: 1,
+        total_count: 3,
+        opportunities_found: 1,
+        error_message: None,
+        remaining_tickers: Vec::new(),
+    };
+    db.record_session(&session).await.unwrap();
+    let stored = StockAnalysisResult { is_opportunity: true, ..sample_result("DIPS", 1.23, Some(25.0), 1_000) };
+    db.store_analysis_result(&stored, "before-restart").await.unwrap();
+
+    let (status, _) = post_json(state.clone(), "/api/analysis/before-restart/resume", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "a running session can't be resumed");
+
+    state.interrupt_sessions().await;
+    let interrupted = db.get_session("before-restart").await.unwrap().unwrap();
+    assert_eq!(interrupted.status, "interrupted");
+    assert!(interrupted.finished_at.is_some());
+
+    let mut rx = state.broadcast_tx.subscribe();
+    let (status, body) = post_json(state.clone(), "/api/analysis/before-restart/resume", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!((body["status"].as_str(), body["analyzed_count"].as_u64()), (Some("resumed"), Some(1)));
+    let first = rx.recv().await.unwrap();
+    let frame = first.message.as_status().unwrap();
+    assert_eq!((frame.status.as_str(), frame.session_id.as_str()), ("resumed", "before-restart"));
+
+    let session = wait_for_session(&state, "before-restart").await;
+    assert_eq!((session.analyzed_count, session.total_count), (3, 3));
+    let tickers: Vec<&str> = session.results.iter().map(|r| r.ticker.as_str()).collect();
+    assert_eq!(tickers, ["DIPS", "RALLY", "CHOP"]);
+    // DIPS kept its stored result instead of being analyzed again
+    assert_eq!(session.results[0].current_price, Some(1.23));
+    assert_eq!(session.opportunities_found, session.results.iter().filter(|r| r.is_opportunity).count());
+    assert_eq!(db.get_results_by_session("before-restart").await.unwrap().len(), 3);
+    let finished = db.get_session("before-restart").await.unwrap().unwrap();
+    assert_eq!((finished.status.as_str(), finished.analyzed_count), ("completed", 3));
+
+    let (status, _) = post_json(state.clone(), "/api/analysis/before-restart/resume", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, body) = post_json(state.clone(), "/api/analysis/missing/resume", serde_json::json!({})).await;
+    assert_eq!((status, body["error"]["code"].as_str()), (StatusCode::NOT_FOUND, Some("session_not_found")));
+    let (status, _) = post_json(AppState::with_database(None), "/api/analysis/before-restart/resume", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_session_events_trace_each_ticker() {
+    use auto_analyser::providers::FixtureProvider;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/market_data.json");
+    let state = watchlist_state(&dir)
+        .await
+        .with_config(AppConfig::new().with_benchmark_symbol(None).with_request_delay(Duration::ZERO))
+        .with_market_data(Arc::new(FixtureProvider::from_file(path.clone()).unwrap()));
+    let body = serde_json::json!({ "filter": { "sectors": ["Technology"] } });
+    let (_, body) = post_json(state.clone(), "/api/analysis", body).await;
+    let session_id = body["session_id"].as_str().unwrap().to_string();
+    wait_for_session(&state, &session_id).await;
+
+    let (status, page) = get_json(state.clone(), &format!("/api/analysis/{}/events?ticker=dips", session_id)).await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    let kinds: Vec<&str> = page["events"].as_array().unwrap().iter().map(|e| e["event"].as_str().unwrap()).collect();
+    assert_eq!(kinds.first(), Some(&"started"));
+    assert_eq!(kinds.last(), Some(&"analyzed"));
+    let fetched = page["events"].as_array().unwrap().iter().find(|e| e["event"] == "fetched").unwrap();
+    assert_eq!((fetched["source"].as_str(), fetched["cache_hit"].as_bool()), (Some("fixture"), Some(false)));
+    assert_eq!(page["dropped"], 0);
+
+    // GHOST is listed without history
+    let (_, page) = get_json(state.clone(), &format!("/api/analysis/{}/events?level=error", session_id)).await;
+    let errors = page["events"].as_array().unwrap();
+    assert_eq!(errors.len(), 1, "{}", page);
+    assert_eq!((errors[0]["ticker"].as_str(), errors[0]["event"].as_str()), (Some("GHOST"), Some("failed")));
+    assert!(errors[0]["category"].is_string());
+
+    // Paged by sequence number
+    let (_, first) = get_json(state.clone(), &format!("/api/analysis/{}/events?limit=2", session_id)).await;
+    assert_eq!(first["events"].as_array().unwrap().len(), 2);
+    let after = first["next_after_seq"].as_u64().unwrap();
+    let (_, rest) = get_json(state.clone(), &format!("/api/analysis/{}/events?after_seq={}", session_id, after)).await;
+    assert_eq!(rest["events"][0]["seq"].as_u64(), Some(after + 1));
+    assert!(rest.get("next_after_seq").is_none());
+    let total = rest["latest_seq"].as_u64().unwrap();
+
+    // After a restart the stored events are served
+    let restarted = watchlist_state(&dir).await;
+    let (status, stored) = get_json(restarted.clone(), &format!("/api/analysis/{}/events?limit=1000", session_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(stored["events"].as_array().unwrap().len() as u64, total);
+    let (_, stored_errors) = get_json(restarted, &format!("/api/analysis/{}/events?level=error&ticker=ghost", session_id)).await;
+    assert_eq!(stored_errors["events"], page["events"]);
+
+    let (status, _) = get_json(state.clone(), &format!("/api/analysis/{}/events?level=loud", session_id)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, body) = get_json(state, "/api/analysis/missing/events").await;
+    assert_eq!((status, body["error"]["code"].as_str()), (StatusCode::NOT_FOUND, Some("session_not_found")));
+}
+
+#[tokio::test]
+async fn test_analysis_uses_requested_interval_and_range() {
+    use auto_analyser::providers::{Interval, QuoteSeries};
+    use auto_analyser::HistoryRange;
+
+    let state = AppState::with_database(None);
+    state.cache.cache_tickers("all_tickers".to_string(), vec![ticker_info("WEEK")]).await;
+
+    // Daily history is cached under the plain key; a weekly request must not see it
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    let daily: Vec<f64> = (0..60).map(|i| 50.0 + i as f64).collect();
+    let weekly: Vec<f64> = (0..30).map(|i| 200.0 - i as f64).collect();
+    let range = HistoryRange::new(Some(start.date_naive()), None, Interval::Weekly);
+    for (key, closes, step) in [
+        (HistoryRange::default().cache_key("WEEK"), &daily, 1),
+        (range.cache_key("WEEK"), &weekly, 7),
+    ] {
+        let series = QuoteSeries {
+            bars: bars("WEEK", start, step, closes),
+            source: "fixture".to_string(),
+            fetched_at: Utc::now(),
+            quality: None,
+            events: Vec::new(),
+        };
+        state.cache.cache_quote_series(key, series).await;
+    }
+
+    let (status, body) = post_json(
+        state.clone(),
+        "/api/analysis",
+        serde_json::json!({ "filter": {}, "interval": "1wk", "start_date": "2023-01-02" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let session = wait_for_session(&state, body["session_id"].as_str().unwrap()).await;
+
+    assert_eq!(session.results.len(), 1);
+    let result = &session.results[0];
+    assert_eq!(result.current_price, Some(171.0));
+    // A steady weekly decline: 20 weekly closes averaged, not daily ones
+    let expected_sma: f64 = weekly[10..].iter().sum::<f64>() / 20.0;
+    assert!((result.sma_20.unwrap() - expected_sma).abs() < 1e-9);
+    assert!(result.rsi.unwrap() < 5.0);
+}
+
+#[tokio::test]
+async fn test_analysis_resamples_to_requested_timeframe() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    state.cache.cache_tickers("all_tickers".to_string(), vec![ticker_info("RISE")]).await;
+    // 20 full ISO weeks of daily bars from Monday 2 January 2023
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    let closes: Vec<f64> = (0..140).map(|i| 50.0 + i as f64).collect();
+    let series = QuoteSeries {
+        bars: bars("RISE", start, 1, &closes),
+        source: "fixture".to_string(),
+        fetched_at: Utc::now(),
+        quality: None,
+        events: Vec::new(),
+    };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("RISE"), series).await;
+
+    let mut sessions = Vec::new();
+    for timeframe in ["daily", "weekly"] {
+        let (status, body) = post_json(state.clone(), "/api/analysis", serde_json::json!({ "filter": {}, "timeframe": timeframe })).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        sessions.push(wait_for_session(&state, body["session_id"].as_str().unwrap()).await);
+    }
+
+    let weekly = &sessions[1].results[0];
+    assert_eq!(weekly.timeframe, Timeframe::Weekly);
+    assert_eq!(weekly.current_price, Some(189.0));
+    // Each week closes on its Sunday bar, 56 + 7k for week k
+    assert_eq!(weekly.sma_20, Some(122.5));
+    assert_eq!(sessions[0].results[0].timeframe, Timeframe::Daily);
+    assert_eq!(sessions[0].results[0].sma_20, Some(179.5));
+
+    // Stored side by side; the latest results stay daily
+    let db = state.database.as_ref().unwrap();
+    let stored = db.get_results_by_session(&sessions[1].session_id).await.unwrap();
+    assert_eq!(stored[0].timeframe, Timeframe::Weekly);
+    let latest = db.get_latest_results(None).await.unwrap();
+    assert_eq!(latest.len(), 1);
+    assert_eq!(latest[0].timeframe, Timeframe::Daily);
+
+    let (status, body) = post_json(
+        state,
+        "/api/analysis",
+        serde_json::json!({ "filter": {}, "interval": "1wk", "timeframe": "monthly" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["message"], "the monthly timeframe is resampled from daily bars, not 1wk");
+}
+
+/// A listing with a believable ticker and two fed bad bars: PENNY's last
+/// close is $0.0001 on a listed 14000% move, HUGE's last volume is 20B
+async fn corrupted_state(dir: &tempfile::TempDir, policy: auto_analyser::anomaly::AnomalyPolicy) -> AppState {
+    use auto_analyser::providers::FixtureProvider;
+
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    let closes: Vec<f64> = (0..60).map(|i| 50.0 + (i as f64 * 0.5).sin() * 4.0).collect();
+    let mut penny = bars("PENNY", start, 1, &closes);
+    let last = penny.last_mut().unwrap();
+    (last.open, last.high, last.low, last.close) = (0.0001, 0.0001, 0.0001, 0.0001);
+    let mut huge = bars("HUGE", start, 1, &closes);
+    huge.last_mut().unwrap().volume = 20_000_000_000;
+    let listed_move = auto_analyser::TickerInfo { pct_change: Some("14000.00%".to_string()), ..ticker_info("PENNY") };
+    let provider = FixtureProvider::new()
+        .with_tickers(vec![ticker_info("FINE"), listed_move, ticker_info("HUGE")])
+        .with_history("FINE", bars("FINE", start, 1, &closes))
+        .with_history("PENNY", penny)
+        .with_history("HUGE", huge);
+
+    watchlist_state(dir)
+        .await
+        .with_config(AppConfig::new().with_anomaly_policy(policy))
+        .with_market_data(Arc::new(provider))
+}
+
+#[tokio::test]
+async fn test_anomalous_results_are_flagged() {
+    use auto_analyser::anomaly::AnomalyPolicy;
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = corrupted_state(&dir, AnomalyPolicy::Flag).await;
+    let (status, body) = post_json(state.clone(), "/api/analysis", serde_json::json!({ "filter": {} })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let session = wait_for_session(&state, body["session_id"].as_str().unwrap()).await;
+
+    let anomalies = |ticker: &str| session.results.iter().find(|r| r.ticker == ticker).unwrap().anomalies.clone();
+    assert!(anomalies("FINE").is_empty());
+    assert_eq!(
+        anomalies("PENNY"),
+        [
+            "price 0.0001 is below 0.001",
+            "pct_change 14000.0% is beyond ±100% without the volume to support it",
+        ]
+    );
+    assert_eq!(anomalies("HUGE"), ["volume 20000000000 is not below 10000000000"]);
+
+    // Stored with the flag, and listed for inspection
+    let stored = state.database.as_ref().unwrap().get_results_by_session(&session.session_id).await.unwrap();
+    assert_eq!(stored.iter().filter(|r| !r.anomalies.is_empty()).count(), 2);
+    let (status, body) = get_json(state.clone(), "/api/anomalies").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+    assert!(body.as_array().unwrap().iter().all(|entry| entry["quarantined"] == false && entry["session_id"] == session.session_id.as_str()));
+    let (_, body) = get_json(state.clone(), "/api/anomalies?ticker=penny&limit=5").await;
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["result"]["anomalies"].as_array().unwrap().len(), 2);
+    let (_, body) = get_json(state, "/api/filtered-results/changed-since?timestamp=2000-01-01T00:00:00Z").await;
+    let fine = body.as_array().unwrap().iter().find(|r| r["ticker"] == "FINE").unwrap();
+    assert!(fine.get("anomalies").is_none(), "believable results carry no anomalies field");
+}
+
+#[tokio::test]
+async fn test_anomalous_results_are_quarantined_or_dropped() {
+    use auto_analyser::anomaly::AnomalyPolicy;
+
+    for policy in [AnomalyPolicy::Quarantine, AnomalyPolicy::Drop] {
+        let dir = tempfile::tempdir().unwrap();
+        let state = corrupted_state(&dir, policy).await;
+        let (_, body) = post_json(state.clone(), "/api/analysis", serde_json::json!({ "filter": {} })).await;
+        let session = wait_for_session(&state, body["session_id"].as_str().unwrap()).await;
+
+        let tickers: Vec<_> = session.results.iter().map(|r| r.ticker.as_str()).collect();
+        assert_eq!(tickers, ["FINE"], "{:?}", policy);
+        let stored = state.database.as_ref().unwrap().get_results_by_session(&session.session_id).await.unwrap();
+        assert_eq!(stored.len(), 1);
+
+        let (_, body) = get_json(state.clone(), "/api/anomalies").await;
+        let listed = body.as_array().unwrap();
+        match policy {
+            AnomalyPolicy::Quarantine => {
+                assert_eq!(listed.len(), 2);
+                assert!(listed.iter().all(|entry| entry["quarantined"] == true));
+                assert_eq!(listed.iter().filter(|entry| entry["result"]["ticker"] == "HUGE").count(), 1);
+            }
+            _ => assert!(listed.is_empty()),
+        }
+    }
+
+    // The same screen applies to results analyzed on demand
+    let dir = tempfile::tempdir().unwrap();
+    let state = corrupted_state(&dir, AnomalyPolicy::Quarantine).await;
+    let (status, _) = post_json(state.clone(), "/api/analyze-now/penny", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let db = state.database.clone().unwrap();
+    let quarantined = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let anomalies = db.get_anomalies(None, 10).await.unwrap();
+            if !anomalies.is_empty() {
+                return anomalies;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the on-demand result was never quarantined");
+    assert_eq!(quarantined[0].session_id, "on_demand");
+    assert!(state.all_results.read().await.is_empty(), "a quarantined result never becomes current");
+}
+
+#[tokio::test]
+async fn test_adaptive_rsi_uses_each_symbols_own_thresholds() {
+    use auto_analyser::providers::FixtureProvider;
+
+    // A steady uptrend whose RSI rarely dips, ending in a shallow pullback
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    let mut closes: Vec<f64> = (0..300).map(|i| 100.0 + i as f64 * 0.4 + (i as f64 * 0.7).sin() * 1.5).collect();
+    let peak = *closes.last().unwrap();
+    closes.extend((1..=4).map(|i| peak - i as f64 * 1.5));
+    let provider = FixtureProvider::new()
+        .with_tickers(vec![ticker_info("CLIMB"), ticker_info("NEW")])
+        .with_history("CLIMB", bars("CLIMB", start, 1, &closes))
+        .with_history("NEW", bars("NEW", start, 1, &closes[closes.len() - 60..]));
+    let state = AppState::with_database(None).with_market_data(Arc::new(provider));
+
+    let body = serde_json::json!({ "filter": { "adaptive_rsi": true } });
+    let (status, body) = post_json(state.clone(), "/api/analysis", body).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let session = wait_for_session(&state, body["session_id"].as_str().unwrap()).await;
+    let result = |ticker: &str| session.results.iter().find(|r| r.ticker == ticker).unwrap().clone();
+
+    // The pullback is nothing by the static thresholds but rare for this symbol
+    let climb = result("CLIMB");
+    let (rsi, oversold) = (climb.rsi.unwrap(), climb.rsi_oversold_used.unwrap());
+    assert!(rsi > 30.0 && rsi <= oversold, "RSI {} against {}", rsi, oversold);
+    assert!(climb.rsi_overbought_used.unwrap() > 70.0);
+    assert!(climb.is_opportunity);
+    assert!(climb.signals.iter().any(|s| s == "Oversold - Potential Buy"), "{:?}", climb.signals);
+    assert!(climb.signals.iter().any(|s| s.starts_with(&format!("Opportunity rule: Oversold (RSI <= {}", oversold))), "{:?}", climb.signals);
+
+    // Too short a history to have a distribution of its own
+    let new = result("NEW");
+    assert_eq!((new.rsi_oversold_used, new.rsi_overbought_used), (Some(30.0), Some(70.0)));
+    assert!(!new.is_opportunity);
+
+    // Static filters neither use nor report them
+    let (_, body) = post_json(state.clone(), "/api/analysis", serde_json::json!({ "filter": {} })).await;
+    let session = wait_for_session(&state, body["session_id"].as_str().unwrap()).await;
+    assert!(session.results.iter().all(|r| !r.is_opportunity && r.rsi_oversold_used.is_none() && r.rsi_overbought_used.is_none()));
+    let json = serde_json::to_value(&session.results[0]).unwrap();
+    assert!(json.get("rsi_oversold_used").is_none());
+}
+
+#[tokio::test]
+async fn test_short_histories_never_become_opportunities() {
+    use auto_analyser::providers::FixtureProvider;
+    use auto_analyser::web_api::filter_results;
+
+    // The same steady slide, pinning RSI near zero, over 200 bars and over its last 20
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    let closes: Vec<f64> = (0..200).map(|i| 300.0 - i as f64).collect();
+    let provider = FixtureProvider::new()
+        .with_tickers(vec![ticker_info("LONG"), ticker_info("THIN")])
+        .with_history("LONG", bars("LONG", start, 1, &closes))
+        .with_history("THIN", bars("THIN", start + chrono::Duration::days(180), 1, &closes[180..]));
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await.with_market_data(Arc::new(provider));
+
+    let (status, body) = post_json(state.clone(), "/api/analysis", serde_json::json!({ "filter": {} })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let session = wait_for_session(&state, body["session_id"].as_str().unwrap()).await;
+    let result = |ticker: &str| session.results.iter().find(|r| r.ticker == ticker).unwrap().clone();
+
+    let long = result("LONG");
+    assert_eq!(long.bars_used, Some(200));
+    assert!(long.is_opportunity);
+    assert!(long.signals.iter().any(|s| s == "Oversold - Potential Buy"), "{:?}", long.signals);
+    assert!(!long.signals.iter().any(|s| s.starts_with("Insufficient history")), "{:?}", long.signals);
+
+    // Its RSI is still reported, but neither rules nor signals act on it
+    let thin = result("THIN");
+    assert_eq!(thin.bars_used, Some(20));
+    assert!(thin.rsi.unwrap() < 30.0, "{:?}", thin.rsi);
+    assert!(!thin.is_opportunity);
+    assert!(thin.signals.contains(&"Insufficient history: 20 of 60 bars".to_string()), "{:?}", thin.signals);
+    assert!(!thin.signals.iter().any(|s| s == "Oversold - Potential Buy" || s.starts_with("Opportunity rule")), "{:?}", thin.signals);
+    assert!(thin.crossovers.is_empty());
+
+    // The screener can leave thin histories out, in SQL and in memory
+    let (status, body) = post_json(state.clone(), "/api/filtered-results", serde_json::json!({ "min_history_bars": 60 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let tickers: Vec<&str> = body["results"].as_array().unwrap().iter().map(|r| r["ticker"].as_str().unwrap()).collect();
+    assert_eq!(tickers, ["LONG"]);
+    let filter = StockFilter::new().with_min_history_bars(Some(60)).into();
+    let kept = filter_results(&session.results, &filter);
+    assert_eq!(kept.iter().map(|r| r.ticker.as_str()).collect::<Vec<_>>(), ["LONG"]);
+}
+
+#[tokio::test]
+async fn test_dry_run_plans_cache_hits_without_starting_a_session() {
+    use auto_analyser::message_log::BroadcastMessage;
+    use auto_analyser::providers::{FixtureProvider, QuoteSeries};
+    use auto_analyser::HistoryRange;
+
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    let closes: Vec<f64> = (0..60).map(|i| 50.0 + (i as f64 * 0.5).sin() * 5.0).collect();
+    let symbols = ["AAA", "BBB", "CCC", "DDD", "EEE"];
+    let mut provider = FixtureProvider::new().with_tickers(symbols.iter().map(|s| ticker_info(s)).collect());
+    for symbol in symbols {
+        provider = provider.with_history(symbol, bars(symbol, start, 1, &closes));
+    }
+    let state = AppState::with_database(None).with_market_data(Arc::new(provider));
+    for symbol in ["AAA", "CCC", "EEE"] {
+        let series = QuoteSeries {
+            bars: bars(symbol, start, 1, &closes),
+            source: "fixture".to_string(),
+            fetched_at: Utc::now(),
+            quality: None,
+            events: Vec::new(),
+        };
+        state.cache.cache_quote_series(HistoryRange::default().cache_key(symbol), series).await;
+    }
+
+    // EEE is cached but left out by max_analysis
+    let request = serde_json::json!({ "filter": {}, "max_analysis": 4, "dry_run": true });
+    let (status, plan) = post_json(state.clone(), "/api/analysis", request).await;
+    assert_eq!(status, StatusCode::OK, "{}", plan);
+    assert_eq!((plan["total_tickers"].as_u64(), plan["filtered_tickers"].as_u64()), (Some(5), Some(5)));
+    assert_eq!((plan["planned_tickers"].as_u64(), plan["cache_hits"].as_u64(), plan["fresh_fetches"].as_u64()), (Some(4), Some(2), Some(2)));
+    // Nothing has been timed yet, so the defaults apply: 2 * 20ms + 2 * 500ms
+    assert_eq!(plan["timing_samples"], 0);
+    assert_eq!(plan["estimated_duration_secs"], 1.04);
+    assert!(plan["estimated_completion"].is_string());
+    assert!(plan.get("session_id").is_none());
+    assert!(state.sessions.read().await.is_empty());
+    assert!(state.message_log.since(0, usize::MAX).is_empty());
+
+    // A history range of its own is cached under other keys
+    let request = serde_json::json!({ "filter": {}, "start_date": "2023-01-02", "dry_run": true });
+    let (_, plan) = post_json(state.clone(), "/api/analysis", request).await;
+    assert_eq!((plan["cache_hits"].as_u64(), plan["fresh_fetches"].as_u64()), (Some(0), Some(5)));
+
+    // A real run estimates its completion while running and times its tickers
+    let (_, body) = post_json(state.clone(), "/api/analysis", serde_json::json!({ "filter": {}, "max_analysis": 4 })).await;
+    let session = wait_for_session(&state, body["session_id"].as_str().unwrap()).await;
+    assert_eq!(session.results.len(), 4);
+    assert_eq!(session.estimated_completion, None);
+    let running_estimates = state
+        .message_log
+        .since(0, usize::MAX)
+        .into_iter()
+        .filter(|m| matches!(&m.message, BroadcastMessage::Status(s) if s.status == "running" && s.estimated_completion.is_some()))
+        .count();
+    assert!(running_estimates > 0);
+
+    // The fetched histories are cached now, and the averages are measured
+    let request = serde_json::json!({ "filter": {}, "dry_run": true });
+    let (_, plan) = post_json(state.clone(), "/api/analysis", request).await;
+    assert_eq!((plan["cache_hits"].as_u64(), plan["fresh_fetches"].as_u64()), (Some(5), Some(0)));
+    assert_eq!(plan["timing_samples"], 4);
+    assert_eq!(state.sessions.read().await.len(), 1);
+}
+
+/// Fixture history served after a pause, like a slow upstream
+struct SlowProvider {
+    delay: Duration,
+    fixture: auto_analyser::providers::FixtureProvider,
+}
+
+#[async_trait::async_trait]
+impl auto_analyser::providers::QuoteProvider for SlowProvider {
+    fn name(&self) -> &str {
+        "slow"
+    }
+
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        start: chrono::DateTime<Utc>,
+        end: chrono::DateTime<Utc>,
+    ) -> Result<auto_analyser::providers::QuoteSeries, auto_analyser::providers::ProviderError> {
+        tokio::time::sleep(self.delay).await;
+        self.fixture.fetch_history(symbol, start, end).await
+    }
+}
+
+#[async_trait::async_trait]
+impl auto_analyser::providers::MarketDataProvider for SlowProvider {
+    async fn fetch_tickers(&self, count: usize) -> auto_analyser::error::Result<Vec<auto_analyser::TickerInfo>> {
+        self.fixture.fetch_tickers(count).await
+    }
+}
+
+#[tokio::test]
+async fn test_abandoned_batch_still_finishes_its_session() {
+    use auto_analyser::providers::FixtureProvider;
+
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    let closes: Vec<f64> = (0..40).map(|i| 20.0 + (i as f64 * 0.3).sin()).collect();
+    let symbols = ["S0", "S1", "S2"];
+    let mut fixture = FixtureProvider::new();
+    for symbol in symbols {
+        fixture = fixture.with_history(symbol, bars(symbol, start, 1, &closes));
+    }
+    let state = AppState::with_database(None).with_market_data(Arc::new(SlowProvider { delay: Duration::from_millis(100), fixture }));
+
+    // The client gives up while the first ticker is still being fetched
+    let body = serde_json::json!({ "filter": {}, "symbols": symbols });
+    let request = post_json(state.clone(), "/api/analyze-batch", body);
+    assert!(tokio::time::timeout(Duration::from_millis(50), request).await.is_err());
+
+    let session_id = state.sessions.read().await.keys().next().cloned().expect("the batch started a session");
+    let session = wait_for_session_status(&state, &session_id, |status| status != "running").await;
+    assert_eq!(session.status, "completed");
+    assert_eq!(session.results.len(), 3);
+}
+
+#[tokio::test]
+async fn test_session_budgets_stop_cleanly_and_leave_the_rest() {
+    use auto_analyser::providers::FixtureProvider;
+
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    let closes: Vec<f64> = (0..40).map(|i| 20.0 + (i as f64 * 0.3).sin()).collect();
+    let symbols: Vec<String> = (0..10).map(|i| format!("S{}", i)).collect();
+    let mut fixture = FixtureProvider::new().with_tickers(symbols.iter().map(|s| ticker_info(s)).collect());
+    for symbol in &symbols {
+        fixture = fixture.with_history(symbol, bars(symbol, start, 1, &closes));
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir)
+        .await
+        .with_market_data(Arc::new(SlowProvider { delay: Duration::from_millis(300), fixture }));
+    let db = state.database.clone().unwrap();
+
+    // Tickers start at 0, 0.3, 0.6 and 0.9s; the one due at 1.2s is not started
+    let body = serde_json::json!({ "filter": {}, "max_duration_secs": 1 });
+    let (status, body) = post_json(state.clone(), "/api/analysis", body).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let session_id = body["session_id"].as_str().unwrap().to_string();
+    let session = wait_for_session_status(&state, &session_id, |status| status != "running").await;
+    assert_eq!(session.status, "budget_exhausted");
+    let analyzed = session.analyzed_count;
+    assert!((1..10).contains(&analyzed), "analyzed {}", analyzed);
+    assert_eq!(session.results.len(), analyzed);
+    let message = session.error_message.clone().unwrap();
+    assert!(message.starts_with("max_duration_secs of 1 reached"), "{}", message);
+    assert!(message.ends_with(&format!("{} of 10 tickers analyzed, {} remaining", analyzed, 10 - analyzed)), "{}", message);
+
+    // Everything gathered so far was stored and broadcast
+    assert_eq!(db.get_results_by_session(&session_id).await.unwrap().len(), analyzed);
+    let frames: Vec<AnalysisStatus> = state
+        .message_log
+        .since(0, usize::MAX)
+        .into_iter()
+        .filter_map(|m| match m.message {
+            auto_analyser::message_log::BroadcastMessage::Status(status) => Some(status),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(frames.last().unwrap().status, "budget_exhausted");
+    assert!(!db.is_session_complete(&session_id).await.unwrap());
+
+    let (status, remaining) = get_json(state.clone(), &format!("/api/analysis/{}/remaining", session_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(remaining["status"], "budget_exhausted");
+    assert_eq!(remaining["analyzed_count"].as_u64(), Some(analyzed as u64));
+    assert_eq!(remaining["remaining_count"].as_u64(), Some((10 - analyzed) as u64));
+    assert_eq!(remaining["tickers"], serde_json::json!(symbols[analyzed..]));
+    // Stored with the session too
+    assert_eq!(db.get_session(&session_id).await.unwrap().unwrap().remaining_tickers, symbols[analyzed..]);
+
+    // A follow-up covers just those, here stopping after two fresh fetches
+    let body = serde_json::json!({ "filter": {}, "symbols": remaining["tickers"], "max_upstream_requests": 2 });
+    let (_, body) = post_json(state.clone(), "/api/analysis", body).await;
+    let follow_up = body["session_id"].as_str().unwrap().to_string();
+    let session = wait_for_session_status(&state, &follow_up, |status| status != "running").await;
+    assert_eq!(session.status, "budget_exhausted");
+    assert_eq!((session.total_count, session.analyzed_count), (10 - analyzed, 2));
+    assert_eq!(session.results[0].ticker, symbols[analyzed]);
+    let (_, remaining) = get_json(state.clone(), &format!("/api/analysis/{}/remaining", follow_up)).await;
+    assert_eq!(remaining["tickers"], serde_json::json!(symbols[analyzed + 2..]));
+
+    // Sessions that finish their list leave nothing; budgets must be positive
+    let (_, body) = post_json(state.clone(), "/api/analysis", serde_json::json!({ "filter": {}, "symbols": ["S0"] })).await;
+    let done = body["session_id"].as_str().unwrap().to_string();
+    assert_eq!(wait_for_session_status(&state, &done, |status| status != "running").await.status, "completed");
+    let (_, remaining) = get_json(state.clone(), &format!("/api/analysis/{}/remaining", done)).await;
+    assert_eq!(remaining["tickers"], serde_json::json!([]));
+    let (status, _) = post_json(state.clone(), "/api/analysis", serde_json::json!({ "filter": {}, "max_upstream_requests": 0 })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get_json(state.clone(), "/api/analysis/nope/remaining").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_analysis_uses_indicator_config() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+
+    let state = AppState::with_database(None);
+    state.cache.cache_tickers("all_tickers".to_string(), vec![ticker_info("EMA")]).await;
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    let closes: Vec<f64> = (0..40).map(|i| 10.0 + i as f64).collect();
+    let series = QuoteSeries {
+        bars: bars("EMA", start, 1, &closes),
+        source: "fixture".to_string(),
+        fetched_at: Utc::now(),
+        quality: None,
+        events: Vec::new(),
+    };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("EMA"), series).await;
+
+    let (status, body) = post_json(
+        state.clone(),
+        "/api/analysis",
+        serde_json::json!({ "filter": {}, "indicator_config": { "sma_periods": [5], "ema_periods": [3] } }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let session = wait_for_session(&state, body["session_id"].as_str().unwrap()).await;
+
+    let result = &session.results[0];
+    // A straight line: every average of the last n closes is the middle one
+    assert_eq!(result.moving_averages.get("sma_5"), Some(&47.0));
+    assert_eq!(result.moving_averages.get("ema_3"), Some(&48.0));
+    assert_eq!(result.moving_averages.len(), 2);
+    assert_eq!(result.sma_20, None);
+
+    let (status, body) = post_json(
+        state,
+        "/api/analysis",
+        serde_json::json!({ "filter": {}, "indicator_config": { "macd_fast": 30 } }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_request");
+}
+
+#[tokio::test]
+async fn test_analysis_rejects_inverted_date_range() {
+    let (status, body) = post_json(
+        AppState::with_database(None),
+        "/api/analysis",
+        serde_json::json!({ "filter": {}, "start_date": "2024-02-01", "end_date": "2024-01-01" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_request");
+
+    let (status, _) = post_json(
+        AppState::with_database(None),
+        "/api/analysis",
+        serde_json::json!({ "filter": {}, "interval": "5m" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // 15-minute bars exist, but not that far back
+    let (status, body) = post_json(
+        AppState::with_database(None),
+        "/api/analysis",
+        serde_json::json!({ "filter": {}, "interval": "15m", "start_date": "2020-01-01" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"]["message"].as_str().unwrap().starts_with("15m bars only go back 60 days"));
+}
+
+async fn watchlist_state(dir: &tempfile::TempDir) -> AppState {
+    use auto_analyser::database::Database;
+
+    let db_url = format!("sqlite:{}", dir.path().join("watchlist.db").to_string_lossy());
+    let db = Database::new(&db_url).await.unwrap();
+    db.initialize_tables().await.unwrap();
+    AppState::with_database(Some(Arc::new(db)))
+}
+
+/// Serves whatever listing the test last set, and history from `history`
+struct ListingProvider {
+    tickers: std::sync::Mutex<Vec<auto_analyser::TickerInfo>>,
+    history: auto_analyser::providers::FixtureProvider,
+}
+
+impl ListingProvider {
+    fn new(tickers: Vec<auto_analyser::TickerInfo>) -> Arc<Self> {
+        Arc::new(Self {
+            tickers: std::sync::Mutex::new(tickers),
+            history: auto_analyser::providers::FixtureProvider::new(),
+        })
+    }
+
+    fn list(&self, tickers: Vec<auto_analyser::TickerInfo>) {
+        *self.tickers.lock().unwrap() = tickers;
+    }
+}
+
+#[async_trait::async_trait]
+impl auto_analyser::providers::QuoteProvider for ListingProvider {
+    fn name(&self) -> &str {
+        "listing"
+    }
+
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        start: chrono::DateTime<Utc>,
+        end: chrono::DateTime<Utc>,
+    ) -> Result<auto_analyser::providers::QuoteSeries, auto_analyser::providers::ProviderError> {
+        self.history.fetch_history(symbol, start, end).await
+    }
+}
+
+#[async_trait::async_trait]
+impl auto_analyser::providers::MarketDataProvider for ListingProvider {
+    async fn fetch_tickers(&self, _count: usize) -> auto_analyser::error::Result<Vec<auto_analyser::TickerInfo>> {
+        Ok(self.tickers.lock().unwrap().clone())
+    }
+}
+
+fn in_sector_info(symbol: &str, sector: &str) -> auto_analyser::TickerInfo {
+    auto_analyser::TickerInfo { sector: Some(sector.to_string()), ..ticker_info(symbol) }
+}
+
+/// Wait for the background refresh of an expired ticker listing to land
+async fn wait_for_ticker_refresh(state: &AppState) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while state.cache.get_cache_stats().await.tickers_refreshing {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("the ticker listing never refreshed");
+}
+
+#[tokio::test]
+async fn test_symbol_universe_file_reloads_when_rewritten() {
+    use auto_analyser::symbol_universe::SymbolUniverse;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("universe.txt");
+    std::fs::write(&path, "AAPL\nXOM,Exxon Mobil,Energy\n").unwrap();
+    let screener = ListingProvider::new(vec![in_sector_info("MSFT", "Technology")]);
+    let state = AppState::with_database(None)
+        .with_market_data(screener)
+        .with_symbol_universe(Arc::new(SymbolUniverse::load(&path).unwrap()));
+    state.watch_symbol_universe();
+    let symbols = |body: serde_json::Value| -> Vec<String> {
+        body.as_array().unwrap().iter().map(|ticker| ticker["symbol"].as_str().unwrap().to_string()).collect()
+    };
+
+    let (status, body) = get_json(state.clone(), "/api/tickers").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(symbols(body.clone()), ["AAPL", "XOM"], "the screener is not consulted");
+    assert_eq!(body[1]["sector"], "Energy");
+
+    // Written aside and renamed over the original, as pipelines tend to
+    let staged = dir.path().join("universe.txt.tmp");
+    std::fs::write(&staged, "symbol,name,sector\nAAPL,Apple,Technology\nNVDA,NVIDIA,Technology\n").unwrap();
+    std::fs::rename(&staged, &path).unwrap();
+    let body = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            let (_, body) = get_json(state.clone(), "/api/tickers").await;
+            if symbols(body.clone()) != ["AAPL", "XOM"] {
+                break body;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("the rewritten universe was never picked up");
+    assert_eq!(symbols(body.clone()), ["AAPL", "NVDA"]);
+    assert_eq!(body[0]["name"], "Apple");
+    assert_eq!(state.ticker_directory.read().await.entries().count(), 3, "the new listing reached the directory");
+
+    // An emptied file keeps the last good universe
+    std::fs::write(&path, "").unwrap();
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    let (_, body) = get_json(state, "/api/tickers").await;
+    assert_eq!(symbols(body), ["AAPL", "NVDA"]);
+}
+
+#[tokio::test]
+async fn test_unreadable_symbol_universe_stops_startup() {
+    use auto_analyser::symbol_universe::{SymbolSource, UniverseError};
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = |path: std::path::PathBuf| AppState::new(AppConfig::new().with_symbol_source(SymbolSource::File { path, watch: false }));
+
+    let missing = state(dir.path().join("missing.txt")).await;
+    assert!(matches!(missing, Err(UniverseError::Read { .. })), "no fallback to the screener");
+
+    let header_only = dir.path().join("header.csv");
+    std::fs::write(&header_only, "symbol,name,sector\n").unwrap();
+    assert!(matches!(state(header_only).await, Err(UniverseError::Empty { .. })));
+
+    let binary = dir.path().join("binary.txt");
+    std::fs::write(&binary, [0xff, 0xfe, 0x00, 0x41]).unwrap();
+    assert!(matches!(state(binary).await, Err(UniverseError::Read { .. })));
+}
+
+#[tokio::test]
+async fn test_ticker_directory_tracks_listing_changes() {
+    use auto_analyser::message_log::{BroadcastEvent, BroadcastMessage};
+
+    let dir = tempfile::tempdir().unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 5, 1, 14, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let provider = ListingProvider::new(vec![in_sector_info("AAPL", "Technology"), in_sector_info("XOM", "Energy")]);
+    let state = watchlist_state(&dir)
+        .await
+        .with_config(AppConfig::new().with_delist_after_missing_fetches(1))
+        .with_clock(clock.clone())
+        .with_market_data(provider.clone());
+    let mut rx = state.broadcast_tx.subscribe();
+
+    // The first listing is the baseline
+    let (status, _) = get_json(state.clone(), "/api/tickers").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(rx.try_recv().is_err());
+
+    provider.list(vec![in_sector_info("AAPL", "Consumer Electronics"), in_sector_info("ARM", "Technology")]);
+    // Within the ticker TTL the cached listing is served and nothing changes
+    get_json(state.clone(), "/api/tickers").await;
+    assert!(rx.try_recv().is_err());
+    clock.advance(Duration::from_secs(3601));
+    // The expired listing is served while it refreshes, then the new one
+    get_json(state.clone(), "/api/tickers").await;
+    assert!(rx.try_recv().is_err());
+    wait_for_ticker_refresh(&state).await;
+    get_json(state.clone(), "/api/tickers").await;
+
+    let message = rx.try_recv().unwrap();
+    let json = serde_json::to_value(&message).unwrap();
+    assert_eq!(json["type"], "ticker_directory_update");
+    assert_eq!(json["added"], serde_json::json!(["ARM"]));
+    assert_eq!(json["removed"], serde_json::json!([]));
+    assert_eq!(json["reclassified"][0]["previous_sector"], "Technology");
+    assert_eq!(json["reclassified"][0]["sector"], "Consumer Electronics");
+
+    let (status, body) = get_json(state.clone(), "/api/tickers/new?since=2024-05-01T14:00:00Z").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["symbol"], "ARM");
+    assert_eq!(body[0]["first_seen"], "2024-05-01T15:00:01Z");
+
+    // XOM has now missed two fetches, more than the one allowed
+    clock.advance(Duration::from_secs(3601));
+    get_json(state.clone(), "/api/tickers").await;
+    wait_for_ticker_refresh(&state).await;
+    get_json(state.clone(), "/api/tickers").await;
+    match rx.try_recv().unwrap().message {
+        BroadcastMessage::Event(BroadcastEvent::TickerDirectoryUpdate(update)) => assert_eq!(update.removed, ["XOM"]),
+        other => panic!("unexpected broadcast {:?}", other),
+    }
+    let (_, body) = get_json(state.clone(), "/api/tickers/removed").await;
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!((&body[0]["symbol"], &body[0]["missing_fetches"]), (&serde_json::json!("XOM"), &serde_json::json!(2)));
+
+    // The directory outlives a restart
+    let restarted = AppState::with_database(state.database.clone()).with_config(AppConfig::new().with_delist_after_missing_fetches(1));
+    restarted.load_ticker_directory().await;
+    let directory = restarted.ticker_directory.read().await;
+    assert_eq!(directory.len(), 3);
+    assert_eq!(directory.get("ARM").unwrap().first_seen, start + chrono::Duration::seconds(3601));
+    assert_eq!(directory.get("AAPL").unwrap().info.sector.as_deref(), Some("Consumer Electronics"));
+    assert_eq!(directory.removed().len(), 1);
+}
+
+#[tokio::test]
+async fn test_continuous_loop_includes_new_listings() {
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let priced = |symbol: &str, price: &str| {
+        auto_analyser::TickerInfo { last_sale: Some(price.to_string()), ..ticker_info(symbol) }.with_parsed_fields()
+    };
+    let provider = ListingProvider::new(vec![priced("BIG", "$120.00")]);
+    let config = AppConfig::new()
+        .with_benchmark_symbol(None)
+        .with_request_delay(Duration::ZERO)
+        .with_continuous_filter(Some(StockFilter::new().with_price_range(Some(50.0), None)));
+    // Without a stale window each cycle waits for the expired listing to refresh
+    let config = AppConfig { cache_ttls: CacheTtls { tickers_max_stale_secs: 0, ..CacheTtls::default() }, ..config };
+    let state = AppState::with_database(None)
+        .with_config(config)
+        .with_clock(clock.clone())
+        .with_market_data(provider.clone());
+
+    state.start_continuous_analysis().await;
+    wait_for_cycle(&state, 1).await;
+    clock.wait_for_sleepers(1).await;
+    assert_eq!(state.continuous_analysis_status.read().await.total_count, 1);
+
+    // A penny stock the filter would skip is analyzed because it is new
+    provider.list(vec![priced("BIG", "$120.00"), priced("NEWCO", "$0.80"), priced("TINY", "$0.50")]);
+    clock.advance(CONTINUOUS_CYCLE_INTERVAL);
+    wait_for_cycle(&state, 2).await;
+    clock.wait_for_sleepers(1).await;
+    assert_eq!(state.continuous_analysis_status.read().await.total_count, 3);
+
+    // Once picked up, new listings go back to being filtered
+    clock.advance(CONTINUOUS_CYCLE_INTERVAL);
+    wait_for_cycle(&state, 3).await;
+    clock.wait_for_sleepers(1).await;
+    assert_eq!(state.continuous_analysis_status.read().await.total_count, 1);
+}
+
+#[tokio::test]
+async fn test_continuous_cycle_scores_rsi_within_sectors() {
+    use auto_analyser::providers::FixtureProvider;
+
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    // Five energy names sliding by different amounts, and two technology
+    // names too few for statistics of their own
+    let history_start = start - chrono::Duration::days(80);
+    let mut provider = FixtureProvider::new();
+    let mut tickers = Vec::new();
+    for (symbol, sector, drift) in [
+        ("OIL1", "Energy", -0.9),
+        ("OIL2", "Energy", -0.6),
+        ("OIL3", "Energy", -0.3),
+        ("OIL4", "Energy", 0.0),
+        ("OIL5", "Energy", 0.3),
+        ("CHIP", "Technology", 0.6),
+        ("SOFT", "Technology", 0.9),
+    ] {
+        let closes: Vec<f64> = (0..60).map(|i| 100.0 + drift * i as f64 + (i as f64 * 0.9).sin() * 2.0).collect();
+        provider = provider.with_history(symbol, bars(symbol, history_start, 1, &closes));
+        tickers.push(auto_analyser::TickerInfo { sector: Some(sector.to_string()), ..ticker_info(symbol) });
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir)
+        .await
+        .with_config(AppConfig::new().with_benchmark_symbol(None).with_request_delay(Duration::ZERO))
+        .with_clock(clock.clone())
+        .with_market_data(Arc::new(provider.with_tickers(tickers)));
+
+    state.start_continuous_analysis().await;
+    // Step past the per-ticker delays until the finished cycle is scored
+    let db = state.database.clone().unwrap();
+    let session_id = "continuous_cycle_1";
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while db.get_group_stats(session_id).await.unwrap().is_empty() {
+            clock.advance(Duration::from_millis(10));
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .expect("the cycle was not scored");
+
+    let results = state.all_results.read().await.clone();
+    let zscore = |ticker: &str| results.iter().find(|r| r.ticker == ticker).unwrap().rsi_sector_zscore.unwrap();
+    assert!(zscore("OIL1") < zscore("OIL3") && zscore("OIL3") < zscore("OIL5"));
+    // Energy's scores are against Energy's own mean, so they sum to zero
+    let energy: f64 = ["OIL1", "OIL2", "OIL3", "OIL4", "OIL5"].iter().map(|t| zscore(t)).sum();
+    assert!(energy.abs() < 1e-9, "{}", energy);
+    assert!(zscore("CHIP") > 0.0 && zscore("SOFT") > zscore("CHIP"), "technology is scored against the market");
+
+    let (status, stats) = get_json(state.clone(), &format!("/api/cycles/{}/sector-stats", session_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let groups: Vec<(&str, &str, u64)> = stats
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|g| (g["scope"].as_str().unwrap(), g["name"].as_str().unwrap(), g["names"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(groups, [("market", "", 7), ("sector", "Energy", 5)]);
+    let (status, _) = get_json(state.clone(), "/api/cycles/nope/sector-stats").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Screening for the most oversold within their sector
+    let (status, body) = post_json(state.clone(), "/api/filtered-results", serde_json::json!({ "max_rsi_sector_zscore": -1.0 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(page_tickers(&body), vec!["OIL1"]);
+    let stored = db.get_results_by_session(session_id).await.unwrap();
+    assert_eq!(stored.len(), 7);
+    assert!(stored.iter().all(|r| r.rsi_sector_zscore.is_some() && r.rsi_industry_zscore.is_some()));
+}
+
+#[tokio::test]
+async fn test_idle_wait_warms_the_cache_for_the_next_cycle() {
+    use auto_analyser::providers::FixtureProvider;
+
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let mut provider = FixtureProvider::new();
+    let mut tickers = Vec::new();
+    for symbol in ["WRMA", "WRMB", "WRMC"] {
+        let closes: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 * 0.7).sin() * 3.0).collect();
+        provider = provider.with_history(symbol, bars(symbol, start - chrono::Duration::days(60), 1, &closes));
+        tickers.push(ticker_info(symbol));
+    }
+    let config = AppConfig::new()
+        .with_cycle_interval(Duration::from_secs(600))
+        .with_ignore_market_hours(true)
+        .with_benchmark_symbol(None)
+        .with_request_delay(Duration::ZERO)
+        .with_cache_warm_interval(Duration::from_secs(2));
+    let state = AppState::with_database(None)
+        .with_config(config)
+        .with_clock(clock.clone())
+        .with_market_data(Arc::new(provider.with_tickers(tickers)));
+    let step_until = |done: fn(&ContinuousAnalysisStatus) -> bool, step: Duration| {
+        let (state, clock) = (state.clone(), clock.clone());
+        async move {
+            tokio::time::timeout(Duration::from_secs(10), async {
+                while !done(&*state.continuous_analysis_status.read().await) {
+                    clock.advance(step);
+                    for _ in 0..10 {
+                        tokio::task::yield_now().await;
+                    }
+                }
+            })
+            .await
+            .expect("continuous status never got there");
+            state.continuous_analysis_status.read().await.clone()
+        }
+    };
+
+    // A cold first cycle fetches everything
+    state.start_continuous_analysis().await;
+    let status = step_until(|s| s.current_cycle == 1 && s.state == ContinuousState::IdleWaiting, Duration::from_millis(10)).await;
+    assert_eq!(status.cache_hit_rate_last_cycle, Some(0.0));
+    let next_run = status.next_run_at.unwrap();
+
+    // Nothing is refetched while a fetch would expire before the next cycle
+    let ttl = chrono::Duration::seconds(CacheTtls::default().stock_data_secs as i64);
+    let warm_from = next_run + ttl / 4 - ttl;
+    clock.set(warm_from - chrono::Duration::seconds(1));
+    for _ in 0..20 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(state.continuous_analysis_status.read().await.symbols_warmed, 0);
+
+    // Nor while a manual session runs
+    let session = AnalysisStatus {
+        session_id: "manual".to_string(),
+        status: "running".to_string(),
+        progress: 0.0,
+        analyzed_count: 0,
+        total_count: 3,
+        opportunities_found: 0,
+        error_message: None,
+        results: Vec::new(),
+        results_total: 0,
+        estimated_completion: None,
+        remaining_tickers: Vec::new(),
+    };
+    state.sessions.write().await.insert(session.session_id.clone(), session);
+    for _ in 0..10 {
+        clock.advance(Duration::from_secs(2));
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+    assert_eq!(state.continuous_analysis_status.read().await.symbols_warmed, 0);
+
+    // Then one symbol every two seconds, well ahead of the cycle
+    state.sessions.write().await.clear();
+    let resumed_at = clock.now();
+    let status = step_until(|s| s.symbols_warmed == 3, Duration::from_secs(1)).await;
+    assert_eq!(status.state, ContinuousState::IdleWaiting);
+    assert!(clock.now() < resumed_at + chrono::Duration::seconds(10), "{}", clock.now());
+
+    clock.set(next_run);
+    let status = step_until(|s| s.current_cycle == 2 && s.state == ContinuousState::IdleWaiting, Duration::from_millis(10)).await;
+    assert_eq!(status.cache_hit_rate_last_cycle, Some(1.0));
+    assert_eq!(status.symbols_warmed, 0, "counted afresh for each wait");
+}
+
+/// Keeps every notification it is sent
+#[derive(Default)]
+struct RecordingNotifier {
+    sent: std::sync::Mutex<Vec<auto_analyser::notifications::Notification>>,
+}
+
+#[async_trait::async_trait]
+impl auto_analyser::notifications::Notifier for RecordingNotifier {
+    fn name(&self) -> &str {
+        "recording"
+    }
+
+    async fn send(&self, notification: &auto_analyser::notifications::Notification) -> Result<(), auto_analyser::notifications::NotifyError> {
+        self.sent.lock().unwrap().push(notification.clone());
+        Ok(())
+    }
+}
+
+async fn wait_for_deliveries(state: &AppState, count: usize) -> serde_json::Value {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let (_, body) = get_json(state.clone(), "/api/notifications").await;
+            if body.as_array().is_some_and(|log| log.len() >= count) {
+                return body;
+            }
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .expect("notifications were not delivered")
+}
+
+#[tokio::test]
+async fn test_continuous_loop_notifies_new_opportunities_and_alerts() {
+    use auto_analyser::alerts::{AlertCondition, NewAlert};
+    use auto_analyser::notifications::{NotificationConfig, NotificationKind};
+    use auto_analyser::providers::FixtureProvider;
+
+    let dir = tempfile::tempdir().unwrap();
+    // The day after the fixture's last bar
+    let start = Utc.with_ymd_and_hms(2024, 3, 26, 15, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/market_data.json");
+    let notifier = Arc::new(RecordingNotifier::default());
+    let config = AppConfig::new()
+        .with_benchmark_symbol(None)
+        .with_request_delay(Duration::ZERO)
+        .with_notifications(NotificationConfig { max_per_cycle: 1, ..NotificationConfig::default() });
+    let state = watchlist_state(&dir)
+        .await
+        .with_config(config)
+        .with_clock(clock.clone())
+        .with_market_data(Arc::new(FixtureProvider::from_file(path).unwrap()))
+        .with_notifier(notifier.clone());
+    state
+        .database
+        .as_ref()
+        .unwrap()
+        .create_alert(&NewAlert { symbol: "CHOP".to_string(), condition_type: AlertCondition::PriceAbove, condition_value: 0.0 }, None)
+        .await
+        .unwrap();
+
+    // The quote poller sleeps too, so step until the cycle reports completion
+    let run_cycle = |cycle: usize| {
+        let (state, clock) = (state.clone(), clock.clone());
+        async move {
+            wait_for_cycle(&state, cycle).await;
+            while state.continuous_analysis_status.read().await.is_running {
+                clock.advance(Duration::from_millis(50));
+                tokio::task::yield_now().await;
+            }
+        }
+    };
+
+    state.start_continuous_analysis().await;
+    run_cycle(1).await;
+    let opportunities = state.continuous_analysis_status.read().await.opportunities_found;
+    assert!(opportunities > 0);
+
+    // One notification goes out on its own; the rest wait for the digest
+    let log = wait_for_deliveries(&state, 2).await;
+    assert_eq!(log.as_array().unwrap().len(), 2);
+    // Deliveries run concurrently, so either may be logged first
+    let digest = log.as_array().unwrap().iter().find(|entry| entry["kind"] == "digest").unwrap();
+    assert_eq!(digest["title"], format!("{} more notifications this cycle", opportunities));
+    assert!(log.as_array().unwrap().iter().all(|entry| {
+        (entry["channel"].as_str(), entry["status"].as_str(), entry["attempts"].as_u64()) == (Some("recording"), Some("sent"), Some(1))
+    }));
+    let sent = notifier.sent.lock().unwrap().clone();
+    let titles: Vec<&str> = std::iter::once(sent[0].title.as_str()).chain(sent[1].message.lines()).collect();
+    assert!(titles.iter().any(|title| title.starts_with("Alert: CHOP price_above")), "{:?}", titles);
+    assert_eq!(titles.iter().filter(|title| title.starts_with("New opportunity: ")).count(), opportunities);
+    assert_eq!(sent[1].kind, NotificationKind::Digest);
+
+    // Tickers still flagged, and an alert already fired, are not news
+    clock.advance(CONTINUOUS_CYCLE_INTERVAL);
+    run_cycle(2).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(notifier.sent.lock().unwrap().len(), 2);
+    let (_, log) = get_json(state.clone(), "/api/notifications?limit=1").await;
+    assert_eq!(log.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_continuing_opportunities_are_announced_once() {
+    use auto_analyser::message_log::{BroadcastEvent, BroadcastMessage};
+    use auto_analyser::providers::FixtureProvider;
+
+    let dir = tempfile::tempdir().unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 3, 26, 15, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/market_data.json");
+    let notifier = Arc::new(RecordingNotifier::default());
+    let state = watchlist_state(&dir)
+        .await
+        .with_config(AppConfig::new().with_benchmark_symbol(None).with_request_delay(Duration::ZERO))
+        .with_clock(clock.clone())
+        .with_market_data(Arc::new(FixtureProvider::from_file(path).unwrap()))
+        .with_notifier(notifier.clone());
+    let mut rx = state.broadcast_tx.subscribe();
+
+    // Three cycles over the same unchanged history keep DIPS oversold
+    state.start_continuous_analysis().await;
+    for cycle in 1..=3 {
+        if cycle > 1 {
+            clock.advance(CONTINUOUS_CYCLE_INTERVAL);
+        }
+        tokio::time::timeout(Duration::from_secs(30), async {
+            loop {
+                let status = state.continuous_analysis_status.read().await.clone();
+                if status.current_cycle == cycle && status.state == ContinuousState::IdleWaiting {
+                    break;
+                }
+                clock.advance(Duration::from_millis(50));
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("cycle {} did not finish", cycle));
+    }
+
+    let mut announced = Vec::new();
+    while let Ok(sequenced) = rx.try_recv() {
+        if let BroadcastMessage::Event(BroadcastEvent::Opportunity(result)) = sequenced.message {
+            announced.push((result.ticker, result.opportunity_state));
+        }
+    }
+    let dips: Vec<_> = announced.iter().filter(|(ticker, _)| ticker == "DIPS").collect();
+    assert_eq!(dips, [&("DIPS".to_string(), OpportunityState::New)]);
+    // RALLY is flagged throughout too, and announced once as well
+    assert_eq!(announced.len(), 2, "{:?}", announced);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let sent = notifier.sent.lock().unwrap().clone();
+    assert_eq!(sent.iter().filter(|n| n.title == "New opportunity: DIPS").count(), 1);
+
+    let streak = state.opportunity_streaks.read().await.get("DIPS").cloned().unwrap();
+    assert_eq!(streak.streak, 3);
+    assert!(streak.started_at < start + chrono::Duration::from_std(CONTINUOUS_CYCLE_INTERVAL).unwrap(), "began in the first cycle");
+    // Still flagged, but no longer new
+    let (_, body) = post_json(state.clone(), "/api/filtered-results", serde_json::json!({ "opportunity_states": ["new"] })).await;
+    assert!(body["results"].as_array().unwrap().iter().all(|r| r["ticker"] != "DIPS"), "{}", body);
+    let recent = serde_json::json!({ "opportunity_states": ["new", "continuing"], "opportunity_within_hours": 24 });
+    let (_, body) = post_json(state.clone(), "/api/filtered-results", recent.clone()).await;
+    let dips = body["results"].as_array().unwrap().iter().find(|r| r["ticker"] == "DIPS").unwrap().clone();
+    assert_eq!((dips["opportunity_state"].as_str(), dips["opportunity_streak"].as_u64()), (Some("continuing"), Some(3)));
+    let (_, body) = post_json(state.clone(), "/api/filtered-results", serde_json::json!({ "opportunity_within_hours": 1 })).await;
+    assert!(body["results"].as_array().unwrap().iter().all(|r| r["ticker"] != "DIPS"), "{}", body);
+    // Filtering in memory agrees
+    let in_memory = state.all_results.read().await.clone();
+    let spec: FilterSpec = serde_json::from_value(recent).unwrap();
+    assert!(auto_analyser::web_api::filter_results(&in_memory, &spec).iter().any(|r| r.ticker == "DIPS"));
+
+    // The run survives a restart
+    let restarted = AppState::with_database(state.database.clone());
+    restarted.load_opportunity_streaks().await;
+    assert_eq!(restarted.opportunity_streaks.read().await.get("DIPS"), Some(&streak));
+}
+
+#[tokio::test]
+async fn test_maintenance_endpoint_and_database_stats() {
+    let (status, body) = post_json(AppState::with_database(None), "/api/maintenance/run", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["error"]["code"], "database_unavailable");
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let (_, stats) = get_json(state.clone(), "/api/database-stats").await;
+    assert!(stats["size_bytes"].as_u64().unwrap() > 0);
+    assert_eq!(stats["last_maintenance"], serde_json::Value::Null);
+
+    let stale = StockAnalysisResult { timestamp: Utc::now() - chrono::Duration::days(60), ..sample_result("OLD", 10.0, Some(40.0), 1_000) };
+    state.database.as_ref().unwrap().store_analysis_result(&stale, "continuous_cycle_1").await.unwrap();
+    let (status, report) = post_json(state.clone(), "/api/maintenance/run", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!((report["expired_results"].as_u64(), report["thinned_results"].as_u64()), (Some(1), Some(0)));
+
+    let (_, stats) = get_json(state, "/api/database-stats").await;
+    assert_eq!(stats["total_results"], 0);
+    assert_eq!(stats["last_maintenance"]["finished_at"], report["finished_at"]);
+}
+
+#[tokio::test]
+async fn test_corrupt_result_rows_do_not_break_reads() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let db = state.database.as_ref().unwrap();
+    for ticker in ["AAA", "BBB"] {
+        db.store_analysis_result(&sample_result(ticker, 10.0, Some(40.0), 1_000), "s1").await.unwrap();
+    }
+
+    let db_url = format!("sqlite:{}", dir.path().join("watchlist.db").to_string_lossy());
+    let pool = sqlx::SqlitePool::connect(&db_url).await.unwrap();
+    let now = Utc::now().to_rfc3339();
+    for (id, ticker, signals, timestamp) in [
+        ("bad-json", "BADJ", "[\"RSI oversold\"", now.as_str()),
+        ("bad-time", "BADT", "[]", "last tuesday"),
+    ] {
+        sqlx::query(
+            "INSERT INTO analysis_results (id, ticker, name, current_price, is_opportunity, signals, timestamp, analysis_session) \
+             VALUES (?, ?, ?, 10.0, 0, ?, ?, 's1')",
+        )
+        .bind(id)
+        .bind(ticker)
+        .bind(ticker)
+        .bind(signals)
+        .bind(timestamp)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let (status, body) = post_json(state.clone(), "/api/filtered-results", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    let mut tickers: Vec<_> = body["results"].as_array().unwrap().iter().map(|r| r["ticker"].as_str().unwrap()).collect();
+    tickers.sort();
+    assert_eq!(tickers, ["AAA", "BADJ", "BBB"]);
+    assert_eq!((body["total"].as_u64(), body["skipped"].as_u64()), (Some(4), Some(1)));
+    let badj = body["results"].as_array().unwrap().iter().find(|r| r["ticker"] == "BADJ").unwrap();
+    assert_eq!(badj["signals"], serde_json::json!([]));
+
+    // The total doesn't depend on which page the unreadable row lands on
+    let mut served = 0;
+    for page in 1..=4 {
+        let (status, body) = post_json(state.clone(), &format!("/api/filtered-results?page={page}&per_page=1"), serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 4);
+        served += body["results"].as_array().unwrap().len();
+    }
+    assert_eq!(served, 3);
+
+    // Reading the same damaged rows again doesn't count them again
+    let (status, stats) = get_json(state.clone(), "/api/database-stats").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(stats["total_results"], 4);
+    assert_eq!(stats["corrupt_rows"], 2);
+    assert!(stats.get("integrity").is_none());
+
+    let (status, stats) = get_json(state, "/api/database-stats?check=true").await;
+    assert_eq!(status, StatusCode::OK);
+    let integrity = &stats["integrity"];
+    assert_eq!(integrity["ok"], false);
+    assert_eq!(integrity["sqlite"], serde_json::json!(["ok"]));
+    assert_eq!((integrity["rows_checked"].as_u64(), integrity["corrupt_row_count"].as_u64()), (Some(4), Some(2)));
+    let skipped: Vec<_> = integrity["corrupt_rows"].as_array().unwrap().iter().map(|r| (r["id"].as_str().unwrap(), r["skipped"].as_bool().unwrap())).collect();
+    assert_eq!(skipped, [("bad-json", false), ("bad-time", true)]);
+}
+
+async fn delete(state: AppState, uri: &str) -> StatusCode {
+    let request = Request::builder().method("DELETE").uri(uri).body(Body::empty()).unwrap();
+    build_router(state).oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_watchlist_endpoints() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+
+    let (status, body) = post_json(state.clone(), "/api/watchlist", serde_json::json!({ "symbol": "nvda" })).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["symbol"], "NVDA");
+
+    // Adding again is a no-op
+    let (status, body) = post_json(state.clone(), "/api/watchlist", serde_json::json!({ "symbol": "NVDA" })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["added"], false);
+
+    let (status, _) = post_json(state.clone(), "/api/watchlist", serde_json::json!({ "symbol": " " })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = get_json(state.clone(), "/api/watchlist").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["symbol"], "NVDA");
+
+    assert_eq!(delete(state.clone(), "/api/watchlist/nvda").await, StatusCode::NO_CONTENT);
+    assert_eq!(delete(state.clone(), "/api/watchlist/NVDA").await, StatusCode::NOT_FOUND);
+
+    // Without a database there is nowhere to keep the list
+    let response = build_router(AppState::with_database(None))
+        .oneshot(Request::builder().uri("/api/watchlist").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+fn import_request(body: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri("/api/watchlist/import")
+        .header("content-type", "text/csv")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_watchlist_csv_import_export_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let tickers = ["AAPL", "MSFT", "XOM", "NVDA"].iter().map(|s| ticker_info(s)).collect();
+    state.cache.cache_tickers("all_tickers".to_string(), tickers).await;
+    post_json(state.clone(), "/api/watchlist", serde_json::json!({ "symbol": "NVDA" })).await;
+
+    let (status, _, body) = send(state.clone(), import_request("aapl, core holding\nMSFT\n# energy\nXOM,\"dividend, buybacks\"\nAAPL\nnvda\nFAKE\n")).await;
+    assert_eq!(status, StatusCode::OK);
+    let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        report,
+        serde_json::json!({ "added": ["AAPL", "MSFT", "XOM"], "skipped_duplicates": ["AAPL", "NVDA"], "unknown_symbols": ["FAKE"] })
+    );
+
+    // The export carries the latest stored price and RSI
+    let db = state.database.clone().unwrap();
+    db.store_analysis_result(&sample_result("AAPL", 172.5, Some(41.25), 1_000), "s1").await.unwrap();
+    let (_, before) = get_json(state.clone(), "/api/watchlist").await;
+    let (status, headers, exported) = send(state.clone(), Request::builder().uri("/api/watchlist/export").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "text/csv");
+    let csv = String::from_utf8(exported.to_vec()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(lines[0], "symbol,note,added_at,last_price,rsi");
+    let aapl = lines.iter().find(|line| line.starts_with("AAPL,")).unwrap();
+    assert!(aapl.starts_with("AAPL,core holding,") && aapl.ends_with(",172.5,41.25"), "{}", aapl);
+    assert!(csv.contains("XOM,\"dividend, buybacks\","), "{}", csv);
+
+    // Wiping and importing the export restores the identical watchlist
+    for symbol in ["NVDA", "AAPL", "MSFT", "XOM"] {
+        assert_eq!(delete(state.clone(), &format!("/api/watchlist/{}", symbol)).await, StatusCode::NO_CONTENT);
+    }
+    let (_, empty) = get_json(state.clone(), "/api/watchlist").await;
+    assert_eq!(empty, serde_json::json!([]));
+    let (status, _, body) = send(state.clone(), import_request(&csv)).await;
+    assert_eq!(status, StatusCode::OK);
+    let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(report["added"].as_array().unwrap().len(), 4);
+    let (_, after) = get_json(state.clone(), "/api/watchlist").await;
+    assert_eq!(after, before);
+    assert_eq!(after[1]["note"], "core holding");
+
+    // Imports are limited in size and must parse
+    let state = state.with_config(AppConfig::new().with_watchlist_import_max_rows(2));
+    for body in ["A\nB\nC\n", "symbol,added_at\nAAPL,yesterday\n"] {
+        let (status, _, error) = send(state.clone(), import_request(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = serde_json::from_slice(&error).unwrap();
+        assert_eq!(error["error"]["code"], "invalid_request");
+    }
+
+    // Without a database the export fails like the other endpoints
+    let (status, _, error) =
+        send(AppState::with_database(None), Request::builder().uri("/api/watchlist/export").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let error: serde_json::Value = serde_json::from_slice(&error).unwrap();
+    assert_eq!(error["error"]["code"], "database_unavailable");
+}
+
+#[tokio::test]
+async fn test_soft_delete_restore_and_audit_log() {
+    use axum::extract::ConnectInfo;
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir)
+        .await
+        .with_config(AppConfig::new().with_admin_keys(vec!["admin-secret".to_string()]));
+    let with_key = |uri: &str, method: &str| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(API_KEY_HEADER, "admin-secret")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    // The client address is recorded when the server knows it
+    let mut request = Request::builder()
+        .method("POST")
+        .uri("/api/alerts")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::json!({ "symbol": "AAPL", "condition_type": "price_above", "condition_value": 200.0 }).to_string(),
+        ))
+        .unwrap();
+    request.extensions_mut().insert(ConnectInfo(std::net::SocketAddr::from(([10, 0, 0, 7], 5123))));
+    let (status, _, body) = send(state.clone(), request).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let id = serde_json::from_slice::<serde_json::Value>(&body).unwrap()["id"].as_i64().unwrap();
+
+    assert_eq!(delete(state.clone(), &format!("/api/alerts/{}", id)).await, StatusCode::NO_CONTENT);
+    let (_, body) = get_json(state.clone(), "/api/alerts").await;
+    assert_eq!(body, serde_json::json!([]), "deleted alerts are hidden by default");
+
+    // Seeing deleted rows is an admin view
+    let (status, body) = get_json(state.clone(), "/api/alerts?include_deleted=true").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"]["code"], "forbidden");
+    let (_, _, body) = send(state.clone(), with_key("/api/alerts?include_deleted=true", "GET")).await;
+    let alerts: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(alerts[0]["id"], id);
+    assert!(alerts[0]["deleted_at"].is_string());
+
+    let (status, body) = post_json(state.clone(), &format!("/api/alerts/{}/restore", id), serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.get("deleted_at").is_none());
+    let (status, _) = post_json(state.clone(), &format!("/api/alerts/{}/restore", id), serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "only deleted alerts can be restored");
+    let (_, body) = get_json(state.clone(), "/api/alerts").await;
+    assert_eq!(body.as_array().unwrap().len(), 1);
+
+    post_json(state.clone(), "/api/watchlist", serde_json::json!({ "symbol": "nvda" })).await;
+    assert_eq!(delete(state.clone(), "/api/watchlist/nvda").await, StatusCode::NO_CONTENT);
+    let (status, body) = get_json(state.clone(), "/api/watchlist?include_deleted=true").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"]["code"], "forbidden");
+    let (status, body) = post_json(state.clone(), "/api/watchlist/nvda/restore", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["symbol"], "NVDA");
+    let (status, body) = post_json(state.clone(), "/api/watchlist/nvda/restore", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["code"], "not_found");
+
+    let (status, _) = get_json(state.clone(), "/api/audit?resource=alerts").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _, body) = send(state.clone(), with_key("/api/audit?resource=alerts&limit=50", "GET")).await;
+    assert_eq!(status, StatusCode::OK);
+    let audit: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+    let actions: Vec<_> = audit.iter().map(|entry| entry["action"].as_str().unwrap()).collect();
+    assert_eq!(actions, ["restore", "delete", "create"], "newest first");
+    assert_eq!(audit[2]["resource_id"], id.to_string());
+    assert_eq!(audit[2]["source_ip"], "10.0.0.7");
+    assert_eq!(audit[2]["old_value"], serde_json::Value::Null);
+    assert_eq!(audit[2]["new_value"]["symbol"], "AAPL");
+    assert!(audit[1]["new_value"]["deleted_at"].is_string());
+
+    let (_, _, body) = send(state.clone(), with_key("/api/audit?limit=2", "GET")).await;
+    let audit: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(audit.len(), 2);
+    assert_eq!(audit[0]["resource"], "watchlist");
+
+    let (status, _, _) = send(state, with_key("/api/audit?resource=positions", "GET")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_watchlist_quotes_are_polled_and_broadcast() {
+    use async_trait::async_trait;
+    use auto_analyser::message_log::{BroadcastEvent, BroadcastMessage};
+    use auto_analyser::providers::{BatchQuoteSource, ProviderError};
+    use auto_analyser::web_api::poll_watchlist_quotes;
+    use std::sync::Mutex;
+
+    /// Quotes every symbol asked for at the current price, opening at 100
+    struct Ticking {
+        price: Mutex<f64>,
+    }
+
+    #[async_trait]
+    impl BatchQuoteSource for Ticking {
+        fn name(&self) -> &str {
+            "ticking"
+        }
+
+        async fn latest_quotes(&self, symbols: &[String]) -> Result<HashMap<String, auto_analyser::StockData>, ProviderError> {
+            let price = *self.price.lock().unwrap();
+            let now = Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap();
+            Ok(symbols
+                .iter()
+                .map(|symbol| (symbol.clone(), auto_analyser::StockData { open: 100.0, ..quote(symbol, price, now) }))
+                .collect())
+        }
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let source = Arc::new(Ticking { price: Mutex::new(101.0) });
+    let state = watchlist_state(&dir).await.with_batch_quotes(source.clone());
+    assert_eq!(poll_watchlist_quotes(&state).await, 0, "nothing to poll yet");
+
+    post_json(state.clone(), "/api/watchlist", serde_json::json!({ "symbol": "nvda" })).await;
+    let (_, body) = get_json(state.clone(), "/api/watchlist").await;
+    assert_eq!(body[0]["symbol"], "NVDA");
+    assert_eq!(body[0]["quote_timestamp"], serde_json::Value::Null);
+
+    assert_eq!(poll_watchlist_quotes(&state).await, 1);
+    // An unchanged price is not broadcast again
+    assert_eq!(poll_watchlist_quotes(&state).await, 0);
+    *source.price.lock().unwrap() = 99.5;
+    assert_eq!(poll_watchlist_quotes(&state).await, 1);
+
+    let quotes: Vec<serde_json::Value> = state
+        .message_log
+        .since(0, 100)
+        .into_iter()
+        .filter(|message| matches!(message.message, BroadcastMessage::Event(BroadcastEvent::Quote(_))))
+        .map(|message| serde_json::to_value(&message).unwrap())
+        .collect();
+    assert_eq!(quotes.len(), 2);
+    assert_eq!(quotes[1]["type"], "quote");
+    assert_eq!(quotes[1]["symbol"], "NVDA");
+    assert_eq!(quotes[1]["price"], 99.5);
+    assert_eq!(quotes[1]["change_pct"], -0.5);
+
+    let (_, body) = get_json(state.clone(), "/api/watchlist").await;
+    assert_eq!(body[0]["last_price"], 99.5);
+    assert_eq!(body[0]["quote_timestamp"], "2024-03-04T15:00:00Z");
+}
+
+#[tokio::test]
+async fn test_symbol_health_endpoints() {
+    use auto_analyser::providers::ProviderError;
+    use auto_analyser::symbol_health::{FailedSymbols, MAX_SKIP_CYCLES};
+    use auto_analyser::AnalyzerError;
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let db = state.database.clone().unwrap();
+
+    // One delisted symbol and one that failed once, as a previous run left them
+    let mut failed = FailedSymbols::new();
+    let gone: AnalyzerError = ProviderError::NotFound { provider: "yahoo".into(), symbol: "GONE".into() }.into();
+    let timeout: AnalyzerError = ProviderError::Upstream { provider: "yahoo".into(), message: "timed out".into() }.into();
+    db.upsert_symbol_health(&failed.record_failure("GONE", &gone, 0, Utc::now())).await.unwrap();
+    db.upsert_symbol_health(&failed.record_failure("FLAKY", &timeout, 0, Utc::now())).await.unwrap();
+    state.load_symbol_health().await;
+    assert_eq!(state.failed_symbols.read().await.len(), 2);
+
+    // Only skipped symbols are listed
+    let (status, body) = get_json(state.clone(), "/api/symbol-health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["symbol"], "GONE");
+    assert_eq!(body[0]["consecutive_failures"], 1);
+    assert_eq!(body[0]["skip_until_cycle"], 1 + MAX_SKIP_CYCLES);
+
+    let (status, body) = post_json(state.clone(), "/api/symbol-health/gone/reset", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["symbol"], "GONE");
+    assert!(state.failed_symbols.read().await.get("GONE").is_none());
+    let stored: Vec<_> = db.get_symbol_health().await.unwrap().into_iter().map(|h| h.symbol).collect();
+    assert_eq!(stored, vec!["FLAKY"]);
+
+    let (status, body) = get_json(state.clone(), "/api/symbol-health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, serde_json::json!([]));
+
+    let (status, _) = post_json(state.clone(), "/api/symbol-health/GONE/reset", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_prioritize_watchlist() {
+    use auto_analyser::web_api::prioritize_watchlist;
+    use auto_analyser::TickerInfo;
+
+    let tickers: Vec<TickerInfo> = ["AAA", "BBB", "CCC", "DDD", "EEE"]
+        .iter()
+        .map(|symbol| TickerInfo {
+            symbol: symbol.to_string(),
+            name: format!("{} Inc", symbol),
+            last_sale: None,
+            net_change: None,
+            pct_change: None,
+            market_cap: None,
+            country: None,
+            ipo_year: None,
+            volume: None,
+            sector: None,
+            industry: None,
+            exchange: None,
+            beta: None,
+            fifty_two_week_high: None,
+            fifty_two_week_low: None,
+            last_sale_f64: None,
+            market_cap_f64: None,
+            pct_change_f64: None,
+            volume_u64: None,
+            country_normalized: None,
+            region: None,
+            is_adr: false,
+        })
+        .collect();
+
+    let watchlist = vec!["DDD".to_string(), "ZZZ".to_string(), "BBB".to_string()];
+    let ordered: Vec<_> = prioritize_watchlist(tickers, &watchlist)
+        .into_iter()
+        .map(|ticker| ticker.symbol)
+        .collect();
+    assert_eq!(ordered, vec!["DDD", "BBB", "AAA", "CCC", "EEE"]);
+}
+
+#[tokio::test]
+async fn test_alert_endpoints() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+
+    let (status, body) = post_json(
+        state.clone(),
+        "/api/alerts",
+        serde_json::json!({ "symbol": "aapl", "condition_type": "price_above", "condition_value": 200.0 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["symbol"], "AAPL");
+    assert_eq!(body["is_active"], true);
+    let id = body["id"].as_i64().unwrap();
+
+    let (status, body) = post_json(
+        state.clone(),
+        "/api/alerts",
+        serde_json::json!({ "symbol": "AAPL", "condition_type": "rsi_below", "condition_value": 150.0 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_request");
+
+    let (status, body) = get_json(state.clone(), "/api/alerts").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["condition_type"], "price_above");
+
+    assert_eq!(delete(state.clone(), &format!("/api/alerts/{}", id)).await, StatusCode::NO_CONTENT);
+    assert_eq!(delete(state.clone(), &format!("/api/alerts/{}", id)).await, StatusCode::NOT_FOUND);
+
+    let (status, body) = get_json(AppState::with_database(None), "/api/alerts").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["error"]["code"], "database_unavailable");
+}
+
+#[tokio::test]
+async fn test_alert_fires_once_and_broadcasts() {
+    use auto_analyser::alerts::{AlertCondition, NewAlert};
+    use auto_analyser::web_api::check_alerts;
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let db = state.database.clone().unwrap();
+    let alert = db
+        .create_alert(&NewAlert {
+            symbol: "AAPL".to_string(),
+            condition_type: AlertCondition::RsiBelow,
+            condition_value: 30.0,
+        }, None)
+        .await
+        .unwrap();
+    let mut rx = state.broadcast_tx.subscribe();
+
+    let fired = check_alerts(&state, &sample_result("AAPL", 180.0, Some(25.0), 1_000)).await;
+    assert_eq!(fired.len(), 1);
+    assert_eq!(fired[0].observed_value, 25.0);
+
+    let message = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+    assert_eq!(message["type"], "alert_triggered");
+    assert_eq!(message["alert_id"], alert.id);
+    assert_eq!(message["symbol"], "AAPL");
+
+    // Still oversold next cycle: no repeat
+    assert!(check_alerts(&state, &sample_result("AAPL", 179.0, Some(22.0), 1_000)).await.is_empty());
+    assert!(rx.try_recv().is_err());
+
+    // Recovers, then drops again: fires a second time
+    assert!(check_alerts(&state, &sample_result("AAPL", 190.0, Some(45.0), 1_000)).await.is_empty());
+    assert_eq!(check_alerts(&state, &sample_result("AAPL", 170.0, Some(28.0), 1_000)).await.len(), 1);
+
+    let triggers = db.get_alert_triggers(alert.id).await.unwrap();
+    assert_eq!(triggers.iter().map(|t| t.observed_value).collect::<Vec<_>>(), vec![25.0, 28.0]);
+}
+
+/// Next text frame from the server as JSON, failing the test after a few seconds
+async fn next_ws_json<S>(socket: &mut S) -> serde_json::Value
+where
+    S: futures::Stream<Item = Result<tokio_tungstenite::tungstenite::Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    use futures::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    loop {
+        let frame = tokio::time::timeout(Duration::from_secs(5), socket.next())
+            .await
+            .expect("timed out waiting for a WebSocket message")
+            .expect("WebSocket closed")
+            .unwrap();
+        if let Message::Text(text) = frame {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_websocket_ticker_subscription_filters_other_symbols() {
+    use auto_analyser::message_log::BroadcastEvent;
+    use futures::SinkExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let state = AppState::with_database(None);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = build_router(state.clone());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws", addr)).await.unwrap();
+
+    // Unsubscribed clients still get the continuous status on connect
+    let initial = next_ws_json(&mut socket).await;
+    assert_eq!(initial["is_running"], false);
+
+    socket
+        .send(Message::Text(r#"{"action":"subscribe","topic":"ticker","symbol":"aapl"}"#.into()))
+        .await
+        .unwrap();
+    let ack = next_ws_json(&mut socket).await;
+    assert_eq!(ack, serde_json::json!({"type": "subscribed", "topic": "ticker", "symbol": "AAPL"}));
+
+    let opportunity = StockAnalysisResult { is_opportunity: true, ..sample_result("MSFT", 400.0, Some(25.0), 1_000) };
+    state.publish_result(&opportunity);
+    state.publish(running_status("continuous"));
+    state.publish_result(&sample_result("AAPL", 190.0, Some(50.0), 1_000));
+    state.publish(BroadcastEvent::TickerUpdate(Box::new(sample_result("NVDA", 120.0, Some(50.0), 1_000))));
+    state.publish_result(&sample_result("AAPL", 191.0, Some(51.0), 1_000));
+
+    // Broadcasts arrive in order, so anything for another symbol or topic
+    // would show up before these
+    for price in [190.0, 191.0] {
+        let message = next_ws_json(&mut socket).await;
+        assert_eq!(message["type"], "ticker_update");
+        assert_eq!(message["ticker"], "AAPL");
+        assert_eq!(message["current_price"], price);
+    }
+}
+
+#[tokio::test]
+async fn test_websocket_replays_missed_broadcasts_on_reconnect() {
+    use auto_analyser::web_api::BROADCAST_SEQ_HEADER;
+
+    let state = AppState::with_database(None);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = build_router(state.clone());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    // A dashboard following AAPL sees the first update, then drops off
+    let url = format!("ws://{}/ws?topics=status,ticker:aapl", addr);
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+    assert_eq!(next_ws_json(&mut socket).await["is_running"], false);
+    assert_eq!(next_ws_json(&mut socket).await, serde_json::json!({"type": "subscribed", "topic": "status"}));
+    assert_eq!(next_ws_json(&mut socket).await["symbol"], "AAPL");
+    state.publish_result(&sample_result("AAPL", 189.0, Some(49.0), 1_000));
+    let seen = next_ws_json(&mut socket).await;
+    assert_eq!(seen["current_price"], 189.0);
+    drop(socket);
+
+    // What it misses while away, some on topics it doesn't follow
+    state.publish_result(&sample_result("AAPL", 190.0, Some(50.0), 1_000));
+    state.publish_result(&sample_result("MSFT", 400.0, Some(50.0), 1_000));
+    state.publish(running_status("s1"));
+    state.publish_result(&sample_result("AAPL", 191.0, Some(51.0), 1_000));
+
+    // REST responses carry the sequence to resume from
+    let response = reqwest::get(format!("http://{}/api/health", addr)).await.unwrap();
+    let latest: u64 = response.headers()[BROADCAST_SEQ_HEADER].to_str().unwrap().parse().unwrap();
+    assert_eq!(latest, seen["seq"].as_u64().unwrap() + 4);
+
+    let url = format!("ws://{}/ws?since_seq={}&topics=status,ticker:aapl", addr, seen["seq"]);
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+    next_ws_json(&mut socket).await;
+    next_ws_json(&mut socket).await;
+    next_ws_json(&mut socket).await;
+    let replayed = [next_ws_json(&mut socket).await, next_ws_json(&mut socket).await, next_ws_json(&mut socket).await];
+    assert_eq!(replayed[0]["current_price"], 190.0);
+    assert_eq!(replayed[1]["session_id"], "s1");
+    assert_eq!(replayed[2]["current_price"], 191.0);
+    assert_eq!(replayed.iter().map(|m| m["seq"].as_u64().unwrap()).collect::<Vec<_>>(), [latest - 3, latest - 1, latest]);
+    assert_eq!(
+        next_ws_json(&mut socket).await,
+        serde_json::json!({"type": "replayed", "since_seq": seen["seq"], "through_seq": latest, "complete": true})
+    );
+
+    // Live broadcasts follow without repeating the replay
+    state.publish_result(&sample_result("AAPL", 192.0, Some(52.0), 1_000));
+    let live = next_ws_json(&mut socket).await;
+    assert_eq!((live["current_price"].as_f64(), live["seq"].as_u64()), (Some(192.0), Some(latest + 1)));
+}
+
+#[tokio::test]
+async fn test_websocket_replay_reports_evicted_broadcasts() {
+    let state = AppState::with_database(None).with_config(AppConfig::new().with_replay_buffer_size(2));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = build_router(state.clone());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    for session in ["s1", "s2", "s3", "s4"] {
+        state.publish(running_status(session));
+    }
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws?since_seq=1&topics=status", addr)).await.unwrap();
+    next_ws_json(&mut socket).await;
+    next_ws_json(&mut socket).await;
+    assert_eq!(next_ws_json(&mut socket).await["session_id"], "s3");
+    assert_eq!(next_ws_json(&mut socket).await["session_id"], "s4");
+    assert_eq!(
+        next_ws_json(&mut socket).await,
+        serde_json::json!({"type": "replayed", "since_seq": 1, "through_seq": 4, "complete": false})
+    );
+
+    // A sequence from before a restart replays nothing and says so
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws?since_seq=900&topics=status", addr)).await.unwrap();
+    next_ws_json(&mut socket).await;
+    next_ws_json(&mut socket).await;
+    assert_eq!(
+        next_ws_json(&mut socket).await,
+        serde_json::json!({"type": "replayed", "since_seq": 900, "through_seq": 4, "complete": false})
+    );
+
+    let rejected = tokio_tungstenite::connect_async(format!("ws://{}/ws?topics=news", addr)).await.unwrap_err();
+    assert!(matches!(rejected, tokio_tungstenite::tungstenite::Error::Http(ref response) if response.status() == 400), "{:?}", rejected);
+}
+
+#[tokio::test]
+async fn test_websocket_heartbeat_disconnects_silent_clients() {
+    use auto_analyser::web_api::{WS_HEARTBEAT_INTERVAL, WS_IDLE_TIMEOUT};
+    use futures::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let clock = Arc::new(TestClock::new(Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap()));
+    let state = AppState::with_database(None).with_clock(clock.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, build_router(state.clone())).await.unwrap() });
+    let clients = || async {
+        let health: serde_json::Value = reqwest::get(format!("http://{}/api/health", addr)).await.unwrap().json().await.unwrap();
+        health["websocket_clients"].as_u64().unwrap()
+    };
+
+    // One client keeps reading, and so answers Pings; the other goes silent
+    let (mut live, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws", addr)).await.unwrap();
+    let (mut silent, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws", addr)).await.unwrap();
+    next_ws_json(&mut live).await;
+    next_ws_json(&mut silent).await;
+    assert_eq!(clients().await, 2);
+
+    let beats = WS_IDLE_TIMEOUT.as_secs() / WS_HEARTBEAT_INTERVAL.as_secs() + 1;
+    for beat in 1..=beats {
+        clock.wait_for_sleepers(2).await;
+        clock.advance(WS_HEARTBEAT_INTERVAL);
+        let heartbeat = next_ws_json(&mut live).await;
+        assert_eq!(heartbeat["type"], "heartbeat");
+        if beat < beats {
+            assert_eq!(clients().await, 2, "disconnected before the idle timeout");
+        }
+    }
+
+    // Past the timeout the silent client is closed, while the live one stays
+    for _ in 0..50 {
+        if clients().await == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(clients().await, 1);
+    let mut frames = Vec::new();
+    while let Some(Ok(frame)) = tokio::time::timeout(Duration::from_secs(5), silent.next()).await.unwrap() {
+        frames.push(frame);
+    }
+    assert!(frames.iter().any(|frame| matches!(frame, Message::Ping(_))), "{:?}", frames);
+}
+
+#[tokio::test]
+async fn test_preset_endpoints() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+
+    let (status, body) = post_json(
+        state.clone(),
+        "/api/presets",
+        serde_json::json!({ "name": "oversold", "filter": { "max_rsi": 30.0, "sectors": ["Technology"] } }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["name"], "oversold");
+    assert_eq!(body["filter"]["max_rsi"], 30.0);
+
+    let (status, body) = post_json(
+        state.clone(),
+        "/api/presets",
+        serde_json::json!({ "name": "broken", "filter": { "min_price": 10.0, "max_price": 5.0 } }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_filter");
+
+    let (status, body) = get_json(state.clone(), "/api/presets").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+
+    assert_eq!(delete(state.clone(), "/api/presets/oversold").await, StatusCode::NO_CONTENT);
+    assert_eq!(delete(state, "/api/presets/oversold").await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_analysis_requires_exactly_one_filter_source() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+
+    for body in [
+        serde_json::json!({ "max_tickers": 1 }),
+        serde_json::json!({ "filter": {}, "preset_name": "oversold" }),
+    ] {
+        let (status, body) = post_json(state.clone(), "/api/analysis", body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "invalid_request");
+    }
+
+    let (status, _) = post_json(state, "/api/analysis", serde_json::json!({ "preset_name": "missing" })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_analysis_resolves_preset() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::{HistoryRange, TickerInfo};
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let in_sector = |symbol: &str, sector: &str| TickerInfo { sector: Some(sector.to_string()), ..ticker_info(symbol) };
+    state
+        .cache
+        .cache_tickers("all_tickers".to_string(), vec![in_sector("TECH", "Technology"), in_sector("OIL", "Energy")])
+        .await;
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    let closes: Vec<f64> = (0..60).map(|i| 20.0 + i as f64).collect();
+    let series = QuoteSeries {
+        bars: bars("TECH", start, 1, &closes),
+        source: "fixture".to_string(),
+        fetched_at: Utc::now(),
+        quality: None,
+        events: Vec::new(),
+    };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("TECH"), series).await;
+
+    let filter = StockFilter { sectors: Some(vec!["Technology".to_string()]), ..StockFilter::new() };
+    state.database.as_ref().unwrap().save_preset("tech", &filter, None, None).await.unwrap();
+
+    let (status, body) = post_json(state.clone(), "/api/analysis", serde_json::json!({ "preset_name": "tech" })).await;
+    assert_eq!(status, StatusCode::OK);
+    let session = wait_for_session(&state, body["session_id"].as_str().unwrap()).await;
+
+    assert_eq!(session.total_count, 1);
+    assert_eq!(session.results.iter().map(|r| r.ticker.as_str()).collect::<Vec<_>>(), ["TECH"]);
+    assert_eq!(session.results[0].sector.as_deref(), Some("Technology"));
+}
+
+#[tokio::test]
+async fn test_opportunity_rules_from_preset_and_request() {
+    use auto_analyser::opportunity::OpportunityRule;
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    state.cache.cache_tickers("all_tickers".to_string(), vec![ticker_info("UP")]).await;
+    // A steady climb: RSI pins at 100 and the close sits above its SMA 20
+    let closes: Vec<f64> = (0..60).map(|i| 20.0 + i as f64).collect();
+    let series = QuoteSeries {
+        bars: bars("UP", Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap(), 1, &closes),
+        source: "fixture".to_string(),
+        fetched_at: Utc::now(),
+        quality: None,
+        events: Vec::new(),
+    };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("UP"), series).await;
+    let deep_oversold: OpportunityRule = serde_json::from_value(serde_json::json!({
+        "name": "Deep oversold",
+        "when": {"field": "rsi", "op": "<", "value": 10}
+    }))
+    .unwrap();
+    state.database.as_ref().unwrap().save_preset("strict", &StockFilter::new(), Some(&[deep_oversold]), None).await.unwrap();
+
+    let run = |body: serde_json::Value| {
+        let state = state.clone();
+        async move {
+            let (status, body) = post_json(state.clone(), "/api/analysis", body).await;
+            assert_eq!(status, StatusCode::OK);
+            wait_for_session(&state, body["session_id"].as_str().unwrap()).await.results.remove(0)
+        }
+    };
+
+    // Without rules the filter's RSI thresholds apply
+    let result = run(serde_json::json!({ "filter": {} })).await;
+    assert!(result.is_opportunity);
+    assert!(result.signals.contains(&"Opportunity rule: Overbought (RSI >= 70)".to_string()));
+
+    // The preset's rules replace them
+    let result = run(serde_json::json!({ "preset_name": "strict" })).await;
+    assert!(!result.is_opportunity);
+    assert!(!result.signals.iter().any(|signal| signal.starts_with("Opportunity rule")));
+
+    // Rules in the request win over the preset's
+    let rules = serde_json::json!([{
+        "name": "Trend",
+        "when": {"and": [
+            {"field": "close", "op": ">", "value": "sma_20"},
+            {"or": [{"field": "rsi", "op": ">", "value": 50}, {"field": "macd_histogram", "op": ">", "value": 0}]}
+        ]}
+    }]);
+    let result = run(serde_json::json!({ "preset_name": "strict", "opportunity_rules": rules })).await;
+    assert!(result.is_opportunity);
+    assert!(result.signals.contains(&"Opportunity rule: Trend".to_string()));
+
+    let (status, _) = post_json(
+        state,
+        "/api/analysis",
+        serde_json::json!({ "filter": {}, "opportunity_rules": [{"name": "Empty", "when": {"or": []}}] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_session_report_states_its_filter() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::{HistoryRange, TickerInfo};
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let in_sector = |symbol: &str, sector: &str| TickerInfo { sector: Some(sector.to_string()), ..ticker_info(symbol) };
+    state.cache.cache_tickers("all_tickers".to_string(), vec![in_sector("TECH", "Technology")]).await;
+    let closes: Vec<f64> = (0..60).map(|i| 20.0 + i as f64).collect();
+    let series = QuoteSeries {
+        bars: bars("TECH", Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap(), 1, &closes),
+        source: "fixture".to_string(),
+        fetched_at: Utc::now(),
+        quality: None,
+        events: Vec::new(),
+    };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("TECH"), series).await;
+    let filter = StockFilter { sectors: Some(vec!["Technology".to_string()]), ..StockFilter::new() };
+    state.database.as_ref().unwrap().save_preset("tech", &filter, None, None).await.unwrap();
+
+    let (_, body) = post_json(state.clone(), "/api/analysis", serde_json::json!({ "preset_name": "tech" })).await;
+    let session_id = body["session_id"].as_str().unwrap().to_string();
+    wait_for_session(&state, &session_id).await;
+
+    let uri = format!("/api/analysis/{}/report", session_id);
+    let (status, report) = get_json(state.clone(), &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["partial"], false);
+    assert_eq!(report["status"], "completed");
+    assert_eq!(report["request"]["preset_name"], "tech");
+    assert_eq!(report["filter"]["sectors"], serde_json::json!(["Technology"]));
+    assert!(report["finished_at"].is_string());
+    assert_eq!(report["total_count"], 1);
+    assert_eq!(report["results"][0]["ticker"], "TECH");
+    assert_eq!(report["sectors"][0]["sector"], "Technology");
+
+    // Once the server has forgotten the session it is rebuilt from the database
+    state.sessions.write().await.clear();
+    let (status, rebuilt) = get_json(state.clone(), &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(rebuilt["request"], report["request"]);
+    assert_eq!(rebuilt["results"][0]["ticker"], "TECH");
+    assert_eq!(rebuilt["analyzed_count"], 1);
+    assert_eq!(rebuilt["partial"], false, "the last results were stored with the completion marker");
+    assert!(state.database.as_ref().unwrap().is_session_complete(&session_id).await.unwrap());
+
+    let response = build_router(state.clone())
+        .oneshot(Request::builder().uri(format!("{}?format=zip", uri)).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/zip");
+    assert!(response.headers()["content-disposition"].to_str().unwrap().contains(&session_id));
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(body.starts_with(b"PK"));
+
+    let (status, _) = get_json(state.clone(), &format!("{}?format=pdf", uri)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get_json(state, "/api/analysis/missing/report").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_running_session_report_is_partial() {
+    let state = AppState::with_database(None);
+    let mut status = running_status("live");
+    status.results = vec![
+        StockAnalysisResult { is_opportunity: true, ..sample_result("AAA", 10.0, Some(45.0), 1_000) },
+        StockAnalysisResult { is_opportunity: true, ..sample_result("BBB", 10.0, Some(15.0), 1_000) },
+    ];
+    state.sessions.write().await.insert("live".to_string(), status);
+
+    let (status, report) = get_json(state, "/api/analysis/live/report?top=1").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["partial"], true);
+    assert_eq!(report["analyzed_count"], 5);
+    assert_eq!(report["request"], serde_json::Value::Null, "nothing records the request without a database");
+    assert_eq!(report["results"].as_array().unwrap().len(), 2);
+    assert_eq!(report["top_opportunities"].as_array().unwrap().len(), 1);
+    assert_eq!(report["top_opportunities"][0]["ticker"], "BBB");
+}
+
+fn quote(symbol: &str, close: f64, timestamp: chrono::DateTime<Utc>) -> auto_analyser::StockData {
+    auto_analyser::StockData {
+        symbol: symbol.to_string(),
+        timestamp,
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume: 7_000,
+        adjusted_close: None,
+    }
+}
+
+#[test]
+fn test_requote_keeps_indicators_until_the_next_trading_day() {
+    let fetched_at = Utc.with_ymd_and_hms(2024, 3, 4, 14, 0, 0).unwrap();
+    let previous = StockAnalysisResult {
+        data_fetched_at: Some(fetched_at),
+        data_source: Some("yahoo".to_string()),
+        signals: vec!["Golden Cross".to_string()],
+        analyst_target: Some(200.0),
+        upside_to_target_pct: Some(20.0),
+        ..sample_result("REQ", 100.0, Some(55.0), 1_000)
+    };
+
+    assert!(!needs_history_refresh(&previous, &quote("REQ", 101.0, fetched_at + chrono::Duration::hours(2))));
+    assert!(needs_history_refresh(&previous, &quote("REQ", 101.0, fetched_at + chrono::Duration::days(1))));
+    let never_fetched = StockAnalysisResult { data_fetched_at: None, ..previous.clone() };
+    assert!(needs_history_refresh(&never_fetched, &quote("REQ", 101.0, fetched_at)));
+
+    let now = fetched_at + chrono::Duration::hours(3);
+    let info = auto_analyser::TickerInfo { sector: Some("Technology".to_string()), ..ticker_info("REQ") };
+    let requoted = requote_result(&previous, &info, &quote("REQ", 101.0, now), now);
+    assert_eq!(requoted.current_price, Some(101.0));
+    assert_eq!(requoted.volume, Some(7_000));
+    assert_eq!(requoted.timestamp, now);
+    assert_eq!(requoted.sector.as_deref(), Some("Technology"));
+    assert_eq!(requoted.rsi, previous.rsi);
+    assert_eq!(requoted.signals, previous.signals);
+    assert_eq!(requoted.data_fetched_at, Some(fetched_at));
+    assert_eq!(requoted.analyst_target, None, "the upside is stale once the price moves");
+}
+
+#[tokio::test]
+async fn test_continuous_cycle_requotes_from_batched_quotes() {
+    use async_trait::async_trait;
+    use auto_analyser::providers::{BatchQuoteSource, ProviderError, QuoteSeries};
+    use auto_analyser::HistoryRange;
+    use std::sync::Mutex;
+
+    /// Quotes only `QUO`, priced at 123 as of the (test) time it is asked
+    struct QuoteOne {
+        clock: Arc<TestClock>,
+        requests: Mutex<Vec<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl BatchQuoteSource for QuoteOne {
+        fn name(&self) -> &str {
+            "quote-one"
+        }
+
+        async fn latest_quotes(&self, symbols: &[String]) -> Result<HashMap<String, auto_analyser::StockData>, ProviderError> {
+            self.requests.lock().unwrap().push(symbols.to_vec());
+            Ok(HashMap::from([("QUO".to_string(), quote("QUO", 123.0, self.clock.now()))]))
+        }
+    }
+
+    async fn run_cycle(state: &AppState, clock: &TestClock, cycle: usize) {
+        wait_for_cycle(state, cycle).await;
+        // Step past the per-ticker delays until the cycle reports completion
+        while state.continuous_analysis_status.read().await.is_running {
+            clock.advance(Duration::from_millis(50));
+            tokio::task::yield_now().await;
+        }
+        clock.wait_for_sleepers(1).await;
+    }
+
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let source = Arc::new(QuoteOne { clock: clock.clone(), requests: Mutex::new(Vec::new()) });
+    let state = AppState::with_database(None)
+        .with_config(AppConfig::new().with_benchmark_symbol(None))
+        .with_clock(clock.clone())
+        .with_batch_quotes(source.clone());
+
+    let cache_inputs = |offset: f64| {
+        let state = state.clone();
+        async move {
+            state.cache.cache_tickers("all_tickers".to_string(), vec![ticker_info("QUO"), ticker_info("HIS")]).await;
+            for symbol in ["QUO", "HIS"] {
+                let closes: Vec<f64> = (0..60).map(|i| offset + 100.0 + (i as f64 * 0.7).sin() * 3.0).collect();
+                let series = QuoteSeries {
+                    bars: bars(symbol, start - chrono::Duration::days(60), 1, &closes),
+                    source: "fixture".to_string(),
+                    fetched_at: start,
+                    quality: None,
+                    events: Vec::new(),
+                };
+                state.cache.cache_quote_series(HistoryRange::default().cache_key(symbol), series).await;
+            }
+        }
+    };
+
+    cache_inputs(0.0).await;
+    state.start_continuous_analysis().await;
+    run_cycle(&state, &clock, 1).await;
+    let first: HashMap<String, StockAnalysisResult> =
+        state.all_results.read().await.iter().map(|r| (r.ticker.clone(), r.clone())).collect();
+    assert_eq!(first.len(), 2);
+    assert_ne!(first["QUO"].current_price, Some(123.0), "no previous result yet: analyzed from history");
+
+    // Same trading day: the new history must only be picked up by the unquoted ticker
+    clock.advance(CONTINUOUS_CYCLE_INTERVAL - Duration::from_secs(1));
+    cache_inputs(50.0).await;
+    clock.advance(Duration::from_secs(1));
+    run_cycle(&state, &clock, 2).await;
+
+    let results = state.all_results.read().await;
+    let quoted = results.iter().find(|r| r.ticker == "QUO").unwrap();
+    assert_eq!(quoted.current_price, Some(123.0));
+    assert_eq!(quoted.rsi, first["QUO"].rsi);
+    let historical = results.iter().find(|r| r.ticker == "HIS").unwrap();
+    assert!(historical.current_price.unwrap() > 140.0);
+    assert_eq!(source.requests.lock().unwrap().len(), 2, "one batch request per cycle");
+}
+
+#[tokio::test]
+async fn test_analyze_now_jumps_the_queue_and_broadcasts() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::message_log::{BroadcastEvent, BroadcastMessage};
+    use auto_analyser::scheduler::{FetchJob, FetchPriority};
+    use auto_analyser::HistoryRange;
+    use tokio::sync::mpsc;
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let now = Utc::now();
+    let closes: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 * 0.7).sin() * 3.0).collect();
+    let series = QuoteSeries {
+        bars: bars("NOW", now - chrono::Duration::days(60), 1, &closes),
+        source: "fixture".to_string(),
+        fetched_at: now,
+        quality: None,
+        events: Vec::new(),
+    };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("NOW"), series).await;
+
+    // A symbol a cycle already queued is not queued twice
+    let (reply, _outcomes) = mpsc::unbounded_channel();
+    state.scheduler.enqueue(FetchJob::new(ticker_info("WAIT"), FetchPriority::for_ticker(&ticker_info("WAIT"), false, None, now), reply).for_cycle(1));
+    let (status, body) = post_json(state.clone(), "/api/analyze-now/wait", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(body["queued"], false);
+    assert_eq!(state.scheduler.pop().unwrap().priority, FetchPriority::TOP, "moved to the front");
+
+    let mut updates = state.broadcast_tx.subscribe();
+    let (status, body) = post_json(state.clone(), "/api/analyze-now/now", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(body["symbol"], "NOW");
+    assert_eq!(body["queued"], true);
+
+    let result = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let message = updates.recv().await.unwrap();
+            if let BroadcastMessage::Event(BroadcastEvent::TickerUpdate(result)) = message.message {
+                return *result;
+            }
+        }
+    })
+    .await
+    .expect("the analyze-now result was never broadcast");
+    assert_eq!(result.ticker, "NOW");
+    assert!(result.summary.is_some());
+    // The broadcast goes out before the result is stored
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !state.all_results.read().await.iter().any(|r| r.ticker == "NOW") {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the analyze-now result was never stored");
+    let stored = state.database.as_ref().unwrap().get_results_by_session("on_demand").await.unwrap();
+    assert_eq!(stored.len(), 1);
+}
+
+/// Step the test clock past ticker delays until the continuous loop reports `expected`
+async fn step_until_continuous_state(state: &AppState, clock: &TestClock, expected: ContinuousState) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while state.continuous_analysis_status.read().await.state != expected {
+            clock.advance(Duration::from_millis(50));
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("continuous analysis never reached {:?}", expected));
+}
+
+#[tokio::test]
+async fn test_continuous_control_pause_resume_stop_and_run_now() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    // One worker, so the cycle goes one ticker at a time
+    let state = AppState::with_database(None)
+        .with_config(AppConfig::new().with_fetch_workers(1).with_benchmark_symbol(None))
+        .with_clock(clock.clone());
+    state.cache.cache_tickers("all_tickers".to_string(), vec![ticker_info("CTLA"), ticker_info("CTLB")]).await;
+    for symbol in ["CTLA", "CTLB"] {
+        let closes: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 * 0.7).sin() * 3.0).collect();
+        let series = QuoteSeries {
+            bars: bars(symbol, start - chrono::Duration::days(60), 1, &closes),
+            source: "fixture".to_string(),
+            fetched_at: start,
+            quality: None,
+            events: Vec::new(),
+        };
+        state.cache.cache_quote_series(HistoryRange::default().cache_key(symbol), series).await;
+    }
+    let mut updates = state.broadcast_tx.subscribe();
+
+    state.start_continuous_analysis().await;
+    wait_for_cycle(&state, 1).await;
+    // Sitting in the delay after the first ticker
+    clock.wait_for_sleepers(1).await;
+    let (status, body) = post_json(state.clone(), "/api/continuous/pause", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(body["command"], "pause");
+    step_until_continuous_state(&state, &clock, ContinuousState::Paused).await;
+
+    clock.advance(Duration::from_secs(60));
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(state.all_results.read().await.len(), 1, "no ticker is analyzed while paused");
+    assert!(state.continuous_analysis_status.read().await.is_running);
+
+    post_json(state.clone(), "/api/continuous/resume", serde_json::json!({})).await;
+    step_until_continuous_state(&state, &clock, ContinuousState::IdleWaiting).await;
+    assert_eq!(state.all_results.read().await.len(), 2);
+
+    // Run-now skips the hour-long wait; stop abandons the cycle it started
+    post_json(state.clone(), "/api/continuous/run-now", serde_json::json!({})).await;
+    wait_for_cycle(&state, 2).await;
+    post_json(state.clone(), "/api/continuous/stop", serde_json::json!({})).await;
+    step_until_continuous_state(&state, &clock, ContinuousState::Stopped).await;
+    assert!(!state.continuous_analysis_status.read().await.is_running);
+
+    clock.advance(CONTINUOUS_CYCLE_INTERVAL * 2);
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(state.continuous_analysis_status.read().await.current_cycle, 2, "no new cycle while stopped");
+
+    // Resuming after a stop starts a fresh cycle straight away
+    state.cache.cache_tickers("all_tickers".to_string(), vec![]).await;
+    post_json(state.clone(), "/api/continuous/resume", serde_json::json!({})).await;
+    wait_for_cycle(&state, 3).await;
+
+    let mut seen = Vec::new();
+    while let Ok(message) = updates.try_recv() {
+        if let Some(status) = message.message.as_status() {
+            seen.push(status.status.clone());
+        }
+    }
+    for expected in ["paused", "idle_waiting", "stopped"] {
+        assert!(seen.iter().any(|s| s == expected), "no {} broadcast in {:?}", expected, seen);
+    }
+}
+
+#[tokio::test]
+async fn test_backtest_endpoint_replays_oversold_entries() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+
+    let state = AppState::with_database(None);
+    // A steady slide drives RSI to zero, then a recovery takes the trade to its target
+    let closes: Vec<f64> = (0..30).map(|i| 100.0 - i as f64).chain((0..30).map(|j| 70.0 + 2.0 * j as f64)).collect();
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    let series = QuoteSeries {
+        bars: bars("BTX", start, 1, &closes),
+        source: "fixture".to_string(),
+        fetched_at: Utc::now(),
+        quality: None,
+        events: Vec::new(),
+    };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("BTX"), series).await;
+
+    let (status, body) = post_json(
+        state.clone(),
+        "/api/backtest",
+        serde_json::json!({
+            "symbol": "btx",
+            "filter": { "oversold_rsi_threshold": 30.0 },
+            "exit": { "take_profit_pct": 10.0 },
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["symbol"], "BTX");
+    let first = &body["trades"][0];
+    assert_eq!(first["entry_price"], 86.0, "the first bar with an RSI is the entry");
+    assert_eq!(first["exit_reason"], "take_profit");
+    assert_eq!(body["equity_curve"].as_array().unwrap().len(), closes.len());
+    assert!(body["max_drawdown_pct"].as_f64().unwrap() > 0.0);
+
+    let (status, body) = post_json(
+        state,
+        "/api/backtest",
+        serde_json::json!({ "symbol": "BTX", "filter": { "oversold_rsi_threshold": 30.0 }, "exit": {} }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_request");
+}
+
+#[tokio::test]
+async fn test_symbol_endpoint_returns_windowed_chart_data() {
+    use auto_analyser::providers::{Interval, QuoteSeries};
+    use auto_analyser::HistoryRange;
+
+    let now = Utc.with_ymd_and_hms(2024, 6, 3, 21, 0, 0).unwrap();
+    let state = AppState::with_database(None).with_clock(Arc::new(TestClock::new(now)));
+    let info = auto_analyser::TickerInfo { sector: Some("Technology".to_string()), ..ticker_info("SYM") };
+    state.cache.cache_tickers("all_tickers".to_string(), vec![info]).await;
+
+    let closes: Vec<f64> = (0..100).map(|i| 100.0 + (i as f64 * 0.4).sin() * 5.0).collect();
+    let cache = |range: HistoryRange, symbol: &str, closes: &[f64], step: i64| {
+        let series = QuoteSeries {
+            bars: bars(symbol, now - chrono::Duration::days((closes.len() as i64 - 1) * step), step, closes),
+            source: "fixture".to_string(),
+            fetched_at: now,
+            quality: None,
+            events: Vec::new(),
+        };
+        state.cache.cache_quote_series(range.cache_key(symbol), series)
+    };
+    cache(HistoryRange::default(), "SYM", &closes, 1).await;
+    cache(HistoryRange::new(None, None, Interval::Weekly), "SYM", &closes[..20], 7).await;
+    cache(HistoryRange::default(), "GONE", &[], 1).await;
+
+    let (status, body) = get_json(state.clone(), "/api/symbol/sym?days=30").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["symbol"], "SYM");
+    assert_eq!(body["info"]["sector"], "Technology");
+    assert_eq!(body["bars"].as_array().unwrap().len(), 31);
+    let indicators = body["indicators"].as_array().unwrap();
+    assert_eq!(indicators.len(), 31);
+    assert_eq!(indicators[0]["timestamp"], body["bars"][0]["timestamp"]);
+    assert!(indicators[0]["sma_50"].is_number(), "history before the window warms the indicators up");
+    assert!(!body["signals"].as_array().unwrap().is_empty());
+
+    let (status, body) = get_json(state.clone(), "/api/symbol/SYM?interval=1wk").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["interval"], "1wk");
+    assert_eq!(body["bars"].as_array().unwrap().len(), 20);
+    assert!(body["indicators"][19]["sma_20"].is_number());
+
+    // RSI(14) on 15-minute bars, cached apart from the daily series
+    let intraday: Vec<auto_analyser::StockData> = closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| auto_analyser::StockData {
+            symbol: "SYM".to_string(),
+            timestamp: now - chrono::Duration::minutes(15 * (closes.len() - 1 - i) as i64),
+            open: close,
+            high: close + 0.5,
+            low: close - 0.5,
+            close,
+            volume: 1_000,
+            adjusted_close: None,
+        })
+        .collect();
+    let series = QuoteSeries { bars: intraday, source: "fixture".to_string(), fetched_at: now, quality: None, events: Vec::new() };
+    state
+        .cache
+        .cache_quote_series(HistoryRange::new(None, None, Interval::FifteenMinutes).cache_key("SYM"), series)
+        .await;
+    let (status, body) = get_json(state.clone(), "/api/symbol/SYM?interval=15m&days=2").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["interval"], "15m");
+    assert_eq!(body["bars"].as_array().unwrap().len(), 100);
+    assert!(body["indicators"][99]["rsi"].is_number());
+    assert!(body["indicators"][0]["rsi"].is_null());
+
+    let (status, body) = get_json(state.clone(), "/api/symbol/SYM?interval=15m&days=90").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["message"], "15m bars only go back 60 days; ask for days=60 or fewer");
+
+    let (status, body) = get_json(state.clone(), "/api/symbol/GONE").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["code"], "not_found");
+
+    let (status, _) = get_json(state, "/api/symbol/SYM?days=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_symbol_endpoint_resamples_daily_bars() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+
+    // Wednesday of ISO week 23, so that week is still trading
+    let now = Utc.with_ymd_and_hms(2024, 6, 5, 21, 0, 0).unwrap();
+    let state = AppState::with_database(None).with_clock(Arc::new(TestClock::new(now)));
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let closes: Vec<f64> = (0..157).map(|i| 100.0 + (i as f64 * 0.3).sin() * 10.0).collect();
+    let series = QuoteSeries { bars: bars("SYM", start, 1, &closes), source: "fixture".to_string(), fetched_at: now, quality: None, events: Vec::new() };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("SYM"), series).await;
+
+    let (status, body) = get_json(state.clone(), "/api/symbol/SYM?timeframe=weekly").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["timeframe"], "weekly");
+    let weekly = body["bars"].as_array().unwrap();
+    assert_eq!(weekly.len(), 23);
+    assert_eq!(weekly[22]["close"], closes[156]);
+    assert_eq!(weekly[22]["volume"], 3_000);
+    assert!(body["indicators"][22]["rsi"].is_number());
+    assert!(body["indicators"][19]["sma_20"].is_number());
+
+    let (_, body) = get_json(state.clone(), "/api/symbol/SYM?timeframe=weekly&include_partial=false").await;
+    let weekly = body["bars"].as_array().unwrap();
+    assert_eq!(weekly.len(), 22);
+    // Week 22 ended with Sunday 2 June
+    assert_eq!(weekly[21]["close"], closes[153]);
+
+    let (_, body) = get_json(state.clone(), "/api/symbol/SYM?timeframe=monthly").await;
+    assert_eq!(body["bars"].as_array().unwrap().len(), 6);
+    let (_, body) = get_json(state.clone(), "/api/symbol/SYM").await;
+    assert_eq!(body["timeframe"], "daily");
+    assert_eq!(body["bars"].as_array().unwrap().len(), 157);
+
+    let (status, _) = get_json(state, "/api/symbol/SYM?interval=1wk&timeframe=weekly").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_seasonality_endpoint_and_symbol_hint() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+    use chrono::Datelike;
+
+    let now = Utc.with_ymd_and_hms(2024, 6, 3, 21, 0, 0).unwrap();
+    let state = AppState::with_database(None).with_clock(Arc::new(TestClock::new(now)));
+    // Three years flat apart from a 1% drop on each of September's first ten days
+    let start = now - chrono::Duration::days(3 * 365);
+    let mut close = 100.0;
+    let closes: Vec<f64> = (0..=3 * 365)
+        .map(|day| {
+            let date = start + chrono::Duration::days(day);
+            if date.month() == 9 && date.day() <= 10 {
+                close *= 0.99;
+            }
+            close
+        })
+        .collect();
+    for (symbol, closes) in [("SEAS", &closes[..]), ("NEW", &closes[closes.len() - 200..])] {
+        let bars = bars(symbol, now - chrono::Duration::days(closes.len() as i64 - 1), 1, closes);
+        let series = QuoteSeries { bars, source: "fixture".to_string(), fetched_at: now, quality: None, events: Vec::new() };
+        state.cache.cache_quote_series(HistoryRange::default().cache_key(symbol), series).await;
+    }
+
+    let (status, body) = get_json(state.clone(), "/api/symbol/seas/seasonality").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["symbol"], "SEAS");
+    assert_eq!(body["confidence"], "normal");
+    let september = &body["by_month"][8];
+    assert_eq!(september["period"], "September");
+    assert_eq!(september["count"], 3);
+    assert_eq!(september["positive_pct"], 0.0);
+    assert!(september["median_pct"].as_f64().unwrap() < -9.0);
+    assert_eq!(body["worst_month"]["month"], 9);
+    assert_eq!(body["by_weekday"].as_array().unwrap().len(), 5);
+
+    let (_, body) = get_json(state.clone(), "/api/symbol/SEAS").await;
+    assert_eq!(body["seasonality_hint"], "historically weak in September");
+
+    // Under a year of history: statistics, but no hint
+    let (status, body) = get_json(state.clone(), "/api/symbol/NEW/seasonality").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["confidence"], "low");
+    let (_, body) = get_json(state, "/api/symbol/NEW").await;
+    assert!(body.get("seasonality_hint").is_none());
+}
+
+#[tokio::test]
+async fn test_indicator_history_endpoint_stores_computed_values() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+
+    let now = Utc.with_ymd_and_hms(2024, 6, 3, 21, 0, 0).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await.with_clock(Arc::new(TestClock::new(now)));
+    let db = state.database.clone().unwrap();
+
+    let closes: Vec<f64> = (0..100).map(|i| 100.0 + (i as f64 * 0.4).sin() * 5.0).collect();
+    let series = QuoteSeries {
+        bars: bars("SYM", now - chrono::Duration::days(99), 1, &closes),
+        source: "fixture".to_string(),
+        fetched_at: now,
+        quality: None,
+        events: Vec::new(),
+    };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("SYM"), series).await;
+
+    // Nothing stored yet: computed from the cached history, then kept
+    let (status, body) = get_json(state.clone(), "/api/symbol/sym/indicators?days=30").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["source"], "computed");
+    let computed = body["indicators"].as_array().unwrap().clone();
+    assert_eq!(computed.len(), 31);
+    assert_eq!(computed[30]["date"], "2024-06-03");
+    assert!(computed[0]["sma_50"].is_number());
+    assert_eq!(db.latest_indicator_date("SYM").await.unwrap(), Some(now.date_naive()));
+
+    let (status, body) = get_json(state.clone(), "/api/symbol/SYM/indicators?days=30").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["source"], "database");
+    assert_eq!(body["indicators"].as_array().unwrap(), &computed);
+
+    // The default window is 90 days
+    let (_, body) = get_json(state.clone(), "/api/symbol/SYM/indicators").await;
+    assert_eq!(body["indicators"].as_array().unwrap().len(), 91);
+
+    let (status, _) = get_json(state, "/api/symbol/SYM/indicators?days=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_continuous_cycles_are_recorded_and_diffed() {
+    use auto_analyser::database::AnalysisCycle;
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+
+    let (status, body) = get_json(AppState::with_database(None), "/api/cycles").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["error"]["code"], "database_unavailable");
+
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir)
+        .await
+        .with_config(AppConfig::new().with_benchmark_symbol(None))
+        .with_clock(clock.clone());
+    let db = state.database.clone().unwrap();
+
+    // A cycle from an earlier run, in which FALL was not an opportunity
+    db.record_cycle(&AnalysisCycle {
+        cycle_number: 4,
+        session_id: "continuous_cycle_4".to_string(),
+        started_at: start - chrono::Duration::hours(2),
+        finished_at: Some(start - chrono::Duration::hours(1)),
+        tickers_analyzed: 1,
+        opportunities_found: 0,
+        error_count: 0,
+    })
+    .await
+    .unwrap();
+    db.store_analysis_result(&sample_result("FALL", 100.0, Some(55.0), 1_000), "continuous_cycle_4").await.unwrap();
+
+    // FALL declines steadily into oversold; EMPTY has no history and fails
+    state.cache.cache_tickers("all_tickers".to_string(), vec![ticker_info("FALL"), ticker_info("EMPTY")]).await;
+    for (symbol, closes) in [("FALL", (0..60).map(|i| 200.0 - i as f64).collect::<Vec<_>>()), ("EMPTY", vec![])] {
+        let series = QuoteSeries {
+            bars: bars(symbol, start - chrono::Duration::days(60), 1, &closes),
+            source: "fixture".to_string(),
+            fetched_at: start,
+            quality: None,
+            events: Vec::new(),
+        };
+        state.cache.cache_quote_series(HistoryRange::default().cache_key(symbol), series).await;
+    }
+
+    state.start_continuous_analysis().await;
+    wait_for_cycle(&state, 5).await;
+    step_until_continuous_state(&state, &clock, ContinuousState::IdleWaiting).await;
+
+    let (status, body) = get_json(state.clone(), "/api/cycles").await;
+    assert_eq!(status, StatusCode::OK);
+    let cycles = body.as_array().unwrap();
+    assert_eq!(cycles.len(), 2);
+    assert_eq!(cycles[0]["cycle_number"], 5, "numbering carries on from the stored cycles");
+    assert_eq!(cycles[0]["session_id"], "continuous_cycle_5");
+    assert_eq!(cycles[0]["tickers_analyzed"], 1);
+    assert_eq!(cycles[0]["opportunities_found"], 1);
+    assert_eq!(cycles[0]["error_count"], 1);
+    assert!(cycles[0]["finished_at"].is_string());
+
+    let (_, body) = get_json(state.clone(), "/api/cycles?limit=1").await;
+    assert_eq!(body.as_array().unwrap().len(), 1);
+
+    let (status, body) = get_json(state.clone(), "/api/cycles/continuous_cycle_5/diff?vs=continuous_cycle_4").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["compared_tickers"], 1);
+    assert_eq!(body["new_opportunities"], serde_json::json!(["FALL"]));
+    assert_eq!(body["dropped_opportunities"], serde_json::json!([]));
+    assert_eq!(body["rsi_changes"][0]["ticker"], "FALL");
+    assert_eq!(body["rsi_changes"][0]["from"], 55.0);
+    assert!(body["rsi_changes"][0]["delta"].as_f64().unwrap() < -25.0);
+
+    let (status, body) = get_json(state, "/api/cycles/continuous_cycle_5/diff?vs=continuous_cycle_1").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["code"], "not_found");
+}
+
+#[tokio::test]
+async fn test_continuous_cycle_ranks_relative_strength() {
+    use auto_analyser::message_log::{BroadcastEvent, BroadcastMessage};
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+
+    let start = Utc.with_ymd_and_hms(2024, 3, 4, 15, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir)
+        .await
+        .with_config(AppConfig::new().with_benchmark_symbol(Some("spy")))
+        .with_clock(clock.clone());
+    let mut updates = state.broadcast_tx.subscribe();
+
+    // SPY gains 10% over the history; LEAD gains 50%, PACE keeps up, LAG
+    // loses 20% and SHORT is too new for the 3-month window
+    let linear = |from: f64, to: f64, n: usize| -> Vec<f64> {
+        (0..n).map(|i| from + (to - from) * i as f64 / (n - 1) as f64).collect()
+    };
+    let tickers = ["LEAD", "PACE", "LAG", "SHORT"].map(ticker_info).to_vec();
+    state.cache.cache_tickers("all_tickers".to_string(), tickers).await;
+    for (symbol, closes) in [
+        ("SPY", linear(100.0, 110.0, 70)),
+        ("LEAD", linear(100.0, 150.0, 70)),
+        ("PACE", linear(100.0, 110.0, 70)),
+        ("LAG", linear(100.0, 80.0, 70)),
+        ("SHORT", linear(100.0, 200.0, 30)),
+    ] {
+        let series = QuoteSeries {
+            bars: bars(symbol, start - chrono::Duration::days(closes.len() as i64), 1, &closes),
+            source: "fixture".to_string(),
+            fetched_at: start,
+            quality: None,
+            events: Vec::new(),
+        };
+        state.cache.cache_quote_series(HistoryRange::default().cache_key(symbol), series).await;
+    }
+
+    state.start_continuous_analysis().await;
+    wait_for_cycle(&state, 1).await;
+    step_until_continuous_state(&state, &clock, ContinuousState::IdleWaiting).await;
+
+    let results: HashMap<String, StockAnalysisResult> =
+        state.all_results.read().await.iter().map(|r| (r.ticker.clone(), r.clone())).collect();
+    assert_eq!(results["LEAD"].rs_rank, Some(100.0));
+    assert_eq!(results["PACE"].rs_rank, Some(50.0));
+    assert_eq!(results["LAG"].rs_rank, Some(0.0));
+    assert!(results["LEAD"].rs_3m.unwrap() > 1.0);
+    assert!(results["LAG"].rs_1w.unwrap() < 1.0);
+    assert_eq!((results["SHORT"].rs_3m, results["SHORT"].rs_rank), (None, None));
+    assert!(results["SHORT"].rs_1m.is_some());
+
+    let ranked = std::iter::from_fn(|| updates.try_recv().ok())
+        .find_map(|message| match message.message {
+            BroadcastMessage::Event(BroadcastEvent::Ranked(ranked)) => Some(ranked),
+            _ => None,
+        })
+        .expect("the cycle's ranks were never broadcast");
+    assert_eq!(ranked.session_id, "continuous_cycle_1");
+    assert_eq!(ranked.ranks.len(), 3);
+
+    // The stored rows were ranked too, so the SQL filter path sees the ranks
+    let (status, body) = post_json(state.clone(), "/api/filtered-results", serde_json::json!({ "min_rs_rank": 50.0 })).await;
+    assert_eq!(status, StatusCode::OK);
+    let mut tickers = page_tickers(&body);
+    tickers.sort();
+    assert_eq!(tickers, vec!["LEAD", "PACE"]);
+
+    let (status, _) = post_json(state, "/api/filtered-results", serde_json::json!({ "min_rs_rank": 101.0 })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_rerun_broadcasts_only_meaningful_deltas() {
+    use auto_analyser::message_log::{BroadcastEvent, BroadcastMessage};
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+
+    let state = AppState::with_database(None);
+    state.cache.cache_tickers("all_tickers".to_string(), vec![ticker_info("RISE"), ticker_info("FRESH")]).await;
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    let closes: Vec<f64> = (0..60).map(|i| 10.0 + i as f64).collect();
+    for symbol in ["RISE", "FRESH"] {
+        let series = QuoteSeries {
+            bars: bars(symbol, start, 1, &closes),
+            source: "fixture".to_string(),
+            fetched_at: Utc::now(),
+            quality: None,
+            events: Vec::new(),
+        };
+        state.cache.cache_quote_series(HistoryRange::default().cache_key(symbol), series).await;
+    }
+    // RISE was neutral last time; FRESH has never been analyzed
+    state.all_results.write().await.push(sample_result("RISE", 60.0, Some(50.0), 1_000));
+
+    let run = |state: AppState| async move {
+        let (_, body) = post_json(state.clone(), "/api/analysis", serde_json::json!({ "filter": {} })).await;
+        wait_for_session(&state, body["session_id"].as_str().unwrap()).await
+    };
+    let session = run(state.clone()).await;
+    assert_eq!(session.results.len(), 2);
+
+    let deltas = |state: &AppState| {
+        state
+            .message_log
+            .since(0, 1_000)
+            .into_iter()
+            .filter_map(|message| match message.message {
+                BroadcastMessage::Event(BroadcastEvent::ResultDelta(delta)) => Some(delta),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let sent = deltas(&state);
+    assert_eq!(sent.len(), 1);
+    let delta = &sent[0];
+    assert_eq!(delta.ticker, "RISE");
+    assert!(delta.changed_fields.iter().any(|field| field == "rsi"));
+    assert_eq!(delta.old_rsi, Some(50.0));
+    // A straight climb is overbought, which this filter counts as an opportunity
+    assert!(delta.new_rsi.unwrap() > 70.0);
+    assert!(delta.became_opportunity && !delta.lost_opportunity);
+
+    // Re-running against the stored result with nothing changed sends no delta
+    state.all_results.write().await.retain(|r| r.ticker != "RISE");
+    state.all_results.write().await.push(session.results.iter().find(|r| r.ticker == "RISE").unwrap().clone());
+    run(state.clone()).await;
+    assert_eq!(deltas(&state).len(), 1);
+}
+
+#[tokio::test]
+async fn test_results_changed_since() {
+    let state = AppState::with_database(None);
+    let earlier = Utc.with_ymd_and_hms(2024, 3, 4, 14, 0, 0).unwrap();
+    let later = Utc.with_ymd_and_hms(2024, 3, 4, 16, 0, 0).unwrap();
+    for (ticker, timestamp) in [("OLD", earlier), ("NEW", later), ("NEWER", later + chrono::Duration::minutes(5))] {
+        let result = StockAnalysisResult { timestamp, summary: Some("summary".to_string()), ..sample_result(ticker, 10.0, Some(40.0), 1_000) };
+        state.all_results.write().await.push(result);
+    }
+
+    let (status, body) = get_json(state.clone(), "/api/filtered-results/changed-since?timestamp=2024-03-04T15:00:00Z").await;
+    assert_eq!(status, StatusCode::OK);
+    let tickers: Vec<&str> = body.as_array().unwrap().iter().map(|r| r["ticker"].as_str().unwrap()).collect();
+    assert_eq!(tickers, ["NEWER", "NEW"]);
+    assert!(body[0].get("summary").is_none());
+
+    let (_, body) = get_json(state, "/api/filtered-results/changed-since?timestamp=2024-03-04T17:00:00Z&include_summary=true").await;
+    assert_eq!(body, serde_json::json!([]));
+}
+
+/// Send `request` and return the status, headers and raw body
+async fn send(state: AppState, request: Request<Body>) -> (StatusCode, axum::http::HeaderMap, axum::body::Bytes) {
+    let response = build_router(state).oneshot(request).await.unwrap();
+    let (parts, body) = response.into_parts();
+    (parts.status, parts.headers, axum::body::to_bytes(body, usize::MAX).await.unwrap())
+}
+
+fn filtered_results_request(filter: serde_json::Value, if_none_match: Option<&axum::http::HeaderValue>) -> Request<Body> {
+    let mut request = Request::builder()
+        .method("POST")
+        .uri("/api/filtered-results")
+        .header("content-type", "application/json");
+    if let Some(etag) = if_none_match {
+        request = request.header("if-none-match", etag);
+    }
+    request.body(Body::from(filter.to_string())).unwrap()
+}
+
+#[tokio::test]
+async fn test_filtered_results_etag_until_a_new_result() {
+    use auto_analyser::providers::QuoteSeries;
+    use auto_analyser::HistoryRange;
+
+    let dir = tempfile::tempdir().unwrap();
+    let state = watchlist_state(&dir).await;
+    let db = state.database.clone().unwrap();
+    db.store_analysis_result(&sample_result("OLD", 50.0, Some(40.0), 1_000), "on_demand").await.unwrap();
+
+    let (status, headers, body) = send(state.clone(), filtered_results_request(serde_json::json!({}), None)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap()["total"], 1);
+    let etag = headers.get("etag").unwrap().clone();
+    assert!(etag.to_str().unwrap().starts_with("W/\""));
+
+    let (status, headers, body) = send(state.clone(), filtered_results_request(serde_json::json!({}), Some(&etag))).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    assert!(body.is_empty());
+    assert_eq!(headers.get("etag"), Some(&etag));
+
+    // The tag covers the filter
+    let other = serde_json::json!({ "max_rsi": 30.0 });
+    let (status, headers, _) = send(state.clone(), filtered_results_request(other, Some(&etag))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(headers.get("etag"), Some(&etag));
+
+    // A new analysis result moves it on
+    let now = Utc::now();
+    let closes: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 * 0.7).sin() * 3.0).collect();
+    let series = QuoteSeries {
+        bars: bars("NEW", now - chrono::Duration::days(60), 1, &closes),
+        source: "fixture".to_string(),
+        fetched_at: now,
+        quality: None,
+        events: Vec::new(),
+    };
+    state.cache.cache_quote_series(HistoryRange::default().cache_key("NEW"), series).await;
+    let (status, _) = post_json(state.clone(), "/api/analyze-now/NEW", serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !state.all_results.read().await.iter().any(|r| r.ticker == "NEW") {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the analyze-now result was never stored");
+
+    let (status, headers, body) = send(state.clone(), filtered_results_request(serde_json::json!({}), Some(&etag))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap()["total"], 2);
+    let fresh = headers.get("etag").unwrap().clone();
+    assert_ne!(fresh, etag);
+    let (status, _, _) = send(state, filtered_results_request(serde_json::json!({}), Some(&fresh))).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+}
+
+#[tokio::test]
+async fn test_tickers_etag_and_compression() {
+    let start = Utc.with_ymd_and_hms(2024, 5, 1, 14, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let provider = ListingProvider::new(vec![in_sector_info("AAPL", "Technology"), in_sector_info("XOM", "Energy")]);
+    let state = AppState::with_database(None).with_clock(clock.clone()).with_market_data(provider.clone());
+    let tickers = |if_none_match: Option<&axum::http::HeaderValue>| {
+        let mut request = Request::builder().uri("/api/tickers");
+        if let Some(etag) = if_none_match {
+            request = request.header("if-none-match", etag);
+        }
+        request.body(Body::empty()).unwrap()
+    };
+
+    let (status, headers, _) = send(state.clone(), tickers(None)).await;
+    assert_eq!(status, StatusCode::OK);
+    let etag = headers.get("etag").unwrap().clone();
+
+    // Listed among others, as a client holding several versions would send it
+    let listed = axum::http::HeaderValue::from_str(&format!("W/\"0000000000000000\", {}", etag.to_str().unwrap())).unwrap();
+    let (status, _, body) = send(state.clone(), tickers(Some(&listed))).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    assert!(body.is_empty());
+
+    // A refresh after the ticker TTL is a new listing; until it lands the old one is served
+    provider.list(vec![in_sector_info("AAPL", "Technology"), in_sector_info("ARM", "Technology")]);
+    clock.advance(Duration::from_secs(3601));
+    let (status, _, _) = send(state.clone(), tickers(Some(&etag))).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    wait_for_ticker_refresh(&state).await;
+    let (status, headers, body) = send(state.clone(), tickers(Some(&etag))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(headers.get("etag"), Some(&etag));
+    assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap()[1]["symbol"], "ARM");
+
+    let request = Request::builder().uri("/api/tickers").header("accept-encoding", "gzip").body(Body::empty()).unwrap();
+    let (status, headers, _) = send(state, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers.get("content-encoding").unwrap(), "gzip");
+    assert!(headers.get("etag").is_some());
+}